@@ -1,10 +1,11 @@
 use iai_callgrind::{library_benchmark, library_benchmark_group, main};
 use infinitedsp_core::core::audio_param::AudioParam;
-use infinitedsp_core::core::ola::Ola;
+use infinitedsp_core::core::ola::{Ola, RealOla};
 use infinitedsp_core::effects::dynamics::compressor::Compressor;
 use infinitedsp_core::effects::filter::state_variable::{StateVariableFilter, SvfType};
 use infinitedsp_core::effects::spectral::spectral_smear::SpectralSmear;
 use infinitedsp_core::effects::time::reverb::Reverb;
+use infinitedsp_core::low_mem::effects::time::reverb_low_mem::ReverbLowMem;
 use infinitedsp_core::synthesis::envelope::Adsr;
 use infinitedsp_core::synthesis::oscillator::{Oscillator, Waveform};
 use infinitedsp_core::synthesis::speech::SpeechSynth;
@@ -55,6 +56,12 @@ fn setup_reverb() -> (Reverb, Vec<f32>) {
     (reverb, vec![0.0; BUFFER_SIZE * 2])
 }
 
+fn setup_reverb_low_mem() -> (ReverbLowMem, Vec<f32>) {
+    let mut reverb = ReverbLowMem::new();
+    reverb.set_sample_rate(SAMPLE_RATE);
+    (reverb, vec![0.0; BUFFER_SIZE * 2])
+}
+
 fn setup_svf() -> (StateVariableFilter, Vec<f32>) {
     let mut filter = StateVariableFilter::new(
         SvfType::LowPass,
@@ -80,6 +87,27 @@ fn setup_ola_smear() -> (Ola<SpectralSmear<512>, 512>, Vec<f32>) {
     (smear, vec![0.5; BUFFER_SIZE])
 }
 
+fn setup_real_ola_smear() -> (RealOla<SpectralSmear<512>, 512>, Vec<f32>) {
+    let smear_proc = SpectralSmear::<512>::new(AudioParam::Static(0.9));
+    let mut smear = RealOla::<_, 512>::with(smear_proc);
+    smear.set_sample_rate(SAMPLE_RATE);
+    (smear, vec![0.5; BUFFER_SIZE])
+}
+
+fn setup_ola_smear_4096() -> (Ola<SpectralSmear<4096>, 4096>, Vec<f32>) {
+    let smear_proc = SpectralSmear::<4096>::new(AudioParam::Static(0.9));
+    let mut smear = Ola::<_, 4096>::with(smear_proc);
+    smear.set_sample_rate(SAMPLE_RATE);
+    (smear, vec![0.5; 4096])
+}
+
+fn setup_real_ola_smear_4096() -> (RealOla<SpectralSmear<4096>, 4096>, Vec<f32>) {
+    let smear_proc = SpectralSmear::<4096>::new(AudioParam::Static(0.9));
+    let mut smear = RealOla::<_, 4096>::with(smear_proc);
+    smear.set_sample_rate(SAMPLE_RATE);
+    (smear, vec![0.5; 4096])
+}
+
 fn setup_speech() -> (SpeechSynth<'static>, Vec<f32>) {
     let mut speech = SpeechSynth::new(SAMPLE_RATE);
     let tokens = ["A", "E", "I", "O", "U"];
@@ -127,6 +155,13 @@ fn bench_reverb(args: (Reverb, Vec<f32>)) {
     reverb.process(black_box(&mut buffer), 0);
 }
 
+#[library_benchmark]
+#[bench::default(setup_reverb_low_mem())]
+fn bench_reverb_low_mem(args: (ReverbLowMem, Vec<f32>)) {
+    let (mut reverb, mut buffer) = args;
+    reverb.process(black_box(&mut buffer), 0);
+}
+
 #[library_benchmark]
 #[bench::default(setup_svf())]
 fn bench_svf_lowpass(args: (StateVariableFilter, Vec<f32>)) {
@@ -148,6 +183,27 @@ fn bench_spectral_smear(args: (Ola<SpectralSmear<512>, 512>, Vec<f32>)) {
     smear.process(black_box(&mut buffer), 0);
 }
 
+#[library_benchmark]
+#[bench::default(setup_real_ola_smear())]
+fn bench_spectral_smear_real(args: (RealOla<SpectralSmear<512>, 512>, Vec<f32>)) {
+    let (mut smear, mut buffer) = args;
+    smear.process(black_box(&mut buffer), 0);
+}
+
+#[library_benchmark]
+#[bench::default(setup_ola_smear_4096())]
+fn bench_spectral_smear_4096(args: (Ola<SpectralSmear<4096>, 4096>, Vec<f32>)) {
+    let (mut smear, mut buffer) = args;
+    smear.process(black_box(&mut buffer), 0);
+}
+
+#[library_benchmark]
+#[bench::default(setup_real_ola_smear_4096())]
+fn bench_spectral_smear_real_4096(args: (RealOla<SpectralSmear<4096>, 4096>, Vec<f32>)) {
+    let (mut smear, mut buffer) = args;
+    smear.process(black_box(&mut buffer), 0);
+}
+
 #[library_benchmark]
 #[bench::default(setup_speech())]
 fn bench_speech_synth(args: (SpeechSynth<'static>, Vec<f32>)) {
@@ -162,7 +218,8 @@ library_benchmark_group!(
 
 library_benchmark_group!(
     name = effects;
-    benchmarks = bench_reverb, bench_svf_lowpass, bench_compressor, bench_spectral_smear
+    benchmarks = bench_reverb, bench_reverb_low_mem, bench_svf_lowpass, bench_compressor, bench_spectral_smear,
+        bench_spectral_smear_real, bench_spectral_smear_4096, bench_spectral_smear_real_4096
 );
 
 library_benchmark_group!(
@@ -0,0 +1,98 @@
+use iai_callgrind::{library_benchmark, library_benchmark_group, main};
+use infinitedsp_core::bench_support::{
+    delay_interpolation, delay_interpolation_x4, AllpassKernel, CombKernel, OscillatorKernel,
+};
+use infinitedsp_core::synthesis::oscillator::Waveform;
+use std::hint::black_box;
+
+const SAMPLE_RATE: f32 = 44100.0;
+const BUFFER_SIZE: usize = 512;
+
+// --- Setup Functions ---
+
+fn setup_comb() -> (CombKernel, Vec<f32>) {
+    let mut buffer = vec![0.0; BUFFER_SIZE];
+    buffer[0] = 1.0;
+    (CombKernel::new([1116, 1188, 1277, 1356], 0.84, 0.2), buffer)
+}
+
+fn setup_allpass() -> (AllpassKernel, Vec<f32>) {
+    let mut buffer = vec![0.0; BUFFER_SIZE];
+    buffer[0] = 1.0;
+    (AllpassKernel::new(&[556, 441, 341, 225]), buffer)
+}
+
+fn setup_delay() -> (Vec<f32>, Vec<f32>, Vec<f32>) {
+    let line: Vec<f32> = (0..4096).map(|i| libm::sinf(i as f32 * 0.01)).collect();
+    let positions = (0..BUFFER_SIZE).map(|i| i as f32 * 7.3 % 4096.0).collect();
+    (line, positions, vec![0.0; BUFFER_SIZE])
+}
+
+fn setup_osc(wf: Waveform) -> (OscillatorKernel, Vec<f32>) {
+    (
+        OscillatorKernel::new(wf, 440.0, SAMPLE_RATE),
+        vec![0.0; BUFFER_SIZE],
+    )
+}
+
+// --- Benchmarks ---
+
+#[library_benchmark]
+#[bench::default(setup_comb())]
+fn bench_comb_simd(args: (CombKernel, Vec<f32>)) {
+    let (mut comb, mut buffer) = args;
+    comb.process_block(black_box(&mut buffer));
+}
+
+#[library_benchmark]
+#[bench::default(setup_comb())]
+fn bench_comb_scalar(args: (CombKernel, Vec<f32>)) {
+    let (mut comb, mut buffer) = args;
+    comb.process_block_scalar(black_box(&mut buffer));
+}
+
+#[library_benchmark]
+#[bench::default(setup_allpass())]
+fn bench_allpass(args: (AllpassKernel, Vec<f32>)) {
+    let (mut allpass, mut buffer) = args;
+    allpass.process_block(black_box(&mut buffer));
+}
+
+#[library_benchmark]
+#[bench::default(setup_delay())]
+fn bench_delay_interpolation(args: (Vec<f32>, Vec<f32>, Vec<f32>)) {
+    let (line, positions, mut output) = args;
+    delay_interpolation(&line, &positions, black_box(&mut output));
+}
+
+#[library_benchmark]
+#[bench::default(setup_delay())]
+fn bench_delay_interpolation_x4(args: (Vec<f32>, Vec<f32>, Vec<f32>)) {
+    let (line, positions, mut output) = args;
+    delay_interpolation_x4(&line, &positions, black_box(&mut output));
+}
+
+#[library_benchmark]
+#[bench::sine(setup_osc(Waveform::Sine))]
+#[bench::saw(setup_osc(Waveform::Saw))]
+fn bench_oscillator_fill(args: (OscillatorKernel, Vec<f32>)) {
+    let (mut osc, mut buffer) = args;
+    osc.fill(black_box(&mut buffer));
+}
+
+#[library_benchmark]
+#[bench::sine(setup_osc(Waveform::Sine))]
+#[bench::saw(setup_osc(Waveform::Saw))]
+fn bench_oscillator_fill_scalar(args: (OscillatorKernel, Vec<f32>)) {
+    let (mut osc, mut buffer) = args;
+    osc.fill_scalar(black_box(&mut buffer));
+}
+
+library_benchmark_group!(
+    name = kernels;
+    benchmarks = bench_comb_simd, bench_comb_scalar, bench_allpass,
+        bench_delay_interpolation, bench_delay_interpolation_x4,
+        bench_oscillator_fill, bench_oscillator_fill_scalar
+);
+
+main!(library_benchmark_groups = kernels);
@@ -1,32 +1,52 @@
 use anyhow::Result;
 use cpal::traits::StreamTrait;
 use infinitedsp_core::core::audio_param::AudioParam;
+use infinitedsp_core::core::automation::{AutomationCurve, AutomationLane, AutomationPoint};
 use infinitedsp_core::core::channels::DualMono;
-use infinitedsp_core::core::channels::Stereo;
+use infinitedsp_core::core::channels::{Mono, Stereo};
 use infinitedsp_core::core::dsp_chain::DspChain;
+use infinitedsp_core::core::parameter::Parameter;
+use infinitedsp_core::core::transport::Transport;
 use infinitedsp_core::effects::time::delay::Delay;
 use infinitedsp_core::effects::utility::gain::Gain;
-use infinitedsp_core::synthesis::envelope::{Adsr, Trigger};
+use infinitedsp_core::synthesis::envelope::Adsr;
 use infinitedsp_core::synthesis::oscillator::{Oscillator, Waveform};
 use infinitedsp_examples::audio_backend::init_audio_interleaved;
-use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
-fn create_dual_mono_chain(sample_rate: f32) -> (DspChain<Stereo>, Trigger) {
+/// A short gate every 2 beats at 120 BPM (once per second), for 10 notes.
+fn gate_points() -> Vec<AutomationPoint> {
+    (0..10)
+        .flat_map(|note| {
+            let beat = note as f64 * 2.0;
+            [
+                AutomationPoint::new(beat, 1.0, AutomationCurve::Step),
+                AutomationPoint::new(beat + 0.2, 0.0, AutomationCurve::Step),
+            ]
+        })
+        .collect()
+}
+
+fn create_dual_mono_chain(sample_rate: f32, gate: &Parameter) -> DspChain<Stereo> {
+    let transport = Transport::new(sample_rate);
+    transport.start();
+    let gate_lane = AutomationLane::new(&transport, gate.clone(), gate_points());
+
     let osc = Oscillator::new(AudioParam::hz(440.0), Waveform::Saw);
 
     let adsr = Adsr::new(
-        AudioParam::Static(0.0),
+        AudioParam::Linked(gate.clone()),
         AudioParam::ms(5.0),
         AudioParam::ms(200.0),
         AudioParam::linear(0.0),
         AudioParam::ms(100.0),
     );
-    let trigger = adsr.create_trigger();
 
-    let source =
-        DspChain::new(osc, sample_rate).and(Gain::new(AudioParam::Dynamic(Box::new(adsr))));
+    let source = DspChain::<Mono>::new(transport, sample_rate)
+        .and(gate_lane)
+        .and(osc)
+        .and(Gain::new(AudioParam::Dynamic(Box::new(adsr))));
 
     let delay_l = Delay::new(
         1.0,
@@ -44,25 +64,19 @@ fn create_dual_mono_chain(sample_rate: f32) -> (DspChain<Stereo>, Trigger) {
 
     let dual_mono_delay = DualMono::new(delay_l, delay_r);
 
-    let chain = source
+    source
         .to_stereo()
         .and(dual_mono_delay)
-        .and(Gain::new_db(-3.0));
-
-    (chain, trigger)
+        .and(Gain::new_db(-3.0))
 }
 
 fn main() -> Result<()> {
-    let trigger_ref = Arc::new(Mutex::new(None));
-    let trigger_clone = trigger_ref.clone();
+    let gate = Parameter::new(0.0);
+    let g = gate.clone();
 
-    let (stream, sample_rate) = init_audio_interleaved(move |sr| {
-        let (chain, trigger) = create_dual_mono_chain(sr);
-        *trigger_clone.lock().unwrap() = Some(trigger);
-        chain
-    })?;
+    let (stream, sample_rate) = init_audio_interleaved(move |sr| create_dual_mono_chain(sr, &g))?;
 
-    let (chain, _) = create_dual_mono_chain(44100.0);
+    let chain = create_dual_mono_chain(44100.0, &gate);
     println!("Signal Chain (Dual Mono):\n{}", chain.get_graph());
 
     println!("Playing Dual Mono Demo at {}Hz...", sample_rate);
@@ -71,12 +85,8 @@ fn main() -> Result<()> {
 
     stream.play()?;
 
-    for _ in 0..10 {
-        if let Some(t) = &*trigger_ref.lock().unwrap() {
-            t.fire();
-        }
-        thread::sleep(Duration::from_secs(1));
-    }
+    // The notes are played by the automation lane; just wait for the 10 of them.
+    thread::sleep(Duration::from_secs(10));
 
     Ok(())
 }
@@ -1,8 +1,11 @@
 use anyhow::Result;
 use cpal::traits::StreamTrait;
 use infinitedsp_core::core::audio_param::AudioParam;
-use infinitedsp_core::core::channels::Stereo;
+use infinitedsp_core::core::automation::{AutomationCurve, AutomationLane, AutomationPoint};
+use infinitedsp_core::core::channels::{Mono, Stereo};
 use infinitedsp_core::core::dsp_chain::DspChain;
+use infinitedsp_core::core::parameter::Parameter;
+use infinitedsp_core::core::transport::Transport;
 use infinitedsp_core::effects::modulation::modulated_delay::ModulatedDelay;
 use infinitedsp_core::effects::modulation::tremolo::Tremolo;
 use infinitedsp_core::effects::time::tape_delay::TapeDelay;
@@ -12,9 +15,26 @@ use infinitedsp_core::synthesis::envelope::Adsr;
 use infinitedsp_core::synthesis::oscillator::{Oscillator, Waveform};
 use infinitedsp_examples::audio_backend::init_audio_interleaved;
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::Duration;
+
+/// A 100 ms gate on every beat at 120 BPM, for 20 beats.
+fn gate_points() -> Vec<AutomationPoint> {
+    (0..20)
+        .flat_map(|beat| {
+            [
+                AutomationPoint::new(beat as f64, 1.0, AutomationCurve::Step),
+                AutomationPoint::new(beat as f64 + 0.2, 0.0, AutomationCurve::Step),
+            ]
+        })
+        .collect()
+}
+
+fn create_modulation_chain(sample_rate: f32, gate_param: &Parameter) -> DspChain<Stereo> {
+    let transport = Transport::new(sample_rate);
+    transport.start();
+    let gate_lane = AutomationLane::new(&transport, gate_param.clone(), gate_points());
+    let gate = AudioParam::Linked(gate_param.clone());
 
-fn create_modulation_chain(sample_rate: f32, gate: AudioParam) -> DspChain<Stereo> {
     let osc = Oscillator::new(AudioParam::hz(110.0), Waveform::Saw);
 
     let env = Adsr::new(
@@ -40,7 +60,9 @@ fn create_modulation_chain(sample_rate: f32, gate: AudioParam) -> DspChain<Stere
 
     let passthrough = Passthrough::new();
 
-    DspChain::new(osc, sample_rate)
+    DspChain::<Mono>::new(transport, sample_rate)
+        .and(gate_lane)
+        .and(osc)
         .and(vca)
         .and(tremolo)
         .and(chorus)
@@ -51,30 +73,19 @@ fn create_modulation_chain(sample_rate: f32, gate: AudioParam) -> DspChain<Stere
 }
 
 fn main() -> Result<()> {
-    use infinitedsp_core::core::parameter::Parameter;
-
     let gate_param = Parameter::new(0.0);
     let g = gate_param.clone();
 
-    let chain = create_modulation_chain(44100.0, AudioParam::Linked(gate_param.clone()));
+    let chain = create_modulation_chain(44100.0, &gate_param);
     println!("Signal Chain:\n{}", chain.get_graph());
 
-    let (stream, _sample_rate) = init_audio_interleaved(move |sr| {
-        create_modulation_chain(sr, AudioParam::Linked(g.clone()))
-    })?;
+    let (stream, _sample_rate) = init_audio_interleaved(move |sr| create_modulation_chain(sr, &g))?;
 
     println!("Playing Modulation Demo (Tremolo -> Chorus -> TapeDelay)...");
     stream.play()?;
 
-    let start = Instant::now();
-    let duration = Duration::from_secs(10);
-
-    while start.elapsed() < duration {
-        gate_param.set(1.0);
-        thread::sleep(Duration::from_millis(100));
-        gate_param.set(0.0);
-        thread::sleep(Duration::from_millis(400));
-    }
+    // The gate is played by the automation lane; just wait for the 20 beats.
+    thread::sleep(Duration::from_secs(10));
 
     Ok(())
 }
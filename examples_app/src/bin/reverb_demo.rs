@@ -1,35 +1,52 @@
 use anyhow::Result;
 use cpal::traits::StreamTrait;
 use infinitedsp_core::core::audio_param::AudioParam;
-use infinitedsp_core::core::channels::Stereo;
+use infinitedsp_core::core::automation::{AutomationCurve, AutomationLane, AutomationPoint};
+use infinitedsp_core::core::channels::{Mono, Stereo};
 use infinitedsp_core::core::dsp_chain::DspChain;
+use infinitedsp_core::core::parameter::Parameter;
+use infinitedsp_core::core::transport::Transport;
 use infinitedsp_core::effects::time::reverb::Reverb;
 use infinitedsp_core::effects::utility::gain::Gain;
 use infinitedsp_core::effects::utility::multiply::Multiply;
-use infinitedsp_core::synthesis::envelope::{Adsr, Trigger};
+use infinitedsp_core::synthesis::envelope::Adsr;
 use infinitedsp_core::synthesis::oscillator::{Oscillator, Waveform};
 use infinitedsp_examples::audio_backend::init_audio_interleaved;
-use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::Duration;
+
+/// A short gate every 2 beats at 120 BPM (once per second), for 15 plucks.
+fn gate_points() -> Vec<AutomationPoint> {
+    (0..15)
+        .flat_map(|note| {
+            let beat = note as f64 * 2.0;
+            [
+                AutomationPoint::new(beat, 1.0, AutomationCurve::Step),
+                AutomationPoint::new(beat + 0.2, 0.0, AutomationCurve::Step),
+            ]
+        })
+        .collect()
+}
 
 struct ReverbDemo {
     chain: DspChain<Stereo>,
-    trigger: Trigger,
 }
 
 impl ReverbDemo {
-    fn new(sample_rate: f32) -> Self {
+    fn new(sample_rate: f32, gate: &Parameter) -> Self {
+        let transport = Transport::new(sample_rate);
+        transport.start();
+        let gate_lane = AutomationLane::new(&transport, gate.clone(), gate_points());
+
         let osc = Oscillator::new(AudioParam::hz(440.0), Waveform::Saw);
 
         let adsr = Adsr::new(
-            AudioParam::linear(0.0),
+            AudioParam::Linked(gate.clone()),
             AudioParam::linear(0.01),
             AudioParam::linear(0.1),
             AudioParam::linear(0.0),
             AudioParam::linear(0.1),
         );
-        let trigger = adsr.create_trigger();
 
         let osc_param = AudioParam::Dynamic(Box::new(DspChain::new(osc, sample_rate)));
         let env_param = AudioParam::Dynamic(Box::new(DspChain::new(adsr, sample_rate)));
@@ -38,39 +55,29 @@ impl ReverbDemo {
 
         let reverb = Reverb::new();
 
-        let chain = DspChain::new(source, sample_rate)
+        let chain = DspChain::<Mono>::new(transport, sample_rate)
+            .and(gate_lane)
+            .and(source)
             .to_stereo()
             .and_mix(0.5, reverb)
             .and(Gain::new_db(-6.0));
 
-        ReverbDemo { chain, trigger }
+        ReverbDemo { chain }
     }
 }
 
 fn main() -> Result<()> {
-    let trigger_ref = Arc::new(Mutex::new(None));
-    let trigger_clone = trigger_ref.clone();
+    let gate = Parameter::new(0.0);
 
-    let (stream, sample_rate) = init_audio_interleaved(move |sr| {
-        let demo = ReverbDemo::new(sr);
-        *trigger_clone.lock().unwrap() = Some(demo.trigger.clone());
-        demo.chain
-    })?;
+    let (stream, sample_rate) = init_audio_interleaved(move |sr| ReverbDemo::new(sr, &gate).chain)?;
 
     println!("Playing Reverb Demo at {}Hz...", sample_rate);
     println!("Triggering pluck sound every second...");
 
     stream.play()?;
 
-    let start_time = Instant::now();
-    let duration = Duration::from_secs(15);
-
-    while start_time.elapsed() < duration {
-        if let Some(trigger) = &*trigger_ref.lock().unwrap() {
-            trigger.fire();
-        }
-        thread::sleep(Duration::from_millis(1000));
-    }
+    // The plucks are played by the automation lane; just wait for the 15 of them.
+    thread::sleep(Duration::from_secs(15));
 
     Ok(())
 }
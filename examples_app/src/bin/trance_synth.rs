@@ -1,7 +1,7 @@
 use anyhow::Result;
 use cpal::traits::StreamTrait;
 use infinitedsp_core::core::audio_param::AudioParam;
-use infinitedsp_core::core::channels::Mono;
+use infinitedsp_core::core::channels::{Mono, Stereo};
 use infinitedsp_core::core::dsp_chain::DspChain;
 use infinitedsp_core::core::frame_processor::FrameProcessor;
 use infinitedsp_core::core::parameter::Parameter;
@@ -94,8 +94,7 @@ struct StereoEngine {
     left_voice: DspChain<Mono>,
     right_voice: DspChain<Mono>,
     riser_voice: DspChain<Mono>,
-    master_filter_l: LadderFilter,
-    master_filter_r: LadderFilter,
+    master_filter: LadderFilter,
     reverb: Reverb,
     master_comp_l: Compressor,
     master_comp_r: Compressor,
@@ -127,14 +126,23 @@ impl StereoProcessor for StereoEngine {
             right[i] += r;
         }
 
-        self.master_filter_l.process(left, sample_index);
-        self.master_filter_r.process(right, sample_index);
-
         for i in 0..len {
             self.stereo_buffer[2 * i] = left[i];
             self.stereo_buffer[2 * i + 1] = right[i];
         }
 
+        // Both channels share one set of filter coefficients.
+        FrameProcessor::<Stereo>::process(
+            &mut self.master_filter,
+            &mut self.stereo_buffer[0..len * 2],
+            sample_index,
+        );
+
+        for i in 0..len {
+            left[i] = self.stereo_buffer[2 * i];
+            right[i] = self.stereo_buffer[2 * i + 1];
+        }
+
         self.reverb.process(&mut self.stereo_buffer, sample_index);
 
         for i in 0..len {
@@ -155,8 +163,8 @@ fn main() -> Result<()> {
     let pitch_param = Parameter::new(440.0);
     let pitch_param_r = Parameter::new(440.0);
     let gate_param = Parameter::new(0.0);
-    let master_cutoff = Parameter::new(100.0);
-    let riser_cutoff = Parameter::new(100.0);
+    let master_cutoff = Parameter::new(100.0).with_smoothing(0.01);
+    let riser_cutoff = Parameter::new(100.0).with_smoothing(0.01);
     let riser_gain = Parameter::new(1.0);
 
     let bpm = 138.0;
@@ -179,8 +187,7 @@ fn main() -> Result<()> {
         left_voice: create_trance_voice(sr, p_l.clone(), g.clone(), delay_l),
         right_voice: create_trance_voice(sr, p_r.clone(), g.clone(), delay_r),
         riser_voice: create_riser_voice(sr, rc.clone(), rg.clone()),
-        master_filter_l: LadderFilter::new(AudioParam::Linked(mc.clone()), AudioParam::Static(0.2)),
-        master_filter_r: LadderFilter::new(AudioParam::Linked(mc.clone()), AudioParam::Static(0.2)),
+        master_filter: LadderFilter::new(AudioParam::Linked(mc.clone()), AudioParam::Static(0.2)),
         reverb: Reverb::new(),
         master_comp_l: Compressor::new_limiter(),
         master_comp_r: Compressor::new_limiter(),
@@ -0,0 +1,581 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::parameter::Parameter;
+use crate::effects::filter::biquad::BiquadCoeffs;
+use crate::FrameProcessor;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+use core::marker::PhantomData;
+
+/// Taps per phase of the true-peak interpolator.
+const TRUE_PEAK_TAPS: usize = 12;
+/// Oversampling factor of the true-peak interpolator.
+const TRUE_PEAK_PHASES: usize = 4;
+
+/// Polyphase windowed-sinc coefficients that interpolate between samples. Phase `p`
+/// evaluates the signal `p / 4` of a sample after the centre tap.
+fn true_peak_kernel() -> [[f32; TRUE_PEAK_TAPS]; TRUE_PEAK_PHASES] {
+    let center = (TRUE_PEAK_TAPS / 2 - 1) as f32;
+    let half = TRUE_PEAK_TAPS as f32 / 2.0;
+    let mut kernel = [[0.0; TRUE_PEAK_TAPS]; TRUE_PEAK_PHASES];
+    for (phase, taps) in kernel.iter_mut().enumerate() {
+        let t = phase as f32 / TRUE_PEAK_PHASES as f32;
+        for (i, tap) in taps.iter_mut().enumerate() {
+            let x = i as f32 - center - t;
+            let sinc = if x.abs() < 1e-6 {
+                1.0
+            } else {
+                libm::sinf(PI * x) / (PI * x)
+            };
+            let window = 0.5 + 0.5 * libm::cosf(PI * x / half);
+            *tap = sinc * window;
+        }
+    }
+    kernel
+}
+
+/// A peak level meter.
+///
+/// The audio passes through unchanged. After each block the meter publishes, per channel,
+/// the peak level (linear), which falls back at `fall_rate` dB per second, plus the highest
+/// peak of any channel since the last reset. Read them from a UI thread through the
+/// [`Parameter`] handles.
+///
+/// With true-peak detection the signal is interpolated 4x before the peak is taken, so
+/// inter-sample peaks that would clip a D/A converter are caught (as in ITU-R BS.1770).
+pub struct PeakMeter<C: ChannelConfig> {
+    true_peak: bool,
+    fall_rate: f32,
+    sample_rate: f32,
+
+    peaks: Vec<f32>,
+    max_peak: f32,
+    // The last `TRUE_PEAK_TAPS` samples of each channel, oldest first.
+    history: Vec<[f32; TRUE_PEAK_TAPS]>,
+    kernel: [[f32; TRUE_PEAK_TAPS]; TRUE_PEAK_PHASES],
+
+    levels: Vec<Parameter>,
+    max_level: Parameter,
+    _channels: PhantomData<C>,
+}
+
+impl<C: ChannelConfig> PeakMeter<C> {
+    /// Creates a new sample-peak PeakMeter.
+    pub fn new() -> Self {
+        let channels = C::num_channels();
+        PeakMeter {
+            true_peak: false,
+            fall_rate: 20.0,
+            sample_rate: 44100.0,
+            peaks: vec![0.0; channels],
+            max_peak: 0.0,
+            history: vec![[0.0; TRUE_PEAK_TAPS]; channels],
+            kernel: true_peak_kernel(),
+            levels: (0..channels).map(|_| Parameter::new(0.0)).collect(),
+            max_level: Parameter::new(0.0),
+            _channels: PhantomData,
+        }
+    }
+
+    /// Enables true-peak (4x oversampled) detection and returns the meter.
+    pub fn with_true_peak(mut self, true_peak: bool) -> Self {
+        self.true_peak = true_peak;
+        self
+    }
+
+    /// Sets how fast the displayed peaks fall back, in dB per second (default 20), and
+    /// returns the meter.
+    pub fn with_fall_rate(mut self, db_per_second: f32) -> Self {
+        self.fall_rate = db_per_second.max(0.0);
+        self
+    }
+
+    /// Returns a handle to the peak level of channel `channel` (linear).
+    pub fn level(&self, channel: usize) -> Parameter {
+        self.levels[channel].clone()
+    }
+
+    /// Returns a handle to the highest peak of any channel since the last reset (linear).
+    pub fn max_level(&self) -> Parameter {
+        self.max_level.clone()
+    }
+
+    #[inline(always)]
+    fn interpolated_peak(&self, history: &[f32; TRUE_PEAK_TAPS]) -> f32 {
+        self.kernel.iter().fold(0.0f32, |peak, taps| {
+            let y: f32 = taps.iter().zip(history.iter()).map(|(h, x)| h * x).sum();
+            peak.max(y.abs())
+        })
+    }
+}
+
+impl<C: ChannelConfig> Default for PeakMeter<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: ChannelConfig> FrameProcessor<C> for PeakMeter<C> {
+    fn process(&mut self, buffer: &mut [f32], _sample_index: u64) {
+        let channels = C::num_channels();
+        let frames = buffer.len() / channels;
+        let fall = libm::powf(
+            10.0,
+            -self.fall_rate * frames as f32 / (20.0 * self.sample_rate),
+        );
+
+        for channel in 0..channels {
+            let mut block_peak = 0.0f32;
+            let mut history = self.history[channel];
+            for &sample in buffer.iter().skip(channel).step_by(channels) {
+                if self.true_peak {
+                    history.copy_within(1.., 0);
+                    history[TRUE_PEAK_TAPS - 1] = sample;
+                    block_peak = block_peak.max(self.interpolated_peak(&history));
+                } else {
+                    block_peak = block_peak.max(sample.abs());
+                }
+            }
+            self.history[channel] = history;
+
+            let peak = block_peak.max(self.peaks[channel] * fall);
+            self.peaks[channel] = peak;
+            self.max_peak = self.max_peak.max(block_peak);
+            self.levels[channel].set(peak);
+        }
+        self.max_level.set(self.max_peak);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+    }
+
+    fn reset(&mut self) {
+        self.peaks.fill(0.0);
+        self.max_peak = 0.0;
+        for history in &mut self.history {
+            history.fill(0.0);
+        }
+        for level in &self.levels {
+            level.set(0.0);
+        }
+        self.max_level.set(0.0);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        if self.true_peak {
+            "PeakMeter (True Peak)"
+        } else {
+            "PeakMeter"
+        }
+    }
+}
+
+/// An RMS level meter.
+///
+/// The audio passes through unchanged. The mean square of each channel is averaged
+/// exponentially over the integration time (300 ms is typical) and its square root published
+/// (linear) after each block.
+pub struct RmsMeter<C: ChannelConfig> {
+    integration_time: f32,
+    sample_rate: f32,
+    coeff: f32,
+    mean_squares: Vec<f32>,
+    levels: Vec<Parameter>,
+    _channels: PhantomData<C>,
+}
+
+impl<C: ChannelConfig> RmsMeter<C> {
+    /// Creates a new RmsMeter.
+    ///
+    /// # Arguments
+    /// * `integration_time` - Averaging time constant in seconds (e.g. 0.3).
+    pub fn new(integration_time: f32) -> Self {
+        let channels = C::num_channels();
+        let mut meter = RmsMeter {
+            integration_time: integration_time.max(1e-4),
+            sample_rate: 44100.0,
+            coeff: 0.0,
+            mean_squares: vec![0.0; channels],
+            levels: (0..channels).map(|_| Parameter::new(0.0)).collect(),
+            _channels: PhantomData,
+        };
+        meter.recalc();
+        meter
+    }
+
+    /// Returns a handle to the RMS level of channel `channel` (linear).
+    pub fn level(&self, channel: usize) -> Parameter {
+        self.levels[channel].clone()
+    }
+
+    fn recalc(&mut self) {
+        self.coeff = libm::expf(-1.0 / (self.integration_time * self.sample_rate));
+    }
+}
+
+impl<C: ChannelConfig> FrameProcessor<C> for RmsMeter<C> {
+    fn process(&mut self, buffer: &mut [f32], _sample_index: u64) {
+        let channels = C::num_channels();
+        for frame in buffer.chunks_exact(channels) {
+            for (mean_square, &sample) in self.mean_squares.iter_mut().zip(frame.iter()) {
+                *mean_square = self.coeff * *mean_square + (1.0 - self.coeff) * sample * sample;
+            }
+        }
+        for (level, &mean_square) in self.levels.iter().zip(self.mean_squares.iter()) {
+            level.set(libm::sqrtf(mean_square));
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.recalc();
+    }
+
+    fn reset(&mut self) {
+        self.mean_squares.fill(0.0);
+        for level in &self.levels {
+            level.set(0.0);
+        }
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "RmsMeter"
+    }
+}
+
+/// Loudness below which 400 ms blocks are ignored by the integrated measurement (LUFS).
+const ABSOLUTE_GATE: f32 = -70.0;
+/// Resolution of the integrated loudness histogram, in LU per bin.
+const HISTOGRAM_STEP: f32 = 0.1;
+/// Histogram bins, covering -70 to +30 LUFS.
+const HISTOGRAM_BINS: usize = 1000;
+/// 100 ms sub-blocks per momentary (400 ms) and short-term (3 s) window.
+const MOMENTARY_BLOCKS: usize = 4;
+const SHORT_TERM_BLOCKS: usize = 30;
+
+/// Direct form I state of one K-weighting stage.
+#[derive(Clone, Copy, Default)]
+struct Stage {
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Stage {
+    #[inline(always)]
+    fn tick(&mut self, c: &BiquadCoeffs, x: f32) -> f32 {
+        let y = c.b0 * x + c.b1 * self.x1 + c.b2 * self.x2 - c.a1 * self.y1 - c.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+/// The two K-weighting filters of ITU-R BS.1770 (a high shelf and a high-pass), derived for
+/// any sample rate.
+fn k_weighting(sample_rate: f32) -> [BiquadCoeffs; 2] {
+    let f0 = 1681.9745;
+    let gain_db = 3.9998438;
+    let q = 0.70717525;
+    let k = libm::tanf(PI * f0 / sample_rate);
+    let vh = libm::powf(10.0, gain_db / 20.0);
+    let vb = libm::powf(vh, 0.49966678);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = BiquadCoeffs {
+        b0: (vh + vb * k / q + k * k) / a0,
+        b1: 2.0 * (k * k - vh) / a0,
+        b2: (vh - vb * k / q + k * k) / a0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+    };
+
+    let f0 = 38.13547;
+    let q = 0.500327;
+    let k = libm::tanf(PI * f0 / sample_rate);
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = BiquadCoeffs {
+        b0: 1.0,
+        b1: -2.0,
+        b2: 1.0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+    };
+    [shelf, high_pass]
+}
+
+fn power_to_lufs(power: f32) -> f32 {
+    if power > 0.0 {
+        -0.691 + 10.0 * libm::log10f(power)
+    } else {
+        f32::NEG_INFINITY
+    }
+}
+
+fn lufs_to_power(lufs: f32) -> f32 {
+    libm::powf(10.0, (lufs + 0.691) / 10.0)
+}
+
+/// An EBU R128 loudness meter.
+///
+/// The audio passes through unchanged. The signal is K-weighted and measured in 100 ms
+/// steps, publishing three readings in LUFS (`f32::NEG_INFINITY` while silent):
+///
+/// * `momentary` - Loudness of the last 400 ms.
+/// * `short_term` - Loudness of the last 3 s.
+/// * `integrated` - Gated loudness of everything since the last reset (absolute gate at
+///   -70 LUFS, relative gate 10 LU below the ungated level).
+///
+/// The integrated measurement keeps a histogram of block loudness at 0.1 LU resolution, so
+/// it runs for any length of time without allocating. All channels are weighted 1.0, which
+/// is correct for mono and stereo.
+pub struct LoudnessMeter<C: ChannelConfig> {
+    sample_rate: f32,
+    filters: [BiquadCoeffs; 2],
+    stages: Vec<[Stage; 2]>,
+
+    block_len: usize,
+    block_pos: usize,
+    block_sum: f32,
+    // Power of the most recent 100 ms sub-blocks, a ring of `SHORT_TERM_BLOCKS`.
+    blocks: [f32; SHORT_TERM_BLOCKS],
+    block_index: usize,
+    blocks_seen: usize,
+    histogram: Vec<u32>,
+
+    momentary: Parameter,
+    short_term: Parameter,
+    integrated: Parameter,
+    _channels: PhantomData<C>,
+}
+
+impl<C: ChannelConfig> LoudnessMeter<C> {
+    /// Creates a new LoudnessMeter.
+    pub fn new() -> Self {
+        let mut meter = LoudnessMeter {
+            sample_rate: 44100.0,
+            filters: k_weighting(44100.0),
+            stages: vec![[Stage::default(); 2]; C::num_channels()],
+            block_len: 4410,
+            block_pos: 0,
+            block_sum: 0.0,
+            blocks: [0.0; SHORT_TERM_BLOCKS],
+            block_index: 0,
+            blocks_seen: 0,
+            histogram: vec![0; HISTOGRAM_BINS],
+            momentary: Parameter::new(f32::NEG_INFINITY),
+            short_term: Parameter::new(f32::NEG_INFINITY),
+            integrated: Parameter::new(f32::NEG_INFINITY),
+            _channels: PhantomData,
+        };
+        meter.set_sample_rate(44100.0);
+        meter
+    }
+
+    /// Returns a handle to the momentary loudness (400 ms) in LUFS.
+    pub fn momentary(&self) -> Parameter {
+        self.momentary.clone()
+    }
+
+    /// Returns a handle to the short-term loudness (3 s) in LUFS.
+    pub fn short_term(&self) -> Parameter {
+        self.short_term.clone()
+    }
+
+    /// Returns a handle to the integrated loudness in LUFS.
+    pub fn integrated(&self) -> Parameter {
+        self.integrated.clone()
+    }
+
+    /// Mean power of the most recent `count` sub-blocks.
+    fn window_power(&self, count: usize) -> f32 {
+        let count = count.min(self.blocks_seen);
+        if count == 0 {
+            return 0.0;
+        }
+        let sum: f32 = (1..=count)
+            .map(|back| {
+                self.blocks[(self.block_index + SHORT_TERM_BLOCKS - back) % SHORT_TERM_BLOCKS]
+            })
+            .sum();
+        sum / count as f32
+    }
+
+    fn bin_power(bin: usize) -> f32 {
+        lufs_to_power(ABSOLUTE_GATE + (bin as f32 + 0.5) * HISTOGRAM_STEP)
+    }
+
+    fn gated_loudness(&self) -> f32 {
+        let (count, sum) =
+            self.histogram
+                .iter()
+                .enumerate()
+                .fold((0u64, 0.0f64), |(count, sum), (bin, &n)| {
+                    (
+                        count + n as u64,
+                        sum + n as f64 * Self::bin_power(bin) as f64,
+                    )
+                });
+        if count == 0 {
+            return f32::NEG_INFINITY;
+        }
+
+        let relative_gate = power_to_lufs((sum / count as f64) as f32) - 10.0;
+        let first = (((relative_gate - ABSOLUTE_GATE) / HISTOGRAM_STEP).max(0.0) as usize)
+            .min(HISTOGRAM_BINS);
+        let (count, sum) = self.histogram[first..].iter().enumerate().fold(
+            (0u64, 0.0f64),
+            |(count, sum), (i, &n)| {
+                (
+                    count + n as u64,
+                    sum + n as f64 * Self::bin_power(first + i) as f64,
+                )
+            },
+        );
+        if count == 0 {
+            f32::NEG_INFINITY
+        } else {
+            power_to_lufs((sum / count as f64) as f32)
+        }
+    }
+
+    fn end_block(&mut self) {
+        self.blocks[self.block_index] = self.block_sum / self.block_len as f32;
+        self.block_index = (self.block_index + 1) % SHORT_TERM_BLOCKS;
+        self.blocks_seen = (self.blocks_seen + 1).min(SHORT_TERM_BLOCKS);
+        self.block_sum = 0.0;
+        self.block_pos = 0;
+
+        let momentary = power_to_lufs(self.window_power(MOMENTARY_BLOCKS));
+        self.momentary.set(momentary);
+        self.short_term
+            .set(power_to_lufs(self.window_power(SHORT_TERM_BLOCKS)));
+
+        // Gating blocks are 400 ms long and overlap by 75%: one per sub-block.
+        if self.blocks_seen >= MOMENTARY_BLOCKS && momentary > ABSOLUTE_GATE {
+            let bin = ((momentary - ABSOLUTE_GATE) / HISTOGRAM_STEP) as usize;
+            self.histogram[bin.min(HISTOGRAM_BINS - 1)] += 1;
+            self.integrated.set(self.gated_loudness());
+        }
+    }
+}
+
+impl<C: ChannelConfig> Default for LoudnessMeter<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: ChannelConfig> FrameProcessor<C> for LoudnessMeter<C> {
+    fn process(&mut self, buffer: &mut [f32], _sample_index: u64) {
+        let channels = C::num_channels();
+        for frame in buffer.chunks_exact(channels) {
+            for (stages, &sample) in self.stages.iter_mut().zip(frame.iter()) {
+                let shelved = stages[0].tick(&self.filters[0], sample);
+                let weighted = stages[1].tick(&self.filters[1], shelved);
+                self.block_sum += weighted * weighted;
+            }
+            self.block_pos += 1;
+            if self.block_pos >= self.block_len {
+                self.end_block();
+            }
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.filters = k_weighting(sample_rate);
+        self.block_len = ((sample_rate * 0.1) as usize).max(1);
+        self.block_pos = 0;
+        self.block_sum = 0.0;
+    }
+
+    fn reset(&mut self) {
+        self.stages.fill([Stage::default(); 2]);
+        self.block_pos = 0;
+        self.block_sum = 0.0;
+        self.blocks.fill(0.0);
+        self.block_index = 0;
+        self.blocks_seen = 0;
+        self.histogram.fill(0);
+        self.momentary.set(f32::NEG_INFINITY);
+        self.short_term.set(f32::NEG_INFINITY);
+        self.integrated.set(f32::NEG_INFINITY);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "LoudnessMeter (EBU R128)"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::{Mono, Stereo};
+
+    fn stereo_sine(freq: f32, amplitude: f32, seconds: f32, sample_rate: f32) -> Vec<f32> {
+        let frames = (seconds * sample_rate) as usize;
+        (0..frames * 2)
+            .map(|i| amplitude * libm::sinf(2.0 * PI * freq * (i / 2) as f32 / sample_rate))
+            .collect()
+    }
+
+    #[test]
+    fn test_peak_and_rms() {
+        // A sine at a quarter of the sample rate, sampled 45 degrees off its peaks.
+        let mut buffer: Vec<f32> = (0..4096)
+            .map(|i| libm::sinf(PI / 2.0 * (i % 4) as f32 + PI / 4.0))
+            .collect();
+        let mut sample_peak = PeakMeter::<Mono>::new();
+        let mut true_peak = PeakMeter::<Mono>::new().with_true_peak(true);
+        let mut rms = RmsMeter::<Mono>::new(0.01);
+        for block in buffer.chunks_mut(128) {
+            sample_peak.process(block, 0);
+            true_peak.process(block, 0);
+            rms.process(block, 0);
+        }
+        assert!((sample_peak.level(0).get() - core::f32::consts::FRAC_1_SQRT_2).abs() < 1e-4);
+        assert!((true_peak.max_level().get() - 1.0).abs() < 0.02);
+        assert!((rms.level(0).get() - core::f32::consts::FRAC_1_SQRT_2).abs() < 0.01);
+
+        // Silence lets the displayed peak fall; the maximum is held.
+        let mut silence = vec![0.0; 44100];
+        for block in silence.chunks_mut(128) {
+            true_peak.process(block, 0);
+        }
+        assert!(true_peak.level(0).get() < 0.11);
+        assert!(true_peak.max_level().get() > 0.98);
+    }
+
+    #[test]
+    fn test_ebu_reference_level() {
+        // EBU Tech 3341: a 1 kHz stereo sine at -23 dBFS reads -23 LUFS.
+        for sample_rate in [44100.0, 48000.0] {
+            let mut meter = LoudnessMeter::<Stereo>::new();
+            meter.set_sample_rate(sample_rate);
+            let amplitude = libm::powf(10.0, -23.0 / 20.0);
+            let mut buffer = stereo_sine(1000.0, amplitude, 5.0, sample_rate);
+            for block in buffer.chunks_mut(256) {
+                meter.process(block, 0);
+            }
+            for reading in [meter.momentary(), meter.short_term(), meter.integrated()] {
+                assert!((reading.get() + 23.0).abs() < 0.1, "{}", reading.get());
+            }
+        }
+
+        // Quiet passages below the relative gate don't drag the integrated level down.
+        let mut meter = LoudnessMeter::<Stereo>::new();
+        let mut loud = stereo_sine(1000.0, libm::powf(10.0, -23.0 / 20.0), 5.0, 44100.0);
+        let mut quiet = stereo_sine(1000.0, libm::powf(10.0, -53.0 / 20.0), 5.0, 44100.0);
+        meter.process(&mut loud, 0);
+        meter.process(&mut quiet, 0);
+        assert!((meter.integrated().get() + 23.0).abs() < 0.2);
+        assert!(meter.short_term().get() < -50.0);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod metering;
+pub mod pitch;
+pub mod scope;
+pub mod spectrum;
+pub mod stereo;
+pub mod waterfall;
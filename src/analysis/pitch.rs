@@ -0,0 +1,284 @@
+use crate::core::channels::Mono;
+use crate::core::parameter::Parameter;
+use crate::FrameProcessor;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A YIN based fundamental frequency estimator.
+///
+/// The audio passes through unchanged. Every `max_period` samples (the longest period of
+/// `min_freq`) the detector takes the most recent window and analyses it over the next
+/// `max_period` samples, one lag of the difference function per sample. So each sample
+/// costs about `max_period` multiply-adds instead of the whole analysis landing on one
+/// sample, and each result is published one hop after its window closes, to two
+/// [`Parameter`]s, which can be read from a UI thread (e.g. a tuner display) or linked into
+/// other processors via `AudioParam::Linked`:
+///
+/// * `frequency` - The estimated fundamental in Hz, or 0.0 when no pitch was found.
+/// * `confidence` - 0.0 (unvoiced) to 1.0 (perfectly periodic).
+pub struct PitchDetector {
+    min_freq: f32,
+    max_freq: f32,
+    threshold: f32,
+    sample_rate: f32,
+
+    min_period: usize,
+    max_period: usize,
+    history: Vec<f32>,
+    write_pos: usize,
+    since_analysis: usize,
+    frame: Vec<f32>,
+    diff: Vec<f32>,
+    // Next lag of the difference function to compute, 0 while idle.
+    next_tau: usize,
+    running_sum: f32,
+
+    frequency: Parameter,
+    confidence: Parameter,
+}
+
+impl PitchDetector {
+    /// Creates a new PitchDetector.
+    ///
+    /// # Arguments
+    /// * `min_freq` - Lowest detectable fundamental in Hz. Determines the analysis window.
+    /// * `max_freq` - Highest detectable fundamental in Hz.
+    pub fn new(min_freq: f32, max_freq: f32) -> Self {
+        assert!(
+            min_freq > 0.0 && max_freq > min_freq,
+            "PitchDetector: Frequency range must be positive and increasing."
+        );
+        let mut detector = PitchDetector {
+            min_freq,
+            max_freq,
+            threshold: 0.15,
+            sample_rate: 44100.0,
+            min_period: 0,
+            max_period: 0,
+            history: Vec::new(),
+            write_pos: 0,
+            since_analysis: 0,
+            frame: Vec::new(),
+            diff: Vec::new(),
+            next_tau: 0,
+            running_sum: 0.0,
+            frequency: Parameter::new(0.0),
+            confidence: Parameter::new(0.0),
+        };
+        detector.allocate();
+        detector
+    }
+
+    /// Sets the YIN threshold and returns the detector.
+    ///
+    /// Lower values reject noisier signals. Typical values are 0.1 - 0.2 (default 0.15).
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Returns a handle to the estimated frequency in Hz (0.0 when unvoiced).
+    pub fn frequency(&self) -> Parameter {
+        self.frequency.clone()
+    }
+
+    /// Returns a handle to the confidence of the last estimate (0.0 - 1.0).
+    pub fn confidence(&self) -> Parameter {
+        self.confidence.clone()
+    }
+
+    fn allocate(&mut self) {
+        self.min_period = ((self.sample_rate / self.max_freq) as usize).max(2);
+        self.max_period = libm::ceilf(self.sample_rate / self.min_freq) as usize + 1;
+        let window = self.max_period * 2;
+
+        self.history = vec![0.0; window];
+        self.frame = vec![0.0; window];
+        self.diff = vec![0.0; self.max_period + 1];
+        self.write_pos = 0;
+        self.since_analysis = 0;
+        self.next_tau = 0;
+    }
+
+    /// Takes the most recent window and starts analysing it.
+    fn start_analysis(&mut self) {
+        let window = self.history.len();
+        let (older, newer) = self.history.split_at(self.write_pos);
+        self.frame[..newer.len()].copy_from_slice(newer);
+        self.frame[newer.len()..window].copy_from_slice(older);
+
+        self.diff[0] = 1.0;
+        self.running_sum = 0.0;
+        self.next_tau = 1;
+    }
+
+    /// Computes the next lag of the difference function and its cumulative mean
+    /// normalization, publishing the result after the last one.
+    fn analysis_step(&mut self) {
+        let size = self.max_period;
+        let tau = self.next_tau;
+        let frame = &self.frame;
+        let mut sum = 0.0;
+        for j in 0..size {
+            let delta = frame[j] - frame[j + tau];
+            sum += delta * delta;
+        }
+        self.running_sum += sum;
+        self.diff[tau] = if self.running_sum > 0.0 {
+            sum * tau as f32 / self.running_sum
+        } else {
+            1.0
+        };
+
+        if tau < size {
+            self.next_tau += 1;
+        } else {
+            self.next_tau = 0;
+            self.publish();
+        }
+    }
+
+    fn publish(&mut self) {
+        let size = self.max_period;
+        let mut found = None;
+        let mut tau = self.min_period;
+        while tau < size {
+            if self.diff[tau] < self.threshold {
+                while tau + 1 < size && self.diff[tau + 1] < self.diff[tau] {
+                    tau += 1;
+                }
+                found = Some(tau);
+                break;
+            }
+            tau += 1;
+        }
+
+        match found {
+            Some(tau) => {
+                let (a, b, c) = (self.diff[tau - 1], self.diff[tau], self.diff[tau + 1]);
+                let denom = a - 2.0 * b + c;
+                let offset = if denom.abs() > 1e-12 {
+                    (0.5 * (a - c) / denom).clamp(-1.0, 1.0)
+                } else {
+                    0.0
+                };
+                let period = tau as f32 + offset;
+                self.frequency.set(self.sample_rate / period);
+                self.confidence.set((1.0 - b).clamp(0.0, 1.0));
+            }
+            None => {
+                self.frequency.set(0.0);
+                self.confidence.set(0.0);
+            }
+        }
+    }
+}
+
+impl FrameProcessor<Mono> for PitchDetector {
+    fn process(&mut self, buffer: &mut [f32], _sample_index: u64) {
+        let window = self.history.len();
+        for &sample in buffer.iter() {
+            self.history[self.write_pos] = sample;
+            self.write_pos += 1;
+            if self.write_pos >= window {
+                self.write_pos = 0;
+            }
+
+            self.since_analysis += 1;
+            if self.since_analysis >= self.max_period {
+                self.since_analysis = 0;
+                while self.next_tau != 0 {
+                    self.analysis_step();
+                }
+                self.start_analysis();
+            }
+            if self.next_tau != 0 {
+                self.analysis_step();
+            }
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.allocate();
+    }
+
+    fn reset(&mut self) {
+        self.history.fill(0.0);
+        self.write_pos = 0;
+        self.since_analysis = 0;
+        self.next_tau = 0;
+        self.frequency.set(0.0);
+        self.confidence.set(0.0);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "PitchDetector (YIN)"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::PI;
+
+    fn feed(detector: &mut PitchDetector, signal: impl Fn(f32) -> f32) {
+        let mut buffer: Vec<f32> = (0..8192).map(|i| signal(i as f32 / 44100.0)).collect();
+        for (i, block) in buffer.chunks_mut(128).enumerate() {
+            detector.process(block, (i * 128) as u64);
+        }
+    }
+
+    #[test]
+    fn test_detects_sine() {
+        for freq in [82.41, 220.0, 659.25] {
+            let mut detector = PitchDetector::new(50.0, 1000.0);
+            feed(&mut detector, |t| libm::sinf(2.0 * PI * freq * t));
+
+            let estimate = detector.frequency().get();
+            assert!(
+                (estimate - freq).abs() < freq * 0.005,
+                "{} vs {}",
+                estimate,
+                freq
+            );
+            assert!(detector.confidence().get() > 0.9);
+        }
+    }
+
+    #[test]
+    fn test_detects_fundamental_of_harmonic_tone() {
+        let mut detector = PitchDetector::new(50.0, 1000.0);
+        feed(&mut detector, |t| {
+            let w = 2.0 * PI * 110.0 * t;
+            0.3 * libm::sinf(w) + 0.6 * libm::sinf(2.0 * w) + 0.4 * libm::sinf(3.0 * w)
+        });
+        assert!((detector.frequency().get() - 110.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_silence_is_unvoiced() {
+        let mut detector = PitchDetector::new(50.0, 1000.0);
+        feed(&mut detector, |_| 0.0);
+        assert_eq!(detector.frequency().get(), 0.0);
+        assert_eq!(detector.confidence().get(), 0.0);
+    }
+
+    #[test]
+    fn test_analysis_is_spread_over_the_hop() {
+        let mut detector = PitchDetector::new(50.0, 1000.0);
+        let hop = detector.max_period;
+        let mut sine: Vec<f32> = (0..hop * 4 - 1)
+            .map(|i| libm::sinf(2.0 * PI * 220.0 * i as f32 / 44100.0))
+            .collect();
+
+        // One lag per sample after the window is taken; nothing is published until all
+        // of them are done.
+        detector.process(&mut sine[..hop * 3 + 10], 0);
+        assert_eq!(detector.next_tau, 12);
+        detector.process(&mut sine[hop * 3 + 10..], 0);
+        assert_eq!(detector.next_tau, 0);
+        assert!((detector.frequency().get() - 220.0).abs() < 1.0);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::error::DspError;
+use crate::FrameProcessor;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// When a [`ScopeTap`] starts a capture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeTrigger {
+    /// Capture back-to-back windows, without synchronizing to the signal.
+    Free,
+    /// Start each capture at a rising zero-crossing, so periodic waveforms stand still.
+    /// When no crossing arrives within one window length, a capture starts anyway.
+    RisingZero,
+}
+
+struct ScopeState {
+    buffers: [Vec<AtomicU32>; 2],
+    front: AtomicU32,
+    frames: AtomicU32,
+}
+
+/// The latest waveform captured by a [`ScopeTap`], readable from any thread.
+///
+/// The tap writes each capture into the back buffer and then flips it to the front, so a
+/// reader always sees a complete capture unless the read takes longer than a whole capture.
+///
+/// Clones share the same buffers.
+#[derive(Clone)]
+pub struct ScopeHandle {
+    state: Arc<ScopeState>,
+}
+
+impl ScopeHandle {
+    /// Returns the capture length in samples.
+    pub fn len(&self) -> usize {
+        self.state.buffers[0].len()
+    }
+
+    /// Returns true if the captures are empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns how many captures have completed so far. Poll it to detect new captures.
+    pub fn frames(&self) -> u32 {
+        self.state.frames.load(Ordering::Acquire)
+    }
+
+    /// Copies the latest capture into `out` (up to its length).
+    pub fn read(&self, out: &mut [f32]) {
+        let front = self.state.front.load(Ordering::Acquire) as usize;
+        for (value, sample) in out.iter_mut().zip(self.state.buffers[front].iter()) {
+            *value = f32::from_bits(sample.load(Ordering::Relaxed));
+        }
+    }
+
+    /// Returns the latest capture.
+    pub fn samples(&self) -> Vec<f32> {
+        let mut out = vec![0.0; self.len()];
+        self.read(&mut out);
+        out
+    }
+}
+
+/// An oscilloscope tap.
+///
+/// The audio passes through unchanged. Windows of `length` samples of one channel (or of
+/// the mono mix of all channels) are captured and published to a [`ScopeHandle`] for a GUI
+/// to draw. With [`ScopeTrigger::RisingZero`] each window starts at a rising
+/// zero-crossing; the capture never allocates or blocks.
+pub struct ScopeTap<C: ChannelConfig> {
+    trigger: ScopeTrigger,
+    channel: Option<usize>,
+
+    capture: Vec<f32>,
+    captured: usize,
+    armed: bool,
+    waited: usize,
+    last: f32,
+
+    handle: ScopeHandle,
+    _channels: PhantomData<C>,
+}
+
+impl<C: ChannelConfig> ScopeTap<C> {
+    /// Creates a new free-running ScopeTap of the mono mix.
+    ///
+    /// # Arguments
+    /// * `length` - Samples per capture.
+    pub fn new(length: usize) -> Self {
+        Self::try_new(length).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a new free-running ScopeTap, or returns an error if `length` is zero.
+    pub fn try_new(length: usize) -> Result<Self, DspError> {
+        if length == 0 {
+            return Err(DspError::Invalid {
+                processor: "ScopeTap",
+                message: "The capture length must be positive.",
+            });
+        }
+        let buffer = || (0..length).map(|_| AtomicU32::new(0)).collect();
+        Ok(ScopeTap {
+            trigger: ScopeTrigger::Free,
+            channel: None,
+            capture: vec![0.0; length],
+            captured: 0,
+            armed: false,
+            waited: 0,
+            last: 0.0,
+            handle: ScopeHandle {
+                state: Arc::new(ScopeState {
+                    buffers: [buffer(), buffer()],
+                    front: AtomicU32::new(0),
+                    frames: AtomicU32::new(0),
+                }),
+            },
+            _channels: PhantomData,
+        })
+    }
+
+    /// Sets the trigger mode and returns the tap.
+    pub fn with_trigger(mut self, trigger: ScopeTrigger) -> Self {
+        self.trigger = trigger;
+        self.armed = trigger == ScopeTrigger::RisingZero;
+        self
+    }
+
+    /// Captures only channel `channel` instead of the mono mix, and returns the tap.
+    pub fn with_channel(self, channel: usize) -> Self {
+        self.try_with_channel(channel)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Captures only channel `channel`, or returns an error if the layout has no such channel.
+    pub fn try_with_channel(mut self, channel: usize) -> Result<Self, DspError> {
+        DspError::check_count("ScopeTap", "channel", channel, 0, C::num_channels() - 1)?;
+        self.channel = Some(channel);
+        Ok(self)
+    }
+
+    /// Returns a handle to the captured waveform.
+    pub fn handle(&self) -> ScopeHandle {
+        self.handle.clone()
+    }
+
+    fn publish(&mut self) {
+        let state = &self.handle.state;
+        let back = 1 - state.front.load(Ordering::Relaxed) as usize;
+        for (slot, &sample) in state.buffers[back].iter().zip(self.capture.iter()) {
+            slot.store(sample.to_bits(), Ordering::Relaxed);
+        }
+        state.front.store(back as u32, Ordering::Release);
+        state.frames.fetch_add(1, Ordering::Release);
+    }
+
+    #[inline(always)]
+    fn push(&mut self, sample: f32) {
+        let previous = self.last;
+        self.last = sample;
+        if self.armed {
+            self.waited += 1;
+            if (previous <= 0.0 && sample > 0.0) || self.waited > self.capture.len() {
+                self.armed = false;
+                self.waited = 0;
+            } else {
+                return;
+            }
+        }
+
+        self.capture[self.captured] = sample;
+        self.captured += 1;
+        if self.captured == self.capture.len() {
+            self.captured = 0;
+            self.publish();
+            self.armed = self.trigger == ScopeTrigger::RisingZero;
+        }
+    }
+}
+
+impl<C: ChannelConfig> FrameProcessor<C> for ScopeTap<C> {
+    fn process(&mut self, buffer: &mut [f32], _sample_index: u64) {
+        let channels = C::num_channels();
+        let gain = 1.0 / channels as f32;
+        for frame in buffer.chunks_exact(channels) {
+            let sample = match self.channel {
+                Some(channel) => frame[channel],
+                None => frame.iter().sum::<f32>() * gain,
+            };
+            self.push(sample);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.captured = 0;
+        self.waited = 0;
+        self.last = 0.0;
+        self.armed = self.trigger == ScopeTrigger::RisingZero;
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "ScopeTap"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::{Mono, Stereo};
+    use core::f32::consts::PI;
+
+    #[test]
+    fn test_captures() {
+        // Free running: consecutive windows of a ramp, right channel only.
+        let mut tap = ScopeTap::<Stereo>::new(100).with_channel(1);
+        let handle = tap.handle();
+        let mut buffer: Vec<f32> = (0..500).map(|i| (i / 2) as f32).collect();
+        tap.process(&mut buffer, 0);
+        assert_eq!(handle.frames(), 2);
+        assert_eq!(handle.samples()[0], 100.0);
+
+        // Triggered: every capture of a sine starts at the same phase.
+        let mut tap = ScopeTap::<Mono>::new(64).with_trigger(ScopeTrigger::RisingZero);
+        let handle = tap.handle();
+        let mut buffer: Vec<f32> = (0..4000)
+            .map(|i| libm::sinf(2.0 * PI * (i as f32 + 0.3) / 73.0))
+            .collect();
+        let mut starts = Vec::new();
+        for block in buffer.chunks_mut(50) {
+            let frames = handle.frames();
+            tap.process(block, 0);
+            if handle.frames() != frames {
+                starts.push(handle.samples()[0]);
+            }
+        }
+        assert!(starts.len() > 10);
+        assert!(starts
+            .iter()
+            .all(|&s| s > 0.0 && (s - starts[0]).abs() < 1e-3));
+    }
+}
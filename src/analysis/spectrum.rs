@@ -0,0 +1,274 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::error::DspError;
+use crate::core::ola::{FftHelper, OlaWindow};
+use crate::FrameProcessor;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicU32, Ordering};
+use num_complex::{Complex32, ComplexFloat};
+
+struct SnapshotState {
+    bins: Vec<AtomicU32>,
+    sample_rate: AtomicU32,
+    frames: AtomicU32,
+}
+
+/// The latest spectrum of a [`SpectrumAnalyzer`], readable from any thread.
+///
+/// Holds the magnitude of each bin from DC to Nyquist, scaled so that a full-scale sine
+/// reads 1.0. Publishing never allocates and never blocks; a read that overlaps a write may
+/// mix two consecutive frames, which is harmless for a display.
+///
+/// Clones share the same buffer.
+#[derive(Clone)]
+pub struct SpectrumSnapshot {
+    state: Arc<SnapshotState>,
+}
+
+impl SpectrumSnapshot {
+    fn new(bins: usize) -> Self {
+        SpectrumSnapshot {
+            state: Arc::new(SnapshotState {
+                bins: (0..bins).map(|_| AtomicU32::new(0)).collect(),
+                sample_rate: AtomicU32::new(44100.0f32.to_bits()),
+                frames: AtomicU32::new(0),
+            }),
+        }
+    }
+
+    /// Returns the number of bins.
+    pub fn len(&self) -> usize {
+        self.state.bins.len()
+    }
+
+    /// Returns true if the snapshot has no bins.
+    pub fn is_empty(&self) -> bool {
+        self.state.bins.is_empty()
+    }
+
+    /// Returns how many spectra have been published so far. Poll it to detect new frames.
+    pub fn frames(&self) -> u32 {
+        self.state.frames.load(Ordering::Acquire)
+    }
+
+    /// Returns the center frequency of bin `bin` in Hz.
+    pub fn frequency(&self, bin: usize) -> f32 {
+        let sample_rate = f32::from_bits(self.state.sample_rate.load(Ordering::Relaxed));
+        bin as f32 * sample_rate / (2 * (self.len() - 1)) as f32
+    }
+
+    /// Copies the magnitudes into `out` (up to its length).
+    pub fn read(&self, out: &mut [f32]) {
+        for (value, bin) in out.iter_mut().zip(self.state.bins.iter()) {
+            *value = f32::from_bits(bin.load(Ordering::Relaxed));
+        }
+    }
+
+    /// Returns the magnitudes.
+    pub fn magnitudes(&self) -> Vec<f32> {
+        let mut out = vec![0.0; self.len()];
+        self.read(&mut out);
+        out
+    }
+
+    /// Returns the magnitudes in dB (relative to a full-scale sine, floored at -140 dB).
+    pub fn magnitudes_db(&self) -> Vec<f32> {
+        self.magnitudes()
+            .iter()
+            .map(|m| 20.0 * libm::log10f(m.max(1e-7)))
+            .collect()
+    }
+
+    fn publish(&self, magnitudes: &[f32]) {
+        for (bin, &m) in self.state.bins.iter().zip(magnitudes.iter()) {
+            bin.store(m.to_bits(), Ordering::Relaxed);
+        }
+        self.state.frames.fetch_add(1, Ordering::Release);
+    }
+}
+
+/// A spectrum analyzer tap.
+///
+/// The audio passes through unchanged. The channels are mixed to mono, and every hop
+/// (default `N / 2` samples) the most recent `N` samples are windowed, transformed and
+/// published to a [`SpectrumSnapshot`] that a UI thread can poll. `averaging` smooths the
+/// magnitudes over time: 0.0 shows each frame as is, values towards 1.0 average longer.
+pub struct SpectrumAnalyzer<C: ChannelConfig, const N: usize>
+where
+    [Complex32; N]: FftHelper,
+{
+    averaging: f32,
+    hop_size: usize,
+
+    window: Vec<f32>,
+    scale: f32,
+    history: Vec<f32>,
+    write_pos: usize,
+    since_frame: usize,
+    fft: [Complex32; N],
+    magnitudes: Vec<f32>,
+
+    snapshot: SpectrumSnapshot,
+    _channels: PhantomData<C>,
+}
+
+impl<C: ChannelConfig, const N: usize> SpectrumAnalyzer<C, N>
+where
+    [Complex32; N]: FftHelper,
+{
+    /// Creates a new SpectrumAnalyzer with a Hann window.
+    ///
+    /// # Arguments
+    /// * `averaging` - Temporal smoothing of the magnitudes (0.0 - 1.0).
+    pub fn new(averaging: f32) -> Self {
+        let mut analyzer = SpectrumAnalyzer {
+            averaging: averaging.clamp(0.0, 0.999),
+            hop_size: N / 2,
+            window: vec![0.0; N],
+            scale: 0.0,
+            history: vec![0.0; N],
+            write_pos: 0,
+            since_frame: 0,
+            fft: [Complex32::new(0.0, 0.0); N],
+            magnitudes: vec![0.0; N / 2 + 1],
+            snapshot: SpectrumSnapshot::new(N / 2 + 1),
+            _channels: PhantomData,
+        };
+        analyzer.set_window(OlaWindow::Hann);
+        analyzer
+    }
+
+    /// Sets the analysis window and returns the analyzer.
+    pub fn with_window(mut self, window: OlaWindow) -> Self {
+        self.set_window(window);
+        self
+    }
+
+    /// Sets the number of samples between frames (1 - `N`) and returns the analyzer.
+    pub fn with_hop_size(self, hop_size: usize) -> Self {
+        self.try_with_hop_size(hop_size)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Sets the hop size, or returns an error if it is not within 1 - `N`.
+    pub fn try_with_hop_size(mut self, hop_size: usize) -> Result<Self, DspError> {
+        DspError::check_count("SpectrumAnalyzer", "hop_size", hop_size, 1, N)?;
+        self.hop_size = hop_size;
+        Ok(self)
+    }
+
+    /// Sets the averaging amount (0.0 - 1.0).
+    pub fn set_averaging(&mut self, averaging: f32) {
+        self.averaging = averaging.clamp(0.0, 0.999);
+    }
+
+    /// Returns a handle to the published spectrum.
+    pub fn snapshot(&self) -> SpectrumSnapshot {
+        self.snapshot.clone()
+    }
+
+    fn set_window(&mut self, window: OlaWindow) {
+        window.fill(&mut self.window);
+        // A sine's energy splits between its positive and negative frequency bins.
+        self.scale = 2.0 / self.window.iter().sum::<f32>();
+    }
+
+    fn analyze(&mut self) {
+        let (older, newer) = self.history.split_at(self.write_pos);
+        for (bin, (&x, &w)) in self
+            .fft
+            .iter_mut()
+            .zip(newer.iter().chain(older.iter()).zip(self.window.iter()))
+        {
+            *bin = Complex32::new(x * w, 0.0);
+        }
+        self.fft.do_fft();
+
+        let keep = self.averaging;
+        for (m, bin) in self.magnitudes.iter_mut().zip(self.fft.iter()) {
+            let magnitude = bin.abs() * self.scale;
+            *m = magnitude + (*m - magnitude) * keep;
+        }
+        self.snapshot.publish(&self.magnitudes);
+    }
+}
+
+impl<C: ChannelConfig, const N: usize> FrameProcessor<C> for SpectrumAnalyzer<C, N>
+where
+    [Complex32; N]: FftHelper,
+{
+    fn process(&mut self, buffer: &mut [f32], _sample_index: u64) {
+        let channels = C::num_channels();
+        let gain = 1.0 / channels as f32;
+        for frame in buffer.chunks_exact(channels) {
+            self.history[self.write_pos] = frame.iter().sum::<f32>() * gain;
+            self.write_pos += 1;
+            if self.write_pos >= N {
+                self.write_pos = 0;
+            }
+
+            self.since_frame += 1;
+            if self.since_frame >= self.hop_size {
+                self.since_frame = 0;
+                self.analyze();
+            }
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.snapshot
+            .state
+            .sample_rate
+            .store(sample_rate.to_bits(), Ordering::Relaxed);
+    }
+
+    fn reset(&mut self) {
+        self.history.fill(0.0);
+        self.write_pos = 0;
+        self.since_frame = 0;
+        self.magnitudes.fill(0.0);
+        self.snapshot.publish(&self.magnitudes);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "SpectrumAnalyzer"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::Stereo;
+    use core::f32::consts::PI;
+
+    #[test]
+    fn test_sine_peak() {
+        let mut analyzer = SpectrumAnalyzer::<Stereo, 1024>::new(0.5);
+        analyzer.set_sample_rate(48000.0);
+        let snapshot = analyzer.snapshot();
+        assert_eq!(snapshot.len(), 513);
+
+        // Exactly on bin 64 (3 kHz), at half scale; the input is left untouched.
+        let mut buffer: Vec<f32> = (0..2 * 8192)
+            .map(|i| 0.5 * libm::sinf(2.0 * PI * 3000.0 * (i / 2) as f32 / 48000.0))
+            .collect();
+        let input = buffer.clone();
+        for block in buffer.chunks_mut(256) {
+            analyzer.process(block, 0);
+        }
+        assert_eq!(buffer, input);
+        assert_eq!(snapshot.frames(), 16);
+
+        let magnitudes = snapshot.magnitudes();
+        let peak = (0..magnitudes.len())
+            .max_by(|&a, &b| magnitudes[a].total_cmp(&magnitudes[b]))
+            .unwrap();
+        assert_eq!(peak, 64);
+        assert!((snapshot.frequency(peak) - 3000.0).abs() < 1e-3);
+        assert!((magnitudes[peak] - 0.5).abs() < 0.01);
+        assert!(snapshot.magnitudes_db()[200] < -60.0);
+    }
+}
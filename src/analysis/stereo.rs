@@ -0,0 +1,148 @@
+use crate::core::channels::Stereo;
+use crate::core::parameter::Parameter;
+use crate::FrameProcessor;
+
+/// Mean power below which the meter treats the signal as silent.
+const SILENCE: f32 = 1e-10;
+
+/// A stereo correlation and balance meter.
+///
+/// The audio passes through unchanged. The products of the two channels are averaged
+/// exponentially over the integration time and published after each block:
+///
+/// * `correlation` - +1.0 for mono (identical channels), 0.0 for unrelated channels and
+///   -1.0 for channels in opposite phase, which cancel when summed to mono. Wideners and
+///   M/S processing push it down; values that stay below zero are a mono compatibility
+///   problem. Reads 0.0 in silence.
+/// * `balance` - -1.0 (all left) to 1.0 (all right), from the channel powers.
+pub struct CorrelationMeter {
+    integration_time: f32,
+    sample_rate: f32,
+    coeff: f32,
+
+    left_power: f32,
+    right_power: f32,
+    cross: f32,
+
+    correlation: Parameter,
+    balance: Parameter,
+}
+
+impl CorrelationMeter {
+    /// Creates a new CorrelationMeter.
+    ///
+    /// # Arguments
+    /// * `integration_time` - Averaging time constant in seconds (e.g. 0.3).
+    pub fn new(integration_time: f32) -> Self {
+        let mut meter = CorrelationMeter {
+            integration_time: integration_time.max(1e-4),
+            sample_rate: 44100.0,
+            coeff: 0.0,
+            left_power: 0.0,
+            right_power: 0.0,
+            cross: 0.0,
+            correlation: Parameter::new(0.0),
+            balance: Parameter::new(0.0),
+        };
+        meter.recalc();
+        meter
+    }
+
+    /// Returns a handle to the correlation (-1.0 - 1.0).
+    pub fn correlation(&self) -> Parameter {
+        self.correlation.clone()
+    }
+
+    /// Returns a handle to the balance (-1.0 = left, 1.0 = right).
+    pub fn balance(&self) -> Parameter {
+        self.balance.clone()
+    }
+
+    fn recalc(&mut self) {
+        self.coeff = libm::expf(-1.0 / (self.integration_time * self.sample_rate));
+    }
+}
+
+impl FrameProcessor<Stereo> for CorrelationMeter {
+    fn process(&mut self, buffer: &mut [f32], _sample_index: u64) {
+        let keep = self.coeff;
+        let take = 1.0 - keep;
+        for frame in buffer.chunks_exact(2) {
+            let (l, r) = (frame[0], frame[1]);
+            self.left_power = keep * self.left_power + take * l * l;
+            self.right_power = keep * self.right_power + take * r * r;
+            self.cross = keep * self.cross + take * l * r;
+        }
+
+        let total = self.left_power + self.right_power;
+        let product = self.left_power * self.right_power;
+        let correlation = if product > SILENCE * SILENCE {
+            (self.cross / libm::sqrtf(product)).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+        let balance = if total > SILENCE {
+            (self.right_power - self.left_power) / total
+        } else {
+            0.0
+        };
+        self.correlation.set(correlation);
+        self.balance.set(balance);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.recalc();
+    }
+
+    fn reset(&mut self) {
+        self.left_power = 0.0;
+        self.right_power = 0.0;
+        self.cross = 0.0;
+        self.correlation.set(0.0);
+        self.balance.set(0.0);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "CorrelationMeter"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use core::f32::consts::PI;
+
+    fn measure(left: impl Fn(f32) -> f32, right: impl Fn(f32) -> f32) -> (f32, f32) {
+        let mut meter = CorrelationMeter::new(0.05);
+        let mut buffer: Vec<f32> = (0..8192)
+            .flat_map(|i| {
+                let phase = 2.0 * PI * 440.0 * i as f32 / 44100.0;
+                [left(phase), right(phase)]
+            })
+            .collect();
+        for block in buffer.chunks_mut(256) {
+            meter.process(block, 0);
+        }
+        (meter.correlation().get(), meter.balance().get())
+    }
+
+    #[test]
+    fn test_correlation_and_balance() {
+        let (mono, centred) = measure(libm::sinf, libm::sinf);
+        assert!((mono - 1.0).abs() < 1e-4 && centred.abs() < 1e-4);
+
+        let (inverted, _) = measure(libm::sinf, |p| -libm::sinf(p));
+        assert!((inverted + 1.0).abs() < 1e-4);
+
+        // A quarter period apart: uncorrelated. Louder right: positive balance.
+        let (quadrature, right) = measure(libm::sinf, |p| 2.0 * libm::cosf(p));
+        assert!(quadrature.abs() < 0.05, "{}", quadrature);
+        assert!((right - 0.6).abs() < 0.01);
+
+        let (silent, _) = measure(|_| 0.0, |_| 0.0);
+        assert_eq!(silent, 0.0);
+    }
+}
@@ -0,0 +1,267 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::parameter::Parameter;
+use crate::FrameProcessor;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Impulse responses of a processor captured while one of its parameters is swept.
+///
+/// Row `i` holds the response (`ir_length` samples) measured with the parameter at
+/// `values[i]`, so the matrix can be drawn directly as a waterfall plot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waterfall {
+    pub values: Vec<f32>,
+    pub ir_length: usize,
+    pub responses: Vec<f32>,
+}
+
+impl Waterfall {
+    /// Returns the number of captured responses.
+    pub fn rows(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the impulse response of row `row`.
+    pub fn response(&self, row: usize) -> &[f32] {
+        &self.responses[row * self.ir_length..(row + 1) * self.ir_length]
+    }
+
+    /// Returns the energy of each response in dB (relative to a unit impulse).
+    pub fn energy_db(&self) -> Vec<f32> {
+        (0..self.rows())
+            .map(|row| {
+                let energy: f32 = self.response(row).iter().map(|s| s * s).sum();
+                10.0 * libm::log10f(energy.max(1e-12))
+            })
+            .collect()
+    }
+
+    /// Dumps the matrix as CSV: one line per response, the parameter value first.
+    pub fn to_csv(&self) -> String {
+        use core::fmt::Write;
+
+        let mut output = String::new();
+        for (row, value) in self.values.iter().enumerate() {
+            let _ = write!(output, "{}", value);
+            for sample in self.response(row) {
+                let _ = write!(output, ",{}", sample);
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// Captures a [`Waterfall`] by feeding periodic impulses through a processor.
+///
+/// Before each impulse the swept Parameter is moved to the next value; the processor then
+/// runs for `ir_length` samples and its output is recorded. By default the processor is not
+/// reset between impulses, so time-varying behaviour (modulation, parameter smoothing,
+/// state carried over from the previous setting) shows up in the matrix exactly as it
+/// would in a live sweep.
+pub struct WaterfallCapture {
+    ir_length: usize,
+    steps: usize,
+    start: f32,
+    end: f32,
+    logarithmic: bool,
+    reset_between: bool,
+    settle: usize,
+    block_size: usize,
+    input_channel: usize,
+    output_channel: usize,
+}
+
+impl WaterfallCapture {
+    /// Creates a new WaterfallCapture.
+    ///
+    /// # Arguments
+    /// * `ir_length` - Samples recorded per impulse (and time between impulses).
+    /// * `steps` - Number of parameter values, i.e. rows in the matrix.
+    /// * `start` - First parameter value.
+    /// * `end` - Last parameter value.
+    pub fn new(ir_length: usize, steps: usize, start: f32, end: f32) -> Self {
+        assert!(
+            ir_length > 0 && steps > 0,
+            "WaterfallCapture: Length and steps must be at least one."
+        );
+        WaterfallCapture {
+            ir_length,
+            steps,
+            start,
+            end,
+            logarithmic: false,
+            reset_between: false,
+            settle: 0,
+            block_size: 64,
+            input_channel: 0,
+            output_channel: 0,
+        }
+    }
+
+    /// Spaces the values logarithmically (e.g. for frequencies). Both ends must be positive.
+    pub fn with_logarithmic(mut self, logarithmic: bool) -> Self {
+        assert!(
+            !logarithmic || (self.start > 0.0 && self.end > 0.0),
+            "WaterfallCapture: Logarithmic sweeps need positive values."
+        );
+        self.logarithmic = logarithmic;
+        self
+    }
+
+    /// Resets the processor before each impulse, for time-invariant responses.
+    pub fn with_reset(mut self, reset: bool) -> Self {
+        self.reset_between = reset;
+        self
+    }
+
+    /// Runs the processor on silence for `samples` after each parameter change, so
+    /// smoothed parameters reach their value before the impulse.
+    pub fn with_settle(mut self, samples: usize) -> Self {
+        self.settle = samples;
+        self
+    }
+
+    /// Sets the block size used to drive the processor (default 64).
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        assert!(
+            block_size > 0,
+            "WaterfallCapture: Block size must be at least one frame."
+        );
+        self.block_size = block_size;
+        self
+    }
+
+    /// Selects the channel the impulse is fed into and the channel recorded (default 0, 0).
+    pub fn with_channels(mut self, input: usize, output: usize) -> Self {
+        self.input_channel = input;
+        self.output_channel = output;
+        self
+    }
+
+    /// Returns the parameter value of each row.
+    pub fn values(&self) -> Vec<f32> {
+        (0..self.steps)
+            .map(|i| {
+                let t = if self.steps > 1 {
+                    i as f32 / (self.steps - 1) as f32
+                } else {
+                    0.0
+                };
+                if self.logarithmic {
+                    self.start * libm::powf(self.end / self.start, t)
+                } else {
+                    self.start + (self.end - self.start) * t
+                }
+            })
+            .collect()
+    }
+
+    /// Runs the capture.
+    ///
+    /// # Arguments
+    /// * `processor` - The processor under test, with the swept parameter linked to `parameter`.
+    /// * `parameter` - The Parameter to sweep. It is left at the last value.
+    pub fn capture<C, P>(&self, processor: &mut P, parameter: &Parameter) -> Waterfall
+    where
+        C: ChannelConfig,
+        P: FrameProcessor<C> + ?Sized,
+    {
+        let channels = C::num_channels();
+        assert!(
+            self.input_channel < channels && self.output_channel < channels,
+            "WaterfallCapture: Channel out of range."
+        );
+
+        let values = self.values();
+        let mut responses = Vec::with_capacity(self.steps * self.ir_length);
+        let mut settle = vec![0.0; self.settle * channels];
+        let mut buffer = vec![0.0; self.ir_length * channels];
+        let mut sample_index = 0u64;
+
+        for &value in &values {
+            parameter.set(value);
+            if self.reset_between {
+                processor.reset();
+            }
+
+            settle.fill(0.0);
+            sample_index = self.run(processor, &mut settle, sample_index);
+
+            buffer.fill(0.0);
+            buffer[self.input_channel] = 1.0;
+            sample_index = self.run(processor, &mut buffer, sample_index);
+
+            responses.extend(
+                buffer
+                    .iter()
+                    .skip(self.output_channel)
+                    .step_by(channels)
+                    .copied(),
+            );
+        }
+
+        Waterfall {
+            values,
+            ir_length: self.ir_length,
+            responses,
+        }
+    }
+
+    fn run<C, P>(&self, processor: &mut P, buffer: &mut [f32], mut sample_index: u64) -> u64
+    where
+        C: ChannelConfig,
+        P: FrameProcessor<C> + ?Sized,
+    {
+        let channels = C::num_channels();
+        for block in buffer.chunks_mut(self.block_size * channels) {
+            processor.process(block, sample_index);
+            sample_index += (block.len() / channels) as u64;
+        }
+        sample_index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+    use crate::core::channels::Mono;
+    use crate::effects::filter::state_variable::{StateVariableFilter, SvfType};
+    use crate::effects::utility::gain::Gain;
+
+    #[test]
+    fn test_gain_sweep_rows() {
+        let level = Parameter::new(0.0);
+        let mut gain = Gain::new(AudioParam::Linked(level.clone()));
+        let waterfall = WaterfallCapture::new(8, 3, 0.0, 1.0).capture::<Mono, _>(&mut gain, &level);
+
+        assert_eq!(waterfall.values, [0.0, 0.5, 1.0]);
+        assert_eq!(
+            waterfall.response(1),
+            [0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]
+        );
+        assert_eq!(waterfall.response(2)[0], 1.0);
+        assert!(waterfall.to_csv().starts_with("0,0,0"));
+    }
+
+    #[test]
+    fn test_lowpass_cutoff_sweep() {
+        let cutoff = Parameter::new(100.0);
+        let mut filter = StateVariableFilter::new(
+            SvfType::LowPass,
+            AudioParam::Linked(cutoff.clone()),
+            AudioParam::Static(0.707),
+        );
+        let waterfall = WaterfallCapture::new(512, 4, 200.0, 8000.0)
+            .with_logarithmic(true)
+            .with_reset(true)
+            .capture::<Mono, _>(&mut filter, &cutoff);
+
+        // A wider passband lets more of the impulse's energy through.
+        let energy = waterfall.energy_db();
+        assert!(energy.windows(2).all(|w| w[1] > w[0]));
+        assert_eq!(cutoff.get(), waterfall.values[3]);
+    }
+}
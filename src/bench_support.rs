@@ -0,0 +1,233 @@
+use crate::core::audio_param::AudioParam;
+use crate::effects::time::delay::read_linear;
+use crate::effects::time::reverb::{Allpass, Comb4};
+use crate::synthesis::oscillator::{Oscillator, Waveform};
+use crate::FrameProcessor;
+use alloc::vec;
+use alloc::vec::Vec;
+use wide::f32x4;
+
+/// Returns the SIMD instruction set the `wide` vectors were compiled for.
+///
+/// One of `"avx2"`, `"sse2"`, `"neon"`, `"simd128"` or `"scalar"`. Targets reporting
+/// `"scalar"` run the SIMD kernels as plain loops, so they should benchmark close to the
+/// `_scalar` variants.
+pub fn simd_backend() -> &'static str {
+    if cfg!(target_feature = "avx2") {
+        "avx2"
+    } else if cfg!(target_feature = "sse2") {
+        "sse2"
+    } else if cfg!(target_feature = "neon") {
+        "neon"
+    } else if cfg!(target_feature = "simd128") {
+        "simd128"
+    } else {
+        "scalar"
+    }
+}
+
+/// The four parallel comb filters of the Reverb tank.
+///
+/// `process_block` runs the library's `f32x4` path, `process_block_scalar` the same
+/// filters one lane at a time. The two paths keep separate state, so feeding both the
+/// same input gives the same output (up to rounding).
+pub struct CombKernel {
+    simd: Comb4,
+    buffers: [Vec<f32>; 4],
+    pos: [usize; 4],
+    feedback: f32,
+    damp: f32,
+    state: [f32; 4],
+}
+
+impl CombKernel {
+    /// Creates a new CombKernel.
+    ///
+    /// # Arguments
+    /// * `sizes` - Delay lengths of the four combs in samples.
+    /// * `feedback` - Feedback amount (0.0 - 1.0).
+    /// * `damp` - High frequency damping (0.0 - 1.0).
+    pub fn new(sizes: [usize; 4], feedback: f32, damp: f32) -> Self {
+        CombKernel {
+            simd: Comb4::new(sizes, feedback, damp),
+            buffers: sizes.map(|size| vec![0.0; size]),
+            pos: [0; 4],
+            feedback,
+            damp,
+            state: [0.0; 4],
+        }
+    }
+
+    /// Processes a block in place with the SIMD comb bank.
+    pub fn process_block(&mut self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.simd.process(*sample);
+        }
+    }
+
+    /// Processes a block in place with the scalar reference.
+    pub fn process_block_scalar(&mut self, buffer: &mut [f32]) {
+        let damp_inv = 1.0 - self.damp;
+        for sample in buffer.iter_mut() {
+            let input = *sample;
+            let mut output = 0.0;
+            for lane in 0..4 {
+                let delayed = self.buffers[lane][self.pos[lane]];
+                self.buffers[lane][self.pos[lane]] = input + self.state[lane] * self.feedback;
+                self.state[lane] = delayed * damp_inv + self.state[lane] * self.damp;
+                output += delayed;
+
+                self.pos[lane] += 1;
+                if self.pos[lane] >= self.buffers[lane].len() {
+                    self.pos[lane] = 0;
+                }
+            }
+            *sample = output;
+        }
+    }
+}
+
+/// A series of allpass diffusers, as used after the Reverb combs.
+pub struct AllpassKernel {
+    stages: Vec<Allpass>,
+}
+
+impl AllpassKernel {
+    /// Creates a new AllpassKernel.
+    ///
+    /// # Arguments
+    /// * `sizes` - Delay length of each stage in samples.
+    pub fn new(sizes: &[usize]) -> Self {
+        AllpassKernel {
+            stages: sizes.iter().map(|&size| Allpass::new(size)).collect(),
+        }
+    }
+
+    /// Processes a block in place through all stages.
+    pub fn process_block(&mut self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            let mut value = *sample;
+            for stage in &mut self.stages {
+                value = stage.process(value);
+            }
+            *sample = value;
+        }
+    }
+}
+
+/// Reads `buffer` at fractional `positions` with the linear interpolation used by Delay.
+///
+/// Positions must lie in `0.0..buffer.len()`.
+pub fn delay_interpolation(buffer: &[f32], positions: &[f32], output: &mut [f32]) {
+    for (out, &position) in output.iter_mut().zip(positions.iter()) {
+        *out = read_linear(buffer, position);
+    }
+}
+
+/// Same as [`delay_interpolation`], with the blend computed four reads at a time.
+pub fn delay_interpolation_x4(buffer: &[f32], positions: &[f32], output: &mut [f32]) {
+    let len = buffer.len();
+    let frames = output.len().min(positions.len());
+    let (out_chunks, out_rem) = output[0..frames].as_chunks_mut::<4>();
+    let (pos_chunks, pos_rem) = positions[0..frames].as_chunks::<4>();
+
+    for (out, pos) in out_chunks.iter_mut().zip(pos_chunks.iter()) {
+        let mut a = [0.0; 4];
+        let mut b = [0.0; 4];
+        let mut frac = [0.0; 4];
+        for lane in 0..4 {
+            let idx_a = pos[lane] as usize;
+            let mut idx_b = idx_a + 1;
+            if idx_b >= len {
+                idx_b -= len;
+            }
+            a[lane] = buffer[idx_a];
+            b[lane] = buffer[idx_b];
+            frac[lane] = pos[lane] - idx_a as f32;
+        }
+        let frac = f32x4::from(frac);
+        let blended = f32x4::from(a) * (f32x4::ONE - frac) + f32x4::from(b) * frac;
+        *out = blended.to_array();
+    }
+
+    delay_interpolation(buffer, pos_rem, out_rem);
+}
+
+/// An Oscillator at a fixed frequency.
+///
+/// `fill` runs the block `process` path (vectorised phase increments), `fill_scalar`
+/// the per-sample `tick` path. Both advance the same phase.
+pub struct OscillatorKernel {
+    osc: Oscillator,
+    frequency: f32,
+}
+
+impl OscillatorKernel {
+    /// Creates a new OscillatorKernel.
+    ///
+    /// # Arguments
+    /// * `waveform` - The waveform to render.
+    /// * `frequency` - Frequency in Hz.
+    /// * `sample_rate` - Sample rate in Hz.
+    pub fn new(waveform: Waveform, frequency: f32, sample_rate: f32) -> Self {
+        let mut osc = Oscillator::new(AudioParam::Static(frequency), waveform);
+        osc.set_sample_rate(sample_rate);
+        OscillatorKernel { osc, frequency }
+    }
+
+    /// Fills the buffer through the block path.
+    pub fn fill(&mut self, buffer: &mut [f32]) {
+        self.osc.process(buffer, 0);
+    }
+
+    /// Fills the buffer one sample at a time.
+    pub fn fill_scalar(&mut self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = self.osc.tick(self.frequency);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn impulse(len: usize) -> Vec<f32> {
+        let mut buffer = vec![0.0; len];
+        buffer[0] = 1.0;
+        buffer
+    }
+
+    #[test]
+    fn test_simd_and_scalar_paths_match() {
+        let mut comb = CombKernel::new([31, 37, 41, 43], 0.8, 0.3);
+        let mut simd = impulse(512);
+        let mut scalar = simd.clone();
+        comb.process_block(&mut simd);
+        comb.process_block_scalar(&mut scalar);
+        assert!(simd.iter().any(|s| s.abs() > 0.1));
+        for (a, b) in simd.iter().zip(scalar.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+
+        let buffer: Vec<f32> = (0..64).map(|i| libm::sinf(i as f32 * 0.3)).collect();
+        let positions: Vec<f32> = (0..37).map(|i| (i as f32 * 1.7) % 64.0).collect();
+        let mut a = vec![0.0; 37];
+        let mut b = vec![0.0; 37];
+        delay_interpolation(&buffer, &positions, &mut a);
+        delay_interpolation_x4(&buffer, &positions, &mut b);
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < 1e-6);
+        }
+
+        let mut block = OscillatorKernel::new(Waveform::Saw, 440.0, 44100.0);
+        let mut tick = OscillatorKernel::new(Waveform::Saw, 440.0, 44100.0);
+        let mut a = vec![0.0; 130];
+        let mut b = vec![0.0; 130];
+        block.fill(&mut a);
+        tick.fill_scalar(&mut b);
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,22 @@
+/// A MIDI channel message. Channels are 0 - 15.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiMessage {
+    /// Releases a note. A note on with velocity 0 is read as a note off.
+    NoteOff { channel: u8, key: u8, velocity: u8 },
+    /// Starts a note.
+    NoteOn { channel: u8, key: u8, velocity: u8 },
+    /// Pressure on one held key.
+    PolyPressure { channel: u8, key: u8, pressure: u8 },
+    /// A controller change.
+    ControlChange {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+    /// A program change.
+    ProgramChange { channel: u8, program: u8 },
+    /// Pressure on the whole channel.
+    ChannelPressure { channel: u8, pressure: u8 },
+    /// Pitch bend (-8192 - 8191, 0 = centre).
+    PitchBend { channel: u8, value: i16 },
+}
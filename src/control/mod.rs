@@ -0,0 +1,8 @@
+pub mod midi;
+pub mod mpe;
+#[cfg(feature = "osc")]
+pub mod osc;
+#[cfg(feature = "plugin")]
+pub mod plugin;
+#[cfg(feature = "smf")]
+pub mod smf;
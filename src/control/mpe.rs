@@ -0,0 +1,457 @@
+use crate::control::midi::MidiMessage;
+use crate::core::parameter::Parameter;
+use crate::synthesis::tuning::TuningTable;
+use alloc::vec::Vec;
+
+/// MIDI controller that carries the MPE timbre dimension.
+const TIMBRE_CC: u8 = 74;
+
+/// An MPE zone: a master channel plus a range of member channels the controller rotates
+/// notes across.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpeZone {
+    /// Master channel 1 (index 0), members from channel 2 upwards.
+    Lower { members: u8 },
+    /// Master channel 16 (index 15), members from channel 15 downwards.
+    Upper { members: u8 },
+}
+
+impl MpeZone {
+    /// Returns the channel index of the zone's master channel.
+    pub fn master_channel(&self) -> u8 {
+        match self {
+            MpeZone::Lower { .. } => 0,
+            MpeZone::Upper { .. } => 15,
+        }
+    }
+
+    /// Returns true if the channel index is a member channel of the zone.
+    pub fn is_member(&self, channel: u8) -> bool {
+        match *self {
+            MpeZone::Lower { members } => channel >= 1 && channel <= members.min(15),
+            MpeZone::Upper { members } => channel <= 14 && channel >= 15 - members.min(15),
+        }
+    }
+}
+
+impl Default for MpeZone {
+    fn default() -> Self {
+        MpeZone::Lower { members: 15 }
+    }
+}
+
+/// The parameters of one voice, for linking into the voice's processors.
+///
+/// All values are updated from the audio thread by [`MpeVoices::handle`].
+#[derive(Clone)]
+pub struct MpeVoice {
+    /// 1.0 while the voice's note is held.
+    pub gate: Parameter,
+    /// Note frequency in Hz including pitch bend (A4 = 440 Hz).
+    pub frequency: Parameter,
+    /// Note-on velocity (0.0 - 1.0).
+    pub velocity: Parameter,
+    /// Channel or key pressure (0.0 - 1.0).
+    pub pressure: Parameter,
+    /// Timbre from CC 74 (0.0 - 1.0, 0.5 = centre).
+    pub timbre: Parameter,
+}
+
+impl MpeVoice {
+    fn new() -> Self {
+        MpeVoice {
+            gate: Parameter::new(0.0),
+            frequency: Parameter::new(440.0),
+            velocity: Parameter::new(0.0),
+            pressure: Parameter::new(0.0),
+            timbre: Parameter::new(0.5),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ChannelState {
+    /// Pitch bend (-1.0 - 1.0).
+    bend: f32,
+    pressure: f32,
+    timbre: f32,
+    /// Selected RPN (MSB, LSB), for pitch bend range and zone configuration.
+    rpn: (u8, u8),
+}
+
+impl ChannelState {
+    const INITIAL: ChannelState = ChannelState {
+        bend: 0.0,
+        pressure: 0.0,
+        timbre: 0.5,
+        rpn: (127, 127),
+    };
+}
+
+struct VoiceSlot {
+    voice: MpeVoice,
+    channel: u8,
+    key: u8,
+    held: bool,
+    /// Note-on or note-off order, to pick the voice released longest ago or the oldest note.
+    age: u64,
+}
+
+/// A polyphonic voice allocator with MIDI Polyphonic Expression.
+///
+/// Every note gets a voice bound to the note's channel, so pitch bend, channel pressure and
+/// CC 74 on a member channel move only the notes of that channel, however the controller
+/// rotates its notes over the zone. The master channel's pitch bend moves all voices. The
+/// last expression values of a channel are also applied to a new note on it, because MPE
+/// controllers send them just before the note on. Notes go to a free voice (the one
+/// released longest ago); with all voices held, the oldest note is stolen.
+///
+/// The zone and the pitch bend ranges can be set directly or by the controller through the
+/// MPE configuration message (RPN 6) and RPN 0 (pitch bend sensitivity).
+pub struct MpeVoices {
+    slots: Vec<VoiceSlot>,
+    channels: [ChannelState; 16],
+    zone: MpeZone,
+    member_bend_range: f32,
+    master_bend_range: f32,
+    tuning: TuningTable,
+    counter: u64,
+}
+
+impl MpeVoices {
+    /// Creates a new MpeVoices with a lower zone over all channels, a 48 semitone member
+    /// pitch bend range and a 2 semitone master range.
+    ///
+    /// # Arguments
+    /// * `voice_count` - Number of voices.
+    pub fn new(voice_count: usize) -> Self {
+        assert!(
+            voice_count > 0,
+            "MpeVoices: At least one voice is required."
+        );
+        MpeVoices {
+            slots: (0..voice_count)
+                .map(|_| VoiceSlot {
+                    voice: MpeVoice::new(),
+                    channel: 0,
+                    key: 0,
+                    held: false,
+                    age: 0,
+                })
+                .collect(),
+            channels: [ChannelState::INITIAL; 16],
+            zone: MpeZone::default(),
+            member_bend_range: 48.0,
+            master_bend_range: 2.0,
+            tuning: TuningTable::default(),
+            counter: 0,
+        }
+    }
+
+    /// Returns the parameters of each voice.
+    pub fn voices(&self) -> impl Iterator<Item = &MpeVoice> {
+        self.slots.iter().map(|slot| &slot.voice)
+    }
+
+    /// Returns the parameters of one voice.
+    pub fn voice(&self, index: usize) -> Option<&MpeVoice> {
+        self.slots.get(index).map(|slot| &slot.voice)
+    }
+
+    /// Sets the zone.
+    pub fn set_zone(&mut self, zone: MpeZone) {
+        self.zone = zone;
+    }
+
+    /// Returns the zone.
+    pub fn zone(&self) -> MpeZone {
+        self.zone
+    }
+
+    /// Sets the pitch bend ranges.
+    ///
+    /// # Arguments
+    /// * `member_semitones` - Range of the member channels (per note).
+    /// * `master_semitones` - Range of the master channel (all notes).
+    pub fn set_pitch_bend_range(&mut self, member_semitones: f32, master_semitones: f32) {
+        self.member_bend_range = member_semitones;
+        self.master_bend_range = master_semitones;
+    }
+
+    /// Returns the member and master pitch bend ranges in semitones.
+    pub fn pitch_bend_range(&self) -> (f32, f32) {
+        (self.member_bend_range, self.master_bend_range)
+    }
+
+    /// Sets the key to frequency mapping. Pitch bend stays in 12-TET semitones.
+    pub fn set_tuning(&mut self, tuning: TuningTable) {
+        self.tuning = tuning;
+        self.update_all_frequencies();
+    }
+
+    /// Releases all voices and clears the per-channel expression.
+    pub fn reset(&mut self) {
+        for slot in self.slots.iter_mut() {
+            slot.held = false;
+            slot.voice.gate.set(0.0);
+        }
+        self.channels = [ChannelState::INITIAL; 16];
+    }
+
+    /// Applies a MIDI message.
+    pub fn handle(&mut self, message: MidiMessage) {
+        match message {
+            MidiMessage::NoteOn {
+                channel,
+                key,
+                velocity,
+            } if velocity > 0 => self.note_on(channel & 0x0F, key, velocity),
+            MidiMessage::NoteOn { channel, key, .. }
+            | MidiMessage::NoteOff { channel, key, .. } => self.note_off(channel & 0x0F, key),
+            MidiMessage::PitchBend { channel, value } => {
+                let channel = channel & 0x0F;
+                self.channels[channel as usize].bend = (value as f32 / 8192.0).clamp(-1.0, 1.0);
+                self.update_frequencies(channel);
+            }
+            MidiMessage::ChannelPressure { channel, pressure } => {
+                let channel = channel & 0x0F;
+                let value = pressure as f32 / 127.0;
+                self.channels[channel as usize].pressure = value;
+                for slot in self.slots.iter().filter(|s| s.held && s.channel == channel) {
+                    slot.voice.pressure.set(value);
+                }
+            }
+            MidiMessage::PolyPressure {
+                channel,
+                key,
+                pressure,
+            } => {
+                let channel = channel & 0x0F;
+                for slot in self.slots.iter().filter(|s| s.held && s.channel == channel) {
+                    if slot.key == key {
+                        slot.voice.pressure.set(pressure as f32 / 127.0);
+                    }
+                }
+            }
+            MidiMessage::ControlChange {
+                channel,
+                controller,
+                value,
+            } => self.control_change(channel & 0x0F, controller, value),
+            MidiMessage::ProgramChange { .. } => {}
+        }
+    }
+
+    fn note_on(&mut self, channel: u8, key: u8, velocity: u8) {
+        self.counter += 1;
+        let index = self
+            .slots
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, slot)| (slot.held, slot.age))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+
+        let state = self.channels[channel as usize];
+        let frequency = self.frequency(channel, key);
+        let slot = &mut self.slots[index];
+        slot.channel = channel;
+        slot.key = key;
+        slot.held = true;
+        slot.age = self.counter;
+        slot.voice.frequency.set(frequency);
+        slot.voice.velocity.set(velocity as f32 / 127.0);
+        slot.voice.pressure.set(state.pressure);
+        slot.voice.timbre.set(state.timbre);
+        slot.voice.gate.set(1.0);
+    }
+
+    fn note_off(&mut self, channel: u8, key: u8) {
+        self.counter += 1;
+        let counter = self.counter;
+        if let Some(slot) = self
+            .slots
+            .iter_mut()
+            .find(|s| s.held && s.channel == channel && s.key == key)
+        {
+            slot.held = false;
+            slot.age = counter;
+            slot.voice.gate.set(0.0);
+        }
+    }
+
+    fn control_change(&mut self, channel: u8, controller: u8, value: u8) {
+        let state = &mut self.channels[channel as usize];
+        match controller {
+            TIMBRE_CC => {
+                state.timbre = value as f32 / 127.0;
+                let timbre = state.timbre;
+                for slot in self.slots.iter().filter(|s| s.held && s.channel == channel) {
+                    slot.voice.timbre.set(timbre);
+                }
+            }
+            100 => state.rpn.1 = value,
+            101 => state.rpn.0 = value,
+            // Data entry MSB for the selected RPN.
+            6 => match state.rpn {
+                (0, 0) => {
+                    if channel == self.zone.master_channel() {
+                        self.master_bend_range = value as f32;
+                    } else {
+                        self.member_bend_range = value as f32;
+                    }
+                    self.update_all_frequencies();
+                }
+                (0, 6) if channel == 0 || channel == 15 => {
+                    let members = value.min(15);
+                    self.zone = if channel == 0 {
+                        MpeZone::Lower { members }
+                    } else {
+                        MpeZone::Upper { members }
+                    };
+                    // A new zone configuration resets the ranges to the MPE defaults.
+                    self.member_bend_range = 48.0;
+                    self.master_bend_range = 2.0;
+                    self.update_all_frequencies();
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn frequency(&self, channel: u8, key: u8) -> f32 {
+        let master = self.zone.master_channel();
+        let mut semitones = self.channels[master as usize].bend * self.master_bend_range;
+        if channel != master {
+            semitones += self.channels[channel as usize].bend * self.member_bend_range;
+        }
+        self.tuning.frequency(key) * libm::exp2f(semitones / 12.0)
+    }
+
+    fn update_frequencies(&mut self, channel: u8) {
+        if channel == self.zone.master_channel() {
+            self.update_all_frequencies();
+            return;
+        }
+        for slot in self.slots.iter().filter(|s| s.held && s.channel == channel) {
+            slot.voice
+                .frequency
+                .set(self.frequency(slot.channel, slot.key));
+        }
+    }
+
+    fn update_all_frequencies(&mut self) {
+        for slot in self.slots.iter().filter(|s| s.held) {
+            slot.voice
+                .frequency
+                .set(self.frequency(slot.channel, slot.key));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frequency(voices: &MpeVoices, index: usize) -> f32 {
+        voices.voice(index).unwrap().frequency.get()
+    }
+
+    #[test]
+    fn test_per_note_expression() {
+        let mut voices = MpeVoices::new(2);
+        // The controller sends the initial timbre before the note on.
+        voices.handle(MidiMessage::ControlChange {
+            channel: 1,
+            controller: TIMBRE_CC,
+            value: 127,
+        });
+        voices.handle(MidiMessage::NoteOn {
+            channel: 1,
+            key: 69,
+            velocity: 127,
+        });
+        voices.handle(MidiMessage::NoteOn {
+            channel: 2,
+            key: 69,
+            velocity: 64,
+        });
+        assert_eq!(voices.voice(0).unwrap().timbre.get(), 1.0);
+        assert_eq!(voices.voice(1).unwrap().timbre.get(), 0.5);
+
+        // A quarter of the 48 semitone range bends only the first note up an octave.
+        voices.handle(MidiMessage::PitchBend {
+            channel: 1,
+            value: 2048,
+        });
+        voices.handle(MidiMessage::ChannelPressure {
+            channel: 2,
+            pressure: 127,
+        });
+        assert!((frequency(&voices, 0) - 880.0).abs() < 0.1);
+        assert_eq!(frequency(&voices, 1), 440.0);
+        assert_eq!(voices.voice(0).unwrap().pressure.get(), 0.0);
+        assert_eq!(voices.voice(1).unwrap().pressure.get(), 1.0);
+
+        // The master channel moves every note by its own range.
+        voices.handle(MidiMessage::PitchBend {
+            channel: 0,
+            value: 8191,
+        });
+        assert!((frequency(&voices, 1) - 440.0 * libm::exp2f(2.0 / 12.0)).abs() < 0.1);
+
+        voices.handle(MidiMessage::NoteOff {
+            channel: 1,
+            key: 69,
+            velocity: 0,
+        });
+        assert_eq!(voices.voice(0).unwrap().gate.get(), 0.0);
+        assert_eq!(voices.voice(1).unwrap().gate.get(), 1.0);
+    }
+
+    #[test]
+    fn test_rpn_configuration_and_stealing() {
+        let mut voices = MpeVoices::new(1);
+        let rpn = |channel, lsb, value| {
+            [(101, 0), (100, lsb), (6, value)].map(|(controller, value)| {
+                MidiMessage::ControlChange {
+                    channel,
+                    controller,
+                    value,
+                }
+            })
+        };
+        for message in rpn(15, 6, 7) {
+            voices.handle(message);
+        }
+        assert_eq!(voices.zone(), MpeZone::Upper { members: 7 });
+        assert!(voices.zone().is_member(8) && !voices.zone().is_member(7));
+
+        for message in rpn(14, 0, 12) {
+            voices.handle(message);
+        }
+        assert_eq!(voices.pitch_bend_range(), (12.0, 2.0));
+
+        voices.handle(MidiMessage::NoteOn {
+            channel: 14,
+            key: 60,
+            velocity: 100,
+        });
+        voices.handle(MidiMessage::NoteOn {
+            channel: 13,
+            key: 69,
+            velocity: 100,
+        });
+        assert_eq!(frequency(&voices, 0), 440.0);
+        voices.set_tuning(TuningTable::equal(24).with_reference(68, 432.0));
+        assert!((frequency(&voices, 0) - 432.0 * libm::exp2f(1.0 / 24.0)).abs() < 0.01);
+        // The stolen note's release no longer affects the voice.
+        voices.handle(MidiMessage::NoteOff {
+            channel: 14,
+            key: 60,
+            velocity: 0,
+        });
+        assert_eq!(voices.voice(0).unwrap().gate.get(), 1.0);
+    }
+}
@@ -0,0 +1,584 @@
+use crate::core::parameter::Parameter;
+use crate::core::transport::Transport;
+use crate::synthesis::envelope::Trigger;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// OSC timetag meaning "process immediately".
+pub const IMMEDIATELY: u64 = 1;
+
+/// Errors produced while parsing an OSC packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OscError {
+    /// The packet ended in the middle of an element.
+    Truncated,
+    /// A string was not NUL terminated, not padded, or not valid UTF-8.
+    InvalidString,
+    /// The address does not start with `/`.
+    InvalidAddress,
+    /// A bundle element has a negative or oversized length.
+    InvalidBundle,
+    /// The type tag string contains an unsupported tag.
+    UnknownTypeTag(u8),
+}
+
+impl core::fmt::Display for OscError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            OscError::Truncated => write!(f, "OSC packet is truncated"),
+            OscError::InvalidString => write!(f, "OSC string is malformed"),
+            OscError::InvalidAddress => write!(f, "OSC address must start with '/'"),
+            OscError::InvalidBundle => write!(f, "OSC bundle element is malformed"),
+            OscError::UnknownTypeTag(tag) => write!(f, "unknown OSC type tag '{}'", *tag as char),
+        }
+    }
+}
+
+/// A single OSC argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+    String(String),
+    Blob(Vec<u8>),
+    Int64(i64),
+    Double(f64),
+    TimeTag(u64),
+    Bool(bool),
+    Nil,
+    Impulse,
+}
+
+impl OscArg {
+    /// Returns the argument as a control value, if it is numeric or boolean.
+    pub fn as_f32(&self) -> Option<f32> {
+        match *self {
+            OscArg::Int(v) => Some(v as f32),
+            OscArg::Float(v) => Some(v),
+            OscArg::Int64(v) => Some(v as f32),
+            OscArg::Double(v) => Some(v as f32),
+            OscArg::Bool(v) => Some(if v { 1.0 } else { 0.0 }),
+            _ => None,
+        }
+    }
+}
+
+/// An OSC message: an address pattern and its arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    pub args: Vec<OscArg>,
+}
+
+/// An OSC bundle: a timetag and the packets to apply at that time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OscBundle {
+    /// NTP timetag (seconds since 1900 in the upper 32 bits, fraction in the lower 32 bits).
+    pub timetag: u64,
+    pub content: Vec<OscPacket>,
+}
+
+/// A parsed OSC packet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscPacket {
+    Message(OscMessage),
+    Bundle(OscBundle),
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], OscError> {
+        let end = self.pos.checked_add(len).ok_or(OscError::Truncated)?;
+        let bytes = self.data.get(self.pos..end).ok_or(OscError::Truncated)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, OscError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u64(&mut self) -> Result<u64, OscError> {
+        let hi = self.u32()? as u64;
+        let lo = self.u32()? as u64;
+        Ok((hi << 32) | lo)
+    }
+
+    fn string(&mut self) -> Result<&'a str, OscError> {
+        let rest = &self.data[self.pos..];
+        let len = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(OscError::InvalidString)?;
+        let bytes = self.take(padded(len + 1))?;
+        if bytes[len..].iter().any(|&b| b != 0) {
+            return Err(OscError::InvalidString);
+        }
+        core::str::from_utf8(&bytes[..len]).map_err(|_| OscError::InvalidString)
+    }
+
+    fn blob(&mut self) -> Result<&'a [u8], OscError> {
+        let len = self.u32()? as usize;
+        let bytes = self.take(padded(len))?;
+        Ok(&bytes[..len])
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+}
+
+fn padded(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+/// Parses an OSC packet (message or bundle).
+pub fn parse(data: &[u8]) -> Result<OscPacket, OscError> {
+    let mut reader = Reader { data, pos: 0 };
+    if data.starts_with(b"#bundle\0") {
+        reader.take(8)?;
+        let timetag = reader.u64()?;
+        let mut content = Vec::new();
+        while !reader.is_empty() {
+            let len = reader.u32()? as usize;
+            if !len.is_multiple_of(4) {
+                return Err(OscError::InvalidBundle);
+            }
+            let element = reader.take(len).map_err(|_| OscError::InvalidBundle)?;
+            content.push(parse(element)?);
+        }
+        return Ok(OscPacket::Bundle(OscBundle { timetag, content }));
+    }
+
+    let address = reader.string()?;
+    if !address.starts_with('/') {
+        return Err(OscError::InvalidAddress);
+    }
+
+    let mut args = Vec::new();
+    // Type tags are optional in OSC 1.0; messages without them carry no arguments.
+    if !reader.is_empty() {
+        let tags = reader.string()?;
+        let tags = tags.strip_prefix(',').ok_or(OscError::InvalidString)?;
+        for tag in tags.bytes() {
+            let arg = match tag {
+                b'i' => OscArg::Int(reader.u32()? as i32),
+                b'f' => OscArg::Float(f32::from_bits(reader.u32()?)),
+                b's' | b'S' => OscArg::String(String::from(reader.string()?)),
+                b'b' => OscArg::Blob(reader.blob()?.to_vec()),
+                b'h' => OscArg::Int64(reader.u64()? as i64),
+                b'd' => OscArg::Double(f64::from_bits(reader.u64()?)),
+                b't' => OscArg::TimeTag(reader.u64()?),
+                b'T' => OscArg::Bool(true),
+                b'F' => OscArg::Bool(false),
+                b'N' => OscArg::Nil,
+                b'I' => OscArg::Impulse,
+                other => return Err(OscError::UnknownTypeTag(other)),
+            };
+            args.push(arg);
+        }
+    }
+
+    Ok(OscPacket::Message(OscMessage {
+        address: String::from(address),
+        args,
+    }))
+}
+
+/// Returns true if the OSC address `pattern` matches `address`.
+///
+/// Supports the OSC 1.0 wildcards `?`, `*`, `[abc]`, `[a-z]`, `[!abc]` and `{foo,bar}`.
+/// Wildcards never match across `/`.
+pub fn pattern_matches(pattern: &str, address: &str) -> bool {
+    match_bytes(pattern.as_bytes(), address.as_bytes())
+}
+
+fn match_bytes(pattern: &[u8], address: &[u8]) -> bool {
+    let (mut p, mut a) = (0, 0);
+    // The pattern after the last `*`, and the end of the address bytes it has absorbed.
+    // Only the last star needs to be retried: the bytes before it are already matched, and
+    // since no wildcard crosses `/`, no earlier star could take a different share.
+    let mut star = None;
+    loop {
+        match pattern.get(p) {
+            Some(b'*') => {
+                p += 1;
+                star = Some((p, a));
+                continue;
+            }
+            Some(b'{') => {
+                let rest = &pattern[p + 1..];
+                let Some(end) = rest.iter().position(|&b| b == b'}') else {
+                    return false;
+                };
+                let tail = &rest[end + 1..];
+                let remaining = &address[a..];
+                if rest[..end].split(|&b| b == b',').any(|option| {
+                    remaining.starts_with(option) && match_bytes(tail, &remaining[option.len()..])
+                }) {
+                    return true;
+                }
+            }
+            Some(_) => {
+                if let Some(len) = address.get(a).and_then(|&c| match_char(&pattern[p..], c)) {
+                    p += len;
+                    a += 1;
+                    continue;
+                }
+            }
+            None if a == address.len() => return true,
+            None => {}
+        }
+
+        // Mismatch: let the last star absorb one more byte, unless that would cross a `/`.
+        match star {
+            Some((resume, absorbed)) if absorbed < address.len() && address[absorbed] != b'/' => {
+                star = Some((resume, absorbed + 1));
+                p = resume;
+                a = absorbed + 1;
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// Matches `c` against the literal, `?` or `[...]` set at the start of `pattern` and
+/// returns the length of that token if it matches.
+fn match_char(pattern: &[u8], c: u8) -> Option<usize> {
+    match pattern[0] {
+        b'?' => (c != b'/').then_some(1),
+        b'[' => {
+            let rest = &pattern[1..];
+            let end = rest.iter().position(|&b| b == b']')?;
+            let (negate, set) = match rest[..end].split_first() {
+                Some((b'!', set)) => (true, set),
+                _ => (false, &rest[..end]),
+            };
+            let mut found = false;
+            let mut i = 0;
+            while i < set.len() {
+                if i + 2 < set.len() && set[i + 1] == b'-' {
+                    found |= (set[i]..=set[i + 2]).contains(&c);
+                    i += 3;
+                } else {
+                    found |= set[i] == c;
+                    i += 1;
+                }
+            }
+            (found != negate && c != b'/').then_some(end + 2)
+        }
+        literal => (literal == c).then_some(1),
+    }
+}
+
+enum Target {
+    Parameter(Parameter),
+    Trigger(Trigger),
+}
+
+struct Route {
+    address: String,
+    target: Target,
+}
+
+impl Route {
+    fn apply(&self, value: Option<f32>) {
+        match &self.target {
+            Target::Parameter(parameter) => {
+                if let Some(value) = value {
+                    parameter.set(value);
+                }
+            }
+            // Buttons usually send 1 on press and 0 on release; only the press fires.
+            Target::Trigger(trigger) => {
+                if value.is_none_or(|v| v != 0.0) {
+                    trigger.fire();
+                }
+            }
+        }
+    }
+}
+
+struct PendingEvent {
+    sample_index: u64,
+    route: usize,
+    value: Option<f32>,
+}
+
+/// Routes OSC messages to named [`Parameter`]s and [`Trigger`]s.
+///
+/// Incoming address patterns are matched against the registered addresses. A Parameter is set
+/// to the first numeric (or boolean) argument, a Trigger fires on any message whose first
+/// argument is not zero or false.
+///
+/// Messages inside a bundle with a timetag are scheduled: with a [`Transport`] attached, the
+/// timetag is mapped to the sample index audible at that time, and the event is applied by
+/// [`OscRouter::apply_due`] once that sample is reached. Without a transport, or for the
+/// "immediately" timetag, events are applied when the packet is handled.
+///
+/// The router is typically owned by the network thread; all targets are thread-safe handles.
+pub struct OscRouter {
+    routes: Vec<Route>,
+    transport: Option<(Transport, f64)>,
+    // Sorted by sample index; events due at the same sample keep their arrival order.
+    pending: Vec<PendingEvent>,
+}
+
+impl OscRouter {
+    /// Creates a new, empty OscRouter.
+    pub fn new() -> Self {
+        OscRouter {
+            routes: Vec::new(),
+            transport: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Attaches a Transport used to map bundle timetags to sample indices.
+    ///
+    /// # Arguments
+    /// * `transport` - The transport anchored by the audio callback.
+    /// * `epoch_offset` - Seconds to subtract from an NTP time (seconds since 1900) to get
+    ///   the host time used by the transport.
+    pub fn with_transport(mut self, transport: Transport, epoch_offset: f64) -> Self {
+        self.transport = Some((transport, epoch_offset));
+        self
+    }
+
+    /// Routes messages matching `address` to a Parameter.
+    pub fn add_parameter(&mut self, address: &str, parameter: Parameter) {
+        self.routes.push(Route {
+            address: String::from(address),
+            target: Target::Parameter(parameter),
+        });
+    }
+
+    /// Routes messages matching `address` to a Trigger.
+    pub fn add_trigger(&mut self, address: &str, trigger: Trigger) {
+        self.routes.push(Route {
+            address: String::from(address),
+            target: Target::Trigger(trigger),
+        });
+    }
+
+    /// Parses and dispatches a raw OSC packet.
+    ///
+    /// Returns the number of targets the packet was routed to.
+    pub fn handle(&mut self, data: &[u8]) -> Result<usize, OscError> {
+        let packet = parse(data)?;
+        Ok(self.dispatch(&packet))
+    }
+
+    /// Dispatches a parsed OSC packet.
+    ///
+    /// Returns the number of targets the packet was routed to.
+    pub fn dispatch(&mut self, packet: &OscPacket) -> usize {
+        self.dispatch_at(packet, None)
+    }
+
+    /// Applies all scheduled events due at or before `sample_index`, in time order.
+    ///
+    /// Returns the number of events applied.
+    pub fn apply_due(&mut self, sample_index: u64) -> usize {
+        let due = self
+            .pending
+            .partition_point(|event| event.sample_index <= sample_index);
+        for event in self.pending.drain(..due) {
+            self.routes[event.route].apply(event.value);
+        }
+        due
+    }
+
+    /// Returns the number of scheduled events not yet applied.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn dispatch_at(&mut self, packet: &OscPacket, due: Option<u64>) -> usize {
+        match packet {
+            OscPacket::Message(message) => {
+                let value = message.args.first().and_then(OscArg::as_f32);
+                let mut routed = 0;
+                for (index, route) in self.routes.iter().enumerate() {
+                    if !pattern_matches(&message.address, &route.address) {
+                        continue;
+                    }
+                    match due {
+                        Some(sample_index) => {
+                            let at = self
+                                .pending
+                                .partition_point(|event| event.sample_index <= sample_index);
+                            self.pending.insert(
+                                at,
+                                PendingEvent {
+                                    sample_index,
+                                    route: index,
+                                    value,
+                                },
+                            );
+                        }
+                        None => route.apply(value),
+                    }
+                    routed += 1;
+                }
+                routed
+            }
+            OscPacket::Bundle(bundle) => {
+                let due = self.timetag_to_sample(bundle.timetag).or(due);
+                bundle
+                    .content
+                    .iter()
+                    .map(|packet| self.dispatch_at(packet, due))
+                    .sum()
+            }
+        }
+    }
+
+    fn timetag_to_sample(&self, timetag: u64) -> Option<u64> {
+        if timetag == IMMEDIATELY {
+            return None;
+        }
+        let (transport, epoch_offset) = self.transport.as_ref()?;
+        let seconds = (timetag >> 32) as f64 + (timetag & 0xFFFF_FFFF) as f64 / 4_294_967_296.0;
+        Some(transport.time_to_sample(seconds - epoch_offset))
+    }
+}
+
+impl Default for OscRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+    use crate::synthesis::envelope::Adsr;
+
+    fn push_string(out: &mut Vec<u8>, s: &str) {
+        out.extend_from_slice(s.as_bytes());
+        out.resize(padded(out.len() + 1), 0);
+    }
+
+    fn message(address: &str, value: f32) -> Vec<u8> {
+        let mut out = Vec::new();
+        push_string(&mut out, address);
+        push_string(&mut out, ",f");
+        out.extend_from_slice(&value.to_bits().to_be_bytes());
+        out
+    }
+
+    fn bundle(timetag: u64, elements: &[Vec<u8>]) -> Vec<u8> {
+        let mut out = Vec::new();
+        push_string(&mut out, "#bundle");
+        out.extend_from_slice(&timetag.to_be_bytes());
+        for element in elements {
+            out.extend_from_slice(&(element.len() as u32).to_be_bytes());
+            out.extend_from_slice(element);
+        }
+        out
+    }
+
+    #[test]
+    fn test_parse_message() {
+        let mut data = Vec::new();
+        push_string(&mut data, "/synth/cutoff");
+        push_string(&mut data, ",isT");
+        data.extend_from_slice(&42i32.to_be_bytes());
+        push_string(&mut data, "hi");
+
+        let expected = OscPacket::Message(OscMessage {
+            address: String::from("/synth/cutoff"),
+            args: alloc::vec![
+                OscArg::Int(42),
+                OscArg::String(String::from("hi")),
+                OscArg::Bool(true)
+            ],
+        });
+        assert_eq!(parse(&data), Ok(expected));
+        assert_eq!(parse(&data[..data.len() - 1]), Err(OscError::Truncated));
+        assert_eq!(parse(b"abc\0"), Err(OscError::InvalidAddress));
+    }
+
+    #[test]
+    fn test_pattern_matching() {
+        assert!(pattern_matches("/synth/cutoff", "/synth/cutoff"));
+        assert!(pattern_matches("/synth/*", "/synth/cutoff"));
+        assert!(!pattern_matches("/*", "/synth/cutoff"));
+        assert!(pattern_matches("/voice/?/gain", "/voice/3/gain"));
+        assert!(pattern_matches("/voice/[1-4]/gain", "/voice/3/gain"));
+        assert!(!pattern_matches("/voice/[!1-4]/gain", "/voice/3/gain"));
+        assert!(pattern_matches("/fx/{delay,reverb}/mix", "/fx/reverb/mix"));
+        assert!(!pattern_matches("/fx/{delay,reverb}/mix", "/fx/chorus/mix"));
+        assert!(pattern_matches("/*/{delay,reverb}*/m?x", "/fx/reverb2/mix"));
+        assert!(pattern_matches("/a*b*c", "/aXbbYc"));
+        assert!(!pattern_matches("/a*/c", "/ab/x/c"));
+
+        // Many stars against a long near miss: linear retries, not exponential ones.
+        let address = alloc::format!("/{}", "a".repeat(60));
+        assert!(!pattern_matches("/a*a*a*a*a*a*a*a*a*a*a*a*b", &address));
+        assert!(pattern_matches("/a*a*a*a*a*a*a*a*a*a*a*a*", &address));
+    }
+
+    #[test]
+    fn test_router_sets_parameters_and_triggers() {
+        let cutoff = Parameter::new(0.0);
+        let adsr = Adsr::new(
+            AudioParam::Static(0.0),
+            AudioParam::Static(0.0),
+            AudioParam::Static(0.1),
+            AudioParam::Static(1.0),
+            AudioParam::Static(0.1),
+        );
+        let mut router = OscRouter::new();
+        router.add_parameter("/synth/cutoff", cutoff.clone());
+        router.add_trigger("/synth/pluck", adsr.create_trigger());
+
+        assert_eq!(router.handle(&message("/synth/cutoff", 1200.0)), Ok(1));
+        assert_eq!(cutoff.get(), 1200.0);
+        assert_eq!(router.handle(&message("/synth/*", 300.0)), Ok(2));
+        assert_eq!(cutoff.get(), 300.0);
+        assert_eq!(router.handle(&message("/other", 1.0)), Ok(0));
+    }
+
+    #[test]
+    fn test_bundle_timetag_is_scheduled() {
+        let transport = Transport::new(1000.0);
+        transport.anchor(0, 10.0);
+        let cutoff = Parameter::new(0.0);
+        let mut router = OscRouter::new().with_transport(transport, 0.0);
+        router.add_parameter("/cutoff", cutoff.clone());
+
+        // 10.5 s in NTP fixed point is sample 500.
+        let timetag = (10u64 << 32) | (1u64 << 31);
+        let packet = bundle(timetag, &[message("/cutoff", 5.0)]);
+        assert_eq!(router.handle(&packet), Ok(1));
+        assert_eq!(router.pending(), 1);
+
+        assert_eq!(router.apply_due(499), 0);
+        assert_eq!(cutoff.get(), 0.0);
+        assert_eq!(router.apply_due(500), 1);
+        assert_eq!(cutoff.get(), 5.0);
+
+        let immediate = bundle(IMMEDIATELY, &[message("/cutoff", 7.0)]);
+        router.handle(&immediate).unwrap();
+        assert_eq!(cutoff.get(), 7.0);
+
+        // Events due in one call apply in time order, whatever order they arrived in.
+        let later = (10u64 << 32) | (3u64 << 30);
+        router
+            .handle(&bundle(later, &[message("/cutoff", 2.0)]))
+            .unwrap();
+        router
+            .handle(&bundle(timetag, &[message("/cutoff", 1.0)]))
+            .unwrap();
+        assert_eq!(router.apply_due(1000), 2);
+        assert_eq!(cutoff.get(), 2.0);
+    }
+}
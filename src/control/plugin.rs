@@ -0,0 +1,537 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::dsp_chain::DspChain;
+use crate::core::parameter::Parameter;
+use crate::core::preset::{ParameterTree, Preset, PresetError};
+use crate::core::tempo::TransportClock;
+use crate::synthesis::tuning::TuningTable;
+use crate::FrameProcessor;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Static information a host shows for a plugin, modelled on the CLAP plugin descriptor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PluginDescriptor {
+    /// Reverse-DNS identifier, e.g. `"com.example.reverb"`.
+    pub id: &'static str,
+    pub name: &'static str,
+    pub vendor: &'static str,
+    pub version: &'static str,
+    /// Feature tags, e.g. `["audio-effect", "reverb"]`.
+    pub features: &'static [&'static str],
+}
+
+/// A parameter as exposed to the host.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginParamInfo {
+    /// Id derived from the path. Paths are positional, so the id stays the same between
+    /// sessions but changes when processors before it are added, removed or reordered.
+    pub id: u32,
+    /// Path in the chain's [`ParameterTree`], e.g. `1/gain`.
+    pub path: String,
+    pub min: f32,
+    pub max: f32,
+    /// Value when the adapter was created.
+    pub default: f32,
+}
+
+/// An event delivered by the host with a frame offset into the current block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PluginEvent {
+    /// Sets a parameter (values outside its range are clamped).
+    ParamValue {
+        time: u32,
+        param_id: u32,
+        value: f32,
+    },
+    /// Starts a note on the connected [`NoteTargets`].
+    NoteOn { time: u32, key: u8, velocity: f32 },
+    /// Releases a note on the connected [`NoteTargets`].
+    NoteOff { time: u32, key: u8 },
+    /// Updates the tempo of the connected clock.
+    Tempo { time: u32, bpm: f32 },
+    /// Changes an expression of a playing note on the connected [`NoteTargets`].
+    NoteExpression {
+        time: u32,
+        key: u8,
+        expression: NoteExpression,
+        value: f32,
+    },
+}
+
+/// A per-note expression sent by the host while a note plays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteExpression {
+    /// Pressure (aftertouch, 0.0 - 1.0).
+    Pressure,
+    /// Brightness or timbre (0.0 - 1.0).
+    Brightness,
+    /// Pitch offset from the key in semitones.
+    Tuning,
+}
+
+impl PluginEvent {
+    /// Returns the frame offset of the event within the block.
+    pub fn time(&self) -> u32 {
+        match *self {
+            PluginEvent::ParamValue { time, .. }
+            | PluginEvent::NoteOn { time, .. }
+            | PluginEvent::NoteOff { time, .. }
+            | PluginEvent::Tempo { time, .. }
+            | PluginEvent::NoteExpression { time, .. } => time,
+        }
+    }
+}
+
+/// Parameters driven by note events, for a monophonic instrument chain.
+///
+/// The gate is set to 1.0 on note on and back to 0.0 when the last played key is released.
+/// The velocity is meant for the velocity inputs of the voice (`Adsr::set_velocity`,
+/// `Gain::with_velocity`); expressions of the playing note go to their own parameters,
+/// and its tuning to the frequency. Pressure and tuning restart at 0.0 with each note.
+#[derive(Clone)]
+pub struct NoteTargets {
+    pub gate: Parameter,
+    /// Receives the key's frequency in Hz (A4 = 440 Hz).
+    pub frequency: Parameter,
+    /// Receives the note velocity (0.0 - 1.0).
+    pub velocity: Option<Parameter>,
+    /// Receives the note pressure (0.0 - 1.0).
+    pub pressure: Option<Parameter>,
+    /// Receives the note brightness (0.0 - 1.0).
+    pub brightness: Option<Parameter>,
+    /// Maps keys to frequencies (12-TET by default).
+    pub tuning: TuningTable,
+}
+
+impl NoteTargets {
+    /// Creates a new NoteTargets with only a gate and a frequency.
+    ///
+    /// # Arguments
+    /// * `gate` - Set to 1.0 while a note plays.
+    /// * `frequency` - Receives the note frequency in Hz.
+    pub fn new(gate: Parameter, frequency: Parameter) -> Self {
+        NoteTargets {
+            gate,
+            frequency,
+            velocity: None,
+            pressure: None,
+            brightness: None,
+            tuning: TuningTable::default(),
+        }
+    }
+
+    /// Sets the velocity target and returns the targets.
+    pub fn with_velocity(mut self, velocity: Parameter) -> Self {
+        self.velocity = Some(velocity);
+        self
+    }
+
+    /// Sets the pressure target and returns the targets.
+    pub fn with_pressure(mut self, pressure: Parameter) -> Self {
+        self.pressure = Some(pressure);
+        self
+    }
+
+    /// Sets the brightness target and returns the targets.
+    pub fn with_brightness(mut self, brightness: Parameter) -> Self {
+        self.brightness = Some(brightness);
+        self
+    }
+
+    /// Sets the key to frequency mapping and returns the targets.
+    pub fn with_tuning(mut self, tuning: TuningTable) -> Self {
+        self.tuning = tuning;
+        self
+    }
+}
+
+struct PluginParam {
+    info: PluginParamInfo,
+    parameter: Parameter,
+}
+
+/// Host-agnostic glue that runs a DspChain as a plugin.
+///
+/// Implements the parts every plugin format (CLAP, LV2, VST3) needs so a format wrapper
+/// only has to forward calls: the chain's linked parameters are listed with path-derived ids,
+/// `activate` applies the host sample rate, `process` splits the block at each event so
+/// parameter changes and notes are sample accurate, and the state is saved and restored
+/// as a binary [`Preset`].
+pub struct PluginAdapter<C: ChannelConfig> {
+    descriptor: PluginDescriptor,
+    chain: DspChain<C>,
+    params: Vec<PluginParam>,
+    tree: ParameterTree,
+    notes: Option<NoteTargets>,
+    clock: Option<TransportClock>,
+    current_key: Option<u8>,
+    sample_rate: f32,
+    sample_index: u64,
+    active: bool,
+}
+
+impl<C: ChannelConfig + 'static> PluginAdapter<C> {
+    /// Creates a new PluginAdapter.
+    ///
+    /// # Arguments
+    /// * `descriptor` - Plugin information reported to the host.
+    /// * `chain` - The chain to run. Its linked parameters become the plugin parameters.
+    pub fn new(descriptor: PluginDescriptor, chain: DspChain<C>) -> Self {
+        let tree = ParameterTree::of(&chain);
+        let params = tree
+            .entries()
+            .into_iter()
+            .map(|(path, entry)| PluginParam {
+                info: PluginParamInfo {
+                    id: param_id(&path),
+                    path,
+                    min: entry.min,
+                    max: entry.max,
+                    default: entry.parameter.get(),
+                },
+                parameter: entry.parameter.clone(),
+            })
+            .collect();
+
+        PluginAdapter {
+            descriptor,
+            chain,
+            params,
+            tree,
+            notes: None,
+            clock: None,
+            current_key: None,
+            sample_rate: 44100.0,
+            sample_index: 0,
+            active: false,
+        }
+    }
+
+    /// Routes note events to the given parameters.
+    pub fn with_notes(mut self, notes: NoteTargets) -> Self {
+        self.notes = Some(notes);
+        self
+    }
+
+    /// Follows the host tempo with the given clock.
+    pub fn with_clock(mut self, clock: TransportClock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+}
+
+impl<C: ChannelConfig> PluginAdapter<C> {
+    /// Returns the plugin descriptor.
+    pub fn descriptor(&self) -> &PluginDescriptor {
+        &self.descriptor
+    }
+
+    /// Returns the number of audio channels per port.
+    pub fn channel_count(&self) -> usize {
+        C::num_channels()
+    }
+
+    /// Prepares the chain for processing at the host sample rate.
+    ///
+    /// Called by the host before processing starts and whenever the sample rate changes.
+    ///
+    /// # Arguments
+    /// * `sample_rate` - The host sample rate.
+    /// * `max_frames` - The largest block the host will pass to `process`.
+    pub fn activate(&mut self, sample_rate: f32, max_frames: usize) {
+        self.sample_rate = sample_rate;
+        self.chain.set_sample_rate(sample_rate);
+        self.chain.prepare(max_frames);
+        self.chain.reset();
+        self.sample_index = 0;
+        self.active = true;
+    }
+
+    /// Stops processing. Call `activate` again before the next `process`.
+    pub fn deactivate(&mut self) {
+        self.active = false;
+    }
+
+    /// Returns true between `activate` and `deactivate`.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns the current sample rate.
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// Returns the latency to report to the host, in samples.
+    pub fn latency_samples(&self) -> u32 {
+        self.chain.latency_samples()
+    }
+
+    /// Returns the parameters exposed to the host.
+    pub fn params(&self) -> impl Iterator<Item = &PluginParamInfo> + '_ {
+        self.params.iter().map(|param| &param.info)
+    }
+
+    /// Returns the current value of a parameter.
+    pub fn param_value(&self, id: u32) -> Option<f32> {
+        self.find(id).map(|param| param.parameter.get())
+    }
+
+    /// Sets a parameter from outside the audio callback (e.g. the host's param flush).
+    ///
+    /// Returns false if no parameter has this id.
+    pub fn set_param(&self, id: u32, value: f32) -> bool {
+        match self.find(id) {
+            Some(param) => {
+                param
+                    .parameter
+                    .set(value.clamp(param.info.min, param.info.max));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Processes one host block, applying `events` at their frame offsets.
+    ///
+    /// Events must be sorted by time, as hosts deliver them. Events at or after the end of
+    /// the block are applied at its end.
+    pub fn process(&mut self, buffer: &mut [f32], events: &[PluginEvent]) {
+        if !self.active {
+            buffer.fill(0.0);
+            return;
+        }
+
+        let channels = C::num_channels();
+        let frames = buffer.len() / channels;
+        let mut position = 0;
+
+        for event in events {
+            let time = (event.time() as usize).min(frames);
+            if time > position {
+                self.process_range(buffer, position, time);
+                position = time;
+            }
+            self.apply(event);
+        }
+
+        if frames > position {
+            self.process_range(buffer, position, frames);
+        }
+    }
+
+    /// Saves the parameter values as a binary preset.
+    pub fn save_state(&self) -> Vec<u8> {
+        self.tree.snapshot().to_bytes()
+    }
+
+    /// Restores parameter values saved by `save_state`.
+    ///
+    /// Returns the number of parameters restored.
+    pub fn load_state(&self, data: &[u8]) -> Result<usize, PresetError> {
+        let preset = Preset::from_bytes(data)?;
+        Ok(self.tree.apply(&preset))
+    }
+
+    /// Returns a mutable reference to the chain.
+    pub fn chain_mut(&mut self) -> &mut DspChain<C> {
+        &mut self.chain
+    }
+
+    fn process_range(&mut self, buffer: &mut [f32], start: usize, end: usize) {
+        let channels = C::num_channels();
+        self.chain.process(
+            &mut buffer[start * channels..end * channels],
+            self.sample_index,
+        );
+        self.sample_index += (end - start) as u64;
+    }
+
+    fn find(&self, id: u32) -> Option<&PluginParam> {
+        self.params.iter().find(|param| param.info.id == id)
+    }
+
+    fn apply(&mut self, event: &PluginEvent) {
+        match *event {
+            PluginEvent::ParamValue {
+                param_id, value, ..
+            } => {
+                self.set_param(param_id, value);
+            }
+            PluginEvent::NoteOn { key, velocity, .. } => {
+                if let Some(notes) = &self.notes {
+                    notes.frequency.set(notes.tuning.frequency(key));
+                    if let Some(v) = &notes.velocity {
+                        v.set(velocity.clamp(0.0, 1.0));
+                    }
+                    if let Some(pressure) = &notes.pressure {
+                        pressure.set(0.0);
+                    }
+                    notes.gate.set(1.0);
+                    self.current_key = Some(key);
+                }
+            }
+            PluginEvent::NoteOff { key, .. } => {
+                if let Some(notes) = &self.notes {
+                    if self.current_key == Some(key) {
+                        notes.gate.set(0.0);
+                        self.current_key = None;
+                    }
+                }
+            }
+            PluginEvent::Tempo { bpm, .. } => {
+                if let Some(clock) = &self.clock {
+                    clock.set_bpm(bpm);
+                }
+            }
+            PluginEvent::NoteExpression {
+                key,
+                expression,
+                value,
+                ..
+            } => {
+                let Some(notes) = &self.notes else {
+                    return;
+                };
+                if self.current_key != Some(key) {
+                    return;
+                }
+                let target = match expression {
+                    NoteExpression::Pressure => notes.pressure.as_ref(),
+                    NoteExpression::Brightness => notes.brightness.as_ref(),
+                    NoteExpression::Tuning => {
+                        notes
+                            .frequency
+                            .set(notes.tuning.frequency(key) * libm::exp2f(value / 12.0));
+                        None
+                    }
+                };
+                if let Some(target) = target {
+                    target.set(value.clamp(0.0, 1.0));
+                }
+            }
+        }
+    }
+}
+
+/// Derives a parameter id from its path (32-bit FNV-1a).
+pub fn param_id(path: &str) -> u32 {
+    let mut hash = 0x811c_9dc5u32;
+    for byte in path.bytes() {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+    use crate::core::channels::Mono;
+    use crate::effects::utility::dc_source::DcSource;
+    use crate::effects::utility::gain::Gain;
+    use crate::synthesis::envelope::Adsr;
+    use alloc::vec;
+
+    const DESCRIPTOR: PluginDescriptor = PluginDescriptor {
+        id: "com.example.test",
+        name: "Test",
+        vendor: "infinitedsp",
+        version: "1.0.0",
+        features: &["instrument"],
+    };
+
+    #[test]
+    fn test_events_are_sample_accurate() {
+        let level = Parameter::new(1.0);
+        let gate = Parameter::new(0.0);
+        let chain = DspChain::new(DcSource::new(AudioParam::Linked(gate.clone())), 44100.0)
+            .and(Gain::new(AudioParam::Linked(level.clone())));
+        let mut plugin = PluginAdapter::<Mono>::new(DESCRIPTOR, chain)
+            .with_notes(NoteTargets::new(gate.clone(), Parameter::new(0.0)));
+
+        let info: Vec<_> = plugin.params().cloned().collect();
+        assert_eq!(info.len(), 1);
+        assert_eq!(info[0].path, "1/gain");
+        let id = info[0].id;
+
+        let mut buffer = vec![0.0; 16];
+        plugin.process(&mut buffer, &[]);
+        assert!(buffer.iter().all(|&s| s == 0.0));
+
+        plugin.activate(48000.0, 16);
+        let events = [
+            PluginEvent::NoteOn {
+                time: 4,
+                key: 69,
+                velocity: 1.0,
+            },
+            PluginEvent::ParamValue {
+                time: 8,
+                param_id: id,
+                value: 0.5,
+            },
+            PluginEvent::NoteOff { time: 12, key: 69 },
+        ];
+        plugin.process(&mut buffer, &events);
+        assert_eq!(&buffer[0..4], &[0.0; 4]);
+        assert_eq!(&buffer[4..8], &[1.0; 4]);
+        assert_eq!(&buffer[8..12], &[0.5; 4]);
+        assert_eq!(&buffer[12..16], &[0.0; 4]);
+
+        let state = plugin.save_state();
+        assert!(plugin.set_param(id, 9.0));
+        assert_eq!(plugin.param_value(id), Some(4.0));
+        assert_eq!(plugin.load_state(&state), Ok(1));
+        assert_eq!(level.get(), 0.5);
+    }
+
+    #[test]
+    fn test_velocity_and_expression() {
+        let gate = Parameter::new(0.0);
+        let frequency = Parameter::new(0.0);
+        let velocity = Parameter::new(0.0);
+        let pressure = Parameter::new(0.0);
+        let mut adsr = Adsr::new(
+            AudioParam::Linked(gate.clone()),
+            AudioParam::Static(0.0),
+            AudioParam::Static(0.1),
+            AudioParam::Static(1.0),
+            AudioParam::Static(0.1),
+        );
+        adsr.set_velocity(AudioParam::Linked(velocity.clone()), 1.0);
+        let chain = DspChain::new(adsr, 44100.0);
+        let notes = NoteTargets::new(gate, frequency.clone())
+            .with_velocity(velocity)
+            .with_pressure(pressure.clone());
+        let mut plugin = PluginAdapter::<Mono>::new(DESCRIPTOR, chain).with_notes(notes);
+        plugin.activate(44100.0, 16);
+
+        let events = [
+            PluginEvent::NoteOn {
+                time: 0,
+                key: 69,
+                velocity: 0.25,
+            },
+            PluginEvent::NoteExpression {
+                time: 8,
+                key: 69,
+                expression: NoteExpression::Tuning,
+                value: 12.0,
+            },
+            PluginEvent::NoteExpression {
+                time: 8,
+                key: 60,
+                expression: NoteExpression::Pressure,
+                value: 0.5,
+            },
+        ];
+        let mut buffer = vec![0.0; 16];
+        plugin.process(&mut buffer, &events);
+        assert!((buffer[15] - 0.25).abs() < 1e-6);
+        assert!((frequency.get() - 880.0).abs() < 1e-3);
+        // Expressions for other keys are ignored.
+        assert_eq!(pressure.get(), 0.0);
+    }
+}
@@ -0,0 +1,596 @@
+use crate::control::midi::MidiMessage;
+use crate::core::channels::ChannelConfig;
+use crate::core::transport::Transport;
+use crate::FrameProcessor;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// Tempo of a file without tempo events: 120 BPM.
+const DEFAULT_MICROS_PER_QUARTER: u32 = 500_000;
+
+/// Errors produced while parsing a Standard MIDI File.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmfError {
+    /// The file ended in the middle of a chunk or event.
+    Truncated,
+    /// The file does not start with an `MThd` header chunk.
+    InvalidHeader,
+    /// The file uses SMPTE time division, which is not supported.
+    SmpteTiming,
+    /// A data byte appeared without a preceding status byte.
+    MissingStatus,
+    /// A system message other than sysex or meta appeared in a track.
+    InvalidStatus(u8),
+    /// A status byte appeared where a channel message expected a data byte.
+    InvalidData(u8),
+}
+
+impl core::fmt::Display for SmfError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SmfError::Truncated => write!(f, "MIDI file is truncated"),
+            SmfError::InvalidHeader => write!(f, "not a Standard MIDI File"),
+            SmfError::SmpteTiming => write!(f, "SMPTE time division is not supported"),
+            SmfError::MissingStatus => write!(f, "MIDI data byte without a status byte"),
+            SmfError::InvalidStatus(status) => {
+                write!(f, "unexpected MIDI status byte 0x{:02X}", status)
+            }
+            SmfError::InvalidData(byte) => {
+                write!(f, "MIDI data byte expected, found 0x{:02X}", byte)
+            }
+        }
+    }
+}
+
+/// The content of an [`SmfEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmfEventKind {
+    /// A channel message.
+    Midi(MidiMessage),
+    /// A tempo change in microseconds per quarter note.
+    Tempo(u32),
+}
+
+/// An event of a track, at an absolute position in ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmfEvent {
+    pub tick: u64,
+    pub kind: SmfEventKind,
+}
+
+/// A channel message placed on the sample timeline by [`Smf::schedule`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduledEvent {
+    pub sample: u64,
+    pub message: MidiMessage,
+}
+
+/// A parsed Standard MIDI File (format 0, 1 or 2).
+///
+/// Only the channel messages and tempo changes are kept; other meta events and system
+/// exclusive messages are skipped. The tracks of a format 2 file are independent
+/// sequences, so [`schedule`](Self::schedule) only plays the first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Smf {
+    pub format: u16,
+    pub ticks_per_quarter: u16,
+    pub tracks: Vec<Vec<SmfEvent>>,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SmfError> {
+        let end = self.pos.checked_add(len).ok_or(SmfError::Truncated)?;
+        let bytes = self.data.get(self.pos..end).ok_or(SmfError::Truncated)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, SmfError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn data(&mut self) -> Result<u8, SmfError> {
+        match self.byte()? {
+            byte if byte >= 0x80 => Err(SmfError::InvalidData(byte)),
+            byte => Ok(byte),
+        }
+    }
+
+    fn u16(&mut self) -> Result<u16, SmfError> {
+        let b = self.take(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, SmfError> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn vlq(&mut self) -> Result<u32, SmfError> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            let b = self.byte()?;
+            value = (value << 7) | (b & 0x7F) as u32;
+            if b & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(SmfError::Truncated)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+}
+
+impl Smf {
+    /// Parses a Standard MIDI File.
+    pub fn parse(data: &[u8]) -> Result<Smf, SmfError> {
+        let mut reader = Reader { data, pos: 0 };
+        if reader.take(4).map_err(|_| SmfError::InvalidHeader)? != b"MThd" {
+            return Err(SmfError::InvalidHeader);
+        }
+        let header_len = reader.u32()? as usize;
+        if header_len < 6 {
+            return Err(SmfError::InvalidHeader);
+        }
+        let format = reader.u16()?;
+        let track_count = reader.u16()?;
+        let division = reader.u16()?;
+        reader.take(header_len - 6)?;
+        if division & 0x8000 != 0 {
+            return Err(SmfError::SmpteTiming);
+        }
+
+        let mut tracks = Vec::with_capacity(track_count as usize);
+        while tracks.len() < track_count as usize && !reader.is_empty() {
+            let id = reader.take(4)?;
+            let len = reader.u32()? as usize;
+            let chunk = reader.take(len)?;
+            // Unknown chunk types are skipped, as the specification asks.
+            if id == b"MTrk" {
+                tracks.push(Self::parse_track(chunk)?);
+            }
+        }
+
+        Ok(Smf {
+            format,
+            ticks_per_quarter: division.max(1),
+            tracks,
+        })
+    }
+
+    fn parse_track(chunk: &[u8]) -> Result<Vec<SmfEvent>, SmfError> {
+        let mut reader = Reader {
+            data: chunk,
+            pos: 0,
+        };
+        let mut events = Vec::new();
+        let mut tick = 0u64;
+        let mut running_status = None;
+
+        while !reader.is_empty() {
+            tick += reader.vlq()? as u64;
+            let mut status = reader.byte()?;
+            let first_data = if status < 0x80 {
+                let data = status;
+                status = running_status.ok_or(SmfError::MissingStatus)?;
+                Some(data)
+            } else {
+                None
+            };
+
+            match status {
+                0xFF => {
+                    running_status = None;
+                    let kind = reader.byte()?;
+                    let len = reader.vlq()? as usize;
+                    let data = reader.take(len)?;
+                    match kind {
+                        0x51 if len == 3 => {
+                            let micros = u32::from_be_bytes([0, data[0], data[1], data[2]]);
+                            events.push(SmfEvent {
+                                tick,
+                                kind: SmfEventKind::Tempo(micros),
+                            });
+                        }
+                        0x2F => break,
+                        _ => {}
+                    }
+                }
+                0xF0 | 0xF7 => {
+                    running_status = None;
+                    let len = reader.vlq()? as usize;
+                    reader.take(len)?;
+                }
+                0xF1..=0xFE => return Err(SmfError::InvalidStatus(status)),
+                _ => {
+                    running_status = Some(status);
+                    let a = match first_data {
+                        Some(a) => a,
+                        None => reader.data()?,
+                    };
+                    let channel = status & 0x0F;
+                    let message = match status & 0xF0 {
+                        0x80 => MidiMessage::NoteOff {
+                            channel,
+                            key: a,
+                            velocity: reader.data()?,
+                        },
+                        0x90 => match reader.data()? {
+                            0 => MidiMessage::NoteOff {
+                                channel,
+                                key: a,
+                                velocity: 0,
+                            },
+                            velocity => MidiMessage::NoteOn {
+                                channel,
+                                key: a,
+                                velocity,
+                            },
+                        },
+                        0xA0 => MidiMessage::PolyPressure {
+                            channel,
+                            key: a,
+                            pressure: reader.data()?,
+                        },
+                        0xB0 => MidiMessage::ControlChange {
+                            channel,
+                            controller: a,
+                            value: reader.data()?,
+                        },
+                        0xC0 => MidiMessage::ProgramChange {
+                            channel,
+                            program: a,
+                        },
+                        0xD0 => MidiMessage::ChannelPressure {
+                            channel,
+                            pressure: a,
+                        },
+                        _ => {
+                            let b = reader.data()?;
+                            let value = ((b as i16) << 7 | a as i16) - 8192;
+                            MidiMessage::PitchBend { channel, value }
+                        }
+                    };
+                    events.push(SmfEvent {
+                        tick,
+                        kind: SmfEventKind::Midi(message),
+                    });
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// Merges the tracks into one list of messages in samples, following the tempo map.
+    ///
+    /// Format 0 and 1 files play all their tracks; format 2 files only their first.
+    ///
+    /// # Arguments
+    /// * `sample_rate` - Sample rate in Hz.
+    pub fn schedule(&self, sample_rate: f32) -> Vec<ScheduledEvent> {
+        let tracks = match self.format {
+            2 => &self.tracks[..self.tracks.len().min(1)],
+            _ => &self.tracks[..],
+        };
+        let mut merged: Vec<SmfEvent> = tracks.iter().flatten().copied().collect();
+        // Stable, so events on the same tick keep their track order.
+        merged.sort_by_key(|event| event.tick);
+
+        let mut scheduled = Vec::with_capacity(merged.len());
+        let mut micros_per_quarter = DEFAULT_MICROS_PER_QUARTER;
+        let mut last_tick = 0u64;
+        let mut last_sample = 0.0f64;
+        let ticks_per_quarter = self.ticks_per_quarter as f64;
+        for event in merged {
+            let seconds_per_tick = micros_per_quarter as f64 / 1_000_000.0 / ticks_per_quarter;
+            last_sample += (event.tick - last_tick) as f64 * seconds_per_tick * sample_rate as f64;
+            last_tick = event.tick;
+            match event.kind {
+                SmfEventKind::Tempo(micros) => micros_per_quarter = micros.max(1),
+                SmfEventKind::Midi(message) => scheduled.push(ScheduledEvent {
+                    sample: libm::round(last_sample) as u64,
+                    message,
+                }),
+            }
+        }
+        scheduled
+    }
+}
+
+/// Plays the messages of a MIDI file into a handler, sample accurately.
+///
+/// As a processor it passes audio through and calls the handler for every message that
+/// falls into the block, with the frame offset of the message within the block. Event
+/// times are song positions in samples of a [`Transport`]: nothing plays while it is
+/// stopped, and seeks and loop wraps move the playback position with it.
+///
+/// The player tracks which keys it has turned on. When the transport stops, the position
+/// jumps (a seek or loop wrap) or the last message has played, it sends a note off for
+/// every key still held, so no note hangs.
+pub struct SmfPlayer {
+    transport: Transport,
+    events: Vec<ScheduledEvent>,
+    cursor: usize,
+    next_position: u64,
+    was_playing: bool,
+    // One bit per key, per channel.
+    held: [u128; 16],
+    handler: Box<dyn FnMut(u32, MidiMessage) + Send>,
+}
+
+impl SmfPlayer {
+    /// Creates a new SmfPlayer.
+    ///
+    /// # Arguments
+    /// * `transport` - The song timeline.
+    /// * `events` - The messages, e.g. from [`Smf::schedule`], sorted by sample.
+    /// * `handler` - Receives the frame offset and each message. Runs on the audio thread.
+    pub fn new(
+        transport: &Transport,
+        events: Vec<ScheduledEvent>,
+        handler: impl FnMut(u32, MidiMessage) + Send + 'static,
+    ) -> Self {
+        SmfPlayer {
+            transport: transport.clone(),
+            events,
+            cursor: 0,
+            next_position: 0,
+            was_playing: false,
+            held: [0; 16],
+            handler: Box::new(handler),
+        }
+    }
+
+    /// Sends a message to the handler, keeping track of the held keys.
+    fn send(&mut self, offset: u32, message: MidiMessage) {
+        match message {
+            MidiMessage::NoteOn {
+                channel,
+                key,
+                velocity,
+            } if velocity > 0 => self.held[channel as usize & 15] |= 1 << (key & 127),
+            MidiMessage::NoteOn { channel, key, .. }
+            | MidiMessage::NoteOff { channel, key, .. } => {
+                self.held[channel as usize & 15] &= !(1 << (key & 127))
+            }
+            _ => {}
+        }
+        (self.handler)(offset, message);
+    }
+
+    /// Sends a note off for every held key.
+    fn release_all(&mut self, offset: u32) {
+        for channel in 0..16 {
+            while self.held[channel] != 0 {
+                let key = self.held[channel].trailing_zeros() as u8;
+                self.send(
+                    offset,
+                    MidiMessage::NoteOff {
+                        channel: channel as u8,
+                        key,
+                        velocity: 0,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Returns true once every message has been played.
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.events.len()
+    }
+}
+
+impl<C: ChannelConfig> FrameProcessor<C> for SmfPlayer {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let frames = buffer.len() / C::num_channels();
+        for frame in 0..frames {
+            let index = sample_index + frame as u64;
+            if !self.transport.is_playing_at(index) {
+                if self.was_playing {
+                    self.release_all(frame as u32);
+                    self.was_playing = false;
+                }
+                continue;
+            }
+            self.was_playing = true;
+            let position = self.transport.position_at(index);
+            if position != self.next_position {
+                self.release_all(frame as u32);
+                self.cursor = self.events.partition_point(|e| e.sample < position);
+            }
+            while let Some(event) = self.events.get(self.cursor) {
+                if event.sample > position {
+                    break;
+                }
+                let message = event.message;
+                self.cursor += 1;
+                self.send(frame as u32, message);
+                if self.is_finished() {
+                    self.release_all(frame as u32);
+                }
+            }
+            self.next_position = position + 1;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.release_all(0);
+        self.cursor = 0;
+        self.next_position = 0;
+        self.was_playing = false;
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "SmfPlayer"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::Mono;
+    use alloc::sync::Arc;
+    use alloc::vec;
+    use core::sync::atomic::{AtomicU32, Ordering};
+
+    /// A format 1 file at 96 ticks per quarter: a tempo track (60 BPM from beat 1) and a
+    /// note track using running status.
+    fn test_file() -> Vec<u8> {
+        let tempo_track: &[u8] = &[
+            0x60, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40, // beat 1: 1,000,000 us per quarter
+            0x00, 0xFF, 0x2F, 0x00,
+        ];
+        let note_track: &[u8] = &[
+            0x00, 0x90, 0x3C, 0x64, // note on C4
+            0x60, 0x3C, 0x00, // running status, velocity 0: note off at beat 1
+            0x81, 0x40, 0xE0, 0x00, 0x60, // pitch bend at beat 3 (delta 192)
+            0x00, 0xFF, 0x2F, 0x00,
+        ];
+        let mut file = vec![];
+        file.extend_from_slice(b"MThd");
+        file.extend_from_slice(&[0, 0, 0, 6, 0, 1, 0, 2, 0, 96]);
+        for track in [tempo_track, note_track] {
+            file.extend_from_slice(b"MTrk");
+            file.extend_from_slice(&(track.len() as u32).to_be_bytes());
+            file.extend_from_slice(track);
+        }
+        file
+    }
+
+    #[test]
+    fn test_parse_and_schedule() {
+        let smf = Smf::parse(&test_file()).unwrap();
+        assert_eq!((smf.format, smf.ticks_per_quarter), (1, 96));
+        assert_eq!(smf.tracks.len(), 2);
+        assert_eq!(
+            smf.tracks[1][1].kind,
+            SmfEventKind::Midi(MidiMessage::NoteOff {
+                channel: 0,
+                key: 60,
+                velocity: 0
+            })
+        );
+
+        // Beat 1 is 0.5 s at 120 BPM; beats 1 - 3 take 2 s at 60 BPM.
+        let events = smf.schedule(1000.0);
+        let samples: Vec<u64> = events.iter().map(|e| e.sample).collect();
+        assert_eq!(samples, vec![0, 500, 2500]);
+        assert_eq!(
+            events[2].message,
+            MidiMessage::PitchBend {
+                channel: 0,
+                value: 4096
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_files() {
+        assert_eq!(Smf::parse(b"RIFF"), Err(SmfError::InvalidHeader));
+        assert_eq!(Smf::parse(&test_file()[..30]), Err(SmfError::Truncated));
+
+        // A note on whose velocity is a status byte.
+        let mut file = test_file();
+        let velocity = file.len() - 13;
+        assert_eq!(file[velocity], 0x64);
+        file[velocity] = 0x90;
+        assert_eq!(Smf::parse(&file), Err(SmfError::InvalidData(0x90)));
+    }
+
+    #[test]
+    fn test_format_2_plays_first_track() {
+        let mut smf = Smf::parse(&test_file()).unwrap();
+        smf.format = 2;
+        smf.tracks.swap(0, 1);
+        // The tempo track is now an independent second sequence and must not apply.
+        let samples: Vec<u64> = smf.schedule(1000.0).iter().map(|e| e.sample).collect();
+        assert_eq!(samples, vec![0, 500, 1500]);
+    }
+
+    #[test]
+    fn test_player_follows_transport() {
+        let transport = Transport::new(1000.0);
+        let events = Smf::parse(&test_file()).unwrap().schedule(1000.0);
+        let last_offset = Arc::new(AtomicU32::new(u32::MAX));
+        let seen = last_offset.clone();
+        let mut player = SmfPlayer::new(&transport, events, move |offset, _| {
+            seen.store(offset, Ordering::Relaxed);
+        });
+
+        let mut timeline = transport.clone();
+        let mut buffer = [0.0; 128];
+        let mut render = |player: &mut SmfPlayer, start: u64| {
+            FrameProcessor::<Mono>::process(&mut timeline, &mut buffer, start);
+            FrameProcessor::<Mono>::process(player, &mut buffer, start);
+        };
+
+        // Nothing plays while the transport is stopped.
+        render(&mut player, 0);
+        assert_eq!(last_offset.load(Ordering::Relaxed), u32::MAX);
+
+        // Started at stream sample 128, song position 500 is sample 628, in the fifth block.
+        transport.start();
+        for block in 1..5 {
+            render(&mut player, block * 128);
+        }
+        assert_eq!(last_offset.load(Ordering::Relaxed), 500 - 384);
+        assert!(!player.is_finished());
+
+        transport.seek(2450);
+        render(&mut player, 640);
+        assert_eq!(last_offset.load(Ordering::Relaxed), 50);
+        assert!(player.is_finished());
+    }
+
+    #[test]
+    fn test_held_notes_are_released() {
+        let transport = Transport::new(1000.0);
+        let events = Smf::parse(&test_file()).unwrap().schedule(1000.0);
+        let (ons, offs) = (Arc::new(AtomicU32::new(0)), Arc::new(AtomicU32::new(0)));
+        let (seen_ons, seen_offs) = (ons.clone(), offs.clone());
+        let mut player = SmfPlayer::new(&transport, events, move |_, message| match message {
+            MidiMessage::NoteOn { velocity, .. } if velocity > 0 => {
+                seen_ons.fetch_add(1, Ordering::Relaxed);
+            }
+            MidiMessage::NoteOn { .. } | MidiMessage::NoteOff { .. } => {
+                seen_offs.fetch_add(1, Ordering::Relaxed);
+            }
+            _ => {}
+        });
+
+        let mut timeline = transport.clone();
+        let mut buffer = [0.0; 100];
+        let mut render = |player: &mut SmfPlayer, block: u64| {
+            FrameProcessor::<Mono>::process(&mut timeline, &mut buffer, block * 100);
+            FrameProcessor::<Mono>::process(player, &mut buffer, block * 100);
+        };
+        let count = || (ons.load(Ordering::Relaxed), offs.load(Ordering::Relaxed));
+
+        // The loop wraps at 300, before the note off at 500: the note is released and
+        // played again from the top.
+        transport.set_loop(0, 300);
+        transport.start();
+        for block in 0..3 {
+            render(&mut player, block);
+        }
+        assert_eq!(count(), (1, 0));
+        render(&mut player, 3);
+        assert_eq!(count(), (2, 1));
+
+        // Stopping releases it too.
+        transport.stop();
+        render(&mut player, 4);
+        assert_eq!(count(), (2, 2));
+        render(&mut player, 5);
+        assert_eq!(count(), (2, 2));
+    }
+}
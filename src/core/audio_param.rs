@@ -1,7 +1,9 @@
 use crate::core::channels::Mono;
 use crate::core::parameter::Parameter;
+use crate::core::tempo::{NoteValue, SyncUnit, TempoSync, TransportClock};
 use crate::FrameProcessor;
 use alloc::boxed::Box;
+use core::ops::Range;
 
 /// A parameter that can be static, dynamic (controlled by another processor), or linked to a thread-safe Parameter.
 pub enum AudioParam {
@@ -11,6 +13,7 @@ pub enum AudioParam {
     /// Parameters can only be Mono signals.
     Dynamic(Box<dyn FrameProcessor<Mono> + Send>),
     /// A value linked to a shared Parameter (e.g., UI control).
+    /// Changes glide over the Parameter's smoothing time, if one is set.
     Linked(Parameter),
 }
 
@@ -25,20 +28,29 @@ impl AudioParam {
                 processor.process(buffer, sample_index);
             }
             AudioParam::Linked(param) => {
-                let val = param.get();
-                buffer.fill(val);
+                param.fill(buffer);
             }
         }
     }
 
+    /// Fills the buffer for the current block and returns its first value.
+    ///
+    /// For processors that update their coefficients once per block. Dynamic parameters
+    /// still run for the whole block, so their state keeps advancing in step.
+    pub fn block_value(&mut self, buffer: &mut [f32], sample_index: u64) -> f32 {
+        self.process(buffer, sample_index);
+        buffer[0]
+    }
+
     /// Returns the constant value if the parameter is Static or Linked.
-    /// Returns None if the parameter is Dynamic.
+    /// Returns None if the parameter is Dynamic, or Linked and still gliding to a new value.
     ///
     /// This allows processors to optimize for the common case where parameters
     /// are constant for the duration of a block.
     pub fn get_constant(&self) -> Option<f32> {
         match self {
             AudioParam::Static(val) => Some(*val),
+            AudioParam::Linked(param) if param.is_ramping() => None,
             AudioParam::Linked(param) => Some(param.get()),
             AudioParam::Dynamic(_) => None,
         }
@@ -46,11 +58,17 @@ impl AudioParam {
 
     /// Returns the parameter value at a specific sample index.
     ///
-    /// For Dynamic parameters, this will process a single sample.
+    /// Each call advances a Dynamic processor and a gliding Linked parameter by a single
+    /// sample, so call it for every sample. To read a value once per block, use
+    /// [`block_value`](Self::block_value), which keeps them in step with the block.
     pub fn get_value_at(&mut self, sample_index: u64) -> f32 {
         match self {
             AudioParam::Static(val) => *val,
-            AudioParam::Linked(param) => param.get(),
+            AudioParam::Linked(param) => {
+                let mut buf = [0.0];
+                param.fill(&mut buf);
+                buf[0]
+            }
             AudioParam::Dynamic(processor) => {
                 let mut buf = [0.0];
                 processor.process(&mut buf, sample_index);
@@ -59,17 +77,30 @@ impl AudioParam {
         }
     }
 
-    /// Sets the sample rate for dynamic parameters.
+    /// Sets the sample rate for dynamic and smoothed linked parameters.
     pub fn set_sample_rate(&mut self, sample_rate: f32) {
-        if let AudioParam::Dynamic(p) = self {
-            p.set_sample_rate(sample_rate);
+        match self {
+            AudioParam::Dynamic(p) => p.set_sample_rate(sample_rate),
+            AudioParam::Linked(p) => p.set_sample_rate(sample_rate),
+            AudioParam::Static(_) => {}
         }
     }
 
     /// Resets the state of dynamic parameters.
+    ///
+    /// Linked parameters jump to their current value.
     pub fn reset(&mut self) {
+        match self {
+            AudioParam::Dynamic(p) => p.reset(),
+            AudioParam::Linked(p) => p.snap(),
+            AudioParam::Static(_) => {}
+        }
+    }
+
+    /// Pre-allocates the buffers of dynamic parameters for blocks of up to `max_block_size`.
+    pub fn prepare(&mut self, max_block_size: usize) {
         if let AudioParam::Dynamic(p) = self {
-            p.reset();
+            p.prepare(max_block_size);
         }
     }
 
@@ -99,6 +130,74 @@ impl AudioParam {
         AudioParam::Static(val)
     }
 
+    /// Creates a linked AudioParam that glides to new values over `ramp_ms` milliseconds.
+    ///
+    /// The ramp time applies to this AudioParam only; other clones of `target` are unaffected.
+    pub fn smoothed(target: Parameter, ramp_ms: f32) -> Self {
+        AudioParam::Linked(target.with_local_smoothing(ramp_ms / 1000.0))
+    }
+
+    /// Creates an AudioParam representing a note length in Seconds, synced to a tempo.
+    ///
+    /// Follows BPM changes of the clock at runtime. Use for delay times.
+    pub fn synced(note: NoteValue, clock: &TransportClock) -> Self {
+        AudioParam::Dynamic(Box::new(TempoSync::new(
+            clock.clone(),
+            note,
+            SyncUnit::Seconds,
+        )))
+    }
+
+    /// Creates an AudioParam representing one cycle per note in Hz, synced to a tempo.
+    ///
+    /// Follows BPM changes of the clock at runtime. Use for LFO and tremolo rates.
+    pub fn synced_hz(note: NoteValue, clock: &TransportClock) -> Self {
+        AudioParam::Dynamic(Box::new(TempoSync::new(clock.clone(), note, SyncUnit::Hz)))
+    }
+
+    /// Wraps a parameter so that its block-average value is recorded into a trace.
+    ///
+    /// The result is always Dynamic, so processors lose their constant fast paths while tracing.
+    #[cfg(feature = "param_trace")]
+    pub fn traced(inner: AudioParam, id: u32, trace: &crate::core::trace::ParamTrace) -> Self {
+        AudioParam::Dynamic(Box::new(crate::core::trace::TracedParam::new(
+            inner,
+            id,
+            trace.clone(),
+        )))
+    }
+
+    /// Creates an AudioParam that applies a function to every value of `inner`.
+    ///
+    /// A static `inner` is mapped once and stays static; otherwise the result is Dynamic.
+    ///
+    /// # Arguments
+    /// * `inner` - The source parameter.
+    /// * `map` - The transfer function, e.g. a response curve.
+    pub fn mapped(inner: AudioParam, map: fn(f32) -> f32) -> Self {
+        match inner {
+            AudioParam::Static(value) => AudioParam::Static(map(value)),
+            inner => AudioParam::Dynamic(Box::new(MappedParam { inner, map })),
+        }
+    }
+
+    /// Creates an AudioParam that converts a level in dB from `inner` to a linear gain.
+    pub fn db_to_linear(inner: AudioParam) -> Self {
+        Self::mapped(inner, |db| libm::powf(10.0, db / 20.0))
+    }
+
+    /// Creates an AudioParam that converts a MIDI note number (69 = A4) from `inner` to Hz.
+    /// Fractional notes are allowed, e.g. for pitch bend.
+    pub fn midi_note_to_hz(inner: AudioParam) -> Self {
+        Self::mapped(inner, |note| 440.0 * libm::exp2f((note - 69.0) / 12.0))
+    }
+
+    /// Creates an AudioParam that converts an interval in semitones from `inner` to a
+    /// frequency ratio, e.g. for detune amounts or pitch-shift factors.
+    pub fn semitones_to_ratio(inner: AudioParam) -> Self {
+        Self::mapped(inner, |semitones| libm::exp2f(semitones / 12.0))
+    }
+
     /// Returns a new static AudioParam with the current constant value.
     ///
     /// If the parameter is dynamic, returns None.
@@ -106,3 +205,123 @@ impl AudioParam {
         self.get_constant().map(AudioParam::Static)
     }
 }
+
+/// Applies a transfer function to a parameter, created by [`AudioParam::mapped`].
+struct MappedParam {
+    inner: AudioParam,
+    map: fn(f32) -> f32,
+}
+
+impl FrameProcessor<Mono> for MappedParam {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        self.inner.process(buffer, sample_index);
+        for value in buffer.iter_mut() {
+            *value = (self.map)(*value);
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.inner.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.inner.prepare(max_block_size);
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "MappedParam"
+    }
+}
+
+/// Splits a block of parameter values into runs that can be treated as constant.
+///
+/// A new segment starts wherever any of the value buffers moves further than `threshold`
+/// away from its value at the start of the current segment. Processors that normally read
+/// a parameter once per block (e.g. to compute coefficients) can use this to apply jumps,
+/// such as a gate opening mid-block, at the right sample while keeping their block-constant
+/// fast path everywhere else. Smooth modulation produces few, long segments.
+///
+/// # Arguments
+/// * `values` - Parameter buffers for the block, all of the same length.
+/// * `threshold` - Maximum deviation tolerated within one segment.
+pub fn segments<const N: usize>(values: [&[f32]; N], threshold: f32) -> Segments<'_, N> {
+    let len = values.iter().map(|v| v.len()).min().unwrap_or(0);
+    Segments {
+        values,
+        threshold,
+        start: 0,
+        len,
+    }
+}
+
+/// Iterator over the constant runs of a parameter block. See [`segments`].
+pub struct Segments<'a, const N: usize> {
+    values: [&'a [f32]; N],
+    threshold: f32,
+    start: usize,
+    len: usize,
+}
+
+impl<const N: usize> Iterator for Segments<'_, N> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Range<usize>> {
+        if self.start >= self.len {
+            return None;
+        }
+        let start = self.start;
+        let mut end = start + 1;
+        while end < self.len
+            && self
+                .values
+                .iter()
+                .all(|v| (v[end] - v[start]).abs() <= self.threshold)
+        {
+            end += 1;
+        }
+        self.start = end;
+        Some(start..end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments_split_on_jumps() {
+        let gate = [0.0, 0.0, 1.0, 1.0, 1.0];
+        let room = [0.5, 0.5, 0.5, 0.5, 0.9];
+        let found: alloc::vec::Vec<_> = segments([&gate, &room], 0.01).collect();
+        assert_eq!(found, [0..2, 2..4, 4..5]);
+
+        let ramp = [0.0, 0.004, 0.008, 0.012, 0.016];
+        let found: alloc::vec::Vec<_> = segments([&ramp], 0.01).collect();
+        assert_eq!(found, [0..3, 3..5]);
+
+        assert_eq!(segments([&[] as &[f32]], 0.01).count(), 0);
+    }
+
+    #[test]
+    fn test_unit_conversions() {
+        let gain = AudioParam::db_to_linear(AudioParam::db(-20.0));
+        assert!((gain.get_constant().unwrap() - 0.1).abs() < 1e-6);
+
+        let note = Parameter::new(81.0);
+        let mut hz = AudioParam::midi_note_to_hz(AudioParam::Linked(note.clone()));
+        assert!(hz.get_constant().is_none());
+        let mut buffer = [0.0; 4];
+        hz.process(&mut buffer, 0);
+        assert!((buffer[3] - 880.0).abs() < 1e-3);
+
+        note.set(-12.0);
+        let mut ratio = AudioParam::semitones_to_ratio(AudioParam::Linked(note));
+        ratio.process(&mut buffer, 0);
+        assert!((buffer[0] - 0.5).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::parameter::Parameter;
+use crate::core::transport::Transport;
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+
+/// How an [`AutomationLane`] moves from one point to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AutomationCurve {
+    /// A straight line.
+    #[default]
+    Linear,
+    /// A constant ratio per beat, which sounds even for frequencies and gains. Falls back
+    /// to linear when the two values are not both positive.
+    Exponential,
+    /// Holds the value until the next point.
+    Step,
+}
+
+/// A point of an [`AutomationLane`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutomationPoint {
+    /// Song position in quarter-note beats.
+    pub beat: f64,
+    /// Parameter value at this point.
+    pub value: f32,
+    /// Shape of the segment towards the next point.
+    pub curve: AutomationCurve,
+}
+
+impl AutomationPoint {
+    /// Creates a new AutomationPoint.
+    ///
+    /// # Arguments
+    /// * `beat` - Song position in beats.
+    /// * `value` - Parameter value.
+    /// * `curve` - Shape of the segment towards the next point.
+    pub fn new(beat: f64, value: f32, curve: AutomationCurve) -> Self {
+        AutomationPoint { beat, value, curve }
+    }
+}
+
+/// Drives a [`Parameter`] from a list of points on the song timeline of a [`Transport`].
+///
+/// As a processor it passes audio through and, at the start of every block, sets the
+/// parameter to the lane's value at the song position. Before the first point the value is
+/// that of the first point, after the last point that of the last one. Give the parameter
+/// a smoothing time to glide between the block steps.
+pub struct AutomationLane {
+    transport: Transport,
+    target: Parameter,
+    points: Vec<AutomationPoint>,
+}
+
+impl AutomationLane {
+    /// Creates a new AutomationLane.
+    ///
+    /// # Arguments
+    /// * `transport` - The song timeline.
+    /// * `target` - The parameter to drive.
+    /// * `points` - The automation, sorted by beat.
+    pub fn new(transport: &Transport, target: Parameter, points: Vec<AutomationPoint>) -> Self {
+        let mut lane = AutomationLane {
+            transport: transport.clone(),
+            target,
+            points: Vec::new(),
+        };
+        lane.set_points(points);
+        lane
+    }
+
+    /// Replaces the automation.
+    ///
+    /// # Arguments
+    /// * `points` - The automation, sorted by beat.
+    pub fn set_points(&mut self, points: Vec<AutomationPoint>) {
+        assert!(
+            !points.is_empty(),
+            "AutomationLane: At least one point is required."
+        );
+        assert!(
+            points.windows(2).all(|w| w[0].beat <= w[1].beat),
+            "AutomationLane: Points must be sorted by beat."
+        );
+        self.points = points;
+    }
+
+    /// Returns the points of the lane.
+    pub fn points(&self) -> &[AutomationPoint] {
+        &self.points
+    }
+
+    /// Returns the value of the lane at a song position in beats.
+    pub fn value_at(&self, beat: f64) -> f32 {
+        let points = &self.points;
+        let index = points.partition_point(|p| p.beat <= beat);
+        if index == 0 {
+            return points[0].value;
+        }
+        if index == points.len() {
+            return points[index - 1].value;
+        }
+
+        let (from, to) = (points[index - 1], points[index]);
+        let t = ((beat - from.beat) / (to.beat - from.beat)) as f32;
+        match from.curve {
+            AutomationCurve::Step => from.value,
+            AutomationCurve::Exponential if from.value > 0.0 && to.value > 0.0 => {
+                from.value * libm::powf(to.value / from.value, t)
+            }
+            _ => from.value + (to.value - from.value) * t,
+        }
+    }
+}
+
+impl<C: ChannelConfig> FrameProcessor<C> for AutomationLane {
+    fn process(&mut self, _buffer: &mut [f32], sample_index: u64) {
+        let value = self.value_at(self.transport.beats_at(sample_index));
+        self.target.set(value);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "AutomationLane"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::Mono;
+    use alloc::vec;
+
+    #[test]
+    fn test_curves_follow_transport() {
+        let transport = Transport::new(1000.0);
+        let cutoff = Parameter::new(0.0);
+        let mut lane = AutomationLane::new(
+            &transport,
+            cutoff.clone(),
+            vec![
+                AutomationPoint::new(1.0, 100.0, AutomationCurve::Exponential),
+                AutomationPoint::new(3.0, 10000.0, AutomationCurve::Step),
+                AutomationPoint::new(4.0, 0.0, AutomationCurve::Linear),
+                AutomationPoint::new(6.0, 1.0, AutomationCurve::Linear),
+            ],
+        );
+        assert_eq!(lane.value_at(0.0), 100.0);
+        assert!((lane.value_at(2.0) - 1000.0).abs() < 0.1);
+        assert_eq!(lane.value_at(3.5), 10000.0);
+        assert_eq!(lane.value_at(5.0), 0.5);
+        assert_eq!(lane.value_at(9.0), 1.0);
+
+        // 120 BPM at 1 kHz: beat 5 is sample 2500.
+        let mut timeline = transport.clone();
+        transport.seek(2500);
+        let mut buffer = [0.0; 16];
+        FrameProcessor::<Mono>::process(&mut timeline, &mut buffer, 0);
+        FrameProcessor::<Mono>::process(&mut lane, &mut buffer, 0);
+        assert_eq!(cutoff.get(), 0.5);
+    }
+
+    #[test]
+    fn test_tempo_change_keeps_position() {
+        let transport = Transport::new(1000.0);
+        let level = Parameter::new(0.0);
+        let mut lane = AutomationLane::new(
+            &transport,
+            level.clone(),
+            vec![
+                AutomationPoint::new(0.0, 0.0, AutomationCurve::Linear),
+                AutomationPoint::new(4.0, 4.0, AutomationCurve::Linear),
+            ],
+        );
+        let mut timeline = transport.clone();
+        let mut buffer = [0.0; 500];
+        let mut render = |lane: &mut AutomationLane, start: u64| {
+            FrameProcessor::<Mono>::process(&mut timeline, &mut buffer, start);
+            FrameProcessor::<Mono>::process(lane, &mut buffer, start);
+        };
+
+        // One beat at 120 BPM, then the tempo halves: the lane moves on from beat 1.
+        transport.start();
+        render(&mut lane, 0);
+        transport.set_bpm(60.0);
+        render(&mut lane, 500);
+        assert_eq!(level.get(), 1.0);
+        render(&mut lane, 1000);
+        assert_eq!(level.get(), 1.5);
+    }
+}
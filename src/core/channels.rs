@@ -1,3 +1,5 @@
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
 use crate::FrameProcessor;
 use alloc::vec::Vec;
 /// Marker type representing a Mono signal configuration (1 channel).
@@ -6,6 +8,12 @@ pub struct Mono;
 /// Marker type representing a Stereo signal configuration (2 channels, interleaved).
 pub struct Stereo;
 
+/// Marker type representing an N-channel signal configuration (interleaved).
+///
+/// Used for surround and other multi-channel layouts, e.g. `Channels<4>` (quad),
+/// `Channels<6>` (5.1) or `Channels<8>` (7.1). The channel order is up to the application.
+pub struct Channels<const N: usize>;
+
 /// Trait implemented by channel configurations to provide buffer utility methods.
 pub trait ChannelConfig: Send + Sync {
     fn num_channels() -> usize;
@@ -25,6 +33,41 @@ impl ChannelConfig for Stereo {
     }
 }
 
+impl<const N: usize> ChannelConfig for Channels<N> {
+    #[inline(always)]
+    fn num_channels() -> usize {
+        N
+    }
+}
+
+/// Returns the number of complete frames in an interleaved buffer.
+#[inline(always)]
+pub fn frame_count<C: ChannelConfig>(interleaved: &[f32]) -> usize {
+    interleaved.len() / C::num_channels()
+}
+
+/// Copies one channel of an interleaved buffer into `output`.
+///
+/// Copies as many frames as fit into `output`.
+pub fn extract_channel<C: ChannelConfig>(interleaved: &[f32], channel: usize, output: &mut [f32]) {
+    let channels = C::num_channels();
+    assert!(channel < channels, "channels: Channel index out of range.");
+    for (out, frame) in output.iter_mut().zip(interleaved.chunks_exact(channels)) {
+        *out = frame[channel];
+    }
+}
+
+/// Writes `input` into one channel of an interleaved buffer, leaving the other channels untouched.
+///
+/// Writes as many frames as are available in both buffers.
+pub fn insert_channel<C: ChannelConfig>(input: &[f32], channel: usize, interleaved: &mut [f32]) {
+    let channels = C::num_channels();
+    assert!(channel < channels, "channels: Channel index out of range.");
+    for (&sample, frame) in input.iter().zip(interleaved.chunks_exact_mut(channels)) {
+        frame[channel] = sample;
+    }
+}
+
 /// A wrapper that processes a stereo interleaved signal using two independent mono processors.
 ///
 /// This implements a "Dual Mono" topology. It splits the interleaved input buffer
@@ -98,6 +141,13 @@ where
         self.right.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.left.prepare(max_block_size);
+        self.right.prepare(max_block_size);
+        self.left_buffer.resize(max_block_size, 0.0);
+        self.right_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         self.left.reset();
         self.right.reset();
@@ -109,11 +159,28 @@ where
             .max(self.right.latency_samples())
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_processor::<Mono, _>("Left", &self.left);
+        tree.add_processor::<Mono, _>("Right", &self.right);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.add_processor::<Mono, _>("Left", &self.left);
+        report.add_processor::<Mono, _>("Right", &self.right);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "DualMono"
     }
 
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.set_routing(crate::core::graph::Routing::Parallel);
+        node.add_processor::<Mono, _>("Left", &self.left);
+        node.add_processor::<Mono, _>("Right", &self.right);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn visualize(&self, indent: usize) -> alloc::string::String {
         use core::fmt::Write;
@@ -161,6 +228,10 @@ impl<P: FrameProcessor<Mono> + Send> FrameProcessor<Stereo> for MonoToStereo<P>
         self.inner.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.inner.prepare(max_block_size);
+    }
+
     fn reset(&mut self) {
         self.inner.reset();
     }
@@ -169,11 +240,24 @@ impl<P: FrameProcessor<Mono> + Send> FrameProcessor<Stereo> for MonoToStereo<P>
         self.inner.latency_samples()
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        self.inner.parameters(tree);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        self.inner.validate(report);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "MonoToStereo Converter"
     }
 
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.add_processor::<Mono, _>("0", &self.inner);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn visualize(&self, indent: usize) -> alloc::string::String {
         use core::fmt::Write;
@@ -230,6 +314,11 @@ impl<P: FrameProcessor<Stereo> + Send> FrameProcessor<Mono> for StereoToMono<P>
         self.inner.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.inner.prepare(max_block_size);
+        self.stereo_buffer.resize(max_block_size * 2, 0.0);
+    }
+
     fn reset(&mut self) {
         self.inner.reset();
     }
@@ -238,11 +327,24 @@ impl<P: FrameProcessor<Stereo> + Send> FrameProcessor<Mono> for StereoToMono<P>
         self.inner.latency_samples()
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        self.inner.parameters(tree);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        self.inner.validate(report);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "StereoToMono Converter"
     }
 
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.add_processor::<Stereo, _>("0", &self.inner);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn visualize(&self, indent: usize) -> alloc::string::String {
         use core::fmt::Write;
@@ -254,3 +356,292 @@ impl<P: FrameProcessor<Stereo> + Send> FrameProcessor<Mono> for StereoToMono<P>
         output
     }
 }
+
+/// Applies independent instances of a Mono processor to each channel.
+///
+/// Works like [`DualMono`], but for any channel count: the first channel is compacted in
+/// place at the front of the interleaved buffer and the remaining channels are moved to a
+/// scratch buffer.
+///
+/// `PerChannel<P>` (N = 2) processes [`Stereo`] buffers; `PerChannel<P, N>` processes
+/// [`Channels<N>`] buffers.
+pub struct PerChannel<P, const N: usize = 2> {
+    pub channels: [P; N],
+    scratch: Vec<f32>,
+}
+
+impl<P: FrameProcessor<Mono>> PerChannel<P, 2> {
+    /// Creates a new Stereo PerChannel adapter.
+    ///
+    /// # Arguments
+    /// * `left` - The processor instance for the left channel.
+    /// * `right` - The processor instance for the right channel.
+    pub fn new(left: P, right: P) -> Self {
+        Self::from_array([left, right])
+    }
+}
+
+impl<P: FrameProcessor<Mono>, const N: usize> PerChannel<P, N> {
+    /// Creates a new PerChannel adapter with one processor per channel.
+    pub fn from_array(channels: [P; N]) -> Self {
+        assert!(N > 0, "PerChannel: At least one channel is required.");
+        PerChannel {
+            channels,
+            scratch: Vec::with_capacity(128 * (N - 1)),
+        }
+    }
+
+    /// Creates a new PerChannel adapter, building one instance per channel with `factory`.
+    pub fn from_fn<F: FnMut() -> P>(mut factory: F) -> Self {
+        Self::from_array(core::array::from_fn(|_| factory()))
+    }
+
+    fn process_channels(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let frames = buffer.len() / N;
+        let scratch_len = frames * (N - 1);
+
+        if self.scratch.len() < scratch_len {
+            self.scratch.resize(scratch_len, 0.0);
+        }
+        let scratch = &mut self.scratch[0..scratch_len];
+
+        // Reading index N*i while writing index i never overtakes unread samples.
+        for i in 0..frames {
+            for c in 1..N {
+                scratch[(c - 1) * frames + i] = buffer[N * i + c];
+            }
+            buffer[i] = buffer[N * i];
+        }
+
+        let (first, rest) = self.channels.split_first_mut().unwrap();
+        first.process(&mut buffer[0..frames], sample_index);
+        for (processor, plane) in rest.iter_mut().zip(scratch.chunks_exact_mut(frames.max(1))) {
+            processor.process(&mut plane[0..frames], sample_index);
+        }
+
+        for i in (0..frames).rev() {
+            buffer[N * i] = buffer[i];
+            for c in 1..N {
+                buffer[N * i + c] = scratch[(c - 1) * frames + i];
+            }
+        }
+    }
+
+    fn set_channels_sample_rate(&mut self, sample_rate: f32) {
+        for processor in &mut self.channels {
+            processor.set_sample_rate(sample_rate);
+        }
+    }
+
+    fn prepare_channels(&mut self, max_block_size: usize) {
+        for processor in &mut self.channels {
+            processor.prepare(max_block_size);
+        }
+        self.scratch.resize(max_block_size * (N - 1), 0.0);
+    }
+
+    fn reset_channels(&mut self) {
+        for processor in &mut self.channels {
+            processor.reset();
+        }
+    }
+
+    fn channels_latency(&self) -> u32 {
+        self.channels
+            .iter()
+            .map(|processor| processor.latency_samples())
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn channels_parameters(&self, tree: &mut ParameterTree) {
+        for (i, processor) in self.channels.iter().enumerate() {
+            tree.add_processor::<Mono, _>(alloc::format!("{}", i), processor);
+        }
+    }
+
+    fn validate_channels(&self, report: &mut ValidationReport) {
+        for (i, processor) in self.channels.iter().enumerate() {
+            report.add_processor::<Mono, _>(&alloc::format!("{}", i), processor);
+        }
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn describe_channels(&self, node: &mut crate::core::graph::GraphNode) {
+        node.set_routing(crate::core::graph::Routing::Parallel);
+        for (i, processor) in self.channels.iter().enumerate() {
+            node.add_processor::<Mono, _>(alloc::format!("{}", i), processor);
+        }
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn visualize_channels(&self, indent: usize) -> alloc::string::String {
+        use core::fmt::Write;
+
+        let spaces = " ".repeat(indent);
+        let mut output = alloc::string::String::new();
+        let _ = writeln!(output, "{}PerChannel Wrapper", spaces);
+
+        for (i, processor) in self.channels.iter().enumerate() {
+            let _ = writeln!(output, "{}  Channel {}:", spaces, i + 1);
+            output.push_str(&processor.visualize(indent + 4));
+        }
+
+        output
+    }
+}
+
+impl<P: FrameProcessor<Mono> + Send> FrameProcessor<Stereo> for PerChannel<P, 2> {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        self.process_channels(buffer, sample_index);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.set_channels_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.prepare_channels(max_block_size);
+    }
+
+    fn reset(&mut self) {
+        self.reset_channels();
+    }
+
+    fn latency_samples(&self) -> u32 {
+        self.channels_latency()
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        self.channels_parameters(tree);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        self.validate_channels(report);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "PerChannel"
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        self.describe_channels(node);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn visualize(&self, indent: usize) -> alloc::string::String {
+        self.visualize_channels(indent)
+    }
+}
+
+impl<P: FrameProcessor<Mono> + Send, const N: usize> FrameProcessor<Channels<N>>
+    for PerChannel<P, N>
+{
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        self.process_channels(buffer, sample_index);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.set_channels_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.prepare_channels(max_block_size);
+    }
+
+    fn reset(&mut self) {
+        self.reset_channels();
+    }
+
+    fn latency_samples(&self) -> u32 {
+        self.channels_latency()
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        self.channels_parameters(tree);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        self.validate_channels(report);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "PerChannel"
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        self.describe_channels(node);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn visualize(&self, indent: usize) -> alloc::string::String {
+        self.visualize_channels(indent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+    use crate::effects::filter::biquad::Biquad;
+    use crate::effects::utility::gain::Gain;
+    use alloc::vec::Vec;
+
+    fn lowpass() -> Biquad {
+        Biquad::new_lowpass(AudioParam::hz(1000.0), AudioParam::Static(0.707))
+    }
+
+    #[test]
+    fn test_per_channel_matches_dual_mono() {
+        let input: Vec<f32> = (0..256)
+            .map(|i| {
+                if i % 2 == 0 {
+                    (i % 7) as f32
+                } else {
+                    -((i % 5) as f32)
+                }
+            })
+            .collect();
+
+        let mut per_channel = PerChannel::<_, 2>::from_fn(lowpass);
+        let mut dual_mono = DualMono::new(lowpass(), lowpass());
+
+        let mut a = input.clone();
+        let mut b = input;
+        FrameProcessor::<Stereo>::process(&mut per_channel, &mut a, 0);
+        dual_mono.process(&mut b, 0);
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_per_channel_surround() {
+        let input: Vec<f32> = (0..6 * 64).map(|i| (i % 6) as f32 + 1.0).collect();
+
+        let mut per_channel = PerChannel::from_array([
+            Gain::new_fixed(1.0),
+            Gain::new_fixed(2.0),
+            Gain::new_fixed(3.0),
+            Gain::new_fixed(4.0),
+            Gain::new_fixed(5.0),
+            Gain::new_fixed(6.0),
+        ]);
+        let mut buffer = input.clone();
+        FrameProcessor::<Channels<6>>::process(&mut per_channel, &mut buffer, 0);
+
+        let mut channel = [0.0; 64];
+        for c in 0..6 {
+            extract_channel::<Channels<6>>(&buffer, c, &mut channel);
+            let expected = ((c + 1) * (c + 1)) as f32;
+            assert!(channel.iter().all(|&s| s == expected));
+        }
+
+        insert_channel::<Channels<6>>(&[0.0; 64], 2, &mut buffer);
+        assert_eq!(frame_count::<Channels<6>>(&buffer), 64);
+        assert_eq!(&buffer[0..6], &[1.0, 4.0, 0.0, 16.0, 25.0, 36.0]);
+    }
+}
@@ -0,0 +1,469 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::dsp_chain::crossfade;
+use crate::core::error::ValidationReport;
+use crate::core::frame_processor::FrameProcessor;
+use crate::core::parameter::Parameter;
+use crate::core::preset::ParameterTree;
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// A bounded single-producer, single-consumer ring buffer.
+///
+/// [`CommandQueue::new`] returns the two ends: a [`Producer`] for the control thread and a
+/// [`Consumer`] for the audio thread. Pushing and popping never lock or allocate, so
+/// neither side can stall the other. Items left in the ring are dropped with it.
+pub struct CommandQueue<T> {
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    // Both counters only grow; a slot index is the counter modulo the capacity.
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+// Each slot is accessed by one side at a time, handed over through the head and tail.
+unsafe impl<T: Send> Send for CommandQueue<T> {}
+unsafe impl<T: Send> Sync for CommandQueue<T> {}
+
+impl<T: Send> CommandQueue<T> {
+    /// Creates a new CommandQueue and returns its two ends.
+    ///
+    /// # Arguments
+    /// * `capacity` - Number of items the ring holds.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(capacity: usize) -> (Producer<T>, Consumer<T>) {
+        assert!(
+            capacity > 0,
+            "CommandQueue: Capacity must be at least one item."
+        );
+        let slots = (0..capacity)
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect();
+        let queue = Arc::new(CommandQueue {
+            slots,
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        });
+        (
+            Producer {
+                queue: queue.clone(),
+            },
+            Consumer { queue },
+        )
+    }
+}
+
+impl<T> CommandQueue<T> {
+    fn len(&self) -> usize {
+        let tail = self.tail.load(Ordering::Acquire);
+        tail.wrapping_sub(self.head.load(Ordering::Acquire))
+    }
+}
+
+impl<T> Drop for CommandQueue<T> {
+    fn drop(&mut self) {
+        let capacity = self.slots.len();
+        let tail = *self.tail.get_mut();
+        let mut head = *self.head.get_mut();
+        while head != tail {
+            // SAFETY: The slots between the head and the tail hold pushed items.
+            unsafe { self.slots[head % capacity].get_mut().assume_init_drop() };
+            head = head.wrapping_add(1);
+        }
+    }
+}
+
+/// The sending end of a [`CommandQueue`].
+pub struct Producer<T> {
+    queue: Arc<CommandQueue<T>>,
+}
+
+impl<T> Producer<T> {
+    /// Pushes an item; returns it back if the ring is full.
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        let queue = &*self.queue;
+        let capacity = queue.slots.len();
+        let tail = queue.tail.load(Ordering::Relaxed);
+        if tail.wrapping_sub(queue.head.load(Ordering::Acquire)) == capacity {
+            return Err(item);
+        }
+        // SAFETY: The slot is outside the readable range, so only the producer touches it.
+        unsafe { (*queue.slots[tail % capacity].get()).write(item) };
+        queue.tail.store(tail.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+
+    /// Returns the number of free slots.
+    pub fn free(&self) -> usize {
+        self.queue.slots.len() - self.queue.len()
+    }
+}
+
+/// The receiving end of a [`CommandQueue`].
+pub struct Consumer<T> {
+    queue: Arc<CommandQueue<T>>,
+}
+
+impl<T> Consumer<T> {
+    /// Pops the oldest item, or returns `None` if the ring is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let queue = &*self.queue;
+        let head = queue.head.load(Ordering::Relaxed);
+        if head == queue.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        // SAFETY: The slot was written before the tail moved past it, and the producer does
+        // not reuse it until the head moves on.
+        let item = unsafe { (*queue.slots[head % queue.slots.len()].get()).assume_init_read() };
+        queue.head.store(head.wrapping_add(1), Ordering::Release);
+        Some(item)
+    }
+
+    /// Returns the number of items waiting.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns true if no items are waiting.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A note event delivered to the note handler of an [`AudioSide`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoteEvent {
+    /// Starts a note.
+    On { key: u8, velocity: f32 },
+    /// Releases a note.
+    Off { key: u8 },
+}
+
+/// A change sent from a [`Controller`] to its [`AudioSide`].
+pub enum Command<C: ChannelConfig> {
+    /// Sets a parameter to a value.
+    SetParameter(Parameter, f32),
+    /// Replaces the processor. The new processor should already have the stream's sample
+    /// rate and be prepared, since preparing may allocate.
+    SwapProcessor(Box<dyn FrameProcessor<C> + Send>),
+    /// Replaces the processor, crossfading from the old one over a time in milliseconds.
+    /// Both run in parallel during the fade; the old one is retired when it ends.
+    SwapProcessorCrossfaded(Box<dyn FrameProcessor<C> + Send>, f32),
+    /// Passes a note event to the note handler.
+    Note(NoteEvent),
+}
+
+/// The control-thread end of a command channel, created by [`command_channel`].
+///
+/// Commands are applied by the [`AudioSide`] at the start of its next block. Processors
+/// replaced by a swap come back through [`take_retired`](Self::take_retired), so they are
+/// dropped here rather than on the audio thread.
+pub struct Controller<C: ChannelConfig> {
+    commands: Producer<Command<C>>,
+    retired: Consumer<Box<dyn FrameProcessor<C> + Send>>,
+}
+
+impl<C: ChannelConfig> Controller<C> {
+    /// Sends a command; returns it back if the queue is full.
+    pub fn send(&mut self, command: Command<C>) -> Result<(), Command<C>> {
+        self.commands.push(command)
+    }
+
+    /// Sets a parameter at the next block boundary.
+    pub fn set_parameter(&mut self, parameter: &Parameter, value: f32) -> Result<(), Command<C>> {
+        self.send(Command::SetParameter(parameter.clone(), value))
+    }
+
+    /// Replaces the processor at the next block boundary.
+    pub fn swap_processor(
+        &mut self,
+        processor: Box<dyn FrameProcessor<C> + Send>,
+    ) -> Result<(), Command<C>> {
+        self.send(Command::SwapProcessor(processor))
+    }
+
+    /// Replaces the processor at the next block boundary without clicks, crossfading
+    /// linearly from the old processor over `fade_ms`.
+    pub fn swap_processor_crossfaded(
+        &mut self,
+        processor: Box<dyn FrameProcessor<C> + Send>,
+        fade_ms: f32,
+    ) -> Result<(), Command<C>> {
+        self.send(Command::SwapProcessorCrossfaded(processor, fade_ms))
+    }
+
+    /// Sends a note on at the next block boundary.
+    pub fn note_on(&mut self, key: u8, velocity: f32) -> Result<(), Command<C>> {
+        self.send(Command::Note(NoteEvent::On { key, velocity }))
+    }
+
+    /// Sends a note off at the next block boundary.
+    pub fn note_off(&mut self, key: u8) -> Result<(), Command<C>> {
+        self.send(Command::Note(NoteEvent::Off { key }))
+    }
+
+    /// Takes the processors that were replaced by swaps, once their crossfade (if any) is
+    /// over.
+    pub fn take_retired(&mut self) -> Vec<Box<dyn FrameProcessor<C> + Send>> {
+        let mut retired = Vec::new();
+        while let Some(processor) = self.retired.pop() {
+            retired.push(processor);
+        }
+        retired
+    }
+}
+
+/// The audio-thread end of a command channel, created by [`command_channel`].
+///
+/// Runs its processor and, before each block, applies every command that has arrived.
+/// If the controller never collects retired processors and their ring fills up, further
+/// replaced processors are dropped on the audio thread.
+pub struct AudioSide<C: ChannelConfig> {
+    processor: Box<dyn FrameProcessor<C> + Send>,
+    commands: Consumer<Command<C>>,
+    retired: Producer<Box<dyn FrameProcessor<C> + Send>>,
+    note_handler: Option<Box<dyn FnMut(NoteEvent) + Send>>,
+    outgoing: Option<OutgoingProcessor<C>>,
+    fade_buffer: Vec<f32>,
+    sample_rate: f32,
+}
+
+/// A processor being faded out after a crossfaded swap.
+struct OutgoingProcessor<C: ChannelConfig> {
+    processor: Box<dyn FrameProcessor<C> + Send>,
+    position: u32,
+    length: u32,
+}
+
+impl<C: ChannelConfig> AudioSide<C> {
+    /// Sets the handler that receives note events and returns the audio side.
+    ///
+    /// The handler runs on the audio thread, so it must not block or allocate; setting
+    /// the gate and frequency `Parameter`s of a voice is the typical use.
+    pub fn with_note_handler(mut self, handler: impl FnMut(NoteEvent) + Send + 'static) -> Self {
+        self.note_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Returns the current processor.
+    pub fn processor(&self) -> &(dyn FrameProcessor<C> + Send) {
+        self.processor.as_ref()
+    }
+
+    fn apply_commands(&mut self) {
+        while let Some(command) = self.commands.pop() {
+            match command {
+                Command::SetParameter(parameter, value) => parameter.set(value),
+                Command::SwapProcessor(processor) => {
+                    let old = core::mem::replace(&mut self.processor, processor);
+                    let _ = self.retired.push(old);
+                }
+                Command::SwapProcessorCrossfaded(processor, fade_ms) => {
+                    let old = core::mem::replace(&mut self.processor, processor);
+                    self.retire_outgoing();
+                    let length = libm::roundf(fade_ms.max(0.0) * 0.001 * self.sample_rate) as u32;
+                    if length > 0 {
+                        self.outgoing = Some(OutgoingProcessor {
+                            processor: old,
+                            position: 0,
+                            length,
+                        });
+                    } else {
+                        let _ = self.retired.push(old);
+                    }
+                }
+                Command::Note(event) => {
+                    if let Some(handler) = self.note_handler.as_mut() {
+                        handler(event);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Hands the processor being faded out, if any, back to the controller.
+    fn retire_outgoing(&mut self) {
+        if let Some(fade) = self.outgoing.take() {
+            let _ = self.retired.push(fade.processor);
+        }
+    }
+}
+
+/// Creates a connected [`Controller`] and [`AudioSide`] around a processor.
+///
+/// # Arguments
+/// * `processor` - The processor run by the audio side.
+/// * `capacity` - Number of commands (and retired processors) that can be in flight.
+pub fn command_channel<C: ChannelConfig>(
+    processor: Box<dyn FrameProcessor<C> + Send>,
+    capacity: usize,
+) -> (Controller<C>, AudioSide<C>) {
+    let (command_tx, command_rx) = CommandQueue::new(capacity);
+    let (retired_tx, retired_rx) = CommandQueue::new(capacity);
+    (
+        Controller {
+            commands: command_tx,
+            retired: retired_rx,
+        },
+        AudioSide {
+            processor,
+            commands: command_rx,
+            retired: retired_tx,
+            note_handler: None,
+            outgoing: None,
+            fade_buffer: Vec::new(),
+            sample_rate: 44100.0,
+        },
+    )
+}
+
+impl<C: ChannelConfig> FrameProcessor<C> for AudioSide<C> {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        self.apply_commands();
+        let Some(fade) = self.outgoing.as_mut() else {
+            self.processor.process(buffer, sample_index);
+            return;
+        };
+
+        let len = buffer.len();
+        if self.fade_buffer.len() < len {
+            self.fade_buffer.resize(len, 0.0);
+        }
+        let old = &mut self.fade_buffer[0..len];
+        old.copy_from_slice(buffer);
+        fade.processor.process(old, sample_index);
+        self.processor.process(buffer, sample_index);
+        crossfade::<C>(buffer, old, &mut fade.position, fade.length);
+        if fade.position >= fade.length {
+            self.retire_outgoing();
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.processor.set_sample_rate(sample_rate);
+        if let Some(fade) = self.outgoing.as_mut() {
+            fade.processor.set_sample_rate(sample_rate);
+        }
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.processor.prepare(max_block_size);
+        if let Some(fade) = self.outgoing.as_mut() {
+            fade.processor.prepare(max_block_size);
+        }
+        self.fade_buffer
+            .resize(max_block_size * C::num_channels(), 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.retire_outgoing();
+        self.processor.reset();
+    }
+
+    fn latency_samples(&self) -> u32 {
+        self.processor.latency_samples()
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        self.processor.parameters(tree);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        self.processor.validate(report);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "AudioSide"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+    use crate::core::channels::Mono;
+    use crate::effects::utility::gain::Gain;
+    use crate::effects::utility::passthrough::Passthrough;
+
+    #[test]
+    fn test_ring_wraps_and_fills() {
+        let (mut tx, mut rx) = CommandQueue::new(3);
+        for round in 0..5 {
+            assert_eq!(tx.push(round), Ok(()));
+            assert_eq!(tx.push(round + 10), Ok(()));
+            assert_eq!(rx.len(), 2);
+            assert_eq!(rx.pop(), Some(round));
+            assert_eq!(rx.pop(), Some(round + 10));
+            assert!(rx.is_empty());
+        }
+        for i in 0..3 {
+            assert_eq!(tx.push(i), Ok(()));
+        }
+        assert_eq!(tx.push(3), Err(3));
+        assert_eq!(tx.free(), 0);
+
+        // Items still in the ring are dropped with it.
+        let marker = Arc::new(());
+        let (mut tx, rx) = CommandQueue::new(2);
+        let _ = tx.push(marker.clone());
+        drop((tx, rx));
+        assert_eq!(Arc::strong_count(&marker), 1);
+    }
+
+    #[test]
+    fn test_commands_apply_at_block_start() {
+        let gain = Parameter::new(1.0);
+        let (mut controller, audio) =
+            command_channel::<Mono>(Box::new(Gain::new(AudioParam::Linked(gain.clone()))), 8);
+        let notes = Parameter::new(0.0);
+        let note_target = notes.clone();
+        let mut audio = audio.with_note_handler(move |event| {
+            if let NoteEvent::On { key, .. } = event {
+                note_target.set(key as f32);
+            }
+        });
+
+        assert!(controller.set_parameter(&gain, 0.5).is_ok());
+        assert!(controller.note_on(60, 1.0).is_ok());
+        assert_eq!(gain.get(), 1.0);
+
+        let mut buffer = [1.0; 4];
+        audio.process(&mut buffer, 0);
+        assert_eq!(buffer, [0.5; 4]);
+        assert_eq!(notes.get(), 60.0);
+
+        assert!(controller
+            .swap_processor(Box::new(Passthrough::new()))
+            .is_ok());
+        let mut buffer = [1.0; 4];
+        audio.process(&mut buffer, 4);
+        assert_eq!(buffer, [1.0; 4]);
+        assert_eq!(controller.take_retired().len(), 1);
+    }
+
+    #[test]
+    fn test_crossfaded_swap_retires_after_fade() {
+        let (mut controller, mut audio) =
+            command_channel::<Mono>(Box::new(Gain::new_fixed(0.0)), 4);
+        audio.set_sample_rate(1000.0);
+        audio.prepare(4);
+
+        // A 6 ms fade at 1 kHz: the old gain of 0 fades into the passthrough over 6 frames.
+        assert!(controller
+            .swap_processor_crossfaded(Box::new(Passthrough::new()), 6.0)
+            .is_ok());
+        let mut buffer = [1.0; 4];
+        audio.process(&mut buffer, 0);
+        assert_eq!(buffer, [0.0, 1.0 / 6.0, 2.0 / 6.0, 0.5]);
+        assert!(controller.take_retired().is_empty());
+
+        let mut buffer = [1.0; 4];
+        audio.process(&mut buffer, 4);
+        assert_eq!(buffer, [4.0 / 6.0, 5.0 / 6.0, 1.0, 1.0]);
+        assert_eq!(controller.take_retired().len(), 1);
+    }
+}
@@ -0,0 +1,185 @@
+use wide::{f32x4, CmpLt};
+
+/// Magnitude below which feedback state is flushed to zero, roughly -400 dBFS.
+///
+/// When a reverb or filter tail decays towards silence, its feedback state ends up below
+/// `f32::MIN_POSITIVE`. Many FPUs handle these subnormal numbers in microcode, which can
+/// make a silent chain many times more expensive than a busy one. The reverbs, delays and
+/// filters of this crate flush their state with [`flush_denormal`] or [`flush_denormal4`],
+/// so every target is protected; [`FlushToZeroGuard`] (feature `flush-to-zero`) also covers
+/// user processors.
+pub const DENORMAL_THRESHOLD: f32 = 1e-20;
+
+/// Returns `x`, or 0.0 if its magnitude is below [`DENORMAL_THRESHOLD`].
+#[inline(always)]
+pub fn flush_denormal(x: f32) -> f32 {
+    if x.abs() < DENORMAL_THRESHOLD {
+        0.0
+    } else {
+        x
+    }
+}
+
+/// Flushes each lane of `x` like [`flush_denormal`].
+#[inline(always)]
+pub fn flush_denormal4(x: f32x4) -> f32x4 {
+    x.abs()
+        .simd_lt(f32x4::splat(DENORMAL_THRESHOLD))
+        .blend(f32x4::ZERO, x)
+}
+
+/// Enables flush-to-zero and denormals-are-zero on the current thread while it lives.
+///
+/// Create one at the top of the audio callback; the previous FPU mode is restored when the
+/// guard is dropped. On targets without a supported control register the guard does
+/// nothing, see [`is_supported`](Self::is_supported). Cortex-M FPUs handle subnormals
+/// without a penalty, so they do not need it.
+#[cfg(feature = "flush-to-zero")]
+pub struct FlushToZeroGuard {
+    previous: usize,
+}
+
+#[cfg(feature = "flush-to-zero")]
+impl FlushToZeroGuard {
+    /// Creates a new FlushToZeroGuard, switching the FPU to flush-to-zero mode.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        let previous = fpu::read();
+        fpu::write(previous | fpu::FLUSH_BITS);
+        FlushToZeroGuard { previous }
+    }
+
+    /// Returns true if the guard can change the FPU mode on this target.
+    pub const fn is_supported() -> bool {
+        fpu::FLUSH_BITS != 0
+    }
+}
+
+#[cfg(feature = "flush-to-zero")]
+impl Drop for FlushToZeroGuard {
+    fn drop(&mut self) {
+        fpu::write(self.previous);
+    }
+}
+
+#[cfg(all(
+    feature = "flush-to-zero",
+    any(
+        target_arch = "x86_64",
+        all(target_arch = "x86", target_feature = "sse")
+    )
+))]
+mod fpu {
+    use core::arch::asm;
+
+    /// MXCSR flush-to-zero (bit 15) and denormals-are-zero (bit 6).
+    pub(super) const FLUSH_BITS: usize = (1 << 15) | (1 << 6);
+
+    pub(super) fn read() -> usize {
+        let mut csr: u32 = 0;
+        // SAFETY: Stores MXCSR to a local.
+        unsafe {
+            asm!("stmxcsr [{}]", in(reg) &mut csr, options(nostack, preserves_flags));
+        }
+        csr as usize
+    }
+
+    pub(super) fn write(value: usize) {
+        let csr = value as u32;
+        // SAFETY: Only the rounding and flush bits that `read` returned are changed.
+        unsafe {
+            asm!("ldmxcsr [{}]", in(reg) &csr, options(nostack, readonly, preserves_flags));
+        }
+    }
+}
+
+#[cfg(all(feature = "flush-to-zero", target_arch = "aarch64"))]
+mod fpu {
+    use core::arch::asm;
+
+    /// FPCR flush-to-zero (bit 24).
+    pub(super) const FLUSH_BITS: usize = 1 << 24;
+
+    pub(super) fn read() -> usize {
+        let fpcr: u64;
+        // SAFETY: Reads the floating-point control register.
+        unsafe {
+            asm!("mrs {}, fpcr", out(reg) fpcr, options(nomem, nostack, preserves_flags));
+        }
+        fpcr as usize
+    }
+
+    pub(super) fn write(value: usize) {
+        // SAFETY: Only the bits that `read` returned plus FZ are written.
+        unsafe {
+            asm!("msr fpcr, {}", in(reg) value as u64, options(nomem, nostack, preserves_flags));
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "flush-to-zero",
+    not(any(
+        target_arch = "x86_64",
+        all(target_arch = "x86", target_feature = "sse"),
+        target_arch = "aarch64"
+    ))
+))]
+mod fpu {
+    pub(super) const FLUSH_BITS: usize = 0;
+
+    pub(super) fn read() -> usize {
+        0
+    }
+
+    pub(super) fn write(_value: usize) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+    use crate::effects::time::reverb::Reverb;
+    use crate::FrameProcessor;
+
+    #[test]
+    fn test_reverb_tail_reaches_zero() {
+        assert_eq!(flush_denormal(1e-30), 0.0);
+        assert_eq!(flush_denormal(-0.5), -0.5);
+        let lanes = flush_denormal4(f32x4::new([1e-25, 1.0, -1e-30, -2.0])).to_array();
+        assert_eq!(lanes, [0.0, 1.0, 0.0, -2.0]);
+
+        // The tail decays to exact zero instead of lingering in subnormals.
+        let mut reverb =
+            Reverb::new_with_params(AudioParam::Static(0.2), AudioParam::Static(0.5), 0);
+        let mut buffer = [0.0; 1024];
+        buffer[0] = 1.0;
+        let mut heard = false;
+        let mut silent = false;
+        for block in 0..4000 {
+            reverb.process(&mut buffer, block * 512);
+            assert!(buffer.iter().all(|s| *s == 0.0 || s.is_normal()));
+            heard |= buffer.iter().any(|s| *s != 0.0);
+            silent = heard && buffer.iter().all(|s| *s == 0.0);
+            if silent {
+                break;
+            }
+            buffer.fill(0.0);
+        }
+        assert!(silent);
+    }
+
+    #[cfg(feature = "flush-to-zero")]
+    #[test]
+    fn test_guard_restores_mode() {
+        let before = fpu::read();
+        {
+            let _guard = FlushToZeroGuard::new();
+            if FlushToZeroGuard::is_supported() {
+                let tiny = core::hint::black_box(f32::MIN_POSITIVE);
+                assert_eq!(core::hint::black_box(tiny * 0.5), 0.0);
+            }
+        }
+        assert_eq!(fpu::read(), before);
+    }
+}
@@ -3,11 +3,23 @@ use super::parallel_mixer::ParallelMixer;
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::{ChannelConfig, Mono, Stereo};
 use crate::core::channels::{MonoToStereo, StereoToMono};
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
+#[cfg(feature = "profiling")]
+use crate::core::profiler::{Clock, ProbeHandle};
 use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
 
+/// A processor being faded out after `DspChain::replace_crossfaded`.
+struct OutgoingProcessor<C: ChannelConfig> {
+    index: usize,
+    processor: Box<dyn FrameProcessor<C> + Send>,
+    position: u32,
+    length: u32,
+}
+
 /// A chain of DSP processors.
 ///
 /// Processes audio sequentially through a list of processors.
@@ -15,6 +27,13 @@ use alloc::vec::Vec;
 pub struct DspChain<C: ChannelConfig> {
     processors: Vec<Box<dyn FrameProcessor<C> + Send>>,
     sample_rate: f32,
+    outgoing: Vec<OutgoingProcessor<C>>,
+    fade_buffer: Vec<f32>,
+    max_block_size: usize,
+    #[cfg(feature = "profiling")]
+    clock: Option<Clock>,
+    #[cfg(feature = "profiling")]
+    probes: Vec<ProbeHandle>,
 }
 
 impl<C: ChannelConfig + 'static> DspChain<C> {
@@ -24,13 +43,48 @@ impl<C: ChannelConfig + 'static> DspChain<C> {
         DspChain {
             processors: vec![Box::new(first)],
             sample_rate,
+            outgoing: Vec::new(),
+            fade_buffer: Vec::with_capacity(128),
+            max_block_size: 0,
+            #[cfg(feature = "profiling")]
+            clock: None,
+            #[cfg(feature = "profiling")]
+            probes: vec![ProbeHandle::new()],
         }
     }
 
+    /// Times every processor of the chain with `clock` and returns the chain.
+    ///
+    /// Each `process` call of each processor (including the outgoing one during
+    /// `replace_crossfaded`) is measured, and the statistics are published to the handles
+    /// returned by [`probes`](Self::probes).
+    #[cfg(feature = "profiling")]
+    pub fn with_profiling(mut self, clock: Clock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Returns the timing handles of the processors, in chain order.
+    ///
+    /// The handles stay valid across `replace_crossfaded`; they only record while profiling
+    /// is enabled with [`with_profiling`](Self::with_profiling).
+    #[cfg(feature = "profiling")]
+    pub fn probes(&self) -> Vec<ProbeHandle> {
+        self.probes.clone()
+    }
+
+    /// Appends a boxed processor, with a timing handle when profiling is compiled in.
+    fn push(&mut self, processor: Box<dyn FrameProcessor<C> + Send>) {
+        self.processors.push(processor);
+        #[cfg(feature = "profiling")]
+        self.probes.push(ProbeHandle::new());
+    }
+
     /// Appends a processor to the chain.
     pub fn and(mut self, mut processor: impl FrameProcessor<C> + Send + 'static) -> Self {
         processor.set_sample_rate(self.sample_rate);
-        self.processors.push(Box::new(processor));
+        self.prepare_new(&mut processor);
+        self.push(Box::new(processor));
         self
     }
 
@@ -41,8 +95,9 @@ impl<C: ChannelConfig + 'static> DspChain<C> {
         mut processor: impl FrameProcessor<C> + Send + 'static,
     ) -> Self {
         processor.set_sample_rate(self.sample_rate);
-        let mixed = ParallelMixer::new(mix, processor);
-        self.processors.push(Box::new(mixed));
+        let mut mixed = ParallelMixer::new(mix, processor);
+        self.prepare_new(&mut mixed);
+        self.push(Box::new(mixed));
         self
     }
 
@@ -55,16 +110,112 @@ impl<C: ChannelConfig + 'static> DspChain<C> {
         processor.set_sample_rate(self.sample_rate);
         let mut mixed = ParallelMixer::new(0.0, processor);
         mixed.set_mix(mix);
-        self.processors.push(Box::new(mixed));
+        self.prepare_new(&mut mixed);
+        self.push(Box::new(mixed));
         self
     }
 
+    /// Replaces the processor at `index` without clicks.
+    ///
+    /// The old and new processors run in parallel while the output crossfades linearly from
+    /// old to new over `fade_ms`. The old processor is dropped once the fade completes. If
+    /// a crossfade is already running at `index`, its outgoing processor is dropped immediately.
+    ///
+    /// The drop happens wherever the chain runs. To swap a processor that is running on the
+    /// audio thread, send the replacement through
+    /// [`Controller::swap_processor_crossfaded`](crate::core::command::Controller::swap_processor_crossfaded),
+    /// which hands the old processor back to the control thread.
+    ///
+    /// # Arguments
+    /// * `index` - Position of the processor in the chain (0 = first).
+    /// * `processor` - The new processor.
+    /// * `fade_ms` - Crossfade time in milliseconds.
+    pub fn replace_crossfaded(
+        &mut self,
+        index: usize,
+        mut processor: impl FrameProcessor<C> + Send + 'static,
+        fade_ms: f32,
+    ) {
+        assert!(
+            index < self.processors.len(),
+            "DspChain: Processor index out of range."
+        );
+        processor.set_sample_rate(self.sample_rate);
+        self.prepare_new(&mut processor);
+        let old = core::mem::replace(&mut self.processors[index], Box::new(processor));
+
+        self.outgoing.retain(|fade| fade.index != index);
+        let length = libm::roundf(fade_ms.max(0.0) * 0.001 * self.sample_rate) as u32;
+        if length > 0 {
+            self.outgoing.push(OutgoingProcessor {
+                index,
+                processor: old,
+                position: 0,
+                length,
+            });
+        }
+    }
+
+    /// Prepares a processor added after `prepare` was called on the chain.
+    fn prepare_new(&self, processor: &mut impl FrameProcessor<C>) {
+        if self.max_block_size > 0 {
+            processor.prepare(self.max_block_size);
+        }
+    }
+
+    /// Returns true while a crossfaded replacement is in progress.
+    pub fn is_crossfading(&self) -> bool {
+        !self.outgoing.is_empty()
+    }
+
     /// Returns a graph visualization of the entire chain.
     pub fn get_graph(&self) -> String {
         self.visualize(0)
     }
 }
 
+impl<C: ChannelConfig> DspChain<C> {
+    /// Runs the processor at `index`, crossfading from its outgoing processor if any.
+    fn process_at(&mut self, index: usize, buffer: &mut [f32], sample_index: u64) {
+        let p = &mut self.processors[index];
+        let Some(fade) = self.outgoing.iter_mut().find(|fade| fade.index == index) else {
+            p.process(buffer, sample_index);
+            return;
+        };
+
+        let len = buffer.len();
+        if self.fade_buffer.len() < len {
+            self.fade_buffer.resize(len, 0.0);
+        }
+        let old = &mut self.fade_buffer[0..len];
+        old.copy_from_slice(buffer);
+        fade.processor.process(old, sample_index);
+        p.process(buffer, sample_index);
+        crossfade::<C>(buffer, old, &mut fade.position, fade.length);
+    }
+}
+
+/// Crossfades linearly from `old` to the frames in `buffer`, advancing `position` by one
+/// per frame of a fade `length` frames long.
+pub(crate) fn crossfade<C: ChannelConfig>(
+    buffer: &mut [f32],
+    old: &[f32],
+    position: &mut u32,
+    length: u32,
+) {
+    let channels = C::num_channels();
+    for (new_frame, old_frame) in buffer
+        .chunks_exact_mut(channels)
+        .zip(old.chunks_exact(channels))
+    {
+        let gain = (*position as f32 / length as f32).min(1.0);
+        for (n, &o) in new_frame.iter_mut().zip(old_frame.iter()) {
+            *n = o + (*n - o) * gain;
+        }
+        *position = position.saturating_add(1);
+    }
+}
+
 impl DspChain<Mono> {
     /// Converts the Mono chain into a Stereo chain.
     ///
@@ -92,9 +243,28 @@ impl DspChain<Stereo> {
 
 impl<C: ChannelConfig> FrameProcessor<C> for DspChain<C> {
     fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
-        for p in &mut self.processors {
-            p.process(buffer, sample_index);
+        #[cfg(feature = "profiling")]
+        if let Some(clock) = self.clock {
+            for i in 0..self.processors.len() {
+                let start = clock();
+                self.process_at(i, buffer, sample_index);
+                self.probes[i].record(clock().saturating_sub(start));
+            }
+            self.outgoing.retain(|fade| fade.position < fade.length);
+            return;
+        }
+
+        if self.outgoing.is_empty() {
+            for p in &mut self.processors {
+                p.process(buffer, sample_index);
+            }
+            return;
+        }
+
+        for i in 0..self.processors.len() {
+            self.process_at(i, buffer, sample_index);
         }
+        self.outgoing.retain(|fade| fade.position < fade.length);
     }
 
     fn set_sample_rate(&mut self, sample_rate: f32) {
@@ -102,9 +272,25 @@ impl<C: ChannelConfig> FrameProcessor<C> for DspChain<C> {
         for p in &mut self.processors {
             p.set_sample_rate(sample_rate);
         }
+        for fade in &mut self.outgoing {
+            fade.processor.set_sample_rate(sample_rate);
+        }
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.max_block_size = max_block_size;
+        for p in &mut self.processors {
+            p.prepare(max_block_size);
+        }
+        for fade in &mut self.outgoing {
+            fade.processor.prepare(max_block_size);
+        }
+        self.fade_buffer
+            .resize(max_block_size * C::num_channels(), 0.0);
     }
 
     fn reset(&mut self) {
+        self.outgoing.clear();
         for p in &mut self.processors {
             p.reset();
         }
@@ -114,11 +300,30 @@ impl<C: ChannelConfig> FrameProcessor<C> for DspChain<C> {
         self.processors.iter().map(|p| p.latency_samples()).sum()
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        for (i, p) in self.processors.iter().enumerate() {
+            tree.add_processor(alloc::format!("{}", i), p);
+        }
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        for (i, p) in self.processors.iter().enumerate() {
+            report.add_processor(&alloc::format!("{}", i), p);
+        }
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "DspChain"
     }
 
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        for (i, p) in self.processors.iter().enumerate() {
+            node.add_processor::<C, _>(alloc::format!("{}", i), p);
+        }
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn visualize(&self, indent: usize) -> String {
         use core::fmt::Write;
@@ -126,10 +331,10 @@ impl<C: ChannelConfig> FrameProcessor<C> for DspChain<C> {
         let spaces = " ".repeat(indent);
         let arrow_spaces = " ".repeat(indent + 2);
 
-        let channel_type = if C::num_channels() == 1 {
-            "Mono"
-        } else {
-            "Stereo"
+        let channel_type = match C::num_channels() {
+            1 => String::from("Mono"),
+            2 => String::from("Stereo"),
+            n => alloc::format!("{} channels", n),
         };
 
         let _ = writeln!(output, "{}DspChain ({})", spaces, channel_type);
@@ -151,3 +356,26 @@ impl<C: ChannelConfig> FrameProcessor<C> for DspChain<C> {
         output
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::utility::gain::Gain;
+
+    #[test]
+    fn test_replace_crossfaded() {
+        let mut chain =
+            DspChain::<Mono>::new(Gain::new_fixed(1.0), 1000.0).and(Gain::new_fixed(2.0));
+        chain.replace_crossfaded(1, Gain::new_fixed(4.0), 4.0);
+        assert!(chain.is_crossfading());
+
+        let mut buffer = [1.0; 6];
+        chain.process(&mut buffer, 0);
+        assert_eq!(buffer, [2.0, 2.5, 3.0, 3.5, 4.0, 4.0]);
+        assert!(!chain.is_crossfading());
+
+        let mut buffer = [1.0; 2];
+        chain.process(&mut buffer, 6);
+        assert_eq!(buffer, [4.0; 2]);
+    }
+}
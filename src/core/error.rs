@@ -0,0 +1,439 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::ChannelConfig;
+use crate::FrameProcessor;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A configuration a processor cannot run with.
+///
+/// Returned by the fallible `try_new` constructors and collected by
+/// [`FrameProcessor::validate`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DspError {
+    /// A parameter value is outside the range the processor supports.
+    OutOfRange {
+        processor: &'static str,
+        parameter: &'static str,
+        value: f32,
+        min: f32,
+        max: f32,
+    },
+    /// A size or count (buffer length, voices, taps) is not supported.
+    InvalidCount {
+        processor: &'static str,
+        parameter: &'static str,
+        value: usize,
+        min: usize,
+        max: usize,
+    },
+    /// Any other invalid configuration.
+    Invalid {
+        processor: &'static str,
+        message: &'static str,
+    },
+}
+
+impl DspError {
+    /// Returns an error if `value` is outside `min..=max` (or NaN).
+    pub fn check_value(
+        processor: &'static str,
+        parameter: &'static str,
+        value: f32,
+        min: f32,
+        max: f32,
+    ) -> Result<(), DspError> {
+        if (min..=max).contains(&value) {
+            Ok(())
+        } else {
+            Err(DspError::OutOfRange {
+                processor,
+                parameter,
+                value,
+                min,
+                max,
+            })
+        }
+    }
+
+    /// Returns an error if `value` is outside `min..=max`.
+    pub fn check_count(
+        processor: &'static str,
+        parameter: &'static str,
+        value: usize,
+        min: usize,
+        max: usize,
+    ) -> Result<(), DspError> {
+        if (min..=max).contains(&value) {
+            Ok(())
+        } else {
+            Err(DspError::InvalidCount {
+                processor,
+                parameter,
+                value,
+                min,
+                max,
+            })
+        }
+    }
+}
+
+impl core::fmt::Display for DspError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DspError::OutOfRange {
+                processor,
+                parameter,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "{}: {} is {} but must be within {} - {}",
+                processor, parameter, value, min, max
+            ),
+            DspError::InvalidCount {
+                processor,
+                parameter,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "{}: {} is {} but must be within {} - {}",
+                processor, parameter, value, min, max
+            ),
+            DspError::Invalid { processor, message } => write!(f, "{}: {}", processor, message),
+        }
+    }
+}
+
+/// A [`DspError`] found by a validation pass, with the path of the processor in the chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    /// `/`-separated position of the processor, as in a `ParameterTree` (e.g. `2/0`).
+    pub path: String,
+    pub error: DspError,
+}
+
+impl core::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.error)
+        } else {
+            write!(f, "{} ({})", self.error, self.path)
+        }
+    }
+}
+
+/// Collects the configuration problems of a processor and everything it contains.
+///
+/// Processors check their settings in [`FrameProcessor::validate`]; containers such as
+/// `DspChain` add one child per contained processor, named by its position. Only values
+/// known before processing are checked: static parameters and the current value of linked
+/// ones. Run it after building a chain, before handing it to the audio thread.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    path: String,
+    issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Creates a new, empty ValidationReport.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validates a processor.
+    pub fn of<C: ChannelConfig, P: FrameProcessor<C> + ?Sized>(processor: &P) -> Self {
+        let mut report = ValidationReport::new();
+        processor.validate(&mut report);
+        report
+    }
+
+    /// Records an error at the current path.
+    pub fn push(&mut self, error: DspError) {
+        self.issues.push(ValidationIssue {
+            path: self.path.clone(),
+            error,
+        });
+    }
+
+    /// Records the error of a check, if any.
+    pub fn check(&mut self, result: Result<(), DspError>) {
+        if let Err(error) = result {
+            self.push(error);
+        }
+    }
+
+    /// Checks that a parameter's value is within `min..=max`, if it is known.
+    ///
+    /// Dynamic parameters are skipped, since their values only exist while processing.
+    pub fn check_param(
+        &mut self,
+        processor: &'static str,
+        parameter: &'static str,
+        param: &AudioParam,
+        min: f32,
+        max: f32,
+    ) {
+        let value = match param {
+            AudioParam::Static(value) => *value,
+            AudioParam::Linked(linked) => linked.get(),
+            AudioParam::Dynamic(_) => return,
+        };
+        self.check(DspError::check_value(processor, parameter, value, min, max));
+    }
+
+    /// Validates a contained processor under the child path `name`.
+    pub fn add_processor<C: ChannelConfig, P: FrameProcessor<C> + ?Sized>(
+        &mut self,
+        name: &str,
+        processor: &P,
+    ) {
+        let parent_len = self.path.len();
+        if !self.path.is_empty() {
+            self.path.push('/');
+        }
+        self.path.push_str(name);
+        processor.validate(self);
+        self.path.truncate(parent_len);
+    }
+
+    /// Returns the issues found.
+    pub fn issues(&self) -> &[ValidationIssue] {
+        &self.issues
+    }
+
+    /// Returns true if no issues were found.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns `Ok` if no issues were found, else the first one.
+    ///
+    /// Used by the `try_new` constructors, which validate a processor as it is built.
+    pub fn into_first_error(self) -> Result<(), DspError> {
+        match self.issues.into_iter().next() {
+            Some(issue) => Err(issue.error),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns `Ok` if no issues were found, else all of them.
+    pub fn into_result(self) -> Result<(), Vec<ValidationIssue>> {
+        if self.issues.is_empty() {
+            Ok(())
+        } else {
+            Err(self.issues)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::scope::ScopeTap;
+    use crate::analysis::spectrum::SpectrumAnalyzer;
+    use crate::core::channels::{DualMono, Mono, Stereo};
+    use crate::core::dsp_chain::DspChain;
+    use crate::core::load_governor::{QualityHandle, QualitySwitch};
+    use crate::core::parallel_mixer::ParallelMixer;
+    use crate::core::parameter::Parameter;
+    use crate::core::scene_crossfader::SceneCrossfader;
+    use crate::core::static_dsp_chain::StaticDspChain;
+    use crate::core::subdivide::Subdivide;
+    use crate::effects::dynamics::cabinet::CabinetSim;
+    use crate::effects::dynamics::compressor::Compressor;
+    use crate::effects::filter::fir::Fir;
+    use crate::effects::modulation::doubler::Doubler;
+    use crate::effects::modulation::modulated_delay::ModulatedDelay;
+    use crate::effects::time::delay::Delay;
+    use crate::effects::time::ping_pong_delay::PingPongDelay;
+    use crate::effects::time::reverb::Reverb;
+    use crate::effects::time::stereo_delay::StereoDelay;
+    use crate::effects::time::stereo_reverb::StereoReverb;
+    use crate::effects::time::stereo_tape_delay::StereoTapeDelay;
+    use crate::effects::time::tape_delay::TapeDelay;
+    use crate::effects::utility::add::Add;
+    use crate::effects::utility::bypass::Bypass;
+    use crate::effects::utility::gain::Gain;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_validate_reports_every_issue_with_path() {
+        let feedback = Parameter::new(0.5);
+        let chain = DspChain::new(Gain::new_fixed(1.0), 44100.0)
+            .and(Delay::new(
+                1.0,
+                AudioParam::Static(-0.1),
+                AudioParam::Linked(feedback.clone()),
+                AudioParam::Static(0.5),
+            ))
+            .and(ParallelMixer::new(
+                1.5,
+                Delay::new(
+                    0.5,
+                    AudioParam::Static(0.25),
+                    AudioParam::Static(0.3),
+                    AudioParam::Static(2.0),
+                ),
+            ));
+        let report = ValidationReport::of::<Mono, _>(&chain);
+        let issues: Vec<_> = report
+            .issues()
+            .iter()
+            .map(|i| (i.path.as_str(), i.error.clone()))
+            .collect();
+        assert_eq!(issues.len(), 3);
+        assert_eq!(
+            issues[0],
+            (
+                "1",
+                DspError::OutOfRange {
+                    processor: "Delay",
+                    parameter: "delay_time",
+                    value: -0.1,
+                    min: 0.0,
+                    max: 1.0,
+                }
+            )
+        );
+        assert_eq!(
+            report.issues()[1].to_string(),
+            "ParallelMixer: mix is 1.5 but must be within 0 - 1 (2)"
+        );
+        assert_eq!(issues[2].0, "2");
+
+        feedback.set(1.5);
+        assert_eq!(ValidationReport::of::<Mono, _>(&chain).issues().len(), 4);
+        let ok = DspChain::new(Gain::new_fixed(1.0), 44100.0);
+        assert!(ValidationReport::of::<Mono, _>(&ok).into_result().is_ok());
+    }
+
+    #[test]
+    fn test_wrappers_forward_validation() {
+        let bad_delay = || {
+            Delay::new(
+                1.0,
+                AudioParam::Static(0.25),
+                AudioParam::Static(1.5),
+                AudioParam::Static(0.5),
+            )
+        };
+        let left = StaticDspChain::new(Gain::new_fixed(1.0), 44100.0).and(bad_delay());
+        let stereo = DualMono::new(left, bad_delay());
+
+        let report = ValidationReport::of::<Stereo, _>(&stereo);
+        let paths: Vec<_> = report.issues().iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, ["Left/1", "Right"]);
+    }
+
+    #[test]
+    fn test_processor_wrappers_forward_validation() {
+        let bad_delay = || {
+            Delay::new(
+                1.0,
+                AudioParam::Static(-0.25),
+                AudioParam::Static(0.5),
+                AudioParam::Static(0.5),
+            )
+        };
+        let scenes = SceneCrossfader::new(Gain::new_fixed(1.0), bad_delay());
+        let quality = QualitySwitch::new(bad_delay(), Gain::new_fixed(1.0), QualityHandle::new());
+        let bypassed = Bypass::new(bad_delay(), false);
+        let subdivided = Subdivide::new(bad_delay(), AudioParam::Static(0.0), 0.1);
+        let added = Add::new(
+            AudioParam::Static(0.0),
+            AudioParam::Dynamic(alloc::boxed::Box::new(bad_delay())),
+        );
+        let chain = DspChain::new(scenes, 44100.0)
+            .and(quality)
+            .and(bypassed)
+            .and(subdivided)
+            .and(added);
+
+        let report = ValidationReport::of::<Mono, _>(&chain);
+        let paths: Vec<_> = report.issues().iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, ["0/B", "1/Full Quality", "2", "3/0", "4/B"]);
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert!(Delay::try_new(
+            0.0,
+            AudioParam::Static(0.0),
+            AudioParam::Static(0.0),
+            AudioParam::Static(0.0),
+        )
+        .is_err());
+        assert_eq!(
+            Doubler::try_new(5).err(),
+            Some(DspError::InvalidCount {
+                processor: "Doubler",
+                parameter: "voices",
+                value: 5,
+                min: 2,
+                max: 4,
+            })
+        );
+        let doubler = Doubler::try_new(3).unwrap();
+        assert!(ValidationReport::of::<Stereo, _>(&doubler).is_ok());
+
+        assert!(ScopeTap::<Stereo>::try_new(0).is_err());
+        assert!(ScopeTap::<Stereo>::new(64).try_with_channel(2).is_err());
+        assert_eq!(
+            SpectrumAnalyzer::<Mono, 256>::new(0.5)
+                .try_with_hop_size(512)
+                .err(),
+            Some(DspError::InvalidCount {
+                processor: "SpectrumAnalyzer",
+                parameter: "hop_size",
+                value: 512,
+                min: 1,
+                max: 256,
+            })
+        );
+        assert!(CabinetSim::try_from_ir(&[]).is_err());
+        assert!(Fir::try_new(&[]).is_err());
+        assert!(Fir::try_new(&[1.0]).is_ok());
+    }
+
+    #[test]
+    fn test_time_effects_try_new() {
+        let s = AudioParam::Static;
+
+        assert!(TapeDelay::try_new(1.0, s(-0.1), s(0.5), s(0.25)).is_err());
+        assert!(TapeDelay::try_new(1.0, s(0.25), s(0.5), s(1.5)).is_err());
+        assert!(PingPongDelay::try_new(0.0, s(0.25), s(0.5), s(0.25)).is_err());
+        assert!(PingPongDelay::try_new(1.0, s(0.25), s(0.5), s(1.5)).is_err());
+        assert!(StereoDelay::try_new(1.0, s(-0.1), s(0.5), s(0.25)).is_err());
+        assert!(StereoTapeDelay::try_new(1.0, s(0.25), s(0.5), s(1.5)).is_err());
+        assert!(StereoTapeDelay::try_new(1.0, s(0.25), s(0.5), s(0.25)).is_ok());
+
+        let mut stereo = StereoDelay::new(1.0, s(0.25), s(0.5), s(0.25));
+        assert!(ValidationReport::of::<Stereo, _>(&stereo).is_ok());
+        stereo.set_offset(s(-2.0));
+        assert_eq!(ValidationReport::of::<Stereo, _>(&stereo).issues().len(), 1);
+
+        assert!(Reverb::try_new_with_params(s(1.5), s(0.25), 0).is_err());
+        assert!(StereoReverb::try_new_with_params(s(0.25), s(0.25), s(-0.1), s(1.0), 0).is_err());
+        assert!(ValidationReport::of::<Stereo, _>(&StereoReverb::new()).is_ok());
+
+        assert_eq!(
+            Compressor::try_new(s(-12.0), s(0.5)).err(),
+            Some(DspError::OutOfRange {
+                processor: "Compressor",
+                parameter: "ratio",
+                value: 0.5,
+                min: 1.0,
+                max: 1000.0,
+            })
+        );
+        assert!(ValidationReport::of::<Mono, _>(&Compressor::new_limiter()).is_ok());
+
+        assert!(ModulatedDelay::try_new(-0.01, 1.0, s(-0.1), s(0.5), s(0.25)).is_err());
+        assert!(ModulatedDelay::try_new(0.01, 1.0, s(0.0), s(0.5), s(1.5)).is_err());
+        assert!(ValidationReport::of::<Mono, _>(&ModulatedDelay::new_chorus()).is_ok());
+        assert!(ValidationReport::of::<Mono, _>(&ModulatedDelay::new_flanger()).is_ok());
+    }
+}
@@ -1,4 +1,8 @@
 use crate::core::channels::ChannelConfig;
+use crate::core::error::ValidationReport;
+#[cfg(feature = "debug_visualize")]
+use crate::core::graph::GraphNode;
+use crate::core::preset::ParameterTree;
 use alloc::boxed::Box;
 use alloc::string::String;
 
@@ -24,6 +28,13 @@ pub trait FrameProcessor<C: ChannelConfig> {
     /// Clears delay lines, resets filters, envelopes, phases, etc.
     fn reset(&mut self) {}
 
+    /// Pre-allocates work buffers for blocks of up to `max_block_size` frames.
+    ///
+    /// Call it before processing starts, together with `set_sample_rate`. After that,
+    /// `process` does not allocate as long as blocks stay within the prepared size.
+    /// Larger blocks still work, but grow the buffers on the audio thread.
+    fn prepare(&mut self, _max_block_size: usize) {}
+
     /// Returns the latency of the processor in samples.
     ///
     /// Used for delay compensation.
@@ -31,6 +42,17 @@ pub trait FrameProcessor<C: ChannelConfig> {
         0
     }
 
+    /// Adds the processor's user-facing parameters to `tree`.
+    ///
+    /// Used to save and restore presets. Containers add one child per contained processor.
+    fn parameters(&self, _tree: &mut ParameterTree) {}
+
+    /// Reports configuration problems (e.g. out-of-range static parameters) to `report`.
+    ///
+    /// Called before processing starts, see [`ValidationReport::of`]. Containers validate
+    /// each contained processor with [`ValidationReport::add_processor`].
+    fn validate(&self, _report: &mut ValidationReport) {}
+
     /// Returns the name of the processor.
     fn name(&self) -> &str {
         #[cfg(feature = "debug_visualize")]
@@ -43,6 +65,13 @@ pub trait FrameProcessor<C: ChannelConfig> {
         }
     }
 
+    /// Adds the contained processors to `node`, see [`GraphNode::of`].
+    ///
+    /// The name, latency and parameters are filled in already. Containers add one child
+    /// per contained processor with [`GraphNode::add_processor`] and set the routing.
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, _node: &mut GraphNode) {}
+
     /// Returns an ASCII visualization of the processor structure.
     fn visualize(&self, indent: usize) -> String {
         #[cfg(feature = "debug_visualize")]
@@ -75,14 +104,31 @@ impl<C: ChannelConfig, T: FrameProcessor<C> + ?Sized> FrameProcessor<C> for Box<
         (**self).reset();
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        (**self).prepare(max_block_size);
+    }
+
     fn latency_samples(&self) -> u32 {
         (**self).latency_samples()
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        (**self).parameters(tree);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        (**self).validate(report);
+    }
+
     fn name(&self) -> &str {
         (**self).name()
     }
 
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut GraphNode) {
+        (**self).describe(node);
+    }
+
     fn visualize(&self, indent: usize) -> String {
         (**self).visualize(indent)
     }
@@ -0,0 +1,316 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::preset::ParameterTree;
+use crate::FrameProcessor;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+/// How the children of a [`GraphNode`] are connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Routing {
+    /// Each child feeds the next one, as in a `DspChain`.
+    #[default]
+    Serial,
+    /// The children all see the same input, and their outputs are combined (summed,
+    /// crossfaded, switched or assigned to separate channels).
+    Parallel,
+}
+
+/// A parameter of a [`GraphNode`], with its value at the time of the capture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphParameter {
+    pub name: String,
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+/// A structured description of a processor and the processors it contains.
+///
+/// Built with [`GraphNode::of`]: each node records the processor's name, channel count,
+/// latency and linked parameters, and containers add their children through
+/// [`FrameProcessor::describe`]. Export the graph to Graphviz with
+/// [`to_dot`](Self::to_dot) or to JSON with [`to_json`](Self::to_json) to draw the signal
+/// flow in external tools.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GraphNode {
+    /// The processor's name (see [`FrameProcessor::name`]).
+    pub name: String,
+    /// The node's role within its parent (e.g. `"0"` or `"Wet"`), empty for the root.
+    pub role: String,
+    pub channels: usize,
+    pub latency: u32,
+    pub routing: Routing,
+    pub parameters: Vec<GraphParameter>,
+    pub children: Vec<GraphNode>,
+}
+
+impl GraphNode {
+    /// Creates a node without a processor, e.g. a dry signal path.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the node.
+    /// * `channels` - Number of channels that flow through it.
+    pub fn new(name: impl Into<String>, channels: usize) -> Self {
+        GraphNode {
+            name: name.into(),
+            channels,
+            ..Default::default()
+        }
+    }
+
+    /// Describes a processor and, recursively, the processors it contains.
+    pub fn of<C: ChannelConfig, P: FrameProcessor<C> + ?Sized>(processor: &P) -> Self {
+        let mut node = GraphNode::new(processor.name(), C::num_channels());
+        node.latency = processor.latency_samples();
+
+        let tree = ParameterTree::of(processor);
+        node.parameters = tree
+            .parameters
+            .iter()
+            .map(|entry| GraphParameter {
+                name: entry.name.clone(),
+                value: entry.parameter.get(),
+                min: entry.min,
+                max: entry.max,
+            })
+            .collect();
+
+        processor.describe(&mut node);
+        node
+    }
+
+    /// Describes a contained processor and adds it as a child.
+    pub fn add_processor<C: ChannelConfig, P: FrameProcessor<C> + ?Sized>(
+        &mut self,
+        role: impl Into<String>,
+        processor: &P,
+    ) {
+        let mut child = GraphNode::of(processor);
+        child.role = role.into();
+        self.children.push(child);
+    }
+
+    /// Adds a child node.
+    pub fn add_child(&mut self, role: impl Into<String>, mut child: GraphNode) {
+        child.role = role.into();
+        self.children.push(child);
+    }
+
+    /// Sets how the children are connected.
+    pub fn set_routing(&mut self, routing: Routing) {
+        self.routing = routing;
+    }
+
+    /// Exports the graph in the Graphviz DOT language.
+    ///
+    /// Processors become boxes listing their parameters, containers become clusters, and
+    /// edges follow the signal: serial children are chained, parallel children fan out from
+    /// a split point and into a sum point.
+    pub fn to_dot(&self) -> String {
+        let mut output = String::from("digraph dsp {\n  rankdir=TB;\n  node [shape=box];\n");
+        let mut next_id = 0;
+        let (entry, exit) = self.write_dot(&mut output, &mut next_id, 1);
+        let _ = writeln!(output, "  input [shape=plaintext, label=\"Input\"];");
+        let _ = writeln!(output, "  output [shape=plaintext, label=\"Output\"];");
+        let _ = writeln!(output, "  input -> {};", entry);
+        let _ = writeln!(output, "  {} -> output;", exit);
+        output.push_str("}\n");
+        output
+    }
+
+    /// Writes the node and returns the ids where the signal enters and leaves it.
+    fn write_dot(
+        &self,
+        output: &mut String,
+        next_id: &mut usize,
+        depth: usize,
+    ) -> (String, String) {
+        let id = *next_id;
+        *next_id += 1;
+        let pad = "  ".repeat(depth);
+
+        let mut label = escape(&self.title());
+        for parameter in &self.parameters {
+            let _ = write!(
+                label,
+                "\\n{} = {}",
+                escape(&parameter.name),
+                parameter.value
+            );
+        }
+        if self.latency > 0 {
+            let _ = write!(label, "\\nlatency {}", self.latency);
+        }
+
+        if self.children.is_empty() {
+            let _ = writeln!(output, "{}n{} [label=\"{}\"];", pad, id, label);
+            return (format!("n{}", id), format!("n{}", id));
+        }
+
+        let _ = writeln!(output, "{}subgraph cluster_{} {{", pad, id);
+        let _ = writeln!(output, "{}  label=\"{}\";", pad, label);
+        let ends: Vec<(String, String)> = self
+            .children
+            .iter()
+            .map(|child| child.write_dot(output, next_id, depth + 1))
+            .collect();
+
+        let result = match self.routing {
+            Routing::Serial => {
+                for pair in ends.windows(2) {
+                    let _ = writeln!(output, "{}  {} -> {};", pad, pair[0].1, pair[1].0);
+                }
+                (ends[0].0.clone(), ends[ends.len() - 1].1.clone())
+            }
+            Routing::Parallel => {
+                let (split, sum) = (format!("split{}", id), format!("sum{}", id));
+                let _ = writeln!(output, "{}  {} [shape=point];", pad, split);
+                let _ = writeln!(output, "{}  {} [shape=circle, label=\"+\"];", pad, sum);
+                for (entry, exit) in &ends {
+                    let _ = writeln!(output, "{}  {} -> {};", pad, split, entry);
+                    let _ = writeln!(output, "{}  {} -> {};", pad, exit, sum);
+                }
+                (split, sum)
+            }
+        };
+        let _ = writeln!(output, "{}}}", pad);
+        result
+    }
+
+    fn title(&self) -> String {
+        let name = if self.name.is_empty() {
+            "Node"
+        } else {
+            &self.name
+        };
+        if self.role.is_empty() {
+            String::from(name)
+        } else {
+            format!("{}: {}", self.role, name)
+        }
+    }
+
+    /// Exports the graph as JSON.
+    ///
+    /// Each node is an object with `name`, `role`, `channels`, `latency`, `routing`
+    /// (`"serial"` or `"parallel"`), `parameters` (objects with `name`, `value`, `min` and
+    /// `max`) and `children`.
+    pub fn to_json(&self) -> String {
+        let mut output = String::new();
+        self.write_json(&mut output);
+        output
+    }
+
+    fn write_json(&self, output: &mut String) {
+        let routing = match self.routing {
+            Routing::Serial => "serial",
+            Routing::Parallel => "parallel",
+        };
+        let _ = write!(
+            output,
+            "{{\"name\":\"{}\",\"role\":\"{}\",\"channels\":{},\"latency\":{},\"routing\":\"{}\",\"parameters\":[",
+            escape(&self.name),
+            escape(&self.role),
+            self.channels,
+            self.latency,
+            routing
+        );
+        for (i, parameter) in self.parameters.iter().enumerate() {
+            if i > 0 {
+                output.push(',');
+            }
+            let _ = write!(
+                output,
+                "{{\"name\":\"{}\",\"value\":{},\"min\":{},\"max\":{}}}",
+                escape(&parameter.name),
+                number(parameter.value),
+                number(parameter.min),
+                number(parameter.max)
+            );
+        }
+        output.push_str("],\"children\":[");
+        for (i, child) in self.children.iter().enumerate() {
+            if i > 0 {
+                output.push(',');
+            }
+            child.write_json(output);
+        }
+        output.push_str("]}");
+    }
+}
+
+/// Escapes a string for a JSON or DOT string literal.
+fn escape(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(output, "\\u{:04x}", c as u32);
+            }
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+/// JSON has no infinities or NaN; they are written as `null`.
+fn number(value: f32) -> String {
+    if value.is_finite() {
+        format!("{}", value)
+    } else {
+        String::from("null")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+    use crate::core::channels::Mono;
+    use crate::core::dsp_chain::DspChain;
+    use crate::core::parallel_mixer::ParallelMixer;
+    use crate::core::parameter::Parameter;
+    use crate::effects::filter::biquad::Biquad;
+    use crate::effects::utility::gain::Gain;
+    use crate::synthesis::oscillator::{Oscillator, Waveform};
+
+    #[test]
+    fn test_graph_export() {
+        let level = Parameter::new(0.5);
+        let chain = DspChain::new(
+            Oscillator::new(AudioParam::hz(440.0), Waveform::Saw),
+            44100.0,
+        )
+        .and(ParallelMixer::new(
+            0.5,
+            Biquad::new_lowpass(AudioParam::hz(800.0), AudioParam::Static(0.7)),
+        ))
+        .and(Gain::new(AudioParam::Linked(level)));
+
+        let graph = GraphNode::of::<Mono, _>(&chain);
+        assert_eq!(graph.name, "DspChain");
+        assert_eq!(graph.children.len(), 3);
+        let mixer = &graph.children[1];
+        assert_eq!(
+            (mixer.role.as_str(), mixer.routing),
+            ("1", Routing::Parallel)
+        );
+        assert_eq!(mixer.children.len(), 2);
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph dsp {"));
+        assert!(dot.contains("subgraph cluster_"));
+        assert!(dot.contains("\\ngain = 0.5"));
+
+        let json = graph.to_json();
+        assert!(json.starts_with("{\"name\":\"DspChain\""));
+        assert!(json.contains("\"routing\":\"parallel\""));
+        assert_eq!(json.matches('{').count(), json.matches('}').count());
+        assert_eq!(escape("a\"b\\"), "a\\\"b\\\\");
+    }
+}
@@ -1,5 +1,7 @@
 use crate::core::channels::ChannelConfig;
+use crate::core::error::ValidationReport;
 use crate::core::frame_processor::FrameProcessor;
+use crate::core::preset::ParameterTree;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::marker::PhantomData;
@@ -79,6 +81,10 @@ impl<C: ChannelConfig, P: FrameProcessor<C>> FrameProcessor<C> for LatencyCompen
         self.processor.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.processor.prepare(max_block_size);
+    }
+
     fn reset(&mut self) {
         self.processor.reset();
         self.delay_line.fill(0.0);
@@ -89,11 +95,24 @@ impl<C: ChannelConfig, P: FrameProcessor<C>> FrameProcessor<C> for LatencyCompen
         self.target_latency
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_processor::<C, _>("0", &self.processor);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.add_processor::<C, _>("0", &self.processor);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "LatencyCompensator"
     }
 
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.add_processor::<C, _>("0", &self.processor);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn visualize(&self, indent: usize) -> alloc::string::String {
         use core::fmt::Write;
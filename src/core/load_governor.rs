@@ -0,0 +1,403 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::error::ValidationReport;
+use crate::core::frame_processor::FrameProcessor;
+use crate::core::preset::ParameterTree;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// A shared, thread-safe quality level.
+///
+/// Level 0 is full quality. Higher levels request progressively cheaper processing.
+/// Clones share the same level, so one handle can be given to every processor that
+/// should follow the [`LoadGovernor`].
+#[derive(Clone)]
+pub struct QualityHandle {
+    level: Arc<AtomicU32>,
+}
+
+impl QualityHandle {
+    /// Creates a new QualityHandle at full quality.
+    pub fn new() -> Self {
+        QualityHandle {
+            level: Arc::new(AtomicU32::new(0)),
+        }
+    }
+
+    /// Sets the quality level.
+    pub fn set(&self, level: u32) {
+        self.level.store(level, Ordering::Relaxed);
+    }
+
+    /// Gets the current quality level.
+    pub fn get(&self) -> u32 {
+        self.level.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for QualityHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Processing statistics collected by the [`LoadGovernor`].
+///
+/// Load is expressed as the fraction of the real-time budget used (1.0 = overrun).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LoadStats {
+    pub last_load: f32,
+    pub average_load: f32,
+    pub peak_load: f32,
+    pub blocks: u64,
+    pub overruns: u64,
+}
+
+/// Monitors processing time against the real-time budget and adjusts a shared quality level.
+///
+/// The crate has no clock of its own, so the host measures how long each block took
+/// and reports it with [`LoadGovernor::report`]. The governor smooths the load and steps
+/// the quality level down when it exceeds the high threshold, and back up once it falls
+/// below the low threshold. A hold time between changes provides hysteresis.
+pub struct LoadGovernor {
+    handle: QualityHandle,
+    max_level: u32,
+    high_threshold: f32,
+    low_threshold: f32,
+    smoothing: f32,
+    hold_blocks: u32,
+    blocks_since_change: u32,
+    stats: LoadStats,
+}
+
+impl LoadGovernor {
+    /// Creates a new LoadGovernor.
+    ///
+    /// # Arguments
+    /// * `max_level` - The cheapest quality level the governor may select.
+    /// * `high_threshold` - Load (0.0 - 1.0) above which quality is reduced.
+    /// * `low_threshold` - Load (0.0 - 1.0) below which quality is restored.
+    pub fn new(max_level: u32, high_threshold: f32, low_threshold: f32) -> Self {
+        assert!(
+            low_threshold < high_threshold,
+            "LoadGovernor: low_threshold must be below high_threshold."
+        );
+        LoadGovernor {
+            handle: QualityHandle::new(),
+            max_level,
+            high_threshold,
+            low_threshold,
+            smoothing: 0.1,
+            hold_blocks: 32,
+            blocks_since_change: u32::MAX,
+            stats: LoadStats::default(),
+        }
+    }
+
+    /// Sets the smoothing coefficient for the averaged load (0.0 - 1.0, higher reacts faster).
+    pub fn with_smoothing(mut self, smoothing: f32) -> Self {
+        self.smoothing = smoothing.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the minimum number of blocks between two quality changes.
+    pub fn with_hold_blocks(mut self, hold_blocks: u32) -> Self {
+        self.hold_blocks = hold_blocks;
+        self
+    }
+
+    /// Returns a handle sharing the governed quality level.
+    pub fn handle(&self) -> QualityHandle {
+        self.handle.clone()
+    }
+
+    /// Returns the statistics collected so far.
+    pub fn stats(&self) -> LoadStats {
+        self.stats
+    }
+
+    /// Clears the collected statistics and restores full quality.
+    pub fn reset(&mut self) {
+        self.stats = LoadStats::default();
+        self.blocks_since_change = u32::MAX;
+        self.handle.set(0);
+    }
+
+    /// Reports the time spent processing one block and returns the resulting quality level.
+    ///
+    /// # Arguments
+    /// * `elapsed_seconds` - Wall-clock time spent processing the block.
+    /// * `frames` - Number of frames in the block.
+    /// * `sample_rate` - The sample rate in Hz.
+    pub fn report(&mut self, elapsed_seconds: f32, frames: usize, sample_rate: f32) -> u32 {
+        let budget = frames as f32 / sample_rate;
+        if budget <= 0.0 {
+            return self.handle.get();
+        }
+        let load = elapsed_seconds / budget;
+
+        let stats = &mut self.stats;
+        stats.last_load = load;
+        stats.peak_load = stats.peak_load.max(load);
+        stats.average_load = if stats.blocks == 0 {
+            load
+        } else {
+            stats.average_load + (load - stats.average_load) * self.smoothing
+        };
+        stats.blocks += 1;
+        if load >= 1.0 {
+            stats.overruns += 1;
+        }
+
+        self.blocks_since_change = self.blocks_since_change.saturating_add(1);
+
+        let level = self.handle.get();
+        let average = stats.average_load;
+        // An overrun always steps down immediately; everything else waits for the hold time.
+        let can_change = self.blocks_since_change >= self.hold_blocks || load >= 1.0;
+
+        if can_change && average > self.high_threshold && level < self.max_level {
+            self.handle.set(level + 1);
+            self.blocks_since_change = 0;
+        } else if can_change && average < self.low_threshold && level > 0 {
+            self.handle.set(level - 1);
+            self.blocks_since_change = 0;
+        }
+
+        self.handle.get()
+    }
+}
+
+/// Switches between a full quality and a cheaper processor based on a [`QualityHandle`].
+///
+/// The cheap processor is used when the level reaches `threshold`. Switches happen on block
+/// boundaries and are crossfaded over one block. Both processors keep running during the
+/// fade only, so the inactive one costs nothing.
+pub struct QualitySwitch<H, L, C: ChannelConfig> {
+    high: H,
+    low: L,
+    handle: QualityHandle,
+    threshold: u32,
+    using_low: bool,
+    scratch: Vec<f32>,
+    _marker: core::marker::PhantomData<C>,
+}
+
+impl<H, L, C> QualitySwitch<H, L, C>
+where
+    H: FrameProcessor<C>,
+    L: FrameProcessor<C>,
+    C: ChannelConfig,
+{
+    /// Creates a new QualitySwitch.
+    ///
+    /// # Arguments
+    /// * `high` - The full quality processor.
+    /// * `low` - The cheaper processor.
+    /// * `handle` - The quality handle to follow (usually from a [`LoadGovernor`]).
+    pub fn new(high: H, low: L, handle: QualityHandle) -> Self {
+        QualitySwitch {
+            high,
+            low,
+            handle,
+            threshold: 1,
+            using_low: false,
+            scratch: Vec::with_capacity(128),
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Sets the quality level at which the cheap processor takes over.
+    pub fn with_threshold(mut self, threshold: u32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Returns true if the cheap processor is currently active.
+    pub fn is_reduced(&self) -> bool {
+        self.using_low
+    }
+}
+
+impl<H, L, C> FrameProcessor<C> for QualitySwitch<H, L, C>
+where
+    H: FrameProcessor<C>,
+    L: FrameProcessor<C>,
+    C: ChannelConfig,
+{
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let want_low = self.handle.get() >= self.threshold;
+
+        if want_low == self.using_low {
+            if self.using_low {
+                self.low.process(buffer, sample_index);
+            } else {
+                self.high.process(buffer, sample_index);
+            }
+            return;
+        }
+
+        if self.scratch.len() < buffer.len() {
+            self.scratch.resize(buffer.len(), 0.0);
+        }
+        let scratch = &mut self.scratch[0..buffer.len()];
+        scratch.copy_from_slice(buffer);
+
+        // `buffer` receives the outgoing processor, `scratch` the incoming one.
+        if self.using_low {
+            self.low.process(buffer, sample_index);
+            self.high.process(scratch, sample_index);
+        } else {
+            self.high.process(buffer, sample_index);
+            self.low.process(scratch, sample_index);
+        }
+
+        let channels = C::num_channels();
+        let frames = buffer.len() / channels;
+        let step = if frames > 0 { 1.0 / frames as f32 } else { 1.0 };
+
+        for (i, (out_frame, in_frame)) in buffer
+            .chunks_mut(channels)
+            .zip(scratch.chunks(channels))
+            .enumerate()
+        {
+            let t = (i + 1) as f32 * step;
+            for (o, &n) in out_frame.iter_mut().zip(in_frame.iter()) {
+                *o = *o * (1.0 - t) + n * t;
+            }
+        }
+
+        self.using_low = want_low;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.high.set_sample_rate(sample_rate);
+        self.low.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.high.prepare(max_block_size);
+        self.low.prepare(max_block_size);
+        self.scratch.resize(max_block_size * C::num_channels(), 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.high.reset();
+        self.low.reset();
+        self.using_low = self.handle.get() >= self.threshold;
+    }
+
+    fn latency_samples(&self) -> u32 {
+        if self.using_low {
+            self.low.latency_samples()
+        } else {
+            self.high.latency_samples()
+        }
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_processor::<C, _>("Full Quality", &self.high);
+        tree.add_processor::<C, _>("Reduced Quality", &self.low);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.add_processor::<C, _>("Full Quality", &self.high);
+        report.add_processor::<C, _>("Reduced Quality", &self.low);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "QualitySwitch"
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.set_routing(crate::core::graph::Routing::Parallel);
+        node.add_processor::<C, _>("Full Quality", &self.high);
+        node.add_processor::<C, _>("Reduced Quality", &self.low);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn visualize(&self, indent: usize) -> alloc::string::String {
+        use core::fmt::Write;
+
+        let spaces = " ".repeat(indent);
+        let mut output = alloc::string::String::new();
+        let _ = writeln!(
+            output,
+            "{}QualitySwitch (level >= {})",
+            spaces, self.threshold
+        );
+
+        let _ = writeln!(output, "{}  Full Quality:", spaces);
+        output.push_str(&self.high.visualize(indent + 4));
+
+        let _ = writeln!(output, "{}  Reduced Quality:", spaces);
+        output.push_str(&self.low.visualize(indent + 4));
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::Mono;
+    use crate::effects::utility::gain::Gain;
+
+    #[test]
+    fn test_governor_hysteresis() {
+        let mut governor = LoadGovernor::new(2, 0.8, 0.5)
+            .with_smoothing(1.0)
+            .with_hold_blocks(4);
+        let block = 0.01;
+
+        assert_eq!(governor.report(block * 0.6, 441, 44100.0), 0);
+
+        // Heavy load: steps down once, then waits for the hold time.
+        assert_eq!(governor.report(block * 0.9, 441, 44100.0), 1);
+        assert_eq!(governor.report(block * 0.9, 441, 44100.0), 1);
+
+        // In between the thresholds nothing changes.
+        for _ in 0..10 {
+            assert_eq!(governor.report(block * 0.6, 441, 44100.0), 1);
+        }
+
+        // Light load restores quality.
+        assert_eq!(governor.report(block * 0.2, 441, 44100.0), 0);
+
+        let stats = governor.stats();
+        assert_eq!(stats.blocks, 14);
+        assert_eq!(stats.overruns, 0);
+        assert!((stats.peak_load - 0.9).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_overrun_steps_down_immediately() {
+        let mut governor = LoadGovernor::new(3, 0.8, 0.5).with_smoothing(1.0);
+        assert_eq!(governor.report(0.02, 441, 44100.0), 1);
+        assert_eq!(governor.report(0.02, 441, 44100.0), 2);
+        assert_eq!(governor.stats().overruns, 2);
+    }
+
+    #[test]
+    fn test_quality_switch_crossfades() {
+        let handle = QualityHandle::new();
+        let mut switch =
+            QualitySwitch::new(Gain::new_fixed(1.0), Gain::new_fixed(0.0), handle.clone());
+
+        let mut buffer = [1.0; 4];
+        FrameProcessor::<Mono>::process(&mut switch, &mut buffer, 0);
+        assert_eq!(buffer, [1.0; 4]);
+
+        handle.set(1);
+        let mut buffer = [1.0; 4];
+        FrameProcessor::<Mono>::process(&mut switch, &mut buffer, 4);
+        assert_eq!(buffer, [0.75, 0.5, 0.25, 0.0]);
+        assert!(switch.is_reduced());
+
+        let mut buffer = [1.0; 4];
+        FrameProcessor::<Mono>::process(&mut switch, &mut buffer, 8);
+        assert_eq!(buffer, [0.0; 4]);
+    }
+}
@@ -0,0 +1,501 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Stereo;
+use crate::core::error::ValidationReport;
+use crate::core::frame_processor::FrameProcessor;
+use crate::core::parameter::Parameter;
+use crate::core::preset::ParameterTree;
+use crate::effects::utility::panner::PanLaw;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Levels at or below this many dB are treated as silence.
+const SILENCE_DB: f32 = -96.0;
+
+/// Identifies a channel of a [`MixBus`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChannelId(usize);
+
+/// Identifies an effect bus of a [`MixBus`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BusId(usize);
+
+/// Where a send taps its channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendTap {
+    /// Before the channel gain and pan: the send level ignores the fader.
+    PreFader,
+    /// After the channel gain and pan: the send follows the fader.
+    PostFader,
+}
+
+struct BusSend {
+    bus: usize,
+    tap: SendTap,
+    level_db: Parameter,
+    level: AudioParam,
+}
+
+struct Channel {
+    name: String,
+    source: Box<dyn FrameProcessor<Stereo> + Send>,
+    gain_db: Parameter,
+    pan: Parameter,
+    gain: AudioParam,
+    pan_param: AudioParam,
+    sends: Vec<BusSend>,
+}
+
+struct EffectBus {
+    name: String,
+    processor: Box<dyn FrameProcessor<Stereo> + Send>,
+    return_db: Parameter,
+    level: AudioParam,
+    buffer: Vec<f32>,
+}
+
+#[inline(always)]
+fn db_to_gain(db: f32) -> f32 {
+    if db <= SILENCE_DB {
+        0.0
+    } else {
+        libm::powf(10.0, db / 20.0)
+    }
+}
+
+/// A stereo mixer with named channels, effect buses and a master fader.
+///
+/// Each channel runs a stereo source, then its gain (in dB) and pan. Sends feed the
+/// channel into effect buses, tapped before or after the fader; each bus processes the sum
+/// of its sends and returns into the master at its own level. The master gain applies to
+/// the whole mix. All levels are [`Parameter`]s, so a UI can hold the handles and move
+/// them while the mixer runs, and they appear in [`parameters`](FrameProcessor::parameters)
+/// under `channels/<name>`, `buses/<name>` and `master`.
+///
+/// Like a [`SummingMixer`](crate::core::summing_mixer::SummingMixer), every source gets a
+/// copy of the input buffer. Latencies are not compensated.
+pub struct MixBus {
+    channels: Vec<Channel>,
+    buses: Vec<EffectBus>,
+    master_db: Parameter,
+    master: AudioParam,
+    pan_law: PanLaw,
+    sample_rate: f32,
+    max_block_size: usize,
+
+    input_buffer: Vec<f32>,
+    channel_buffer: Vec<f32>,
+    gain_buffer: Vec<f32>,
+    pan_buffer: Vec<f32>,
+}
+
+impl MixBus {
+    /// Creates a new, empty MixBus with the master at 0 dB.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        let master_db = Parameter::new(0.0);
+        MixBus {
+            channels: Vec::new(),
+            buses: Vec::new(),
+            master: AudioParam::Linked(master_db.clone()),
+            master_db,
+            pan_law: PanLaw::ConstantPower,
+            sample_rate: 44100.0,
+            max_block_size: 128,
+            input_buffer: Vec::with_capacity(256),
+            channel_buffer: Vec::with_capacity(256),
+            gain_buffer: Vec::with_capacity(128),
+            pan_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the pan law of the channels and returns the mixer.
+    pub fn with_pan_law(mut self, law: PanLaw) -> Self {
+        self.pan_law = law;
+        self
+    }
+
+    /// Adds a channel at 0 dB, panned to the centre.
+    ///
+    /// # Arguments
+    /// * `name` - The channel name in the parameter tree.
+    /// * `source` - The stereo processor feeding the channel.
+    pub fn add_channel(
+        &mut self,
+        name: impl Into<String>,
+        mut source: Box<dyn FrameProcessor<Stereo> + Send>,
+    ) -> ChannelId {
+        source.set_sample_rate(self.sample_rate);
+        source.prepare(self.max_block_size);
+        let gain_db = Parameter::new(0.0);
+        let pan = Parameter::new(0.0);
+        self.channels.push(Channel {
+            name: name.into(),
+            source,
+            gain: AudioParam::Linked(gain_db.clone()),
+            pan_param: AudioParam::Linked(pan.clone()),
+            gain_db,
+            pan,
+            sends: Vec::new(),
+        });
+        ChannelId(self.channels.len() - 1)
+    }
+
+    /// Adds an effect bus that returns into the master at 0 dB.
+    ///
+    /// # Arguments
+    /// * `name` - The bus name in the parameter tree.
+    /// * `processor` - The effect processing the summed sends.
+    pub fn add_bus(
+        &mut self,
+        name: impl Into<String>,
+        mut processor: Box<dyn FrameProcessor<Stereo> + Send>,
+    ) -> BusId {
+        processor.set_sample_rate(self.sample_rate);
+        processor.prepare(self.max_block_size);
+        let return_db = Parameter::new(0.0);
+        self.buses.push(EffectBus {
+            name: name.into(),
+            processor,
+            level: AudioParam::Linked(return_db.clone()),
+            return_db,
+            buffer: Vec::with_capacity(256),
+        });
+        BusId(self.buses.len() - 1)
+    }
+
+    /// Sends a channel into an effect bus and returns the send level (in dB).
+    ///
+    /// A second send from the same channel to the same bus replaces the first.
+    ///
+    /// # Arguments
+    /// * `channel` - The sending channel.
+    /// * `bus` - The receiving effect bus.
+    /// * `tap` - Whether the send is taken before or after the fader.
+    /// * `level_db` - The initial send level in dB.
+    pub fn add_send(
+        &mut self,
+        channel: ChannelId,
+        bus: BusId,
+        tap: SendTap,
+        level_db: f32,
+    ) -> Parameter {
+        assert!(bus.0 < self.buses.len(), "MixBus: Unknown bus.");
+        let level_db = Parameter::new(level_db);
+        let send = BusSend {
+            bus: bus.0,
+            tap,
+            level: AudioParam::Linked(level_db.clone()),
+            level_db: level_db.clone(),
+        };
+        let sends = &mut self.channels[channel.0].sends;
+        match sends.iter_mut().find(|s| s.bus == bus.0) {
+            Some(existing) => *existing = send,
+            None => sends.push(send),
+        }
+        level_db
+    }
+
+    /// Returns the gain (in dB) of a channel.
+    pub fn channel_gain(&self, channel: ChannelId) -> Parameter {
+        self.channels[channel.0].gain_db.clone()
+    }
+
+    /// Returns the pan (-1.0 = Left, 1.0 = Right) of a channel.
+    pub fn channel_pan(&self, channel: ChannelId) -> Parameter {
+        self.channels[channel.0].pan.clone()
+    }
+
+    /// Returns the return level (in dB) of an effect bus.
+    pub fn bus_return(&self, bus: BusId) -> Parameter {
+        self.buses[bus.0].return_db.clone()
+    }
+
+    /// Returns the master gain (in dB).
+    pub fn master_gain(&self) -> Parameter {
+        self.master_db.clone()
+    }
+
+    fn read_gains(param: &mut AudioParam, buffer: &mut [f32], sample_index: u64) {
+        param.process(buffer, sample_index);
+        for g in buffer.iter_mut() {
+            *g = db_to_gain(*g);
+        }
+    }
+}
+
+impl FrameProcessor<Stereo> for MixBus {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        let frames = len / 2;
+        if self.input_buffer.len() < len {
+            self.input_buffer.resize(len, 0.0);
+            self.channel_buffer.resize(len, 0.0);
+        }
+        if self.gain_buffer.len() < frames {
+            self.gain_buffer.resize(frames, 0.0);
+            self.pan_buffer.resize(frames, 0.0);
+        }
+        for bus in self.buses.iter_mut() {
+            if bus.buffer.len() < len {
+                bus.buffer.resize(len, 0.0);
+            }
+            bus.buffer[0..len].fill(0.0);
+        }
+
+        self.input_buffer[0..len].copy_from_slice(buffer);
+        buffer.fill(0.0);
+
+        let gains = &mut self.gain_buffer[0..frames];
+        let pans = &mut self.pan_buffer[0..frames];
+        for channel in self.channels.iter_mut() {
+            let signal = &mut self.channel_buffer[0..len];
+            signal.copy_from_slice(&self.input_buffer[0..len]);
+            channel.source.process(signal, sample_index);
+
+            for send in channel.sends.iter_mut() {
+                if send.tap == SendTap::PreFader {
+                    Self::read_gains(&mut send.level, gains, sample_index);
+                    let target = &mut self.buses[send.bus].buffer[0..len];
+                    for ((out, frame), &g) in target
+                        .chunks_exact_mut(2)
+                        .zip(signal.chunks_exact(2))
+                        .zip(gains.iter())
+                    {
+                        out[0] += frame[0] * g;
+                        out[1] += frame[1] * g;
+                    }
+                }
+            }
+
+            Self::read_gains(&mut channel.gain, gains, sample_index);
+            channel.pan_param.process(pans, sample_index);
+            let law = self.pan_law;
+            for ((frame, &g), &pan) in signal
+                .chunks_exact_mut(2)
+                .zip(gains.iter())
+                .zip(pans.iter())
+            {
+                let (left, right) = law.gains(pan);
+                frame[0] *= g * left;
+                frame[1] *= g * right;
+            }
+            for (out, &s) in buffer.iter_mut().zip(signal.iter()) {
+                *out += s;
+            }
+
+            for send in channel.sends.iter_mut() {
+                if send.tap == SendTap::PostFader {
+                    Self::read_gains(&mut send.level, gains, sample_index);
+                    let target = &mut self.buses[send.bus].buffer[0..len];
+                    for ((out, frame), &g) in target
+                        .chunks_exact_mut(2)
+                        .zip(signal.chunks_exact(2))
+                        .zip(gains.iter())
+                    {
+                        out[0] += frame[0] * g;
+                        out[1] += frame[1] * g;
+                    }
+                }
+            }
+        }
+
+        for bus in self.buses.iter_mut() {
+            let returned = &mut bus.buffer[0..len];
+            bus.processor.process(returned, sample_index);
+            Self::read_gains(&mut bus.level, gains, sample_index);
+            for ((out, frame), &g) in buffer
+                .chunks_exact_mut(2)
+                .zip(returned.chunks_exact(2))
+                .zip(gains.iter())
+            {
+                out[0] += frame[0] * g;
+                out[1] += frame[1] * g;
+            }
+        }
+
+        Self::read_gains(&mut self.master, gains, sample_index);
+        for (frame, &g) in buffer.chunks_exact_mut(2).zip(gains.iter()) {
+            frame[0] *= g;
+            frame[1] *= g;
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        for channel in self.channels.iter_mut() {
+            channel.source.set_sample_rate(sample_rate);
+            channel.gain.set_sample_rate(sample_rate);
+            channel.pan_param.set_sample_rate(sample_rate);
+            for send in channel.sends.iter_mut() {
+                send.level.set_sample_rate(sample_rate);
+            }
+        }
+        for bus in self.buses.iter_mut() {
+            bus.processor.set_sample_rate(sample_rate);
+            bus.level.set_sample_rate(sample_rate);
+        }
+        self.master.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.max_block_size = max_block_size;
+        let len = max_block_size * 2;
+        for channel in self.channels.iter_mut() {
+            channel.source.prepare(max_block_size);
+            channel.gain.prepare(max_block_size);
+            channel.pan_param.prepare(max_block_size);
+            for send in channel.sends.iter_mut() {
+                send.level.prepare(max_block_size);
+            }
+        }
+        for bus in self.buses.iter_mut() {
+            bus.processor.prepare(max_block_size);
+            bus.level.prepare(max_block_size);
+            bus.buffer.resize(len, 0.0);
+        }
+        self.master.prepare(max_block_size);
+        self.input_buffer.resize(len, 0.0);
+        self.channel_buffer.resize(len, 0.0);
+        self.gain_buffer.resize(max_block_size, 0.0);
+        self.pan_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        for channel in self.channels.iter_mut() {
+            channel.source.reset();
+            channel.gain.reset();
+            channel.pan_param.reset();
+            for send in channel.sends.iter_mut() {
+                send.level.reset();
+            }
+        }
+        for bus in self.buses.iter_mut() {
+            bus.processor.reset();
+            bus.level.reset();
+        }
+        self.master.reset();
+    }
+
+    fn latency_samples(&self) -> u32 {
+        self.channels
+            .iter()
+            .map(|channel| channel.source.latency_samples())
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        let mut channels = ParameterTree::new("channels");
+        for channel in self.channels.iter() {
+            let mut node = ParameterTree::new(channel.name.clone());
+            node.add("gain", channel.gain_db.clone(), -60.0, 12.0);
+            node.add("pan", channel.pan.clone(), -1.0, 1.0);
+            let mut sends = ParameterTree::new("sends");
+            for send in channel.sends.iter() {
+                let name = self.buses[send.bus].name.clone();
+                sends.add(name, send.level_db.clone(), -60.0, 12.0);
+            }
+            node.add_child(sends);
+            node.add_processor("source", channel.source.as_ref());
+            channels.add_child(node);
+        }
+        tree.add_child(channels);
+
+        let mut buses = ParameterTree::new("buses");
+        for bus in self.buses.iter() {
+            let mut node = ParameterTree::new(bus.name.clone());
+            node.add("return", bus.return_db.clone(), -60.0, 12.0);
+            node.add_processor("effect", bus.processor.as_ref());
+            buses.add_child(node);
+        }
+        tree.add_child(buses);
+
+        tree.add("master", self.master_db.clone(), -60.0, 12.0);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        for channel in self.channels.iter() {
+            report.add_processor(&channel.name, channel.source.as_ref());
+        }
+        for bus in self.buses.iter() {
+            report.add_processor(&bus.name, bus.processor.as_ref());
+        }
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "MixBus"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::utility::passthrough::Passthrough;
+
+    fn render(mixer: &mut MixBus) -> [f32; 2] {
+        let mut buffer = [1.0; 128];
+        mixer.process(&mut buffer, 0);
+        [buffer[126], buffer[127]]
+    }
+
+    #[test]
+    fn test_gains_pan_and_sends() {
+        let mut mixer = MixBus::new().with_pan_law(PanLaw::Linear);
+        let dry = mixer.add_channel("dry", Box::new(Passthrough::new()));
+        let fx = mixer.add_bus("fx", Box::new(Passthrough::new()));
+
+        // Centre pan with the linear law halves both sides.
+        let [l, r] = render(&mut mixer);
+        assert!((l - 0.5).abs() < 1e-6 && (r - 0.5).abs() < 1e-6);
+
+        mixer.channel_pan(dry).set(1.0);
+        mixer.channel_gain(dry).set(-6.0);
+        let [l, r] = render(&mut mixer);
+        assert!(l.abs() < 1e-6);
+        assert!((r - db_to_gain(-6.0)).abs() < 1e-5);
+
+        // A pre-fader send ignores the fader, a post-fader send follows it.
+        mixer.add_send(dry, fx, SendTap::PreFader, 0.0);
+        mixer.channel_gain(dry).set(SILENCE_DB);
+        let [l, r] = render(&mut mixer);
+        assert!((l - 1.0).abs() < 1e-6 && (r - 1.0).abs() < 1e-6);
+
+        mixer.add_send(dry, fx, SendTap::PostFader, 0.0);
+        assert_eq!(render(&mut mixer), [0.0, 0.0]);
+        mixer.channel_gain(dry).set(0.0);
+        let [l, r] = render(&mut mixer);
+        assert!(l.abs() < 1e-6 && (r - 2.0).abs() < 1e-6);
+
+        mixer.bus_return(fx).set(SILENCE_DB);
+        mixer.master_gain().set(-6.0);
+        let [_, r] = render(&mut mixer);
+        assert!((r - db_to_gain(-6.0)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_parameter_tree() {
+        let mut mixer = MixBus::new();
+        let vox = mixer.add_channel("vox", Box::new(Passthrough::new()));
+        let reverb = mixer.add_bus("reverb", Box::new(Passthrough::new()));
+        mixer.add_send(vox, reverb, SendTap::PostFader, -12.0);
+
+        let tree = ParameterTree::of::<Stereo, _>(&mixer);
+        assert!(tree.find("channels/vox/gain").is_some());
+        assert!(tree.find("channels/vox/pan").is_some());
+        assert_eq!(
+            tree.find("channels/vox/sends/reverb")
+                .unwrap()
+                .parameter
+                .get(),
+            -12.0
+        );
+        tree.find("buses/reverb/return")
+            .unwrap()
+            .parameter
+            .set(-3.0);
+        assert_eq!(mixer.bus_return(reverb).get(), -3.0);
+        assert!(tree.find("master").is_some());
+    }
+}
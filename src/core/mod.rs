@@ -1,11 +1,35 @@
 pub mod audio_param;
+pub mod automation;
 pub mod channels;
+pub mod command;
+pub mod denormal;
 pub mod dsp_chain;
+pub mod error;
 pub mod frame_processor;
+#[cfg(feature = "debug_visualize")]
+pub mod graph;
 pub mod latency_compensator;
+pub mod load_governor;
+pub mod mixer;
 pub mod ola;
 pub mod parallel_mixer;
+pub mod param_registry;
 pub mod parameter;
+pub mod patch_bank;
+pub mod pickup;
+pub mod preset;
+#[cfg(feature = "profiling")]
+pub mod profiler;
+pub mod rate_bridge;
+#[cfg(feature = "realtime_audit")]
+pub mod realtime;
+pub mod render;
+pub mod scene_crossfader;
 pub mod static_dsp_chain;
+pub mod subdivide;
 pub mod summing_mixer;
+pub mod tempo;
+#[cfg(feature = "param_trace")]
+pub mod trace;
+pub mod transport;
 pub mod utils;
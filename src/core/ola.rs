@@ -13,6 +13,10 @@ use num_complex::{Complex32, ComplexFloat};
 pub trait SpectralProcessor {
     /// Process a block of complex spectral bins.
     ///
+    /// [`Ola`] passes all `N` bins, where the upper half mirrors the lower one, and
+    /// [`RealOla`] only the `N / 2 + 1` bins from DC to Nyquist. Processors that modify
+    /// the bins should keep the mirror in sync when given the full spectrum.
+    ///
     /// # Arguments
     /// * `bins` - The spectral data.
     /// * `sample_index` - The sample index corresponding to the start of the analysis window.
@@ -24,6 +28,10 @@ pub trait SpectralProcessor {
     /// Resets the internal state of the processor.
     fn reset(&mut self) {}
 
+    /// Pre-allocates work buffers. Spectral processors are called once per hop; this is
+    /// called again whenever the hop size of the wrapping `Ola` changes.
+    fn prepare(&mut self, _hop_size: usize) {}
+
     /// Returns the name of the spectral processor.
     fn name(&self) -> &str {
         #[cfg(feature = "debug_visualize")]
@@ -103,16 +111,245 @@ impl FftHelper for [Complex32; 2048] {
     }
 }
 
+impl FftHelper for [Complex32; 4096] {
+    fn do_fft(&mut self) {
+        let _ = microfft::complex::cfft_4096(self);
+    }
+    fn do_ifft(&mut self) {
+        for x in self.iter_mut() {
+            *x = x.conj();
+        }
+        let _ = microfft::complex::cfft_4096(self);
+        for x in self.iter_mut() {
+            *x = x.conj() / 4096.0;
+        }
+    }
+}
+
+#[cfg(feature = "fft-8192")]
+impl FftHelper for [Complex32; 8192] {
+    fn do_fft(&mut self) {
+        let _ = microfft::complex::cfft_8192(self);
+    }
+    fn do_ifft(&mut self) {
+        for x in self.iter_mut() {
+            *x = x.conj();
+        }
+        let _ = microfft::complex::cfft_8192(self);
+        for x in self.iter_mut() {
+            *x = x.conj() / 8192.0;
+        }
+    }
+}
+
+/// Helper trait to abstract real FFT operations for different sizes.
+///
+/// The spectrum holds the `N / 2 + 1` bins from DC to Nyquist. The inverse transform
+/// uses the spectrum as its work buffer, so its contents are lost.
+pub trait RealFftHelper {
+    /// Transforms the frame in place and unpacks the result into `bins`.
+    fn do_rfft(&mut self, bins: &mut [Complex32]);
+    /// Writes the real signal of `bins` into the frame.
+    ///
+    /// `twiddles` holds `exp(2πik / N)` for each bin `k` (see [`real_fft_twiddles`]).
+    fn do_irfft(&mut self, bins: &mut [Complex32], twiddles: &[Complex32]);
+}
+
+/// Returns the twiddle factors `exp(2πik / N)` for the `N / 2 + 1` bins of a real FFT.
+pub fn real_fft_twiddles(n: usize) -> Vec<Complex32> {
+    (0..=n / 2)
+        .map(|k| Complex32::from_polar(1.0, 2.0 * PI * k as f32 / n as f32))
+        .collect()
+}
+
+/// Copies a packed real FFT (Nyquist in the imaginary part of DC) into `N / 2 + 1` bins.
+fn unpack_rfft(packed: &[Complex32], bins: &mut [Complex32]) {
+    let half = packed.len();
+    bins[..half].copy_from_slice(packed);
+    bins[0] = Complex32::new(packed[0].re, 0.0);
+    bins[half] = Complex32::new(packed[0].im, 0.0);
+}
+
+/// Folds `N / 2 + 1` bins into the `N / 2` point complex spectrum whose inverse holds the
+/// even samples in its real and the odd samples in its imaginary parts.
+fn pack_irfft(bins: &mut [Complex32], twiddles: &[Complex32]) {
+    let half = bins.len() - 1;
+    let fold = |k: usize, a: Complex32, b: Complex32| {
+        let even = (a + b.conj()) * 0.5;
+        let odd = (a - b.conj()) * 0.5 * twiddles[k];
+        even + Complex32::i() * odd
+    };
+    for k in 0..=half / 2 {
+        let (a, b) = (bins[k], bins[half - k]);
+        bins[k] = fold(k, a, b);
+        if k != half - k {
+            bins[half - k] = fold(half - k, b, a);
+        }
+    }
+}
+
+/// Unpacks the inverse of [`pack_irfft`] into the frame.
+fn interleave_irfft(work: &[Complex32], frame: &mut [f32]) {
+    let scale = 1.0 / work.len() as f32;
+    for (pair, z) in frame.chunks_exact_mut(2).zip(work.iter()) {
+        // The forward transform of a conjugated input gives the inverse, conjugated.
+        pair[0] = z.re * scale;
+        pair[1] = -z.im * scale;
+    }
+}
+
+impl RealFftHelper for [f32; 256] {
+    fn do_rfft(&mut self, bins: &mut [Complex32]) {
+        unpack_rfft(microfft::real::rfft_256(self), bins);
+    }
+    fn do_irfft(&mut self, bins: &mut [Complex32], twiddles: &[Complex32]) {
+        pack_irfft(bins, twiddles);
+        let work: &mut [Complex32; 128] = (&mut bins[..128]).try_into().unwrap();
+        for x in work.iter_mut() {
+            *x = x.conj();
+        }
+        interleave_irfft(microfft::complex::cfft_128(work), self);
+    }
+}
+
+impl RealFftHelper for [f32; 512] {
+    fn do_rfft(&mut self, bins: &mut [Complex32]) {
+        unpack_rfft(microfft::real::rfft_512(self), bins);
+    }
+    fn do_irfft(&mut self, bins: &mut [Complex32], twiddles: &[Complex32]) {
+        pack_irfft(bins, twiddles);
+        let work: &mut [Complex32; 256] = (&mut bins[..256]).try_into().unwrap();
+        for x in work.iter_mut() {
+            *x = x.conj();
+        }
+        interleave_irfft(microfft::complex::cfft_256(work), self);
+    }
+}
+
+impl RealFftHelper for [f32; 1024] {
+    fn do_rfft(&mut self, bins: &mut [Complex32]) {
+        unpack_rfft(microfft::real::rfft_1024(self), bins);
+    }
+    fn do_irfft(&mut self, bins: &mut [Complex32], twiddles: &[Complex32]) {
+        pack_irfft(bins, twiddles);
+        let work: &mut [Complex32; 512] = (&mut bins[..512]).try_into().unwrap();
+        for x in work.iter_mut() {
+            *x = x.conj();
+        }
+        interleave_irfft(microfft::complex::cfft_512(work), self);
+    }
+}
+
+impl RealFftHelper for [f32; 2048] {
+    fn do_rfft(&mut self, bins: &mut [Complex32]) {
+        unpack_rfft(microfft::real::rfft_2048(self), bins);
+    }
+    fn do_irfft(&mut self, bins: &mut [Complex32], twiddles: &[Complex32]) {
+        pack_irfft(bins, twiddles);
+        let work: &mut [Complex32; 1024] = (&mut bins[..1024]).try_into().unwrap();
+        for x in work.iter_mut() {
+            *x = x.conj();
+        }
+        interleave_irfft(microfft::complex::cfft_1024(work), self);
+    }
+}
+
+impl RealFftHelper for [f32; 4096] {
+    fn do_rfft(&mut self, bins: &mut [Complex32]) {
+        unpack_rfft(microfft::real::rfft_4096(self), bins);
+    }
+    fn do_irfft(&mut self, bins: &mut [Complex32], twiddles: &[Complex32]) {
+        pack_irfft(bins, twiddles);
+        let work: &mut [Complex32; 2048] = (&mut bins[..2048]).try_into().unwrap();
+        for x in work.iter_mut() {
+            *x = x.conj();
+        }
+        interleave_irfft(microfft::complex::cfft_2048(work), self);
+    }
+}
+
+impl RealFftHelper for [f32; 8192] {
+    fn do_rfft(&mut self, bins: &mut [Complex32]) {
+        unpack_rfft(microfft::real::rfft_8192(self), bins);
+    }
+    fn do_irfft(&mut self, bins: &mut [Complex32], twiddles: &[Complex32]) {
+        pack_irfft(bins, twiddles);
+        let work: &mut [Complex32; 4096] = (&mut bins[..4096]).try_into().unwrap();
+        for x in work.iter_mut() {
+            *x = x.conj();
+        }
+        interleave_irfft(microfft::complex::cfft_4096(work), self);
+    }
+}
+
+/// The analysis and synthesis window of an [`Ola`] or [`RealOla`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OlaWindow {
+    /// Raised cosine. A good default; at 50% overlap its level ripples slightly, which
+    /// the normalization corrects for unmodified spectra only.
+    Hann,
+    /// Four-term Blackman-Harris: very low sidelobes for clean spectral edits, best with
+    /// 75% overlap.
+    BlackmanHarris,
+    /// No windowing. Leaks heavily; mostly useful for analysis and testing.
+    Rectangular,
+}
+
+impl OlaWindow {
+    pub(crate) fn fill(self, window: &mut [f32]) {
+        let n = window.len() as f32;
+        for (i, w) in window.iter_mut().enumerate() {
+            let x = 2.0 * PI * i as f32 / n;
+            *w = match self {
+                OlaWindow::Hann => 0.5 * (1.0 - libm::cosf(x)),
+                OlaWindow::BlackmanHarris => {
+                    0.35875 - 0.48829 * libm::cosf(x) + 0.14128 * libm::cosf(2.0 * x)
+                        - 0.01168 * libm::cosf(3.0 * x)
+                }
+                OlaWindow::Rectangular => 1.0,
+            };
+        }
+    }
+}
+
+/// Returns the gain for each position within a hop that makes the overlapping
+/// analysis and synthesis windows sum to unity.
+fn overlap_gains(window: &[f32], hop_size: usize) -> Vec<f32> {
+    (0..hop_size)
+        .map(|i| {
+            let sum: f32 = window.iter().skip(i).step_by(hop_size).map(|w| w * w).sum();
+            if sum > 1e-6 {
+                1.0 / sum
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+fn check_hop_size(n: usize, hop_size: usize) {
+    assert!(
+        hop_size > 0 && hop_size <= n / 2 && n.is_multiple_of(hop_size),
+        "Ola: The hop size must divide the FFT size and be at most half of it."
+    );
+}
+
 /// Overlap-Add (OLA) processor for spectral effects.
 ///
 /// Handles windowing, FFT, processing, IFFT, and overlap-add reconstruction.
-/// Supports block sizes independent of FFT size.
+/// Supports block sizes independent of FFT size. Uses a Hann window with 50% overlap
+/// by default; see [`with_window`](Self::with_window) and
+/// [`with_hop_size`](Self::with_hop_size).
+///
+/// The output is delayed by `N` samples (one FFT frame), reported by `latency_samples`
+/// so wrappers such as `ParallelMixer` keep the dry signal aligned.
 ///
 /// This processor operates on Mono signals only
 pub struct Ola<P: SpectralProcessor, const N: usize> {
     processor: P,
     window: [f32; N],
     hop_size: usize,
+    gains: Vec<f32>,
 
     input_queue: VecDeque<f32>,
     output_queue: VecDeque<f32>,
@@ -133,24 +370,49 @@ where
     /// * `processor` - The spectral processor to apply.
     pub fn with(processor: P) -> Self {
         let mut window = [0.0; N];
-        for (i, w) in window.iter_mut().enumerate() {
-            let arg = 2.0 * PI * i as f32 / (N - 1) as f32;
-            *w = 0.5 * (1.0 - libm::cosf(arg));
-        }
+        OlaWindow::Hann.fill(&mut window);
 
         let hop_size = N / 2;
+        let gains = overlap_gains(&window, hop_size);
         let output_queue = VecDeque::from(vec![0.0; N]);
 
-        Ola {
+        let mut ola = Ola {
             processor,
             window,
             hop_size,
+            gains,
             input_queue: VecDeque::with_capacity(N * 2),
             output_queue,
             fft_buffer: [Complex32::new(0.0, 0.0); N],
             ola_buffer: vec![0.0; N],
             current_sample_index: 0,
-        }
+        };
+        ola.processor.prepare(hop_size);
+        ola
+    }
+
+    /// Sets the analysis and synthesis window (default [`OlaWindow::Hann`]).
+    pub fn with_window(mut self, window: OlaWindow) -> Self {
+        window.fill(&mut self.window);
+        self.gains = overlap_gains(&self.window, self.hop_size);
+        self
+    }
+
+    /// Sets the hop size (default `N / 2`, 50% overlap). `N / 4` gives 75% overlap.
+    ///
+    /// The hop size must divide `N` and be at most `N / 2`. The output is normalized so the
+    /// overlapping windows sum to unity gain for any window and hop size.
+    pub fn with_hop_size(mut self, hop_size: usize) -> Self {
+        check_hop_size(N, hop_size);
+        self.hop_size = hop_size;
+        self.gains = overlap_gains(&self.window, hop_size);
+        self.processor.prepare(hop_size);
+        self
+    }
+
+    /// Returns the hop size.
+    pub fn hop_size(&self) -> usize {
+        self.hop_size
     }
 }
 
@@ -179,19 +441,17 @@ where
 
             self.fft_buffer.do_ifft();
 
-            let scale = (2.0 / 3.0) as f32;
             for i in 0..N {
-                self.ola_buffer[i] += self.fft_buffer[i].re * self.window[i] * scale;
+                self.ola_buffer[i] += self.fft_buffer[i].re * self.window[i];
             }
 
             for i in 0..self.hop_size {
-                self.output_queue.push_back(self.ola_buffer[i]);
+                self.output_queue
+                    .push_back(self.ola_buffer[i] * self.gains[i]);
             }
 
-            for i in 0..self.hop_size {
-                self.ola_buffer[i] = self.ola_buffer[i + self.hop_size];
-                self.ola_buffer[i + self.hop_size] = 0.0;
-            }
+            self.ola_buffer.copy_within(self.hop_size.., 0);
+            self.ola_buffer[N - self.hop_size..].fill(0.0);
 
             self.input_queue.drain(0..self.hop_size);
             self.current_sample_index += self.hop_size as u64;
@@ -210,6 +470,15 @@ where
         self.processor.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.processor.prepare(self.hop_size);
+        let needed = N + max_block_size;
+        self.input_queue
+            .reserve(needed.saturating_sub(self.input_queue.len()));
+        self.output_queue
+            .reserve(needed.saturating_sub(self.output_queue.len()));
+    }
+
     fn reset(&mut self) {
         self.input_queue.clear();
         self.output_queue.clear();
@@ -219,6 +488,10 @@ where
         self.processor.reset();
     }
 
+    fn latency_samples(&self) -> u32 {
+        N as u32
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "Ola (Spectral Wrapper)"
@@ -240,3 +513,285 @@ where
         s
     }
 }
+
+/// Overlap-Add processor using a real FFT.
+///
+/// Works like [`Ola`], but takes advantage of the input being real: the transform runs as
+/// an `N / 2` point complex FFT, roughly halving the cost, and the spectral processor gets
+/// only the `N / 2 + 1` bins from DC to Nyquist instead of the full mirrored spectrum.
+/// Supports FFT sizes from 256 to 8192 and has the same `N` samples of latency.
+pub struct RealOla<P: SpectralProcessor, const N: usize> {
+    processor: P,
+    window: [f32; N],
+    hop_size: usize,
+    gains: Vec<f32>,
+
+    input_queue: VecDeque<f32>,
+    output_queue: VecDeque<f32>,
+
+    frame: [f32; N],
+    bins: Vec<Complex32>,
+    twiddles: Vec<Complex32>,
+    ola_buffer: Vec<f32>,
+
+    current_sample_index: u64,
+}
+
+impl<P: SpectralProcessor, const N: usize> RealOla<P, N>
+where
+    [f32; N]: RealFftHelper,
+{
+    /// Creates a new real-FFT OLA processor.
+    ///
+    /// # Arguments
+    /// * `processor` - The spectral processor to apply.
+    pub fn with(processor: P) -> Self {
+        let mut window = [0.0; N];
+        OlaWindow::Hann.fill(&mut window);
+
+        let hop_size = N / 2;
+        let gains = overlap_gains(&window, hop_size);
+        let output_queue = VecDeque::from(vec![0.0; N]);
+
+        let mut ola = RealOla {
+            processor,
+            window,
+            hop_size,
+            gains,
+            input_queue: VecDeque::with_capacity(N * 2),
+            output_queue,
+            frame: [0.0; N],
+            bins: vec![Complex32::new(0.0, 0.0); N / 2 + 1],
+            twiddles: real_fft_twiddles(N),
+            ola_buffer: vec![0.0; N],
+            current_sample_index: 0,
+        };
+        ola.processor.prepare(hop_size);
+        ola
+    }
+
+    /// Sets the analysis and synthesis window (default [`OlaWindow::Hann`]).
+    pub fn with_window(mut self, window: OlaWindow) -> Self {
+        window.fill(&mut self.window);
+        self.gains = overlap_gains(&self.window, self.hop_size);
+        self
+    }
+
+    /// Sets the hop size (default `N / 2`, 50% overlap). `N / 4` gives 75% overlap.
+    ///
+    /// The hop size must divide `N` and be at most `N / 2`. The output is normalized so the
+    /// overlapping windows sum to unity gain for any window and hop size.
+    pub fn with_hop_size(mut self, hop_size: usize) -> Self {
+        check_hop_size(N, hop_size);
+        self.hop_size = hop_size;
+        self.gains = overlap_gains(&self.window, hop_size);
+        self.processor.prepare(hop_size);
+        self
+    }
+
+    /// Returns the hop size.
+    pub fn hop_size(&self) -> usize {
+        self.hop_size
+    }
+}
+
+impl<P: SpectralProcessor, const N: usize> FrameProcessor<Mono> for RealOla<P, N>
+where
+    [f32; N]: RealFftHelper,
+{
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        if self.input_queue.is_empty() {
+            self.current_sample_index = sample_index;
+        }
+
+        for &sample in buffer.iter() {
+            self.input_queue.push_back(sample);
+        }
+
+        while self.input_queue.len() >= N {
+            for i in 0..N {
+                self.frame[i] = self.input_queue[i] * self.window[i];
+            }
+
+            self.frame.do_rfft(&mut self.bins);
+
+            self.processor
+                .process_spectral(&mut self.bins, self.current_sample_index);
+
+            self.frame.do_irfft(&mut self.bins, &self.twiddles);
+
+            for i in 0..N {
+                self.ola_buffer[i] += self.frame[i] * self.window[i];
+            }
+
+            for i in 0..self.hop_size {
+                self.output_queue
+                    .push_back(self.ola_buffer[i] * self.gains[i]);
+            }
+
+            self.ola_buffer.copy_within(self.hop_size.., 0);
+            self.ola_buffer[N - self.hop_size..].fill(0.0);
+
+            self.input_queue.drain(0..self.hop_size);
+            self.current_sample_index += self.hop_size as u64;
+        }
+
+        for sample in buffer.iter_mut() {
+            *sample = self.output_queue.pop_front().unwrap_or(0.0);
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.processor.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.processor.prepare(self.hop_size);
+        let needed = N + max_block_size;
+        self.input_queue
+            .reserve(needed.saturating_sub(self.input_queue.len()));
+        self.output_queue
+            .reserve(needed.saturating_sub(self.output_queue.len()));
+    }
+
+    fn reset(&mut self) {
+        self.input_queue.clear();
+        self.output_queue.clear();
+        self.output_queue.extend(vec![0.0; N]);
+        self.ola_buffer.fill(0.0);
+        self.current_sample_index = 0;
+        self.processor.reset();
+    }
+
+    fn latency_samples(&self) -> u32 {
+        N as u32
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "RealOla (Spectral Wrapper)"
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn visualize(&self, indent: usize) -> String {
+        use core::fmt::Write;
+        let mut s = String::new();
+        let spaces = " ".repeat(indent);
+        let _ = write!(
+            s,
+            "{}RealOla (FFT Size: {})\n{}  |-- {}\n",
+            spaces,
+            N,
+            spaces,
+            self.processor.name()
+        );
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parallel_mixer::ParallelMixer;
+
+    struct Identity;
+
+    impl SpectralProcessor for Identity {
+        fn process_spectral(&mut self, _bins: &mut [Complex32], _sample_index: u64) {}
+    }
+
+    fn peak(buffer: &[f32]) -> usize {
+        (0..buffer.len())
+            .max_by(|&a, &b| buffer[a].abs().total_cmp(&buffer[b].abs()))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_latency_is_compensated() {
+        let mut ola = Ola::<_, 256>::with(Identity);
+        assert_eq!(FrameProcessor::<Mono>::latency_samples(&ola), 256);
+        let mut buffer = vec![0.0; 1024];
+        buffer[300] = 1.0;
+        for block in buffer.chunks_mut(64) {
+            ola.process(block, 0);
+        }
+        assert_eq!(peak(&buffer), 300 + 256);
+        let wet = buffer[300 + 256];
+
+        // Dry and wet line up, so the impulse comes out once, at full level.
+        let mut mixer = ParallelMixer::new(0.5, Ola::<_, 256>::with(Identity));
+        let mut buffer = vec![0.0; 1024];
+        buffer[300] = 1.0;
+        for block in buffer.chunks_mut(64) {
+            mixer.process(block, 0);
+        }
+        assert_eq!(peak(&buffer), 300 + 256);
+        assert!(buffer[300].abs() < 1e-6);
+        assert!((buffer[300 + 256] - (0.5 + 0.5 * wet)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_real_matches_complex() {
+        let input: Vec<f32> = (0..4096)
+            .map(|i| libm::sinf(i as f32 * 0.05) + 0.3 * libm::sinf(i as f32 * 0.71))
+            .collect();
+        let mut complex = input.clone();
+        let mut real = input.clone();
+        let mut ola = Ola::<_, 512>::with(Identity);
+        let mut real_ola = RealOla::<_, 512>::with(Identity);
+        for (c, r) in complex.chunks_mut(100).zip(real.chunks_mut(100)) {
+            ola.process(c, 0);
+            real_ola.process(r, 0);
+        }
+        for (c, r) in complex.iter().zip(real.iter()) {
+            assert!((c - r).abs() < 1e-4);
+        }
+        assert_eq!(FrameProcessor::<Mono>::latency_samples(&real_ola), 512);
+
+        // The spectrum stops at Nyquist, which the transform keeps.
+        let mut frame = [0.0; 256];
+        for (i, x) in frame.iter_mut().enumerate() {
+            *x = if i % 2 == 0 { 1.0 } else { -1.0 };
+        }
+        let mut bins = vec![Complex32::new(0.0, 0.0); 129];
+        frame.do_rfft(&mut bins);
+        assert!((bins[128].re - 256.0).abs() < 1e-3 && bins[0].norm() < 1e-3);
+        frame.do_irfft(&mut bins, &real_fft_twiddles(256));
+        assert!(frame
+            .iter()
+            .enumerate()
+            .all(|(i, &x)| (x - if i % 2 == 0 { 1.0 } else { -1.0 }).abs() < 1e-4));
+    }
+
+    #[test]
+    fn test_windows_and_hops_reconstruct() {
+        let input: Vec<f32> = (0..4096)
+            .map(|i| libm::sinf(i as f32 * 0.031) + 0.5 * libm::sinf(i as f32 * 0.37))
+            .collect();
+        let configs = [
+            (OlaWindow::Hann, 256),
+            (OlaWindow::Hann, 128),
+            (OlaWindow::BlackmanHarris, 128),
+            (OlaWindow::Rectangular, 256),
+        ];
+        for (window, hop) in configs {
+            let mut ola = Ola::<_, 512>::with(Identity)
+                .with_window(window)
+                .with_hop_size(hop);
+            let mut real = RealOla::<_, 512>::with(Identity)
+                .with_window(window)
+                .with_hop_size(hop);
+            let mut a = input.clone();
+            let mut b = input.clone();
+            for (x, y) in a.chunks_mut(100).zip(b.chunks_mut(100)) {
+                ola.process(x, 0);
+                real.process(y, 0);
+            }
+            // Unity gain once the windows fully overlap.
+            for i in 1024..4096 {
+                assert!((a[i] - input[i - 512]).abs() < 1e-3);
+                assert!((b[i] - input[i - 512]).abs() < 1e-3);
+            }
+        }
+    }
+}
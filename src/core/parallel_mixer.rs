@@ -1,18 +1,52 @@
 use super::frame_processor::FrameProcessor;
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::ChannelConfig;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
 #[cfg(feature = "debug_visualize")]
 #[cfg(feature = "debug_visualize")]
 use alloc::string::String;
 use alloc::vec::Vec;
+use core::f32::consts::FRAC_PI_2;
+
+/// How a [`ParallelMixer`] turns the mix amount into dry and wet gains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MixLaw {
+    /// The gains sum to one: `dry = 1 - mix`, `wet = mix`.
+    #[default]
+    Linear,
+    /// The squared gains sum to one: -3 dB each at 50%, so uncorrelated signals keep
+    /// their loudness and correlated ones dip less than with `Linear`.
+    EqualPower,
+}
+
+impl MixLaw {
+    /// Returns the dry and wet gains for a mix amount (0.0 = dry, 1.0 = wet).
+    pub fn gains(self, mix: f32) -> (f32, f32) {
+        match self {
+            MixLaw::Linear => (1.0 - mix, mix),
+            MixLaw::EqualPower => {
+                let angle = mix.clamp(0.0, 1.0) * FRAC_PI_2;
+                (libm::cosf(angle), libm::sinf(angle))
+            }
+        }
+    }
+}
 
 /// A Parallel Mixer (Dry/Wet).
 ///
 /// Mixes the processed signal (wet) with the original signal (dry).
 /// Handles latency compensation if the processor reports latency.
+///
+/// The mix follows a [`MixLaw`]. The wet signal can be phase-inverted, which turns the
+/// comb filter of a short modulated delay into its complement, and soloed to audition
+/// the effect alone.
 pub struct ParallelMixer<P, C: ChannelConfig> {
     processor: P,
     mix: AudioParam,
+    law: MixLaw,
+    wet_solo: bool,
+    wet_invert: bool,
     dry_buffer: Vec<f32>,
     delay_line: Vec<f32>,
     write_ptr: usize,
@@ -30,6 +64,9 @@ impl<P: FrameProcessor<C>, C: ChannelConfig> ParallelMixer<P, C> {
         ParallelMixer {
             processor,
             mix: AudioParam::Static(mix),
+            law: MixLaw::Linear,
+            wet_solo: false,
+            wet_invert: false,
             dry_buffer: Vec::with_capacity(128),
             delay_line: Vec::with_capacity(128),
             write_ptr: 0,
@@ -42,6 +79,41 @@ impl<P: FrameProcessor<C>, C: ChannelConfig> ParallelMixer<P, C> {
     pub fn set_mix(&mut self, mix: AudioParam) {
         self.mix = mix;
     }
+
+    /// Sets the mix law and returns the mixer.
+    pub fn with_law(mut self, law: MixLaw) -> Self {
+        self.law = law;
+        self
+    }
+
+    /// Sets the mix law.
+    pub fn set_law(&mut self, law: MixLaw) {
+        self.law = law;
+    }
+
+    /// Mutes the dry signal and plays the wet signal at full level, whatever the mix.
+    pub fn set_wet_solo(&mut self, enabled: bool) {
+        self.wet_solo = enabled;
+    }
+
+    /// Inverts the polarity of the wet signal.
+    pub fn set_wet_invert(&mut self, enabled: bool) {
+        self.wet_invert = enabled;
+    }
+
+    #[inline(always)]
+    fn gains(&self, mix: f32) -> (f32, f32) {
+        let (dry, wet) = if self.wet_solo {
+            (0.0, 1.0)
+        } else {
+            self.law.gains(mix)
+        };
+        if self.wet_invert {
+            (dry, -wet)
+        } else {
+            (dry, wet)
+        }
+    }
 }
 
 impl<P: FrameProcessor<C>, C: ChannelConfig> FrameProcessor<C> for ParallelMixer<P, C> {
@@ -50,7 +122,7 @@ impl<P: FrameProcessor<C>, C: ChannelConfig> FrameProcessor<C> for ParallelMixer
         let channels = C::num_channels();
 
         if latency > 0 {
-            let needed = (latency + 4096) * channels;
+            let needed = (latency + (buffer.len() / channels).max(4096)) * channels;
             if self.delay_line.len() < needed {
                 self.delay_line.resize(needed, 0.0);
             }
@@ -98,8 +170,7 @@ impl<P: FrameProcessor<C>, C: ChannelConfig> FrameProcessor<C> for ParallelMixer
                 let dry = self.delay_line[read_idx];
 
                 let frame_idx = i / channels;
-                let wet_gain = self.mix_buffer[frame_idx];
-                let dry_gain = 1.0 - wet_gain;
+                let (dry_gain, wet_gain) = self.gains(self.mix_buffer[frame_idx]);
 
                 *sample = dry * dry_gain + *sample * wet_gain;
             }
@@ -107,8 +178,7 @@ impl<P: FrameProcessor<C>, C: ChannelConfig> FrameProcessor<C> for ParallelMixer
             for (i, sample) in buffer.iter_mut().enumerate() {
                 let dry = self.dry_buffer[i];
                 let frame_idx = i / channels;
-                let wet_gain = self.mix_buffer[frame_idx];
-                let dry_gain = 1.0 - wet_gain;
+                let (dry_gain, wet_gain) = self.gains(self.mix_buffer[frame_idx]);
 
                 *sample = dry * dry_gain + *sample * wet_gain;
             }
@@ -120,6 +190,21 @@ impl<P: FrameProcessor<C>, C: ChannelConfig> FrameProcessor<C> for ParallelMixer
         self.mix.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.processor.prepare(max_block_size);
+        self.mix.prepare(max_block_size);
+        let channels = C::num_channels();
+        self.dry_buffer.resize(max_block_size * channels, 0.0);
+        self.mix_buffer.resize(max_block_size, 0.0);
+        let latency = self.processor.latency_samples() as usize;
+        if latency > 0 {
+            let needed = (latency + max_block_size.max(4096)) * channels;
+            if self.delay_line.len() < needed {
+                self.delay_line.resize(needed, 0.0);
+            }
+        }
+    }
+
     fn reset(&mut self) {
         self.processor.reset();
         self.delay_line.fill(0.0);
@@ -131,11 +216,31 @@ impl<P: FrameProcessor<C>, C: ChannelConfig> FrameProcessor<C> for ParallelMixer
         self.processor.latency_samples()
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("mix", &self.mix, 0.0, 1.0);
+        self.processor.parameters(tree);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("ParallelMixer", "mix", &self.mix, 0.0, 1.0);
+        self.processor.validate(report);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "ParallelMixer"
     }
 
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.set_routing(crate::core::graph::Routing::Parallel);
+        node.add_child(
+            "Dry",
+            crate::core::graph::GraphNode::new("Passthrough", C::num_channels()),
+        );
+        node.add_processor::<C, _>("Wet", &self.processor);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn visualize(&self, indent: usize) -> String {
         use core::fmt::Write;
@@ -161,3 +266,33 @@ impl<P: FrameProcessor<C>, C: ChannelConfig> FrameProcessor<C> for ParallelMixer
         output
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::Mono;
+    use crate::effects::utility::passthrough::Passthrough;
+
+    fn mix(mixer: &mut ParallelMixer<Passthrough, Mono>) -> f32 {
+        let mut buffer = [1.0; 4];
+        mixer.process(&mut buffer, 0);
+        buffer[3]
+    }
+
+    #[test]
+    fn test_laws_solo_and_invert() {
+        let mut mixer = ParallelMixer::new(0.5, Passthrough::new());
+        assert!((mix(&mut mixer) - 1.0).abs() < 1e-6);
+
+        let mut mixer = mixer.with_law(MixLaw::EqualPower);
+        assert!((mix(&mut mixer) - core::f32::consts::SQRT_2).abs() < 1e-5);
+
+        // Inverting an identical wet signal cancels it against the dry one.
+        mixer.set_law(MixLaw::Linear);
+        mixer.set_wet_invert(true);
+        assert!(mix(&mut mixer).abs() < 1e-6);
+
+        mixer.set_wet_solo(true);
+        assert!((mix(&mut mixer) + 1.0).abs() < 1e-6);
+    }
+}
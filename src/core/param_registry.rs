@@ -0,0 +1,208 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::parameter::Parameter;
+use crate::core::preset::ParameterTree;
+use crate::FrameProcessor;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+/// A directory of named Parameters, addressed by `/`-separated paths.
+///
+/// Register the Parameters that drive a chain while building it (e.g.
+/// `"voice1/filter/cutoff"`), then hand the registry to a UI or scripting layer, which
+/// looks up handles by path and binds controls to them at runtime. The returned handles
+/// are clones of the registered Parameter, so setting them changes the running chain.
+///
+/// Registration allocates and is meant for setup time; lookups do not allocate.
+#[derive(Clone, Default)]
+pub struct ParamRegistry {
+    entries: BTreeMap<String, Parameter>,
+}
+
+impl ParamRegistry {
+    /// Creates a new, empty ParamRegistry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collects the parameters a processor exposes through [`FrameProcessor::parameters`].
+    pub fn from_processor<C: ChannelConfig, P: FrameProcessor<C> + ?Sized>(processor: &P) -> Self {
+        Self::from_tree(&ParameterTree::of(processor))
+    }
+
+    /// Collects the parameters of a tree, keyed by their paths.
+    pub fn from_tree(tree: &ParameterTree) -> Self {
+        let mut registry = Self::new();
+        for (path, entry) in tree.entries() {
+            registry.register(path, entry.parameter.clone());
+        }
+        registry
+    }
+
+    /// Registers a Parameter under `path`.
+    ///
+    /// Returns the Parameter previously registered under the same path, if any.
+    pub fn register(&mut self, path: impl Into<String>, parameter: Parameter) -> Option<Parameter> {
+        let path = path.into();
+        assert!(
+            !path.is_empty() && path.split('/').all(|segment| !segment.is_empty()),
+            "ParamRegistry: Paths must consist of non-empty segments."
+        );
+        self.entries.insert(path, parameter)
+    }
+
+    /// Creates a Parameter, registers it under `path` and returns a handle to it.
+    pub fn create(&mut self, path: impl Into<String>, value: f32) -> Parameter {
+        let parameter = Parameter::new(value);
+        self.register(path, parameter.clone());
+        parameter
+    }
+
+    /// Returns a registration scope that prefixes every path with `prefix/`.
+    pub fn scope(&mut self, prefix: impl Into<String>) -> RegistryScope<'_> {
+        RegistryScope {
+            registry: self,
+            prefix: prefix.into(),
+        }
+    }
+
+    /// Removes the Parameter at `path` and returns it.
+    pub fn unregister(&mut self, path: &str) -> Option<Parameter> {
+        self.entries.remove(path)
+    }
+
+    /// Returns a handle to the Parameter at `path`.
+    pub fn get(&self, path: &str) -> Option<Parameter> {
+        self.entries.get(path).cloned()
+    }
+
+    /// Returns true if a Parameter is registered at `path`.
+    pub fn contains(&self, path: &str) -> bool {
+        self.entries.contains_key(path)
+    }
+
+    /// Returns all registered paths in sorted order.
+    pub fn paths(&self) -> impl Iterator<Item = &str> + '_ {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Returns the paths and Parameters below `prefix` (e.g. all of `"voice1"`).
+    pub fn children<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a Parameter)> + 'a {
+        let prefix = prefix.trim_end_matches('/');
+        self.entries
+            .range::<str, _>((
+                core::ops::Bound::Included(prefix),
+                core::ops::Bound::Unbounded,
+            ))
+            .take_while(move |(path, _)| path.starts_with(prefix))
+            .filter(move |(path, _)| {
+                prefix.is_empty() || path.as_bytes().get(prefix.len()) == Some(&b'/')
+            })
+            .map(|(path, parameter)| (path.as_str(), parameter))
+    }
+
+    /// Returns the number of registered Parameters.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if nothing is registered.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Merges another registry below `prefix`, e.g. the registry of one voice into a synth.
+    pub fn merge(&mut self, prefix: &str, other: ParamRegistry) {
+        let mut scope = self.scope(prefix);
+        for (path, parameter) in other.entries {
+            scope.register(&path, parameter);
+        }
+    }
+}
+
+/// Registers Parameters below a common path prefix, see [`ParamRegistry::scope`].
+pub struct RegistryScope<'a> {
+    registry: &'a mut ParamRegistry,
+    prefix: String,
+}
+
+impl RegistryScope<'_> {
+    fn path(&self, name: &str) -> String {
+        if self.prefix.is_empty() {
+            String::from(name)
+        } else {
+            let mut path = self.prefix.clone();
+            path.push('/');
+            path.push_str(name);
+            path
+        }
+    }
+
+    /// Registers a Parameter under `prefix/name`.
+    pub fn register(&mut self, name: &str, parameter: Parameter) -> Option<Parameter> {
+        let path = self.path(name);
+        self.registry.register(path, parameter)
+    }
+
+    /// Creates and registers a Parameter under `prefix/name`.
+    pub fn create(&mut self, name: &str, value: f32) -> Parameter {
+        let path = self.path(name);
+        self.registry.create(path, value)
+    }
+
+    /// Returns a nested scope, `prefix/name`.
+    pub fn scope(&mut self, name: &str) -> RegistryScope<'_> {
+        let prefix = self.path(name);
+        RegistryScope {
+            registry: self.registry,
+            prefix,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+    use crate::core::channels::Mono;
+    use crate::core::dsp_chain::DspChain;
+    use crate::effects::utility::gain::Gain;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_register_and_lookup() {
+        let mut registry = ParamRegistry::new();
+        let cutoff = {
+            let mut voice = registry.scope("voice1");
+            let mut filter = voice.scope("filter");
+            filter.create("resonance", 0.5);
+            filter.create("cutoff", 1000.0)
+        };
+        registry.create("voice10/gain", 1.0);
+        registry.create("master", 0.8);
+
+        registry.get("voice1/filter/cutoff").unwrap().set(440.0);
+        assert_eq!(cutoff.get(), 440.0);
+        assert!(registry.get("voice1/filter").is_none());
+
+        let voice1: Vec<_> = registry.children("voice1").map(|(p, _)| p).collect();
+        assert_eq!(voice1, ["voice1/filter/cutoff", "voice1/filter/resonance"]);
+        assert_eq!(registry.children("").count(), 4);
+    }
+
+    #[test]
+    fn test_from_processor() {
+        let level = Parameter::new(0.5);
+        let chain = DspChain::<Mono>::new(Gain::new_fixed(2.0), 44100.0)
+            .and(Gain::new(AudioParam::Linked(level.clone())));
+
+        let mut synth = ParamRegistry::new();
+        synth.merge("fx", ParamRegistry::from_processor(&chain));
+        assert_eq!(synth.paths().collect::<Vec<_>>(), ["fx/1/gain"]);
+
+        synth.get("fx/1/gain").unwrap().set(0.25);
+        assert_eq!(level.get(), 0.25);
+    }
+}
@@ -1,12 +1,29 @@
 use alloc::sync::Arc;
 use core::sync::atomic::{AtomicU32, Ordering};
 
+/// Per-clone ramp state used when smoothing is enabled.
+#[derive(Clone, Copy)]
+struct Ramp {
+    current: f32,
+    target: f32,
+    step: f32,
+    remaining: u32,
+    sample_rate: f32,
+}
+
 /// A thread-safe floating point parameter.
 ///
 /// Uses atomic operations to allow safe concurrent access from UI and audio threads.
-#[derive(Clone)]
+///
+/// A Parameter can carry a default smoothing time (see [`Parameter::with_smoothing`]).
+/// When it is read through `AudioParam::Linked`, value changes then glide linearly over
+/// that time instead of jumping, which avoids zipper noise. The smoothing time is shared
+/// by all clones, while each clone keeps its own ramp.
 pub struct Parameter {
     value: Arc<AtomicU32>,
+    smoothing: Arc<AtomicU32>,
+    local_smoothing: Option<f32>,
+    ramp: Ramp,
 }
 
 impl Parameter {
@@ -14,9 +31,24 @@ impl Parameter {
     pub fn new(value: f32) -> Self {
         Parameter {
             value: Arc::new(AtomicU32::new(value.to_bits())),
+            smoothing: Arc::new(AtomicU32::new(0.0f32.to_bits())),
+            local_smoothing: None,
+            ramp: Ramp {
+                current: value,
+                target: value,
+                step: 0.0,
+                remaining: 0,
+                sample_rate: 44100.0,
+            },
         }
     }
 
+    /// Sets the default smoothing time in seconds and returns the Parameter.
+    pub fn with_smoothing(self, seconds: f32) -> Self {
+        self.set_smoothing(seconds);
+        self
+    }
+
     /// Sets the parameter value.
     pub fn set(&self, value: f32) {
         self.value.store(value.to_bits(), Ordering::Relaxed);
@@ -26,4 +58,154 @@ impl Parameter {
     pub fn get(&self) -> f32 {
         f32::from_bits(self.value.load(Ordering::Relaxed))
     }
+
+    /// Sets the smoothing time in seconds (0.0 disables smoothing).
+    pub fn set_smoothing(&self, seconds: f32) {
+        self.smoothing
+            .store(seconds.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Gets the smoothing time in seconds.
+    pub fn smoothing(&self) -> f32 {
+        f32::from_bits(self.smoothing.load(Ordering::Relaxed))
+    }
+
+    /// Overrides the smoothing time for this clone only and returns it.
+    ///
+    /// Other clones keep using the shared smoothing time.
+    pub fn with_local_smoothing(mut self, seconds: f32) -> Self {
+        self.local_smoothing = Some(seconds.max(0.0));
+        self
+    }
+
+    fn effective_smoothing(&self) -> f32 {
+        self.local_smoothing.unwrap_or_else(|| self.smoothing())
+    }
+
+    /// Returns true if this clone is gliding towards a new value.
+    pub fn is_ramping(&self) -> bool {
+        self.effective_smoothing() > 0.0
+            && (self.ramp.remaining > 0 || self.ramp.target != self.get())
+    }
+
+    /// Fills the buffer with (smoothed) values, advancing this clone's ramp.
+    pub(crate) fn fill(&mut self, buffer: &mut [f32]) {
+        let target = self.get();
+        let smoothing = self.effective_smoothing();
+        let ramp = &mut self.ramp;
+
+        if smoothing <= 0.0 {
+            ramp.current = target;
+            ramp.target = target;
+            ramp.remaining = 0;
+            buffer.fill(target);
+            return;
+        }
+
+        if target != ramp.target {
+            let samples = libm::roundf(smoothing * ramp.sample_rate).max(1.0);
+            ramp.target = target;
+            ramp.step = (target - ramp.current) / samples;
+            ramp.remaining = samples as u32;
+        }
+
+        if ramp.remaining == 0 {
+            buffer.fill(ramp.current);
+            return;
+        }
+
+        for sample in buffer.iter_mut() {
+            if ramp.remaining > 0 {
+                ramp.remaining -= 1;
+                ramp.current = if ramp.remaining == 0 {
+                    ramp.target
+                } else {
+                    ramp.current + ramp.step
+                };
+            }
+            *sample = ramp.current;
+        }
+    }
+
+    pub(crate) fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.ramp.sample_rate = sample_rate;
+    }
+
+    /// Jumps this clone's ramp to the current value.
+    pub(crate) fn snap(&mut self) {
+        let target = self.get();
+        self.ramp.current = target;
+        self.ramp.target = target;
+        self.ramp.remaining = 0;
+    }
+}
+
+impl Clone for Parameter {
+    /// Clones share the value and smoothing time. The new clone's ramp starts at the current value.
+    fn clone(&self) -> Self {
+        let mut clone = Parameter {
+            value: self.value.clone(),
+            smoothing: self.smoothing.clone(),
+            local_smoothing: self.local_smoothing,
+            ramp: self.ramp,
+        };
+        clone.snap();
+        clone
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+
+    #[test]
+    fn test_smoothing_ramps_linearly() {
+        let ui = Parameter::new(0.0).with_smoothing(0.001);
+        let mut audio = ui.clone();
+        audio.set_sample_rate(1000.0);
+
+        let mut buffer = [0.0; 4];
+        audio.fill(&mut buffer);
+        assert_eq!(buffer, [0.0; 4]);
+
+        ui.set(1.0);
+        assert!(audio.is_ramping());
+        audio.fill(&mut buffer[0..1]);
+        assert_eq!(buffer[0], 1.0);
+        assert!(!audio.is_ramping());
+
+        ui.set_smoothing(0.004);
+        ui.set(0.0);
+        audio.fill(&mut buffer);
+        assert_eq!(buffer, [0.75, 0.5, 0.25, 0.0]);
+    }
+
+    #[test]
+    fn test_no_smoothing_jumps() {
+        let ui = Parameter::new(0.0);
+        let mut audio = ui.clone();
+        let mut buffer = [0.0; 2];
+        audio.fill(&mut buffer);
+        ui.set(2.0);
+        assert!(!audio.is_ramping());
+        audio.fill(&mut buffer);
+        assert_eq!(buffer, [2.0; 2]);
+    }
+
+    #[test]
+    fn test_smoothed_audio_param() {
+        let ui = Parameter::new(0.0);
+        let mut param = AudioParam::smoothed(ui.clone(), 2.0);
+        param.set_sample_rate(1000.0);
+
+        let mut buffer = [0.0; 3];
+        param.process(&mut buffer, 0);
+        ui.set(1.0);
+        assert!(param.get_constant().is_none());
+        param.process(&mut buffer, 3);
+        assert_eq!(buffer, [0.5, 1.0, 1.0]);
+        assert_eq!(param.get_constant(), Some(1.0));
+        assert_eq!(ui.smoothing(), 0.0);
+    }
 }
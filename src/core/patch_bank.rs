@@ -0,0 +1,286 @@
+use crate::core::parameter::Parameter;
+use crate::core::preset::{ParameterTree, Preset};
+use crate::core::tempo::TransportClock;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A named preset of a [`PatchBank`], optionally tied to a tempo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Patch {
+    pub name: String,
+    pub preset: Preset,
+    /// Tempo in BPM set on the bank's clock when the patch is selected.
+    pub tempo: Option<f32>,
+}
+
+impl Patch {
+    /// Creates a new Patch without a tempo.
+    ///
+    /// # Arguments
+    /// * `name` - Display name.
+    /// * `preset` - The parameter values.
+    pub fn new(name: impl Into<String>, preset: Preset) -> Self {
+        Patch {
+            name: name.into(),
+            preset,
+            tempo: None,
+        }
+    }
+
+    /// Sets the tempo associated with the patch and returns it.
+    pub fn with_tempo(mut self, bpm: f32) -> Self {
+        self.tempo = Some(bpm);
+        self
+    }
+}
+
+/// One parameter gliding during a morph.
+struct MorphTarget {
+    parameter: Parameter,
+    start: f32,
+    end: f32,
+}
+
+struct Morph {
+    targets: Vec<MorphTarget>,
+    tempo: Option<(f32, f32)>,
+    elapsed: f32,
+    length: f32,
+}
+
+/// A bank of patches (program changes) for a live set.
+///
+/// The bank owns the [`ParameterTree`] of the running processors and switches all of its
+/// parameters between stored [`Patch`]es, either instantly or by morphing every value
+/// linearly over a time. A patch can carry a tempo, which is applied to the bank's
+/// [`TransportClock`] (and morphed along with the parameters).
+///
+/// The bank lives on a control thread: parameters are shared atomic handles, so
+/// switching never touches the audio thread. Morphs advance when the control thread
+/// calls [`update`](Self::update), e.g. from its UI or MIDI loop.
+pub struct PatchBank {
+    tree: ParameterTree,
+    clock: Option<TransportClock>,
+    patches: Vec<Patch>,
+    current: Option<usize>,
+    morph_time: f32,
+    morph: Option<Morph>,
+}
+
+impl PatchBank {
+    /// Creates a new, empty PatchBank.
+    ///
+    /// # Arguments
+    /// * `tree` - Parameters of the processors the patches control.
+    pub fn new(tree: ParameterTree) -> Self {
+        PatchBank {
+            tree,
+            clock: None,
+            patches: Vec::new(),
+            current: None,
+            morph_time: 0.0,
+            morph: None,
+        }
+    }
+
+    /// Sets the clock that receives the patch tempos and returns the bank.
+    pub fn with_clock(mut self, clock: TransportClock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Sets the switch time in seconds used by [`next_patch`](Self::next_patch) and
+    /// [`previous_patch`](Self::previous_patch) (0.0 = instant).
+    pub fn set_morph_time(&mut self, seconds: f32) {
+        self.morph_time = seconds.max(0.0);
+    }
+
+    /// Adds a patch and returns its index.
+    pub fn add(&mut self, patch: Patch) -> usize {
+        self.patches.push(patch);
+        self.patches.len() - 1
+    }
+
+    /// Stores the current parameter values (and tempo) as a new patch and returns its index.
+    pub fn capture(&mut self, name: impl Into<String>) -> usize {
+        let mut patch = Patch::new(name, self.tree.snapshot());
+        patch.tempo = self.clock.as_ref().map(|c| c.bpm());
+        self.add(patch)
+    }
+
+    /// Replaces the patch at `index`. Returns false if there is no such patch.
+    pub fn replace(&mut self, index: usize, patch: Patch) -> bool {
+        match self.patches.get_mut(index) {
+            Some(slot) => {
+                *slot = patch;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the patch at `index`, if any.
+    pub fn patch(&self, index: usize) -> Option<&Patch> {
+        self.patches.get(index)
+    }
+
+    /// Returns the number of patches.
+    pub fn len(&self) -> usize {
+        self.patches.len()
+    }
+
+    /// Returns true if the bank holds no patches.
+    pub fn is_empty(&self) -> bool {
+        self.patches.is_empty()
+    }
+
+    /// Returns the index of the selected patch (the target of a running morph).
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Returns the parameters controlled by the bank.
+    pub fn tree(&self) -> &ParameterTree {
+        &self.tree
+    }
+
+    /// Returns true while a morph is running.
+    pub fn is_morphing(&self) -> bool {
+        self.morph.is_some()
+    }
+
+    /// Switches to the patch at `index` instantly. Returns false if there is no such patch.
+    pub fn select(&mut self, index: usize) -> bool {
+        let Some(patch) = self.patches.get(index) else {
+            return false;
+        };
+        self.morph = None;
+        self.tree.apply(&patch.preset);
+        if let (Some(clock), Some(bpm)) = (&self.clock, patch.tempo) {
+            clock.set_bpm(bpm);
+        }
+        self.current = Some(index);
+        true
+    }
+
+    /// Starts morphing to the patch at `index` over `seconds`.
+    ///
+    /// A morph started during another one glides on from the current values. Returns false
+    /// if there is no such patch.
+    pub fn morph_to(&mut self, index: usize, seconds: f32) -> bool {
+        if seconds <= 0.0 {
+            return self.select(index);
+        }
+        let Some(patch) = self.patches.get(index) else {
+            return false;
+        };
+
+        let targets = patch
+            .preset
+            .values
+            .iter()
+            .filter_map(|value| {
+                self.tree.find(&value.path).map(|entry| MorphTarget {
+                    parameter: entry.parameter.clone(),
+                    start: entry.parameter.get(),
+                    end: value.value.clamp(entry.min, entry.max),
+                })
+            })
+            .collect();
+        let tempo = match (&self.clock, patch.tempo) {
+            (Some(clock), Some(bpm)) => Some((clock.bpm(), bpm)),
+            _ => None,
+        };
+
+        self.morph = Some(Morph {
+            targets,
+            tempo,
+            elapsed: 0.0,
+            length: seconds,
+        });
+        self.current = Some(index);
+        true
+    }
+
+    /// Switches to the next patch using the morph time. Returns the new index, or None at
+    /// the end of the bank.
+    pub fn next_patch(&mut self) -> Option<usize> {
+        let index = self.current.map_or(0, |i| i + 1);
+        self.step_to(index)
+    }
+
+    /// Switches to the previous patch using the morph time. Returns the new index, or None
+    /// at the start of the bank.
+    pub fn previous_patch(&mut self) -> Option<usize> {
+        let index = self.current?.checked_sub(1)?;
+        self.step_to(index)
+    }
+
+    fn step_to(&mut self, index: usize) -> Option<usize> {
+        self.morph_to(index, self.morph_time).then_some(index)
+    }
+
+    /// Advances a running morph by `elapsed` seconds. Returns true while it is still running.
+    pub fn update(&mut self, elapsed: f32) -> bool {
+        let Some(morph) = &mut self.morph else {
+            return false;
+        };
+        morph.elapsed += elapsed.max(0.0);
+        let t = (morph.elapsed / morph.length).min(1.0);
+
+        for target in &morph.targets {
+            target
+                .parameter
+                .set(target.start + (target.end - target.start) * t);
+        }
+        if let (Some(clock), Some((start, end))) = (&self.clock, morph.tempo) {
+            clock.set_bpm(start + (end - start) * t);
+        }
+
+        if t >= 1.0 {
+            self.morph = None;
+        }
+        self.morph.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+    use crate::core::channels::Mono;
+    use crate::core::dsp_chain::DspChain;
+    use crate::effects::utility::gain::Gain;
+
+    #[test]
+    fn test_select_and_morph() {
+        let gain = Parameter::new(0.2);
+        let chain: DspChain<Mono> =
+            DspChain::new(Gain::new(AudioParam::Linked(gain.clone())), 44100.0);
+        let clock = TransportClock::new(100.0);
+        let mut bank = PatchBank::new(ParameterTree::of(&chain)).with_clock(clock.clone());
+        bank.set_morph_time(1.0);
+
+        assert_eq!(bank.capture("verse"), 0);
+        gain.set(1.0);
+        let chorus = bank.tree().snapshot();
+        bank.add(Patch::new("chorus", chorus).with_tempo(140.0));
+
+        assert!(bank.select(0));
+        assert_eq!((gain.get(), clock.bpm()), (0.2, 100.0));
+        assert!(!bank.select(2));
+
+        assert_eq!(bank.next_patch(), Some(1));
+        assert!(bank.update(0.5));
+        assert!((gain.get() - 0.6).abs() < 1e-6);
+        assert!((clock.bpm() - 120.0).abs() < 1e-4);
+        assert!(!bank.update(0.5));
+        assert_eq!((gain.get(), clock.bpm()), (1.0, 140.0));
+
+        assert_eq!(bank.next_patch(), None);
+        assert_eq!(bank.current(), Some(1));
+        bank.set_morph_time(0.0);
+        assert_eq!(bank.previous_patch(), Some(0));
+        assert_eq!(gain.get(), 0.2);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::core::parameter::Parameter;
+
+/// Soft-takeover wrapper for attaching hardware controllers to a [`Parameter`].
+///
+/// A physical knob or fader rarely matches the parameter it controls (e.g. after loading a
+/// preset). Writing its value straight through would make the parameter jump. A
+/// `PickupParameter` instead ignores incoming controller values until they reach the
+/// current value, either by crossing it or by landing within the catch window, and only
+/// then starts following the controller.
+///
+/// If the parameter is changed by someone else (UI, automation, preset recall), the pickup
+/// is released automatically and the controller has to catch the new value again.
+pub struct PickupParameter {
+    parameter: Parameter,
+    catch_window: f32,
+    picked_up: bool,
+    last_controller: Option<f32>,
+    last_written: f32,
+}
+
+impl PickupParameter {
+    /// Creates a new PickupParameter.
+    ///
+    /// # Arguments
+    /// * `parameter` - The parameter to control.
+    /// * `catch_window` - Distance (in parameter units) within which the controller picks up.
+    pub fn new(parameter: Parameter, catch_window: f32) -> Self {
+        let last_written = parameter.get();
+        PickupParameter {
+            parameter,
+            catch_window: catch_window.abs(),
+            picked_up: false,
+            last_controller: None,
+            last_written,
+        }
+    }
+
+    /// Feeds a new controller value.
+    ///
+    /// Returns `true` if the value was applied to the parameter.
+    pub fn set(&mut self, value: f32) -> bool {
+        let current = self.parameter.get();
+        if self.picked_up && current != self.last_written {
+            self.picked_up = false;
+        }
+
+        if !self.picked_up {
+            let within_window = (value - current).abs() <= self.catch_window;
+            let crossed = self
+                .last_controller
+                .is_some_and(|last| (last - current) * (value - current) <= 0.0);
+            self.picked_up = within_window || crossed;
+        }
+        self.last_controller = Some(value);
+
+        if self.picked_up {
+            self.parameter.set(value);
+            self.last_written = value;
+        }
+        self.picked_up
+    }
+
+    /// Returns true if the controller currently drives the parameter.
+    pub fn is_picked_up(&self) -> bool {
+        self.picked_up && self.parameter.get() == self.last_written
+    }
+
+    /// Releases the pickup, so the controller has to catch the current value again.
+    pub fn release(&mut self) {
+        self.picked_up = false;
+        self.last_controller = None;
+    }
+
+    /// Sets the catch window (in parameter units).
+    pub fn set_catch_window(&mut self, catch_window: f32) {
+        self.catch_window = catch_window.abs();
+    }
+
+    /// Returns the controlled parameter.
+    pub fn parameter(&self) -> &Parameter {
+        &self.parameter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_picks_up_on_crossing() {
+        let param = Parameter::new(0.5);
+        let mut knob = PickupParameter::new(param.clone(), 0.01);
+
+        assert!(!knob.set(0.1));
+        assert!(!knob.set(0.3));
+        assert_eq!(param.get(), 0.5);
+
+        // Jumping over the current value picks it up.
+        assert!(knob.set(0.6));
+        assert_eq!(param.get(), 0.6);
+        assert!(knob.set(0.2));
+        assert_eq!(param.get(), 0.2);
+    }
+
+    #[test]
+    fn test_catch_window_and_external_change() {
+        let param = Parameter::new(0.5);
+        let mut knob = PickupParameter::new(param.clone(), 0.05);
+
+        assert!(knob.set(0.46));
+        assert!(knob.is_picked_up());
+
+        // A preset recall moves the parameter away from the knob.
+        param.set(0.9);
+        assert!(!knob.is_picked_up());
+        assert!(!knob.set(0.47));
+        assert_eq!(param.get(), 0.9);
+        assert!(knob.set(0.88));
+        assert_eq!(param.get(), 0.88);
+    }
+}
@@ -0,0 +1,376 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::ChannelConfig;
+use crate::core::parameter::Parameter;
+use crate::FrameProcessor;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const MAGIC: &[u8; 4] = b"IDSP";
+const VERSION: u8 = 1;
+
+/// A user-facing parameter: a shared [`Parameter`] handle with a name and a valid range.
+#[derive(Clone)]
+pub struct ParameterEntry {
+    pub name: String,
+    pub parameter: Parameter,
+    pub min: f32,
+    pub max: f32,
+}
+
+/// The parameters of a processor and of the processors it contains.
+///
+/// Processors fill a tree in [`FrameProcessor::parameters`]; containers such as `DspChain`
+/// add one child per contained processor, named by its position. A parameter is addressed
+/// by the `/`-separated names from the root, e.g. `1/gain`. The entries are shared handles,
+/// so setting them (or applying a [`Preset`]) changes the running processors.
+#[derive(Clone, Default)]
+pub struct ParameterTree {
+    pub name: String,
+    pub parameters: Vec<ParameterEntry>,
+    pub children: Vec<ParameterTree>,
+}
+
+impl ParameterTree {
+    /// Creates a new, empty ParameterTree.
+    pub fn new(name: impl Into<String>) -> Self {
+        ParameterTree {
+            name: name.into(),
+            parameters: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Collects the parameter tree of a processor.
+    pub fn of<C: ChannelConfig, P: FrameProcessor<C> + ?Sized>(processor: &P) -> Self {
+        let mut tree = ParameterTree::new("");
+        processor.parameters(&mut tree);
+        tree
+    }
+
+    /// Adds a parameter.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the parameter, unique within this node.
+    /// * `parameter` - The shared parameter handle.
+    /// * `min` - Lowest valid value.
+    /// * `max` - Highest valid value.
+    pub fn add(&mut self, name: impl Into<String>, parameter: Parameter, min: f32, max: f32) {
+        self.parameters.push(ParameterEntry {
+            name: name.into(),
+            parameter,
+            min,
+            max,
+        });
+    }
+
+    /// Adds an AudioParam if it is linked to a Parameter. Static and dynamic values are skipped.
+    pub fn add_audio_param(
+        &mut self,
+        name: impl Into<String>,
+        param: &AudioParam,
+        min: f32,
+        max: f32,
+    ) {
+        if let AudioParam::Linked(parameter) = param {
+            self.add(name, parameter.clone(), min, max);
+        }
+    }
+
+    /// Adds a child node, unless it has no parameters.
+    pub fn add_child(&mut self, child: ParameterTree) {
+        if !child.is_empty() {
+            self.children.push(child);
+        }
+    }
+
+    /// Collects the parameters of a contained processor into a child node.
+    pub fn add_processor<C: ChannelConfig, P: FrameProcessor<C> + ?Sized>(
+        &mut self,
+        name: impl Into<String>,
+        processor: &P,
+    ) {
+        let mut child = ParameterTree::new(name);
+        processor.parameters(&mut child);
+        self.add_child(child);
+    }
+
+    /// Returns true if neither this node nor its children hold parameters.
+    pub fn is_empty(&self) -> bool {
+        self.parameters.is_empty() && self.children.iter().all(|c| c.is_empty())
+    }
+
+    /// Returns every parameter together with its path, depth first.
+    pub fn entries(&self) -> Vec<(String, &ParameterEntry)> {
+        let mut output = Vec::new();
+        self.collect("", &mut output);
+        output
+    }
+
+    fn collect<'a>(&'a self, prefix: &str, output: &mut Vec<(String, &'a ParameterEntry)>) {
+        for entry in &self.parameters {
+            output.push((join(prefix, &entry.name), entry));
+        }
+        for child in &self.children {
+            child.collect(&join(prefix, &child.name), output);
+        }
+    }
+
+    /// Returns the parameter at `path`, if any.
+    pub fn find(&self, path: &str) -> Option<&ParameterEntry> {
+        match path.split_once('/') {
+            Some((head, rest)) => self
+                .children
+                .iter()
+                .find(|c| c.name == head)
+                .and_then(|c| c.find(rest)),
+            None => self.parameters.iter().find(|p| p.name == path),
+        }
+    }
+
+    /// Captures the current value of every parameter.
+    pub fn snapshot(&self) -> Preset {
+        Preset {
+            values: self
+                .entries()
+                .into_iter()
+                .map(|(path, entry)| PresetValue {
+                    path,
+                    value: entry.parameter.get(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Sets the parameters stored in a preset, clamped to their ranges.
+    ///
+    /// Values whose path does not exist in this tree are ignored, so presets survive
+    /// processors being added or removed. Returns the number of parameters set.
+    pub fn apply(&self, preset: &Preset) -> usize {
+        let mut applied = 0;
+        for value in &preset.values {
+            if let Some(entry) = self.find(&value.path) {
+                entry.parameter.set(value.value.clamp(entry.min, entry.max));
+                applied += 1;
+            }
+        }
+        applied
+    }
+}
+
+fn join(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        String::from(name)
+    } else {
+        let mut path = String::from(prefix);
+        path.push('/');
+        path.push_str(name);
+        path
+    }
+}
+
+/// Errors returned when decoding a preset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PresetError {
+    /// The data ended in the middle of a field.
+    Truncated,
+    /// The data does not start with the preset magic bytes.
+    BadMagic,
+    /// The data was written by an unknown format version.
+    UnsupportedVersion(u8),
+    /// A path is not valid UTF-8.
+    InvalidPath,
+    /// The JSON could not be parsed.
+    #[cfg(feature = "serde")]
+    InvalidJson,
+}
+
+impl core::fmt::Display for PresetError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PresetError::Truncated => write!(f, "preset data is truncated"),
+            PresetError::BadMagic => write!(f, "not a preset"),
+            PresetError::UnsupportedVersion(v) => write!(f, "unsupported preset version {}", v),
+            PresetError::InvalidPath => write!(f, "preset path is not valid UTF-8"),
+            #[cfg(feature = "serde")]
+            PresetError::InvalidJson => write!(f, "invalid preset JSON"),
+        }
+    }
+}
+
+/// One stored parameter value.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PresetValue {
+    pub path: String,
+    pub value: f32,
+}
+
+/// A saved set of parameter values, see [`ParameterTree::snapshot`].
+///
+/// The binary format is the magic `IDSP`, a version byte, a little-endian `u32` count and
+/// then, per value, a `u16` path length, the UTF-8 path and the value as `f32`.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Preset {
+    pub values: Vec<PresetValue>,
+}
+
+impl Preset {
+    /// Encodes the preset in the compact binary format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let size: usize = self.values.iter().map(|v| 6 + v.path.len()).sum();
+        let mut output = Vec::with_capacity(9 + size);
+        output.extend_from_slice(MAGIC);
+        output.push(VERSION);
+        output.extend_from_slice(&(self.values.len() as u32).to_le_bytes());
+        for value in &self.values {
+            let path = &value.path.as_bytes()[0..value.path.len().min(u16::MAX as usize)];
+            output.extend_from_slice(&(path.len() as u16).to_le_bytes());
+            output.extend_from_slice(path);
+            output.extend_from_slice(&value.value.to_le_bytes());
+        }
+        output
+    }
+
+    /// Decodes a preset from the compact binary format.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, PresetError> {
+        let mut reader = Reader { data, pos: 0 };
+        if reader.take(4)? != MAGIC {
+            return Err(PresetError::BadMagic);
+        }
+        let version = reader.take(1)?[0];
+        if version != VERSION {
+            return Err(PresetError::UnsupportedVersion(version));
+        }
+
+        let count = u32::from_le_bytes(reader.array()?) as usize;
+        let mut values = Vec::with_capacity(count.min(data.len() / 6));
+        for _ in 0..count {
+            let len = u16::from_le_bytes(reader.array()?) as usize;
+            let path =
+                core::str::from_utf8(reader.take(len)?).map_err(|_| PresetError::InvalidPath)?;
+            let value = f32::from_le_bytes(reader.array()?);
+            values.push(PresetValue {
+                path: String::from(path),
+                value,
+            });
+        }
+        Ok(Preset { values })
+    }
+
+    /// Encodes the preset as JSON.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Decodes a preset from JSON.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, PresetError> {
+        serde_json::from_str(json).map_err(|_| PresetError::InvalidJson)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], PresetError> {
+        let end = self.pos.checked_add(len).ok_or(PresetError::Truncated)?;
+        let bytes = self.data.get(self.pos..end).ok_or(PresetError::Truncated)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], PresetError> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::{DualMono, Mono};
+    use crate::core::dsp_chain::DspChain;
+    use crate::core::static_dsp_chain::StaticDspChain;
+    use crate::effects::filter::biquad::Biquad;
+    use crate::effects::utility::gain::Gain;
+
+    fn chain(first: &Parameter, second: &Parameter) -> DspChain<Mono> {
+        DspChain::new(Gain::new(AudioParam::Linked(first.clone())), 44100.0)
+            .and(Gain::new_fixed(0.5))
+            .and(Gain::new(AudioParam::Linked(second.clone())))
+    }
+
+    #[test]
+    fn test_tree_snapshot_and_apply() {
+        let first = Parameter::new(0.25);
+        let second = Parameter::new(1.0);
+        let chain = chain(&first, &second);
+
+        let tree = ParameterTree::of(&chain);
+        let paths: Vec<_> = tree.entries().into_iter().map(|(p, _)| p).collect();
+        assert_eq!(paths, ["0/gain", "2/gain"]);
+
+        let preset = tree.snapshot();
+        first.set(0.0);
+        second.set(3.0);
+
+        let mut stored = preset.clone();
+        stored.values.push(PresetValue {
+            path: String::from("7/gain"),
+            value: 1.0,
+        });
+        stored.values[1].value = 100.0;
+        assert_eq!(tree.apply(&stored), 2);
+        assert_eq!(first.get(), 0.25);
+        assert_eq!(second.get(), 4.0);
+    }
+
+    #[test]
+    fn test_wrappers_forward_parameters() {
+        let cutoff = Parameter::new(1000.0);
+        let level = Parameter::new(0.5);
+        let filtered = StaticDspChain::new(
+            Biquad::new_lowpass(AudioParam::Linked(cutoff), AudioParam::Static(0.7)),
+            44100.0,
+        )
+        .and(Gain::new_fixed(0.5));
+        let stereo = DualMono::new(filtered, Gain::new(AudioParam::Linked(level)));
+
+        let tree = ParameterTree::of(&stereo);
+        let paths: Vec<_> = tree.entries().into_iter().map(|(p, _)| p).collect();
+        assert_eq!(paths, ["Left/0/frequency", "Right/gain"]);
+    }
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let tree = ParameterTree::of(&chain(&Parameter::new(0.1), &Parameter::new(0.9)));
+        let preset = tree.snapshot();
+        let bytes = preset.to_bytes();
+
+        assert_eq!(Preset::from_bytes(&bytes), Ok(preset));
+        assert_eq!(
+            Preset::from_bytes(&bytes[0..bytes.len() - 1]),
+            Err(PresetError::Truncated)
+        );
+        assert_eq!(Preset::from_bytes(b"RIFF\x01"), Err(PresetError::BadMagic));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_roundtrip() {
+        let tree = ParameterTree::of(&chain(&Parameter::new(0.5), &Parameter::new(2.0)));
+        let preset = tree.snapshot();
+        let json = preset.to_json();
+        assert_eq!(
+            json,
+            r#"{"values":[{"path":"0/gain","value":0.5},{"path":"2/gain","value":2.0}]}"#
+        );
+        assert_eq!(Preset::from_json(&json), Ok(preset));
+    }
+}
@@ -0,0 +1,217 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
+use crate::FrameProcessor;
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// A monotonic clock for [`Probe`]s, in arbitrary ticks.
+///
+/// The crate has no clock of its own. On a microcontroller, pass a function reading a cycle
+/// counter (e.g. the Cortex-M `DWT::cycle_count`); on a desktop, nanoseconds since a fixed
+/// `Instant`.
+pub type Clock = fn() -> u64;
+
+/// Timing statistics of a [`Probe`], in clock ticks per block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProfileStats {
+    /// Number of blocks measured.
+    pub blocks: u32,
+    /// Time spent on the last block.
+    pub last: u32,
+    /// Moving average over roughly the last 16 blocks.
+    pub average: u32,
+    /// The longest block since the last reset.
+    pub max: u32,
+}
+
+#[derive(Default)]
+struct ProbeSlot {
+    blocks: AtomicU32,
+    last: AtomicU32,
+    average: AtomicU32,
+    max: AtomicU32,
+}
+
+/// A shared handle to the statistics of a [`Probe`], readable from any thread.
+#[derive(Clone, Default)]
+pub struct ProbeHandle {
+    slot: Arc<ProbeSlot>,
+}
+
+impl ProbeHandle {
+    /// Creates a new ProbeHandle.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the statistics collected so far.
+    pub fn stats(&self) -> ProfileStats {
+        ProfileStats {
+            blocks: self.slot.blocks.load(Ordering::Relaxed),
+            last: self.slot.last.load(Ordering::Relaxed),
+            average: self.slot.average.load(Ordering::Relaxed),
+            max: self.slot.max.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Clears the statistics.
+    pub fn clear(&self) {
+        self.slot.blocks.store(0, Ordering::Relaxed);
+        self.slot.last.store(0, Ordering::Relaxed);
+        self.slot.average.store(0, Ordering::Relaxed);
+        self.slot.max.store(0, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record(&self, ticks: u64) {
+        let ticks = ticks.min(u32::MAX as u64) as u32;
+        let slot = &self.slot;
+        let blocks = slot.blocks.load(Ordering::Relaxed);
+        let average = if blocks == 0 {
+            ticks
+        } else {
+            let previous = slot.average.load(Ordering::Relaxed) as i64;
+            (previous + (ticks as i64 - previous) / 16) as u32
+        };
+        slot.last.store(ticks, Ordering::Relaxed);
+        slot.average.store(average, Ordering::Relaxed);
+        slot.max.store(
+            slot.max.load(Ordering::Relaxed).max(ticks),
+            Ordering::Relaxed,
+        );
+        slot.blocks
+            .store(blocks.saturating_add(1), Ordering::Relaxed);
+    }
+}
+
+/// A timing probe around a processor.
+///
+/// Reads the clock before and after every `process` call of the inner processor and
+/// records the difference to a [`ProbeHandle`]. Everything else is forwarded unchanged.
+/// `StaticDspChain::and_profiled` adds probes for you; `DspChain::with_profiling` times its
+/// processors the same way.
+pub struct Probe<P> {
+    inner: P,
+    clock: Clock,
+    handle: ProbeHandle,
+}
+
+impl<P> Probe<P> {
+    /// Creates a new Probe.
+    ///
+    /// # Arguments
+    /// * `inner` - The processor to time.
+    /// * `clock` - The clock to read.
+    pub fn new(inner: P, clock: Clock) -> Self {
+        Self::with_handle(inner, clock, ProbeHandle::new())
+    }
+
+    /// Creates a new Probe that records to an existing handle.
+    pub fn with_handle(inner: P, clock: Clock, handle: ProbeHandle) -> Self {
+        Probe {
+            inner,
+            clock,
+            handle,
+        }
+    }
+
+    /// Returns a handle to the statistics.
+    pub fn handle(&self) -> ProbeHandle {
+        self.handle.clone()
+    }
+}
+
+impl<C: ChannelConfig, P: FrameProcessor<C>> FrameProcessor<C> for Probe<P> {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let start = (self.clock)();
+        self.inner.process(buffer, sample_index);
+        self.handle.record((self.clock)().saturating_sub(start));
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.inner.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.inner.prepare(max_block_size);
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    fn latency_samples(&self) -> u32 {
+        self.inner.latency_samples()
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        self.inner.parameters(tree);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        self.inner.validate(report);
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        self.inner.describe(node);
+    }
+
+    fn visualize(&self, indent: usize) -> String {
+        self.inner.visualize(indent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::Mono;
+    use crate::core::dsp_chain::DspChain;
+    use crate::core::static_dsp_chain::StaticDspChain;
+    use core::sync::atomic::AtomicU64;
+
+    static NOW: AtomicU64 = AtomicU64::new(0);
+
+    fn fake_clock() -> u64 {
+        NOW.load(Ordering::Relaxed)
+    }
+
+    /// Advances the fake clock by the given number of ticks per sample.
+    struct Busy(u64);
+
+    impl FrameProcessor<Mono> for Busy {
+        fn process(&mut self, buffer: &mut [f32], _sample_index: u64) {
+            NOW.fetch_add(self.0 * buffer.len() as u64, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_chain_profiling() {
+        let mut chain = DspChain::new(Busy(1), 44100.0)
+            .with_profiling(fake_clock)
+            .and(Busy(10));
+        let probes = chain.probes();
+        assert_eq!(probes.len(), 2);
+
+        for len in [64, 64, 128] {
+            let mut buffer = alloc::vec![0.0; len];
+            chain.process(&mut buffer, 0);
+        }
+        let cheap = probes[0].stats();
+        let expensive = probes[1].stats();
+        assert_eq!((cheap.blocks, cheap.last, cheap.max), (3, 128, 128));
+        assert_eq!((expensive.last, expensive.max), (1280, 1280));
+        assert!(expensive.average > 640 && expensive.average < 1280);
+
+        let mut chain =
+            StaticDspChain::<Mono, _>::new(Busy(2), 44100.0).and_profiled(Busy(3), fake_clock);
+        let mut buffer = [0.0; 32];
+        chain.process(&mut buffer, 0);
+        assert_eq!(chain.probes()[0].stats().last, 96);
+    }
+}
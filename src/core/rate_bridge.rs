@@ -0,0 +1,357 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
+use crate::FrameProcessor;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+
+/// Half the number of filter taps.
+const HALF_TAPS: usize = 16;
+const TAPS: usize = 2 * HALF_TAPS;
+/// Fractional positions in the filter table; positions in between are interpolated.
+const PHASES: usize = 256;
+/// Delay of a resampler in input frames, so an output frame never needs unseen input.
+const DELAY: i64 = HALF_TAPS as i64 + 1;
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// A streaming polyphase windowed-sinc resampler at a fixed rational ratio.
+///
+/// Output frame `n` is the input interpolated at `n · from / to - DELAY`. Positions are
+/// exact integer fractions, so the output only depends on the input, not on how it is
+/// split into chunks.
+struct Resampler {
+    channels: usize,
+    step_num: u64,
+    step_den: u64,
+    // (PHASES + 1) rows of TAPS coefficients.
+    table: Vec<f32>,
+    // Interleaved input from absolute frame `base` on.
+    history: Vec<f32>,
+    base: u64,
+    emitted: u64,
+}
+
+impl Resampler {
+    fn new(channels: usize, from: u64, to: u64) -> Self {
+        let divisor = gcd(from, to).max(1);
+        // Cut off below the lower of the two Nyquist frequencies, relative to the input rate.
+        let cutoff = 0.5 * 0.95 * (to as f32 / from as f32).min(1.0);
+
+        let mut table = vec![0.0; (PHASES + 1) * TAPS];
+        for (phase, row) in table.chunks_exact_mut(TAPS).enumerate() {
+            let fraction = phase as f32 / PHASES as f32;
+            for (k, coefficient) in row.iter_mut().enumerate() {
+                let distance = k as f32 - (HALF_TAPS as f32 - 1.0) - fraction;
+                let x = 2.0 * cutoff * distance;
+                let sinc = if x.abs() < 1e-6 {
+                    1.0
+                } else {
+                    libm::sinf(PI * x) / (PI * x)
+                };
+                let w = (distance / HALF_TAPS as f32).clamp(-1.0, 1.0);
+                let window = 0.42 + 0.5 * libm::cosf(PI * w) + 0.08 * libm::cosf(2.0 * PI * w);
+                *coefficient = sinc * window;
+            }
+            // Unity gain at DC for every phase.
+            let sum: f32 = row.iter().sum();
+            row.iter_mut().for_each(|c| *c /= sum);
+        }
+
+        Resampler {
+            channels,
+            step_num: from / divisor,
+            step_den: to / divisor,
+            table,
+            history: Vec::new(),
+            base: 0,
+            emitted: 0,
+        }
+    }
+
+    fn push(&mut self, input: &[f32]) {
+        self.history.extend_from_slice(input);
+    }
+
+    /// Fills `output` with the next frames.
+    fn emit(&mut self, output: &mut [f32]) {
+        let channels = self.channels;
+        let end = self.base as i64 + (self.history.len() / channels) as i64;
+
+        for frame in output.chunks_exact_mut(channels) {
+            let position = self.emitted * self.step_num;
+            let index = (position / self.step_den) as i64 - DELAY;
+            let phase = (position % self.step_den) as f32 * PHASES as f32 / self.step_den as f32;
+            let row = (phase as usize).min(PHASES - 1);
+            let blend = phase - row as f32;
+            let (low, high) = self.table[row * TAPS..(row + 2) * TAPS].split_at(TAPS);
+
+            frame.fill(0.0);
+            let first = index - HALF_TAPS as i64 + 1;
+            debug_assert!(first + TAPS as i64 <= end);
+            for k in 0..TAPS {
+                let j = first + k as i64;
+                if j < self.base as i64 {
+                    // Before the start of the stream.
+                    continue;
+                }
+                let coefficient = low[k] + (high[k] - low[k]) * blend;
+                let offset = (j - self.base as i64) as usize * channels;
+                for (c, sample) in frame.iter_mut().enumerate() {
+                    *sample += self.history[offset + c] * coefficient;
+                }
+            }
+            self.emitted += 1;
+        }
+
+        // Drop the input the next frame no longer reaches.
+        let next = (self.emitted * self.step_num / self.step_den) as i64 - DELAY;
+        let keep_from = (next - HALF_TAPS as i64 + 1).max(self.base as i64) as u64;
+        let drop = ((keep_from - self.base) as usize).min(self.history.len() / channels);
+        self.history.drain(0..drop * channels);
+        self.base += drop as u64;
+    }
+
+    fn reserve(&mut self, frames: usize) {
+        let needed = (frames + TAPS + 2) * self.channels;
+        if self.history.capacity() < needed {
+            self.history.reserve(needed - self.history.len());
+        }
+    }
+
+    fn reset(&mut self) {
+        self.history.clear();
+        self.base = 0;
+        self.emitted = 0;
+    }
+}
+
+/// Runs a chain at its design sample rate inside a host running at another rate.
+///
+/// Patches tuned at one rate (e.g. 44.1 kHz) can sound different at another unless every
+/// processor handles `set_sample_rate` perfectly. `RateBridge` keeps the inner chain at its
+/// design rate: the device input is resampled down (or up) to it, the chain runs, and its
+/// output is resampled back to the device rate with a 32-tap polyphase windowed-sinc filter.
+///
+/// The number of design frames per device block follows from the exact rate ratio and the
+/// running frame count, so the output does not depend on the host's block sizes. The inner
+/// chain sees sample indices counted at the design rate. The two filters add latency,
+/// reported by `latency_samples`. When the rates match, the bridge is a pass-through.
+pub struct RateBridge<P, C: ChannelConfig> {
+    inner: P,
+    design_rate: u64,
+    device_rate: u64,
+    input: Resampler,
+    output: Resampler,
+    inner_buffer: Vec<f32>,
+    device_frames: u64,
+    design_frames: u64,
+    _marker: core::marker::PhantomData<C>,
+}
+
+impl<P: FrameProcessor<C>, C: ChannelConfig> RateBridge<P, C> {
+    /// Creates a new RateBridge.
+    ///
+    /// # Arguments
+    /// * `inner` - The chain to run at its design rate.
+    /// * `design_rate` - The sample rate the chain was designed for, in Hz.
+    pub fn new(mut inner: P, design_rate: f32) -> Self {
+        assert!(
+            design_rate >= 1.0,
+            "RateBridge: Design rate must be positive."
+        );
+        inner.set_sample_rate(design_rate);
+        let rate = libm::roundf(design_rate) as u64;
+        let channels = C::num_channels();
+        RateBridge {
+            inner,
+            design_rate: rate,
+            device_rate: rate,
+            input: Resampler::new(channels, rate, rate),
+            output: Resampler::new(channels, rate, rate),
+            inner_buffer: Vec::with_capacity(128),
+            device_frames: 0,
+            design_frames: 0,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Returns a mutable reference to the inner chain.
+    pub fn inner_mut(&mut self) -> &mut P {
+        &mut self.inner
+    }
+
+    fn clear(&mut self) {
+        self.input.reset();
+        self.output.reset();
+        self.device_frames = 0;
+        self.design_frames = 0;
+    }
+}
+
+impl<P: FrameProcessor<C>, C: ChannelConfig> FrameProcessor<C> for RateBridge<P, C> {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        if self.device_rate == self.design_rate {
+            self.inner.process(buffer, sample_index);
+            return;
+        }
+
+        let channels = C::num_channels();
+        self.device_frames += (buffer.len() / channels) as u64;
+        let design_total = self.device_frames * self.design_rate / self.device_rate;
+        let frames = (design_total - self.design_frames) as usize;
+
+        if self.inner_buffer.len() < frames * channels {
+            self.inner_buffer.resize(frames * channels, 0.0);
+        }
+        let inner_buffer = &mut self.inner_buffer[0..frames * channels];
+
+        self.input.push(buffer);
+        self.input.emit(inner_buffer);
+        self.inner.process(inner_buffer, self.design_frames);
+        self.design_frames = design_total;
+
+        self.output.push(inner_buffer);
+        self.output.emit(buffer);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        let channels = C::num_channels();
+        self.device_rate = (libm::roundf(sample_rate) as u64).max(1);
+        self.input = Resampler::new(channels, self.device_rate, self.design_rate);
+        self.output = Resampler::new(channels, self.design_rate, self.device_rate);
+        self.clear();
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        let design_block =
+            (max_block_size as u64 * self.design_rate / self.device_rate) as usize + 1;
+        self.inner.prepare(design_block);
+        self.inner_buffer
+            .resize(design_block * C::num_channels(), 0.0);
+        self.input.reserve(max_block_size);
+        self.output.reserve(design_block);
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.clear();
+    }
+
+    fn latency_samples(&self) -> u32 {
+        if self.device_rate == self.design_rate {
+            return self.inner.latency_samples();
+        }
+        // One filter delay at the device rate, one (plus the chain) at the design rate.
+        let design_latency = (DELAY as u64 + self.inner.latency_samples() as u64) as f32
+            * self.device_rate as f32
+            / self.design_rate as f32;
+        libm::roundf(DELAY as f32 + design_latency) as u32
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_processor::<C, _>("0", &self.inner);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.add_processor::<C, _>("0", &self.inner);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "RateBridge"
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.add_processor::<C, _>("0", &self.inner);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn visualize(&self, indent: usize) -> alloc::string::String {
+        use core::fmt::Write;
+
+        let spaces = " ".repeat(indent);
+        let mut output = alloc::string::String::new();
+        let _ = writeln!(
+            output,
+            "{}RateBridge ({} Hz -> {} Hz)",
+            spaces, self.design_rate, self.device_rate
+        );
+        output.push_str(&self.inner.visualize(indent + 2));
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+    use crate::core::channels::{Mono, Stereo};
+    use crate::effects::utility::gain::Gain;
+    use crate::synthesis::oscillator::{Oscillator, Waveform};
+
+    fn render<P: FrameProcessor<Mono>>(processor: &mut P, frames: usize, block: usize) -> Vec<f32> {
+        let mut buffer = vec![0.0; frames];
+        let mut index = 0;
+        for chunk in buffer.chunks_mut(block) {
+            processor.process(chunk, index);
+            index += chunk.len() as u64;
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_pitch_survives_rate_change() {
+        let bridge = || {
+            let osc = Oscillator::new(AudioParam::hz(1000.0), Waveform::Sine);
+            let mut bridge = RateBridge::<_, Mono>::new(osc, 44100.0);
+            bridge.set_sample_rate(48000.0);
+            bridge
+        };
+        let a = render(&mut bridge(), 9600, 64);
+        let b = render(&mut bridge(), 9600, 100);
+        assert_eq!(a, b);
+
+        // 1 kHz at 48 kHz: 48 samples per cycle, i.e. 2 sign changes per 48 frames.
+        let crossings = a[480..9600]
+            .windows(2)
+            .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+            .count();
+        assert!((379..=381).contains(&crossings));
+        let peak = a[480..].iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!((peak - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_reports_latency() {
+        let mut bridge = RateBridge::<_, Stereo>::new(Gain::new_fixed(1.0), 44100.0);
+        FrameProcessor::<Stereo>::set_sample_rate(&mut bridge, 96000.0);
+        FrameProcessor::<Stereo>::prepare(&mut bridge, 64);
+
+        let mut buffer = vec![0.0; 2 * 256];
+        buffer[0] = 1.0;
+        buffer[1] = 1.0;
+        for block in buffer.chunks_mut(2 * 64) {
+            FrameProcessor::<Stereo>::process(&mut bridge, block, 0);
+        }
+        let peak = buffer
+            .iter()
+            .step_by(2)
+            .enumerate()
+            .fold(
+                (0, 0.0f32),
+                |best, (i, &s)| if s > best.1 { (i, s) } else { best },
+            );
+        let latency = FrameProcessor::<Stereo>::latency_samples(&bridge) as usize;
+        assert!(peak.0.abs_diff(latency) <= 1);
+        assert_eq!(buffer[2 * peak.0], buffer[2 * peak.0 + 1]);
+    }
+}
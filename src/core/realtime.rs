@@ -0,0 +1,158 @@
+use crate::core::channels::ChannelConfig;
+use crate::FrameProcessor;
+use alloc::vec;
+use core::alloc::{GlobalAlloc, Layout};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static AUDITING: AtomicBool = AtomicBool::new(false);
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static DEALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// A global allocator wrapper that counts allocations made during an [`audit`].
+///
+/// Install it in the application or test binary that runs the audit:
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOC: AuditAllocator<std::alloc::System> = AuditAllocator::new(std::alloc::System);
+/// ```
+///
+/// Outside an audit it only forwards to the inner allocator.
+pub struct AuditAllocator<A> {
+    inner: A,
+}
+
+impl<A> AuditAllocator<A> {
+    /// Creates a new AuditAllocator.
+    ///
+    /// # Arguments
+    /// * `inner` - The allocator that does the work.
+    pub const fn new(inner: A) -> Self {
+        AuditAllocator { inner }
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for AuditAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        INSTALLED.store(true, Ordering::Relaxed);
+        if AUDITING.load(Ordering::Relaxed) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        self.inner.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if AUDITING.load(Ordering::Relaxed) {
+            DEALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        self.inner.dealloc(ptr, layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        INSTALLED.store(true, Ordering::Relaxed);
+        if AUDITING.load(Ordering::Relaxed) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if AUDITING.load(Ordering::Relaxed) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        self.inner.realloc(ptr, layout, new_size)
+    }
+}
+
+/// The result of an [`audit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditReport {
+    /// Allocations and reallocations made while processing.
+    pub allocations: usize,
+    /// Deallocations made while processing.
+    pub deallocations: usize,
+    /// False if no [`AuditAllocator`] is installed, in which case nothing was counted.
+    pub instrumented: bool,
+}
+
+impl AuditReport {
+    /// Returns true if the allocator was installed and `process` never touched the heap.
+    pub fn is_clean(&self) -> bool {
+        self.instrumented && self.allocations == 0 && self.deallocations == 0
+    }
+}
+
+/// Runs a processor like a host would and counts heap use inside `process`.
+///
+/// The processor is given the sample rate and prepared for `block_size` first, so lazy
+/// buffer growth in `process` is not reported, only allocations that a prepared processor
+/// still makes on the audio thread. The counters are global: run one audit at a time, and
+/// keep other threads quiet, since their allocations are counted as well.
+///
+/// This is an allocation audit only: locks, blocking calls and other real-time hazards in
+/// `process` are not detected.
+///
+/// # Arguments
+/// * `processor` - The processor or chain to audit.
+/// * `sample_rate` - Sample rate in Hz.
+/// * `block_size` - Frames per block.
+/// * `blocks` - Number of blocks to process.
+pub fn audit<C: ChannelConfig, P: FrameProcessor<C> + ?Sized>(
+    processor: &mut P,
+    sample_rate: f32,
+    block_size: usize,
+    blocks: usize,
+) -> AuditReport {
+    processor.set_sample_rate(sample_rate);
+    processor.prepare(block_size);
+    let mut buffer = vec![0.0; block_size * C::num_channels()];
+
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    DEALLOCATIONS.store(0, Ordering::Relaxed);
+    AUDITING.store(true, Ordering::SeqCst);
+    for block in 0..blocks {
+        // A click per block keeps envelopes, gates and dynamics busy.
+        buffer.fill(0.0);
+        buffer[0] = 1.0;
+        processor.process(&mut buffer, (block * block_size) as u64);
+    }
+    AUDITING.store(false, Ordering::SeqCst);
+
+    AuditReport {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        deallocations: DEALLOCATIONS.load(Ordering::Relaxed),
+        instrumented: INSTALLED.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::core::channels::Mono;
+    use alloc::vec::Vec;
+    use std::alloc::System;
+
+    #[global_allocator]
+    static ALLOC: AuditAllocator<System> = AuditAllocator::new(System);
+
+    #[test]
+    fn test_audit_counts_allocations() {
+        struct Allocating(Vec<Vec<f32>>);
+        impl FrameProcessor<Mono> for Allocating {
+            fn process(&mut self, buffer: &mut [f32], _sample_index: u64) {
+                self.0.push(buffer.to_vec());
+            }
+        }
+
+        // Other tests run in parallel and may add to the counts, so only a lower bound
+        // holds.
+        let mut allocating = Allocating(Vec::with_capacity(8));
+        let report = audit::<Mono, _>(&mut allocating, 44100.0, 64, 4);
+        assert!(report.instrumented);
+        assert!(report.allocations >= 4);
+        assert!(!report.is_clean());
+    }
+}
@@ -0,0 +1,470 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::frame_processor::FrameProcessor;
+use crate::core::summing_mixer::SummingMixer;
+use crate::core::tempo::TransportClock;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Renders a processor offline, without an audio backend.
+///
+/// The processor is driven with silent input in blocks of `block_size` frames and
+/// the sample index advances exactly as it would in a realtime callback, so the
+/// result is deterministic and suitable for regression tests or bouncing to disk.
+///
+/// # Arguments
+/// * `processor` - The processor to render (usually a source such as an Oscillator or a chain).
+/// * `num_samples` - Number of frames to render.
+/// * `block_size` - Number of frames passed to each `process` call.
+///
+/// Returns the rendered audio, interleaved for multi-channel configurations.
+pub fn render_offline<C, P>(processor: &mut P, num_samples: usize, block_size: usize) -> Vec<f32>
+where
+    C: ChannelConfig,
+    P: FrameProcessor<C> + ?Sized,
+{
+    let mut output = vec![0.0; num_samples * C::num_channels()];
+    process_offline::<C, P>(processor, &mut output, block_size);
+    output
+}
+
+/// Renders a processor offline, feeding it the given input signal.
+///
+/// Works like [`render_offline`], but the processor receives `input` (interleaved)
+/// instead of silence. Useful for rendering effects.
+pub fn render_offline_with_input<C, P>(
+    processor: &mut P,
+    input: &[f32],
+    block_size: usize,
+) -> Vec<f32>
+where
+    C: ChannelConfig,
+    P: FrameProcessor<C> + ?Sized,
+{
+    let mut output = input.to_vec();
+    process_offline::<C, P>(processor, &mut output, block_size);
+    output
+}
+
+/// Processes an interleaved buffer in place, block by block.
+///
+/// Any trailing samples that do not form a complete frame are left untouched.
+pub fn process_offline<C, P>(processor: &mut P, buffer: &mut [f32], block_size: usize)
+where
+    C: ChannelConfig,
+    P: FrameProcessor<C> + ?Sized,
+{
+    assert!(
+        block_size > 0,
+        "render: block_size must be at least one frame."
+    );
+
+    let channels = C::num_channels();
+    let frames = buffer.len() / channels;
+    let mut sample_index = 0u64;
+
+    for block in buffer[0..frames * channels].chunks_mut(block_size * channels) {
+        processor.process(block, sample_index);
+        sample_index += (block.len() / channels) as u64;
+    }
+}
+
+/// Splits an interleaved buffer into one buffer per channel.
+pub fn split_channels<C: ChannelConfig>(interleaved: &[f32]) -> Vec<Vec<f32>> {
+    let channels = C::num_channels();
+    let frames = interleaved.len() / channels;
+    let mut output = vec![Vec::with_capacity(frames); channels];
+
+    for frame in interleaved.chunks_exact(channels) {
+        for (channel, &sample) in output.iter_mut().zip(frame.iter()) {
+            channel.push(sample);
+        }
+    }
+
+    output
+}
+
+/// A range of the timeline to render, in frames. `end` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Region {
+    /// Creates a region from sample positions.
+    ///
+    /// # Arguments
+    /// * `start` - First frame to render.
+    /// * `end` - Frame after the last one to render.
+    pub fn samples(start: u64, end: u64) -> Self {
+        assert!(end >= start, "Region: End must not be before start.");
+        Region { start, end }
+    }
+
+    /// Creates a region from beat positions at the clock's current tempo.
+    ///
+    /// # Arguments
+    /// * `start_beat` - First beat to render (quarter notes from the timeline start).
+    /// * `end_beat` - Beat at which rendering stops.
+    /// * `clock` - Tempo source.
+    /// * `sample_rate` - Sample rate of the render.
+    pub fn beats(start_beat: f64, end_beat: f64, clock: &TransportClock, sample_rate: f32) -> Self {
+        let samples_per_beat = 60.0 / clock.bpm() as f64 * sample_rate as f64;
+        let to_samples = |beat: f64| libm::round(beat.max(0.0) * samples_per_beat) as u64;
+        Self::samples(to_samples(start_beat), to_samples(end_beat))
+    }
+
+    /// Returns the number of frames in the region.
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    /// Returns true if the region contains no frames.
+    pub fn is_empty(&self) -> bool {
+        self.end == self.start
+    }
+}
+
+/// A shareable flag that stops an offline render from another thread.
+///
+/// Clones share the same flag.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// Creates a new CancelToken.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation. The render stops before its next block.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    /// Returns true if cancellation was requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+}
+
+/// Returned when a render was stopped through its [`CancelToken`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl core::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("render cancelled")
+    }
+}
+
+/// Renders a region of a processor in chunks pulled by the caller.
+///
+/// Unlike [`render_offline`], the caller decides how much to render per step, so a GUI
+/// can bounce audio between frames, report progress and abort without blocking. By
+/// default the frames before the region are rendered and discarded (pre-roll), so
+/// envelopes, delays and sequencers are in the same state as during realtime playback.
+pub struct OfflineRenderer<'a, C: ChannelConfig, P: FrameProcessor<C> + ?Sized> {
+    processor: &'a mut P,
+    region: Region,
+    block_size: usize,
+    position: u64,
+    preroll: bool,
+    cancel: Option<CancelToken>,
+    buffer: Vec<f32>,
+    _marker: core::marker::PhantomData<C>,
+}
+
+impl<'a, C: ChannelConfig, P: FrameProcessor<C> + ?Sized> OfflineRenderer<'a, C, P> {
+    /// Creates a new OfflineRenderer.
+    ///
+    /// # Arguments
+    /// * `processor` - The processor to render.
+    /// * `region` - The part of the timeline to return.
+    /// * `block_size` - Maximum number of frames passed to each `process` call.
+    pub fn new(processor: &'a mut P, region: Region, block_size: usize) -> Self {
+        assert!(
+            block_size > 0,
+            "render: block_size must be at least one frame."
+        );
+        processor.prepare(block_size);
+        OfflineRenderer {
+            processor,
+            region,
+            block_size,
+            position: 0,
+            preroll: true,
+            cancel: None,
+            buffer: Vec::with_capacity(block_size * C::num_channels()),
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Attaches a cancellation token.
+    pub fn with_cancel(mut self, token: CancelToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Starts directly at the region start instead of rendering the frames before it.
+    pub fn without_preroll(mut self) -> Self {
+        self.preroll = false;
+        self
+    }
+
+    /// Returns the fraction of the region rendered so far (0.0 - 1.0).
+    pub fn progress(&self) -> f32 {
+        if self.region.is_empty() {
+            return 1.0;
+        }
+        let done = self.position.saturating_sub(self.region.start);
+        (done as f64 / self.region.len() as f64) as f32
+    }
+
+    /// Returns true once the whole region has been rendered.
+    pub fn is_finished(&self) -> bool {
+        self.position.max(self.region.start) >= self.region.end
+    }
+
+    /// Renders the next chunk of at most `max_frames` frames.
+    ///
+    /// Returns the interleaved audio of the chunk, `None` once the region is complete, or
+    /// `Cancelled` if the token was triggered.
+    pub fn pull(&mut self, max_frames: usize) -> Result<Option<&[f32]>, Cancelled> {
+        let channels = C::num_channels();
+
+        if self.position < self.region.start {
+            if self.preroll {
+                while self.position < self.region.start {
+                    self.check_cancel()?;
+                    let frames = (self.region.start - self.position).min(self.block_size as u64);
+                    self.process_block(frames as usize * channels, 0);
+                }
+            } else {
+                self.position = self.region.start;
+            }
+        }
+
+        if self.is_finished() {
+            return Ok(None);
+        }
+
+        let frames = (self.region.end - self.position).min(max_frames.max(1) as u64) as usize;
+        let len = frames * channels;
+        if self.buffer.len() < len {
+            self.buffer.resize(len, 0.0);
+        }
+
+        let mut offset = 0;
+        while offset < len {
+            self.check_cancel()?;
+            let block = (len - offset).min(self.block_size * channels);
+            self.process_block(block, offset);
+            offset += block;
+        }
+
+        Ok(Some(&self.buffer[0..len]))
+    }
+
+    /// Renders the rest of the region, calling `progress` after every block.
+    pub fn run(mut self, mut progress: impl FnMut(f32)) -> Result<Vec<f32>, Cancelled> {
+        let total = self.region.len() as usize * C::num_channels();
+        let mut output = Vec::with_capacity(total);
+        let block_size = self.block_size;
+        while let Some(chunk) = self.pull(block_size)? {
+            output.extend_from_slice(chunk);
+            progress(self.progress());
+        }
+        Ok(output)
+    }
+
+    fn process_block(&mut self, len: usize, offset: usize) {
+        if self.buffer.len() < offset + len {
+            self.buffer.resize(offset + len, 0.0);
+        }
+        let block = &mut self.buffer[offset..offset + len];
+        block.fill(0.0);
+        self.processor.process(block, self.position);
+        self.position += (len / C::num_channels()) as u64;
+    }
+
+    fn check_cancel(&self) -> Result<(), Cancelled> {
+        match &self.cancel {
+            Some(token) if token.is_cancelled() => Err(Cancelled),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The result of [`render_stems`]: the master mix and one buffer per mixer input.
+pub struct StemRender {
+    /// The mixer output, after its gain and soft clipping.
+    pub mix: Vec<f32>,
+    /// One interleaved buffer per input, in input order, before the mixer output stage.
+    pub stems: Vec<Vec<f32>>,
+}
+
+/// Renders a region of a SummingMixer, capturing every input as a separate stem.
+///
+/// The mix and all stems come from a single pass over the inputs, so rendering N stems
+/// costs the same as rendering the mix. The frames before the region are pre-rolled.
+/// Inputs must not be added or removed while rendering.
+///
+/// # Arguments
+/// * `mixer` - The mixer to render.
+/// * `region` - The part of the timeline to return.
+/// * `block_size` - Number of frames passed to each `process` call.
+/// * `cancel` - Optional cancellation token, checked before every block.
+/// * `progress` - Called after every block with the fraction rendered (0.0 - 1.0).
+pub fn render_stems<C, T>(
+    mixer: &mut SummingMixer<C, T>,
+    region: Region,
+    block_size: usize,
+    cancel: Option<&CancelToken>,
+    mut progress: impl FnMut(f32),
+) -> Result<StemRender, Cancelled>
+where
+    C: ChannelConfig + 'static,
+    T: FrameProcessor<C> + Send + 'static,
+{
+    assert!(
+        block_size > 0,
+        "render: block_size must be at least one frame."
+    );
+
+    let channels = C::num_channels();
+    let count = mixer.input_count();
+    let total = region.len() as usize * channels;
+    let mut mix = Vec::with_capacity(total);
+    let mut stems = vec![Vec::with_capacity(total); count];
+    let mut block_stems = vec![Vec::new(); count];
+    let mut buffer = vec![0.0; block_size * channels];
+
+    let mut position = 0u64;
+    while position < region.end {
+        if cancel.is_some_and(|token| token.is_cancelled()) {
+            return Err(Cancelled);
+        }
+
+        let limit = if position < region.start {
+            region.start
+        } else {
+            region.end
+        };
+        let frames = (limit - position).min(block_size as u64) as usize;
+        let block = &mut buffer[0..frames * channels];
+        block.fill(0.0);
+        mixer.process_stems(block, position, &mut block_stems);
+
+        if position >= region.start {
+            mix.extend_from_slice(block);
+            for (stem, block_stem) in stems.iter_mut().zip(block_stems.iter()) {
+                stem.extend_from_slice(block_stem);
+            }
+            let done = position + frames as u64 - region.start;
+            progress((done as f64 / region.len() as f64) as f32);
+        }
+        position += frames as u64;
+    }
+
+    Ok(StemRender { mix, stems })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+    use crate::core::channels::{Mono, MonoToStereo, Stereo};
+    use crate::effects::utility::dc_source::DcSource;
+    use crate::effects::utility::gain::Gain;
+    use crate::synthesis::oscillator::{Oscillator, Waveform};
+
+    #[test]
+    fn test_render_is_block_size_independent() {
+        let mut osc_a = Oscillator::new(AudioParam::hz(440.0), Waveform::Saw);
+        let mut osc_b = Oscillator::new(AudioParam::hz(440.0), Waveform::Saw);
+
+        let a = render_offline::<Mono, _>(&mut osc_a, 1000, 64);
+        let b = render_offline::<Mono, _>(&mut osc_b, 1000, 7);
+
+        assert_eq!(a.len(), 1000);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_render_stereo_and_split() {
+        let osc = Oscillator::new(AudioParam::hz(100.0), Waveform::Sine);
+        let mut stereo = MonoToStereo::new(osc);
+
+        let out = render_offline::<Stereo, _>(&mut stereo, 100, 32);
+        assert_eq!(out.len(), 200);
+
+        let channels = split_channels::<Stereo>(&out);
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0], channels[1]);
+        assert!(channels[0][10].abs() > 0.0);
+    }
+
+    #[test]
+    fn test_render_with_input() {
+        let mut gain = Gain::new_fixed(0.5);
+        let input = [1.0; 10];
+        let out = render_offline_with_input::<Mono, _>(&mut gain, &input, 3);
+        assert_eq!(out, [0.5; 10]);
+    }
+
+    #[test]
+    fn test_region_matches_full_render() {
+        let mut osc_a = Oscillator::new(AudioParam::hz(440.0), Waveform::Saw);
+        let mut osc_b = Oscillator::new(AudioParam::hz(440.0), Waveform::Saw);
+        let full = render_offline::<Mono, _>(&mut osc_a, 1000, 64);
+
+        let clock = TransportClock::new(120.0);
+        let region = Region::beats(0.0, 1.0, &clock, 1000.0);
+        assert_eq!(region, Region::samples(0, 500));
+
+        let mut renderer =
+            OfflineRenderer::<Mono, _>::new(&mut osc_b, Region::samples(300, 700), 64);
+        let mut reported = Vec::new();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = renderer.pull(150).unwrap() {
+            chunks.extend_from_slice(chunk);
+            reported.push(renderer.progress());
+        }
+        assert_eq!(chunks, full[300..700]);
+        assert_eq!(reported, [0.375, 0.75, 1.0]);
+    }
+
+    #[test]
+    fn test_cancel_stops_render() {
+        let mut osc = Oscillator::new(AudioParam::hz(440.0), Waveform::Sine);
+        let token = CancelToken::new();
+        let cancel = token.clone();
+        let result = OfflineRenderer::<Mono, _>::new(&mut osc, Region::samples(0, 10_000), 64)
+            .with_cancel(token)
+            .run(|progress| {
+                if progress > 0.1 {
+                    cancel.cancel();
+                }
+            });
+        assert_eq!(result, Err(Cancelled));
+    }
+
+    #[test]
+    fn test_render_stems() {
+        let mut mixer = SummingMixer::new(vec![
+            DcSource::new(AudioParam::Static(0.25)),
+            DcSource::new(AudioParam::Static(0.5)),
+        ]);
+        let stems = render_stems(&mut mixer, Region::samples(10, 30), 8, None, |_| {}).unwrap();
+
+        assert_eq!(stems.mix, [0.75; 20]);
+        assert_eq!(stems.stems.len(), 2);
+        assert_eq!(stems.stems[0], [0.25; 20]);
+        assert_eq!(stems.stems[1], [0.5; 20]);
+    }
+}
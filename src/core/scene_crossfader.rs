@@ -0,0 +1,344 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
+use crate::core::tempo::{NoteValue, TransportClock};
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+
+/// One of the two scenes hosted by a [`SceneCrossfader`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scene {
+    A,
+    B,
+}
+
+impl Scene {
+    fn other(self) -> Scene {
+        match self {
+            Scene::A => Scene::B,
+            Scene::B => Scene::A,
+        }
+    }
+}
+
+/// A time window over which a gain curve is applied.
+#[derive(Clone, Copy)]
+struct Window {
+    position: u32,
+    length: u32,
+}
+
+impl Window {
+    fn progress(&self) -> f32 {
+        self.position as f32 / self.length as f32
+    }
+}
+
+/// Hosts two complete chains (scenes) and crossfades between them.
+///
+/// Only the active scene is rendered until a transition is started with
+/// [`crossfade_to`](Self::crossfade_to) or [`crossfade_to_synced`](Self::crossfade_to_synced).
+/// During the transition both scenes are rendered from the same input and mixed with an
+/// equal-power curve; afterwards only the new scene is rendered again, so the inactive
+/// scene is paused rather than running in the background.
+///
+/// A duck depth dips the overall level towards the middle of each transition, which hides
+/// the overlap of two dense arrangements. [`duck`](Self::duck) applies the same dip
+/// momentarily without switching scenes.
+///
+/// The crossfader reports the larger latency of the two scenes but does not delay the
+/// other one; wrap the scene with less latency in a `LatencyCompensator` so the two line
+/// up during a transition.
+pub struct SceneCrossfader<A, B, C: ChannelConfig> {
+    scene_a: A,
+    scene_b: B,
+    active: Scene,
+    transition: Option<Window>,
+    transition_duck: f32,
+    duck: Option<(Window, f32)>,
+    sample_rate: f32,
+    scratch: Vec<f32>,
+    _marker: core::marker::PhantomData<C>,
+}
+
+impl<A: FrameProcessor<C>, B: FrameProcessor<C>, C: ChannelConfig> SceneCrossfader<A, B, C> {
+    /// Creates a new SceneCrossfader playing scene A.
+    ///
+    /// # Arguments
+    /// * `scene_a` - The first chain.
+    /// * `scene_b` - The second chain.
+    pub fn new(scene_a: A, scene_b: B) -> Self {
+        SceneCrossfader {
+            scene_a,
+            scene_b,
+            active: Scene::A,
+            transition: None,
+            transition_duck: 0.0,
+            duck: None,
+            sample_rate: 44100.0,
+            scratch: Vec::with_capacity(128),
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Sets how far the level dips in the middle of a transition (0.0 = none, 1.0 = silence).
+    pub fn with_transition_duck(mut self, depth: f32) -> Self {
+        self.transition_duck = depth.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Starts a transition to `scene` over `seconds`.
+    ///
+    /// Does nothing if `scene` is already active and no transition is running. Requesting the
+    /// other scene during a transition reverses it from the current position.
+    pub fn crossfade_to(&mut self, scene: Scene, seconds: f32) {
+        let target = self.target();
+        if scene == target {
+            return;
+        }
+
+        let length = (libm::roundf(seconds.max(0.0) * self.sample_rate) as u32).max(1);
+        let position = match self.transition {
+            // Reverse: keep the current mix and head back towards the previous scene.
+            Some(window) => length - (window.progress() * length as f32) as u32,
+            None => 0,
+        };
+        self.active = target;
+        self.transition = Some(Window { position, length });
+    }
+
+    /// Starts a transition to `scene` lasting `note` at the tempo of `clock`.
+    pub fn crossfade_to_synced(&mut self, scene: Scene, note: NoteValue, clock: &TransportClock) {
+        self.crossfade_to(scene, clock.note_seconds(note));
+    }
+
+    /// Momentarily dips the output level without switching scenes.
+    ///
+    /// # Arguments
+    /// * `depth` - Maximum level reduction (0.0 - 1.0), reached halfway through.
+    /// * `seconds` - Duration of the dip.
+    pub fn duck(&mut self, depth: f32, seconds: f32) {
+        let length = (libm::roundf(seconds.max(0.0) * self.sample_rate) as u32).max(1);
+        self.duck = Some((
+            Window {
+                position: 0,
+                length,
+            },
+            depth.clamp(0.0, 1.0),
+        ));
+    }
+
+    /// Returns the scene that is playing, or being faded to.
+    pub fn target(&self) -> Scene {
+        match self.transition {
+            Some(_) => self.active.other(),
+            None => self.active,
+        }
+    }
+
+    /// Returns true while a transition is running.
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    /// Returns a mutable reference to scene A.
+    pub fn scene_a_mut(&mut self) -> &mut A {
+        &mut self.scene_a
+    }
+
+    /// Returns a mutable reference to scene B.
+    pub fn scene_b_mut(&mut self) -> &mut B {
+        &mut self.scene_b
+    }
+}
+
+impl<A, B, C> FrameProcessor<C> for SceneCrossfader<A, B, C>
+where
+    A: FrameProcessor<C> + Send,
+    B: FrameProcessor<C> + Send,
+    C: ChannelConfig,
+{
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let channels = C::num_channels();
+
+        match self.transition {
+            None => match self.active {
+                Scene::A => self.scene_a.process(buffer, sample_index),
+                Scene::B => self.scene_b.process(buffer, sample_index),
+            },
+            Some(mut window) => {
+                let len = buffer.len();
+                if self.scratch.len() < len {
+                    self.scratch.resize(len, 0.0);
+                }
+                let other = &mut self.scratch[0..len];
+                other.copy_from_slice(buffer);
+
+                // `buffer` holds the outgoing scene, `other` the incoming one.
+                match self.active {
+                    Scene::A => {
+                        self.scene_a.process(buffer, sample_index);
+                        self.scene_b.process(other, sample_index);
+                    }
+                    Scene::B => {
+                        self.scene_b.process(buffer, sample_index);
+                        self.scene_a.process(other, sample_index);
+                    }
+                }
+
+                for (out_frame, in_frame) in buffer
+                    .chunks_exact_mut(channels)
+                    .zip(other.chunks_exact(channels))
+                {
+                    let t = window.progress().min(1.0);
+                    let gain_out = libm::cosf(t * PI * 0.5);
+                    let gain_in = libm::sinf(t * PI * 0.5);
+                    let duck = 1.0 - self.transition_duck * libm::sinf(t * PI);
+                    for (o, &i) in out_frame.iter_mut().zip(in_frame.iter()) {
+                        *o = (*o * gain_out + i * gain_in) * duck;
+                    }
+                    window.position = window.position.saturating_add(1);
+                }
+
+                if window.position >= window.length {
+                    self.active = self.active.other();
+                    self.transition = None;
+                } else {
+                    self.transition = Some(window);
+                }
+            }
+        }
+
+        if let Some((mut window, depth)) = self.duck {
+            for frame in buffer.chunks_exact_mut(channels) {
+                let t = window.progress().min(1.0);
+                let gain = 1.0 - depth * libm::sinf(t * PI);
+                for sample in frame.iter_mut() {
+                    *sample *= gain;
+                }
+                window.position = window.position.saturating_add(1);
+            }
+            self.duck = if window.position >= window.length {
+                None
+            } else {
+                Some((window, depth))
+            };
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.scene_a.set_sample_rate(sample_rate);
+        self.scene_b.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.scene_a.prepare(max_block_size);
+        self.scene_b.prepare(max_block_size);
+        self.scratch.resize(max_block_size * C::num_channels(), 0.0);
+    }
+
+    fn reset(&mut self) {
+        if self.transition.take().is_some() {
+            self.active = self.active.other();
+        }
+        self.duck = None;
+        self.scene_a.reset();
+        self.scene_b.reset();
+    }
+
+    fn latency_samples(&self) -> u32 {
+        self.scene_a
+            .latency_samples()
+            .max(self.scene_b.latency_samples())
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_processor::<C, _>("A", &self.scene_a);
+        tree.add_processor::<C, _>("B", &self.scene_b);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.add_processor::<C, _>("A", &self.scene_a);
+        report.add_processor::<C, _>("B", &self.scene_b);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "SceneCrossfader"
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.set_routing(crate::core::graph::Routing::Parallel);
+        node.add_processor::<C, _>("A", &self.scene_a);
+        node.add_processor::<C, _>("B", &self.scene_b);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn visualize(&self, indent: usize) -> alloc::string::String {
+        use core::fmt::Write;
+
+        let spaces = " ".repeat(indent);
+        let mut output = alloc::string::String::new();
+        let _ = writeln!(output, "{}SceneCrossfader ({:?})", spaces, self.target());
+
+        let _ = writeln!(output, "{}  Scene A:", spaces);
+        output.push_str(&self.scene_a.visualize(indent + 4));
+
+        let _ = writeln!(output, "{}  Scene B:", spaces);
+        output.push_str(&self.scene_b.visualize(indent + 4));
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+    use crate::core::channels::Mono;
+    use crate::effects::utility::dc_source::DcSource;
+
+    fn crossfader() -> SceneCrossfader<DcSource, DcSource, Mono> {
+        let mut fader = SceneCrossfader::new(
+            DcSource::new(AudioParam::Static(1.0)),
+            DcSource::new(AudioParam::Static(-1.0)),
+        );
+        fader.set_sample_rate(1000.0);
+        fader
+    }
+
+    #[test]
+    fn test_equal_power_transition() {
+        let mut fader = crossfader();
+        let mut buffer = [0.0; 4];
+        fader.process(&mut buffer, 0);
+        assert_eq!(buffer, [1.0; 4]);
+
+        fader.crossfade_to_synced(Scene::B, NoteValue::Sixteenth, &TransportClock::new(150.0));
+        assert_eq!(fader.target(), Scene::B);
+
+        // A sixteenth at 150 BPM is 100 ms = 100 samples.
+        let mut buffer = [0.0; 100];
+        fader.process(&mut buffer, 4);
+        assert_eq!(buffer[0], 1.0);
+        assert!(buffer[50].abs() < 1e-6);
+        assert!(!fader.is_transitioning());
+
+        let mut buffer = [0.0; 4];
+        fader.process(&mut buffer, 104);
+        assert_eq!(buffer, [-1.0; 4]);
+    }
+
+    #[test]
+    fn test_duck() {
+        let mut fader = crossfader();
+        fader.duck(0.5, 0.01);
+        let mut buffer = [0.0; 12];
+        fader.process(&mut buffer, 0);
+        assert!((buffer[5] - 0.5).abs() < 1e-6);
+        assert_eq!(buffer[10..], [1.0; 2]);
+    }
+}
@@ -2,7 +2,13 @@ use super::channels::{ChannelConfig, Mono, MonoToStereo, Stereo, StereoToMono};
 use super::frame_processor::FrameProcessor;
 use super::parallel_mixer::ParallelMixer;
 use crate::core::audio_param::AudioParam;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
+#[cfg(feature = "profiling")]
+use crate::core::profiler::{Clock, Probe, ProbeHandle};
 use alloc::string::String;
+#[cfg(feature = "profiling")]
+use alloc::vec::Vec;
 use core::marker::PhantomData;
 
 /// A processor that runs two processors in series.
@@ -35,6 +41,11 @@ where
         self.second.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.first.prepare(max_block_size);
+        self.second.prepare(max_block_size);
+    }
+
     fn reset(&mut self) {
         self.first.reset();
         self.second.reset();
@@ -44,11 +55,27 @@ where
         self.first.latency_samples() + self.second.latency_samples()
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_processor::<C, _>("0", &self.first);
+        tree.add_processor::<C, _>("1", &self.second);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.add_processor::<C, _>("0", &self.first);
+        report.add_processor::<C, _>("1", &self.second);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "SerialProcessor"
     }
 
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.add_processor::<C, _>("0", &self.first);
+        node.add_processor::<C, _>("1", &self.second);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn visualize(&self, indent: usize) -> String {
         use core::fmt::Write;
@@ -72,6 +99,8 @@ where
 pub struct StaticDspChain<C: ChannelConfig, P> {
     pub processor: P,
     sample_rate: f32,
+    #[cfg(feature = "profiling")]
+    probes: Vec<ProbeHandle>,
     _marker: PhantomData<C>,
 }
 
@@ -82,6 +111,8 @@ impl<C: ChannelConfig, P: FrameProcessor<C>> StaticDspChain<C, P> {
         Self {
             processor,
             sample_rate,
+            #[cfg(feature = "profiling")]
+            probes: Vec::new(),
             _marker: PhantomData,
         }
     }
@@ -96,10 +127,41 @@ impl<C: ChannelConfig, P: FrameProcessor<C>> StaticDspChain<C, P> {
         StaticDspChain {
             processor: serial,
             sample_rate: self.sample_rate,
+            #[cfg(feature = "profiling")]
+            probes: self.probes,
             _marker: PhantomData,
         }
     }
 
+    /// Appends a processor wrapped in a timing [`Probe`] to the chain.
+    ///
+    /// The probe's handle is added to [`probes`](Self::probes). To time the first
+    /// processor too, pass `Probe::new(first, clock)` to `new` and keep its handle.
+    ///
+    /// # Arguments
+    /// * `next` - The processor to append.
+    /// * `clock` - The clock to read before and after each block.
+    #[cfg(feature = "profiling")]
+    pub fn and_profiled<P2>(
+        mut self,
+        next: P2,
+        clock: Clock,
+    ) -> StaticDspChain<C, SerialProcessor<P, Probe<P2>>>
+    where
+        P2: FrameProcessor<C>,
+    {
+        let probe = Probe::new(next, clock);
+        self.probes.push(probe.handle());
+        self.and(probe)
+    }
+
+    /// Returns the timing handles of the processors added with
+    /// [`and_profiled`](Self::and_profiled), in chain order.
+    #[cfg(feature = "profiling")]
+    pub fn probes(&self) -> Vec<ProbeHandle> {
+        self.probes.clone()
+    }
+
     /// Appends a processor to the chain with a dry/wet mix.
     pub fn and_mix<P2>(
         self,
@@ -115,6 +177,8 @@ impl<C: ChannelConfig, P: FrameProcessor<C>> StaticDspChain<C, P> {
         StaticDspChain {
             processor: serial,
             sample_rate: self.sample_rate,
+            #[cfg(feature = "profiling")]
+            probes: self.probes,
             _marker: PhantomData,
         }
     }
@@ -136,6 +200,8 @@ impl<C: ChannelConfig, P: FrameProcessor<C>> StaticDspChain<C, P> {
         StaticDspChain {
             processor: serial,
             sample_rate: self.sample_rate,
+            #[cfg(feature = "profiling")]
+            probes: self.probes,
             _marker: PhantomData,
         }
     }
@@ -156,16 +222,30 @@ impl<C: ChannelConfig, P: FrameProcessor<C>> StaticDspChain<C, P> {
 impl<P: FrameProcessor<Mono> + Send> StaticDspChain<Mono, P> {
     /// Converts the Mono chain into a Stereo chain.
     pub fn to_stereo(self) -> StaticDspChain<Stereo, MonoToStereo<P>> {
-        let converted = MonoToStereo::new(self.processor);
-        StaticDspChain::new(converted, self.sample_rate)
+        let mut converted = MonoToStereo::new(self.processor);
+        converted.set_sample_rate(self.sample_rate);
+        StaticDspChain {
+            processor: converted,
+            sample_rate: self.sample_rate,
+            #[cfg(feature = "profiling")]
+            probes: self.probes,
+            _marker: PhantomData,
+        }
     }
 }
 
 impl<P: FrameProcessor<Stereo> + Send> StaticDspChain<Stereo, P> {
     /// Converts the Stereo chain into a Mono chain.
     pub fn to_mono(self) -> StaticDspChain<Mono, StereoToMono<P>> {
-        let converted = StereoToMono::new(self.processor);
-        StaticDspChain::new(converted, self.sample_rate)
+        let mut converted = StereoToMono::new(self.processor);
+        converted.set_sample_rate(self.sample_rate);
+        StaticDspChain {
+            processor: converted,
+            sample_rate: self.sample_rate,
+            #[cfg(feature = "profiling")]
+            probes: self.probes,
+            _marker: PhantomData,
+        }
     }
 }
 
@@ -179,6 +259,10 @@ impl<C: ChannelConfig, P: FrameProcessor<C>> FrameProcessor<C> for StaticDspChai
         self.processor.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.processor.prepare(max_block_size);
+    }
+
     fn reset(&mut self) {
         self.processor.reset();
     }
@@ -187,21 +271,34 @@ impl<C: ChannelConfig, P: FrameProcessor<C>> FrameProcessor<C> for StaticDspChai
         self.processor.latency_samples()
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        self.processor.parameters(tree);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        self.processor.validate(report);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "StaticDspChain"
     }
 
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.add_processor::<C, _>("0", &self.processor);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn visualize(&self, indent: usize) -> String {
         use core::fmt::Write;
         let spaces = " ".repeat(indent);
         let arrow_spaces = " ".repeat(indent + 2);
 
-        let channel_type = if C::num_channels() == 1 {
-            "Mono"
-        } else {
-            "Stereo"
+        let channel_type = match C::num_channels() {
+            1 => String::from("Mono"),
+            2 => String::from("Stereo"),
+            n => alloc::format!("{} channels", n),
         };
 
         let mut output = String::new();
@@ -0,0 +1,170 @@
+use crate::core::audio_param::{segments, AudioParam};
+use crate::core::channels::ChannelConfig;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+
+/// Splits the block at discontinuities of a watched parameter before processing.
+///
+/// Processors that read their parameters once per block see a jump (e.g. a gate opening)
+/// only at the start of the next block. Wrapping them in `Subdivide` with the signal that
+/// causes the jump calls the inner processor once per constant segment instead, with the
+/// correct sample index, so the change lands on the right sample. Blocks without jumps are
+/// processed in one call, so the inner fast paths are kept.
+pub struct Subdivide<P, C: ChannelConfig> {
+    inner: P,
+    watch: AudioParam,
+    threshold: f32,
+    watch_buffer: Vec<f32>,
+    _marker: core::marker::PhantomData<C>,
+}
+
+impl<P: FrameProcessor<C>, C: ChannelConfig> Subdivide<P, C> {
+    /// Creates a new Subdivide wrapper.
+    ///
+    /// # Arguments
+    /// * `inner` - The processor to drive.
+    /// * `watch` - The parameter whose jumps split the block (e.g. a gate signal).
+    /// * `threshold` - Minimum change that counts as a discontinuity.
+    pub fn new(inner: P, watch: AudioParam, threshold: f32) -> Self {
+        Subdivide {
+            inner,
+            watch,
+            threshold,
+            watch_buffer: Vec::with_capacity(128),
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Returns a mutable reference to the inner processor.
+    pub fn inner_mut(&mut self) -> &mut P {
+        &mut self.inner
+    }
+}
+
+impl<P: FrameProcessor<C>, C: ChannelConfig> FrameProcessor<C> for Subdivide<P, C> {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let channels = C::num_channels();
+        let frames = buffer.len() / channels;
+
+        if self.watch.get_constant().is_some() {
+            self.inner.process(buffer, sample_index);
+            return;
+        }
+
+        if self.watch_buffer.len() < frames {
+            self.watch_buffer.resize(frames, 0.0);
+        }
+        self.watch
+            .process(&mut self.watch_buffer[0..frames], sample_index);
+
+        for segment in segments([&self.watch_buffer[0..frames]], self.threshold) {
+            self.inner.process(
+                &mut buffer[segment.start * channels..segment.end * channels],
+                sample_index + segment.start as u64,
+            );
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.watch.set_sample_rate(sample_rate);
+        self.inner.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.inner.prepare(max_block_size);
+        self.watch.prepare(max_block_size);
+        self.watch_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.watch.reset();
+        self.inner.reset();
+    }
+
+    fn latency_samples(&self) -> u32 {
+        self.inner.latency_samples()
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_processor::<C, _>("0", &self.inner);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.add_processor::<C, _>("0", &self.inner);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "Subdivide"
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.add_processor::<C, _>("0", &self.inner);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn visualize(&self, indent: usize) -> alloc::string::String {
+        use core::fmt::Write;
+
+        let spaces = " ".repeat(indent);
+        let mut output = alloc::string::String::new();
+        let _ = writeln!(
+            output,
+            "{}Subdivide (on jumps > {})",
+            spaces, self.threshold
+        );
+        output.push_str(&self.inner.visualize(indent + 2));
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::Mono;
+    use alloc::boxed::Box;
+    use alloc::vec;
+
+    struct Step(u64);
+
+    impl FrameProcessor<Mono> for Step {
+        fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+            for (i, sample) in buffer.iter_mut().enumerate() {
+                *sample = if sample_index + i as u64 >= self.0 {
+                    1.0
+                } else {
+                    0.0
+                };
+            }
+        }
+    }
+
+    struct BlockRecorder(Vec<(u64, usize)>);
+
+    impl FrameProcessor<Mono> for BlockRecorder {
+        fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+            self.0.push((sample_index, buffer.len()));
+        }
+    }
+
+    #[test]
+    fn test_splits_at_jump() {
+        let mut sub = Subdivide::new(
+            BlockRecorder(Vec::new()),
+            AudioParam::Dynamic(Box::new(Step(20))),
+            0.5,
+        );
+        let mut buffer = vec![0.0; 64];
+        sub.process(&mut buffer, 0);
+        sub.process(&mut buffer, 64);
+        assert_eq!(sub.inner_mut().0, [(0, 20), (20, 44), (64, 64)]);
+
+        let mut static_sub =
+            Subdivide::new(BlockRecorder(Vec::new()), AudioParam::Static(0.0), 0.5);
+        static_sub.process(&mut buffer, 0);
+        assert_eq!(static_sub.inner_mut().0, [(0, 64)]);
+    }
+}
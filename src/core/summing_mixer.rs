@@ -1,39 +1,164 @@
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::ChannelConfig;
+use crate::core::error::ValidationReport;
 use crate::core::frame_processor::FrameProcessor;
 use crate::core::latency_compensator::LatencyCompensator;
+use crate::core::preset::ParameterTree;
+use crate::effects::utility::panner::PanLaw;
 use alloc::boxed::Box;
 use alloc::string::String;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::marker::PhantomData;
+use core::sync::atomic::{AtomicBool, Ordering};
 use wide::f32x4;
 
+const DEFAULT_FADE_SECONDS: f32 = 0.01;
+
+/// Identifies an input of a [`SummingMixer`], stable across additions and removals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InputId(u32);
+
+struct ControlFlags {
+    mute: AtomicBool,
+    solo: AtomicBool,
+}
+
+/// A thread-safe solo/mute handle for one [`SummingMixer`] input.
+///
+/// Obtained from [`SummingMixer::input_control`]. Clones share the same flags, so a UI
+/// thread can toggle them while the audio thread is mixing. The mixer ramps the input in
+/// and out over its fade time, so toggling never clicks.
+#[derive(Clone)]
+pub struct InputControl {
+    flags: Arc<ControlFlags>,
+}
+
+impl InputControl {
+    fn new() -> Self {
+        InputControl {
+            flags: Arc::new(ControlFlags {
+                mute: AtomicBool::new(false),
+                solo: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Mutes or unmutes the input.
+    pub fn set_mute(&self, muted: bool) {
+        self.flags.mute.store(muted, Ordering::Relaxed);
+    }
+
+    /// Solos or unsolos the input. While any input is soloed, only soloed inputs are heard.
+    pub fn set_solo(&self, soloed: bool) {
+        self.flags.solo.store(soloed, Ordering::Relaxed);
+    }
+
+    /// Returns true if the input is muted.
+    pub fn is_muted(&self) -> bool {
+        self.flags.mute.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if the input is soloed.
+    pub fn is_soloed(&self) -> bool {
+        self.flags.solo.load(Ordering::Relaxed)
+    }
+}
+
+/// Per-input fade state used while inputs join or leave the mix.
+///
+/// `gain` follows `target` for additions and removals, `mix` follows `mix_target` for
+/// solo, mute and auto-gain. The input is scaled by their product, and by the optional
+/// per-input gain and pan.
+struct InputFade {
+    id: InputId,
+    gain: f32,
+    target: f32,
+    mix: f32,
+    mix_target: f32,
+    control: InputControl,
+    level: Option<AudioParam>,
+    pan: Option<AudioParam>,
+}
+
+impl InputFade {
+    fn new(id: InputId, gain: f32) -> Self {
+        InputFade {
+            id,
+            gain,
+            target: 1.0,
+            mix: 1.0,
+            mix_target: 1.0,
+            control: InputControl::new(),
+            level: None,
+            pan: None,
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.gain != self.target
+            || self.mix != self.mix_target
+            || self.mix != 1.0
+            || self.level.is_some()
+            || self.pan.is_some()
+    }
+
+    fn is_finished_removal(&self) -> bool {
+        self.target == 0.0 && self.gain == 0.0
+    }
+}
+
 /// Sums multiple audio signals together, with optional gain and soft clipping.
 ///
 /// Automatically synchronizes input latencies by adding delay to inputs with lower latency.
+///
+/// Inputs can be added and removed while running (e.g. to grow and shrink a polyphonic
+/// texture). Joining inputs fade in and leaving inputs fade out over the fade time, so no
+/// clicks occur. Removed inputs are not dropped on the audio thread: they are kept until
+/// collected with [`SummingMixer::take_retired`], so the caller decides where deallocation
+/// happens.
 pub struct SummingMixer<
     C: ChannelConfig,
     T: FrameProcessor<C> + Send = Box<dyn FrameProcessor<C> + Send>,
 > {
     inputs: Vec<T>,
+    fades: Vec<InputFade>,
+    retired: Vec<T>,
+    next_id: u32,
+    fade_time: f32,
+    sample_rate: f32,
+    max_block_size: usize,
     gain: AudioParam,
     soft_clip: bool,
+    auto_gain: bool,
     input_buffer: Vec<f32>,
     temp_buffer: Vec<f32>,
     gain_buffer: Vec<f32>,
+    pan_buffer: Vec<f32>,
     _marker: PhantomData<C>,
 }
 
 impl<C: ChannelConfig + 'static, T: FrameProcessor<C> + Send + 'static> SummingMixer<C, T> {
     /// Creates a new SummingMixer with the given inputs.
     pub fn new(inputs: Vec<T>) -> Self {
+        let fades = (0..inputs.len() as u32)
+            .map(|id| InputFade::new(InputId(id), 1.0))
+            .collect();
         SummingMixer {
+            next_id: inputs.len() as u32,
             inputs,
+            fades,
+            retired: Vec::new(),
+            fade_time: DEFAULT_FADE_SECONDS,
+            sample_rate: 44100.0,
+            max_block_size: 0,
             gain: AudioParam::Static(1.0),
             soft_clip: false,
+            auto_gain: false,
             input_buffer: Vec::with_capacity(128),
             temp_buffer: Vec::with_capacity(128),
             gain_buffer: Vec::with_capacity(128),
+            pan_buffer: Vec::with_capacity(128),
             _marker: PhantomData,
         }
     }
@@ -87,6 +212,314 @@ impl<C: ChannelConfig + 'static, T: FrameProcessor<C> + Send + 'static> SummingM
         self.soft_clip = enabled;
         self
     }
+
+    /// Enables or disables loudness compensation for muted inputs.
+    ///
+    /// When enabled, the audible inputs are boosted by `sqrt(inputs / audible)` so the
+    /// overall loudness of uncorrelated sources stays roughly constant as inputs are muted
+    /// or soloed.
+    pub fn set_auto_gain(&mut self, enabled: bool) {
+        self.auto_gain = enabled;
+    }
+
+    /// Builder method to enable auto-gain.
+    pub fn with_auto_gain(mut self, enabled: bool) -> Self {
+        self.auto_gain = enabled;
+        self
+    }
+
+    /// Sets the fade time in seconds used when inputs are added or removed (default 10 ms).
+    pub fn set_fade_time(&mut self, seconds: f32) {
+        self.fade_time = seconds.max(0.0);
+    }
+
+    /// Reserves room for `additional` more inputs, so that later calls to
+    /// [`add_input`](Self::add_input) and [`remove_input`](Self::remove_input) do not allocate.
+    pub fn reserve(&mut self, additional: usize) {
+        self.inputs.reserve(additional);
+        self.fades.reserve(additional);
+        self.retired.reserve(additional);
+    }
+
+    /// Adds an input, which fades in from silence.
+    ///
+    /// The input is not latency compensated; wrap it in a `LatencyCompensator` if needed.
+    pub fn add_input(&mut self, mut input: T) -> InputId {
+        input.set_sample_rate(self.sample_rate);
+        if self.max_block_size > 0 {
+            input.prepare(self.max_block_size);
+        }
+        let id = InputId(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+        self.inputs.push(input);
+        self.fades.push(InputFade::new(id, 0.0));
+        id
+    }
+
+    /// Starts fading out an input. Once silent it is moved to the retired list.
+    ///
+    /// Returns `false` if no input with this id is playing.
+    pub fn remove_input(&mut self, id: InputId) -> bool {
+        match self.fades.iter_mut().find(|fade| fade.id == id) {
+            Some(fade) if fade.target != 0.0 => {
+                fade.target = 0.0;
+                // Room for every input heading to the retired list, so retiring never
+                // allocates on the audio thread.
+                let removing = self.fades.iter().filter(|fade| fade.target == 0.0).count();
+                self.retired.reserve(removing);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Sets the gain of one input (linear, 1.0 = unity).
+    ///
+    /// Returns `false` if no input has this id.
+    pub fn set_input_gain(&mut self, id: InputId, mut gain: AudioParam) -> bool {
+        match self.fades.iter_mut().find(|fade| fade.id == id) {
+            Some(fade) => {
+                gain.set_sample_rate(self.sample_rate);
+                if self.max_block_size > 0 {
+                    gain.prepare(self.max_block_size);
+                }
+                fade.level = Some(gain);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sets the pan of one input (-1.0 = Left, 1.0 = Right), with a constant power law.
+    ///
+    /// Only stereo mixers pan; for other channel layouts the parameter is ignored.
+    /// Returns `false` if no input has this id.
+    pub fn set_input_pan(&mut self, id: InputId, mut pan: AudioParam) -> bool {
+        match self.fades.iter_mut().find(|fade| fade.id == id) {
+            Some(fade) => {
+                pan.set_sample_rate(self.sample_rate);
+                if self.max_block_size > 0 {
+                    pan.prepare(self.max_block_size);
+                }
+                fade.pan = Some(pan);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the ids of all inputs, including those still fading out.
+    pub fn input_ids(&self) -> impl Iterator<Item = InputId> + '_ {
+        self.fades.iter().map(|fade| fade.id)
+    }
+
+    /// Returns the solo/mute handle of an input, or `None` if no input has this id.
+    pub fn input_control(&self, id: InputId) -> Option<InputControl> {
+        self.fades
+            .iter()
+            .find(|fade| fade.id == id)
+            .map(|fade| fade.control.clone())
+    }
+
+    /// Returns the number of inputs, including those still fading out.
+    pub fn input_count(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Takes the inputs that have finished fading out.
+    ///
+    /// The returned inputs are owned by the caller and can be dropped (or sent to another
+    /// thread to be dropped) outside the audio callback.
+    pub fn take_retired(&mut self) -> Vec<T> {
+        core::mem::take(&mut self.retired)
+    }
+}
+
+impl<C: ChannelConfig, T: FrameProcessor<C> + Send> SummingMixer<C, T> {
+    /// Processes a block like `process`, additionally writing each input's contribution.
+    ///
+    /// `stems` must hold one buffer per input (in input order); each is resized to the block
+    /// length and overwritten with that input's signal after its fade gain. The stems are taken
+    /// before the output gain and soft clipping, which only apply to the mix in `buffer`.
+    pub fn process_stems(&mut self, buffer: &mut [f32], sample_index: u64, stems: &mut [Vec<f32>]) {
+        assert_eq!(
+            stems.len(),
+            self.inputs.len(),
+            "SummingMixer: One stem buffer per input is required."
+        );
+        if self.inputs.is_empty() {
+            buffer.fill(0.0);
+            return;
+        }
+        self.update_mix_targets();
+        self.process_weighted(buffer, sample_index, Some(stems));
+        self.apply_output_stage(buffer, sample_index);
+    }
+
+    fn process_weighted(
+        &mut self,
+        buffer: &mut [f32],
+        sample_index: u64,
+        mut stems: Option<&mut [Vec<f32>]>,
+    ) {
+        let channels = C::num_channels();
+        let len = buffer.len();
+        let frames = len / channels;
+        if self.input_buffer.len() < len {
+            self.input_buffer.resize(len, 0.0);
+        }
+        if self.temp_buffer.len() < len {
+            self.temp_buffer.resize(len, 0.0);
+        }
+        if self.gain_buffer.len() < frames {
+            self.gain_buffer.resize(frames, 0.0);
+        }
+        if self.pan_buffer.len() < frames {
+            self.pan_buffer.resize(frames, 0.0);
+        }
+
+        self.input_buffer[0..len].copy_from_slice(buffer);
+        buffer.fill(0.0);
+
+        let fade_samples = self.fade_time * self.sample_rate;
+        let step = if fade_samples >= 1.0 {
+            1.0 / fade_samples
+        } else {
+            1.0
+        };
+
+        for (i, (input, fade)) in self
+            .inputs
+            .iter_mut()
+            .zip(self.fades.iter_mut())
+            .enumerate()
+        {
+            let temp_slice = &mut self.temp_buffer[0..len];
+            temp_slice.copy_from_slice(&self.input_buffer[0..len]);
+            input.process(temp_slice, sample_index);
+
+            let levels = &mut self.gain_buffer[0..frames];
+            match fade.level.as_mut() {
+                Some(level) => level.process(levels, sample_index),
+                None => levels.fill(1.0),
+            }
+            let pans = &mut self.pan_buffer[0..frames];
+            let pan = match fade.pan.as_mut() {
+                Some(pan) if channels == 2 => {
+                    pan.process(pans, sample_index);
+                    true
+                }
+                _ => false,
+            };
+
+            for frame in 0..frames {
+                fade.gain = ramp(fade.gain, fade.target, step);
+                fade.mix = ramp(fade.mix, fade.mix_target, step);
+                let weight = fade.gain * fade.mix * levels[frame];
+                let start = frame * channels;
+                if pan {
+                    let (left, right) = PanLaw::ConstantPower.gains(pans[frame]);
+                    temp_slice[start] *= weight * left;
+                    temp_slice[start + 1] *= weight * right;
+                } else {
+                    for sample in &mut temp_slice[start..start + channels] {
+                        *sample *= weight;
+                    }
+                }
+                for c in start..start + channels {
+                    buffer[c] += temp_slice[c];
+                }
+            }
+
+            if let Some(stems) = stems.as_deref_mut() {
+                let stem = &mut stems[i];
+                stem.resize(len, 0.0);
+                stem.copy_from_slice(temp_slice);
+            }
+        }
+
+        self.retire_finished();
+    }
+
+    fn apply_output_stage(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let constant_gain = self.gain.get_constant();
+        let skip_processing = !self.soft_clip && constant_gain == Some(1.0);
+
+        if !skip_processing {
+            let channels = C::num_channels();
+            let frames = buffer.len() / channels;
+
+            if self.gain_buffer.len() < frames {
+                self.gain_buffer.resize(frames, 0.0);
+            }
+
+            let gain_slice = &mut self.gain_buffer[0..frames];
+            self.gain.process(gain_slice, sample_index);
+
+            for (i, sample) in buffer.iter_mut().enumerate() {
+                let frame_idx = i / channels;
+                let g = gain_slice[frame_idx];
+
+                let mut val = *sample * g;
+
+                if self.soft_clip {
+                    val = libm::tanhf(val);
+                }
+                *sample = val;
+            }
+        }
+    }
+
+    fn update_mix_targets(&mut self) {
+        let any_solo = self.fades.iter().any(|fade| fade.control.is_soloed());
+        let audible =
+            |fade: &InputFade| !fade.control.is_muted() && (!any_solo || fade.control.is_soloed());
+
+        let compensation = if self.auto_gain {
+            let live = self.fades.iter().filter(|fade| fade.target > 0.0).count();
+            let heard = self
+                .fades
+                .iter()
+                .filter(|fade| fade.target > 0.0 && audible(fade))
+                .count();
+            if heard > 0 {
+                libm::sqrtf(live as f32 / heard as f32)
+            } else {
+                1.0
+            }
+        } else {
+            1.0
+        };
+
+        for i in 0..self.fades.len() {
+            let target = if audible(&self.fades[i]) {
+                compensation
+            } else {
+                0.0
+            };
+            self.fades[i].mix_target = target;
+        }
+    }
+
+    fn retire_finished(&mut self) {
+        let mut i = self.fades.len();
+        while i > 0 {
+            i -= 1;
+            if self.fades[i].is_finished_removal() {
+                self.fades.remove(i);
+                self.retired.push(self.inputs.remove(i));
+            }
+        }
+    }
+}
+
+#[inline(always)]
+fn ramp(current: f32, target: f32, step: f32) -> f32 {
+    if current < target {
+        (current + step).min(target)
+    } else {
+        (current - step).max(target)
+    }
 }
 
 impl<C: ChannelConfig, T: FrameProcessor<C> + Send> FrameProcessor<C> for SummingMixer<C, T> {
@@ -96,7 +529,10 @@ impl<C: ChannelConfig, T: FrameProcessor<C> + Send> FrameProcessor<C> for Summin
             return;
         }
 
-        if self.inputs.len() == 1 {
+        self.update_mix_targets();
+        if self.fades.iter().any(|fade| fade.is_active()) {
+            self.process_weighted(buffer, sample_index, None);
+        } else if self.inputs.len() == 1 {
             self.inputs[0].process(buffer, sample_index);
         } else {
             let len = buffer.len();
@@ -133,42 +569,50 @@ impl<C: ChannelConfig, T: FrameProcessor<C> + Send> FrameProcessor<C> for Summin
             }
         }
 
-        let constant_gain = self.gain.get_constant();
-        let skip_processing = !self.soft_clip && constant_gain == Some(1.0);
-
-        if !skip_processing {
-            let channels = C::num_channels();
-            let frames = buffer.len() / channels;
-
-            if self.gain_buffer.len() < frames {
-                self.gain_buffer.resize(frames, 0.0);
-            }
-
-            let gain_slice = &mut self.gain_buffer[0..frames];
-            self.gain.process(gain_slice, sample_index);
-
-            for (i, sample) in buffer.iter_mut().enumerate() {
-                let frame_idx = i / channels;
-                let g = gain_slice[frame_idx];
-
-                let mut val = *sample * g;
-
-                if self.soft_clip {
-                    val = libm::tanhf(val);
-                }
-                *sample = val;
-            }
-        }
+        self.apply_output_stage(buffer, sample_index);
     }
 
     fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
         for input in &mut self.inputs {
             input.set_sample_rate(sample_rate);
         }
+        for fade in &mut self.fades {
+            for param in fade.level.iter_mut().chain(fade.pan.iter_mut()) {
+                param.set_sample_rate(sample_rate);
+            }
+        }
         self.gain.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.max_block_size = max_block_size;
+        for input in &mut self.inputs {
+            input.prepare(max_block_size);
+        }
+        for fade in &mut self.fades {
+            for param in fade.level.iter_mut().chain(fade.pan.iter_mut()) {
+                param.prepare(max_block_size);
+            }
+        }
+        self.gain.prepare(max_block_size);
+        let len = max_block_size * C::num_channels();
+        self.input_buffer.resize(len, 0.0);
+        self.temp_buffer.resize(len, 0.0);
+        self.gain_buffer.resize(max_block_size, 0.0);
+        self.pan_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
+        self.update_mix_targets();
+        for fade in &mut self.fades {
+            fade.gain = fade.target;
+            fade.mix = fade.mix_target;
+            for param in fade.level.iter_mut().chain(fade.pan.iter_mut()) {
+                param.reset();
+            }
+        }
+        self.retire_finished();
         for input in &mut self.inputs {
             input.reset();
         }
@@ -184,10 +628,31 @@ impl<C: ChannelConfig, T: FrameProcessor<C> + Send> FrameProcessor<C> for Summin
             .unwrap_or_default()
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("gain", &self.gain, 0.0, 4.0);
+        for (i, input) in self.inputs.iter().enumerate() {
+            tree.add_processor(alloc::format!("{}", i), input);
+        }
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        for (i, input) in self.inputs.iter().enumerate() {
+            report.add_processor(&alloc::format!("{}", i), input);
+        }
+    }
+
     fn name(&self) -> &str {
         "SummingMixer"
     }
 
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.set_routing(crate::core::graph::Routing::Parallel);
+        for (i, input) in self.inputs.iter().enumerate() {
+            node.add_processor::<C, _>(alloc::format!("{}", i), input);
+        }
+    }
+
     fn visualize(&self, indent: usize) -> String {
         #[cfg(feature = "debug_visualize")]
         {
@@ -217,6 +682,7 @@ impl<C: ChannelConfig, T: FrameProcessor<C> + Send> FrameProcessor<C> for Summin
 mod tests {
     use super::*;
     use crate::core::channels::Mono;
+    use crate::effects::utility::dc_source::DcSource;
     use crate::effects::utility::lookahead::Lookahead;
     use crate::effects::utility::passthrough::Passthrough;
     use alloc::vec;
@@ -248,4 +714,91 @@ mod tests {
         // Sample 5 should be 2.0 (1.0 from each input, both delayed by 5 samples)
         assert_eq!(buffer[5], 2.0);
     }
+
+    #[test]
+    fn test_add_and_remove_inputs_fade() {
+        let mut mixer =
+            SummingMixer::<Mono, DcSource>::new(vec![DcSource::new(AudioParam::Static(1.0))]);
+        mixer.set_fade_time(64.0 / 44100.0);
+
+        let id = mixer.add_input(DcSource::new(AudioParam::Static(1.0)));
+        let mut buffer = [0.0; 128];
+        mixer.process(&mut buffer, 0);
+
+        // The new input ramps in over 64 samples on top of the existing one.
+        assert!((buffer[0] - (1.0 + 1.0 / 64.0)).abs() < 1e-6);
+        assert!((buffer[31] - 1.5).abs() < 1e-6);
+        assert!(buffer[63..].iter().all(|&s| (s - 2.0).abs() < 1e-6));
+
+        assert!(mixer.remove_input(id));
+        assert!(!mixer.remove_input(InputId(42)));
+        mixer.process(&mut buffer, 128);
+        assert!((buffer[31] - 1.5).abs() < 1e-6);
+        assert!(buffer[63..].iter().all(|&s| (s - 1.0).abs() < 1e-6));
+
+        assert_eq!(mixer.input_count(), 1);
+        assert_eq!(mixer.take_retired().len(), 1);
+        assert!(mixer.take_retired().is_empty());
+    }
+
+    #[test]
+    fn test_removal_reserves_every_retired_slot() {
+        let dc = || DcSource::new(AudioParam::Static(1.0));
+        let mut mixer = SummingMixer::<Mono, DcSource>::new(vec![dc()]);
+        let ids: Vec<_> = (0..5).map(|_| mixer.add_input(dc())).collect();
+        for id in ids {
+            assert!(mixer.remove_input(id));
+        }
+        assert!(mixer.retired.capacity() >= 5);
+    }
+
+    #[test]
+    fn test_solo_mute_with_auto_gain() {
+        let mut mixer = SummingMixer::<Mono, DcSource>::new(vec![
+            DcSource::new(AudioParam::Static(1.0)),
+            DcSource::new(AudioParam::Static(2.0)),
+        ]);
+        mixer.set_fade_time(8.0 / 44100.0);
+        let ids: Vec<_> = mixer.input_ids().collect();
+        let first = mixer.input_control(ids[0]).unwrap();
+        assert!(mixer.input_control(InputId(42)).is_none());
+
+        let mut buffer = [0.0; 32];
+        first.set_mute(true);
+        mixer.process(&mut buffer, 0);
+        assert!((buffer[3] - 2.5).abs() < 1e-6);
+        assert!(buffer[8..].iter().all(|&s| (s - 2.0).abs() < 1e-6));
+
+        first.set_mute(false);
+        first.set_solo(true);
+        mixer.process(&mut buffer, 32);
+        assert!(buffer[8..].iter().all(|&s| (s - 1.0).abs() < 1e-6));
+
+        mixer.set_auto_gain(true);
+        mixer.process(&mut buffer, 64);
+        assert!((buffer[31] - core::f32::consts::SQRT_2).abs() < 1e-6);
+
+        first.set_solo(false);
+        mixer.process(&mut buffer, 96);
+        assert!(buffer[8..].iter().all(|&s| (s - 3.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_per_input_gain_and_pan() {
+        use crate::core::channels::Stereo;
+
+        let mut mixer =
+            SummingMixer::<Stereo, Passthrough>::new(vec![Passthrough::new(), Passthrough::new()]);
+        let ids: Vec<_> = mixer.input_ids().collect();
+        assert!(mixer.set_input_gain(ids[0], AudioParam::Static(0.5)));
+        assert!(mixer.set_input_pan(ids[1], AudioParam::Static(1.0)));
+        assert!(!mixer.set_input_pan(InputId(42), AudioParam::Static(0.0)));
+
+        let mut buffer = [1.0; 8];
+        mixer.process(&mut buffer, 0);
+        for frame in buffer.chunks_exact(2) {
+            assert!((frame[0] - 0.5).abs() < 1e-6);
+            assert!((frame[1] - 1.5).abs() < 1e-6);
+        }
+    }
 }
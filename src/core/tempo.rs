@@ -0,0 +1,209 @@
+use crate::core::channels::Mono;
+use crate::FrameProcessor;
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Musical note lengths for tempo-synced parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoteValue {
+    Whole,
+    Half,
+    Quarter,
+    Eighth,
+    Sixteenth,
+    ThirtySecond,
+    DottedHalf,
+    DottedQuarter,
+    DottedEighth,
+    DottedSixteenth,
+    HalfTriplet,
+    QuarterTriplet,
+    EighthTriplet,
+    SixteenthTriplet,
+    /// A number of 4/4 bars.
+    Bars(u32),
+}
+
+impl NoteValue {
+    /// The straight, dotted and triplet divisions from a whole note down, for quantizing.
+    pub const DIVISIONS: [NoteValue; 14] = [
+        NoteValue::Whole,
+        NoteValue::DottedHalf,
+        NoteValue::Half,
+        NoteValue::HalfTriplet,
+        NoteValue::DottedQuarter,
+        NoteValue::Quarter,
+        NoteValue::DottedEighth,
+        NoteValue::QuarterTriplet,
+        NoteValue::Eighth,
+        NoteValue::DottedSixteenth,
+        NoteValue::EighthTriplet,
+        NoteValue::Sixteenth,
+        NoteValue::SixteenthTriplet,
+        NoteValue::ThirtySecond,
+    ];
+
+    /// Returns the length of the note in quarter-note beats.
+    pub fn beats(&self) -> f32 {
+        match self {
+            NoteValue::Whole => 4.0,
+            NoteValue::Half => 2.0,
+            NoteValue::Quarter => 1.0,
+            NoteValue::Eighth => 0.5,
+            NoteValue::Sixteenth => 0.25,
+            NoteValue::ThirtySecond => 0.125,
+            NoteValue::DottedHalf => 3.0,
+            NoteValue::DottedQuarter => 1.5,
+            NoteValue::DottedEighth => 0.75,
+            NoteValue::DottedSixteenth => 0.375,
+            NoteValue::HalfTriplet => 4.0 / 3.0,
+            NoteValue::QuarterTriplet => 2.0 / 3.0,
+            NoteValue::EighthTriplet => 1.0 / 3.0,
+            NoteValue::SixteenthTriplet => 1.0 / 6.0,
+            NoteValue::Bars(bars) => *bars as f32 * 4.0,
+        }
+    }
+}
+
+/// A shared tempo source.
+///
+/// Clones share the same BPM, so the host (or a UI) can change the tempo at runtime
+/// and every synced parameter follows on the next block.
+#[derive(Clone)]
+pub struct TransportClock {
+    bpm: Arc<AtomicU32>,
+}
+
+impl TransportClock {
+    /// Creates a new TransportClock.
+    ///
+    /// # Arguments
+    /// * `bpm` - Tempo in beats (quarter notes) per minute.
+    pub fn new(bpm: f32) -> Self {
+        TransportClock {
+            bpm: Arc::new(AtomicU32::new(bpm.to_bits())),
+        }
+    }
+
+    /// Sets the tempo in BPM.
+    pub fn set_bpm(&self, bpm: f32) {
+        self.bpm.store(bpm.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Gets the tempo in BPM.
+    pub fn bpm(&self) -> f32 {
+        f32::from_bits(self.bpm.load(Ordering::Relaxed))
+    }
+
+    /// Returns the length of a note in seconds at the current tempo.
+    pub fn note_seconds(&self, note: NoteValue) -> f32 {
+        let bpm = self.bpm().max(1.0);
+        note.beats() * 60.0 / bpm
+    }
+
+    /// Returns the rate in Hz of one cycle per note at the current tempo.
+    pub fn note_hz(&self, note: NoteValue) -> f32 {
+        1.0 / self.note_seconds(note)
+    }
+
+    /// Returns the note division closest to `seconds` at the current tempo.
+    ///
+    /// Closeness is measured as a ratio, so a free delay time snaps to the division that
+    /// sounds nearest. Useful when switching a delay from free time to sync.
+    pub fn quantize(&self, seconds: f32) -> NoteValue {
+        let distance =
+            |note: &NoteValue| libm::fabsf(libm::logf(seconds / self.note_seconds(*note)));
+        NoteValue::DIVISIONS
+            .into_iter()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .unwrap_or(NoteValue::Quarter)
+    }
+}
+
+/// Output unit of a [`TempoSync`] source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncUnit {
+    Seconds,
+    Hz,
+}
+
+/// A parameter source that outputs a note length (or rate) derived from a [`TransportClock`].
+///
+/// Usually created via `AudioParam::synced` or `AudioParam::synced_hz`.
+pub struct TempoSync {
+    clock: TransportClock,
+    note: NoteValue,
+    unit: SyncUnit,
+}
+
+impl TempoSync {
+    /// Creates a new TempoSync.
+    ///
+    /// # Arguments
+    /// * `clock` - The tempo source.
+    /// * `note` - The note value to follow.
+    /// * `unit` - Whether to output seconds (delays) or Hz (LFOs).
+    pub fn new(clock: TransportClock, note: NoteValue, unit: SyncUnit) -> Self {
+        TempoSync { clock, note, unit }
+    }
+
+    /// Sets the note value.
+    pub fn set_note(&mut self, note: NoteValue) {
+        self.note = note;
+    }
+}
+
+impl FrameProcessor<Mono> for TempoSync {
+    fn process(&mut self, buffer: &mut [f32], _sample_index: u64) {
+        let value = match self.unit {
+            SyncUnit::Seconds => self.clock.note_seconds(self.note),
+            SyncUnit::Hz => self.clock.note_hz(self.note),
+        };
+        buffer.fill(value);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "TempoSync"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+
+    #[test]
+    fn test_synced_follows_bpm() {
+        let clock = TransportClock::new(120.0);
+        let mut delay_time = AudioParam::synced(NoteValue::DottedEighth, &clock);
+        let mut lfo_rate = AudioParam::synced_hz(NoteValue::Quarter, &clock);
+
+        let mut buffer = [0.0; 4];
+        delay_time.process(&mut buffer, 0);
+        assert!((buffer[0] - 0.375).abs() < 1e-6);
+        lfo_rate.process(&mut buffer, 0);
+        assert!((buffer[3] - 2.0).abs() < 1e-6);
+
+        clock.set_bpm(60.0);
+        delay_time.process(&mut buffer, 4);
+        assert!((buffer[0] - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_triplets() {
+        let clock = TransportClock::new(90.0);
+        let triplet = clock.note_seconds(NoteValue::EighthTriplet) * 3.0;
+        assert!((triplet - clock.note_seconds(NoteValue::Quarter)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_quantize() {
+        let clock = TransportClock::new(120.0);
+        assert_eq!(clock.quantize(0.36), NoteValue::DottedEighth);
+        assert_eq!(clock.quantize(0.16), NoteValue::EighthTriplet);
+        assert_eq!(clock.quantize(5.0), NoteValue::Whole);
+        clock.set_bpm(60.0);
+        assert_eq!(clock.quantize(0.36), NoteValue::DottedSixteenth);
+    }
+}
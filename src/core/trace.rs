@@ -0,0 +1,202 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::FrameProcessor;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// One recorded block: which parameter, where in the stream, and its average value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TraceRecord {
+    pub id: u32,
+    pub sample_index: u64,
+    pub value: f32,
+}
+
+struct TraceSlot {
+    id: AtomicU32,
+    index_lo: AtomicU32,
+    index_hi: AtomicU32,
+    value: AtomicU32,
+}
+
+struct TraceState {
+    slots: Vec<TraceSlot>,
+    written: AtomicU32,
+}
+
+/// A fixed-size, lock-free ring of parameter values for offline debugging.
+///
+/// Traced parameters (see `AudioParam::traced`) record their block-average value each time
+/// they are processed. Recording never allocates, so it is safe on the audio thread; once
+/// the ring is full the oldest records are overwritten. Reading while the audio thread is
+/// writing may return a record that is being replaced, which is acceptable for a debug tool.
+///
+/// Clones share the same ring.
+#[derive(Clone)]
+pub struct ParamTrace {
+    state: Arc<TraceState>,
+}
+
+impl ParamTrace {
+    /// Creates a new ParamTrace.
+    ///
+    /// # Arguments
+    /// * `capacity` - Number of records kept in the ring.
+    pub fn new(capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "ParamTrace: Capacity must be at least one record."
+        );
+        let slots = (0..capacity)
+            .map(|_| TraceSlot {
+                id: AtomicU32::new(0),
+                index_lo: AtomicU32::new(0),
+                index_hi: AtomicU32::new(0),
+                value: AtomicU32::new(0),
+            })
+            .collect();
+        ParamTrace {
+            state: Arc::new(TraceState {
+                slots,
+                written: AtomicU32::new(0),
+            }),
+        }
+    }
+
+    /// Records a value.
+    pub fn record(&self, id: u32, sample_index: u64, value: f32) {
+        let n = self.state.written.fetch_add(1, Ordering::Relaxed);
+        let slot = &self.state.slots[n as usize % self.state.slots.len()];
+        slot.id.store(id, Ordering::Relaxed);
+        slot.index_lo.store(sample_index as u32, Ordering::Relaxed);
+        slot.index_hi
+            .store((sample_index >> 32) as u32, Ordering::Relaxed);
+        slot.value.store(value.to_bits(), Ordering::Release);
+    }
+
+    /// Returns the retained records, oldest first.
+    pub fn records(&self) -> Vec<TraceRecord> {
+        let capacity = self.state.slots.len();
+        let written = self.state.written.load(Ordering::Acquire) as usize;
+        let count = written.min(capacity);
+        let start = written - count;
+
+        (start..written)
+            .map(|n| {
+                let slot = &self.state.slots[n % capacity];
+                let value = f32::from_bits(slot.value.load(Ordering::Acquire));
+                let lo = slot.index_lo.load(Ordering::Relaxed) as u64;
+                let hi = slot.index_hi.load(Ordering::Relaxed) as u64;
+                TraceRecord {
+                    id: slot.id.load(Ordering::Relaxed),
+                    sample_index: lo | (hi << 32),
+                    value,
+                }
+            })
+            .collect()
+    }
+
+    /// Clears the ring.
+    pub fn clear(&self) {
+        self.state.written.store(0, Ordering::Release);
+    }
+
+    /// Dumps the retained records as CSV (`param,sample_index,value`).
+    ///
+    /// # Arguments
+    /// * `names` - Optional names, indexed by parameter id. Ids without a name are written as numbers.
+    pub fn to_csv(&self, names: &[&str]) -> String {
+        use core::fmt::Write;
+
+        let mut output = String::from("param,sample_index,value\n");
+        for record in self.records() {
+            match names.get(record.id as usize) {
+                Some(name) => {
+                    let _ = write!(output, "{}", name);
+                }
+                None => {
+                    let _ = write!(output, "{}", record.id);
+                }
+            }
+            let _ = writeln!(output, ",{},{}", record.sample_index, record.value);
+        }
+        output
+    }
+}
+
+/// Wraps an AudioParam and records its block-average value into a [`ParamTrace`].
+///
+/// Usually created via `AudioParam::traced`.
+pub struct TracedParam {
+    inner: AudioParam,
+    id: u32,
+    trace: ParamTrace,
+}
+
+impl TracedParam {
+    /// Creates a new TracedParam.
+    ///
+    /// # Arguments
+    /// * `inner` - The parameter to trace.
+    /// * `id` - Identifier written to the trace.
+    /// * `trace` - The trace to record into.
+    pub fn new(inner: AudioParam, id: u32, trace: ParamTrace) -> Self {
+        TracedParam { inner, id, trace }
+    }
+}
+
+impl FrameProcessor<Mono> for TracedParam {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        self.inner.process(buffer, sample_index);
+        if buffer.is_empty() {
+            return;
+        }
+        let average = buffer.iter().sum::<f32>() / buffer.len() as f32;
+        self.trace.record(self.id, sample_index, average);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.inner.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.inner.prepare(max_block_size);
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "TracedParam"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_records_block_average() {
+        let trace = ParamTrace::new(2);
+        let mut cutoff = AudioParam::traced(AudioParam::Static(100.0), 0, &trace);
+        let mut gain = AudioParam::traced(AudioParam::Static(0.5), 1, &trace);
+
+        let mut buffer = [0.0; 8];
+        cutoff.process(&mut buffer, 0);
+        gain.process(&mut buffer, 0);
+        cutoff.process(&mut buffer, 8);
+
+        let records = trace.records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id, 1);
+        assert_eq!(records[1].sample_index, 8);
+        assert_eq!(records[1].value, 100.0);
+
+        let csv = trace.to_csv(&["cutoff"]);
+        assert_eq!(csv, "param,sample_index,value\n1,0,0.5\ncutoff,8,100\n");
+    }
+}
@@ -0,0 +1,602 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::tempo::TransportClock;
+use crate::FrameProcessor;
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Words of one timeline segment: start sample (2), position (2), play state (1), beat
+/// position (2) and tempo (2).
+const SEGMENT_WORDS: usize = 9;
+/// A block holds at most one play and one seek change, so three segments cover it.
+const MAX_SEGMENTS: usize = 3;
+const TIMELINE_WORDS: usize = 1 + SEGMENT_WORDS * MAX_SEGMENTS;
+
+/// A group of 32-bit atomics published together and guarded by a sequence counter.
+///
+/// 64-bit atomics are not available on every embedded target, so wide values are split
+/// into words and published seqlock-style: one writer and any number of readers.
+struct SeqCell<const N: usize> {
+    sequence: AtomicU32,
+    words: [AtomicU32; N],
+}
+
+impl<const N: usize> SeqCell<N> {
+    fn new() -> Self {
+        SeqCell {
+            sequence: AtomicU32::new(0),
+            words: core::array::from_fn(|_| AtomicU32::new(0)),
+        }
+    }
+
+    fn store(&self, words: &[u32; N]) {
+        let seq = self.sequence.load(Ordering::Relaxed);
+        self.sequence.store(seq.wrapping_add(1), Ordering::Release);
+        core::sync::atomic::fence(Ordering::Release);
+        for (word, &value) in self.words.iter().zip(words.iter()) {
+            word.store(value, Ordering::Relaxed);
+        }
+        self.sequence.store(seq.wrapping_add(2), Ordering::Release);
+    }
+
+    fn load(&self) -> [u32; N] {
+        loop {
+            let before = self.sequence.load(Ordering::Acquire);
+            if before & 1 != 0 {
+                core::hint::spin_loop();
+                continue;
+            }
+            let words = core::array::from_fn(|i| self.words[i].load(Ordering::Relaxed));
+            core::sync::atomic::fence(Ordering::Acquire);
+            if self.sequence.load(Ordering::Relaxed) == before {
+                return words;
+            }
+        }
+    }
+}
+
+fn split(value: u64) -> [u32; 2] {
+    [value as u32, (value >> 32) as u32]
+}
+
+fn join(lo: u32, hi: u32) -> u64 {
+    lo as u64 | ((hi as u64) << 32)
+}
+
+/// The song position from one sample index on, until the next segment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Segment {
+    start: u64,
+    position: u64,
+    playing: bool,
+    /// Beat position at `start`.
+    beats: f64,
+    /// Tempo of the segment in beats per sample, latched once per block.
+    beats_per_sample: f64,
+}
+
+impl Segment {
+    const STOPPED: Segment = Segment {
+        start: 0,
+        position: 0,
+        playing: false,
+        beats: 0.0,
+        beats_per_sample: 0.0,
+    };
+
+    fn position_at(&self, sample_index: u64, loop_region: Option<(u64, u64)>) -> u64 {
+        if !self.playing || sample_index <= self.start {
+            return self.position;
+        }
+        let position = self.position + (sample_index - self.start);
+        match loop_region {
+            // Only a position that entered the loop from before its end wraps.
+            Some((start, end)) if self.position < end && position >= end => {
+                start + (position - start) % (end - start)
+            }
+            _ => position,
+        }
+    }
+
+    /// Returns the beat position at `sample_index`. The beats only grow by the elapsed
+    /// samples at the segment's tempo, except past a loop wrap, where they restart from the
+    /// loop position.
+    fn beats_at(&self, sample_index: u64, loop_region: Option<(u64, u64)>) -> f64 {
+        if !self.playing || sample_index <= self.start {
+            return self.beats;
+        }
+        let elapsed = sample_index - self.start;
+        match loop_region {
+            Some((_, end)) if self.position < end && self.position + elapsed >= end => {
+                self.position_at(sample_index, loop_region) as f64 * self.beats_per_sample
+            }
+            _ => self.beats + elapsed as f64 * self.beats_per_sample,
+        }
+    }
+}
+
+/// The segments of the current block, written by the processing clone.
+struct Timeline {
+    cell: SeqCell<TIMELINE_WORDS>,
+}
+
+impl Timeline {
+    fn new() -> Self {
+        let timeline = Timeline {
+            cell: SeqCell::new(),
+        };
+        timeline.store(&[Segment::STOPPED]);
+        timeline
+    }
+
+    fn store(&self, segments: &[Segment]) {
+        let mut words = [0u32; TIMELINE_WORDS];
+        words[0] = segments.len() as u32;
+        for (chunk, segment) in words[1..]
+            .chunks_exact_mut(SEGMENT_WORDS)
+            .zip(segments.iter())
+        {
+            chunk[0..2].copy_from_slice(&split(segment.start));
+            chunk[2..4].copy_from_slice(&split(segment.position));
+            chunk[4] = segment.playing as u32;
+            chunk[5..7].copy_from_slice(&split(segment.beats.to_bits()));
+            chunk[7..9].copy_from_slice(&split(segment.beats_per_sample.to_bits()));
+        }
+        self.cell.store(&words);
+    }
+
+    fn load(&self) -> ([Segment; MAX_SEGMENTS], usize) {
+        let words = self.cell.load();
+        let mut segments = [Segment::STOPPED; MAX_SEGMENTS];
+        for (segment, chunk) in segments
+            .iter_mut()
+            .zip(words[1..].chunks_exact(SEGMENT_WORDS))
+        {
+            *segment = Segment {
+                start: join(chunk[0], chunk[1]),
+                position: join(chunk[2], chunk[3]),
+                playing: chunk[4] != 0,
+                beats: f64::from_bits(join(chunk[5], chunk[6])),
+                beats_per_sample: f64::from_bits(join(chunk[7], chunk[8])),
+            };
+        }
+        (segments, (words[0] as usize).clamp(1, MAX_SEGMENTS))
+    }
+
+    /// Returns the segment that is in effect at `sample_index`.
+    fn segment_at(&self, sample_index: u64) -> Segment {
+        let (segments, count) = self.load();
+        segments[1..count]
+            .iter()
+            .rev()
+            .find(|segment| segment.start <= sample_index)
+            .copied()
+            .unwrap_or(segments[0])
+    }
+}
+
+struct TransportState {
+    anchor: SeqCell<4>,
+    sample_rate: AtomicU32,
+    output_latency: AtomicU32,
+
+    timeline: Timeline,
+    // Requests from the control thread: start sample, value and a generation counter.
+    play_request: SeqCell<4>,
+    seek_request: SeqCell<5>,
+    applied_play: AtomicU32,
+    applied_seek: AtomicU32,
+    loop_region: SeqCell<4>,
+    looping: AtomicBool,
+    time_signature: AtomicU32,
+}
+
+/// The song timeline, and the mapping between `sample_index` and host time.
+///
+/// **Timeline:** the transport holds a tempo, a time signature, a play state, the song
+/// position and an optional loop region. Put a clone at the head of the processing chain:
+/// as a processor it passes audio through unchanged and advances the song position by each
+/// block. Starts, stops and seeks are scheduled at a sample index and take effect at
+/// exactly that sample, and sequencers, LFOs and delays further down the chain can ask for
+/// the position of any sample of the current block. The tempo is a [`TransportClock`]
+/// (see [`clock`](Self::clock)), so tempo-synced parameters follow it.
+///
+/// **Host time:** the audio callback calls [`Transport::anchor`] once per block with the
+/// sample index of the block and the host time at which the callback started. The host
+/// also supplies the output latency reported by the audio backend. Any other thread
+/// (renderer, game loop) can then ask when a given sample becomes audible, or which sample
+/// is audible now.
+///
+/// Clones share the same state. The controls are meant for one control thread.
+#[derive(Clone)]
+pub struct Transport {
+    state: Arc<TransportState>,
+    clock: TransportClock,
+}
+
+impl Transport {
+    /// Creates a new, stopped Transport at 120 BPM in 4/4.
+    ///
+    /// # Arguments
+    /// * `sample_rate` - The sample rate in Hz.
+    pub fn new(sample_rate: f32) -> Self {
+        Transport {
+            state: Arc::new(TransportState {
+                anchor: SeqCell::new(),
+                sample_rate: AtomicU32::new(sample_rate.to_bits()),
+                output_latency: AtomicU32::new(0.0f32.to_bits()),
+                timeline: Timeline::new(),
+                play_request: SeqCell::new(),
+                seek_request: SeqCell::new(),
+                applied_play: AtomicU32::new(0),
+                applied_seek: AtomicU32::new(0),
+                loop_region: SeqCell::new(),
+                looping: AtomicBool::new(false),
+                time_signature: AtomicU32::new(4 << 16 | 4),
+            }),
+            clock: TransportClock::new(120.0),
+        }
+    }
+
+    /// Sets the sample rate used for the mapping.
+    pub fn set_sample_rate(&self, sample_rate: f32) {
+        self.state
+            .sample_rate
+            .store(sample_rate.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Gets the sample rate used for the mapping.
+    pub fn sample_rate(&self) -> f32 {
+        f32::from_bits(self.state.sample_rate.load(Ordering::Relaxed))
+    }
+
+    /// Sets the output latency in seconds (time from the callback to the speaker).
+    pub fn set_output_latency(&self, seconds: f32) {
+        self.state
+            .output_latency
+            .store(seconds.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Gets the output latency in seconds.
+    pub fn output_latency(&self) -> f32 {
+        f32::from_bits(self.state.output_latency.load(Ordering::Relaxed))
+    }
+
+    /// Records that the block starting at `sample_index` is being processed at `host_time`.
+    ///
+    /// Should be called from the audio callback, once per block, before processing.
+    ///
+    /// # Arguments
+    /// * `sample_index` - The sample index passed to `process` for this block.
+    /// * `host_time` - Host time in seconds when the callback started.
+    pub fn anchor(&self, sample_index: u64, host_time: f64) {
+        let [s_lo, s_hi] = split(sample_index);
+        let [t_lo, t_hi] = split(host_time.to_bits());
+        self.state.anchor.store(&[s_lo, s_hi, t_lo, t_hi]);
+    }
+
+    /// Returns the host time (in seconds) at which `sample_index` will be audible.
+    pub fn sample_to_time(&self, sample_index: u64) -> f64 {
+        let (anchor_sample, anchor_time) = self.anchor_point();
+        let offset = sample_index as f64 - anchor_sample as f64;
+        anchor_time + offset / self.sample_rate() as f64 + self.output_latency() as f64
+    }
+
+    /// Returns the sample index that is audible at `host_time`.
+    ///
+    /// Times before the start of the stream map to sample 0.
+    pub fn time_to_sample(&self, host_time: f64) -> u64 {
+        let (anchor_sample, anchor_time) = self.anchor_point();
+        let elapsed = host_time - anchor_time - self.output_latency() as f64;
+        let position = anchor_sample as f64 + elapsed * self.sample_rate() as f64;
+        if position <= 0.0 {
+            0
+        } else {
+            libm::floor(position + 1e-6) as u64
+        }
+    }
+
+    fn anchor_point(&self) -> (u64, f64) {
+        let [s_lo, s_hi, t_lo, t_hi] = self.state.anchor.load();
+        (join(s_lo, s_hi), f64::from_bits(join(t_lo, t_hi)))
+    }
+
+    /// Returns the tempo clock of the transport.
+    pub fn clock(&self) -> TransportClock {
+        self.clock.clone()
+    }
+
+    /// Sets the tempo in BPM.
+    pub fn set_bpm(&self, bpm: f32) {
+        self.clock.set_bpm(bpm);
+    }
+
+    /// Gets the tempo in BPM.
+    pub fn bpm(&self) -> f32 {
+        self.clock.bpm()
+    }
+
+    /// Sets the time signature, e.g. `(6, 8)`.
+    pub fn set_time_signature(&self, numerator: u16, denominator: u16) {
+        let packed = (numerator.max(1) as u32) << 16 | denominator.max(1) as u32;
+        self.state.time_signature.store(packed, Ordering::Relaxed);
+    }
+
+    /// Gets the time signature as `(numerator, denominator)`.
+    pub fn time_signature(&self) -> (u16, u16) {
+        let packed = self.state.time_signature.load(Ordering::Relaxed);
+        ((packed >> 16) as u16, packed as u16)
+    }
+
+    /// Starts playback at the next block.
+    pub fn start(&self) {
+        self.start_at(0);
+    }
+
+    /// Stops playback at the next block. The position is kept.
+    pub fn stop(&self) {
+        self.stop_at(0);
+    }
+
+    /// Starts playback at `sample_index` (past indices take effect at the next block).
+    pub fn start_at(&self, sample_index: u64) {
+        self.request_play(sample_index, true);
+    }
+
+    /// Stops playback at `sample_index` (past indices take effect at the next block).
+    pub fn stop_at(&self, sample_index: u64) {
+        self.request_play(sample_index, false);
+    }
+
+    /// Moves the song position (in samples) at the next block.
+    pub fn seek(&self, position: u64) {
+        self.seek_at(position, 0);
+    }
+
+    /// Moves the song position (in samples) at `sample_index`.
+    ///
+    /// A later request replaces one that has not been reached yet.
+    pub fn seek_at(&self, position: u64, sample_index: u64) {
+        let state = &self.state;
+        let generation = state.seek_request.load()[4].wrapping_add(1);
+        let [at_lo, at_hi] = split(sample_index);
+        let [p_lo, p_hi] = split(position);
+        state
+            .seek_request
+            .store(&[at_lo, at_hi, p_lo, p_hi, generation]);
+    }
+
+    fn request_play(&self, sample_index: u64, playing: bool) {
+        let state = &self.state;
+        let generation = state.play_request.load()[3].wrapping_add(1);
+        let [at_lo, at_hi] = split(sample_index);
+        state
+            .play_request
+            .store(&[at_lo, at_hi, playing as u32, generation]);
+    }
+
+    /// Loops the song position between `start` and `end` (in samples).
+    pub fn set_loop(&self, start: u64, end: u64) {
+        if end > start {
+            let [s_lo, s_hi] = split(start);
+            let [e_lo, e_hi] = split(end);
+            self.state.loop_region.store(&[s_lo, s_hi, e_lo, e_hi]);
+            self.state.looping.store(true, Ordering::Release);
+        } else {
+            self.clear_loop();
+        }
+    }
+
+    /// Removes the loop region.
+    pub fn clear_loop(&self) {
+        self.state.looping.store(false, Ordering::Release);
+    }
+
+    /// Returns the loop region as `(start, end)` in samples, if looping.
+    pub fn loop_region(&self) -> Option<(u64, u64)> {
+        if self.state.looping.load(Ordering::Acquire) {
+            let [s_lo, s_hi, e_lo, e_hi] = self.state.loop_region.load();
+            Some((join(s_lo, s_hi), join(e_lo, e_hi)))
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the transport is playing at `sample_index`.
+    pub fn is_playing_at(&self, sample_index: u64) -> bool {
+        self.state.timeline.segment_at(sample_index).playing
+    }
+
+    /// Returns the song position in samples at `sample_index`.
+    pub fn position_at(&self, sample_index: u64) -> u64 {
+        self.state
+            .timeline
+            .segment_at(sample_index)
+            .position_at(sample_index, self.loop_region())
+    }
+
+    /// Returns the song position in quarter-note beats at `sample_index`.
+    ///
+    /// The beats advance at the tempo of each block, so they stay continuous when the BPM
+    /// changes. Seeks and loop wraps set them from the new sample position at the current
+    /// tempo.
+    pub fn beats_at(&self, sample_index: u64) -> f64 {
+        self.state
+            .timeline
+            .segment_at(sample_index)
+            .beats_at(sample_index, self.loop_region())
+    }
+
+    /// Returns the bar (from 0) and the beat within the bar (in beats of the time
+    /// signature, from 0.0) at `sample_index`.
+    pub fn bar_beat_at(&self, sample_index: u64) -> (u64, f64) {
+        let (numerator, denominator) = self.time_signature();
+        let beats = self.beats_at(sample_index) * denominator as f64 / 4.0;
+        let bar = libm::floor(beats / numerator as f64);
+        (bar as u64, beats - bar * numerator as f64)
+    }
+
+    fn advance(&self, sample_index: u64, frames: usize) {
+        let state = &self.state;
+        let loop_region = self.loop_region();
+        let current = state.timeline.segment_at(sample_index);
+        let beats_per_sample =
+            self.bpm().max(0.0) as f64 / 60.0 / self.sample_rate().max(1.0) as f64;
+        let mut segments = [Segment {
+            start: sample_index,
+            position: current.position_at(sample_index, loop_region),
+            playing: current.playing,
+            beats: current.beats_at(sample_index, loop_region),
+            beats_per_sample,
+        }; MAX_SEGMENTS];
+        let mut count = 1;
+
+        let end = sample_index + frames as u64;
+        let play = state.play_request.load();
+        let seek = state.seek_request.load();
+        let mut events = [
+            (play[3] != state.applied_play.load(Ordering::Relaxed))
+                .then(|| (join(play[0], play[1]).max(sample_index), false)),
+            (seek[4] != state.applied_seek.load(Ordering::Relaxed))
+                .then(|| (join(seek[0], seek[1]).max(sample_index), true)),
+        ];
+        events.sort_by_key(|event| event.map(|(at, _)| at));
+
+        for (at, is_seek) in events.into_iter().flatten() {
+            if at >= end {
+                continue;
+            }
+            let last = segments[count - 1];
+            let mut next = Segment {
+                start: at,
+                position: last.position_at(at, loop_region),
+                playing: last.playing,
+                beats: last.beats_at(at, loop_region),
+                beats_per_sample,
+            };
+            if is_seek {
+                next.position = join(seek[2], seek[3]);
+                next.beats = next.position as f64 * beats_per_sample;
+                state.applied_seek.store(seek[4], Ordering::Relaxed);
+            } else {
+                next.playing = play[2] != 0;
+                state.applied_play.store(play[3], Ordering::Relaxed);
+            }
+            if at == last.start {
+                segments[count - 1] = next;
+            } else {
+                segments[count] = next;
+                count += 1;
+            }
+        }
+        state.timeline.store(&segments[0..count]);
+    }
+}
+
+impl<C: ChannelConfig> FrameProcessor<C> for Transport {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        self.advance(sample_index, buffer.len() / C::num_channels());
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        Transport::set_sample_rate(self, sample_rate);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "Transport"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_time_round_trip() {
+        let transport = Transport::new(48000.0);
+        transport.set_output_latency(0.01);
+        transport.anchor(96000, 100.0);
+
+        let t = transport.sample_to_time(96000 + 4800);
+        assert!((t - 100.11).abs() < 1e-6);
+        assert_eq!(transport.time_to_sample(t), 96000 + 4800);
+
+        let shared = transport.clone();
+        shared.anchor(0, 50.0);
+        assert!((transport.sample_to_time(0) - 50.01).abs() < 1e-6);
+        assert_eq!(transport.time_to_sample(10.0), 0);
+    }
+
+    #[test]
+    fn test_timeline_start_seek_and_loop() {
+        let mut transport = Transport::new(48000.0);
+        let mut buffer = [0.0; 128];
+        let mut process = |transport: &mut Transport, index: u64| {
+            FrameProcessor::<crate::core::channels::Mono>::process(transport, &mut buffer, index)
+        };
+
+        // Start and seek land on their exact samples inside the block.
+        transport.start_at(10);
+        transport.seek_at(1000, 60);
+        process(&mut transport, 0);
+        assert!(!transport.is_playing_at(9));
+        assert_eq!(transport.position_at(9), 0);
+        assert_eq!(transport.position_at(59), 49);
+        assert_eq!(transport.position_at(60), 1000);
+        assert_eq!(transport.position_at(127), 1067);
+
+        process(&mut transport, 128);
+        assert_eq!(transport.position_at(128), 1068);
+
+        // 48000 samples are two beats at 120 BPM.
+        transport.seek(48000 * 7);
+        process(&mut transport, 256);
+        assert!((transport.beats_at(256) - 14.0).abs() < 1e-9);
+        assert_eq!(transport.bar_beat_at(256), (3, 2.0));
+        transport.set_time_signature(6, 8);
+        assert_eq!(transport.bar_beat_at(256), (4, 4.0));
+
+        transport.set_loop(100, 200);
+        transport.seek(150);
+        process(&mut transport, 384);
+        assert_eq!(transport.position_at(433), 199);
+        assert_eq!(transport.position_at(434), 100);
+
+        assert_eq!(transport.position_at(511), 177);
+
+        transport.stop_at(520);
+        process(&mut transport, 512);
+        assert!(transport.is_playing_at(519));
+        assert_eq!(transport.position_at(519), 185);
+        assert!(!transport.is_playing_at(520));
+        assert_eq!(transport.position_at(600), 186);
+    }
+
+    #[test]
+    fn test_beats_continue_across_tempo_changes() {
+        let mut transport = Transport::new(1000.0);
+        let mut buffer = [0.0; 100];
+        let mut process = |transport: &mut Transport, index: u64| {
+            FrameProcessor::<crate::core::channels::Mono>::process(transport, &mut buffer, index)
+        };
+
+        // 120 BPM at 1 kHz is 0.002 beats per sample.
+        transport.start();
+        process(&mut transport, 0);
+        let before = transport.beats_at(99);
+        assert!((before - 0.198).abs() < 1e-9);
+
+        // The new tempo takes over at the next block without moving the position.
+        transport.set_bpm(60.0);
+        assert_eq!(transport.beats_at(99), before);
+        process(&mut transport, 100);
+        assert!((transport.beats_at(100) - 0.2).abs() < 1e-9);
+        assert!((transport.beats_at(199) - 0.299).abs() < 1e-9);
+
+        transport.set_bpm(240.0);
+        process(&mut transport, 200);
+        assert!((transport.beats_at(200) - 0.3).abs() < 1e-9);
+        assert!((transport.beats_at(250) - 0.5).abs() < 1e-9);
+        assert_eq!(transport.bar_beat_at(250), (0, 0.5));
+    }
+}
@@ -53,3 +53,13 @@ impl Default for FastRng {
         Self::new(12345)
     }
 }
+
+/// Returns the level factor for a note velocity.
+///
+/// # Arguments
+/// * `velocity` - Note velocity (0.0 - 1.0).
+/// * `sensitivity` - How much the velocity matters (0.0 = always 1.0, 1.0 = the velocity).
+#[inline]
+pub fn velocity_scale(velocity: f32, sensitivity: f32) -> f32 {
+    1.0 - sensitivity.clamp(0.0, 1.0) * (1.0 - velocity.clamp(0.0, 1.0))
+}
@@ -0,0 +1,255 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::core::error::ValidationReport;
+use crate::core::parameter::Parameter;
+use crate::core::preset::ParameterTree;
+use crate::effects::dynamics::compressor::Compressor;
+use crate::effects::dynamics::softclip::{ClipShape, SoftClipper};
+use crate::effects::filter::biquad::{Biquad, FilterType};
+use crate::effects::utility::gain::Gain;
+use crate::FrameProcessor;
+
+/// Names of the EQ bands in the parameter tree, from low to high.
+const BAND_NAMES: [&str; 4] = ["low", "low_mid", "high_mid", "high"];
+
+/// The parameters of one [`ChannelStrip`] EQ band.
+#[derive(Clone)]
+pub struct EqBandParams {
+    /// Center or corner frequency in Hz.
+    pub frequency: Parameter,
+    /// Boost or cut in dB.
+    pub gain_db: Parameter,
+    /// Q factor (bandwidth of the mid bands, slope of the shelves).
+    pub q: Parameter,
+}
+
+/// The parameter surface of a [`ChannelStrip`].
+///
+/// Every field is a shared handle, so a mixer UI can keep a clone and set the values
+/// while the strip runs.
+#[derive(Clone)]
+pub struct ChannelStripParams {
+    /// Input trim in dB.
+    pub trim_db: Parameter,
+    /// High-pass cutoff in Hz.
+    pub highpass_hz: Parameter,
+    /// Low shelf, two peaking bands and a high shelf.
+    pub eq: [EqBandParams; 4],
+    /// Compressor threshold in dB.
+    pub threshold_db: Parameter,
+    /// Compressor ratio (1.0 = off).
+    pub ratio: Parameter,
+    /// Compressor attack in ms.
+    pub attack_ms: Parameter,
+    /// Compressor release in ms.
+    pub release_ms: Parameter,
+    /// Compressor makeup gain in dB.
+    pub makeup_db: Parameter,
+    /// Drive into the output saturation in dB.
+    pub drive_db: Parameter,
+}
+
+impl ChannelStripParams {
+    fn new() -> Self {
+        let band = |frequency: f32, q: f32| EqBandParams {
+            frequency: Parameter::new(frequency),
+            gain_db: Parameter::new(0.0),
+            q: Parameter::new(q),
+        };
+        ChannelStripParams {
+            trim_db: Parameter::new(0.0),
+            highpass_hz: Parameter::new(20.0),
+            eq: [
+                band(100.0, 0.707),
+                band(500.0, 1.0),
+                band(2500.0, 1.0),
+                band(8000.0, 0.707),
+            ],
+            threshold_db: Parameter::new(0.0),
+            ratio: Parameter::new(1.0),
+            attack_ms: Parameter::new(10.0),
+            release_ms: Parameter::new(100.0),
+            makeup_db: Parameter::new(0.0),
+            drive_db: Parameter::new(0.0),
+        }
+    }
+}
+
+/// A mixer channel strip.
+///
+/// Runs input trim, a 12 dB/octave high-pass, a four-band EQ (low shelf, two peaking mids,
+/// high shelf), a compressor and a tanh saturation stage in series, all driven by one
+/// [`ChannelStripParams`]. The defaults are neutral: 0 dB trim, a 20 Hz high-pass, flat
+/// bands and a 1:1 ratio. The strip is mono; use one per channel of a stereo source.
+pub struct ChannelStrip {
+    params: ChannelStripParams,
+    trim: Gain,
+    highpass: Biquad,
+    eq: [Biquad; 4],
+    compressor: Compressor,
+    saturation: SoftClipper<Mono>,
+}
+
+impl ChannelStrip {
+    /// Creates a new ChannelStrip with neutral settings.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        let params = ChannelStripParams::new();
+        let linked = |p: &Parameter| AudioParam::Linked(p.clone());
+
+        let trim = Gain::new_db_param(linked(&params.trim_db));
+        let highpass = Biquad::new(
+            FilterType::HighPass,
+            linked(&params.highpass_hz),
+            AudioParam::Static(0.707),
+        );
+        let types = [
+            FilterType::LowShelf,
+            FilterType::Peaking,
+            FilterType::Peaking,
+            FilterType::HighShelf,
+        ];
+        let eq = core::array::from_fn(|i| {
+            let band = &params.eq[i];
+            let mut filter = Biquad::new(types[i], linked(&band.frequency), linked(&band.q));
+            filter.set_gain(linked(&band.gain_db));
+            filter
+        });
+        let mut compressor = Compressor::new(linked(&params.threshold_db), linked(&params.ratio));
+        compressor.set_attack(linked(&params.attack_ms));
+        compressor.set_release(linked(&params.release_ms));
+        compressor.set_makeup(linked(&params.makeup_db));
+        let saturation = SoftClipper::new(
+            ClipShape::Tanh,
+            linked(&params.drive_db),
+            AudioParam::Static(0.0),
+        );
+
+        ChannelStrip {
+            params,
+            trim,
+            highpass,
+            eq,
+            compressor,
+            saturation,
+        }
+    }
+
+    /// Returns the parameter surface of the strip.
+    pub fn params(&self) -> &ChannelStripParams {
+        &self.params
+    }
+}
+
+impl FrameProcessor<Mono> for ChannelStrip {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        FrameProcessor::<Mono>::process(&mut self.trim, buffer, sample_index);
+        self.highpass.process(buffer, sample_index);
+        for band in self.eq.iter_mut() {
+            band.process(buffer, sample_index);
+        }
+        self.compressor.process(buffer, sample_index);
+        self.saturation.process(buffer, sample_index);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        FrameProcessor::<Mono>::set_sample_rate(&mut self.trim, sample_rate);
+        self.highpass.set_sample_rate(sample_rate);
+        for band in self.eq.iter_mut() {
+            band.set_sample_rate(sample_rate);
+        }
+        self.compressor.set_sample_rate(sample_rate);
+        self.saturation.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        FrameProcessor::<Mono>::prepare(&mut self.trim, max_block_size);
+        self.highpass.prepare(max_block_size);
+        for band in self.eq.iter_mut() {
+            band.prepare(max_block_size);
+        }
+        self.compressor.prepare(max_block_size);
+        self.saturation.prepare(max_block_size);
+    }
+
+    fn reset(&mut self) {
+        FrameProcessor::<Mono>::reset(&mut self.trim);
+        self.highpass.reset();
+        for band in self.eq.iter_mut() {
+            band.reset();
+        }
+        self.compressor.reset();
+        self.saturation.reset();
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        let p = &self.params;
+        tree.add("trim", p.trim_db.clone(), -24.0, 24.0);
+        tree.add("highpass", p.highpass_hz.clone(), 20.0, 1000.0);
+
+        let mut eq = ParameterTree::new("eq");
+        for (band, name) in p.eq.iter().zip(BAND_NAMES) {
+            let mut node = ParameterTree::new(name);
+            node.add("frequency", band.frequency.clone(), 20.0, 20000.0);
+            node.add("gain", band.gain_db.clone(), -18.0, 18.0);
+            node.add("q", band.q.clone(), 0.1, 10.0);
+            eq.add_child(node);
+        }
+        tree.add_child(eq);
+
+        let mut compressor = ParameterTree::new("compressor");
+        compressor.add("threshold", p.threshold_db.clone(), -60.0, 0.0);
+        compressor.add("ratio", p.ratio.clone(), 1.0, 20.0);
+        compressor.add("attack", p.attack_ms.clone(), 0.1, 200.0);
+        compressor.add("release", p.release_ms.clone(), 10.0, 2000.0);
+        compressor.add("makeup", p.makeup_db.clone(), 0.0, 24.0);
+        tree.add_child(compressor);
+
+        tree.add("drive", p.drive_db.clone(), 0.0, 24.0);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.add_processor("compressor", &self.compressor);
+        report.add_processor("saturation", &self.saturation);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "ChannelStrip"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use core::f32::consts::PI;
+
+    fn level(strip: &mut ChannelStrip, freq: f32) -> f32 {
+        strip.reset();
+        let mut buffer: Vec<f32> = (0..8820)
+            .map(|i| 0.1 * libm::sinf(2.0 * PI * freq * i as f32 / 44100.0))
+            .collect();
+        for block in buffer.chunks_mut(128) {
+            strip.process(block, 0);
+        }
+        let peak = buffer[4410..].iter().fold(0.0f32, |p, s| p.max(s.abs()));
+        20.0 * libm::log10f(peak / 0.1)
+    }
+
+    #[test]
+    fn test_parameter_surface() {
+        let mut strip = ChannelStrip::new();
+        assert!(level(&mut strip, 1000.0).abs() < 0.1);
+
+        let tree = ParameterTree::of::<Mono, _>(&strip);
+        tree.find("trim").unwrap().parameter.set(-6.0);
+        tree.find("eq/high/gain").unwrap().parameter.set(6.0);
+        assert!((level(&mut strip, 1000.0) + 6.0).abs() < 0.3);
+        assert!(level(&mut strip, 16000.0).abs() < 0.5);
+
+        strip.params().highpass_hz.set(500.0);
+        assert!(level(&mut strip, 50.0) < -30.0);
+        assert!(tree.find("compressor/threshold").is_some());
+    }
+}
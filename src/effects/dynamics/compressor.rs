@@ -1,5 +1,7 @@
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::Mono;
+use crate::core::error::{DspError, ValidationReport};
+use crate::core::preset::ParameterTree;
 use crate::FrameProcessor;
 use alloc::vec::Vec;
 
@@ -125,6 +127,15 @@ impl Compressor {
         c
     }
 
+    /// Creates a new Compressor, or returns the first problem with the arguments.
+    ///
+    /// Fails if a static parameter is out of range.
+    pub fn try_new(threshold_db: AudioParam, ratio: AudioParam) -> Result<Self, DspError> {
+        let compressor = Self::new(threshold_db, ratio);
+        ValidationReport::of::<Mono, _>(&compressor).into_first_error()?;
+        Ok(compressor)
+    }
+
     /// Creates a Compressor configured as a Limiter.
     ///
     /// Sets a high ratio and fast attack/release times.
@@ -343,10 +354,43 @@ impl FrameProcessor<Mono> for Compressor {
         self.last_attack_bits = u32::MAX;
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.threshold_db.prepare(max_block_size);
+        self.ratio.prepare(max_block_size);
+        self.attack_ms.prepare(max_block_size);
+        self.release_ms.prepare(max_block_size);
+        self.makeup_gain_db.prepare(max_block_size);
+        self.knee_width_db.prepare(max_block_size);
+        self.threshold_buffer.resize(max_block_size, 0.0);
+        self.ratio_buffer.resize(max_block_size, 0.0);
+        self.attack_buffer.resize(max_block_size, 0.0);
+        self.release_buffer.resize(max_block_size, 0.0);
+        self.makeup_buffer.resize(max_block_size, 0.0);
+        self.knee_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         self.envelope = 0.0;
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("threshold", &self.threshold_db, -60.0, 0.0);
+        tree.add_audio_param("ratio", &self.ratio, 1.0, 20.0);
+        tree.add_audio_param("attack", &self.attack_ms, 0.1, 100.0);
+        tree.add_audio_param("release", &self.release_ms, 1.0, 1000.0);
+        tree.add_audio_param("makeup", &self.makeup_gain_db, 0.0, 24.0);
+        tree.add_audio_param("knee", &self.knee_width_db, 0.0, 24.0);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("Compressor", "threshold", &self.threshold_db, -120.0, 24.0);
+        report.check_param("Compressor", "ratio", &self.ratio, 1.0, 1000.0);
+        report.check_param("Compressor", "attack", &self.attack_ms, 0.01, 1000.0);
+        report.check_param("Compressor", "release", &self.release_ms, 0.1, 10000.0);
+        report.check_param("Compressor", "makeup", &self.makeup_gain_db, -24.0, 48.0);
+        report.check_param("Compressor", "knee", &self.knee_width_db, 0.0, 48.0);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "Compressor"
@@ -1,5 +1,6 @@
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::Mono;
+use crate::core::preset::ParameterTree;
 use crate::FrameProcessor;
 use alloc::vec::Vec;
 use wide::f32x4;
@@ -170,10 +171,22 @@ impl FrameProcessor<Mono> for Distortion {
         self.mix.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.drive.prepare(max_block_size);
+        self.mix.prepare(max_block_size);
+        self.drive_buffer.resize(max_block_size, 0.0);
+        self.mix_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         // Distortion is stateless (memoryless), so nothing to reset
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("drive", &self.drive, 0.0, 20.0);
+        tree.add_audio_param("mix", &self.mix, 0.0, 1.0);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         match self.dist_type {
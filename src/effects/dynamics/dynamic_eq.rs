@@ -0,0 +1,255 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::core::error::ValidationReport;
+use crate::effects::filter::biquad::{Biquad4, BiquadCoeffs, FilterType};
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+use wide::f32x4;
+
+/// How many samples the peaking filter's coefficients are held between updates.
+const CONTROL_INTERVAL: usize = 16;
+
+/// A dynamic EQ band.
+///
+/// A peaking filter whose gain follows the level of its own band: a band-pass detector at
+/// the same frequency and Q feeds an envelope follower, and whenever the band rises above
+/// the threshold the filter cuts it like a compressor would (by `ratio`, at most `range`
+/// dB). Below the threshold the band is left flat. Useful for de-essing and for taming
+/// resonances that only ring out on some notes.
+///
+/// The filter gain is updated every 16 samples.
+pub struct DynamicEqBand {
+    frequency: AudioParam,
+    q: AudioParam,
+    threshold_db: AudioParam,
+    ratio: AudioParam,
+    attack_ms: AudioParam,
+    release_ms: AudioParam,
+    range_db: AudioParam,
+    sample_rate: f32,
+
+    // Lane 0 is the detector, lane 1 the peaking filter, both fed the input.
+    filters: Biquad4,
+    detector_bits: (u32, u32),
+    envelope: f32,
+    gain_db: f32,
+
+    param_buffer: Vec<f32>,
+}
+
+impl DynamicEqBand {
+    /// Creates a new DynamicEqBand.
+    ///
+    /// # Arguments
+    /// * `frequency` - Center frequency in Hz.
+    /// * `q` - Q factor of the filter and the detector.
+    /// * `threshold_db` - Band level above which the cut starts (in dB).
+    /// * `ratio` - The amount of gain reduction (e.g., 4.0 for 4:1).
+    pub fn new(
+        frequency: AudioParam,
+        q: AudioParam,
+        threshold_db: AudioParam,
+        ratio: AudioParam,
+    ) -> Self {
+        DynamicEqBand {
+            frequency,
+            q,
+            threshold_db,
+            ratio,
+            attack_ms: AudioParam::Static(5.0),
+            release_ms: AudioParam::Static(80.0),
+            range_db: AudioParam::Static(12.0),
+            sample_rate: 44100.0,
+            filters: Biquad4::default(),
+            detector_bits: (u32::MAX, u32::MAX),
+            envelope: 0.0,
+            gain_db: 0.0,
+            param_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the frequency parameter in Hz.
+    pub fn set_frequency(&mut self, frequency: AudioParam) {
+        self.frequency = frequency;
+    }
+
+    /// Sets the Q factor parameter.
+    pub fn set_q(&mut self, q: AudioParam) {
+        self.q = q;
+    }
+
+    /// Sets the threshold parameter.
+    pub fn set_threshold(&mut self, threshold: AudioParam) {
+        self.threshold_db = threshold;
+    }
+
+    /// Sets the ratio parameter.
+    pub fn set_ratio(&mut self, ratio: AudioParam) {
+        self.ratio = ratio;
+    }
+
+    /// Sets the attack time parameter (in ms, default 5).
+    pub fn set_attack(&mut self, attack: AudioParam) {
+        self.attack_ms = attack;
+    }
+
+    /// Sets the release time parameter (in ms, default 80).
+    pub fn set_release(&mut self, release: AudioParam) {
+        self.release_ms = release;
+    }
+
+    /// Sets the range parameter: the deepest cut, in dB (default 12).
+    pub fn set_range(&mut self, range: AudioParam) {
+        self.range_db = range;
+    }
+
+    /// Returns the current gain of the filter in dB (0 or below).
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
+    }
+}
+
+impl FrameProcessor<Mono> for DynamicEqBand {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len().max(1);
+        if self.param_buffer.len() < len {
+            self.param_buffer.resize(len, 0.0);
+        }
+        let buf = &mut self.param_buffer[0..len];
+        let freq = self
+            .frequency
+            .block_value(buf, sample_index)
+            .clamp(10.0, self.sample_rate * 0.49);
+        let q = self.q.block_value(buf, sample_index).max(0.05);
+        let threshold_db = self.threshold_db.block_value(buf, sample_index);
+        let ratio = self.ratio.block_value(buf, sample_index).max(1.0);
+        let attack_ms = self.attack_ms.block_value(buf, sample_index).max(0.01);
+        let release_ms = self.release_ms.block_value(buf, sample_index).max(0.01);
+        let range_db = self.range_db.block_value(buf, sample_index).max(0.0);
+
+        let bits = (freq.to_bits(), q.to_bits());
+        if bits != self.detector_bits {
+            let detector = BiquadCoeffs::new(FilterType::BandPass, freq, q, 0.0, self.sample_rate);
+            self.filters.set_lane(0, detector);
+            self.detector_bits = bits;
+        }
+
+        let attack = libm::expf(-1.0 / (attack_ms * self.sample_rate * 0.001));
+        let release = libm::expf(-1.0 / (release_ms * self.sample_rate * 0.001));
+        let slope = 1.0 - 1.0 / ratio;
+
+        for chunk in buffer.chunks_mut(CONTROL_INTERVAL) {
+            let gain_db = if self.envelope > 0.0 {
+                let over_db = 20.0 * libm::log10f(self.envelope + 1e-9) - threshold_db;
+                (-over_db.max(0.0) * slope).max(-range_db)
+            } else {
+                0.0
+            };
+            self.gain_db = gain_db;
+            let peaking =
+                BiquadCoeffs::new(FilterType::Peaking, freq, q, gain_db, self.sample_rate);
+            self.filters.set_lane(1, peaking);
+
+            for sample in chunk.iter_mut() {
+                let [band, filtered, _, _] = self.filters.tick(f32x4::splat(*sample)).to_array();
+                let level = band.abs();
+                let coeff = if level > self.envelope {
+                    attack
+                } else {
+                    release
+                };
+                self.envelope = coeff * self.envelope + (1.0 - coeff) * level;
+                *sample = filtered;
+            }
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.frequency.set_sample_rate(sample_rate);
+        self.q.set_sample_rate(sample_rate);
+        self.threshold_db.set_sample_rate(sample_rate);
+        self.ratio.set_sample_rate(sample_rate);
+        self.attack_ms.set_sample_rate(sample_rate);
+        self.release_ms.set_sample_rate(sample_rate);
+        self.range_db.set_sample_rate(sample_rate);
+        self.detector_bits = (u32::MAX, u32::MAX);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.frequency.prepare(max_block_size);
+        self.q.prepare(max_block_size);
+        self.threshold_db.prepare(max_block_size);
+        self.ratio.prepare(max_block_size);
+        self.attack_ms.prepare(max_block_size);
+        self.release_ms.prepare(max_block_size);
+        self.range_db.prepare(max_block_size);
+        self.param_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.filters.reset();
+        self.envelope = 0.0;
+        self.gain_db = 0.0;
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        let nyquist = self.sample_rate * 0.49;
+        report.check_param("DynamicEqBand", "frequency", &self.frequency, 10.0, nyquist);
+        report.check_param("DynamicEqBand", "q", &self.q, 0.05, 100.0);
+        report.check_param("DynamicEqBand", "ratio", &self.ratio, 1.0, 1000.0);
+        report.check_param("DynamicEqBand", "range", &self.range_db, 0.0, 60.0);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "DynamicEqBand"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use core::f32::consts::PI;
+
+    fn sine_rms(band: &mut DynamicEqBand, freq: f32, amplitude: f32) -> f32 {
+        let mut buffer: Vec<f32> = (0..8192)
+            .map(|i| amplitude * libm::sinf(2.0 * PI * freq * i as f32 / 44100.0))
+            .collect();
+        for block in buffer.chunks_mut(128) {
+            band.process(block, 0);
+        }
+        let tail = &buffer[4096..];
+        libm::sqrtf(tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32) / amplitude
+    }
+
+    fn de_esser() -> DynamicEqBand {
+        DynamicEqBand::new(
+            AudioParam::hz(6000.0),
+            AudioParam::Static(2.0),
+            AudioParam::Static(-30.0),
+            AudioParam::Static(4.0),
+        )
+    }
+
+    #[test]
+    fn test_cuts_only_loud_bands() {
+        // Quiet sibilance and loud lows pass flat.
+        let quiet = sine_rms(&mut de_esser(), 6000.0, 0.01);
+        assert!((quiet - core::f32::consts::FRAC_1_SQRT_2).abs() < 0.01);
+        let lows = sine_rms(&mut de_esser(), 200.0, 0.5);
+        assert!((lows - core::f32::consts::FRAC_1_SQRT_2).abs() < 0.02);
+
+        // Loud sibilance is cut, and never deeper than the range.
+        let mut band = de_esser();
+        let loud = sine_rms(&mut band, 6000.0, 0.5);
+        assert!(loud < 0.3, "{}", loud);
+        assert!(band.gain_db() >= -12.0 && band.gain_db() < -6.0);
+
+        let mut silent = vec![0.0; 64];
+        band.reset();
+        band.process(&mut silent, 0);
+        assert_eq!(band.gain_db(), 0.0);
+    }
+}
@@ -116,6 +116,13 @@ impl<C: ChannelConfig> FrameProcessor<C> for Limiter<C> {
         self.release_ms.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.threshold_db.prepare(max_block_size);
+        self.release_ms.prepare(max_block_size);
+        self.threshold_buffer.resize(max_block_size, 0.0);
+        self.release_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         self.lookahead.reset();
         self.envelope = 0.0;
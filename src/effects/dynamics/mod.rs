@@ -1,3 +1,7 @@
+pub mod cabinet;
 pub mod compressor;
 pub mod distortion;
+pub mod dynamic_eq;
 pub mod limiter;
+pub mod pumper;
+pub mod softclip;
@@ -0,0 +1,222 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::ChannelConfig;
+use crate::core::preset::ParameterTree;
+use crate::core::tempo::NoteValue;
+use crate::core::transport::Transport;
+use crate::effects::utility::gain_automation::shape_segment;
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+
+/// Share of the duck period spent recovering to full level.
+const RELEASE: f32 = 0.5;
+/// Share of the duck period spent falling into the next duck, to avoid a click.
+const ATTACK: f32 = 0.03;
+
+/// A tempo-synced sidechain pumping effect.
+///
+/// Ducks the signal once per `rate` (e.g. every quarter note) as if a four-on-the-floor
+/// kick were keying a compressor, without needing either. The gain drops to
+/// `1.0 - depth` on the beat and recovers over half the period along the curve, built on
+/// the same segment shapes as [`GainAutomationClip`](crate::effects::utility::gain_automation::GainAutomationClip).
+///
+/// The ducks count from song position 0 of a [`Transport`], so they follow seeks and loops
+/// and keep their place through tempo changes.
+///
+/// With a stereo link below 1.0 the channels pump out of phase: at 0.0 the right channel
+/// ducks halfway between the left channel's ducks.
+pub struct Pumper {
+    transport: Transport,
+    rate: NoteValue,
+    depth: AudioParam,
+    curve: AudioParam,
+    stereo_link: AudioParam,
+
+    depth_buffer: Vec<f32>,
+    curve_buffer: Vec<f32>,
+    link_buffer: Vec<f32>,
+}
+
+impl Pumper {
+    /// Creates a new Pumper.
+    ///
+    /// # Arguments
+    /// * `transport` - The song timeline.
+    /// * `rate` - Time between ducks, e.g. `NoteValue::Quarter`.
+    /// * `depth` - Amount of ducking (0.0 - 1.0).
+    pub fn new(transport: &Transport, rate: NoteValue, depth: AudioParam) -> Self {
+        Pumper {
+            transport: transport.clone(),
+            rate,
+            depth,
+            curve: AudioParam::Static(-0.6),
+            stereo_link: AudioParam::Static(1.0),
+            depth_buffer: Vec::with_capacity(128),
+            curve_buffer: Vec::with_capacity(128),
+            link_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the time between ducks.
+    pub fn set_rate(&mut self, rate: NoteValue) {
+        self.rate = rate;
+    }
+
+    /// Sets the depth parameter (0.0 - 1.0).
+    pub fn set_depth(&mut self, depth: AudioParam) {
+        self.depth = depth;
+    }
+
+    /// Sets the recovery curve (-1.0 = snappy, 0.0 = linear, 1.0 = slow swell).
+    pub fn set_curve(&mut self, curve: AudioParam) {
+        self.curve = curve;
+    }
+
+    /// Sets the stereo link (1.0 = channels duck together, 0.0 = alternating).
+    pub fn set_stereo_link(&mut self, stereo_link: AudioParam) {
+        self.stereo_link = stereo_link;
+    }
+
+    #[inline]
+    fn gain(phase: f32, depth: f32, curve: f32) -> f32 {
+        let level = if phase < RELEASE {
+            shape_segment(phase / RELEASE, curve)
+        } else if phase > 1.0 - ATTACK {
+            (1.0 - phase) / ATTACK
+        } else {
+            1.0
+        };
+        1.0 - depth.clamp(0.0, 1.0) * (1.0 - level)
+    }
+}
+
+impl<C: ChannelConfig> FrameProcessor<C> for Pumper {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let channels = C::num_channels();
+        let frames = buffer.len() / channels;
+
+        for buf in [
+            &mut self.depth_buffer,
+            &mut self.curve_buffer,
+            &mut self.link_buffer,
+        ] {
+            if buf.len() < frames {
+                buf.resize(frames, 0.0);
+            }
+        }
+        self.depth
+            .process(&mut self.depth_buffer[0..frames], sample_index);
+        self.curve
+            .process(&mut self.curve_buffer[0..frames], sample_index);
+        self.stereo_link
+            .process(&mut self.link_buffer[0..frames], sample_index);
+
+        let period_beats = self.rate.beats() as f64;
+        for (i, frame) in buffer.chunks_exact_mut(channels).enumerate() {
+            let position = self.transport.beats_at(sample_index + i as u64) / period_beats;
+            let phase = (position - libm::floor(position)) as f32;
+            let depth = self.depth_buffer[i];
+            let curve = self.curve_buffer[i];
+
+            let gain = Self::gain(phase, depth, curve);
+            let offset = 0.5 * (1.0 - self.link_buffer[i].clamp(0.0, 1.0));
+            let mut other_phase = phase + offset;
+            if other_phase >= 1.0 {
+                other_phase -= 1.0;
+            }
+            let other_gain = Self::gain(other_phase, depth, curve);
+
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                *sample *= if channel % 2 == 0 { gain } else { other_gain };
+            }
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.depth.set_sample_rate(sample_rate);
+        self.curve.set_sample_rate(sample_rate);
+        self.stereo_link.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.depth.prepare(max_block_size);
+        self.curve.prepare(max_block_size);
+        self.stereo_link.prepare(max_block_size);
+        self.depth_buffer.resize(max_block_size, 0.0);
+        self.curve_buffer.resize(max_block_size, 0.0);
+        self.link_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.depth.reset();
+        self.curve.reset();
+        self.stereo_link.reset();
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("depth", &self.depth, 0.0, 1.0);
+        tree.add_audio_param("curve", &self.curve, -1.0, 1.0);
+        tree.add_audio_param("stereo_link", &self.stereo_link, 0.0, 1.0);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "Pumper"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::Stereo;
+    use alloc::vec;
+
+    /// Runs the transport and the pumper over `buffer` in 32-frame stereo blocks.
+    fn render(transport: &Transport, pumper: &mut Pumper, buffer: &mut [f32], start: u64) {
+        let mut timeline = transport.clone();
+        for (i, block) in buffer.chunks_mut(64).enumerate() {
+            let sample_index = start + (i * 32) as u64;
+            FrameProcessor::<Stereo>::process(&mut timeline, block, sample_index);
+            FrameProcessor::<Stereo>::process(pumper, block, sample_index);
+        }
+    }
+
+    #[test]
+    fn test_pumps_on_the_beat() {
+        // 120 BPM at 1 kHz with eighth-note ducks: one duck every 250 samples.
+        let transport = Transport::new(1000.0);
+        let mut pumper = Pumper::new(&transport, NoteValue::Eighth, AudioParam::Static(1.0));
+        pumper.set_stereo_link(AudioParam::Static(0.0));
+        transport.start();
+
+        let mut buffer = vec![1.0; 2 * 500];
+        render(&transport, &mut pumper, &mut buffer, 0);
+        let left: Vec<f32> = buffer.iter().step_by(2).copied().collect();
+        let right: Vec<f32> = buffer.iter().skip(1).step_by(2).copied().collect();
+
+        assert_eq!(left[0], 0.0);
+        assert_eq!(left[250], 0.0);
+        assert_eq!(left[200], 1.0);
+        // Unlinked, the right channel ducks between the left channel's ducks.
+        assert_eq!(right[125], 0.0);
+        assert_eq!(right[0], 1.0);
+    }
+
+    #[test]
+    fn test_tempo_change_keeps_phase() {
+        let transport = Transport::new(1000.0);
+        let mut pumper = Pumper::new(&transport, NoteValue::Quarter, AudioParam::Static(1.0));
+        pumper.set_curve(AudioParam::Static(0.0));
+        transport.start();
+
+        // 96 samples at 120 BPM reach beat 0.192, 38.4% into the linear recovery.
+        let mut buffer = vec![1.0; 2 * 96];
+        render(&transport, &mut pumper, &mut buffer, 0);
+
+        // At 60 BPM the recovery carries on from there at half the rate.
+        transport.set_bpm(60.0);
+        let mut buffer = vec![1.0; 2 * 96];
+        render(&transport, &mut pumper, &mut buffer, 96);
+        assert!((buffer[0] - 0.384).abs() < 1e-3);
+        assert!((buffer[2 * 95] - 0.574).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::ChannelConfig;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
+use crate::effects::filter::fir::{design, Fir, FirBand, FirWindow};
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+use core::f32::consts::FRAC_PI_2;
+use core::marker::PhantomData;
+
+/// Length of the half-band filters of the 2x oversampler.
+const OVERSAMPLING_TAPS: usize = 47;
+
+/// The transfer curve of a [`SoftClipper`].
+///
+/// Every shape has unity slope at zero, so quiet signals pass unchanged, and saturates at
+/// exactly ±1.0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipShape {
+    /// Hyperbolic tangent: the smoothest knee.
+    Tanh,
+    /// Cubic polynomial reaching ±1.0 at ±1.5: stays cleaner up to the knee.
+    Cubic,
+    /// Quarter sine reaching ±1.0 at ±π/2: the hardest knee of the three.
+    Sine,
+}
+
+impl ClipShape {
+    #[inline(always)]
+    fn apply(self, x: f32) -> f32 {
+        match self {
+            ClipShape::Tanh => libm::tanhf(x),
+            ClipShape::Cubic => {
+                let x = x.clamp(-1.5, 1.5);
+                x - (4.0 / 27.0) * x * x * x
+            }
+            ClipShape::Sine => libm::sinf(x.clamp(-FRAC_PI_2, FRAC_PI_2)),
+        }
+    }
+}
+
+/// A soft clipper for the end of a mix bus.
+///
+/// Drives the signal into a saturating curve and trims the output, on every channel. With
+/// [`with_oversampling`](Self::with_oversampling) the curve runs at twice the sample rate
+/// between two half-band filters, which keeps the harmonics of hard-driven high notes from
+/// folding back as inharmonic aliases, at the cost of a short latency.
+pub struct SoftClipper<C: ChannelConfig> {
+    shape: ClipShape,
+    drive_db: AudioParam,
+    output_db: AudioParam,
+    // Upsampling and downsampling filters, one pair per channel.
+    oversamplers: Vec<(Fir, Fir)>,
+
+    drive_buffer: Vec<f32>,
+    output_buffer: Vec<f32>,
+    _channels: PhantomData<C>,
+}
+
+impl<C: ChannelConfig> SoftClipper<C> {
+    /// Creates a new SoftClipper without oversampling.
+    ///
+    /// # Arguments
+    /// * `shape` - The transfer curve.
+    /// * `drive_db` - Gain into the curve in dB.
+    /// * `output_db` - Gain after the curve in dB.
+    pub fn new(shape: ClipShape, drive_db: AudioParam, output_db: AudioParam) -> Self {
+        SoftClipper {
+            shape,
+            drive_db,
+            output_db,
+            oversamplers: Vec::new(),
+            drive_buffer: Vec::with_capacity(128),
+            output_buffer: Vec::with_capacity(128),
+            _channels: PhantomData,
+        }
+    }
+
+    /// Runs the curve at twice the sample rate. Adds 23 samples of latency.
+    pub fn with_oversampling(mut self) -> Self {
+        let kernel = design(
+            FirBand::LowPass(0.225),
+            OVERSAMPLING_TAPS,
+            FirWindow::Blackman,
+            1.0,
+        );
+        self.oversamplers = (0..C::num_channels())
+            .map(|_| (Fir::new(&kernel), Fir::new(&kernel)))
+            .collect();
+        self
+    }
+
+    /// Sets the transfer curve.
+    pub fn set_shape(&mut self, shape: ClipShape) {
+        self.shape = shape;
+    }
+
+    /// Sets the drive parameter.
+    pub fn set_drive(&mut self, drive_db: AudioParam) {
+        self.drive_db = drive_db;
+    }
+
+    /// Sets the output trim parameter.
+    pub fn set_output(&mut self, output_db: AudioParam) {
+        self.output_db = output_db;
+    }
+}
+
+impl<C: ChannelConfig> FrameProcessor<C> for SoftClipper<C> {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let channels = C::num_channels();
+        let frames = buffer.len() / channels;
+        if self.drive_buffer.len() < frames {
+            self.drive_buffer.resize(frames, 0.0);
+        }
+        if self.output_buffer.len() < frames {
+            self.output_buffer.resize(frames, 0.0);
+        }
+        self.drive_db
+            .process(&mut self.drive_buffer[0..frames], sample_index);
+        self.output_db
+            .process(&mut self.output_buffer[0..frames], sample_index);
+
+        let shape = self.shape;
+        for (i, frame) in buffer.chunks_exact_mut(channels).enumerate() {
+            let drive = libm::powf(10.0, self.drive_buffer[i] / 20.0);
+            let output = libm::powf(10.0, self.output_buffer[i] / 20.0);
+            if self.oversamplers.is_empty() {
+                for sample in frame.iter_mut() {
+                    *sample = shape.apply(*sample * drive) * output;
+                }
+            } else {
+                for (sample, (up, down)) in frame.iter_mut().zip(self.oversamplers.iter_mut()) {
+                    // Zero-stuffing halves the level, so the interpolator runs at double gain.
+                    let a = up.tick(2.0 * *sample);
+                    let b = up.tick(0.0);
+                    down.tick(shape.apply(a * drive));
+                    *sample = down.tick(shape.apply(b * drive)) * output;
+                }
+            }
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.drive_db.set_sample_rate(sample_rate);
+        self.output_db.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.drive_buffer.resize(max_block_size, 0.0);
+        self.output_buffer.resize(max_block_size, 0.0);
+        self.drive_db.prepare(max_block_size);
+        self.output_db.prepare(max_block_size);
+    }
+
+    fn reset(&mut self) {
+        for (up, down) in self.oversamplers.iter_mut() {
+            up.reset();
+            down.reset();
+        }
+        self.drive_db.reset();
+        self.output_db.reset();
+    }
+
+    fn latency_samples(&self) -> u32 {
+        if self.oversamplers.is_empty() {
+            0
+        } else {
+            // Each half-band delays by (taps - 1) / 2 samples at the doubled rate.
+            (OVERSAMPLING_TAPS as u32 - 1) / 2
+        }
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("drive", &self.drive_db, 0.0, 24.0);
+        tree.add_audio_param("output", &self.output_db, -24.0, 6.0);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("SoftClipper", "drive", &self.drive_db, -24.0, 48.0);
+        report.check_param("SoftClipper", "output", &self.output_db, -60.0, 12.0);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "SoftClipper"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::{Mono, Stereo};
+    use core::f32::consts::PI;
+
+    fn sine(freq: f32, amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * libm::sinf(2.0 * PI * freq * i as f32 / 44100.0))
+            .collect()
+    }
+
+    fn bin_level(signal: &[f32], freq: f32) -> f32 {
+        let (re, im) = signal
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(re, im), (n, &x)| {
+                let phase = 2.0 * PI * freq * n as f32 / 44100.0;
+                (re + x * libm::cosf(phase), im + x * libm::sinf(phase))
+            });
+        2.0 * libm::sqrtf(re * re + im * im) / signal.len() as f32
+    }
+
+    #[test]
+    fn test_ceiling_and_transparency() {
+        for shape in [ClipShape::Tanh, ClipShape::Cubic, ClipShape::Sine] {
+            let mut clipper =
+                SoftClipper::<Stereo>::new(shape, AudioParam::db(12.0), AudioParam::db(0.0));
+            let mut loud = sine(100.0, 1.0, 4410);
+            clipper.process(&mut loud, 0);
+            assert!(loud.iter().all(|s| s.abs() <= 1.0), "{:?}", shape);
+            assert!(loud.iter().any(|s| s.abs() > 0.99), "{:?}", shape);
+
+            let mut quiet = sine(100.0, 0.01, 4410);
+            let reference = quiet.clone();
+            let mut clipper =
+                SoftClipper::<Mono>::new(shape, AudioParam::db(0.0), AudioParam::db(0.0));
+            clipper.process(&mut quiet, 0);
+            for (q, r) in quiet.iter().zip(reference.iter()) {
+                assert!((q - r).abs() < 1e-4, "{:?}", shape);
+            }
+        }
+    }
+
+    #[test]
+    fn test_oversampling_reduces_aliasing() {
+        // The third harmonic of 15 kHz aliases to 900 Hz at 44.1 kHz.
+        let render = |clipper: SoftClipper<Mono>| {
+            let mut clipper = clipper;
+            let mut buffer = sine(15000.0, 0.5, 8820);
+            clipper.process(&mut buffer, 0);
+            bin_level(&buffer[4410..], 900.0)
+        };
+        let make = || SoftClipper::new(ClipShape::Tanh, AudioParam::db(18.0), AudioParam::db(0.0));
+        let plain = render(make());
+        let oversampled = make().with_oversampling();
+        assert_eq!(FrameProcessor::<Mono>::latency_samples(&oversampled), 23);
+        let oversampled = render(oversampled);
+        assert!(plain > 0.05, "{}", plain);
+        assert!(oversampled < plain * 0.1, "{} vs {}", oversampled, plain);
+    }
+}
@@ -0,0 +1,429 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::ChannelConfig;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
+use crate::FrameProcessor;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::{FRAC_1_SQRT_2, PI};
+
+/// Normalized second order section coefficients (a0 = 1).
+#[derive(Clone, Copy)]
+pub(crate) struct SectionCoeffs {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl SectionCoeffs {
+    const IDENTITY: SectionCoeffs = SectionCoeffs {
+        b0: 1.0,
+        b1: 0.0,
+        b2: 0.0,
+        a1: 0.0,
+        a2: 0.0,
+    };
+
+    /// Butterworth (Q = 1/sqrt(2)) sections. Cascading two gives a Linkwitz-Riley slope.
+    fn butterworth(kind: Lr4Kind, freq: f32, sample_rate: f32) -> Self {
+        let freq = freq.clamp(1.0, sample_rate * 0.49);
+        let w0 = 2.0 * PI * freq / sample_rate;
+        let alpha = libm::sinf(w0) / (2.0 * FRAC_1_SQRT_2);
+        let cos_w0 = libm::cosf(w0);
+
+        let (b0, b1, b2) = match kind {
+            Lr4Kind::LowPass => ((1.0 - cos_w0) / 2.0, 1.0 - cos_w0, (1.0 - cos_w0) / 2.0),
+            Lr4Kind::HighPass => ((1.0 + cos_w0) / 2.0, -(1.0 + cos_w0), (1.0 + cos_w0) / 2.0),
+            Lr4Kind::Allpass => (1.0 - alpha, -2.0 * cos_w0, 1.0 + alpha),
+        };
+        let inv_a0 = 1.0 / (1.0 + alpha);
+
+        SectionCoeffs {
+            b0: b0 * inv_a0,
+            b1: b1 * inv_a0,
+            b2: b2 * inv_a0,
+            a1: -2.0 * cos_w0 * inv_a0,
+            a2: (1.0 - alpha) * inv_a0,
+        }
+    }
+}
+
+/// The response of an [`Lr4`] filter.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Lr4Kind {
+    LowPass,
+    HighPass,
+    /// The sum of the LR4 low and high pass at the same frequency (second order allpass).
+    /// Used to phase-align bands that did not pass through a crossover point.
+    Allpass,
+}
+
+/// A 4th order Linkwitz-Riley filter (two cascaded Butterworth sections, TDF2).
+///
+/// Low and high pass outputs at the same frequency sum to an allpass, so a band split
+/// built from these recombines with a flat magnitude response.
+#[derive(Clone, Copy)]
+pub(crate) struct Lr4 {
+    kind: Lr4Kind,
+    coeffs: SectionCoeffs,
+    state: [[f32; 2]; 2],
+}
+
+impl Lr4 {
+    pub(crate) fn new(kind: Lr4Kind) -> Self {
+        Lr4 {
+            kind,
+            coeffs: SectionCoeffs::IDENTITY,
+            state: [[0.0; 2]; 2],
+        }
+    }
+
+    pub(crate) fn set_frequency(&mut self, freq: f32, sample_rate: f32) {
+        self.coeffs = SectionCoeffs::butterworth(self.kind, freq, sample_rate);
+    }
+
+    #[inline(always)]
+    pub(crate) fn process(&mut self, x: f32) -> f32 {
+        let c = self.coeffs;
+        // The allpass is a single section; LR4 low/high pass are two.
+        let sections = if self.kind == Lr4Kind::Allpass { 1 } else { 2 };
+        let mut y = x;
+        for s in self.state.iter_mut().take(sections) {
+            let input = y;
+            y = c.b0 * input + s[0];
+            s[0] = c.b1 * input - c.a1 * y + s[1];
+            s[1] = c.b2 * input - c.a2 * y;
+        }
+        y
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.state = [[0.0; 2]; 2];
+    }
+}
+
+/// Selects which band a [`BandLimited`] wrapper processes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Band {
+    /// Everything below the low crossover.
+    Low,
+    /// Everything between the low and high crossover.
+    Mid,
+    /// Everything above the high crossover.
+    High,
+}
+
+struct ChannelSplit {
+    low_lp: Lr4,
+    low_hp: Lr4,
+    high_lp: Lr4,
+    high_hp: Lr4,
+    low_align: Lr4,
+}
+
+impl ChannelSplit {
+    fn new() -> Self {
+        ChannelSplit {
+            low_lp: Lr4::new(Lr4Kind::LowPass),
+            low_hp: Lr4::new(Lr4Kind::HighPass),
+            high_lp: Lr4::new(Lr4Kind::LowPass),
+            high_hp: Lr4::new(Lr4Kind::HighPass),
+            low_align: Lr4::new(Lr4Kind::Allpass),
+        }
+    }
+
+    fn set_frequencies(&mut self, low: f32, high: f32, sample_rate: f32) {
+        self.low_lp.set_frequency(low, sample_rate);
+        self.low_hp.set_frequency(low, sample_rate);
+        self.high_lp.set_frequency(high, sample_rate);
+        self.high_hp.set_frequency(high, sample_rate);
+        self.low_align.set_frequency(high, sample_rate);
+    }
+
+    /// Returns (low, mid, high).
+    #[inline(always)]
+    fn split(&mut self, x: f32) -> (f32, f32, f32) {
+        let low = self.low_align.process(self.low_lp.process(x));
+        let upper = self.low_hp.process(x);
+        let mid = self.high_lp.process(upper);
+        let high = self.high_hp.process(upper);
+        (low, mid, high)
+    }
+
+    fn reset(&mut self) {
+        self.low_lp.reset();
+        self.low_hp.reset();
+        self.high_lp.reset();
+        self.high_hp.reset();
+        self.low_align.reset();
+    }
+}
+
+/// Processes only one frequency band of the signal.
+///
+/// The signal is split into three bands with Linkwitz-Riley (LR4) crossovers. The selected
+/// band is passed through the inner processor and the other bands are recombined with it
+/// unchanged. The low band is phase-aligned to the upper crossover, so with an identity
+/// processor the output has a flat magnitude response. If the inner processor reports
+/// latency, the untouched bands are delayed to match.
+pub struct BandLimited<P, C: ChannelConfig> {
+    inner: P,
+    band: Band,
+    low_freq: AudioParam,
+    high_freq: AudioParam,
+    sample_rate: f32,
+
+    splits: Vec<ChannelSplit>,
+    band_buffer: Vec<f32>,
+    rest_buffer: Vec<f32>,
+    freq_buffer: Vec<f32>,
+    delay_line: Vec<f32>,
+    write_ptr: usize,
+
+    last_low_bits: u32,
+    last_high_bits: u32,
+    _marker: core::marker::PhantomData<C>,
+}
+
+impl<P: FrameProcessor<C>, C: ChannelConfig> BandLimited<P, C> {
+    /// Creates a new BandLimited wrapper.
+    ///
+    /// # Arguments
+    /// * `inner` - The processor applied to the selected band.
+    /// * `band` - Which band to process.
+    /// * `low_freq` - Low crossover frequency in Hz.
+    /// * `high_freq` - High crossover frequency in Hz.
+    pub fn new(inner: P, band: Band, low_freq: AudioParam, high_freq: AudioParam) -> Self {
+        BandLimited {
+            inner,
+            band,
+            low_freq,
+            high_freq,
+            sample_rate: 44100.0,
+            splits: (0..C::num_channels())
+                .map(|_| ChannelSplit::new())
+                .collect(),
+            band_buffer: Vec::with_capacity(128),
+            rest_buffer: Vec::with_capacity(128),
+            freq_buffer: Vec::with_capacity(128),
+            delay_line: Vec::new(),
+            write_ptr: 0,
+            last_low_bits: u32::MAX,
+            last_high_bits: u32::MAX,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Sets the band to process.
+    pub fn set_band(&mut self, band: Band) {
+        self.band = band;
+    }
+
+    /// Sets the low crossover frequency parameter.
+    pub fn set_low_freq(&mut self, low_freq: AudioParam) {
+        self.low_freq = low_freq;
+    }
+
+    /// Sets the high crossover frequency parameter.
+    pub fn set_high_freq(&mut self, high_freq: AudioParam) {
+        self.high_freq = high_freq;
+    }
+
+    /// Returns a reference to the inner processor.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner processor.
+    pub fn inner_mut(&mut self) -> &mut P {
+        &mut self.inner
+    }
+}
+
+impl<P: FrameProcessor<C>, C: ChannelConfig> FrameProcessor<C> for BandLimited<P, C> {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let channels = C::num_channels();
+        let len = buffer.len();
+        let frames = len / channels;
+        if frames == 0 {
+            return;
+        }
+
+        if self.freq_buffer.len() < frames {
+            self.freq_buffer.resize(frames, 0.0);
+        }
+        self.low_freq
+            .process(&mut self.freq_buffer[0..frames], sample_index);
+        let low = self.freq_buffer[0];
+        self.high_freq
+            .process(&mut self.freq_buffer[0..frames], sample_index);
+        let high = self.freq_buffer[0].max(low);
+
+        if low.to_bits() != self.last_low_bits || high.to_bits() != self.last_high_bits {
+            for split in &mut self.splits {
+                split.set_frequencies(low, high, self.sample_rate);
+            }
+            self.last_low_bits = low.to_bits();
+            self.last_high_bits = high.to_bits();
+        }
+
+        if self.band_buffer.len() < len {
+            self.band_buffer.resize(len, 0.0);
+        }
+        if self.rest_buffer.len() < len {
+            self.rest_buffer.resize(len, 0.0);
+        }
+
+        for (i, &x) in buffer.iter().enumerate() {
+            let (low, mid, high) = self.splits[i % channels].split(x);
+            let (selected, rest) = match self.band {
+                Band::Low => (low, mid + high),
+                Band::Mid => (mid, low + high),
+                Band::High => (high, low + mid),
+            };
+            self.band_buffer[i] = selected;
+            self.rest_buffer[i] = rest;
+        }
+
+        self.inner
+            .process(&mut self.band_buffer[0..len], sample_index);
+
+        let delay_len = self.inner.latency_samples() as usize * channels;
+        if self.delay_line.len() != delay_len {
+            self.delay_line = vec![0.0; delay_len];
+            self.write_ptr = 0;
+        }
+
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let rest = if delay_len > 0 {
+                let delayed = self.delay_line[self.write_ptr];
+                self.delay_line[self.write_ptr] = self.rest_buffer[i];
+                self.write_ptr += 1;
+                if self.write_ptr >= delay_len {
+                    self.write_ptr = 0;
+                }
+                delayed
+            } else {
+                self.rest_buffer[i]
+            };
+            *sample = self.band_buffer[i] + rest;
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.low_freq.set_sample_rate(sample_rate);
+        self.high_freq.set_sample_rate(sample_rate);
+        self.inner.set_sample_rate(sample_rate);
+        self.last_low_bits = u32::MAX;
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.inner.prepare(max_block_size);
+        self.low_freq.prepare(max_block_size);
+        self.high_freq.prepare(max_block_size);
+        let len = max_block_size * C::num_channels();
+        self.freq_buffer.resize(max_block_size, 0.0);
+        self.band_buffer.resize(len, 0.0);
+        self.rest_buffer.resize(len, 0.0);
+
+        let delay_len = self.inner.latency_samples() as usize * C::num_channels();
+        if self.delay_line.len() != delay_len {
+            self.delay_line = vec![0.0; delay_len];
+            self.write_ptr = 0;
+        }
+    }
+
+    fn reset(&mut self) {
+        for split in &mut self.splits {
+            split.reset();
+        }
+        self.delay_line.fill(0.0);
+        self.write_ptr = 0;
+        self.low_freq.reset();
+        self.high_freq.reset();
+        self.inner.reset();
+    }
+
+    fn latency_samples(&self) -> u32 {
+        self.inner.latency_samples()
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_processor::<C, _>("0", &self.inner);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.add_processor::<C, _>("0", &self.inner);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "BandLimited"
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.add_processor::<C, _>("0", &self.inner);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn visualize(&self, indent: usize) -> alloc::string::String {
+        use core::fmt::Write;
+
+        let spaces = " ".repeat(indent);
+        let mut output = alloc::string::String::new();
+        let _ = writeln!(output, "{}BandLimited ({:?} band)", spaces, self.band);
+        output.push_str(&self.inner.visualize(indent + 2));
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::Mono;
+    use crate::effects::utility::gain::Gain;
+    use crate::effects::utility::passthrough::Passthrough;
+
+    fn sine_rms<P: FrameProcessor<Mono>>(processor: &mut P, freq: f32) -> f32 {
+        let mut buffer: Vec<f32> = (0..8192)
+            .map(|i| libm::sinf(2.0 * PI * freq * i as f32 / 44100.0))
+            .collect();
+        for (i, block) in buffer.chunks_mut(128).enumerate() {
+            processor.process(block, (i * 128) as u64);
+        }
+        let tail = &buffer[4096..];
+        libm::sqrtf(tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32)
+    }
+
+    #[test]
+    fn test_identity_is_flat() {
+        for freq in [50.0, 300.0, 1000.0, 4000.0, 12000.0] {
+            let mut split = BandLimited::<_, Mono>::new(
+                Passthrough::new(),
+                Band::Mid,
+                AudioParam::hz(200.0),
+                AudioParam::hz(5000.0),
+            );
+            let rms = sine_rms(&mut split, freq);
+            assert!((rms - FRAC_1_SQRT_2).abs() < 0.01, "{} Hz: {}", freq, rms);
+        }
+    }
+
+    #[test]
+    fn test_only_selected_band_is_processed() {
+        let mut mute_mids = BandLimited::<_, Mono>::new(
+            Gain::new_fixed(0.0),
+            Band::Mid,
+            AudioParam::hz(200.0),
+            AudioParam::hz(5000.0),
+        );
+        assert!(sine_rms(&mut mute_mids, 1000.0) < 0.07);
+
+        mute_mids.reset();
+        assert!((sine_rms(&mut mute_mids, 40.0) - FRAC_1_SQRT_2).abs() < 0.05);
+    }
+}
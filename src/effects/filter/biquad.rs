@@ -1,10 +1,14 @@
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::Mono;
+use crate::core::denormal::{flush_denormal, flush_denormal4};
+use crate::core::preset::ParameterTree;
 use crate::FrameProcessor;
 use alloc::vec::Vec;
 use core::f32::consts::PI;
+use wide::f32x4;
 
 /// The type of biquad filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FilterType {
     /// Low-pass filter.
     LowPass,
@@ -22,9 +26,135 @@ pub enum FilterType {
     HighShelf,
 }
 
+/// Normalized biquad coefficients (`a0` = 1), as used by the transposed direct form II.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BiquadCoeffs {
+    pub b0: f32,
+    pub b1: f32,
+    pub b2: f32,
+    pub a1: f32,
+    pub a2: f32,
+}
+
+impl BiquadCoeffs {
+    /// Coefficients that pass the signal through unchanged.
+    pub const IDENTITY: BiquadCoeffs = BiquadCoeffs {
+        b0: 1.0,
+        b1: 0.0,
+        b2: 0.0,
+        a1: 0.0,
+        a2: 0.0,
+    };
+
+    /// Computes the coefficients of a filter (RBJ cookbook formulas).
+    ///
+    /// # Arguments
+    /// * `filter_type` - The type of filter.
+    /// * `freq` - Cutoff/Center frequency in Hz.
+    /// * `q` - Q factor.
+    /// * `gain_db` - Gain in dB (peaking and shelving filters only).
+    /// * `sample_rate` - Sample rate in Hz.
+    pub fn new(filter_type: FilterType, freq: f32, q: f32, gain_db: f32, sample_rate: f32) -> Self {
+        let w0 = 2.0 * PI * freq / sample_rate;
+        let alpha = libm::sinf(w0) / (2.0 * q);
+        let cos_w0 = libm::cosf(w0);
+        let a = libm::powf(10.0, gain_db / 40.0); // For peaking/shelving
+
+        let (b0, b1, b2, a0, a1, a2) = match filter_type {
+            FilterType::LowPass => (
+                (1.0 - cos_w0) / 2.0,
+                1.0 - cos_w0,
+                (1.0 - cos_w0) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos_w0,
+                1.0 - alpha,
+            ),
+            FilterType::HighPass => (
+                (1.0 + cos_w0) / 2.0,
+                -(1.0 + cos_w0),
+                (1.0 + cos_w0) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos_w0,
+                1.0 - alpha,
+            ),
+            FilterType::BandPass => (alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha),
+            FilterType::Notch => (
+                1.0,
+                -2.0 * cos_w0,
+                1.0,
+                1.0 + alpha,
+                -2.0 * cos_w0,
+                1.0 - alpha,
+            ),
+            FilterType::Peaking => (
+                1.0 + alpha * a,
+                -2.0 * cos_w0,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos_w0,
+                1.0 - alpha / a,
+            ),
+            FilterType::LowShelf => {
+                let sqrt_a = libm::sqrtf(a);
+                (
+                    a * ((a + 1.0) - (a - 1.0) * cos_w0 + 2.0 * sqrt_a * alpha),
+                    2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0),
+                    a * ((a + 1.0) - (a - 1.0) * cos_w0 - 2.0 * sqrt_a * alpha),
+                    (a + 1.0) + (a - 1.0) * cos_w0 + 2.0 * sqrt_a * alpha,
+                    -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0),
+                    (a + 1.0) + (a - 1.0) * cos_w0 - 2.0 * sqrt_a * alpha,
+                )
+            }
+            FilterType::HighShelf => {
+                let sqrt_a = libm::sqrtf(a);
+                (
+                    a * ((a + 1.0) + (a - 1.0) * cos_w0 + 2.0 * sqrt_a * alpha),
+                    -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+                    a * ((a + 1.0) + (a - 1.0) * cos_w0 - 2.0 * sqrt_a * alpha),
+                    (a + 1.0) - (a - 1.0) * cos_w0 + 2.0 * sqrt_a * alpha,
+                    2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+                    (a + 1.0) - (a - 1.0) * cos_w0 - 2.0 * sqrt_a * alpha,
+                )
+            }
+        };
+
+        let inv_a0 = 1.0 / a0;
+        BiquadCoeffs {
+            b0: b0 * inv_a0,
+            b1: b1 * inv_a0,
+            b2: b2 * inv_a0,
+            a1: a1 * inv_a0,
+            a2: a2 * inv_a0,
+        }
+    }
+}
+
+/// The two state variables of a transposed direct form II biquad.
+///
+/// Lets processors that compute their own coefficients (cascades, shelf pairs) run the
+/// same filter section as [`Biquad`] without owning a full `Biquad`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BiquadState {
+    pub z1: f32,
+    pub z2: f32,
+}
+
+impl BiquadState {
+    /// Filters one sample through the section described by `c`.
+    #[inline(always)]
+    pub fn tick(&mut self, c: &BiquadCoeffs, x: f32) -> f32 {
+        let y = c.b0 * x + self.z1;
+        self.z1 = flush_denormal(c.b1 * x - c.a1 * y + self.z2);
+        self.z2 = flush_denormal(c.b2 * x - c.a2 * y);
+        y
+    }
+}
+
 /// A biquad filter implementation.
 ///
 /// Can be configured as LowPass, HighPass, BandPass, Notch, Peaking, LowShelf, or HighShelf.
+/// Uses the transposed direct form II. Coefficients are only recomputed when a parameter
+/// changes: once per block for constant parameters, per sample while they are modulated.
 pub struct Biquad {
     filter_type: FilterType,
     frequency: AudioParam,
@@ -32,17 +162,8 @@ pub struct Biquad {
     gain_db: AudioParam,
     sample_rate: f32,
 
-    a0: f32,
-    a1: f32,
-    a2: f32,
-    b0: f32,
-    b1: f32,
-    b2: f32,
-
-    x1: f32,
-    x2: f32,
-    y1: f32,
-    y2: f32,
+    coeffs: BiquadCoeffs,
+    state: BiquadState,
 
     freq_buffer: Vec<f32>,
     q_buffer: Vec<f32>,
@@ -67,16 +188,8 @@ impl Biquad {
             q,
             gain_db: AudioParam::Static(0.0),
             sample_rate: 44100.0,
-            a0: 0.0,
-            a1: 0.0,
-            a2: 0.0,
-            b0: 0.0,
-            b1: 0.0,
-            b2: 0.0,
-            x1: 0.0,
-            x2: 0.0,
-            y1: 0.0,
-            y2: 0.0,
+            coeffs: BiquadCoeffs::IDENTITY,
+            state: BiquadState::default(),
             freq_buffer: Vec::with_capacity(128),
             q_buffer: Vec::with_capacity(128),
             gain_buffer: Vec::with_capacity(128),
@@ -105,84 +218,153 @@ impl Biquad {
         self.gain_db = gain;
     }
 
-    fn recalc(&mut self, freq: f32, q: f32, gain_db: f32) {
-        let w0 = 2.0 * PI * freq / self.sample_rate;
-        let alpha = libm::sinf(w0) / (2.0 * q);
-        let cos_w0 = libm::cosf(w0);
-        let a = libm::powf(10.0, gain_db / 40.0); // For peaking/shelving
+    /// Returns the coefficients currently in use.
+    pub fn coefficients(&self) -> BiquadCoeffs {
+        self.coeffs
+    }
 
-        match self.filter_type {
-            FilterType::LowPass => {
-                self.b0 = (1.0 - cos_w0) / 2.0;
-                self.b1 = 1.0 - cos_w0;
-                self.b2 = (1.0 - cos_w0) / 2.0;
-                self.a0 = 1.0 + alpha;
-                self.a1 = -2.0 * cos_w0;
-                self.a2 = 1.0 - alpha;
-            }
-            FilterType::HighPass => {
-                self.b0 = (1.0 + cos_w0) / 2.0;
-                self.b1 = -(1.0 + cos_w0);
-                self.b2 = (1.0 + cos_w0) / 2.0;
-                self.a0 = 1.0 + alpha;
-                self.a1 = -2.0 * cos_w0;
-                self.a2 = 1.0 - alpha;
-            }
-            FilterType::BandPass => {
-                self.b0 = alpha;
-                self.b1 = 0.0;
-                self.b2 = -alpha;
-                self.a0 = 1.0 + alpha;
-                self.a1 = -2.0 * cos_w0;
-                self.a2 = 1.0 - alpha;
-            }
-            FilterType::Notch => {
-                self.b0 = 1.0;
-                self.b1 = -2.0 * cos_w0;
-                self.b2 = 1.0;
-                self.a0 = 1.0 + alpha;
-                self.a1 = -2.0 * cos_w0;
-                self.a2 = 1.0 - alpha;
-            }
-            FilterType::Peaking => {
-                self.b0 = 1.0 + alpha * a;
-                self.b1 = -2.0 * cos_w0;
-                self.b2 = 1.0 - alpha * a;
-                self.a0 = 1.0 + alpha / a;
-                self.a1 = -2.0 * cos_w0;
-                self.a2 = 1.0 - alpha / a;
-            }
-            FilterType::LowShelf => {
-                let sqrt_a = libm::sqrtf(a);
-                self.b0 = a * ((a + 1.0) - (a - 1.0) * cos_w0 + 2.0 * sqrt_a * alpha);
-                self.b1 = 2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0);
-                self.b2 = a * ((a + 1.0) - (a - 1.0) * cos_w0 - 2.0 * sqrt_a * alpha);
-                self.a0 = (a + 1.0) + (a - 1.0) * cos_w0 + 2.0 * sqrt_a * alpha;
-                self.a1 = -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0);
-                self.a2 = (a + 1.0) + (a - 1.0) * cos_w0 - 2.0 * sqrt_a * alpha;
-            }
-            FilterType::HighShelf => {
-                let sqrt_a = libm::sqrtf(a);
-                self.b0 = a * ((a + 1.0) + (a - 1.0) * cos_w0 + 2.0 * sqrt_a * alpha);
-                self.b1 = -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0);
-                self.b2 = a * ((a + 1.0) + (a - 1.0) * cos_w0 - 2.0 * sqrt_a * alpha);
-                self.a0 = (a + 1.0) - (a - 1.0) * cos_w0 + 2.0 * sqrt_a * alpha;
-                self.a1 = 2.0 * ((a - 1.0) - (a + 1.0) * cos_w0);
-                self.a2 = (a + 1.0) - (a - 1.0) * cos_w0 - 2.0 * sqrt_a * alpha;
-            }
+    #[inline(always)]
+    fn update(&mut self, freq: f32, q: f32, gain_db: f32) {
+        let freq_bits = freq.to_bits();
+        let q_bits = q.to_bits();
+        let gain_bits = gain_db.to_bits();
+
+        if freq_bits != self.last_freq_bits
+            || q_bits != self.last_q_bits
+            || gain_bits != self.last_gain_bits
+        {
+            self.coeffs = BiquadCoeffs::new(self.filter_type, freq, q, gain_db, self.sample_rate);
+            self.last_freq_bits = freq_bits;
+            self.last_q_bits = q_bits;
+            self.last_gain_bits = gain_bits;
         }
+    }
 
-        let inv_a0 = 1.0 / self.a0;
-        self.b0 *= inv_a0;
-        self.b1 *= inv_a0;
-        self.b2 *= inv_a0;
-        self.a1 *= inv_a0;
-        self.a2 *= inv_a0;
+    #[inline(always)]
+    fn tick(&mut self, x: f32) -> f32 {
+        self.state.tick(&self.coeffs, x)
+    }
+}
+
+/// Four independent biquads in one SIMD vector.
+///
+/// Each lane has its own coefficients and state, so the lanes can be the bands of an
+/// equaliser fed with the same signal, or the channels of a multichannel signal.
+pub struct Biquad4 {
+    b0: f32x4,
+    b1: f32x4,
+    b2: f32x4,
+    a1: f32x4,
+    a2: f32x4,
+    z1: f32x4,
+    z2: f32x4,
+}
+
+impl Default for Biquad4 {
+    fn default() -> Self {
+        Self::new([BiquadCoeffs::IDENTITY; 4])
+    }
+}
+
+impl Biquad4 {
+    /// Creates a new Biquad4.
+    ///
+    /// # Arguments
+    /// * `coeffs` - The coefficients of each lane.
+    pub fn new(coeffs: [BiquadCoeffs; 4]) -> Self {
+        let mut filter = Biquad4 {
+            b0: f32x4::ZERO,
+            b1: f32x4::ZERO,
+            b2: f32x4::ZERO,
+            a1: f32x4::ZERO,
+            a2: f32x4::ZERO,
+            z1: f32x4::ZERO,
+            z2: f32x4::ZERO,
+        };
+        filter.set_coefficients(coeffs);
+        filter
+    }
+
+    /// Sets the coefficients of all lanes. The filter state is kept.
+    pub fn set_coefficients(&mut self, coeffs: [BiquadCoeffs; 4]) {
+        self.b0 = f32x4::from(coeffs.map(|c| c.b0));
+        self.b1 = f32x4::from(coeffs.map(|c| c.b1));
+        self.b2 = f32x4::from(coeffs.map(|c| c.b2));
+        self.a1 = f32x4::from(coeffs.map(|c| c.a1));
+        self.a2 = f32x4::from(coeffs.map(|c| c.a2));
+    }
+
+    /// Sets the coefficients of one lane (0 - 3).
+    pub fn set_lane(&mut self, lane: usize, coeffs: BiquadCoeffs) {
+        let set = |v: &mut f32x4, value: f32| {
+            let mut lanes = v.to_array();
+            lanes[lane] = value;
+            *v = f32x4::from(lanes);
+        };
+        set(&mut self.b0, coeffs.b0);
+        set(&mut self.b1, coeffs.b1);
+        set(&mut self.b2, coeffs.b2);
+        set(&mut self.a1, coeffs.a1);
+        set(&mut self.a2, coeffs.a2);
+    }
+
+    /// Processes one sample per lane.
+    #[inline(always)]
+    pub fn tick(&mut self, x: f32x4) -> f32x4 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = flush_denormal4(self.b1 * x - self.a1 * y + self.z2);
+        self.z2 = flush_denormal4(self.b2 * x - self.a2 * y);
+        y
+    }
+
+    /// Runs every lane on the same input and returns the four outputs per sample.
+    ///
+    /// `output` receives four values (lane 0 - 3) per input sample.
+    pub fn process_parallel(&mut self, input: &[f32], output: &mut [[f32; 4]]) {
+        for (&x, out) in input.iter().zip(output.iter_mut()) {
+            *out = self.tick(f32x4::splat(x)).to_array();
+        }
+    }
+
+    /// Filters an interleaved buffer in place, channel `i` through lane `i`.
+    ///
+    /// # Arguments
+    /// * `buffer` - Interleaved samples.
+    /// * `channels` - Number of channels (1 - 4). Unused lanes are fed silence.
+    pub fn process_interleaved(&mut self, buffer: &mut [f32], channels: usize) {
+        assert!(
+            (1..=4).contains(&channels),
+            "Biquad4: Channel count must be between 1 and 4."
+        );
+        for frame in buffer.chunks_exact_mut(channels) {
+            let mut lanes = [0.0; 4];
+            lanes[0..channels].copy_from_slice(frame);
+            let y = self.tick(f32x4::from(lanes)).to_array();
+            frame.copy_from_slice(&y[0..channels]);
+        }
+    }
+
+    /// Clears the state of all lanes.
+    pub fn reset(&mut self) {
+        self.z1 = f32x4::ZERO;
+        self.z2 = f32x4::ZERO;
     }
 }
 
 impl FrameProcessor<Mono> for Biquad {
     fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        if let (Some(freq), Some(q), Some(gain)) = (
+            self.frequency.get_constant(),
+            self.q.get_constant(),
+            self.gain_db.get_constant(),
+        ) {
+            self.update(freq, q, gain);
+            for sample in buffer.iter_mut() {
+                *sample = self.tick(*sample);
+            }
+            return;
+        }
+
         let len = buffer.len();
 
         if self.freq_buffer.len() < len {
@@ -202,37 +384,8 @@ impl FrameProcessor<Mono> for Biquad {
             .process(&mut self.gain_buffer[0..len], sample_index);
 
         for (i, sample) in buffer.iter_mut().enumerate() {
-            let freq = self.freq_buffer[i];
-            let q = self.q_buffer[i];
-            let gain = self.gain_buffer[i];
-
-            let freq_bits = freq.to_bits();
-            let q_bits = q.to_bits();
-            let gain_bits = gain.to_bits();
-
-            if freq_bits != self.last_freq_bits
-                || q_bits != self.last_q_bits
-                || gain_bits != self.last_gain_bits
-            {
-                self.recalc(freq, q, gain);
-                self.last_freq_bits = freq_bits;
-                self.last_q_bits = q_bits;
-                self.last_gain_bits = gain_bits;
-            }
-
-            let x = *sample;
-            let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2
-                - self.a1 * self.y1
-                - self.a2 * self.y2;
-
-            let y = if y.abs() < 1e-20 { 0.0 } else { y };
-
-            self.x2 = self.x1;
-            self.x1 = x;
-            self.y2 = self.y1;
-            self.y1 = y;
-
-            *sample = y;
+            self.update(self.freq_buffer[i], self.q_buffer[i], self.gain_buffer[i]);
+            *sample = self.tick(*sample);
         }
     }
 
@@ -244,11 +397,23 @@ impl FrameProcessor<Mono> for Biquad {
         self.last_freq_bits = u32::MAX;
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.frequency.prepare(max_block_size);
+        self.q.prepare(max_block_size);
+        self.gain_db.prepare(max_block_size);
+        self.freq_buffer.resize(max_block_size, 0.0);
+        self.q_buffer.resize(max_block_size, 0.0);
+        self.gain_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
-        self.x1 = 0.0;
-        self.x2 = 0.0;
-        self.y1 = 0.0;
-        self.y2 = 0.0;
+        self.state = BiquadState::default();
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("frequency", &self.frequency, 20.0, 20000.0);
+        tree.add_audio_param("q", &self.q, 0.1, 20.0);
+        tree.add_audio_param("gain", &self.gain_db, -24.0, 24.0);
     }
 
     #[cfg(feature = "debug_visualize")]
@@ -264,3 +429,75 @@ impl FrameProcessor<Mono> for Biquad {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn impulse(len: usize) -> Vec<f32> {
+        let mut buffer = alloc::vec![0.0; len];
+        buffer[0] = 1.0;
+        buffer
+    }
+
+    #[test]
+    fn test_matches_direct_form_one() {
+        let c = BiquadCoeffs::new(FilterType::Peaking, 1000.0, 2.0, 6.0, 44100.0);
+        let mut filter = Biquad::new(
+            FilterType::Peaking,
+            AudioParam::hz(1000.0),
+            AudioParam::Static(2.0),
+        );
+        filter.set_gain(AudioParam::db(6.0));
+
+        let input: Vec<f32> = (0..256).map(|i| libm::sinf(i as f32 * 0.13)).collect();
+        let mut output = input.clone();
+        filter.process(&mut output, 0);
+        assert_eq!(filter.coefficients(), c);
+
+        let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+        for (&x, &out) in input.iter().zip(output.iter()) {
+            let y = c.b0 * x + c.b1 * x1 + c.b2 * x2 - c.a1 * y1 - c.a2 * y2;
+            x2 = x1;
+            x1 = x;
+            y2 = y1;
+            y1 = y;
+            assert!((y - out).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_biquad4_lanes_match_biquad() {
+        let types = [
+            FilterType::LowPass,
+            FilterType::HighPass,
+            FilterType::BandPass,
+            FilterType::Notch,
+        ];
+        let coeffs = types.map(|t| BiquadCoeffs::new(t, 2000.0, 0.707, 0.0, 44100.0));
+        let mut bank = Biquad4::new(coeffs);
+
+        let input = impulse(64);
+        let mut output = [[0.0; 4]; 64];
+        bank.process_parallel(&input, &mut output);
+
+        for (lane, filter_type) in types.into_iter().enumerate() {
+            let mut filter = Biquad::new(
+                filter_type,
+                AudioParam::hz(2000.0),
+                AudioParam::Static(0.707),
+            );
+            let mut expected = impulse(64);
+            filter.process(&mut expected, 0);
+            for (frame, &e) in output.iter().zip(expected.iter()) {
+                assert!((frame[lane] - e).abs() < 1e-6);
+            }
+        }
+
+        let mut stereo = Biquad4::new([coeffs[0], coeffs[0], coeffs[0], coeffs[0]]);
+        let mut buffer = [1.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        stereo.process_interleaved(&mut buffer, 2);
+        assert_eq!(buffer[1], 0.0);
+        assert!((buffer[2] - output[1][0]).abs() < 1e-6);
+    }
+}
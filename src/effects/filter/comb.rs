@@ -0,0 +1,226 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::core::denormal::flush_denormal;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
+use crate::effects::time::delay::read_linear;
+use crate::FrameProcessor;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The lowest frequency a [`CombFilter`] can be tuned to, in Hz.
+pub const MIN_COMB_FREQUENCY: f32 = 20.0;
+
+/// The structure of a [`CombFilter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CombMode {
+    /// `y[n] = x[n] + g * x[n - D]`: notches between the harmonics, never unstable.
+    FeedForward,
+    /// `y[n] = x[n] + g * y[n - D]`: resonant peaks at the harmonics.
+    Feedback,
+}
+
+/// A comb filter tuned in Hz.
+///
+/// The delay is one period of `frequency`, so the peaks (or notches) sit on its harmonics.
+/// A negative `feedback` flips the polarity: the comb then resonates on the odd harmonics
+/// only, an octave below `frequency`. `damping` runs a one-pole low-pass on the delayed
+/// signal, so high harmonics ring out sooner, like a plucked string. Modulating the
+/// frequency is smooth, as the delay line is read with linear interpolation.
+pub struct CombFilter {
+    mode: CombMode,
+    frequency: AudioParam,
+    feedback: AudioParam,
+    damping: AudioParam,
+    sample_rate: f32,
+
+    buffer: Vec<f32>,
+    write_ptr: usize,
+    filter_state: f32,
+
+    frequency_buffer: Vec<f32>,
+    feedback_buffer: Vec<f32>,
+    damping_buffer: Vec<f32>,
+}
+
+impl CombFilter {
+    /// Creates a new CombFilter without damping.
+    ///
+    /// # Arguments
+    /// * `mode` - Feedforward or feedback structure.
+    /// * `frequency` - Tuning frequency in Hz (at least `MIN_COMB_FREQUENCY`).
+    /// * `feedback` - Gain of the delayed signal (-0.999 to 0.999; negative inverts).
+    pub fn new(mode: CombMode, frequency: AudioParam, feedback: AudioParam) -> Self {
+        let sample_rate = 44100.0;
+        CombFilter {
+            mode,
+            frequency,
+            feedback,
+            damping: AudioParam::Static(0.0),
+            sample_rate,
+            buffer: vec![0.0; Self::buffer_len(sample_rate)],
+            write_ptr: 0,
+            filter_state: 0.0,
+            frequency_buffer: Vec::with_capacity(128),
+            feedback_buffer: Vec::with_capacity(128),
+            damping_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the frequency parameter.
+    pub fn set_frequency(&mut self, frequency: AudioParam) {
+        self.frequency = frequency;
+    }
+
+    /// Sets the feedback parameter.
+    pub fn set_feedback(&mut self, feedback: AudioParam) {
+        self.feedback = feedback;
+    }
+
+    /// Sets the damping of the delayed signal (0.0 = bright, 1.0 = dark).
+    pub fn set_damping(&mut self, damping: AudioParam) {
+        self.damping = damping;
+    }
+
+    /// Sets the structure of the filter.
+    pub fn set_mode(&mut self, mode: CombMode) {
+        self.mode = mode;
+    }
+
+    fn buffer_len(sample_rate: f32) -> usize {
+        (sample_rate / MIN_COMB_FREQUENCY) as usize + 2
+    }
+}
+
+impl FrameProcessor<Mono> for CombFilter {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        if self.frequency_buffer.len() < len {
+            self.frequency_buffer.resize(len, 0.0);
+        }
+        if self.feedback_buffer.len() < len {
+            self.feedback_buffer.resize(len, 0.0);
+        }
+        if self.damping_buffer.len() < len {
+            self.damping_buffer.resize(len, 0.0);
+        }
+
+        self.frequency
+            .process(&mut self.frequency_buffer[0..len], sample_index);
+        self.feedback
+            .process(&mut self.feedback_buffer[0..len], sample_index);
+        self.damping
+            .process(&mut self.damping_buffer[0..len], sample_index);
+
+        let buf_len = self.buffer.len();
+        let max_delay = (buf_len - 2) as f32;
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let frequency = self.frequency_buffer[i].max(MIN_COMB_FREQUENCY);
+            let delay = (self.sample_rate / frequency).clamp(1.0, max_delay);
+            let g = self.feedback_buffer[i].clamp(-0.999, 0.999);
+            let damp = self.damping_buffer[i].clamp(0.0, 0.99);
+
+            let mut position = self.write_ptr as f32 - delay;
+            if position < 0.0 {
+                position += buf_len as f32;
+            }
+            let delayed = read_linear(&self.buffer, position);
+            self.filter_state = flush_denormal(delayed * (1.0 - damp) + self.filter_state * damp);
+
+            let input = *sample;
+            let output = input + g * self.filter_state;
+            self.buffer[self.write_ptr] = match self.mode {
+                CombMode::FeedForward => input,
+                CombMode::Feedback => output,
+            };
+            self.write_ptr += 1;
+            if self.write_ptr >= buf_len {
+                self.write_ptr = 0;
+            }
+            *sample = output;
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        if sample_rate != self.sample_rate {
+            self.sample_rate = sample_rate;
+            self.buffer = vec![0.0; Self::buffer_len(sample_rate)];
+            self.write_ptr = 0;
+        }
+        self.frequency.set_sample_rate(sample_rate);
+        self.feedback.set_sample_rate(sample_rate);
+        self.damping.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.frequency_buffer.resize(max_block_size, 0.0);
+        self.feedback_buffer.resize(max_block_size, 0.0);
+        self.damping_buffer.resize(max_block_size, 0.0);
+        self.frequency.prepare(max_block_size);
+        self.feedback.prepare(max_block_size);
+        self.damping.prepare(max_block_size);
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.write_ptr = 0;
+        self.filter_state = 0.0;
+        self.frequency.reset();
+        self.feedback.reset();
+        self.damping.reset();
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("frequency", &self.frequency, MIN_COMB_FREQUENCY, 20000.0);
+        tree.add_audio_param("feedback", &self.feedback, -0.999, 0.999);
+        tree.add_audio_param("damping", &self.damping, 0.0, 0.99);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param(
+            "CombFilter",
+            "frequency",
+            &self.frequency,
+            MIN_COMB_FREQUENCY,
+            20000.0,
+        );
+        report.check_param("CombFilter", "feedback", &self.feedback, -0.999, 0.999);
+        report.check_param("CombFilter", "damping", &self.damping, 0.0, 0.99);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "CombFilter"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn impulse_response(mode: CombMode, feedback: f32) -> [f32; 400] {
+        let mut comb = CombFilter::new(mode, AudioParam::hz(441.0), AudioParam::Static(feedback));
+        let mut buffer = [0.0; 400];
+        buffer[0] = 1.0;
+        comb.process(&mut buffer, 0);
+        buffer
+    }
+
+    #[test]
+    fn test_echo_spacing_and_polarity() {
+        // 441 Hz at 44.1 kHz is a 100 sample period.
+        let ff = impulse_response(CombMode::FeedForward, 0.5);
+        assert_eq!(ff[0], 1.0);
+        assert!((ff[100] - 0.5).abs() < 1e-6);
+        assert!(ff[101..].iter().all(|s| s.abs() < 1e-6));
+
+        let fb = impulse_response(CombMode::Feedback, 0.5);
+        assert!((fb[200] - 0.25).abs() < 1e-6);
+        assert!((fb[300] - 0.125).abs() < 1e-6);
+
+        let negative = impulse_response(CombMode::Feedback, -0.5);
+        assert!((negative[100] + 0.5).abs() < 1e-6);
+        assert!((negative[200] - 0.25).abs() < 1e-6);
+        assert!((negative[300] + 0.125).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,481 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::ChannelConfig;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
+use crate::effects::filter::band_limited::{Lr4, Lr4Kind};
+use crate::FrameProcessor;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The most bands a [`Crossover`] can split into.
+pub const MAX_CROSSOVER_BANDS: usize = 4;
+
+/// The filters splitting one channel.
+struct ChannelBands {
+    low_pass: Vec<Lr4>,
+    high_pass: Vec<Lr4>,
+    // `align[b]` runs band `b` through the allpass of every crossover above it.
+    align: Vec<Vec<Lr4>>,
+}
+
+impl ChannelBands {
+    fn new(points: usize) -> Self {
+        ChannelBands {
+            low_pass: vec![Lr4::new(Lr4Kind::LowPass); points],
+            high_pass: vec![Lr4::new(Lr4Kind::HighPass); points],
+            align: (0..=points)
+                .map(|band| vec![Lr4::new(Lr4Kind::Allpass); points.saturating_sub(band + 1)])
+                .collect(),
+        }
+    }
+
+    fn set_frequencies(&mut self, frequencies: &[f32], sample_rate: f32) {
+        for (k, &freq) in frequencies.iter().enumerate() {
+            self.low_pass[k].set_frequency(freq, sample_rate);
+            self.high_pass[k].set_frequency(freq, sample_rate);
+        }
+        for (band, filters) in self.align.iter_mut().enumerate() {
+            for (j, filter) in filters.iter_mut().enumerate() {
+                filter.set_frequency(frequencies[band + 1 + j], sample_rate);
+            }
+        }
+    }
+
+    /// Splits one sample, lowest band first.
+    #[inline(always)]
+    fn split(&mut self, x: f32, out: &mut [f32; MAX_CROSSOVER_BANDS]) {
+        let points = self.low_pass.len();
+        let mut rest = x;
+        for ((band, low_pass), high_pass) in out
+            .iter_mut()
+            .zip(self.low_pass.iter_mut())
+            .zip(self.high_pass.iter_mut())
+        {
+            *band = low_pass.process(rest);
+            rest = high_pass.process(rest);
+        }
+        out[points] = rest;
+        for (band, filters) in self.align.iter_mut().enumerate() {
+            for filter in filters.iter_mut() {
+                out[band] = filter.process(out[band]);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.low_pass.iter_mut().for_each(Lr4::reset);
+        self.high_pass.iter_mut().for_each(Lr4::reset);
+        self.align.iter_mut().flatten().for_each(Lr4::reset);
+    }
+}
+
+/// A band's processor and the delay that aligns it with the slowest band.
+struct BandSlot<C: ChannelConfig> {
+    processor: Option<Box<dyn FrameProcessor<C> + Send>>,
+    buffer: Vec<f32>,
+    delay_line: Vec<f32>,
+    write_ptr: usize,
+}
+
+impl<C: ChannelConfig> BandSlot<C> {
+    fn latency(&self) -> u32 {
+        self.processor.as_ref().map_or(0, |p| p.latency_samples())
+    }
+}
+
+/// A multiband crossover network.
+///
+/// Splits the signal into 2 - 4 bands with Linkwitz-Riley (LR4) crossovers, runs each band
+/// through its own processor and sums the bands again. Every band is phase-aligned to the
+/// crossovers above it, so without processors the output has a flat magnitude response.
+/// Bands whose processor reports less latency than the others are delayed to match.
+///
+/// Bands without a processor pass unchanged. To handle the bands some other way (metering,
+/// separate outputs), use [`process_bands`](Self::process_bands) with a closure instead.
+pub struct Crossover<C: ChannelConfig> {
+    frequencies: Vec<AudioParam>,
+    sample_rate: f32,
+    max_block_size: usize,
+    channels: Vec<ChannelBands>,
+    bands: Vec<BandSlot<C>>,
+    current: Vec<f32>,
+    last: Vec<f32>,
+    freq_buffer: Vec<f32>,
+}
+
+impl<C: ChannelConfig> Crossover<C> {
+    /// Creates a new Crossover.
+    ///
+    /// # Arguments
+    /// * `frequencies` - The crossover frequencies in Hz, ascending. 1 - 3 of them, for
+    ///   2 - 4 bands.
+    pub fn new(frequencies: Vec<AudioParam>) -> Self {
+        let points = frequencies.len();
+        assert!(
+            (1..MAX_CROSSOVER_BANDS).contains(&points),
+            "Crossover: 1 - 3 crossover frequencies are supported."
+        );
+        Crossover {
+            frequencies,
+            sample_rate: 44100.0,
+            max_block_size: 128,
+            channels: (0..C::num_channels())
+                .map(|_| ChannelBands::new(points))
+                .collect(),
+            bands: (0..=points)
+                .map(|_| BandSlot {
+                    processor: None,
+                    buffer: Vec::with_capacity(128),
+                    delay_line: Vec::new(),
+                    write_ptr: 0,
+                })
+                .collect(),
+            current: vec![0.0; points],
+            last: vec![-1.0; points],
+            freq_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the processor for band `band` (0 = lowest) and returns the crossover.
+    pub fn with_band(
+        mut self,
+        band: usize,
+        processor: impl FrameProcessor<C> + Send + 'static,
+    ) -> Self {
+        self.set_band(band, Box::new(processor));
+        self
+    }
+
+    /// Sets the processor for band `band` (0 = lowest).
+    ///
+    /// The processor is prepared for the crossover's sample rate and block size.
+    pub fn set_band(&mut self, band: usize, mut processor: Box<dyn FrameProcessor<C> + Send>) {
+        processor.set_sample_rate(self.sample_rate);
+        processor.prepare(self.max_block_size);
+        self.bands[band].processor = Some(processor);
+        self.resize_delays();
+    }
+
+    /// Removes the processor of band `band`, which then passes unchanged.
+    pub fn clear_band(&mut self, band: usize) {
+        self.bands[band].processor = None;
+        self.resize_delays();
+    }
+
+    /// Sets the crossover frequency parameter `index` (0 = lowest).
+    pub fn set_frequency(&mut self, index: usize, frequency: AudioParam) {
+        self.frequencies[index] = frequency;
+    }
+
+    /// Returns the number of bands.
+    pub fn num_bands(&self) -> usize {
+        self.bands.len()
+    }
+
+    /// Splits `buffer` into the bands, calls `f` with each band (lowest first, interleaved
+    /// like the input) and writes the sum of the bands back to `buffer`.
+    ///
+    /// The band processors are not run, and no latency is compensated.
+    pub fn process_bands(
+        &mut self,
+        buffer: &mut [f32],
+        sample_index: u64,
+        mut f: impl FnMut(usize, &mut [f32]),
+    ) {
+        self.split(buffer, sample_index);
+        let len = buffer.len();
+        for (index, band) in self.bands.iter_mut().enumerate() {
+            f(index, &mut band.buffer[0..len]);
+        }
+        self.sum(buffer);
+    }
+
+    fn split(&mut self, buffer: &[f32], sample_index: u64) {
+        let channels = C::num_channels();
+        let len = buffer.len();
+        let frames = len / channels;
+
+        if self.freq_buffer.len() < frames.max(1) {
+            self.freq_buffer.resize(frames.max(1), 0.0);
+        }
+        let mut floor = 0.0f32;
+        for (k, param) in self.frequencies.iter_mut().enumerate() {
+            param.process(&mut self.freq_buffer[0..frames.max(1)], sample_index);
+            floor = self.freq_buffer[0].max(floor);
+            self.current[k] = floor;
+        }
+        if self.current != self.last {
+            for channel in &mut self.channels {
+                channel.set_frequencies(&self.current, self.sample_rate);
+            }
+            self.last.copy_from_slice(&self.current);
+        }
+
+        for band in &mut self.bands {
+            if band.buffer.len() < len {
+                band.buffer.resize(len, 0.0);
+            }
+        }
+
+        let mut split = [0.0; MAX_CROSSOVER_BANDS];
+        for (i, &x) in buffer.iter().enumerate() {
+            self.channels[i % channels].split(x, &mut split);
+            for (band, &value) in self.bands.iter_mut().zip(split.iter()) {
+                band.buffer[i] = value;
+            }
+        }
+    }
+
+    fn sum(&self, buffer: &mut [f32]) {
+        buffer.fill(0.0);
+        for band in &self.bands {
+            for (out, &x) in buffer.iter_mut().zip(band.buffer.iter()) {
+                *out += x;
+            }
+        }
+    }
+
+    fn resize_delays(&mut self) {
+        let channels = C::num_channels();
+        let latency = FrameProcessor::<C>::latency_samples(self);
+        for band in &mut self.bands {
+            let delay_len = (latency - band.latency()) as usize * channels;
+            if band.delay_line.len() != delay_len {
+                band.delay_line = vec![0.0; delay_len];
+                band.write_ptr = 0;
+            }
+        }
+    }
+}
+
+impl<C: ChannelConfig> FrameProcessor<C> for Crossover<C> {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        self.split(buffer, sample_index);
+        self.resize_delays();
+
+        for band in &mut self.bands {
+            let samples = &mut band.buffer[0..len];
+            if let Some(processor) = band.processor.as_mut() {
+                processor.process(samples, sample_index);
+            }
+            let delay_len = band.delay_line.len();
+            if delay_len > 0 {
+                for sample in samples.iter_mut() {
+                    let delayed = band.delay_line[band.write_ptr];
+                    band.delay_line[band.write_ptr] = *sample;
+                    band.write_ptr += 1;
+                    if band.write_ptr >= delay_len {
+                        band.write_ptr = 0;
+                    }
+                    *sample = delayed;
+                }
+            }
+        }
+
+        self.sum(buffer);
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        for param in &mut self.frequencies {
+            param.set_sample_rate(sample_rate);
+        }
+        for band in &mut self.bands {
+            if let Some(processor) = band.processor.as_mut() {
+                processor.set_sample_rate(sample_rate);
+            }
+        }
+        self.last.fill(-1.0);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.max_block_size = max_block_size;
+        let len = max_block_size * C::num_channels();
+        for param in &mut self.frequencies {
+            param.prepare(max_block_size);
+        }
+        for band in &mut self.bands {
+            if let Some(processor) = band.processor.as_mut() {
+                processor.prepare(max_block_size);
+            }
+            band.buffer.resize(len, 0.0);
+        }
+        self.freq_buffer.resize(max_block_size, 0.0);
+        self.resize_delays();
+    }
+
+    fn reset(&mut self) {
+        for channel in &mut self.channels {
+            channel.reset();
+        }
+        for band in &mut self.bands {
+            if let Some(processor) = band.processor.as_mut() {
+                processor.reset();
+            }
+            band.delay_line.fill(0.0);
+            band.write_ptr = 0;
+        }
+        for param in &mut self.frequencies {
+            param.reset();
+        }
+    }
+
+    fn latency_samples(&self) -> u32 {
+        self.bands.iter().map(BandSlot::latency).max().unwrap_or(0)
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        for (i, band) in self.bands.iter().enumerate() {
+            if let Some(processor) = band.processor.as_ref() {
+                tree.add_processor(alloc::format!("{}", i), processor);
+            }
+        }
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        let nyquist = self.sample_rate * 0.49;
+        for param in &self.frequencies {
+            report.check_param("Crossover", "frequency", param, 1.0, nyquist);
+        }
+        for (i, band) in self.bands.iter().enumerate() {
+            if let Some(processor) = band.processor.as_ref() {
+                report.add_processor(&alloc::format!("{}", i), processor);
+            }
+        }
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "Crossover"
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.set_routing(crate::core::graph::Routing::Parallel);
+        for (i, band) in self.bands.iter().enumerate() {
+            match band.processor.as_ref() {
+                Some(processor) => node.add_processor::<C, _>(alloc::format!("{}", i), processor),
+                None => node.add_child(
+                    alloc::format!("{}", i),
+                    crate::core::graph::GraphNode::new("Passthrough", C::num_channels()),
+                ),
+            }
+        }
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn visualize(&self, indent: usize) -> alloc::string::String {
+        use core::fmt::Write;
+
+        let spaces = " ".repeat(indent);
+        let mut output = alloc::string::String::new();
+        let _ = writeln!(output, "{}Crossover ({} bands)", spaces, self.bands.len());
+        for (i, band) in self.bands.iter().enumerate() {
+            match band.processor.as_ref() {
+                Some(processor) => output.push_str(&processor.visualize(indent + 2)),
+                None => {
+                    let _ = writeln!(output, "{}  Band {}: Passthrough", spaces, i);
+                }
+            }
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::Mono;
+    use crate::effects::utility::gain::Gain;
+    use crate::effects::utility::lookahead::Lookahead;
+    use alloc::sync::Arc;
+    use core::f32::consts::{FRAC_1_SQRT_2, PI};
+    use core::sync::atomic::{AtomicU32, Ordering};
+
+    /// Records the sample rate and block size it was configured with.
+    struct Probe(Arc<[AtomicU32; 2]>);
+
+    impl FrameProcessor<Mono> for Probe {
+        fn process(&mut self, _buffer: &mut [f32], _sample_index: u64) {}
+
+        fn set_sample_rate(&mut self, sample_rate: f32) {
+            self.0[0].store(sample_rate as u32, Ordering::Relaxed);
+        }
+
+        fn prepare(&mut self, max_block_size: usize) {
+            self.0[1].store(max_block_size as u32, Ordering::Relaxed);
+        }
+    }
+
+    fn sine_rms<P: FrameProcessor<Mono>>(processor: &mut P, freq: f32) -> f32 {
+        let mut buffer: Vec<f32> = (0..8192)
+            .map(|i| libm::sinf(2.0 * PI * freq * i as f32 / 44100.0))
+            .collect();
+        for (i, block) in buffer.chunks_mut(128).enumerate() {
+            processor.process(block, (i * 128) as u64);
+        }
+        let tail = &buffer[4096..];
+        libm::sqrtf(tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32)
+    }
+
+    fn four_bands() -> Crossover<Mono> {
+        Crossover::new(vec![
+            AudioParam::hz(150.0),
+            AudioParam::hz(1500.0),
+            AudioParam::hz(6000.0),
+        ])
+    }
+
+    #[test]
+    fn test_recombines_flat() {
+        for freq in [40.0, 150.0, 700.0, 1500.0, 3000.0, 6000.0, 14000.0] {
+            let rms = sine_rms(&mut four_bands(), freq);
+            assert!((rms - FRAC_1_SQRT_2).abs() < 0.01, "{} Hz: {}", freq, rms);
+        }
+
+        // The closure sees each band; muting one leaves only its neighbours' skirts.
+        let mut crossover = four_bands();
+        let mut buffer: Vec<f32> = (0..8192)
+            .map(|i| libm::sinf(2.0 * PI * 3000.0 * i as f32 / 44100.0))
+            .collect();
+        for block in buffer.chunks_mut(128) {
+            crossover.process_bands(block, 0, |band, samples| {
+                if band == 2 {
+                    samples.fill(0.0);
+                }
+            });
+        }
+        assert!(buffer[4096..].iter().all(|s| s.abs() < 0.15));
+    }
+
+    #[test]
+    fn test_band_processors_and_latency() {
+        let mut crossover = Crossover::<Mono>::new(vec![AudioParam::hz(500.0)])
+            .with_band(0, Gain::new_fixed(0.0))
+            .with_band(1, Lookahead::<Mono>::new(32));
+        assert_eq!(crossover.latency_samples(), 32);
+        assert!(sine_rms(&mut crossover, 60.0) < 0.05);
+
+        // With the lows delayed to match, the bands still sum flat.
+        let mut aligned = Crossover::<Mono>::new(vec![AudioParam::hz(500.0)])
+            .with_band(1, Lookahead::<Mono>::new(32));
+        for freq in [100.0, 500.0, 2000.0] {
+            let rms = sine_rms(&mut aligned, freq);
+            assert!((rms - FRAC_1_SQRT_2).abs() < 0.01, "{} Hz: {}", freq, rms);
+        }
+    }
+
+    #[test]
+    fn test_set_band_prepares_processor() {
+        let mut crossover = Crossover::<Mono>::new(vec![AudioParam::hz(500.0)]);
+        crossover.set_sample_rate(48000.0);
+        crossover.prepare(256);
+
+        let config = Arc::new([AtomicU32::new(0), AtomicU32::new(0)]);
+        crossover.set_band(1, Box::new(Probe(config.clone())));
+        assert_eq!(config[0].load(Ordering::Relaxed), 48000);
+        assert_eq!(config[1].load(Ordering::Relaxed), 256);
+    }
+}
@@ -0,0 +1,291 @@
+use crate::core::channels::Mono;
+use crate::core::error::DspError;
+use crate::FrameProcessor;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+use wide::f32x4;
+
+/// The pass band of a windowed-sinc design.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FirBand {
+    /// Passes everything below the cutoff (in Hz).
+    LowPass(f32),
+    /// Passes everything above the cutoff (in Hz).
+    HighPass(f32),
+    /// Passes the band between the two edges (in Hz).
+    BandPass(f32, f32),
+}
+
+/// The window applied to a windowed-sinc design.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FirWindow {
+    /// Narrow transition band, about 44 dB stop band rejection.
+    Hann,
+    /// Slightly narrower transition than Hann, about 53 dB rejection.
+    Hamming,
+    /// Wider transition band, about 74 dB rejection.
+    #[default]
+    Blackman,
+}
+
+impl FirWindow {
+    fn value(self, n: usize, taps: usize) -> f32 {
+        if taps == 1 {
+            return 1.0;
+        }
+        let x = 2.0 * PI * n as f32 / (taps - 1) as f32;
+        match self {
+            FirWindow::Hann => 0.5 - 0.5 * libm::cosf(x),
+            FirWindow::Hamming => 0.54 - 0.46 * libm::cosf(x),
+            FirWindow::Blackman => 0.42 - 0.5 * libm::cosf(x) + 0.08 * libm::cosf(2.0 * x),
+        }
+    }
+}
+
+/// Designs a linear-phase windowed-sinc FIR kernel.
+///
+/// The length is rounded up to an odd number of taps, so the kernel has a whole-sample
+/// group delay of `(taps - 1) / 2` and can be spectrally inverted for the high-pass.
+/// Low-pass kernels are normalized to unity gain at DC, high-pass kernels at Nyquist and
+/// band-pass kernels at the band center.
+///
+/// # Arguments
+/// * `band` - The pass band.
+/// * `taps` - The kernel length.
+/// * `window` - The window to apply.
+/// * `sample_rate` - The sample rate the kernel will run at.
+pub fn design(band: FirBand, taps: usize, window: FirWindow, sample_rate: f32) -> Vec<f32> {
+    let taps = taps.max(1) | 1;
+    let nyquist = sample_rate * 0.5;
+    let normalize = |hz: f32| hz.clamp(0.0, nyquist) / sample_rate;
+
+    let lowpass = |cutoff: f32| -> Vec<f32> {
+        let center = (taps / 2) as f32;
+        let mut kernel: Vec<f32> = (0..taps)
+            .map(|n| {
+                let t = n as f32 - center;
+                let sinc = if t == 0.0 {
+                    2.0 * cutoff
+                } else {
+                    libm::sinf(2.0 * PI * cutoff * t) / (PI * t)
+                };
+                sinc * window.value(n, taps)
+            })
+            .collect();
+        let sum: f32 = kernel.iter().sum();
+        if sum.abs() > 1e-9 {
+            for h in kernel.iter_mut() {
+                *h /= sum;
+            }
+        }
+        kernel
+    };
+
+    match band {
+        FirBand::LowPass(cutoff) => lowpass(normalize(cutoff)),
+        FirBand::HighPass(cutoff) => {
+            let mut kernel = lowpass(normalize(cutoff));
+            for h in kernel.iter_mut() {
+                *h = -*h;
+            }
+            kernel[taps / 2] += 1.0;
+            kernel
+        }
+        FirBand::BandPass(low, high) => {
+            let (low, high) = (normalize(low.min(high)), normalize(low.max(high)));
+            let narrow = lowpass(low);
+            let mut kernel = lowpass(high);
+            for (h, l) in kernel.iter_mut().zip(narrow.iter()) {
+                *h -= l;
+            }
+            // Normalize at the band center.
+            let omega = PI * (low + high);
+            let center = (taps / 2) as f32;
+            let gain: f32 = kernel
+                .iter()
+                .enumerate()
+                .map(|(n, h)| h * libm::cosf(omega * (n as f32 - center)))
+                .sum();
+            if gain.abs() > 1e-9 {
+                for h in kernel.iter_mut() {
+                    *h /= gain;
+                }
+            }
+            kernel
+        }
+    }
+}
+
+/// A SIMD direct-form FIR filter.
+///
+/// Convolves the signal with its kernel in the time domain, four taps at a time, which
+/// suits kernels up to a few thousand taps. Build it from any kernel with
+/// [`new`](Self::new), or from a windowed-sinc [`design`] with [`designed`](Self::designed),
+/// which is redesigned when the sample rate changes and reports its group delay as latency.
+pub struct Fir {
+    design: Option<(FirBand, usize, FirWindow)>,
+    // Reversed and zero-padded to a multiple of four taps.
+    kernel: Vec<f32>,
+    // Every sample is written twice, so the last `kernel.len()` samples are contiguous.
+    history: Vec<f32>,
+    pos: usize,
+    latency: u32,
+    sample_rate: f32,
+}
+
+impl Fir {
+    /// Creates a new Fir filter from a kernel, with no reported latency.
+    ///
+    /// # Arguments
+    /// * `kernel` - The impulse response (at least one tap).
+    pub fn new(kernel: &[f32]) -> Self {
+        let mut fir = Fir {
+            design: None,
+            kernel: Vec::new(),
+            history: Vec::new(),
+            pos: 0,
+            latency: 0,
+            sample_rate: 44100.0,
+        };
+        fir.set_kernel(kernel);
+        fir
+    }
+
+    /// Creates a new Fir filter, or returns an error if the kernel is empty.
+    pub fn try_new(kernel: &[f32]) -> Result<Self, DspError> {
+        if kernel.is_empty() {
+            return Err(DspError::Invalid {
+                processor: "Fir",
+                message: "The kernel must have at least one tap.",
+            });
+        }
+        Ok(Self::new(kernel))
+    }
+
+    /// Creates a new linear-phase Fir filter from a windowed-sinc design.
+    ///
+    /// # Arguments
+    /// * `band` - The pass band.
+    /// * `taps` - The kernel length (rounded up to odd).
+    /// * `window` - The window to apply.
+    pub fn designed(band: FirBand, taps: usize, window: FirWindow) -> Self {
+        let mut fir = Self::new(&design(band, taps, window, 44100.0));
+        fir.design = Some((band, taps, window));
+        fir.latency = (taps.max(1) | 1) as u32 / 2;
+        fir
+    }
+
+    /// Replaces the kernel. Allocates; call it outside the audio callback.
+    pub fn set_kernel(&mut self, kernel: &[f32]) {
+        assert!(
+            !kernel.is_empty(),
+            "Fir: The kernel must have at least one tap."
+        );
+        let taps = kernel.len().div_ceil(4) * 4;
+        self.kernel = vec![0.0; taps];
+        for (k, &h) in self.kernel.iter_mut().rev().zip(kernel.iter()) {
+            *k = h;
+        }
+        self.history = vec![0.0; 2 * taps];
+        self.pos = 0;
+    }
+
+    /// Sets the latency to report, e.g. the group delay of a linear-phase kernel.
+    pub fn set_latency(&mut self, samples: u32) {
+        self.latency = samples;
+    }
+
+    /// Returns the number of taps, including SIMD padding.
+    pub fn taps(&self) -> usize {
+        self.kernel.len()
+    }
+
+    #[inline(always)]
+    pub(crate) fn tick(&mut self, input: f32) -> f32 {
+        let taps = self.kernel.len();
+        self.history[self.pos] = input;
+        self.history[self.pos + taps] = input;
+        let window = &self.history[self.pos + 1..self.pos + 1 + taps];
+        self.pos += 1;
+        if self.pos >= taps {
+            self.pos = 0;
+        }
+
+        let mut acc = f32x4::ZERO;
+        for (x, h) in window.chunks_exact(4).zip(self.kernel.chunks_exact(4)) {
+            acc += f32x4::new([x[0], x[1], x[2], x[3]]) * f32x4::new([h[0], h[1], h[2], h[3]]);
+        }
+        acc.reduce_add()
+    }
+}
+
+impl FrameProcessor<Mono> for Fir {
+    fn process(&mut self, buffer: &mut [f32], _sample_index: u64) {
+        for sample in buffer.iter_mut() {
+            *sample = self.tick(*sample);
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        if sample_rate != self.sample_rate {
+            self.sample_rate = sample_rate;
+            if let Some((band, taps, window)) = self.design {
+                self.set_kernel(&design(band, taps, window, sample_rate));
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.history.fill(0.0);
+        self.pos = 0;
+    }
+
+    fn latency_samples(&self) -> u32 {
+        self.latency
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "Fir"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(fir: &mut Fir, freq: f32) -> f32 {
+        fir.reset();
+        let mut buffer: Vec<f32> = (0..4410)
+            .map(|i| libm::sinf(2.0 * PI * freq * i as f32 / 44100.0))
+            .collect();
+        fir.process(&mut buffer, 0);
+        buffer[1000..].iter().fold(0.0f32, |p, s| p.max(s.abs()))
+    }
+
+    #[test]
+    fn test_windowed_sinc_designs() {
+        let mut lowpass = Fir::designed(FirBand::LowPass(1000.0), 255, FirWindow::Blackman);
+        assert_eq!(lowpass.latency_samples(), 127);
+        assert!((level(&mut lowpass, 200.0) - 1.0).abs() < 0.01);
+        assert!(level(&mut lowpass, 3000.0) < 0.001);
+
+        let mut highpass = Fir::designed(FirBand::HighPass(1000.0), 255, FirWindow::Hann);
+        assert!(level(&mut highpass, 200.0) < 0.01);
+        assert!((level(&mut highpass, 5000.0) - 1.0).abs() < 0.01);
+
+        let mut bandpass = Fir::designed(FirBand::BandPass(800.0, 2000.0), 255, FirWindow::Hamming);
+        assert!(level(&mut bandpass, 100.0) < 0.01);
+        assert!((level(&mut bandpass, 1400.0) - 1.0).abs() < 0.02);
+        assert!(level(&mut bandpass, 6000.0) < 0.01);
+
+        // The impulse response is the kernel, delayed by the group delay.
+        let kernel = design(FirBand::LowPass(1000.0), 255, FirWindow::Blackman, 44100.0);
+        let mut impulse = alloc::vec![0.0; 255];
+        impulse[0] = 1.0;
+        lowpass.reset();
+        lowpass.process(&mut impulse, 0);
+        assert_eq!(impulse, kernel);
+    }
+}
@@ -0,0 +1,460 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
+use crate::effects::filter::biquad::{BiquadCoeffs, BiquadState, FilterType};
+use crate::FrameProcessor;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+
+/// The highest filter order an [`IirDesign`] supports.
+pub const MAX_IIR_ORDER: usize = 12;
+
+/// The analog prototype of an [`IirDesign`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IirPrototype {
+    /// Maximally flat pass band.
+    Butterworth,
+    /// Maximally flat group delay: the gentlest slope, but no overshoot on transients.
+    Bessel,
+    /// Chebyshev type I with the given pass band ripple in dB: the steepest slope.
+    Chebyshev(f32),
+}
+
+/// The response shape of an [`IirDesign`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IirResponse {
+    LowPass,
+    HighPass,
+}
+
+/// One analog prototype section, normalized to a cutoff of 1 rad/s.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AnalogSection {
+    /// A real pole at `-omega`.
+    FirstOrder { omega: f64 },
+    /// A conjugate pole pair with natural frequency `omega` and quality `q`.
+    SecondOrder { omega: f64, q: f64 },
+}
+
+#[derive(Clone, Copy)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    fn add(self, o: Complex) -> Complex {
+        Complex::new(self.re + o.re, self.im + o.im)
+    }
+
+    fn sub(self, o: Complex) -> Complex {
+        Complex::new(self.re - o.re, self.im - o.im)
+    }
+
+    fn mul(self, o: Complex) -> Complex {
+        Complex::new(
+            self.re * o.re - self.im * o.im,
+            self.re * o.im + self.im * o.re,
+        )
+    }
+
+    fn div(self, o: Complex) -> Complex {
+        let d = o.re * o.re + o.im * o.im;
+        Complex::new(
+            (self.re * o.re + self.im * o.im) / d,
+            (self.im * o.re - self.re * o.im) / d,
+        )
+    }
+
+    fn norm_sqr(self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+}
+
+/// A filter designed from a classic analog prototype, as a cascade of biquads.
+///
+/// The analog poles are computed once; [`coefficients`](Self::coefficients) maps them to
+/// the digital domain with the bilinear transform, prewarped so the cutoff lands exactly.
+/// Butterworth and Bessel designs are -3 dB at the cutoff; Chebyshev designs leave the
+/// ripple band at the cutoff and peak at 0 dB in the pass band.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IirDesign {
+    sections: Vec<AnalogSection>,
+    gain: f64,
+}
+
+impl IirDesign {
+    /// Creates a new IirDesign.
+    ///
+    /// # Arguments
+    /// * `prototype` - The analog prototype.
+    /// * `order` - The filter order (1 to `MAX_IIR_ORDER`); the slope is 6 dB/octave per order.
+    pub fn new(prototype: IirPrototype, order: usize) -> Self {
+        let order = order.clamp(1, MAX_IIR_ORDER);
+        let (poles, gain) = match prototype {
+            IirPrototype::Butterworth => (Self::butterworth_poles(order), 1.0),
+            IirPrototype::Bessel => (Self::bessel_poles(order), 1.0),
+            IirPrototype::Chebyshev(ripple_db) => Self::chebyshev_poles(order, ripple_db),
+        };
+
+        let sections = poles
+            .into_iter()
+            .filter(|p| p.im >= -1e-9)
+            .map(|p| {
+                if p.im.abs() < 1e-9 {
+                    AnalogSection::FirstOrder { omega: -p.re }
+                } else {
+                    let omega = libm::sqrt(p.norm_sqr());
+                    AnalogSection::SecondOrder {
+                        omega,
+                        q: omega / (-2.0 * p.re),
+                    }
+                }
+            })
+            .collect();
+        IirDesign { sections, gain }
+    }
+
+    /// Returns the number of biquad stages of the cascade.
+    pub fn stages(&self) -> usize {
+        self.sections.len()
+    }
+
+    /// Computes the biquad cascade for a cutoff frequency.
+    ///
+    /// # Arguments
+    /// * `response` - Low-pass or high-pass.
+    /// * `cutoff` - Cutoff frequency in Hz.
+    /// * `sample_rate` - Sample rate in Hz.
+    pub fn coefficients(
+        &self,
+        response: IirResponse,
+        cutoff: f32,
+        sample_rate: f32,
+    ) -> Vec<BiquadCoeffs> {
+        let mut coeffs = vec![BiquadCoeffs::IDENTITY; self.stages()];
+        self.write_coefficients(response, cutoff, sample_rate, &mut coeffs);
+        coeffs
+    }
+
+    /// Like [`coefficients`](Self::coefficients), but writes into `out` without allocating.
+    /// `out` must hold [`stages`](Self::stages) coefficients.
+    pub fn write_coefficients(
+        &self,
+        response: IirResponse,
+        cutoff: f32,
+        sample_rate: f32,
+        out: &mut [BiquadCoeffs],
+    ) {
+        let fs = sample_rate as f64;
+        let cutoff = (cutoff as f64).clamp(1.0, fs * 0.49);
+        let k = libm::tan(PI * cutoff / fs);
+
+        for (c, section) in out.iter_mut().zip(self.sections.iter()) {
+            *c = match *section {
+                AnalogSection::FirstOrder { omega } => {
+                    let w = match response {
+                        IirResponse::LowPass => omega * k,
+                        IirResponse::HighPass => k / omega,
+                    };
+                    let a1 = ((w - 1.0) / (w + 1.0)) as f32;
+                    let (b0, b1) = match response {
+                        IirResponse::LowPass => (w / (1.0 + w), w / (1.0 + w)),
+                        IirResponse::HighPass => (1.0 / (1.0 + w), -1.0 / (1.0 + w)),
+                    };
+                    BiquadCoeffs {
+                        b0: b0 as f32,
+                        b1: b1 as f32,
+                        b2: 0.0,
+                        a1,
+                        a2: 0.0,
+                    }
+                }
+                AnalogSection::SecondOrder { omega, q } => {
+                    let (filter_type, w) = match response {
+                        IirResponse::LowPass => (FilterType::LowPass, omega * k),
+                        IirResponse::HighPass => (FilterType::HighPass, k / omega),
+                    };
+                    // The RBJ forms prewarp at their own frequency, so place it where the
+                    // bilinear transform maps the analog section frequency.
+                    let freq = (fs / PI * libm::atan(w)).min(fs * 0.499);
+                    BiquadCoeffs::new(filter_type, freq as f32, q as f32, 0.0, sample_rate)
+                }
+            };
+        }
+
+        if let Some(first) = out.first_mut() {
+            let gain = self.gain as f32;
+            first.b0 *= gain;
+            first.b1 *= gain;
+            first.b2 *= gain;
+        }
+    }
+
+    fn butterworth_poles(order: usize) -> Vec<Complex> {
+        (0..order)
+            .map(|k| {
+                let theta = PI * (2 * k + order + 1) as f64 / (2 * order) as f64;
+                Complex::new(libm::cos(theta), libm::sin(theta))
+            })
+            .collect()
+    }
+
+    fn chebyshev_poles(order: usize, ripple_db: f32) -> (Vec<Complex>, f64) {
+        let ripple_db = (ripple_db as f64).clamp(0.01, 6.0);
+        let epsilon = libm::sqrt(libm::pow(10.0, ripple_db / 10.0) - 1.0);
+        let v = libm::asinh(1.0 / epsilon) / order as f64;
+        let poles = (0..order)
+            .map(|k| {
+                let theta = PI * (2 * k + 1) as f64 / (2 * order) as f64;
+                Complex::new(
+                    -libm::sinh(v) * libm::sin(theta),
+                    libm::cosh(v) * libm::cos(theta),
+                )
+            })
+            .collect();
+        // Even orders start the pass band at the bottom of the ripple.
+        let gain = if order.is_multiple_of(2) {
+            1.0 / libm::sqrt(1.0 + epsilon * epsilon)
+        } else {
+            1.0
+        };
+        (poles, gain)
+    }
+
+    /// Finds the roots of the reverse Bessel polynomial, scaled to -3 dB at 1 rad/s.
+    fn bessel_poles(order: usize) -> Vec<Complex> {
+        // theta_n(s) = sum (2n - k)! / (2^(n - k) k! (n - k)!) s^k
+        let factorial = |n: usize| (1..=n).fold(1.0f64, |acc, i| acc * i as f64);
+        let coeffs: Vec<f64> = (0..=order)
+            .map(|k| {
+                factorial(2 * order - k)
+                    / (libm::pow(2.0, (order - k) as f64) * factorial(k) * factorial(order - k))
+            })
+            .collect();
+        let eval = |s: Complex| {
+            coeffs.iter().rev().fold(Complex::new(0.0, 0.0), |acc, &c| {
+                acc.mul(s).add(Complex::new(c, 0.0))
+            })
+        };
+
+        // Durand-Kerner iteration; the polynomial is monic.
+        let seed = Complex::new(0.4, 0.9);
+        let mut roots: Vec<Complex> = (0..order)
+            .scan(Complex::new(1.0, 0.0), |z, _| {
+                *z = z.mul(seed);
+                Some(*z)
+            })
+            .collect();
+        for _ in 0..500 {
+            for i in 0..order {
+                let mut denom = Complex::new(1.0, 0.0);
+                for (j, &other) in roots.iter().enumerate() {
+                    if j != i {
+                        denom = denom.mul(roots[i].sub(other));
+                    }
+                }
+                roots[i] = roots[i].sub(eval(roots[i]).div(denom));
+            }
+        }
+
+        // |H(jw)|^2 falls monotonically; bisect for the -3 dB point.
+        let magnitude_sqr = |w: f64| coeffs[0] * coeffs[0] / eval(Complex::new(0.0, w)).norm_sqr();
+        let (mut lo, mut hi) = (0.01, 100.0);
+        for _ in 0..100 {
+            let mid = 0.5 * (lo + hi);
+            if magnitude_sqr(mid) > 0.5 {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let scale = 1.0 / lo;
+        roots
+            .into_iter()
+            .map(|p| Complex::new(p.re * scale, p.im * scale))
+            .collect()
+    }
+}
+
+/// A steep low- or high-pass filter built from an [`IirDesign`].
+///
+/// Runs the biquad cascade in the transposed direct form II. The cutoff is read once per
+/// block and the coefficients are only recomputed when it changes.
+pub struct IirFilter {
+    design: IirDesign,
+    response: IirResponse,
+    cutoff: AudioParam,
+    sample_rate: f32,
+    last_cutoff: f32,
+
+    coeffs: Vec<BiquadCoeffs>,
+    state: Vec<BiquadState>,
+    param_buffer: Vec<f32>,
+}
+
+impl IirFilter {
+    /// Creates a new IirFilter.
+    ///
+    /// # Arguments
+    /// * `prototype` - The analog prototype.
+    /// * `response` - Low-pass or high-pass.
+    /// * `order` - The filter order (1 to `MAX_IIR_ORDER`).
+    /// * `cutoff` - Cutoff frequency in Hz.
+    pub fn new(
+        prototype: IirPrototype,
+        response: IirResponse,
+        order: usize,
+        cutoff: AudioParam,
+    ) -> Self {
+        let design = IirDesign::new(prototype, order);
+        let stages = design.stages();
+        IirFilter {
+            design,
+            response,
+            cutoff,
+            sample_rate: 44100.0,
+            last_cutoff: f32::NAN,
+            coeffs: vec![BiquadCoeffs::IDENTITY; stages],
+            state: vec![BiquadState::default(); stages],
+            param_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the cutoff parameter.
+    pub fn set_cutoff(&mut self, cutoff: AudioParam) {
+        self.cutoff = cutoff;
+    }
+
+    /// Returns the coefficients of the cascade currently in use.
+    pub fn coefficients(&self) -> &[BiquadCoeffs] {
+        &self.coeffs
+    }
+}
+
+impl FrameProcessor<Mono> for IirFilter {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        if len == 0 {
+            return;
+        }
+        if self.param_buffer.len() < len {
+            self.param_buffer.resize(len, 0.0);
+        }
+        let cutoff = self
+            .cutoff
+            .block_value(&mut self.param_buffer[0..len], sample_index);
+        if cutoff.to_bits() != self.last_cutoff.to_bits() {
+            self.last_cutoff = cutoff;
+            self.design.write_coefficients(
+                self.response,
+                cutoff,
+                self.sample_rate,
+                &mut self.coeffs,
+            );
+        }
+
+        for sample in buffer.iter_mut() {
+            let mut x = *sample;
+            for (c, state) in self.coeffs.iter().zip(self.state.iter_mut()) {
+                x = state.tick(c, x);
+            }
+            *sample = x;
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.last_cutoff = f32::NAN;
+        self.cutoff.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.param_buffer.resize(max_block_size, 0.0);
+        self.cutoff.prepare(max_block_size);
+    }
+
+    fn reset(&mut self) {
+        self.state.fill(BiquadState::default());
+        self.cutoff.reset();
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("cutoff", &self.cutoff, 20.0, 20000.0);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param(
+            "IirFilter",
+            "cutoff",
+            &self.cutoff,
+            1.0,
+            0.49 * self.sample_rate,
+        );
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        match self.response {
+            IirResponse::LowPass => "IirFilter (LowPass)",
+            IirResponse::HighPass => "IirFilter (HighPass)",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(prototype: IirPrototype, response: IirResponse, order: usize, freq: f32) -> f32 {
+        let mut filter = IirFilter::new(prototype, response, order, AudioParam::hz(1000.0));
+        let mut buffer: Vec<f32> = (0..22050)
+            .map(|i| libm::sinf(2.0 * core::f32::consts::PI * freq * i as f32 / 44100.0))
+            .collect();
+        filter.process(&mut buffer, 0);
+        buffer[11025..].iter().fold(0.0f32, |p, s| p.max(s.abs()))
+    }
+
+    #[test]
+    fn test_prototype_responses() {
+        use IirPrototype::*;
+        use IirResponse::*;
+
+        for order in [1, 2, 3, 4, 5, 8] {
+            for prototype in [Butterworth, Bessel] {
+                let edge = level(prototype, LowPass, order, 1000.0);
+                assert!(
+                    (edge - core::f32::consts::FRAC_1_SQRT_2).abs() < 0.01,
+                    "{:?} {} {}",
+                    prototype,
+                    order,
+                    edge
+                );
+                let edge = level(prototype, HighPass, order, 1000.0);
+                assert!(
+                    (edge - core::f32::consts::FRAC_1_SQRT_2).abs() < 0.01,
+                    "{:?} {} {}",
+                    prototype,
+                    order,
+                    edge
+                );
+            }
+            let edge = level(Chebyshev(1.0), LowPass, order, 1000.0);
+            assert!((edge - 0.8913).abs() < 0.01, "{} {}", order, edge);
+        }
+
+        // 24 dB/octave Butterworth, and far steeper Chebyshev of the same order.
+        assert!((level(Butterworth, LowPass, 4, 100.0) - 1.0).abs() < 0.01);
+        assert!(level(Butterworth, LowPass, 4, 2000.0) < 0.07);
+        assert!(level(Chebyshev(1.0), LowPass, 4, 2000.0) < 0.02);
+        assert!(level(Chebyshev(1.0), LowPass, 4, 500.0) > 0.89);
+        assert!(level(Butterworth, HighPass, 4, 500.0) < 0.07);
+        assert_eq!(IirDesign::new(Bessel, 5).stages(), 3);
+    }
+}
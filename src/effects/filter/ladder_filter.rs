@@ -1,8 +1,11 @@
 use crate::core::audio_param::AudioParam;
-use crate::core::channels::Mono;
+use crate::core::channels::{Mono, Stereo};
+use crate::core::denormal::{flush_denormal, flush_denormal4};
+use crate::core::preset::ParameterTree;
 use crate::FrameProcessor;
 use alloc::vec::Vec;
 use core::f32::consts::PI;
+use wide::f32x4;
 
 struct LadderCoeffs {
     g: f32,
@@ -12,20 +15,33 @@ struct LadderCoeffs {
     g3: f32,
     g4: f32,
     beta: f32,
+    input_gain: f32,
 }
 
 /// A 4-pole lowpass ladder filter using Newton-Raphson ZDF.
 ///
 /// This implementation uses an iterative solver to handle the non-linear feedback loop,
 /// providing high accuracy and stability even at high resonance.
+///
+/// Also processes Stereo buffers: both channels share the cutoff and resonance, so the
+/// coefficients are computed once per sample (or block) and the two ladders run side by
+/// side in one SIMD vector.
+///
+/// An optional drive stage soft-clips the input before the ladder, and resonance
+/// compensation restores the passband level the ladder loses as resonance increases.
 pub struct LadderFilter {
     cutoff: AudioParam,
     resonance: AudioParam,
+    drive: AudioParam,
+    compensation: bool,
     sample_rate: f32,
     s: [f32; 4],
+    // Stereo state: lane 0 is the left channel, lane 1 the right one.
+    s_stereo: [f32x4; 4],
 
     cutoff_buffer: Vec<f32>,
     res_buffer: Vec<f32>,
+    drive_buffer: Vec<f32>,
 }
 
 impl LadderFilter {
@@ -38,15 +54,45 @@ impl LadderFilter {
         LadderFilter {
             cutoff,
             resonance,
+            drive: AudioParam::Static(0.0),
+            compensation: false,
             sample_rate: 44100.0,
             s: [0.0; 4],
+            s_stereo: [f32x4::ZERO; 4],
             cutoff_buffer: Vec::with_capacity(128),
             res_buffer: Vec::with_capacity(128),
+            drive_buffer: Vec::with_capacity(128),
         }
     }
 
+    /// Sets the input drive (0.0 = clean).
+    ///
+    /// The input is amplified by `1 + drive` and soft-clipped before it enters the ladder.
+    pub fn set_drive(&mut self, drive: AudioParam) {
+        self.drive = drive;
+    }
+
+    /// Enables resonance compensation.
+    ///
+    /// The ladder's passband gain drops to `1 / (1 + 4 * resonance)`; with compensation the
+    /// input is boosted by the inverse, so high resonance settings keep their low end.
+    pub fn set_compensation(&mut self, enabled: bool) {
+        self.compensation = enabled;
+    }
+
+    /// Enables or disables resonance compensation.
+    pub fn with_compensation(mut self, enabled: bool) -> Self {
+        self.compensation = enabled;
+        self
+    }
+
     #[inline(always)]
-    fn calc_coeffs(cutoff_val: f32, res_val: f32, sample_rate: f32) -> LadderCoeffs {
+    fn calc_coeffs(
+        cutoff_val: f32,
+        res_val: f32,
+        sample_rate: f32,
+        compensation: bool,
+    ) -> LadderCoeffs {
         let fc = cutoff_val.clamp(10.0, sample_rate * 0.49);
         let g = libm::tanf(PI * fc / sample_rate);
         let k = res_val * 4.0;
@@ -66,12 +112,13 @@ impl LadderFilter {
             g3,
             g4,
             beta,
+            input_gain: if compensation { 1.0 + k } else { 1.0 },
         }
     }
 
     #[inline(always)]
     fn step(s: &mut [f32; 4], sample: &mut f32, coeffs: &LadderCoeffs) {
-        let x = *sample;
+        let x = *sample * coeffs.input_gain;
         let c = coeffs;
 
         let s1_term = s[0] * c.beta;
@@ -100,35 +147,85 @@ impl LadderFilter {
         let y2 = (c.g * y1 + s[1]) * c.beta;
         let y3 = (c.g * y2 + s[2]) * c.beta;
 
-        s[0] = 2.0 * y1 - s[0];
-        s[1] = 2.0 * y2 - s[1];
-        s[2] = 2.0 * y3 - s[2];
-        s[3] = 2.0 * y4 - s[3];
+        s[0] = flush_denormal(2.0 * y1 - s[0]);
+        s[1] = flush_denormal(2.0 * y2 - s[1]);
+        s[2] = flush_denormal(2.0 * y3 - s[2]);
+        s[3] = flush_denormal(2.0 * y4 - s[3]);
 
         *sample = y4;
     }
-}
 
-impl FrameProcessor<Mono> for LadderFilter {
-    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
-        let len = buffer.len();
+    #[inline(always)]
+    fn tanh_stereo(x: f32x4) -> f32x4 {
+        let [l, r, _, _] = x.to_array();
+        f32x4::from([libm::tanhf(l), libm::tanhf(r), 0.0, 0.0])
+    }
+
+    /// Same as `step`, for the left and right channel in lanes 0 and 1.
+    #[inline(always)]
+    fn step_stereo(s: &mut [f32x4; 4], frame: &mut [f32], coeffs: &LadderCoeffs) {
+        let x = f32x4::from([frame[0], frame[1], 0.0, 0.0]) * f32x4::splat(coeffs.input_gain);
+        let g = f32x4::splat(coeffs.g);
+        let k = f32x4::splat(coeffs.k);
+        let g4 = f32x4::splat(coeffs.g4);
+        let beta = f32x4::splat(coeffs.beta);
+        let one = f32x4::ONE;
+        let two = f32x4::splat(2.0);
+
+        let sigma = f32x4::splat(coeffs.g3) * (s[0] * beta)
+            + f32x4::splat(coeffs.g2) * (s[1] * beta)
+            + f32x4::splat(coeffs.g1) * (s[2] * beta)
+            + s[3] * beta;
+
+        let mut y4 = s[3];
+
+        for _ in 0..5 {
+            let tanh_y4 = Self::tanh_stereo(y4);
+            let u = x - k * tanh_y4;
+
+            let f_y = y4 - (g4 * u + sigma);
+            let df_y = one + g4 * k * (one - tanh_y4 * tanh_y4);
+
+            y4 -= f_y / df_y;
+        }
+
+        let tanh_y4 = Self::tanh_stereo(y4);
+        let u = x - k * tanh_y4;
+
+        let y1 = (g * u + s[0]) * beta;
+        let y2 = (g * y1 + s[1]) * beta;
+        let y3 = (g * y2 + s[2]) * beta;
 
-        let cutoff_is_dynamic = matches!(self.cutoff, AudioParam::Dynamic(_));
-        let res_is_dynamic = matches!(self.resonance, AudioParam::Dynamic(_));
+        s[0] = flush_denormal4(two * y1 - s[0]);
+        s[1] = flush_denormal4(two * y2 - s[1]);
+        s[2] = flush_denormal4(two * y3 - s[2]);
+        s[3] = flush_denormal4(two * y4 - s[3]);
+
+        let out = y4.to_array();
+        frame[0] = out[0];
+        frame[1] = out[1];
+    }
+
+    /// Fills the parameter buffers for `frames` frames.
+    ///
+    /// Returns whether cutoff and resonance are dynamic, and their values if they are not.
+    fn update_params(&mut self, frames: usize, sample_index: u64) -> (bool, bool, f32, f32) {
+        let cutoff_is_dynamic = self.cutoff.get_constant().is_none();
+        let res_is_dynamic = self.resonance.get_constant().is_none();
 
         if cutoff_is_dynamic {
-            if self.cutoff_buffer.len() < len {
-                self.cutoff_buffer.resize(len, 0.0);
+            if self.cutoff_buffer.len() < frames {
+                self.cutoff_buffer.resize(frames, 0.0);
             }
             self.cutoff
-                .process(&mut self.cutoff_buffer[0..len], sample_index);
+                .process(&mut self.cutoff_buffer[0..frames], sample_index);
         }
         if res_is_dynamic {
-            if self.res_buffer.len() < len {
-                self.res_buffer.resize(len, 0.0);
+            if self.res_buffer.len() < frames {
+                self.res_buffer.resize(frames, 0.0);
             }
             self.resonance
-                .process(&mut self.res_buffer[0..len], sample_index);
+                .process(&mut self.res_buffer[0..frames], sample_index);
         }
 
         let cutoff_static = match &self.cutoff {
@@ -142,11 +239,51 @@ impl FrameProcessor<Mono> for LadderFilter {
             _ => 0.0,
         };
 
+        (cutoff_is_dynamic, res_is_dynamic, cutoff_static, res_static)
+    }
+
+    /// Soft-clips the input (interleaved, `channels` channels) according to the drive.
+    fn apply_drive(&mut self, buffer: &mut [f32], channels: usize, sample_index: u64) {
+        if let Some(drive) = self.drive.get_constant() {
+            if drive > 0.0 {
+                let gain = 1.0 + drive;
+                for sample in buffer.iter_mut() {
+                    *sample = libm::tanhf(*sample * gain);
+                }
+            }
+            return;
+        }
+
+        let frames = buffer.len() / channels;
+        if self.drive_buffer.len() < frames {
+            self.drive_buffer.resize(frames, 0.0);
+        }
+        self.drive
+            .process(&mut self.drive_buffer[0..frames], sample_index);
+
+        for (frame, &drive) in buffer.chunks_exact_mut(channels).zip(&self.drive_buffer) {
+            if drive > 0.0 {
+                let gain = 1.0 + drive;
+                for sample in frame.iter_mut() {
+                    *sample = libm::tanhf(*sample * gain);
+                }
+            }
+        }
+    }
+}
+
+impl FrameProcessor<Mono> for LadderFilter {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let (cutoff_is_dynamic, res_is_dynamic, cutoff_static, res_static) =
+            self.update_params(buffer.len(), sample_index);
+        self.apply_drive(buffer, 1, sample_index);
+
         let s = &mut self.s;
         let sample_rate = self.sample_rate;
+        let compensation = self.compensation;
 
         if !cutoff_is_dynamic && !res_is_dynamic {
-            let coeffs = Self::calc_coeffs(cutoff_static, res_static, sample_rate);
+            let coeffs = Self::calc_coeffs(cutoff_static, res_static, sample_rate, compensation);
             for sample in buffer.iter_mut() {
                 Self::step(s, sample, &coeffs);
             }
@@ -163,7 +300,7 @@ impl FrameProcessor<Mono> for LadderFilter {
                     res_static
                 };
 
-                let coeffs = Self::calc_coeffs(c, r, sample_rate);
+                let coeffs = Self::calc_coeffs(c, r, sample_rate, compensation);
                 Self::step(s, sample, &coeffs);
             }
         }
@@ -173,14 +310,175 @@ impl FrameProcessor<Mono> for LadderFilter {
         self.sample_rate = sample_rate;
         self.cutoff.set_sample_rate(sample_rate);
         self.resonance.set_sample_rate(sample_rate);
+        self.drive.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.cutoff.prepare(max_block_size);
+        self.resonance.prepare(max_block_size);
+        self.cutoff_buffer.resize(max_block_size, 0.0);
+        self.res_buffer.resize(max_block_size, 0.0);
+        self.drive.prepare(max_block_size);
+        self.drive_buffer.resize(max_block_size, 0.0);
     }
 
     fn reset(&mut self) {
         self.s = [0.0; 4];
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("cutoff", &self.cutoff, 20.0, 20000.0);
+        tree.add_audio_param("resonance", &self.resonance, 0.0, 1.0);
+        tree.add_audio_param("drive", &self.drive, 0.0, 10.0);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "LadderFilter (Moog)"
     }
 }
+
+impl FrameProcessor<Stereo> for LadderFilter {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let (cutoff_is_dynamic, res_is_dynamic, cutoff_static, res_static) =
+            self.update_params(buffer.len() / 2, sample_index);
+        self.apply_drive(buffer, 2, sample_index);
+
+        let s = &mut self.s_stereo;
+        let sample_rate = self.sample_rate;
+        let compensation = self.compensation;
+
+        if !cutoff_is_dynamic && !res_is_dynamic {
+            let coeffs = Self::calc_coeffs(cutoff_static, res_static, sample_rate, compensation);
+            for frame in buffer.chunks_exact_mut(2) {
+                Self::step_stereo(s, frame, &coeffs);
+            }
+        } else {
+            for (i, frame) in buffer.chunks_exact_mut(2).enumerate() {
+                let c = if cutoff_is_dynamic {
+                    self.cutoff_buffer[i]
+                } else {
+                    cutoff_static
+                };
+                let r = if res_is_dynamic {
+                    self.res_buffer[i]
+                } else {
+                    res_static
+                };
+
+                let coeffs = Self::calc_coeffs(c, r, sample_rate, compensation);
+                Self::step_stereo(s, frame, &coeffs);
+            }
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.cutoff.set_sample_rate(sample_rate);
+        self.resonance.set_sample_rate(sample_rate);
+        self.drive.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.cutoff.prepare(max_block_size);
+        self.resonance.prepare(max_block_size);
+        self.cutoff_buffer.resize(max_block_size, 0.0);
+        self.res_buffer.resize(max_block_size, 0.0);
+        self.drive.prepare(max_block_size);
+        self.drive_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.s_stereo = [f32x4::ZERO; 4];
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("cutoff", &self.cutoff, 20.0, 20000.0);
+        tree.add_audio_param("resonance", &self.resonance, 0.0, 1.0);
+        tree.add_audio_param("drive", &self.drive, 0.0, 10.0);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "LadderFilter (Moog, Stereo)"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synthesis::lfo::{Lfo, LfoWaveform};
+    use alloc::boxed::Box;
+    use alloc::vec;
+
+    fn cutoff_sweep() -> AudioParam {
+        let mut lfo = Lfo::new(AudioParam::Static(30.0), LfoWaveform::Sine);
+        lfo.set_range(200.0, 5000.0);
+        AudioParam::Dynamic(Box::new(lfo))
+    }
+
+    #[test]
+    fn test_stereo_matches_dual_mono() {
+        let mut stereo = LadderFilter::new(cutoff_sweep(), AudioParam::Static(0.8));
+        let mut left = LadderFilter::new(cutoff_sweep(), AudioParam::Static(0.8));
+        let mut right = LadderFilter::new(cutoff_sweep(), AudioParam::Static(0.8));
+
+        let input_l: Vec<f32> = (0..256).map(|i| libm::sinf(i as f32 * 0.3)).collect();
+        let input_r: Vec<f32> = (0..256)
+            .map(|i| if i % 32 < 16 { 0.8 } else { -0.8 })
+            .collect();
+
+        let mut interleaved = vec![0.0; 512];
+        for i in 0..256 {
+            interleaved[2 * i] = input_l[i];
+            interleaved[2 * i + 1] = input_r[i];
+        }
+        let mut out_l = input_l.clone();
+        let mut out_r = input_r.clone();
+
+        for block in 0..4 {
+            let frames = block * 64..(block + 1) * 64;
+            let index = frames.start as u64;
+            FrameProcessor::<Stereo>::process(
+                &mut stereo,
+                &mut interleaved[frames.start * 2..frames.end * 2],
+                index,
+            );
+            FrameProcessor::<Mono>::process(&mut left, &mut out_l[frames.clone()], index);
+            FrameProcessor::<Mono>::process(&mut right, &mut out_r[frames], index);
+        }
+
+        for i in 0..256 {
+            assert!((interleaved[2 * i] - out_l[i]).abs() < 1e-5);
+            assert!((interleaved[2 * i + 1] - out_r[i]).abs() < 1e-5);
+        }
+        assert!(out_r.iter().any(|s| s.abs() > 0.1));
+    }
+
+    fn settle(filter: &mut dyn FrameProcessor<Mono>, input: f32) -> f32 {
+        let mut buffer = vec![input; 4096];
+        filter.process(&mut buffer, 0);
+        buffer[4095]
+    }
+
+    #[test]
+    fn test_compensation_and_drive() {
+        use crate::effects::filter::predictive_ladder::PredictiveLadderFilter;
+
+        let cutoff = || AudioParam::hz(1000.0);
+        let mut plain = LadderFilter::new(cutoff(), AudioParam::Static(0.8));
+        let mut compensated =
+            LadderFilter::new(cutoff(), AudioParam::Static(0.8)).with_compensation(true);
+        let mut predictive =
+            PredictiveLadderFilter::new(cutoff(), AudioParam::Static(0.8)).with_compensation(true);
+
+        // The passband gain of the ladder is 1 / (1 + 4 * resonance).
+        assert!((settle(&mut plain, 0.1) - 0.1 / 4.2).abs() < 1e-3);
+        assert!((settle(&mut compensated, 0.1) - 0.1).abs() < 1e-3);
+        assert!((settle(&mut predictive, 0.1) - 0.1).abs() < 2e-3);
+
+        let mut driven = LadderFilter::new(cutoff(), AudioParam::Static(0.0));
+        driven.set_drive(AudioParam::Static(9.0));
+        assert!((settle(&mut driven, 0.5) - libm::tanhf(5.0)).abs() < 1e-3);
+    }
+}
@@ -1,5 +1,13 @@
+pub mod band_limited;
 pub mod biquad;
+pub mod comb;
+pub mod crossover;
+pub mod fir;
+pub mod iir_design;
 pub mod ladder_filter;
+pub mod nonlinear_svf;
 pub mod predictive_ladder;
+pub mod resonator;
 pub mod state_variable;
+pub mod tilt;
 pub mod vowel;
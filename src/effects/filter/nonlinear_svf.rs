@@ -0,0 +1,268 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::effects::filter::state_variable::{prewarp_tan, SvfType};
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+
+/// The slope of a [`NonlinearSvf`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SvfSlope {
+    /// A single resonant stage (12 dB/oct).
+    TwoPole,
+    /// A clean stage followed by the resonant stage (24 dB/oct).
+    FourPole,
+}
+
+/// A State Variable Filter with saturation in its resonance loop (MS-20 style).
+///
+/// The resonance is positive feedback of the band-pass state through a `tanh`, fighting
+/// the filter's fixed damping. At low levels it behaves like the linear
+/// [`StateVariableFilter`](crate::effects::filter::state_variable::StateVariableFilter);
+/// loud signals and high resonance push the feedback into saturation, which limits
+/// self-oscillation (resonance above 1.0) instead of letting it blow up and gives the
+/// aggressive, screaming character of diode/OTA state variable designs.
+///
+/// The saturating loop is solved with a one-step linearization of the `tanh` around the
+/// current state, so the filter stays zero-delay and stable.
+pub struct NonlinearSvf {
+    filter_type: SvfType,
+    slope: SvfSlope,
+    cutoff: AudioParam,
+    resonance: AudioParam,
+    drive: AudioParam,
+    character: AudioParam,
+    sample_rate: f32,
+    // [s1, s2] of the clean and the resonant stage.
+    s: [[f32; 2]; 2],
+
+    last_cutoff: f32,
+    g: f32,
+
+    cutoff_buffer: Vec<f32>,
+    res_buffer: Vec<f32>,
+    drive_buffer: Vec<f32>,
+    character_buffer: Vec<f32>,
+}
+
+impl NonlinearSvf {
+    /// Creates a new NonlinearSvf.
+    ///
+    /// # Arguments
+    /// * `filter_type` - The output type.
+    /// * `slope` - Two or four poles.
+    /// * `cutoff` - Cutoff frequency in Hz.
+    /// * `resonance` - Resonance (0.0 - 1.2). Self-oscillates above 1.0.
+    pub fn new(
+        filter_type: SvfType,
+        slope: SvfSlope,
+        cutoff: AudioParam,
+        resonance: AudioParam,
+    ) -> Self {
+        NonlinearSvf {
+            filter_type,
+            slope,
+            cutoff,
+            resonance,
+            drive: AudioParam::Static(0.0),
+            character: AudioParam::Static(0.5),
+            sample_rate: 44100.0,
+            s: [[0.0; 2]; 2],
+            last_cutoff: -1.0,
+            g: 0.0,
+            cutoff_buffer: Vec::with_capacity(128),
+            res_buffer: Vec::with_capacity(128),
+            drive_buffer: Vec::with_capacity(128),
+            character_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the filter type.
+    pub fn set_type(&mut self, filter_type: SvfType) {
+        self.filter_type = filter_type;
+    }
+
+    /// Sets the filter slope.
+    pub fn set_slope(&mut self, slope: SvfSlope) {
+        self.slope = slope;
+    }
+
+    /// Sets the cutoff frequency parameter.
+    pub fn set_cutoff(&mut self, cutoff: AudioParam) {
+        self.cutoff = cutoff;
+    }
+
+    /// Sets the resonance parameter (0.0 - 1.2).
+    pub fn set_resonance(&mut self, resonance: AudioParam) {
+        self.resonance = resonance;
+    }
+
+    /// Sets the input drive (0.0 = clean, higher values soft-clip the input harder).
+    pub fn set_drive(&mut self, drive: AudioParam) {
+        self.drive = drive;
+    }
+
+    /// Sets the character parameter (0.0 - 1.0).
+    ///
+    /// Higher values saturate the resonance loop at lower levels: a smaller, grittier
+    /// self-oscillation and more distortion on loud input.
+    pub fn set_character(&mut self, character: AudioParam) {
+        self.character = character;
+    }
+
+    #[inline(always)]
+    fn stage(s: &mut [f32; 2], input: f32, g: f32, r: f32, c: f32, filter_type: SvfType) -> f32 {
+        // Feedback of the band-pass output: fixed damping 2·bp minus the saturated
+        // resonance r·tanh(c·bp)/c, with tanh linearized around the current state.
+        let t = libm::tanhf(c * s[0]);
+        let sat = t / c;
+        let slope = 1.0 - t * t;
+
+        let hp = (input - (2.0 + g) * s[0] + r * sat - s[1]) / (1.0 + g * (2.0 + g - r * slope));
+        let bp = g * hp + s[0];
+        let lp = g * bp + s[1];
+
+        s[0] = bp + g * hp;
+        s[1] = lp + g * bp;
+
+        match filter_type {
+            SvfType::LowPass => lp,
+            SvfType::HighPass => hp,
+            SvfType::BandPass => bp,
+            SvfType::Notch => hp + lp,
+            SvfType::Peak => lp - hp,
+        }
+    }
+}
+
+impl FrameProcessor<Mono> for NonlinearSvf {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        for buf in [
+            &mut self.cutoff_buffer,
+            &mut self.res_buffer,
+            &mut self.drive_buffer,
+            &mut self.character_buffer,
+        ] {
+            if buf.len() < len {
+                buf.resize(len, 0.0);
+            }
+        }
+
+        self.cutoff
+            .process(&mut self.cutoff_buffer[0..len], sample_index);
+        self.resonance
+            .process(&mut self.res_buffer[0..len], sample_index);
+        self.drive
+            .process(&mut self.drive_buffer[0..len], sample_index);
+        self.character
+            .process(&mut self.character_buffer[0..len], sample_index);
+
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let cutoff = self.cutoff_buffer[i];
+            if (cutoff - self.last_cutoff).abs() > 0.001 {
+                self.g = prewarp_tan(
+                    (PI / self.sample_rate) * cutoff.clamp(10.0, self.sample_rate * 0.49),
+                );
+                self.last_cutoff = cutoff;
+            }
+
+            let r = 2.0 * self.res_buffer[i].clamp(0.0, 1.2);
+            let c = 0.5 + 4.5 * self.character_buffer[i].clamp(0.0, 1.0);
+
+            let mut input = *sample;
+            let drive = self.drive_buffer[i];
+            if drive > 0.0 {
+                input = libm::tanhf(input * (1.0 + drive));
+            }
+
+            if self.slope == SvfSlope::FourPole {
+                input = Self::stage(&mut self.s[0], input, self.g, 0.0, c, self.filter_type);
+            }
+            *sample = Self::stage(&mut self.s[1], input, self.g, r, c, self.filter_type);
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.cutoff.set_sample_rate(sample_rate);
+        self.resonance.set_sample_rate(sample_rate);
+        self.drive.set_sample_rate(sample_rate);
+        self.character.set_sample_rate(sample_rate);
+        self.last_cutoff = -1.0;
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.cutoff.prepare(max_block_size);
+        self.resonance.prepare(max_block_size);
+        self.drive.prepare(max_block_size);
+        self.character.prepare(max_block_size);
+        self.cutoff_buffer.resize(max_block_size, 0.0);
+        self.res_buffer.resize(max_block_size, 0.0);
+        self.drive_buffer.resize(max_block_size, 0.0);
+        self.character_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.s = [[0.0; 2]; 2];
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        match self.slope {
+            SvfSlope::TwoPole => "NonlinearSvf (2-Pole)",
+            SvfSlope::FourPole => "NonlinearSvf (4-Pole)",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::filter::state_variable::StateVariableFilter;
+    use alloc::vec;
+
+    #[test]
+    fn test_small_signal_matches_linear_svf() {
+        // Resonance 0.5 leaves a net damping of 1.0, i.e. an SVF with Q = 1.
+        let mut nonlinear = NonlinearSvf::new(
+            SvfType::LowPass,
+            SvfSlope::TwoPole,
+            AudioParam::Static(2000.0),
+            AudioParam::Static(0.5),
+        );
+        let mut linear = StateVariableFilter::new(
+            SvfType::LowPass,
+            AudioParam::Static(2000.0),
+            AudioParam::Static(1.0),
+        );
+
+        let mut a = vec![0.0; 256];
+        a[0] = 1e-3;
+        let mut b = a.clone();
+        nonlinear.process(&mut a, 0);
+        linear.process(&mut b, 0);
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < 1e-7);
+        }
+    }
+
+    #[test]
+    fn test_self_oscillation_is_bounded() {
+        let mut filter = NonlinearSvf::new(
+            SvfType::BandPass,
+            SvfSlope::FourPole,
+            AudioParam::Static(500.0),
+            AudioParam::Static(1.2),
+        );
+        let mut buffer = vec![0.0; 44100];
+        buffer[0] = 0.01;
+        for block in buffer.chunks_mut(128) {
+            filter.process(block, 0);
+        }
+
+        let tail = &buffer[22050..];
+        let peak = tail.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(peak > 0.1 && peak < 10.0);
+    }
+}
@@ -9,13 +9,18 @@ use wide::f32x4;
 ///
 /// This implementation is significantly faster than the Newton-Raphson solver used in `LadderFilter`,
 /// while retaining comparable audio fidelity.
+///
+/// Offers the same drive stage and resonance compensation as `LadderFilter`.
 pub struct PredictiveLadderFilter {
     cutoff: AudioParam,
     resonance: AudioParam,
+    drive: AudioParam,
+    compensation: bool,
     sample_rate: f32,
     s: [f32; 4],
     cutoff_buffer: Vec<f32>,
     res_buffer: Vec<f32>,
+    drive_buffer: Vec<f32>,
 }
 
 impl PredictiveLadderFilter {
@@ -28,10 +33,57 @@ impl PredictiveLadderFilter {
         Self {
             cutoff,
             resonance,
+            drive: AudioParam::Static(0.0),
+            compensation: false,
             sample_rate: 44100.0,
             s: [0.0; 4],
             cutoff_buffer: Vec::with_capacity(128),
             res_buffer: Vec::with_capacity(128),
+            drive_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the input drive (0.0 = clean).
+    ///
+    /// The input is amplified by `1 + drive` and soft-clipped before it enters the ladder.
+    pub fn set_drive(&mut self, drive: AudioParam) {
+        self.drive = drive;
+    }
+
+    /// Enables resonance compensation, see `LadderFilter::set_compensation`.
+    pub fn set_compensation(&mut self, enabled: bool) {
+        self.compensation = enabled;
+    }
+
+    /// Enables or disables resonance compensation.
+    pub fn with_compensation(mut self, enabled: bool) -> Self {
+        self.compensation = enabled;
+        self
+    }
+
+    /// Soft-clips the input according to the drive.
+    fn apply_drive(&mut self, buffer: &mut [f32], sample_index: u64) {
+        if let Some(drive) = self.drive.get_constant() {
+            if drive > 0.0 {
+                let gain = 1.0 + drive;
+                for sample in buffer.iter_mut() {
+                    *sample = fast_tanh(*sample * gain);
+                }
+            }
+            return;
+        }
+
+        let len = buffer.len();
+        if self.drive_buffer.len() < len {
+            self.drive_buffer.resize(len, 0.0);
+        }
+        self.drive
+            .process(&mut self.drive_buffer[0..len], sample_index);
+
+        for (sample, &drive) in buffer.iter_mut().zip(&self.drive_buffer) {
+            if drive > 0.0 {
+                *sample = fast_tanh(*sample * (1.0 + drive));
+            }
         }
     }
 
@@ -56,8 +108,12 @@ impl PredictiveLadderFilter {
     }
 
     #[inline(always)]
-    fn step(s: &mut [f32; 4], sample: &mut f32, g: f32, k: f32, beta: f32) {
-        let x = *sample;
+    fn step(s: &mut [f32; 4], sample: &mut f32, g: f32, k: f32, beta: f32, compensation: bool) {
+        let x = if compensation {
+            *sample * (1.0 + k)
+        } else {
+            *sample
+        };
 
         let g_val = g * beta;
         let s0 = s[0] * beta;
@@ -110,9 +166,10 @@ fn fast_tanh(x: f32) -> f32 {
 impl FrameProcessor<Mono> for PredictiveLadderFilter {
     fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
         let len = buffer.len();
+        self.apply_drive(buffer, sample_index);
 
-        let cutoff_is_dynamic = matches!(self.cutoff, AudioParam::Dynamic(_));
-        let res_is_dynamic = matches!(self.resonance, AudioParam::Dynamic(_));
+        let cutoff_is_dynamic = self.cutoff.get_constant().is_none();
+        let res_is_dynamic = self.resonance.get_constant().is_none();
 
         if cutoff_is_dynamic {
             if self.cutoff_buffer.len() < len {
@@ -144,11 +201,12 @@ impl FrameProcessor<Mono> for PredictiveLadderFilter {
         let cutoff_buf = &self.cutoff_buffer;
         let res_buf = &self.res_buffer;
         let sample_rate = self.sample_rate;
+        let compensation = self.compensation;
 
         if !cutoff_is_dynamic && !res_is_dynamic {
             let (g, k, beta) = Self::calc_coeffs(cutoff_static, res_static, sample_rate);
             for sample in buffer.iter_mut() {
-                Self::step(s, sample, g, k, beta);
+                Self::step(s, sample, g, k, beta, compensation);
             }
         } else {
             let (chunks, remainder) = buffer.as_chunks_mut::<4>();
@@ -172,7 +230,14 @@ impl FrameProcessor<Mono> for PredictiveLadderFilter {
                 let (g_arr, k_arr, beta_arr) = Self::calc_coeffs_simd(c_vec, r_vec, sample_rate);
 
                 for j in 0..4 {
-                    Self::step(s, &mut chunk[j], g_arr[j], k_arr[j], beta_arr[j]);
+                    Self::step(
+                        s,
+                        &mut chunk[j],
+                        g_arr[j],
+                        k_arr[j],
+                        beta_arr[j],
+                        compensation,
+                    );
                 }
 
                 i += 4;
@@ -192,7 +257,7 @@ impl FrameProcessor<Mono> for PredictiveLadderFilter {
                 };
 
                 let (g, k, beta) = Self::calc_coeffs(c, r, sample_rate);
-                Self::step(s, sample, g, k, beta);
+                Self::step(s, sample, g, k, beta, compensation);
             }
         }
     }
@@ -201,6 +266,16 @@ impl FrameProcessor<Mono> for PredictiveLadderFilter {
         self.sample_rate = sample_rate;
         self.cutoff.set_sample_rate(sample_rate);
         self.resonance.set_sample_rate(sample_rate);
+        self.drive.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.cutoff.prepare(max_block_size);
+        self.resonance.prepare(max_block_size);
+        self.cutoff_buffer.resize(max_block_size, 0.0);
+        self.res_buffer.resize(max_block_size, 0.0);
+        self.drive.prepare(max_block_size);
+        self.drive_buffer.resize(max_block_size, 0.0);
     }
 
     fn reset(&mut self) {
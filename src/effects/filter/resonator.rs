@@ -0,0 +1,270 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::core::denormal::flush_denormal4;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+use wide::f32x4;
+
+/// One mode of a [`ResonatorBank`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Resonator {
+    /// Frequency in Hz, or a ratio of the pitch when the bank is keyboard-tracked.
+    pub frequency: f32,
+    /// Time for the ringing to decay by 60 dB, in seconds.
+    pub decay: f32,
+    /// Output gain at the resonance.
+    pub gain: f32,
+}
+
+impl Resonator {
+    /// Creates a new Resonator.
+    ///
+    /// # Arguments
+    /// * `frequency` - Frequency in Hz, or a pitch ratio when tracked.
+    /// * `decay` - T60 decay time in seconds.
+    /// * `gain` - Output gain at the resonance.
+    pub fn new(frequency: f32, decay: f32, gain: f32) -> Self {
+        Resonator {
+            frequency,
+            decay,
+            gain,
+        }
+    }
+}
+
+/// Four resonators: y = b * (x - x2) + a1 * y1 - a2 * y2.
+#[derive(Clone, Copy)]
+struct ResonatorGroup {
+    b: f32x4,
+    a1: f32x4,
+    a2: f32x4,
+    y1: f32x4,
+    y2: f32x4,
+}
+
+impl ResonatorGroup {
+    const ZERO: ResonatorGroup = ResonatorGroup {
+        b: f32x4::ZERO,
+        a1: f32x4::ZERO,
+        a2: f32x4::ZERO,
+        y1: f32x4::ZERO,
+        y2: f32x4::ZERO,
+    };
+}
+
+/// A bank of tuned two-pole resonators, run four at a time.
+///
+/// Each resonator is a band-pass with unity peak gain (scaled by its `gain`) and a ring
+/// time set by `decay`, so noise bursts or drums excite pitched, bell- or string-like
+/// modes. With [`with_pitch_tracking`](Self::with_pitch_tracking) the frequencies are
+/// read as ratios of a pitch parameter, and the bank follows the keyboard.
+pub struct ResonatorBank {
+    resonators: Vec<Resonator>,
+    pitch: Option<AudioParam>,
+    mix: AudioParam,
+    sample_rate: f32,
+    pitch_hz: f32,
+
+    groups: Vec<ResonatorGroup>,
+    x1: f32,
+    x2: f32,
+
+    param_buffer: Vec<f32>,
+    mix_buffer: Vec<f32>,
+}
+
+impl ResonatorBank {
+    /// Creates a new ResonatorBank with fixed frequencies and a fully wet output.
+    ///
+    /// # Arguments
+    /// * `resonators` - The modes of the bank.
+    pub fn new(resonators: &[Resonator]) -> Self {
+        let groups = resonators.len().div_ceil(4);
+        let mut bank = ResonatorBank {
+            resonators: resonators.to_vec(),
+            pitch: None,
+            mix: AudioParam::Static(1.0),
+            sample_rate: 44100.0,
+            pitch_hz: 1.0,
+            groups: alloc::vec![ResonatorGroup::ZERO; groups],
+            x1: 0.0,
+            x2: 0.0,
+            param_buffer: Vec::with_capacity(128),
+            mix_buffer: Vec::with_capacity(128),
+        };
+        bank.update_coeffs();
+        bank
+    }
+
+    /// Tracks a pitch parameter (in Hz): each resonator's `frequency` becomes a ratio of it.
+    pub fn with_pitch_tracking(mut self, pitch: AudioParam) -> Self {
+        self.pitch = Some(pitch);
+        self
+    }
+
+    /// Replaces one resonator; does nothing if `index` is out of range.
+    pub fn set_resonator(&mut self, index: usize, resonator: Resonator) {
+        if let Some(slot) = self.resonators.get_mut(index) {
+            *slot = resonator;
+            self.update_coeffs();
+        }
+    }
+
+    /// Returns the resonators of the bank.
+    pub fn resonators(&self) -> &[Resonator] {
+        &self.resonators
+    }
+
+    /// Sets the dry/wet mix (default 1.0).
+    pub fn set_mix(&mut self, mix: AudioParam) {
+        self.mix = mix;
+    }
+
+    fn update_coeffs(&mut self) {
+        let nyquist = self.sample_rate * 0.49;
+        for (group, chunk) in self.groups.iter_mut().zip(self.resonators.chunks(4)) {
+            let mut b = [0.0; 4];
+            let mut a1 = [0.0; 4];
+            let mut a2 = [0.0; 4];
+            for (lane, r) in chunk.iter().enumerate() {
+                let freq = (r.frequency * self.pitch_hz).clamp(1.0, nyquist);
+                let radius = libm::expf(-6.907_755 / (r.decay.max(0.001) * self.sample_rate));
+                let omega = 2.0 * PI * freq / self.sample_rate;
+                a1[lane] = 2.0 * radius * libm::cosf(omega);
+                a2[lane] = radius * radius;
+                b[lane] = r.gain * (1.0 - radius * radius) * 0.5;
+            }
+            group.b = f32x4::from(b);
+            group.a1 = f32x4::from(a1);
+            group.a2 = f32x4::from(a2);
+        }
+    }
+}
+
+impl FrameProcessor<Mono> for ResonatorBank {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        if len == 0 {
+            return;
+        }
+        if self.param_buffer.len() < len {
+            self.param_buffer.resize(len, 0.0);
+        }
+        if self.mix_buffer.len() < len {
+            self.mix_buffer.resize(len, 0.0);
+        }
+
+        if let Some(pitch) = self.pitch.as_mut() {
+            pitch.process(&mut self.param_buffer[0..len], sample_index);
+            let pitch_hz = self.param_buffer[0].max(1.0);
+            if pitch_hz != self.pitch_hz {
+                self.pitch_hz = pitch_hz;
+                self.update_coeffs();
+            }
+        }
+        self.mix.process(&mut self.mix_buffer[0..len], sample_index);
+
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let input = *sample;
+            let excitation = f32x4::splat(input - self.x2);
+            self.x2 = self.x1;
+            self.x1 = input;
+
+            let mut acc = f32x4::ZERO;
+            for group in self.groups.iter_mut() {
+                let y = group.b * excitation + group.a1 * group.y1 - group.a2 * group.y2;
+                group.y2 = group.y1;
+                group.y1 = flush_denormal4(y);
+                acc += y;
+            }
+            let wet = acc.reduce_add();
+            let mix = self.mix_buffer[i];
+            *sample = input + (wet - input) * mix;
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.update_coeffs();
+        if let Some(pitch) = self.pitch.as_mut() {
+            pitch.set_sample_rate(sample_rate);
+        }
+        self.mix.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.param_buffer.resize(max_block_size, 0.0);
+        self.mix_buffer.resize(max_block_size, 0.0);
+        if let Some(pitch) = self.pitch.as_mut() {
+            pitch.prepare(max_block_size);
+        }
+        self.mix.prepare(max_block_size);
+    }
+
+    fn reset(&mut self) {
+        for group in self.groups.iter_mut() {
+            group.y1 = f32x4::ZERO;
+            group.y2 = f32x4::ZERO;
+        }
+        self.x1 = 0.0;
+        self.x2 = 0.0;
+        if let Some(pitch) = self.pitch.as_mut() {
+            pitch.reset();
+        }
+        self.mix.reset();
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("mix", &self.mix, 0.0, 1.0);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("ResonatorBank", "mix", &self.mix, 0.0, 1.0);
+        if let Some(pitch) = &self.pitch {
+            report.check_param("ResonatorBank", "pitch", pitch, 1.0, 20000.0);
+        }
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "ResonatorBank"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ringing_frequency(bank: &mut ResonatorBank) -> usize {
+        let mut buffer = alloc::vec![0.0; 44100];
+        buffer[0] = 1.0;
+        for block in buffer.chunks_mut(64) {
+            bank.process(block, 0);
+        }
+        // Count over the last 0.9 s, after any short-lived modes have died out.
+        buffer[4410..]
+            .windows(2)
+            .filter(|w| w[0] <= 0.0 && w[1] > 0.0)
+            .count()
+    }
+
+    #[test]
+    fn test_fixed_and_tracked_tuning() {
+        let mut fixed = ResonatorBank::new(&[Resonator::new(1000.0, 4.0, 1.0)]);
+        assert!((ringing_frequency(&mut fixed) as i32 - 900).abs() <= 1);
+
+        // Five modes span two SIMD groups; the strong one sets the pitch.
+        let modes = [
+            Resonator::new(2.0, 4.0, 1.0),
+            Resonator::new(3.0, 0.01, 0.1),
+            Resonator::new(4.0, 0.01, 0.1),
+            Resonator::new(5.0, 0.01, 0.1),
+            Resonator::new(6.0, 0.01, 0.1),
+        ];
+        let mut tracked = ResonatorBank::new(&modes).with_pitch_tracking(AudioParam::hz(220.0));
+        assert!((ringing_frequency(&mut tracked) as i32 - 396).abs() <= 1);
+    }
+}
@@ -1,15 +1,18 @@
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::Mono;
+use crate::core::denormal::flush_denormal;
+use crate::core::preset::ParameterTree;
 use crate::FrameProcessor;
 use alloc::vec::Vec;
 use core::f32::consts::PI;
+use wide::f32x4;
 
 /// Prewarp tangent for the TPT/ZDF bilinear transform.
 ///
 /// Exact `libm::tanf` by default.
 #[cfg(not(feature = "perf-approximations"))]
 #[inline]
-fn prewarp_tan(x: f32) -> f32 {
+pub(crate) fn prewarp_tan(x: f32) -> f32 {
     libm::tanf(x)
 }
 
@@ -22,11 +25,26 @@ fn prewarp_tan(x: f32) -> f32 {
 /// `perf-approximations` feature.
 #[cfg(feature = "perf-approximations")]
 #[inline]
-fn prewarp_tan(x: f32) -> f32 {
+pub(crate) fn prewarp_tan(x: f32) -> f32 {
     let x2 = x * x;
     x * (15.0 - x2) / (15.0 - 6.0 * x2)
 }
 
+/// Prewarp tangent for four cutoffs at once, see [`prewarp_tan`].
+#[cfg(not(feature = "perf-approximations"))]
+#[inline]
+fn prewarp_tan_simd(x: f32x4) -> f32x4 {
+    f32x4::from(x.to_array().map(libm::tanf))
+}
+
+/// Prewarp tangent for four cutoffs at once, see [`prewarp_tan`].
+#[cfg(feature = "perf-approximations")]
+#[inline]
+fn prewarp_tan_simd(x: f32x4) -> f32x4 {
+    let x2 = x * x;
+    x * (f32x4::splat(15.0) - x2) / (f32x4::splat(15.0) - f32x4::splat(6.0) * x2)
+}
+
 /// The output type of the State Variable Filter.
 #[derive(Clone, Copy)]
 pub enum SvfType {
@@ -37,11 +55,64 @@ pub enum SvfType {
     Peak,
 }
 
+// Per-sample-invariant quantities derived from g/k, so the per-sample body needs
+// no division.
+#[derive(Clone, Copy)]
+struct SvfCoeffs {
+    g: f32,
+    denom: f32,    // 1 / (1 + g*(g+k))
+    g_plus_k: f32, // g + k
+    two_g: f32,    // 2*g
+}
+
+impl SvfCoeffs {
+    const ZERO: SvfCoeffs = SvfCoeffs {
+        g: 0.0,
+        denom: 0.0,
+        g_plus_k: 0.0,
+        two_g: 0.0,
+    };
+
+    fn new(cutoff_hz: f32, res: f32, sample_rate: f32) -> Self {
+        let g = prewarp_tan((PI / sample_rate) * cutoff_hz.clamp(10.0, sample_rate * 0.49));
+        let k = 1.0 / res.max(0.01);
+        SvfCoeffs {
+            g,
+            denom: 1.0 / (1.0 + g * (g + k)),
+            g_plus_k: g + k,
+            two_g: 2.0 * g,
+        }
+    }
+
+    fn new_simd(cutoff_hz: f32x4, res: f32x4, sample_rate: f32) -> [Self; 4] {
+        let cutoff = cutoff_hz
+            .max(f32x4::splat(10.0))
+            .min(f32x4::splat(sample_rate * 0.49));
+        let g = prewarp_tan_simd(cutoff * f32x4::splat(PI / sample_rate));
+        let k = f32x4::ONE / res.max(f32x4::splat(0.01));
+        let g_plus_k = g + k;
+        let denom = f32x4::ONE / (f32x4::ONE + g * g_plus_k);
+
+        let g = g.to_array();
+        let denom = denom.to_array();
+        let g_plus_k = g_plus_k.to_array();
+        core::array::from_fn(|i| SvfCoeffs {
+            g: g[i],
+            denom: denom[i],
+            g_plus_k: g_plus_k[i],
+            two_g: 2.0 * g[i],
+        })
+    }
+}
+
 /// A State Variable Filter (SVF).
 ///
 /// A stable and versatile filter that provides simultaneous low-pass, high-pass, band-pass and notch outputs.
 /// This implementation uses the TPT (Topology Preserving Transform) / ZDF (Zero Delay Feedback) method
 /// for excellent stability and response across the frequency range.
+///
+/// Static cutoff and resonance are turned into coefficients once per block; modulated
+/// ones are converted four samples at a time.
 pub struct StateVariableFilter {
     filter_type: SvfType,
     cutoff: AudioParam,
@@ -52,13 +123,7 @@ pub struct StateVariableFilter {
 
     last_cutoff: f32,
     last_res: f32,
-    g: f32,
-    k: f32,
-    // Per-sample-invariant quantities derived from g/k, cached behind the same
-    // change guard as g/k so the per-sample body needs no division.
-    denom: f32,    // 1 / (1 + g*(g+k))
-    g_plus_k: f32, // g + k
-    two_g: f32,    // 2*g
+    coeffs: SvfCoeffs,
 
     cutoff_buffer: Vec<f32>,
     res_buffer: Vec<f32>,
@@ -81,11 +146,7 @@ impl StateVariableFilter {
             s2: 0.0,
             last_cutoff: -1.0,
             last_res: -1.0,
-            g: 0.0,
-            k: 0.0,
-            denom: 0.0,
-            g_plus_k: 0.0,
-            two_g: 0.0,
+            coeffs: SvfCoeffs::ZERO,
             cutoff_buffer: Vec::with_capacity(128),
             res_buffer: Vec::with_capacity(128),
         }
@@ -109,25 +170,29 @@ impl StateVariableFilter {
     /// Processes a single sample through the filter.
     #[inline(always)]
     pub fn tick(&mut self, input: f32, cutoff_hz: f32, res: f32) -> f32 {
+        self.update(cutoff_hz, res);
+        let coeffs = self.coeffs;
+        self.step(input, &coeffs)
+    }
+
+    // Recomputes the cached coefficients only when cutoff or resonance change.
+    #[inline(always)]
+    fn update(&mut self, cutoff_hz: f32, res: f32) {
         if (cutoff_hz - self.last_cutoff).abs() > 0.001 || (res - self.last_res).abs() > 0.001 {
-            self.g = prewarp_tan(
-                (PI / self.sample_rate) * cutoff_hz.clamp(10.0, self.sample_rate * 0.49),
-            );
-            self.k = 1.0 / res.max(0.01);
-            // Recompute the g/k-derived constants only when g/k change.
-            self.g_plus_k = self.g + self.k;
-            self.two_g = 2.0 * self.g;
-            self.denom = 1.0 / (1.0 + self.g * self.g_plus_k);
+            self.coeffs = SvfCoeffs::new(cutoff_hz, res, self.sample_rate);
             self.last_cutoff = cutoff_hz;
             self.last_res = res;
         }
+    }
 
-        let hp = (input - self.s1 * self.g_plus_k - self.s2) * self.denom;
-        let bp = self.g * hp + self.s1;
-        let lp = self.g * bp + self.s2;
+    #[inline(always)]
+    fn step(&mut self, input: f32, c: &SvfCoeffs) -> f32 {
+        let hp = (input - self.s1 * c.g_plus_k - self.s2) * c.denom;
+        let bp = c.g * hp + self.s1;
+        let lp = c.g * bp + self.s2;
 
-        self.s1 += self.two_g * hp;
-        self.s2 += self.two_g * bp;
+        self.s1 = flush_denormal(self.s1 + c.two_g * hp);
+        self.s2 = flush_denormal(self.s2 + c.two_g * bp);
 
         match self.filter_type {
             SvfType::LowPass => lp,
@@ -142,21 +207,60 @@ impl StateVariableFilter {
 impl FrameProcessor<Mono> for StateVariableFilter {
     fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
         let len = buffer.len();
+        let cutoff_static = self.cutoff.get_constant();
+        let res_static = self.resonance.get_constant();
+
+        if let (Some(cutoff), Some(res)) = (cutoff_static, res_static) {
+            self.update(cutoff, res);
+            let coeffs = self.coeffs;
+            for sample in buffer.iter_mut() {
+                *sample = self.step(*sample, &coeffs);
+            }
+            return;
+        }
+
         if self.cutoff_buffer.len() < len {
             self.cutoff_buffer.resize(len, 0.0);
         }
         if self.res_buffer.len() < len {
             self.res_buffer.resize(len, 0.0);
         }
+        match cutoff_static {
+            Some(cutoff) => self.cutoff_buffer[0..len].fill(cutoff),
+            None => self
+                .cutoff
+                .process(&mut self.cutoff_buffer[0..len], sample_index),
+        }
+        match res_static {
+            Some(res) => self.res_buffer[0..len].fill(res),
+            None => self
+                .resonance
+                .process(&mut self.res_buffer[0..len], sample_index),
+        }
 
-        self.cutoff
-            .process(&mut self.cutoff_buffer[0..len], sample_index);
-        self.resonance
-            .process(&mut self.res_buffer[0..len], sample_index);
-
-        for (i, sample) in buffer.iter_mut().enumerate() {
-            *sample = self.tick(*sample, self.cutoff_buffer[i], self.res_buffer[i]);
+        let sample_rate = self.sample_rate;
+        let (chunks, remainder) = buffer.as_chunks_mut::<4>();
+        let mut i = 0;
+        for chunk in chunks {
+            let cutoff: [f32; 4] = self.cutoff_buffer[i..i + 4].try_into().unwrap_or([0.0; 4]);
+            let res: [f32; 4] = self.res_buffer[i..i + 4].try_into().unwrap_or([0.0; 4]);
+            let coeffs = SvfCoeffs::new_simd(f32x4::from(cutoff), f32x4::from(res), sample_rate);
+            for (sample, c) in chunk.iter_mut().zip(coeffs.iter()) {
+                *sample = self.step(*sample, c);
+            }
+            i += 4;
         }
+        for (j, sample) in remainder.iter_mut().enumerate() {
+            let c = SvfCoeffs::new(
+                self.cutoff_buffer[i + j],
+                self.res_buffer[i + j],
+                sample_rate,
+            );
+            *sample = self.step(*sample, &c);
+        }
+
+        // The cached coefficients no longer match the last values seen by `tick`.
+        self.last_cutoff = -1.0;
     }
 
     fn set_sample_rate(&mut self, sample_rate: f32) {
@@ -166,11 +270,23 @@ impl FrameProcessor<Mono> for StateVariableFilter {
         self.last_cutoff = -1.0;
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.cutoff.prepare(max_block_size);
+        self.resonance.prepare(max_block_size);
+        self.cutoff_buffer.resize(max_block_size, 0.0);
+        self.res_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         self.s1 = 0.0;
         self.s2 = 0.0;
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("cutoff", &self.cutoff, 20.0, 20000.0);
+        tree.add_audio_param("resonance", &self.resonance, 0.0, 1.0);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         match self.filter_type {
@@ -182,3 +298,70 @@ impl FrameProcessor<Mono> for StateVariableFilter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parameter::Parameter;
+    use crate::synthesis::lfo::{Lfo, LfoWaveform};
+    use alloc::boxed::Box;
+    use alloc::vec;
+
+    fn noise(len: usize) -> Vec<f32> {
+        let mut seed = 1u32;
+        (0..len)
+            .map(|_| {
+                seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                (seed >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_block_paths_match_tick() {
+        let input = noise(301);
+
+        let mut block = StateVariableFilter::new(
+            SvfType::BandPass,
+            AudioParam::Linked(Parameter::new(1200.0)),
+            AudioParam::Static(2.0),
+        );
+        let mut reference = StateVariableFilter::new(
+            SvfType::BandPass,
+            AudioParam::Static(0.0),
+            AudioParam::Static(0.0),
+        );
+        let mut output = input.clone();
+        block.process(&mut output, 0);
+        for (x, y) in input.iter().zip(output.iter()) {
+            assert_eq!(reference.tick(*x, 1200.0, 2.0), *y);
+        }
+
+        let sweep = || {
+            let mut lfo = Lfo::new(AudioParam::Static(50.0), LfoWaveform::Sine);
+            lfo.set_range(200.0, 4000.0);
+            lfo
+        };
+        let mut block = StateVariableFilter::new(
+            SvfType::LowPass,
+            AudioParam::Dynamic(Box::new(sweep())),
+            AudioParam::Static(0.7),
+        );
+        let mut cutoff = vec![0.0; input.len()];
+        sweep().process(&mut cutoff, 0);
+        let mut output = input.clone();
+        block.process(&mut output, 0);
+
+        // The reference moves the coefficients exactly every sample too.
+        let mut reference = StateVariableFilter::new(
+            SvfType::LowPass,
+            AudioParam::Static(0.0),
+            AudioParam::Static(0.0),
+        );
+        for i in 0..input.len() {
+            reference.last_cutoff = -1.0;
+            let expected = reference.tick(input[i], cutoff[i], 0.7);
+            assert!((expected - output[i]).abs() < 1e-4);
+        }
+    }
+}
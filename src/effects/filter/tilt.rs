@@ -0,0 +1,328 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
+use crate::effects::filter::biquad::{BiquadCoeffs, BiquadState, FilterType};
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+
+/// A low shelf and a high shelf in series.
+struct ShelfPair {
+    low: BiquadCoeffs,
+    high: BiquadCoeffs,
+    state: [BiquadState; 2],
+    last: [u32; 4],
+}
+
+impl ShelfPair {
+    fn new() -> Self {
+        ShelfPair {
+            low: BiquadCoeffs::IDENTITY,
+            high: BiquadCoeffs::IDENTITY,
+            state: [BiquadState::default(); 2],
+            last: [u32::MAX; 4],
+        }
+    }
+
+    fn update(&mut self, low: (f32, f32), high: (f32, f32), q: f32, sample_rate: f32) {
+        let bits = [
+            low.0.to_bits(),
+            low.1.to_bits(),
+            high.0.to_bits(),
+            high.1.to_bits(),
+        ];
+        if bits != self.last {
+            let limit = sample_rate * 0.45;
+            self.low = BiquadCoeffs::new(
+                FilterType::LowShelf,
+                low.0.clamp(10.0, limit),
+                q,
+                low.1,
+                sample_rate,
+            );
+            self.high = BiquadCoeffs::new(
+                FilterType::HighShelf,
+                high.0.clamp(10.0, limit),
+                q,
+                high.1,
+                sample_rate,
+            );
+            self.last = bits;
+        }
+    }
+
+    fn invalidate(&mut self) {
+        self.last = [u32::MAX; 4];
+    }
+
+    #[inline(always)]
+    fn tick(&mut self, x: f32) -> f32 {
+        let [low, high] = &mut self.state;
+        high.tick(&self.high, low.tick(&self.low, x))
+    }
+
+    fn reset(&mut self) {
+        self.state = [BiquadState::default(); 2];
+    }
+}
+
+/// A tilt equaliser.
+///
+/// One knob that leans the whole spectrum around a pivot frequency: a positive tilt boosts
+/// the highs and cuts the lows by half the amount each, a negative tilt does the opposite,
+/// and the pivot itself stays at unity.
+pub struct TiltEq {
+    frequency: AudioParam,
+    tilt_db: AudioParam,
+    sample_rate: f32,
+    shelves: ShelfPair,
+    param_buffer: Vec<f32>,
+}
+
+impl TiltEq {
+    /// Creates a new TiltEq.
+    ///
+    /// # Arguments
+    /// * `frequency` - Pivot frequency in Hz.
+    /// * `tilt_db` - Difference between the high and low extremes in dB (positive = brighter).
+    pub fn new(frequency: AudioParam, tilt_db: AudioParam) -> Self {
+        TiltEq {
+            frequency,
+            tilt_db,
+            sample_rate: 44100.0,
+            shelves: ShelfPair::new(),
+            param_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the pivot frequency parameter.
+    pub fn set_frequency(&mut self, frequency: AudioParam) {
+        self.frequency = frequency;
+    }
+
+    /// Sets the tilt parameter.
+    pub fn set_tilt(&mut self, tilt_db: AudioParam) {
+        self.tilt_db = tilt_db;
+    }
+}
+
+impl FrameProcessor<Mono> for TiltEq {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        if len == 0 {
+            return;
+        }
+        if self.param_buffer.len() < len {
+            self.param_buffer.resize(len, 0.0);
+        }
+
+        let buf = &mut self.param_buffer[0..len];
+        let frequency = self.frequency.block_value(buf, sample_index);
+        let half = self.tilt_db.block_value(buf, sample_index) * 0.5;
+        // Shallow shelves, so the two slopes meet in one smooth line.
+        self.shelves
+            .update((frequency, -half), (frequency, half), 0.5, self.sample_rate);
+
+        for sample in buffer.iter_mut() {
+            *sample = self.shelves.tick(*sample);
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.shelves.invalidate();
+        self.frequency.set_sample_rate(sample_rate);
+        self.tilt_db.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.param_buffer.resize(max_block_size, 0.0);
+        self.frequency.prepare(max_block_size);
+        self.tilt_db.prepare(max_block_size);
+    }
+
+    fn reset(&mut self) {
+        self.shelves.reset();
+        self.frequency.reset();
+        self.tilt_db.reset();
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("frequency", &self.frequency, 100.0, 5000.0);
+        tree.add_audio_param("tilt", &self.tilt_db, -12.0, 12.0);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("TiltEq", "frequency", &self.frequency, 20.0, 20000.0);
+        report.check_param("TiltEq", "tilt", &self.tilt_db, -24.0, 24.0);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "TiltEq"
+    }
+}
+
+/// A Baxandall-style bass and treble tone control.
+///
+/// Two broad shelves, at 100 Hz and 10 kHz by default, that leave the mids alone. The
+/// gentle slope of the shelves keeps boosts musical, like the classic hi-fi tone stack.
+pub struct BaxandallTone {
+    bass_db: AudioParam,
+    treble_db: AudioParam,
+    bass_frequency: AudioParam,
+    treble_frequency: AudioParam,
+    sample_rate: f32,
+    shelves: ShelfPair,
+    param_buffer: Vec<f32>,
+}
+
+impl BaxandallTone {
+    /// Creates a new BaxandallTone.
+    ///
+    /// # Arguments
+    /// * `bass_db` - Bass boost or cut in dB.
+    /// * `treble_db` - Treble boost or cut in dB.
+    pub fn new(bass_db: AudioParam, treble_db: AudioParam) -> Self {
+        BaxandallTone {
+            bass_db,
+            treble_db,
+            bass_frequency: AudioParam::hz(100.0),
+            treble_frequency: AudioParam::hz(10000.0),
+            sample_rate: 44100.0,
+            shelves: ShelfPair::new(),
+            param_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the bass parameter.
+    pub fn set_bass(&mut self, bass_db: AudioParam) {
+        self.bass_db = bass_db;
+    }
+
+    /// Sets the treble parameter.
+    pub fn set_treble(&mut self, treble_db: AudioParam) {
+        self.treble_db = treble_db;
+    }
+
+    /// Sets the corner frequency of the bass shelf (in Hz).
+    pub fn set_bass_frequency(&mut self, frequency: AudioParam) {
+        self.bass_frequency = frequency;
+    }
+
+    /// Sets the corner frequency of the treble shelf (in Hz).
+    pub fn set_treble_frequency(&mut self, frequency: AudioParam) {
+        self.treble_frequency = frequency;
+    }
+}
+
+impl FrameProcessor<Mono> for BaxandallTone {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        if len == 0 {
+            return;
+        }
+        if self.param_buffer.len() < len {
+            self.param_buffer.resize(len, 0.0);
+        }
+
+        let buf = &mut self.param_buffer[0..len];
+        let bass = self.bass_db.block_value(buf, sample_index);
+        let treble = self.treble_db.block_value(buf, sample_index);
+        let bass_frequency = self.bass_frequency.block_value(buf, sample_index);
+        let treble_frequency = self.treble_frequency.block_value(buf, sample_index);
+        self.shelves.update(
+            (bass_frequency, bass),
+            (treble_frequency, treble),
+            0.6,
+            self.sample_rate,
+        );
+
+        for sample in buffer.iter_mut() {
+            *sample = self.shelves.tick(*sample);
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.shelves.invalidate();
+        self.bass_db.set_sample_rate(sample_rate);
+        self.treble_db.set_sample_rate(sample_rate);
+        self.bass_frequency.set_sample_rate(sample_rate);
+        self.treble_frequency.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.param_buffer.resize(max_block_size, 0.0);
+        self.bass_db.prepare(max_block_size);
+        self.treble_db.prepare(max_block_size);
+        self.bass_frequency.prepare(max_block_size);
+        self.treble_frequency.prepare(max_block_size);
+    }
+
+    fn reset(&mut self) {
+        self.shelves.reset();
+        self.bass_db.reset();
+        self.treble_db.reset();
+        self.bass_frequency.reset();
+        self.treble_frequency.reset();
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("bass", &self.bass_db, -15.0, 15.0);
+        tree.add_audio_param("treble", &self.treble_db, -15.0, 15.0);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("BaxandallTone", "bass", &self.bass_db, -24.0, 24.0);
+        report.check_param("BaxandallTone", "treble", &self.treble_db, -24.0, 24.0);
+        report.check_param(
+            "BaxandallTone",
+            "bass_frequency",
+            &self.bass_frequency,
+            20.0,
+            2000.0,
+        );
+        report.check_param(
+            "BaxandallTone",
+            "treble_frequency",
+            &self.treble_frequency,
+            1000.0,
+            20000.0,
+        );
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "BaxandallTone"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::PI;
+
+    fn level<P: FrameProcessor<Mono>>(processor: &mut P, freq: f32) -> f32 {
+        processor.reset();
+        let mut buffer: Vec<f32> = (0..22050)
+            .map(|i| libm::sinf(2.0 * PI * freq * i as f32 / 44100.0))
+            .collect();
+        processor.process(&mut buffer, 0);
+        let peak = buffer[11025..].iter().fold(0.0f32, |p, s| p.max(s.abs()));
+        20.0 * libm::log10f(peak)
+    }
+
+    #[test]
+    fn test_tilt_and_shelves() {
+        let mut tilt = TiltEq::new(AudioParam::hz(1000.0), AudioParam::db(6.0));
+        assert!((level(&mut tilt, 20.0) + 3.0).abs() < 0.3);
+        assert!(level(&mut tilt, 1000.0).abs() < 0.3);
+        assert!((level(&mut tilt, 18000.0) - 3.0).abs() < 0.3);
+
+        let mut tone = BaxandallTone::new(AudioParam::db(9.0), AudioParam::db(-6.0));
+        assert!((level(&mut tone, 20.0) - 9.0).abs() < 0.5);
+        assert!(level(&mut tone, 1000.0).abs() < 0.5);
+        assert!((level(&mut tone, 20000.0) + 6.0).abs() < 0.5);
+    }
+}
@@ -2,6 +2,7 @@ use crate::core::audio_param::AudioParam;
 use crate::core::channels::Mono;
 use crate::effects::filter::state_variable::{StateVariableFilter, SvfType};
 use crate::FrameProcessor;
+use alloc::vec::Vec;
 
 /// Standard vowels for the [`VowelFilter`].
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -69,6 +70,8 @@ pub struct VowelFilter {
     q: AudioParam,
     sample_rate: f32,
     manual_formants: Option<(f32, f32, f32)>,
+    morph_buffer: Vec<f32>,
+    q_buffer: Vec<f32>,
 }
 
 impl VowelFilter {
@@ -139,6 +142,8 @@ impl VowelFilter {
             q,
             sample_rate: sr,
             manual_formants: None,
+            morph_buffer: Vec::with_capacity(128),
+            q_buffer: Vec::with_capacity(128),
         }
     }
 
@@ -183,12 +188,20 @@ impl VowelFilter {
 
 impl FrameProcessor<Mono> for VowelFilter {
     fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        if self.morph_buffer.len() < len {
+            self.morph_buffer.resize(len, 0.0);
+            self.q_buffer.resize(len, 0.0);
+        }
+        self.vowel_morph
+            .process(&mut self.morph_buffer[0..len], sample_index);
+        self.q.process(&mut self.q_buffer[0..len], sample_index);
+
         for (i, sample) in buffer.iter_mut().enumerate() {
-            let current_idx = sample_index + i as u64;
             let input = *sample;
 
-            let morph = self.vowel_morph.get_value_at(current_idx);
-            let cur_q_val = self.q.get_value_at(current_idx);
+            let morph = self.morph_buffer[i];
+            let cur_q_val = self.q_buffer[i];
 
             let (f1, f2, f3) = if let Some(manual) = self.manual_formants {
                 manual
@@ -209,6 +222,13 @@ impl FrameProcessor<Mono> for VowelFilter {
         self.q.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.vowel_morph.prepare(max_block_size);
+        self.q.prepare(max_block_size);
+        self.morph_buffer.resize(max_block_size, 0.0);
+        self.q_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         self.f1.reset();
         self.f2.reset();
@@ -1,3 +1,4 @@
+pub mod channel_strip;
 pub mod dynamics;
 pub mod filter;
 pub mod modulation;
@@ -0,0 +1,209 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Stereo;
+use crate::core::error::ValidationReport;
+use crate::core::utils::FastRng;
+use crate::effects::utility::panner::PanLaw;
+use crate::synthesis::lfo::LfoWaveform;
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+
+/// An auto-panner.
+///
+/// Sweeps a stereo signal across the field with an internal LFO. Each channel follows its
+/// own LFO phase: the right channel's runs `phase_offset` cycles ahead of the left's.
+/// The default offset of 0.5 moves the two in opposition, a classic auto-pan; 0.0 moves
+/// them together, which turns the effect into a tremolo, and values in between give a
+/// circling, wider motion. The `SampleAndHold` waveform jumps to a random position once
+/// per cycle and ignores the offset.
+///
+/// At zero depth the signal sits in the centre, at the centre level of the panning law.
+pub struct AutoPanner {
+    rate: AudioParam,
+    depth: AudioParam,
+    phase_offset: AudioParam,
+    waveform: LfoWaveform,
+    law: PanLaw,
+    sample_rate: f32,
+
+    phase: f32,
+    rng: FastRng,
+    random_position: f32,
+
+    rate_buffer: Vec<f32>,
+    depth_buffer: Vec<f32>,
+    offset_buffer: Vec<f32>,
+}
+
+impl AutoPanner {
+    /// Creates a new AutoPanner.
+    ///
+    /// # Arguments
+    /// * `rate` - LFO rate in Hz.
+    /// * `depth` - Sweep width (0.0 = centre, 1.0 = hard left to hard right).
+    /// * `waveform` - LFO waveform.
+    pub fn new(rate: AudioParam, depth: AudioParam, waveform: LfoWaveform) -> Self {
+        AutoPanner {
+            rate,
+            depth,
+            phase_offset: AudioParam::Static(0.5),
+            waveform,
+            law: PanLaw::ConstantPower,
+            sample_rate: 44100.0,
+            phase: 0.0,
+            rng: FastRng::new(12345),
+            random_position: 0.0,
+            rate_buffer: Vec::with_capacity(128),
+            depth_buffer: Vec::with_capacity(128),
+            offset_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the panning law and returns the panner.
+    pub fn with_law(mut self, law: PanLaw) -> Self {
+        self.law = law;
+        self
+    }
+
+    /// Sets the rate parameter in Hz.
+    pub fn set_rate(&mut self, rate: AudioParam) {
+        self.rate = rate;
+    }
+
+    /// Sets the depth parameter.
+    pub fn set_depth(&mut self, depth: AudioParam) {
+        self.depth = depth;
+    }
+
+    /// Sets the phase offset of the right channel's LFO, in cycles (default 0.5).
+    pub fn set_phase_offset(&mut self, offset: AudioParam) {
+        self.phase_offset = offset;
+    }
+
+    /// Sets the LFO waveform.
+    pub fn set_waveform(&mut self, waveform: LfoWaveform) {
+        self.waveform = waveform;
+    }
+}
+
+impl FrameProcessor<Stereo> for AutoPanner {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let frames = buffer.len() / 2;
+        if self.rate_buffer.len() < frames {
+            self.rate_buffer.resize(frames, 0.0);
+        }
+        if self.depth_buffer.len() < frames {
+            self.depth_buffer.resize(frames, 0.0);
+        }
+        if self.offset_buffer.len() < frames {
+            self.offset_buffer.resize(frames, 0.0);
+        }
+        self.rate
+            .process(&mut self.rate_buffer[0..frames], sample_index);
+        self.depth
+            .process(&mut self.depth_buffer[0..frames], sample_index);
+        self.phase_offset
+            .process(&mut self.offset_buffer[0..frames], sample_index);
+
+        for (i, frame) in buffer.chunks_exact_mut(2).enumerate() {
+            let depth = self.depth_buffer[i].clamp(0.0, 1.0);
+            let (left, right) = match self.waveform.periodic(self.phase) {
+                Some(left) => {
+                    let mut right_phase = self.phase + self.offset_buffer[i];
+                    right_phase -= libm::floorf(right_phase);
+                    // Mirrored, so opposite LFO values move both channels the same way.
+                    let right = -self.waveform.periodic(right_phase).unwrap_or(0.0);
+                    (left, right)
+                }
+                None => (self.random_position, self.random_position),
+            };
+
+            // The left position only sets the left gain, and the right the right gain.
+            let (gain_l, _) = self.law.gains(left * depth);
+            let (_, gain_r) = self.law.gains(right * depth);
+            frame[0] *= gain_l;
+            frame[1] *= gain_r;
+
+            self.phase += self.rate_buffer[i] / self.sample_rate;
+            if self.phase >= 1.0 {
+                self.phase -= libm::floorf(self.phase);
+                self.random_position = self.rng.next_f32_bipolar();
+            }
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.rate.set_sample_rate(sample_rate);
+        self.depth.set_sample_rate(sample_rate);
+        self.phase_offset.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.rate.prepare(max_block_size);
+        self.depth.prepare(max_block_size);
+        self.phase_offset.prepare(max_block_size);
+        self.rate_buffer.resize(max_block_size, 0.0);
+        self.depth_buffer.resize(max_block_size, 0.0);
+        self.offset_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.phase = 0.0;
+        self.random_position = 0.0;
+        self.rate.reset();
+        self.depth.reset();
+        self.phase_offset.reset();
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("AutoPanner", "depth", &self.depth, 0.0, 1.0);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "AutoPanner"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn render(panner: &mut AutoPanner) -> Vec<f32> {
+        panner.set_sample_rate(1000.0);
+        let mut buffer = vec![1.0; 2 * 1000];
+        panner.process(&mut buffer, 0);
+        buffer
+    }
+
+    #[test]
+    fn test_auto_pan() {
+        // A triangle starts at +1: hard right, then sweeps to hard left half a cycle later.
+        let mut panner = AutoPanner::new(
+            AudioParam::hz(1.0),
+            AudioParam::Static(1.0),
+            LfoWaveform::Triangle,
+        );
+        let out = render(&mut panner);
+        assert!((out[0] - 0.0).abs() < 1e-6 && (out[1] - 1.0).abs() < 1e-6);
+        let middle = 2 * 500;
+        assert!((out[middle] - 1.0).abs() < 1e-3 && out[middle + 1].abs() < 1e-3);
+        // Constant power all the way.
+        for frame in out.chunks(2) {
+            assert!((frame[0] * frame[0] + frame[1] * frame[1] - 1.0).abs() < 1e-4);
+        }
+
+        // Without an offset both channels move together: a tremolo.
+        let mut tremolo = AutoPanner::new(
+            AudioParam::hz(1.0),
+            AudioParam::Static(1.0),
+            LfoWaveform::Sine,
+        )
+        .with_law(PanLaw::Linear);
+        tremolo.set_phase_offset(AudioParam::Static(0.0));
+        let out = render(&mut tremolo);
+        assert!(out.chunks(2).all(|f| (f[0] - f[1]).abs() < 1e-6));
+        assert!(out.iter().any(|&g| g < 0.01) && out.iter().any(|&g| g > 0.99));
+    }
+}
@@ -0,0 +1,298 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Stereo;
+use crate::core::error::{DspError, ValidationReport};
+use crate::core::utils::FastRng;
+use crate::effects::time::delay::read_linear;
+use crate::FrameProcessor;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::FRAC_PI_4;
+
+const MAX_VOICES: usize = 4;
+const BUFFER_SECONDS: f32 = 0.06;
+const BASE_DELAYS: [f32; MAX_VOICES] = [0.012, 0.019, 0.026, 0.033];
+const DRIFT_PERIODS: [f32; MAX_VOICES] = [0.37, 0.49, 0.61, 0.73];
+const DEFAULT_PANS: [f32; MAX_VOICES] = [-0.8, 0.8, -0.4, 0.4];
+
+struct Voice {
+    base_delay: f32,
+    gain_l: f32,
+    gain_r: f32,
+
+    // Sample-and-hold target, smoothed twice so the delay (and thus pitch) glides.
+    target: f32,
+    smooth1: f32,
+    smooth2: f32,
+    hold_samples: usize,
+    countdown: usize,
+    coeff: f32,
+}
+
+impl Voice {
+    fn set_pan(&mut self, pan: f32, level: f32) {
+        let angle = (pan.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4;
+        self.gain_l = libm::cosf(angle) * level;
+        self.gain_r = libm::sinf(angle) * level;
+    }
+
+    fn set_drift_period(&mut self, period: f32, sample_rate: f32) {
+        self.hold_samples = ((period * sample_rate) as usize).max(1);
+        self.coeff = 1.0 - libm::expf(-2.0 / self.hold_samples as f32);
+    }
+}
+
+/// A studio doubler with 2 - 4 drifting voices (Dimension-style thickening).
+///
+/// Each voice is a delayed copy of the (mono-summed) input placed in the stereo field.
+/// Instead of a periodic LFO, every voice's delay follows its own slowly gliding random
+/// walk, so the copies drift in pitch and timing against each other like separate takes
+/// rather than wobbling in sync like a chorus.
+pub struct Doubler {
+    voices: Vec<Voice>,
+    buffer: Vec<f32>,
+    write_ptr: usize,
+    rng: FastRng,
+    drift: AudioParam,
+    mix: AudioParam,
+    sample_rate: f32,
+    drift_buffer: Vec<f32>,
+    mix_buffer: Vec<f32>,
+}
+
+impl Doubler {
+    /// Creates a new Doubler.
+    ///
+    /// # Arguments
+    /// * `voices` - Number of delayed voices (2 - 4).
+    pub fn new(voices: usize) -> Self {
+        Self::try_new(voices).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a new Doubler, or returns an error if the voice count is not 2 - 4.
+    pub fn try_new(voices: usize) -> Result<Self, DspError> {
+        DspError::check_count("Doubler", "voices", voices, 2, MAX_VOICES)?;
+
+        let sample_rate = 44100.0;
+        let voices = (0..voices)
+            .map(|i| {
+                let mut voice = Voice {
+                    base_delay: BASE_DELAYS[i],
+                    gain_l: 0.0,
+                    gain_r: 0.0,
+                    target: 0.0,
+                    smooth1: 0.0,
+                    smooth2: 0.0,
+                    hold_samples: 1,
+                    countdown: 0,
+                    coeff: 0.0,
+                };
+                voice.set_pan(DEFAULT_PANS[i], 0.7);
+                voice.set_drift_period(DRIFT_PERIODS[i], sample_rate);
+                voice
+            })
+            .collect();
+
+        Ok(Doubler {
+            voices,
+            buffer: vec![0.0; (BUFFER_SECONDS * sample_rate) as usize],
+            write_ptr: 0,
+            rng: FastRng::new(0x1234_5678),
+            drift: AudioParam::ms(1.5),
+            mix: AudioParam::Static(0.5),
+            sample_rate,
+            drift_buffer: Vec::with_capacity(128),
+            mix_buffer: Vec::with_capacity(128),
+        })
+    }
+
+    /// Sets the pan (-1.0 = left, 1.0 = right) and level of a voice.
+    pub fn set_voice(&mut self, index: usize, pan: f32, level: f32) {
+        self.voices[index].set_pan(pan, level);
+    }
+
+    /// Sets the maximum delay drift in seconds (up to 20 ms).
+    pub fn set_drift(&mut self, drift: AudioParam) {
+        self.drift = drift;
+    }
+
+    /// Sets the mix parameter.
+    pub fn set_mix(&mut self, mix: AudioParam) {
+        self.mix = mix;
+    }
+}
+
+impl FrameProcessor<Stereo> for Doubler {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let frames = buffer.len() / 2;
+        if self.drift_buffer.len() < frames {
+            self.drift_buffer.resize(frames, 0.0);
+        }
+        if self.mix_buffer.len() < frames {
+            self.mix_buffer.resize(frames, 0.0);
+        }
+        self.drift
+            .process(&mut self.drift_buffer[0..frames], sample_index);
+        self.mix
+            .process(&mut self.mix_buffer[0..frames], sample_index);
+
+        let len = self.buffer.len();
+        let len_f = len as f32;
+
+        for (i, frame) in buffer.chunks_exact_mut(2).enumerate() {
+            self.buffer[self.write_ptr] = (frame[0] + frame[1]) * 0.5;
+            let drift = self.drift_buffer[i].clamp(0.0, 0.02);
+
+            let mut wet_l = 0.0;
+            let mut wet_r = 0.0;
+            for voice in &mut self.voices {
+                if voice.countdown == 0 {
+                    voice.target = self.rng.next_f32_bipolar();
+                    voice.countdown = voice.hold_samples;
+                }
+                voice.countdown -= 1;
+                voice.smooth1 += (voice.target - voice.smooth1) * voice.coeff;
+                voice.smooth2 += (voice.smooth1 - voice.smooth2) * voice.coeff;
+
+                // The drift is centered on the base delay. At large drifts it can swing past
+                // zero, so the read is kept at least one sample behind the write head.
+                let delay = ((voice.base_delay + voice.smooth2 * drift) * self.sample_rate)
+                    .clamp(1.0, len_f - 2.0);
+                let mut position = self.write_ptr as f32 - delay;
+                if position < 0.0 {
+                    position += len_f;
+                }
+                let delayed = read_linear(&self.buffer, position);
+                wet_l += delayed * voice.gain_l;
+                wet_r += delayed * voice.gain_r;
+            }
+
+            self.write_ptr += 1;
+            if self.write_ptr >= len {
+                self.write_ptr = 0;
+            }
+
+            let mix = self.mix_buffer[i];
+            frame[0] = frame[0] * (1.0 - mix) + wet_l * mix;
+            frame[1] = frame[1] * (1.0 - mix) + wet_r * mix;
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.drift.set_sample_rate(sample_rate);
+        self.mix.set_sample_rate(sample_rate);
+        for (voice, &period) in self.voices.iter_mut().zip(DRIFT_PERIODS.iter()) {
+            voice.set_drift_period(period, sample_rate);
+            voice.countdown = voice.countdown.min(voice.hold_samples);
+        }
+
+        let needed = (BUFFER_SECONDS * sample_rate) as usize;
+        if needed > self.buffer.len() {
+            self.buffer.resize(needed, 0.0);
+        }
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.drift.prepare(max_block_size);
+        self.mix.prepare(max_block_size);
+        self.drift_buffer.resize(max_block_size, 0.0);
+        self.mix_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.write_ptr = 0;
+        for voice in &mut self.voices {
+            voice.target = 0.0;
+            voice.smooth1 = 0.0;
+            voice.smooth2 = 0.0;
+            voice.countdown = 0;
+        }
+        self.drift.reset();
+        self.mix.reset();
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("Doubler", "drift", &self.drift, 0.0, 0.02);
+        report.check_param("Doubler", "mix", &self.mix, 0.0, 1.0);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "Doubler"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_voices_without_drift() {
+        let mut doubler = Doubler::new(2);
+        doubler.set_drift(AudioParam::Static(0.0));
+        doubler.set_mix(AudioParam::Static(1.0));
+        doubler.set_voice(1, 1.0, 0.5);
+
+        let mut buffer = vec![0.0; 2 * 2048];
+        buffer[0] = 1.0;
+        buffer[1] = 1.0;
+        doubler.process(&mut buffer, 0);
+
+        // Voice 0 sits at 12 ms, left of center; voice 1 at 19 ms, hard right. The delays
+        // fall between samples, so each echo is split over two frames.
+        let echo = |delay: f32, channel: usize| {
+            let frame = (delay * 44100.0) as usize;
+            buffer[2 * frame + channel] + buffer[2 * frame + 2 + channel]
+        };
+        let (cos, sin) = (libm::cosf(0.2 * FRAC_PI_4), libm::sinf(0.2 * FRAC_PI_4));
+        assert!((echo(0.012, 0) - 0.7 * cos).abs() < 1e-3);
+        assert!((echo(0.012, 1) - 0.7 * sin).abs() < 1e-3);
+        assert!(echo(0.019, 0).abs() < 1e-3);
+        assert!((echo(0.019, 1) - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_drift_moves_voices() {
+        let mut doubler = Doubler::new(4);
+        doubler.set_mix(AudioParam::Static(1.0));
+        doubler.set_drift(AudioParam::ms(5.0));
+
+        let mut buffer = vec![0.0; 2 * 44100];
+        for (i, frame) in buffer.chunks_mut(2).enumerate() {
+            if i % 4410 == 0 {
+                frame[0] = 1.0;
+                frame[1] = 1.0;
+            }
+        }
+        for block in buffer.chunks_mut(256) {
+            doubler.process(block, 0);
+        }
+
+        // The first voice's echo of each click arrives at a different offset.
+        let onsets: Vec<usize> = buffer
+            .chunks(2)
+            .map(|f| f[0])
+            .collect::<Vec<_>>()
+            .chunks(4410)
+            .map(|c| c.iter().position(|s| s.abs() > 1e-4).unwrap())
+            .collect();
+        assert!(onsets.windows(2).any(|w| w[0] != w[1]));
+        assert!(onsets.iter().all(|&o| (300..800).contains(&o)));
+    }
+
+    #[test]
+    fn test_maximum_drift_stays_in_buffer() {
+        let mut doubler = Doubler::new(4);
+        doubler.set_mix(AudioParam::Static(1.0));
+        doubler.set_drift(AudioParam::Static(0.02));
+
+        // Long enough for the random walk of the shortest voice to swing below zero delay.
+        let mut buffer = vec![0.5; 2 * 1024];
+        for _ in 0..1000 {
+            doubler.process(&mut buffer, 0);
+            assert!(buffer.iter().all(|s| s.is_finite() && s.abs() <= 2.0));
+            buffer.fill(0.5);
+        }
+    }
+}
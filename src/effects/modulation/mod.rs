@@ -1,4 +1,7 @@
+pub mod auto_pan;
+pub mod doubler;
 pub mod modulated_delay;
+pub mod octaver;
 pub mod phaser;
 pub mod ring_mod;
 pub mod tremolo;
@@ -1,10 +1,14 @@
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::Mono;
+use crate::core::error::{DspError, ValidationReport};
 use crate::FrameProcessor;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::f32::consts::PI;
 
+/// Length of the delay line in seconds.
+const MAX_DELAY_SECONDS: f32 = 0.1;
+
 /// A modulated delay effect, used for Chorus and Flanger.
 pub struct ModulatedDelay {
     buffer: Vec<f32>,
@@ -25,22 +29,33 @@ pub struct ModulatedDelay {
 }
 
 impl ModulatedDelay {
-    /// Creates a new Chorus effect.
+    /// Creates a new ModulatedDelay.
     ///
-    /// Uses a longer delay time and moderate modulation depth.
-    pub fn new_chorus() -> Self {
+    /// # Arguments
+    /// * `base_delay_s` - Centre delay time in seconds (up to 0.05 s).
+    /// * `rate_hz` - LFO rate in Hz.
+    /// * `depth` - Modulation depth in samples (up to the base delay).
+    /// * `feedback` - Feedback amount (-1.0 - 1.0).
+    /// * `mix` - Dry/Wet mix (0.0 - 1.0).
+    pub fn new(
+        base_delay_s: f32,
+        rate_hz: f32,
+        depth: AudioParam,
+        feedback: AudioParam,
+        mix: AudioParam,
+    ) -> Self {
         let sample_rate = 44100.0;
-        let buffer_size = (sample_rate * 0.1) as usize;
+        let buffer_size = (sample_rate * MAX_DELAY_SECONDS) as usize;
 
         ModulatedDelay {
             buffer: vec![0.0; buffer_size],
             write_ptr: 0,
             lfo_phase: 0.0,
-            lfo_inc: 2.0 * PI * 1.5 / sample_rate,
-            depth: AudioParam::Static(0.002 * sample_rate),
-            base_delay: 0.015 * sample_rate,
-            feedback: AudioParam::Static(0.4),
-            mix: AudioParam::Static(0.5),
+            lfo_inc: 2.0 * PI * rate_hz / sample_rate,
+            depth,
+            base_delay: base_delay_s * sample_rate,
+            feedback,
+            mix,
             sample_rate,
             depth_buffer: Vec::with_capacity(128),
             feedback_buffer: Vec::with_capacity(128),
@@ -48,27 +63,53 @@ impl ModulatedDelay {
         }
     }
 
+    /// Creates a new ModulatedDelay, or returns the first problem with the arguments.
+    ///
+    /// Fails if the base delay or rate is out of range, or a static parameter is.
+    pub fn try_new(
+        base_delay_s: f32,
+        rate_hz: f32,
+        depth: AudioParam,
+        feedback: AudioParam,
+        mix: AudioParam,
+    ) -> Result<Self, DspError> {
+        DspError::check_value(
+            "ModulatedDelay",
+            "base_delay",
+            base_delay_s,
+            0.0,
+            MAX_DELAY_SECONDS * 0.5,
+        )?;
+        DspError::check_value("ModulatedDelay", "rate", rate_hz, 0.0, 20.0)?;
+        let delay = Self::new(base_delay_s, rate_hz, depth, feedback, mix);
+        ValidationReport::of::<Mono, _>(&delay).into_first_error()?;
+        Ok(delay)
+    }
+
+    /// Creates a new Chorus effect.
+    ///
+    /// Uses a longer delay time and moderate modulation depth.
+    pub fn new_chorus() -> Self {
+        Self::new(
+            0.015,
+            1.5,
+            AudioParam::Static(0.002 * 44100.0),
+            AudioParam::Static(0.4),
+            AudioParam::Static(0.5),
+        )
+    }
+
     /// Creates a new Flanger effect.
     ///
     /// Uses a short delay time and higher feedback.
     pub fn new_flanger() -> Self {
-        let sample_rate = 44100.0;
-        let buffer_size = (sample_rate * 0.1) as usize;
-
-        ModulatedDelay {
-            buffer: vec![0.0; buffer_size],
-            write_ptr: 0,
-            lfo_phase: 0.0,
-            lfo_inc: 2.0 * PI * 0.5 / sample_rate,
-            depth: AudioParam::Static(0.005 * sample_rate),
-            base_delay: 0.005 * sample_rate,
-            feedback: AudioParam::Static(0.7),
-            mix: AudioParam::Static(0.5),
-            sample_rate,
-            depth_buffer: Vec::with_capacity(128),
-            feedback_buffer: Vec::with_capacity(128),
-            mix_buffer: Vec::with_capacity(128),
-        }
+        Self::new(
+            0.005,
+            0.5,
+            AudioParam::Static(0.005 * 44100.0),
+            AudioParam::Static(0.7),
+            AudioParam::Static(0.5),
+        )
     }
 
     /// Sets the modulation depth parameter.
@@ -159,18 +200,33 @@ impl FrameProcessor<Mono> for ModulatedDelay {
 
         self.base_delay = self.base_delay * sample_rate / old_sr;
 
-        let needed = (sample_rate * 0.1) as usize;
+        let needed = (sample_rate * MAX_DELAY_SECONDS) as usize;
         if needed > self.buffer.len() {
             self.buffer.resize(needed, 0.0);
         }
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.depth.prepare(max_block_size);
+        self.feedback.prepare(max_block_size);
+        self.mix.prepare(max_block_size);
+        self.depth_buffer.resize(max_block_size, 0.0);
+        self.feedback_buffer.resize(max_block_size, 0.0);
+        self.mix_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         self.buffer.fill(0.0);
         self.write_ptr = 0;
         self.lfo_phase = 0.0;
     }
 
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("ModulatedDelay", "depth", &self.depth, 0.0, self.base_delay);
+        report.check_param("ModulatedDelay", "feedback", &self.feedback, -1.0, 1.0);
+        report.check_param("ModulatedDelay", "mix", &self.mix, 0.0, 1.0);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "ModulatedDelay (Chorus/Flanger)"
@@ -0,0 +1,269 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
+use crate::effects::filter::biquad::{BiquadCoeffs, BiquadState, FilterType};
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+
+/// Cutoff of the filter that isolates the fundamental for tracking, in Hz.
+const TRACKING_CUTOFF: f32 = 900.0;
+/// Input level below which the sub voices are muted, roughly -60 dBFS.
+const GATE_LEVEL: f32 = 0.001;
+
+/// An analog-style octave divider.
+///
+/// The input is low-passed to its fundamental, and a Schmitt trigger toggles a flip-flop on
+/// every rising zero crossing, giving a square wave one octave down; a second flip-flop
+/// driven by the first gives two octaves down. Each square is scaled by the envelope of the
+/// fundamental, so the sub voices follow the playing dynamics, and low-passed by `tone`.
+/// Works on monophonic material; chords confuse the tracker.
+pub struct Octaver {
+    dry: AudioParam,
+    sub1: AudioParam,
+    sub2: AudioParam,
+    tone: AudioParam,
+    sample_rate: f32,
+
+    tracking: BiquadCoeffs,
+    tracking_state: [BiquadState; 2],
+    tone_coeffs: BiquadCoeffs,
+    tone_freq: f32,
+    sub1_state: BiquadState,
+    sub2_state: BiquadState,
+
+    envelope: f32,
+    release: f32,
+    high: bool,
+    flip1: bool,
+    flip2: bool,
+
+    param_buffer: Vec<f32>,
+}
+
+impl Octaver {
+    /// Creates a new Octaver with the dry signal at unity and an 800 Hz tone filter.
+    ///
+    /// # Arguments
+    /// * `sub1` - Level of the voice one octave down.
+    /// * `sub2` - Level of the voice two octaves down.
+    pub fn new(sub1: AudioParam, sub2: AudioParam) -> Self {
+        let sample_rate = 44100.0;
+        Octaver {
+            dry: AudioParam::Static(1.0),
+            sub1,
+            sub2,
+            tone: AudioParam::hz(800.0),
+            sample_rate,
+            tracking: Self::lowpass(TRACKING_CUTOFF, sample_rate),
+            tracking_state: [BiquadState::default(); 2],
+            tone_coeffs: Self::lowpass(800.0, sample_rate),
+            tone_freq: 800.0,
+            sub1_state: BiquadState::default(),
+            sub2_state: BiquadState::default(),
+            envelope: 0.0,
+            release: Self::release_coeff(sample_rate),
+            high: false,
+            flip1: false,
+            flip2: false,
+            param_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the level of the dry signal (default 1.0).
+    pub fn set_dry(&mut self, dry: AudioParam) {
+        self.dry = dry;
+    }
+
+    /// Sets the level of the voice one octave down.
+    pub fn set_sub1(&mut self, sub1: AudioParam) {
+        self.sub1 = sub1;
+    }
+
+    /// Sets the level of the voice two octaves down.
+    pub fn set_sub2(&mut self, sub2: AudioParam) {
+        self.sub2 = sub2;
+    }
+
+    /// Sets the cutoff of the low-pass filter on the sub voices (in Hz).
+    pub fn set_tone(&mut self, tone: AudioParam) {
+        self.tone = tone;
+    }
+
+    fn lowpass(freq: f32, sample_rate: f32) -> BiquadCoeffs {
+        let freq = freq.clamp(20.0, sample_rate * 0.45);
+        BiquadCoeffs::new(FilterType::LowPass, freq, 0.707, 0.0, sample_rate)
+    }
+
+    fn release_coeff(sample_rate: f32) -> f32 {
+        libm::expf(-1.0 / (0.05 * sample_rate))
+    }
+}
+
+impl FrameProcessor<Mono> for Octaver {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        if len == 0 {
+            return;
+        }
+        if self.param_buffer.len() < len {
+            self.param_buffer.resize(len, 0.0);
+        }
+
+        let buf = &mut self.param_buffer[0..len];
+        let dry = self.dry.block_value(buf, sample_index);
+        let sub1 = self.sub1.block_value(buf, sample_index);
+        let sub2 = self.sub2.block_value(buf, sample_index);
+        let tone = self.tone.block_value(buf, sample_index);
+        if tone != self.tone_freq {
+            self.tone_freq = tone;
+            self.tone_coeffs = Self::lowpass(tone, self.sample_rate);
+        }
+
+        for sample in buffer.iter_mut() {
+            let input = *sample;
+            let mut tracked = input;
+            for state in self.tracking_state.iter_mut() {
+                tracked = state.tick(&self.tracking, tracked);
+            }
+
+            let level = tracked.abs();
+            self.envelope = if level > self.envelope {
+                level
+            } else {
+                level + (self.envelope - level) * self.release
+            };
+
+            // Hysteresis relative to the envelope rejects ripple around the crossings.
+            let hysteresis = self.envelope * 0.1;
+            if !self.high && tracked > hysteresis {
+                self.high = true;
+                self.flip1 = !self.flip1;
+                if self.flip1 {
+                    self.flip2 = !self.flip2;
+                }
+            } else if self.high && tracked < -hysteresis {
+                self.high = false;
+            }
+
+            let (square1, square2) = if self.envelope > GATE_LEVEL {
+                (
+                    if self.flip1 {
+                        self.envelope
+                    } else {
+                        -self.envelope
+                    },
+                    if self.flip2 {
+                        self.envelope
+                    } else {
+                        -self.envelope
+                    },
+                )
+            } else {
+                (0.0, 0.0)
+            };
+            let voice1 = self.sub1_state.tick(&self.tone_coeffs, square1);
+            let voice2 = self.sub2_state.tick(&self.tone_coeffs, square2);
+
+            *sample = input * dry + voice1 * sub1 + voice2 * sub2;
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.tracking = Self::lowpass(TRACKING_CUTOFF, sample_rate);
+        self.tone_coeffs = Self::lowpass(self.tone_freq, sample_rate);
+        self.release = Self::release_coeff(sample_rate);
+        self.dry.set_sample_rate(sample_rate);
+        self.sub1.set_sample_rate(sample_rate);
+        self.sub2.set_sample_rate(sample_rate);
+        self.tone.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.param_buffer.resize(max_block_size, 0.0);
+        self.dry.prepare(max_block_size);
+        self.sub1.prepare(max_block_size);
+        self.sub2.prepare(max_block_size);
+        self.tone.prepare(max_block_size);
+    }
+
+    fn reset(&mut self) {
+        self.tracking_state = [BiquadState::default(); 2];
+        self.sub1_state = BiquadState::default();
+        self.sub2_state = BiquadState::default();
+        self.envelope = 0.0;
+        self.high = false;
+        self.flip1 = false;
+        self.flip2 = false;
+        self.dry.reset();
+        self.sub1.reset();
+        self.sub2.reset();
+        self.tone.reset();
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("dry", &self.dry, 0.0, 1.0);
+        tree.add_audio_param("sub1", &self.sub1, 0.0, 1.0);
+        tree.add_audio_param("sub2", &self.sub2, 0.0, 1.0);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("Octaver", "dry", &self.dry, 0.0, 2.0);
+        report.check_param("Octaver", "sub1", &self.sub1, 0.0, 2.0);
+        report.check_param("Octaver", "sub2", &self.sub2, 0.0, 2.0);
+        report.check_param("Octaver", "tone", &self.tone, 20.0, 20000.0);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "Octaver"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::PI;
+
+    fn rising_crossings(signal: &[f32]) -> usize {
+        let peak = signal.iter().fold(0.0f32, |p, s| p.max(s.abs()));
+        let mut high = false;
+        let mut count = 0;
+        for &s in signal {
+            if !high && s > peak * 0.2 {
+                high = true;
+                count += 1;
+            } else if high && s < -peak * 0.2 {
+                high = false;
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn test_sub_voices_divide_frequency() {
+        let render = |sub1: f32, sub2: f32| {
+            let mut octaver = Octaver::new(AudioParam::Static(sub1), AudioParam::Static(sub2));
+            octaver.set_dry(AudioParam::Static(0.0));
+            let mut buffer: Vec<f32> = (0..2 * 44100)
+                .map(|i| 0.5 * libm::sinf(2.0 * PI * 220.0 * i as f32 / 44100.0))
+                .collect();
+            for block in buffer.chunks_mut(128) {
+                octaver.process(block, 0);
+            }
+            buffer.split_off(44100)
+        };
+
+        let one_down = rising_crossings(&render(1.0, 0.0));
+        let two_down = rising_crossings(&render(0.0, 1.0));
+        assert!((one_down as i32 - 110).abs() <= 2, "{}", one_down);
+        assert!((two_down as i32 - 55).abs() <= 2, "{}", two_down);
+
+        // Silence keeps the sub voices quiet.
+        let mut octaver = Octaver::new(AudioParam::Static(1.0), AudioParam::Static(1.0));
+        let mut buffer = [0.0; 512];
+        octaver.process(&mut buffer, 0);
+        assert!(buffer.iter().all(|s| *s == 0.0));
+    }
+}
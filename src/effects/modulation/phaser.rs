@@ -188,6 +188,19 @@ impl FrameProcessor<Mono> for Phaser {
         self.mix.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.rate.prepare(max_block_size);
+        self.min_freq.prepare(max_block_size);
+        self.max_freq.prepare(max_block_size);
+        self.feedback.prepare(max_block_size);
+        self.mix.prepare(max_block_size);
+        self.rate_buffer.resize(max_block_size, 0.0);
+        self.min_freq_buffer.resize(max_block_size, 0.0);
+        self.max_freq_buffer.resize(max_block_size, 0.0);
+        self.feedback_buffer.resize(max_block_size, 0.0);
+        self.mix_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         for filter in &mut self.filters {
             filter.reset();
@@ -88,6 +88,13 @@ impl FrameProcessor<Mono> for RingMod {
         self.mix.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.freq.prepare(max_block_size);
+        self.mix.prepare(max_block_size);
+        self.freq_buffer.resize(max_block_size, 0.0);
+        self.mix_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         self.phase = 0.0;
     }
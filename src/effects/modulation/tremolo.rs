@@ -90,6 +90,13 @@ impl FrameProcessor<Mono> for Tremolo {
         self.rate.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.depth.prepare(max_block_size);
+        self.rate.prepare(max_block_size);
+        self.depth_buffer.resize(max_block_size, 0.0);
+        self.rate_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         self.phase = 0.0;
     }
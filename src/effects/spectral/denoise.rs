@@ -0,0 +1,260 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::ola::SpectralProcessor;
+use crate::synthesis::envelope::Trigger;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+use num_complex::{Complex32, ComplexFloat};
+
+/// A spectral noise gate (denoiser).
+///
+/// Learns the average magnitude of each bin while noise alone is playing, then attenuates
+/// every bin that doesn't rise far enough above its learned level. Wrap it in an `Ola` or
+/// `RealOla`: `RealOla::<_, 1024>::with(SpectralGate::new(...))`.
+///
+/// Learning runs for the learn time (default 0.5 s) after a [`learn`](Self::learn) call
+/// or a fired trigger; the signal passes unchanged meanwhile, and until a profile exists.
+/// The per-bin gains are smoothed over time to avoid "musical noise" artifacts.
+pub struct SpectralGate<const N: usize> {
+    threshold: AudioParam,
+    reduction: AudioParam,
+    smoothing: AudioParam,
+    sample_rate: f32,
+    learn_time: f32,
+    hop_size: usize,
+
+    profile: [f32; N],
+    gains: [f32; N],
+    learned_frames: u32,
+    learn_remaining: u32,
+    has_profile: bool,
+
+    param_buffer: Vec<f32>,
+    learn_flag: Arc<AtomicBool>,
+}
+
+impl<const N: usize> SpectralGate<N> {
+    /// Creates a new SpectralGate.
+    ///
+    /// # Arguments
+    /// * `threshold` - How far above the noise profile a bin must be to pass, in dB (e.g. 6 - 18).
+    /// * `reduction` - Attenuation of gated bins, in dB (0 = none, 60 = near silence).
+    /// * `smoothing` - Temporal smoothing of the bin gains (0.0 - 1.0).
+    pub fn new(threshold: AudioParam, reduction: AudioParam, smoothing: AudioParam) -> Self {
+        SpectralGate {
+            threshold,
+            reduction,
+            smoothing,
+            sample_rate: 44100.0,
+            learn_time: 0.5,
+            hop_size: N / 2,
+            profile: [0.0; N],
+            gains: [1.0; N],
+            learned_frames: 0,
+            learn_remaining: 0,
+            has_profile: false,
+            param_buffer: Vec::with_capacity(128),
+            learn_flag: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Sets how long each learning pass listens, in seconds, and returns the gate.
+    pub fn with_learn_time(mut self, seconds: f32) -> Self {
+        self.learn_time = seconds.max(0.0);
+        self
+    }
+
+    /// Starts learning a new noise profile, replacing the current one.
+    pub fn learn(&mut self) {
+        let hop_size = self.hop_size as f32;
+        self.learn_remaining = ((self.learn_time * self.sample_rate / hop_size) as u32).max(1);
+        self.learned_frames = 0;
+        self.profile.fill(0.0);
+    }
+
+    /// Creates a trigger handle that starts learning from any thread.
+    pub fn create_trigger(&self) -> Trigger {
+        Trigger::from_flag(&self.learn_flag)
+    }
+
+    /// Returns true while a noise profile is being learned.
+    pub fn is_learning(&self) -> bool {
+        self.learn_remaining > 0
+    }
+
+    /// Returns the learned magnitude of each bin, or `None` if nothing was learned yet.
+    pub fn noise_profile(&self) -> Option<&[f32]> {
+        self.has_profile.then_some(&self.profile[..])
+    }
+
+    /// Sets the threshold parameter in dB.
+    pub fn set_threshold(&mut self, threshold: AudioParam) {
+        self.threshold = threshold;
+    }
+
+    /// Sets the reduction parameter in dB.
+    pub fn set_reduction(&mut self, reduction: AudioParam) {
+        self.reduction = reduction;
+    }
+
+    /// Sets the smoothing parameter.
+    pub fn set_smoothing(&mut self, smoothing: AudioParam) {
+        self.smoothing = smoothing;
+    }
+}
+
+impl<const N: usize> SpectralProcessor for SpectralGate<N> {
+    fn process_spectral(&mut self, bins: &mut [Complex32], sample_index: u64) {
+        if bins.len() != N && bins.len() != N / 2 + 1 {
+            return;
+        }
+
+        if self.learn_flag.swap(false, Ordering::Relaxed) {
+            self.learn();
+        }
+
+        let half_n = N / 2;
+        let full = bins.len() == N;
+        if self.learn_remaining > 0 {
+            // Running mean of each bin's magnitude.
+            self.learned_frames += 1;
+            let weight = 1.0 / self.learned_frames as f32;
+            for (profile, bin) in self.profile.iter_mut().zip(bins.iter()).take(half_n + 1) {
+                *profile += (bin.abs() - *profile) * weight;
+            }
+            self.learn_remaining -= 1;
+            if self.learn_remaining == 0 {
+                self.has_profile = true;
+                self.gains.fill(1.0);
+            }
+            return;
+        }
+        if !self.has_profile {
+            return;
+        }
+
+        let hop_size = self.hop_size;
+        if self.param_buffer.len() != hop_size {
+            self.param_buffer.resize(hop_size, 0.0);
+        }
+        let threshold_db = self
+            .threshold
+            .block_value(&mut self.param_buffer, sample_index);
+        let reduction_db = self
+            .reduction
+            .block_value(&mut self.param_buffer, sample_index);
+        let smoothing = self
+            .smoothing
+            .block_value(&mut self.param_buffer, sample_index)
+            .clamp(0.0, 0.999);
+
+        let threshold = libm::powf(10.0, threshold_db.max(0.0) / 20.0);
+        let floor = libm::powf(10.0, -reduction_db.max(0.0) / 20.0);
+
+        for i in 0..=half_n {
+            let target = if bins[i].abs() > self.profile[i] * threshold {
+                1.0
+            } else {
+                floor
+            };
+            self.gains[i] = target + (self.gains[i] - target) * smoothing;
+
+            bins[i] *= self.gains[i];
+            if i > 0 && i < half_n && full {
+                bins[N - i] = bins[i].conj();
+            }
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.threshold.set_sample_rate(sample_rate);
+        self.reduction.set_sample_rate(sample_rate);
+        self.smoothing.set_sample_rate(sample_rate);
+    }
+
+    fn reset(&mut self) {
+        // The learned profile is kept; only the gain smoothing starts over.
+        self.gains.fill(1.0);
+        self.threshold.reset();
+        self.reduction.reset();
+        self.smoothing.reset();
+    }
+
+    fn prepare(&mut self, hop_size: usize) {
+        self.hop_size = hop_size;
+        self.threshold.prepare(hop_size);
+        self.reduction.prepare(hop_size);
+        self.smoothing.prepare(hop_size);
+        self.param_buffer.resize(hop_size, 0.0);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "SpectralGate"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ola::RealOla;
+    use crate::core::utils::FastRng;
+    use crate::FrameProcessor;
+    use core::f32::consts::PI;
+
+    fn rms(buffer: &[f32]) -> f32 {
+        libm::sqrtf(buffer.iter().map(|s| s * s).sum::<f32>() / buffer.len() as f32)
+    }
+
+    fn new_gate() -> SpectralGate<512> {
+        SpectralGate::new(
+            AudioParam::Static(12.0),
+            AudioParam::Static(40.0),
+            AudioParam::Static(0.5),
+        )
+    }
+
+    fn run<P: FrameProcessor<crate::core::channels::Mono>>(
+        processor: &mut P,
+        input: &[f32],
+    ) -> f32 {
+        let mut buffer = input.to_vec();
+        for block in buffer.chunks_mut(128) {
+            processor.process(block, 0);
+        }
+        rms(&buffer[11025..])
+    }
+
+    #[test]
+    fn test_learns_and_removes_noise() {
+        let gate = new_gate();
+        let trigger = gate.create_trigger();
+        let mut ola = RealOla::<_, 512>::with(gate);
+        // Without a profile the gate passes everything: the reference level.
+        let mut reference = RealOla::<_, 512>::with(new_gate());
+
+        let mut rng = FastRng::new(7);
+        let mut noise = |frames: usize| -> Vec<f32> {
+            (0..frames).map(|_| rng.next_f32_bipolar() * 0.05).collect()
+        };
+
+        // Half a second of noise alone passes unchanged while it is learned.
+        trigger.fire();
+        let input = noise(22050);
+        let learning = run(&mut ola, &input);
+        assert!((learning / run(&mut reference, &input) - 1.0).abs() < 1e-3);
+
+        let input = noise(22050);
+        assert!(run(&mut ola, &input) < run(&mut reference, &input) * 0.1);
+
+        // A tone well above the noise floor survives.
+        let input: Vec<f32> = noise(22050)
+            .iter()
+            .enumerate()
+            .map(|(i, n)| n + 0.3 * libm::sinf(i as f32 * 2.0 * PI * 1000.0 / 44100.0))
+            .collect();
+        let ratio = run(&mut ola, &input) / run(&mut reference, &input);
+        assert!(ratio > 0.95 && ratio < 1.05);
+    }
+}
@@ -127,6 +127,11 @@ impl FrameProcessor<Mono> for GranularPitchShift {
         }
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.semitones.prepare(max_block_size);
+        self.semitones_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         self.buffer.fill(0.0);
         self.write_ptr = 0;
@@ -1,3 +1,4 @@
+pub mod denoise;
 pub mod granular_pitch;
 pub mod pitch_shift;
 pub mod spectral_smear;
@@ -17,6 +17,7 @@ pub struct FftPitchShift<const N: usize> {
     synthesis_freqs: [f32; N],
     semitones: AudioParam,
     factor: f32,
+    hop_size: usize,
     semitones_buffer: Vec<f32>,
 }
 
@@ -35,6 +36,7 @@ impl<const N: usize> FftPitchShift<N> {
             synthesis_freqs: [0.0; N],
             semitones,
             factor: 1.0,
+            hop_size: N / 2,
             semitones_buffer: Vec::with_capacity(128),
         }
     }
@@ -46,7 +48,8 @@ impl<const N: usize> FftPitchShift<N> {
 
     fn process_phase_vocoder(&mut self, bins: &mut [Complex32]) {
         let half_n = N / 2;
-        let hop_size = N / 2;
+        let full = bins.len() == N;
+        let hop_size = self.hop_size;
         let expect = 2.0 * PI * hop_size as f32 / N as f32;
 
         for (k, bin) in bins.iter().enumerate().take(half_n + 1) {
@@ -100,7 +103,7 @@ impl<const N: usize> FftPitchShift<N> {
             let bin = Complex32::from_polar(mag, p);
             bins[k] = bin;
 
-            if k > 0 && k < half_n {
+            if k > 0 && k < half_n && full {
                 bins[N - k] = bin.conj();
             }
         }
@@ -114,11 +117,11 @@ impl<const N: usize> FftPitchShift<N> {
 
 impl<const N: usize> SpectralProcessor for FftPitchShift<N> {
     fn process_spectral(&mut self, bins: &mut [Complex32], sample_index: u64) {
-        if bins.len() != N {
+        if bins.len() != N && bins.len() != N / 2 + 1 {
             return;
         }
 
-        let hop_size = N / 2;
+        let hop_size = self.hop_size;
 
         if self.semitones_buffer.len() != hop_size {
             self.semitones_buffer.resize(hop_size, 0.0);
@@ -134,6 +137,12 @@ impl<const N: usize> SpectralProcessor for FftPitchShift<N> {
         self.process_phase_vocoder(bins);
     }
 
+    fn prepare(&mut self, hop_size: usize) {
+        self.hop_size = hop_size;
+        self.semitones.prepare(hop_size);
+        self.semitones_buffer.resize(hop_size, 0.0);
+    }
+
     fn set_sample_rate(&mut self, sample_rate: f32) {
         self.semitones.set_sample_rate(sample_rate);
     }
@@ -12,6 +12,7 @@ use num_complex::{Complex32, ComplexFloat};
 pub struct SpectralSmear<const N: usize> {
     smear: AudioParam,
     prev_magnitudes: [f32; N],
+    hop_size: usize,
     smear_buffer: Vec<f32>,
     rng: FastRng,
 }
@@ -25,6 +26,7 @@ impl<const N: usize> SpectralSmear<N> {
         SpectralSmear {
             smear,
             prev_magnitudes: [0.0; N],
+            hop_size: N / 2,
             smear_buffer: Vec::with_capacity(128),
             rng: FastRng::new(12345),
         }
@@ -38,11 +40,11 @@ impl<const N: usize> SpectralSmear<N> {
 
 impl<const N: usize> SpectralProcessor for SpectralSmear<N> {
     fn process_spectral(&mut self, bins: &mut [Complex32], sample_index: u64) {
-        if bins.len() != N {
+        if bins.len() != N && bins.len() != N / 2 + 1 {
             return;
         }
 
-        let hop_size = N / 2;
+        let hop_size = self.hop_size;
         if self.smear_buffer.len() != hop_size {
             self.smear_buffer.resize(hop_size, 0.0);
         }
@@ -52,6 +54,7 @@ impl<const N: usize> SpectralProcessor for SpectralSmear<N> {
         let one_minus_s = 1.0 - s;
 
         let half_n = N / 2;
+        let full = bins.len() == N;
         for i in 0..=half_n {
             let (mag, _) = bins[i].to_polar();
 
@@ -62,12 +65,18 @@ impl<const N: usize> SpectralProcessor for SpectralSmear<N> {
             let new_bin = Complex32::from_polar(smoothed_mag, phase);
             bins[i] = new_bin;
 
-            if i > 0 && i < half_n {
+            if i > 0 && i < half_n && full {
                 bins[N - i] = new_bin.conj();
             }
         }
     }
 
+    fn prepare(&mut self, hop_size: usize) {
+        self.hop_size = hop_size;
+        self.smear.prepare(hop_size);
+        self.smear_buffer.resize(hop_size, 0.0);
+    }
+
     fn set_sample_rate(&mut self, sample_rate: f32) {
         self.smear.set_sample_rate(sample_rate);
     }
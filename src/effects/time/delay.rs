@@ -1,15 +1,89 @@
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::Mono;
+use crate::core::denormal::flush_denormal;
+use crate::core::error::{DspError, ValidationReport};
+use crate::core::preset::ParameterTree;
+use crate::core::tempo::{NoteValue, TransportClock};
 use crate::FrameProcessor;
 use alloc::vec;
 use alloc::vec::Vec;
 
 const PARAM_CHUNK_SIZE: usize = 64;
 
-/// A digital delay effect with linear interpolation.
+/// Length in samples of the crossfade when a constant delay time jumps.
+const JUMP_FADE_SAMPLES: u32 = 64;
+
+/// Returns an error unless `max_delay_seconds` is positive.
+pub(crate) fn check_max_delay(
+    processor: &'static str,
+    max_delay_seconds: f32,
+) -> Result<(), DspError> {
+    if max_delay_seconds.is_nan() || max_delay_seconds <= 0.0 {
+        return Err(DspError::Invalid {
+            processor,
+            message: "max_delay_seconds must be positive.",
+        });
+    }
+    Ok(())
+}
+
+/// How a [`Delay`] reads between samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DelayInterpolation {
+    /// Two-point linear interpolation. Cheap, but dulls the highs slightly at fractional
+    /// delay times.
+    #[default]
+    Linear,
+    /// Four-point cubic (Catmull-Rom) interpolation, for cleaner modulated delays.
+    Cubic,
+}
+
+/// Reads a circular buffer at a fractional position in `0.0..len`, interpolating linearly.
+#[inline(always)]
+pub(crate) fn read_linear(buffer: &[f32], position: f32) -> f32 {
+    let len = buffer.len();
+    let idx_a = position as usize;
+    let mut idx_b = idx_a + 1;
+    if idx_b >= len {
+        idx_b -= len;
+    }
+    let frac = position - idx_a as f32;
+    buffer[idx_a] * (1.0 - frac) + buffer[idx_b] * frac
+}
+
+/// Reads a circular buffer at a fractional position in `0.0..len`, interpolating with a
+/// four-point Catmull-Rom spline.
+#[inline(always)]
+pub(crate) fn read_cubic(buffer: &[f32], position: f32) -> f32 {
+    let len = buffer.len();
+    let idx = position as usize;
+    let prev = if idx == 0 { len - 1 } else { idx - 1 };
+    let next = if idx + 1 >= len {
+        idx + 1 - len
+    } else {
+        idx + 1
+    };
+    let next2 = if next + 1 >= len {
+        next + 1 - len
+    } else {
+        next + 1
+    };
+    let frac = position - idx as f32;
+
+    let (a, b, c, d) = (buffer[prev], buffer[idx], buffer[next], buffer[next2]);
+    let c1 = 0.5 * (c - a);
+    let c2 = a - 2.5 * b + 2.0 * c - 0.5 * d;
+    let c3 = 0.5 * (d - a) + 1.5 * (b - c);
+    ((c3 * frac + c2) * frac + c1) * frac + b
+}
+
+/// A digital delay effect with linear or cubic interpolation.
 ///
 /// Provides a clean delay line with feedback and dry/wet mix control.
-/// Supports sample-accurate modulation of delay time.
+/// Supports sample-accurate modulation of delay time. While the delay time is constant
+/// over a block, it is read once per block instead; when such a constant time jumps (e.g.
+/// a linked parameter set without glide), the output crossfades from the old to the new
+/// delay over 64 samples instead of clicking.
 pub struct Delay {
     buffer: Vec<f32>,
     write_ptr: usize,
@@ -21,6 +95,10 @@ pub struct Delay {
     delay_buffer: [f32; PARAM_CHUNK_SIZE],
     feedback_buffer: [f32; PARAM_CHUNK_SIZE],
     mix_buffer: [f32; PARAM_CHUNK_SIZE],
+    interpolation: DelayInterpolation,
+    last_delay: Option<f32>,
+    fade_from: f32,
+    fade_pos: u32,
 }
 
 impl Delay {
@@ -51,14 +129,49 @@ impl Delay {
             delay_buffer: [0.0; PARAM_CHUNK_SIZE],
             feedback_buffer: [0.0; PARAM_CHUNK_SIZE],
             mix_buffer: [0.0; PARAM_CHUNK_SIZE],
+            interpolation: DelayInterpolation::Linear,
+            last_delay: None,
+            fade_from: 0.0,
+            fade_pos: JUMP_FADE_SAMPLES,
         }
     }
 
+    /// Sets the interpolation and returns the delay.
+    pub fn with_interpolation(mut self, interpolation: DelayInterpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Sets the interpolation.
+    pub fn set_interpolation(&mut self, interpolation: DelayInterpolation) {
+        self.interpolation = interpolation;
+    }
+
+    /// Creates a new Delay, or returns the first problem with the arguments.
+    ///
+    /// Fails if `max_delay_seconds` is not positive or a static parameter is out of range.
+    pub fn try_new(
+        max_delay_seconds: f32,
+        delay_time: AudioParam,
+        feedback: AudioParam,
+        mix: AudioParam,
+    ) -> Result<Self, DspError> {
+        check_max_delay("Delay", max_delay_seconds)?;
+        let delay = Self::new(max_delay_seconds, delay_time, feedback, mix);
+        ValidationReport::of::<Mono, _>(&delay).into_first_error()?;
+        Ok(delay)
+    }
+
     /// Sets the delay time parameter.
     pub fn set_delay_time(&mut self, delay_time: AudioParam) {
         self.delay_time = delay_time;
     }
 
+    /// Locks the delay time to a note length at the tempo of `clock`.
+    pub fn set_delay_note(&mut self, note: NoteValue, clock: &TransportClock) {
+        self.delay_time = AudioParam::synced(note, clock);
+    }
+
     /// Sets the feedback parameter.
     pub fn set_feedback(&mut self, feedback: AudioParam) {
         self.feedback = feedback;
@@ -70,21 +183,81 @@ impl Delay {
     }
 }
 
+impl Delay {
+    /// Reads the line `delay_samples` behind the write position.
+    #[inline(always)]
+    fn read(&self, delay_samples: f32) -> f32 {
+        let len_f = self.buffer.len() as f32;
+        let mut position = self.write_ptr as f32 - delay_samples;
+        while position < 0.0 {
+            position += len_f;
+        }
+        while position >= len_f {
+            position -= len_f;
+        }
+        match self.interpolation {
+            DelayInterpolation::Linear => read_linear(&self.buffer, position),
+            DelayInterpolation::Cubic => read_cubic(&self.buffer, position),
+        }
+    }
+
+    /// Writes the next sample and advances the line, returning the mixed output.
+    #[inline(always)]
+    fn write(&mut self, input: f32, delayed: f32, feedback: f32, mix: f32) -> f32 {
+        self.buffer[self.write_ptr] = flush_denormal(input + delayed * feedback);
+        self.write_ptr += 1;
+        if self.write_ptr >= self.buffer.len() {
+            self.write_ptr = 0;
+        }
+        input * (1.0 - mix) + delayed * mix
+    }
+
+    fn process_constant(&mut self, chunk: &mut [f32], delay_samples: f32) {
+        if let Some(last) = self.last_delay {
+            if last != delay_samples {
+                self.fade_from = last;
+                self.fade_pos = 0;
+            }
+        }
+        self.last_delay = Some(delay_samples);
+
+        for (i, sample) in chunk.iter_mut().enumerate() {
+            let mut delayed = self.read(delay_samples);
+            if self.fade_pos < JUMP_FADE_SAMPLES {
+                let old = self.read(self.fade_from);
+                let gain = self.fade_pos as f32 / JUMP_FADE_SAMPLES as f32;
+                delayed = old + (delayed - old) * gain;
+                self.fade_pos += 1;
+            }
+            let (fb, mix) = (self.feedback_buffer[i], self.mix_buffer[i]);
+            *sample = self.write(*sample, delayed, fb, mix);
+        }
+    }
+
+    fn process_modulated(&mut self, chunk: &mut [f32]) {
+        for (i, sample) in chunk.iter_mut().enumerate() {
+            let delay_samples = self.delay_buffer[i] * self.sample_rate;
+            let delayed = self.read(delay_samples);
+            let (fb, mix) = (self.feedback_buffer[i], self.mix_buffer[i]);
+            *sample = self.write(*sample, delayed, fb, mix);
+            self.last_delay = Some(delay_samples);
+        }
+        // A modulated time moves smoothly; there is no jump to fade.
+        self.fade_pos = JUMP_FADE_SAMPLES;
+    }
+}
+
 impl FrameProcessor<Mono> for Delay {
     fn process(&mut self, buffer: &mut [f32], start_sample_index: u64) {
-        let len = self.buffer.len();
-        if len == 0 {
+        if self.buffer.is_empty() {
             return;
         }
-        let len_f = len as f32;
 
         let mut current_sample_index = start_sample_index;
 
         for chunk in buffer.chunks_mut(PARAM_CHUNK_SIZE) {
             let chunk_len = chunk.len();
 
-            self.delay_time
-                .process(&mut self.delay_buffer[0..chunk_len], current_sample_index);
             self.feedback.process(
                 &mut self.feedback_buffer[0..chunk_len],
                 current_sample_index,
@@ -92,39 +265,12 @@ impl FrameProcessor<Mono> for Delay {
             self.mix
                 .process(&mut self.mix_buffer[0..chunk_len], current_sample_index);
 
-            for (i, sample) in chunk.iter_mut().enumerate() {
-                let input = *sample;
-
-                let delay_seconds = self.delay_buffer[i];
-                let fb = self.feedback_buffer[i];
-                let mix = self.mix_buffer[i];
-
-                let delay_samples = delay_seconds * self.sample_rate;
-                let read_ptr_f = self.write_ptr as f32 - delay_samples;
-
-                let mut read_ptr_norm = read_ptr_f;
-                while read_ptr_norm < 0.0 {
-                    read_ptr_norm += len_f;
-                }
-                while read_ptr_norm >= len_f {
-                    read_ptr_norm -= len_f;
-                }
-
-                let idx_a = read_ptr_norm as usize;
-                let mut idx_b = idx_a + 1;
-                if idx_b >= len {
-                    idx_b -= len;
-                }
-                let frac = read_ptr_norm - idx_a as f32;
-
-                let delayed = self.buffer[idx_a] * (1.0 - frac) + self.buffer[idx_b] * frac;
-                let next_val = input + delayed * fb;
-                self.buffer[self.write_ptr] = next_val;
-
-                *sample = input * (1.0 - mix) + delayed * mix;
-                self.write_ptr += 1;
-                if self.write_ptr >= len {
-                    self.write_ptr -= len;
+            match self.delay_time.get_constant() {
+                Some(seconds) => self.process_constant(chunk, seconds * self.sample_rate),
+                None => {
+                    self.delay_time
+                        .process(&mut self.delay_buffer[0..chunk_len], current_sample_index);
+                    self.process_modulated(chunk);
                 }
             }
 
@@ -144,14 +290,40 @@ impl FrameProcessor<Mono> for Delay {
         }
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.delay_time.prepare(max_block_size);
+        self.feedback.prepare(max_block_size);
+        self.mix.prepare(max_block_size);
+    }
+
     fn reset(&mut self) {
         self.buffer.fill(0.0);
         self.write_ptr = 0;
+        self.last_delay = None;
+        self.fade_pos = JUMP_FADE_SAMPLES;
         self.delay_time.reset();
         self.feedback.reset();
         self.mix.reset();
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("delay_time", &self.delay_time, 0.0, self.max_delay_seconds);
+        tree.add_audio_param("feedback", &self.feedback, 0.0, 1.0);
+        tree.add_audio_param("mix", &self.mix, 0.0, 1.0);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param(
+            "Delay",
+            "delay_time",
+            &self.delay_time,
+            0.0,
+            self.max_delay_seconds,
+        );
+        report.check_param("Delay", "feedback", &self.feedback, 0.0, 1.0);
+        report.check_param("Delay", "mix", &self.mix, 0.0, 1.0);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "Delay (Digital)"
@@ -178,4 +350,48 @@ mod tests {
         assert_eq!(buffer[0], 0.0);
         assert!((buffer[1] - 0.5).abs() < 1e-5);
     }
+
+    #[test]
+    fn test_cubic_interpolation_and_jump_fade() {
+        use crate::core::parameter::Parameter;
+        use core::f32::consts::PI;
+
+        // A sine delayed by 10.5 samples: cubic lands much closer to the exact value.
+        let error = |interpolation| {
+            let mut delay = Delay::new(
+                0.1,
+                AudioParam::Static(10.5 / 44100.0),
+                AudioParam::Static(0.0),
+                AudioParam::Static(1.0),
+            )
+            .with_interpolation(interpolation);
+            let omega = 2.0 * PI * 5000.0 / 44100.0;
+            let mut buffer: Vec<f32> = (0..256).map(|i| libm::sinf(omega * i as f32)).collect();
+            delay.process(&mut buffer, 0);
+            (100..256)
+                .map(|i| (buffer[i] - libm::sinf(omega * (i as f32 - 10.5))).abs())
+                .fold(0.0, f32::max)
+        };
+        let (linear, cubic) = (
+            error(DelayInterpolation::Linear),
+            error(DelayInterpolation::Cubic),
+        );
+        assert!(cubic < linear * 0.25, "{} vs {}", cubic, linear);
+
+        // Jumping a linked time fades between the two taps instead of switching.
+        let time = Parameter::new(0.001);
+        let mut delay = Delay::new(
+            0.1,
+            AudioParam::Linked(time.clone()),
+            AudioParam::Static(0.0),
+            AudioParam::Static(1.0),
+        );
+        let mut ramp: Vec<f32> = (0..512).map(|i| i as f32).collect();
+        delay.process(&mut ramp[0..256], 0);
+        time.set(0.002);
+        delay.process(&mut ramp[256..], 256);
+        let mut steps = ramp[256..].windows(2).map(|w| w[1] - w[0]);
+        assert!(steps.all(|step| (-0.5..=1.0).contains(&step)));
+        assert!((ramp[511] - (511.0 - 88.2)).abs() < 1e-2);
+    }
 }
@@ -0,0 +1,268 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Stereo;
+use crate::core::error::{DspError, ValidationReport};
+use crate::effects::time::delay::read_linear;
+use crate::effects::time::reverb::Allpass;
+use crate::FrameProcessor;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Length of the reflection pattern at room size 1.0.
+const MAX_PATTERN_SECONDS: f32 = 0.08;
+/// Shortest pattern, so a room size of 0.0 still produces distinct taps.
+const MIN_PATTERN_SECONDS: f32 = 0.002;
+const MAX_TAPS: usize = 64;
+const DIFFUSER_TUNING_L: [usize; 2] = [113, 337];
+const DIFFUSER_TUNING_R: [usize; 2] = [127, 353];
+
+struct Tap {
+    /// Arrival time as a fraction of the pattern length.
+    position: f32,
+    gain: f32,
+    /// Side of the room the reflection comes from (-1.0 = left, 1.0 = right).
+    pan: f32,
+}
+
+/// An early reflections generator.
+///
+/// Feeds the (mono-summed) input through a multi-tap delay whose taps model the first
+/// reflections off the walls of a room: their arrival times scale with the room size,
+/// they get quieter towards the end of the pattern and alternate between the left and
+/// right side according to the stereo spread. A short allpass diffuser blurs the taps.
+///
+/// The output is 100% wet. Chain it before a [`Reverb`](crate::effects::time::reverb::Reverb)
+/// to excite the tank with a room-like pattern instead of a bare impulse.
+pub struct EarlyReflections {
+    taps: Vec<Tap>,
+    room_size: AudioParam,
+    spread: AudioParam,
+    diffusion: AudioParam,
+
+    delay_line: Vec<f32>,
+    write_ptr: usize,
+    diffusers_l: [Allpass; 2],
+    diffusers_r: [Allpass; 2],
+    sample_rate: f32,
+
+    room_buffer: Vec<f32>,
+    spread_buffer: Vec<f32>,
+    diffusion_buffer: Vec<f32>,
+}
+
+impl EarlyReflections {
+    /// Creates a new EarlyReflections.
+    ///
+    /// # Arguments
+    /// * `room_size` - Room size (0.0 - 1.0), scaling the pattern up to 80 ms.
+    /// * `spread` - Stereo spread of the reflections (0.0 = centered, 1.0 = full width).
+    /// * `taps` - Number of reflections (1 - 64).
+    pub fn new(room_size: AudioParam, spread: AudioParam, taps: usize) -> Self {
+        Self::try_new(room_size, spread, taps).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a new EarlyReflections, or returns the first problem with the arguments.
+    pub fn try_new(
+        room_size: AudioParam,
+        spread: AudioParam,
+        taps: usize,
+    ) -> Result<Self, DspError> {
+        DspError::check_count("EarlyReflections", "taps", taps, 1, MAX_TAPS)?;
+
+        let mut seed = 0x2545_f491u32;
+        let mut random = move || {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            (seed >> 8) as f32 / (1u32 << 24) as f32
+        };
+
+        let norm = 1.0 / libm::sqrtf(taps as f32);
+        let taps = (0..taps)
+            .map(|i| {
+                let position =
+                    ((i as f32 + 0.5 + 0.8 * (random() - 0.5)) / taps as f32).clamp(0.0, 1.0);
+                let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+                Tap {
+                    position,
+                    gain: sign * (1.0 - 0.7 * position) * norm,
+                    pan: -sign * (0.5 + 0.5 * random()),
+                }
+            })
+            .collect();
+
+        let sample_rate = 44100.0;
+        let reflections = EarlyReflections {
+            taps,
+            room_size,
+            spread,
+            diffusion: AudioParam::Static(0.0),
+            delay_line: vec![0.0; (MAX_PATTERN_SECONDS * sample_rate) as usize + 2],
+            write_ptr: 0,
+            diffusers_l: DIFFUSER_TUNING_L.map(Allpass::new),
+            diffusers_r: DIFFUSER_TUNING_R.map(Allpass::new),
+            sample_rate,
+            room_buffer: Vec::with_capacity(128),
+            spread_buffer: Vec::with_capacity(128),
+            diffusion_buffer: Vec::with_capacity(128),
+        };
+        ValidationReport::of::<Stereo, _>(&reflections).into_first_error()?;
+        Ok(reflections)
+    }
+
+    /// Sets the room size parameter (0.0 - 1.0).
+    pub fn set_room_size(&mut self, room_size: AudioParam) {
+        self.room_size = room_size;
+    }
+
+    /// Sets the stereo spread parameter (0.0 - 1.0).
+    pub fn set_spread(&mut self, spread: AudioParam) {
+        self.spread = spread;
+    }
+
+    /// Sets the diffusion parameter (0.0 = discrete taps, 1.0 = fully diffused).
+    pub fn set_diffusion(&mut self, diffusion: AudioParam) {
+        self.diffusion = diffusion;
+    }
+}
+
+impl FrameProcessor<Stereo> for EarlyReflections {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let frames = buffer.len() / 2;
+
+        for buf in [
+            &mut self.room_buffer,
+            &mut self.spread_buffer,
+            &mut self.diffusion_buffer,
+        ] {
+            if buf.len() < frames {
+                buf.resize(frames, 0.0);
+            }
+        }
+        self.room_size
+            .process(&mut self.room_buffer[0..frames], sample_index);
+        self.spread
+            .process(&mut self.spread_buffer[0..frames], sample_index);
+        self.diffusion
+            .process(&mut self.diffusion_buffer[0..frames], sample_index);
+
+        let len = self.delay_line.len();
+        let len_f = len as f32;
+        let max_length = len_f - 2.0;
+
+        for (i, frame) in buffer.chunks_exact_mut(2).enumerate() {
+            self.delay_line[self.write_ptr] = (frame[0] + frame[1]) * 0.5;
+
+            let pattern_seconds = MIN_PATTERN_SECONDS
+                + self.room_buffer[i].clamp(0.0, 1.0) * (MAX_PATTERN_SECONDS - MIN_PATTERN_SECONDS);
+            let length = (pattern_seconds * self.sample_rate).min(max_length);
+            let spread = self.spread_buffer[i].clamp(0.0, 1.0);
+
+            let mut out_l = 0.0;
+            let mut out_r = 0.0;
+            for tap in &self.taps {
+                let mut position = self.write_ptr as f32 - tap.position * length;
+                if position < 0.0 {
+                    position += len_f;
+                }
+                let value = read_linear(&self.delay_line, position) * tap.gain;
+                out_l += value * (1.0 - spread * tap.pan);
+                out_r += value * (1.0 + spread * tap.pan);
+            }
+
+            self.write_ptr += 1;
+            if self.write_ptr >= len {
+                self.write_ptr = 0;
+            }
+
+            let mut diffused_l = out_l;
+            for ap in &mut self.diffusers_l {
+                diffused_l = ap.process(diffused_l);
+            }
+            let mut diffused_r = out_r;
+            for ap in &mut self.diffusers_r {
+                diffused_r = ap.process(diffused_r);
+            }
+
+            let diffusion = self.diffusion_buffer[i].clamp(0.0, 1.0);
+            frame[0] = out_l + (diffused_l - out_l) * diffusion;
+            frame[1] = out_r + (diffused_r - out_r) * diffusion;
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.room_size.set_sample_rate(sample_rate);
+        self.spread.set_sample_rate(sample_rate);
+        self.diffusion.set_sample_rate(sample_rate);
+
+        let new_size = (MAX_PATTERN_SECONDS * sample_rate) as usize + 2;
+        if new_size > self.delay_line.len() {
+            self.delay_line.resize(new_size, 0.0);
+        }
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.room_size.prepare(max_block_size);
+        self.spread.prepare(max_block_size);
+        self.diffusion.prepare(max_block_size);
+        self.room_buffer.resize(max_block_size, 0.0);
+        self.spread_buffer.resize(max_block_size, 0.0);
+        self.diffusion_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.delay_line.fill(0.0);
+        self.write_ptr = 0;
+        for ap in self
+            .diffusers_l
+            .iter_mut()
+            .chain(self.diffusers_r.iter_mut())
+        {
+            ap.reset();
+        }
+        self.room_size.reset();
+        self.spread.reset();
+        self.diffusion.reset();
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("EarlyReflections", "room_size", &self.room_size, 0.0, 1.0);
+        report.check_param("EarlyReflections", "spread", &self.spread, 0.0, 1.0);
+        report.check_param("EarlyReflections", "diffusion", &self.diffusion, 0.0, 1.0);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "EarlyReflections"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(reflections: &mut EarlyReflections) -> Vec<f32> {
+        let mut buffer = vec![0.0; 2 * 4096];
+        buffer[0] = 1.0;
+        buffer[1] = 1.0;
+        for (i, block) in buffer.chunks_mut(256).enumerate() {
+            reflections.process(block, (i * 128) as u64);
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_taps_fit_the_room() {
+        let mut reflections =
+            EarlyReflections::new(AudioParam::Static(0.5), AudioParam::Static(0.0), 12);
+        let out = render(&mut reflections);
+
+        // Centered taps land on both channels equally and end within the pattern.
+        let pattern = ((0.002 + 0.5 * 0.078) * 44100.0) as usize;
+        assert!(out.chunks(2).all(|f| f[0] == f[1]));
+        assert!(out[0..2 * pattern].iter().any(|s| s.abs() > 0.05));
+        assert!(out[2 * (pattern + 2)..].iter().all(|&s| s == 0.0));
+
+        let mut wide = EarlyReflections::new(AudioParam::Static(0.5), AudioParam::Static(1.0), 12);
+        let out = render(&mut wide);
+        assert!(out.chunks(2).any(|f| (f[0] - f[1]).abs() > 0.05));
+    }
+}
@@ -0,0 +1,249 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::ChannelConfig;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+/// Time for the gate to open fully, in seconds.
+const OPEN_TIME: f32 = 0.001;
+
+/// A reverb with a gate on its output, keyed by the dry input.
+///
+/// The classic gated snare: whenever the dry signal peaks above the threshold, the gate
+/// opens and stays open for the hold time after the last peak, then closes over the
+/// release time, cutting the tail off abruptly instead of letting it decay. The reverb is
+/// any processor that outputs only the wet signal, such as `Reverb` or `StereoReverb`;
+/// the output mixes dry and gated wet by `mix`.
+pub struct GatedReverb<C: ChannelConfig, P> {
+    reverb: P,
+    threshold_db: AudioParam,
+    hold_ms: AudioParam,
+    release_ms: AudioParam,
+    mix: AudioParam,
+    sample_rate: f32,
+
+    gain: f32,
+    hold_remaining: u32,
+
+    wet_buffer: Vec<f32>,
+    param_buffer: Vec<f32>,
+    _channels: PhantomData<C>,
+}
+
+impl<C: ChannelConfig, P: FrameProcessor<C>> GatedReverb<C, P> {
+    /// Creates a new GatedReverb with a 250 ms hold, a 40 ms release and a 50% mix.
+    ///
+    /// # Arguments
+    /// * `reverb` - The reverb whose output is gated.
+    /// * `threshold_db` - Dry input level that opens the gate (in dB).
+    pub fn new(reverb: P, threshold_db: AudioParam) -> Self {
+        GatedReverb {
+            reverb,
+            threshold_db,
+            hold_ms: AudioParam::Static(250.0),
+            release_ms: AudioParam::Static(40.0),
+            mix: AudioParam::Static(0.5),
+            sample_rate: 44100.0,
+            gain: 0.0,
+            hold_remaining: 0,
+            wet_buffer: Vec::with_capacity(128),
+            param_buffer: Vec::with_capacity(128),
+            _channels: PhantomData,
+        }
+    }
+
+    /// Sets the threshold parameter.
+    pub fn set_threshold(&mut self, threshold: AudioParam) {
+        self.threshold_db = threshold;
+    }
+
+    /// Sets how long the gate stays open after the last peak (in ms).
+    pub fn set_hold(&mut self, hold: AudioParam) {
+        self.hold_ms = hold;
+    }
+
+    /// Sets how long the gate takes to close (in ms).
+    pub fn set_release(&mut self, release: AudioParam) {
+        self.release_ms = release;
+    }
+
+    /// Sets the dry/wet mix (0.0 = dry, 1.0 = gated reverb only).
+    pub fn set_mix(&mut self, mix: AudioParam) {
+        self.mix = mix;
+    }
+
+    /// Returns the wrapped reverb.
+    pub fn reverb_mut(&mut self) -> &mut P {
+        &mut self.reverb
+    }
+
+    /// Returns the current gate gain (0.0 = closed, 1.0 = open).
+    pub fn gate_gain(&self) -> f32 {
+        self.gain
+    }
+}
+
+impl<C: ChannelConfig, P: FrameProcessor<C>> FrameProcessor<C> for GatedReverb<C, P> {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let channels = C::num_channels();
+        let len = buffer.len();
+        let frames = (len / channels).max(1);
+        if self.wet_buffer.len() < len {
+            self.wet_buffer.resize(len, 0.0);
+        }
+        if self.param_buffer.len() < frames {
+            self.param_buffer.resize(frames, 0.0);
+        }
+
+        let buf = &mut self.param_buffer[0..frames];
+        let threshold_db = self.threshold_db.block_value(buf, sample_index);
+        let hold_ms = self.hold_ms.block_value(buf, sample_index).max(0.0);
+        let release_ms = self.release_ms.block_value(buf, sample_index).max(0.1);
+        let mix = self.mix.block_value(buf, sample_index).clamp(0.0, 1.0);
+
+        let threshold = libm::powf(10.0, threshold_db / 20.0);
+        let hold = (hold_ms * 0.001 * self.sample_rate) as u32;
+        let open_step = 1.0 / (OPEN_TIME * self.sample_rate);
+        let close_step = 1.0 / (release_ms * 0.001 * self.sample_rate);
+
+        let wet = &mut self.wet_buffer[0..len];
+        wet.copy_from_slice(buffer);
+        self.reverb.process(wet, sample_index);
+
+        for (dry_frame, wet_frame) in buffer
+            .chunks_exact_mut(channels)
+            .zip(wet.chunks_exact(channels))
+        {
+            let peak = dry_frame.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+            if peak > threshold {
+                self.hold_remaining = hold;
+                self.gain = (self.gain + open_step).min(1.0);
+            } else if self.hold_remaining > 0 {
+                self.hold_remaining -= 1;
+                self.gain = (self.gain + open_step).min(1.0);
+            } else {
+                self.gain = (self.gain - close_step).max(0.0);
+            }
+
+            let wet_gain = self.gain * mix;
+            for (d, &w) in dry_frame.iter_mut().zip(wet_frame.iter()) {
+                *d = *d * (1.0 - mix) + w * wet_gain;
+            }
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.reverb.set_sample_rate(sample_rate);
+        self.threshold_db.set_sample_rate(sample_rate);
+        self.hold_ms.set_sample_rate(sample_rate);
+        self.release_ms.set_sample_rate(sample_rate);
+        self.mix.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.wet_buffer
+            .resize(max_block_size * C::num_channels(), 0.0);
+        self.param_buffer.resize(max_block_size, 0.0);
+        self.reverb.prepare(max_block_size);
+        self.threshold_db.prepare(max_block_size);
+        self.hold_ms.prepare(max_block_size);
+        self.release_ms.prepare(max_block_size);
+        self.mix.prepare(max_block_size);
+    }
+
+    fn reset(&mut self) {
+        self.gain = 0.0;
+        self.hold_remaining = 0;
+        self.reverb.reset();
+        self.threshold_db.reset();
+        self.hold_ms.reset();
+        self.release_ms.reset();
+        self.mix.reset();
+    }
+
+    fn latency_samples(&self) -> u32 {
+        self.reverb.latency_samples()
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("threshold", &self.threshold_db, -60.0, 0.0);
+        tree.add_audio_param("hold", &self.hold_ms, 0.0, 10000.0);
+        tree.add_audio_param("release", &self.release_ms, 0.1, 10000.0);
+        tree.add_audio_param("mix", &self.mix, 0.0, 1.0);
+        self.reverb.parameters(tree);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("GatedReverb", "hold_ms", &self.hold_ms, 0.0, 10000.0);
+        report.check_param("GatedReverb", "release_ms", &self.release_ms, 0.1, 10000.0);
+        report.check_param("GatedReverb", "mix", &self.mix, 0.0, 1.0);
+        self.reverb.validate(report);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "GatedReverb"
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.add_processor::<C, _>("Reverb", &self.reverb);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn visualize(&self, indent: usize) -> alloc::string::String {
+        let mut output = alloc::string::String::new();
+        output.push_str(&" ".repeat(indent));
+        output.push_str("GatedReverb\n");
+        output.push_str(&self.reverb.visualize(indent + 4));
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::Stereo;
+    use crate::core::parameter::Parameter;
+    use crate::effects::time::reverb::Reverb;
+    use alloc::string::String;
+
+    #[test]
+    fn test_gate_cuts_tail() {
+        let mut reverb = GatedReverb::<Stereo, _>::new(Reverb::new(), AudioParam::Static(-20.0));
+        reverb.set_hold(AudioParam::Static(100.0));
+        reverb.set_release(AudioParam::Static(10.0));
+        reverb.set_mix(AudioParam::Static(1.0));
+
+        // A 20 ms burst, then silence: the wet tail lasts until hold + release.
+        let mut buffer = alloc::vec![0.0; 2 * 22050];
+        for (i, s) in buffer.iter_mut().take(2 * 882).enumerate() {
+            *s = if i % 4 < 2 { 0.5 } else { -0.5 };
+        }
+        for block in buffer.chunks_mut(256) {
+            reverb.process(block, 0);
+        }
+        let energy = |from: usize, to: usize| -> f32 {
+            buffer[2 * from..2 * to].iter().map(|s| s * s).sum()
+        };
+        assert!(energy(2000, 4000) > 1e-3);
+        assert_eq!(energy(6000, 22050), 0.0);
+        assert_eq!(reverb.gate_gain(), 0.0);
+    }
+
+    #[test]
+    fn test_parameters_expose_gate() {
+        let mut reverb =
+            GatedReverb::<Stereo, _>::new(Reverb::new(), AudioParam::Linked(Parameter::new(-20.0)));
+        reverb.set_hold(AudioParam::Linked(Parameter::new(100.0)));
+        reverb.set_release(AudioParam::Linked(Parameter::new(10.0)));
+        reverb.set_mix(AudioParam::Linked(Parameter::new(0.5)));
+
+        let tree = ParameterTree::of::<Stereo, _>(&reverb);
+        let paths: Vec<String> = tree.entries().into_iter().map(|(p, _)| p).collect();
+        assert_eq!(paths, ["threshold", "hold", "release", "mix"]);
+    }
+}
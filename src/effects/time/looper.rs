@@ -0,0 +1,494 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::core::error::ValidationReport;
+use crate::synthesis::envelope::Trigger;
+use crate::FrameProcessor;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// The transport state of a [`Looper`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LooperState {
+    /// No loop recorded.
+    Empty,
+    /// Recording the first pass; its length becomes the loop length.
+    Recording,
+    /// Playing the loop.
+    Playing,
+    /// Playing the loop while adding the input to it.
+    Overdubbing,
+    /// Silent, with the loop kept.
+    Stopped,
+}
+
+/// A loop recorder with overdub, in the style of a looper pedal.
+///
+/// The input always passes through; the loop is added on top. Two buttons drive the
+/// transport, each from a gate `AudioParam` (rising edges crossing 0.5) or a [`Trigger`]:
+///
+/// * `record` - Starts recording when empty or stopped, closes the first pass and starts
+///   playing, and toggles overdubbing while playing.
+/// * `play` - Stops, or restarts the loop from the beginning when stopped.
+///
+/// While overdubbing, the existing loop is scaled by `feedback` before the input is
+/// added, so older layers fade out over repeated passes. Playback can run at half speed
+/// (an octave down) and in reverse; overdubs follow the playback position, and at half
+/// speed each loop sample takes the average of the two input samples that pass over it.
+pub struct Looper {
+    buffer: Vec<f32>,
+    state: LooperState,
+    length: usize,
+    position: f32,
+    fixed_length: Option<f32>,
+    half_speed: bool,
+    reverse: bool,
+    sample_rate: f32,
+
+    record: AudioParam,
+    play: AudioParam,
+    feedback: AudioParam,
+    level: AudioParam,
+    last_record: f32,
+    last_play: f32,
+    record_trigger: Arc<AtomicBool>,
+    play_trigger: Arc<AtomicBool>,
+
+    record_buffer: Vec<f32>,
+    play_buffer: Vec<f32>,
+    feedback_buffer: Vec<f32>,
+    level_buffer: Vec<f32>,
+}
+
+impl Looper {
+    /// Creates a new Looper.
+    ///
+    /// # Arguments
+    /// * `max_seconds` - Longest loop that can be recorded.
+    /// * `record` - Gate for the record button.
+    /// * `play` - Gate for the play/stop button.
+    pub fn new(max_seconds: f32, record: AudioParam, play: AudioParam) -> Self {
+        Looper {
+            buffer: vec![0.0; (max_seconds.max(0.0) * 44100.0) as usize + 1],
+            state: LooperState::Empty,
+            length: 0,
+            position: 0.0,
+            fixed_length: None,
+            half_speed: false,
+            reverse: false,
+            sample_rate: 44100.0,
+            record,
+            play,
+            feedback: AudioParam::Static(1.0),
+            level: AudioParam::Static(1.0),
+            last_record: 0.0,
+            last_play: 0.0,
+            record_trigger: Arc::new(AtomicBool::new(false)),
+            play_trigger: Arc::new(AtomicBool::new(false)),
+            record_buffer: Vec::with_capacity(128),
+            play_buffer: Vec::with_capacity(128),
+            feedback_buffer: Vec::with_capacity(128),
+            level_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Closes the first pass automatically after `seconds` (e.g. a number of bars), and
+    /// returns the looper.
+    pub fn with_fixed_length(mut self, seconds: f32) -> Self {
+        self.fixed_length = Some(seconds);
+        self
+    }
+
+    /// Sets how much of the loop survives each overdub pass (0.0 - 1.0, default 1.0).
+    pub fn set_feedback(&mut self, feedback: AudioParam) {
+        self.feedback = feedback;
+    }
+
+    /// Sets the loop playback level (default 1.0).
+    pub fn set_level(&mut self, level: AudioParam) {
+        self.level = level;
+    }
+
+    /// Plays the loop at half speed, an octave down.
+    pub fn set_half_speed(&mut self, half_speed: bool) {
+        self.half_speed = half_speed;
+        self.snap_position();
+    }
+
+    /// Plays the loop backwards.
+    pub fn set_reverse(&mut self, reverse: bool) {
+        if reverse != self.reverse {
+            self.reverse = reverse;
+            self.snap_position();
+        }
+    }
+
+    /// Creates a handle that presses the record button from any thread.
+    pub fn create_record_trigger(&self) -> Trigger {
+        Trigger::from_flag(&self.record_trigger)
+    }
+
+    /// Creates a handle that presses the play/stop button from any thread.
+    pub fn create_play_trigger(&self) -> Trigger {
+        Trigger::from_flag(&self.play_trigger)
+    }
+
+    /// Returns the transport state.
+    pub fn state(&self) -> LooperState {
+        self.state
+    }
+
+    /// Returns the loop length in samples (0 while nothing is recorded).
+    pub fn loop_length(&self) -> usize {
+        self.length
+    }
+
+    /// Erases the loop.
+    pub fn clear(&mut self) {
+        self.state = LooperState::Empty;
+        self.length = 0;
+        self.position = 0.0;
+    }
+
+    fn press_record(&mut self) {
+        self.state = match self.state {
+            LooperState::Empty | LooperState::Stopped => {
+                self.length = 0;
+                self.position = 0.0;
+                LooperState::Recording
+            }
+            LooperState::Recording => {
+                self.close_loop();
+                return;
+            }
+            LooperState::Playing => LooperState::Overdubbing,
+            LooperState::Overdubbing => LooperState::Playing,
+        };
+    }
+
+    fn press_play(&mut self) {
+        self.state = match self.state {
+            LooperState::Empty => LooperState::Empty,
+            LooperState::Recording => {
+                self.close_loop();
+                if self.length > 0 {
+                    LooperState::Stopped
+                } else {
+                    LooperState::Empty
+                }
+            }
+            LooperState::Playing | LooperState::Overdubbing => LooperState::Stopped,
+            LooperState::Stopped => {
+                self.position = self.start_position();
+                LooperState::Playing
+            }
+        };
+    }
+
+    /// Ends the first pass and starts playing it.
+    fn close_loop(&mut self) {
+        self.length = self.position as usize;
+        if self.length == 0 {
+            self.state = LooperState::Empty;
+            return;
+        }
+        self.position = self.start_position();
+        self.state = LooperState::Playing;
+    }
+
+    fn start_position(&self) -> f32 {
+        if self.reverse {
+            self.length as f32 - 1.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Moves a position between two indices (left by half speed) to the next index in the
+    /// direction of travel, so full-speed overdubs and reversed half-speed ones land on
+    /// the indices they write.
+    fn snap_position(&mut self) {
+        if self.length == 0 || self.position == libm::floorf(self.position) {
+            return;
+        }
+        self.position = if self.reverse {
+            libm::floorf(self.position)
+        } else {
+            libm::ceilf(self.position)
+        };
+        if self.position >= self.length as f32 {
+            self.position = 0.0;
+        }
+    }
+
+    #[inline(always)]
+    fn read(&self) -> f32 {
+        let index = self.position as usize;
+        let frac = self.position - index as f32;
+        let next = if index + 1 >= self.length {
+            0
+        } else {
+            index + 1
+        };
+        self.buffer[index] + (self.buffer[next] - self.buffer[index]) * frac
+    }
+
+    #[inline(always)]
+    fn advance(&mut self) {
+        let speed = if self.half_speed { 0.5 } else { 1.0 };
+        let length = self.length as f32;
+        if self.reverse {
+            self.position -= speed;
+            if self.position < 0.0 {
+                self.position += length;
+            }
+        } else {
+            self.position += speed;
+            if self.position >= length {
+                self.position -= length;
+            }
+        }
+    }
+}
+
+impl FrameProcessor<Mono> for Looper {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        if self.record_buffer.len() < len {
+            self.record_buffer.resize(len, 0.0);
+            self.play_buffer.resize(len, 0.0);
+            self.feedback_buffer.resize(len, 0.0);
+            self.level_buffer.resize(len, 0.0);
+        }
+        self.record
+            .process(&mut self.record_buffer[0..len], sample_index);
+        self.play
+            .process(&mut self.play_buffer[0..len], sample_index);
+        self.feedback
+            .process(&mut self.feedback_buffer[0..len], sample_index);
+        self.level
+            .process(&mut self.level_buffer[0..len], sample_index);
+
+        let mut record_pressed = self.record_trigger.swap(false, Ordering::Relaxed);
+        let mut play_pressed = self.play_trigger.swap(false, Ordering::Relaxed);
+        let fixed_length = self
+            .fixed_length
+            .map(|seconds| ((seconds * self.sample_rate) as usize).clamp(1, self.buffer.len()));
+
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let (record, play) = (self.record_buffer[i], self.play_buffer[i]);
+            record_pressed |= record > 0.5 && self.last_record <= 0.5;
+            play_pressed |= play > 0.5 && self.last_play <= 0.5;
+            self.last_record = record;
+            self.last_play = play;
+            if record_pressed {
+                self.press_record();
+                record_pressed = false;
+            }
+            if play_pressed {
+                self.press_play();
+                play_pressed = false;
+            }
+
+            let input = *sample;
+            match self.state {
+                LooperState::Empty | LooperState::Stopped => {}
+                LooperState::Recording => {
+                    let index = self.position as usize;
+                    self.buffer[index] = input;
+                    self.position += 1.0;
+                    let limit = fixed_length.unwrap_or(self.buffer.len());
+                    if self.position as usize >= limit {
+                        self.close_loop();
+                    }
+                }
+                LooperState::Playing => {
+                    *sample = input + self.read() * self.level_buffer[i];
+                    self.advance();
+                }
+                LooperState::Overdubbing => {
+                    let looped = self.read();
+                    let index = self.position as usize;
+                    if self.position == index as f32 {
+                        let gain = if self.half_speed { 0.5 } else { 1.0 };
+                        self.buffer[index] = looped * self.feedback_buffer[i] + input * gain;
+                    } else {
+                        // A half-speed step between two indices: its half of the input
+                        // joins the index written on the previous step.
+                        let previous = if self.reverse {
+                            (index + 1) % self.length
+                        } else {
+                            index
+                        };
+                        self.buffer[previous] += input * 0.5;
+                    }
+                    *sample = input + looped * self.level_buffer[i];
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        let max_seconds = (self.buffer.len() - 1) as f32 / self.sample_rate;
+        self.sample_rate = sample_rate;
+        let size = (max_seconds * sample_rate) as usize + 1;
+        if size > self.buffer.len() {
+            self.buffer.resize(size, 0.0);
+        }
+        self.record.set_sample_rate(sample_rate);
+        self.play.set_sample_rate(sample_rate);
+        self.feedback.set_sample_rate(sample_rate);
+        self.level.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.record_buffer.resize(max_block_size, 0.0);
+        self.play_buffer.resize(max_block_size, 0.0);
+        self.feedback_buffer.resize(max_block_size, 0.0);
+        self.level_buffer.resize(max_block_size, 0.0);
+        self.record.prepare(max_block_size);
+        self.play.prepare(max_block_size);
+        self.feedback.prepare(max_block_size);
+        self.level.prepare(max_block_size);
+    }
+
+    fn reset(&mut self) {
+        self.clear();
+        self.last_record = 0.0;
+        self.last_play = 0.0;
+        self.record.reset();
+        self.play.reset();
+        self.feedback.reset();
+        self.level.reset();
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("Looper", "feedback", &self.feedback, 0.0, 1.0);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "Looper"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_overdub_and_playback_modes() {
+        let mut looper = Looper::new(1.0, AudioParam::Static(0.0), AudioParam::Static(0.0));
+        looper.set_feedback(AudioParam::Static(0.5));
+        let record = looper.create_record_trigger();
+
+        // Record a 4-sample ramp; the second press closes the loop.
+        record.fire();
+        let mut buffer = [1.0, 2.0, 3.0, 4.0];
+        looper.process(&mut buffer, 0);
+        assert_eq!(looper.state(), LooperState::Recording);
+        record.fire();
+        let mut buffer = [0.0; 8];
+        looper.process(&mut buffer, 4);
+        assert_eq!(looper.loop_length(), 4);
+        assert_eq!(buffer, [1.0, 2.0, 3.0, 4.0, 1.0, 2.0, 3.0, 4.0]);
+
+        // Overdub one pass of 10s: the old layer is halved.
+        record.fire();
+        let mut buffer = [10.0; 4];
+        looper.process(&mut buffer, 12);
+        record.fire();
+        let mut buffer = [0.0; 4];
+        looper.process(&mut buffer, 16);
+        assert_eq!(buffer, [10.5, 11.0, 11.5, 12.0]);
+
+        looper.set_reverse(true);
+        looper.set_half_speed(true);
+        looper.create_play_trigger().fire();
+        looper.process(&mut [0.0; 1], 20);
+        assert_eq!(looper.state(), LooperState::Stopped);
+        looper.create_play_trigger().fire();
+        let mut buffer = [0.0; 4];
+        looper.process(&mut buffer, 21);
+        assert_eq!(buffer, [12.0, 11.75, 11.5, 11.25]);
+    }
+
+    #[test]
+    fn test_gate_and_fixed_length() {
+        use crate::core::parameter::Parameter;
+
+        let gate = Parameter::new(0.0);
+        let mut looper = Looper::new(
+            1.0,
+            AudioParam::Linked(gate.clone()),
+            AudioParam::Static(0.0),
+        )
+        .with_fixed_length(3.0 / 44100.0);
+        gate.set(1.0);
+        let mut buffer = [5.0, 6.0, 7.0, 8.0, 0.0, 0.0];
+        looper.process(&mut buffer, 0);
+        // Recording closes itself after three samples and plays them straight away.
+        assert_eq!(looper.loop_length(), 3);
+        assert_eq!(buffer, [5.0, 6.0, 7.0, 13.0, 6.0, 7.0]);
+    }
+
+    #[test]
+    fn test_half_speed_overdub() {
+        let mut looper = Looper::new(1.0, AudioParam::Static(0.0), AudioParam::Static(0.0));
+        looper.set_feedback(AudioParam::Static(0.5));
+        let record = looper.create_record_trigger();
+        record.fire();
+        looper.process(&mut [1.0, 2.0, 3.0, 4.0], 0);
+        record.fire();
+        looper.process(&mut [0.0; 4], 4);
+
+        // One pass takes eight samples; each index gets the feedback once and all the input.
+        let overdub = |looper: &mut Looper, sample_index: u64| {
+            looper.set_half_speed(true);
+            record.fire();
+            looper.process(&mut [10.0; 8], sample_index);
+            record.fire();
+            looper.set_half_speed(false);
+            looper.set_reverse(false);
+            let mut buffer = [0.0; 4];
+            looper.process(&mut buffer, sample_index + 8);
+            buffer
+        };
+        assert_eq!(overdub(&mut looper, 8), [10.5, 11.0, 11.5, 12.0]);
+        looper.set_reverse(true);
+        assert_eq!(overdub(&mut looper, 20), [15.25, 15.5, 15.75, 16.0]);
+    }
+
+    #[test]
+    fn test_half_speed_change_between_indices() {
+        let mut looper = Looper::new(1.0, AudioParam::Static(0.0), AudioParam::Static(0.0));
+        looper.set_feedback(AudioParam::Static(0.5));
+        let record = looper.create_record_trigger();
+        record.fire();
+        looper.process(&mut [1.0, 2.0, 3.0, 4.0], 0);
+        record.fire();
+        looper.process(&mut [0.0; 4], 4);
+
+        // Three half-speed steps stop between indices 1 and 2; a reverse toggle there
+        // moves to 1, and leaving half speed moves on to 2.
+        looper.set_half_speed(true);
+        record.fire();
+        looper.process(&mut [10.0; 3], 8);
+        assert_eq!(looper.position, 1.5);
+        looper.set_reverse(true);
+        assert_eq!(looper.position, 1.0);
+        looper.set_reverse(false);
+        looper.process(&mut [0.0; 1], 11);
+        looper.set_half_speed(false);
+        assert_eq!(looper.position, 2.0);
+
+        // At full speed the rest of the pass is written with the full input.
+        looper.process(&mut [10.0; 2], 12);
+        record.fire();
+        let mut buffer = [0.0; 4];
+        looper.process(&mut buffer, 14);
+        assert_eq!(buffer[2..], [11.5, 12.0]);
+    }
+}
@@ -1,5 +1,11 @@
 pub mod delay;
+pub mod early_reflections;
+pub mod gated_reverb;
+pub mod looper;
 pub mod ping_pong_delay;
 pub mod reverb;
+pub mod stereo_delay;
+pub mod stereo_reverb;
+pub mod stereo_tape_delay;
 pub mod stutter;
 pub mod tape_delay;
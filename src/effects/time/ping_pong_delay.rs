@@ -1,5 +1,8 @@
-use crate::core::audio_param::AudioParam;
+use crate::core::audio_param::{segments, AudioParam};
 use crate::core::channels::Stereo;
+use crate::core::error::{DspError, ValidationReport};
+use crate::core::tempo::{NoteValue, TransportClock};
+use crate::effects::time::delay::check_max_delay;
 use crate::FrameProcessor;
 use alloc::vec;
 use alloc::vec::Vec;
@@ -54,11 +57,31 @@ impl PingPongDelay {
         }
     }
 
+    /// Creates a new PingPongDelay, or returns the first problem with the arguments.
+    ///
+    /// Fails if `max_delay_seconds` is not positive or a static parameter is out of range.
+    pub fn try_new(
+        max_delay_seconds: f32,
+        delay_time: AudioParam,
+        feedback: AudioParam,
+        mix: AudioParam,
+    ) -> Result<Self, DspError> {
+        check_max_delay("PingPongDelay", max_delay_seconds)?;
+        let delay = Self::new(max_delay_seconds, delay_time, feedback, mix);
+        ValidationReport::of::<Stereo, _>(&delay).into_first_error()?;
+        Ok(delay)
+    }
+
     /// Sets the delay time parameter.
     pub fn set_delay_time(&mut self, delay_time: AudioParam) {
         self.delay_time = delay_time;
     }
 
+    /// Locks the delay time to a note length at the tempo of `clock`.
+    pub fn set_delay_note(&mut self, note: NoteValue, clock: &TransportClock) {
+        self.delay_time = AudioParam::synced(note, clock);
+    }
+
     /// Sets the feedback parameter.
     pub fn set_feedback(&mut self, feedback: AudioParam) {
         self.feedback = feedback;
@@ -96,49 +119,49 @@ impl FrameProcessor<Stereo> for PingPongDelay {
         self.mix
             .process(&mut self.mix_buffer[0..frames], sample_index);
 
-        let current_delay_s = self.delay_buffer[0];
-        let delay_samples = libm::roundf(current_delay_s * self.sample_rate as f32) as usize;
-        let delay_samples = if delay_samples >= len {
-            if len > 0 {
+        // The delay time is rounded to whole samples, so it only needs to be
+        // re-read when it moves by at least half a sample.
+        let threshold = 0.5 / self.sample_rate as f32;
+
+        for segment in segments([&self.delay_buffer[0..frames]], threshold) {
+            let current_delay_s = self.delay_buffer[segment.start];
+            let delay_samples = libm::roundf(current_delay_s * self.sample_rate as f32) as usize;
+            let delay_samples = if delay_samples >= len {
                 len - 1
             } else {
-                0
-            }
-        } else {
-            delay_samples
-        };
+                delay_samples
+            };
 
-        for (i, frame) in buffer.chunks_mut(2).enumerate() {
-            if frame.len() < 2 {
-                break;
-            }
+            for i in segment {
+                let frame = &mut buffer[i * 2..i * 2 + 2];
 
-            let input_l = frame[0];
-            let input_r = frame[1];
+                let input_l = frame[0];
+                let input_r = frame[1];
 
-            let fb = self.feedback_buffer[i];
-            let mix = self.mix_buffer[i];
+                let fb = self.feedback_buffer[i];
+                let mix = self.mix_buffer[i];
 
-            let mut read_ptr = self.write_ptr + len - delay_samples;
-            while read_ptr >= len {
-                read_ptr -= len;
-            }
+                let mut read_ptr = self.write_ptr + len - delay_samples;
+                while read_ptr >= len {
+                    read_ptr -= len;
+                }
 
-            let delayed_l = self.left_buffer[read_ptr];
-            let delayed_r = self.right_buffer[read_ptr];
+                let delayed_l = self.left_buffer[read_ptr];
+                let delayed_r = self.right_buffer[read_ptr];
 
-            let next_l = input_l + delayed_r * fb;
-            let next_r = input_r + delayed_l * fb;
+                let next_l = input_l + delayed_r * fb;
+                let next_r = input_r + delayed_l * fb;
 
-            self.left_buffer[self.write_ptr] = next_l;
-            self.right_buffer[self.write_ptr] = next_r;
+                self.left_buffer[self.write_ptr] = next_l;
+                self.right_buffer[self.write_ptr] = next_r;
 
-            frame[0] = input_l * (1.0 - mix) + delayed_l * mix;
-            frame[1] = input_r * (1.0 - mix) + delayed_r * mix;
+                frame[0] = input_l * (1.0 - mix) + delayed_l * mix;
+                frame[1] = input_r * (1.0 - mix) + delayed_r * mix;
 
-            self.write_ptr += 1;
-            if self.write_ptr >= len {
-                self.write_ptr -= len;
+                self.write_ptr += 1;
+                if self.write_ptr >= len {
+                    self.write_ptr -= len;
+                }
             }
         }
     }
@@ -155,6 +178,15 @@ impl FrameProcessor<Stereo> for PingPongDelay {
         }
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.delay_time.prepare(max_block_size);
+        self.feedback.prepare(max_block_size);
+        self.mix.prepare(max_block_size);
+        self.delay_buffer.resize(max_block_size, 0.0);
+        self.feedback_buffer.resize(max_block_size, 0.0);
+        self.mix_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         self.left_buffer.fill(0.0);
         self.right_buffer.fill(0.0);
@@ -164,6 +196,18 @@ impl FrameProcessor<Stereo> for PingPongDelay {
         self.mix.reset();
     }
 
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param(
+            "PingPongDelay",
+            "delay_time",
+            &self.delay_time,
+            0.0,
+            self.max_delay_seconds,
+        );
+        report.check_param("PingPongDelay", "feedback", &self.feedback, 0.0, 1.0);
+        report.check_param("PingPongDelay", "mix", &self.mix, 0.0, 1.0);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "PingPongDelay"
@@ -1,12 +1,100 @@
-use crate::core::audio_param::AudioParam;
+use crate::core::audio_param::{segments, AudioParam};
 use crate::core::channels::Stereo;
+use crate::core::denormal::{flush_denormal, flush_denormal4};
+use crate::core::error::{DspError, ValidationReport};
+use crate::core::preset::ParameterTree;
 use crate::FrameProcessor;
 use alloc::vec;
 use alloc::vec::Vec;
 use wide::f32x4;
 
+pub(crate) const COMB_TUNING: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+pub(crate) const ALLPASS_TUNING: [usize; 4] = [556, 441, 341, 225];
+pub(crate) const STEREO_SPREAD: usize = 23;
+
+/// Tank tuning of a room model. Delay lengths are in samples at 44.1 kHz.
+struct RoomTuning {
+    combs: [usize; 8],
+    allpasses: [usize; 4],
+    pre_delay_seconds: f32,
+    // Comb feedback = room_size * decay_scale + decay_offset.
+    decay_scale: f32,
+    decay_offset: f32,
+    diffusion: f32,
+    damping: f32,
+}
+
+const CLASSIC_TUNING: RoomTuning = RoomTuning {
+    combs: COMB_TUNING,
+    allpasses: ALLPASS_TUNING,
+    pre_delay_seconds: 0.0,
+    decay_scale: 0.28,
+    decay_offset: 0.7,
+    diffusion: 0.5,
+    damping: 0.2,
+};
+
+/// Room models for [`Reverb::new_with_model`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoomModel {
+    /// Short, dense reflections with a quick, darker decay.
+    SmallRoom,
+    /// Long combs and a moderate pre-delay for a spacious concert hall.
+    Hall,
+    /// Bright, highly diffuse tail without pre-delay, like a plate reverb.
+    Plate,
+    /// Very long combs, a long pre-delay and slow decay.
+    Cathedral,
+}
+
+impl RoomModel {
+    fn tuning(self) -> RoomTuning {
+        match self {
+            RoomModel::SmallRoom => RoomTuning {
+                combs: [503, 541, 577, 613, 641, 673, 701, 733],
+                allpasses: [347, 263, 197, 131],
+                pre_delay_seconds: 0.002,
+                decay_scale: 0.2,
+                decay_offset: 0.6,
+                diffusion: 0.5,
+                damping: 0.4,
+            },
+            RoomModel::Hall => RoomTuning {
+                combs: [1395, 1485, 1597, 1695, 1777, 1863, 1947, 2021],
+                allpasses: [556, 441, 341, 225],
+                pre_delay_seconds: 0.02,
+                decay_scale: 0.24,
+                decay_offset: 0.74,
+                diffusion: 0.5,
+                damping: 0.3,
+            },
+            RoomModel::Plate => RoomTuning {
+                combs: [887, 947, 1013, 1069, 1123, 1181, 1237, 1291],
+                allpasses: [443, 353, 269, 179],
+                pre_delay_seconds: 0.0,
+                decay_scale: 0.25,
+                decay_offset: 0.72,
+                diffusion: 0.7,
+                damping: 0.1,
+            },
+            RoomModel::Cathedral => RoomTuning {
+                combs: [2129, 2269, 2437, 2591, 2713, 2851, 2969, 3089],
+                allpasses: [953, 751, 577, 383],
+                pre_delay_seconds: 0.045,
+                decay_scale: 0.18,
+                decay_offset: 0.8,
+                diffusion: 0.6,
+                damping: 0.4,
+            },
+        }
+    }
+}
+
+/// Room size / damping changes larger than this split the block.
+pub(crate) const PARAM_JUMP_THRESHOLD: f32 = 0.01;
+
 /// 4 parallel Comb filters (SIMD friendly... hopefully).
-struct Comb4 {
+pub(crate) struct Comb4 {
     buffers: [Vec<f32>; 4],
     pos: [usize; 4],
 
@@ -17,7 +105,7 @@ struct Comb4 {
 }
 
 impl Comb4 {
-    fn new(sizes: [usize; 4], feedback: f32, damp: f32) -> Self {
+    pub(crate) fn new(sizes: [usize; 4], feedback: f32, damp: f32) -> Self {
         assert!(
             !sizes.iter().any(|&s| s < 1),
             "Comb4: All filters must be at least 1 unit long."
@@ -37,13 +125,13 @@ impl Comb4 {
         }
     }
 
-    fn set_params(&mut self, feedback: f32, damp: f32, damp_inv: f32) {
+    pub(crate) fn set_params(&mut self, feedback: f32, damp: f32, damp_inv: f32) {
         self.feedback = f32x4::splat(feedback);
         self.damp = f32x4::splat(damp);
         self.damp_inv = f32x4::splat(damp_inv);
     }
 
-    fn process(&mut self, input: f32) -> f32 {
+    pub(crate) fn process(&mut self, input: f32) -> f32 {
         let input_vec = f32x4::splat(input);
 
         // SAFETY: pos can never be outside the bounds.
@@ -56,7 +144,8 @@ impl Comb4 {
         };
 
         let new_input = input_vec + self.filter_state * self.feedback;
-        self.filter_state = delayed * self.damp_inv + self.filter_state * self.damp;
+        self.filter_state =
+            flush_denormal4(delayed * self.damp_inv + self.filter_state * self.damp);
 
         let to_write = new_input.to_array();
 
@@ -78,7 +167,7 @@ impl Comb4 {
         delayed.reduce_add()
     }
 
-    fn reset(&mut self) {
+    pub(crate) fn reset(&mut self) {
         for buf in &mut self.buffers {
             buf.fill(0.0);
         }
@@ -87,14 +176,14 @@ impl Comb4 {
     }
 }
 
-struct Allpass {
+pub(crate) struct Allpass {
     buffer: Vec<f32>,
     pos: usize,
     feedback: f32,
 }
 
 impl Allpass {
-    fn new(size: usize) -> Self {
+    pub(crate) fn new(size: usize) -> Self {
         assert!(size > 0, "Allpass: Length must be at least one unit.");
         Allpass {
             buffer: vec![0.0; size],
@@ -103,12 +192,16 @@ impl Allpass {
         }
     }
 
-    fn process(&mut self, input: f32) -> f32 {
+    pub(crate) fn set_feedback(&mut self, feedback: f32) {
+        self.feedback = feedback;
+    }
+
+    pub(crate) fn process(&mut self, input: f32) -> f32 {
         let len = self.buffer.len();
         // SAFETY: pos can never be outside the bounds.
         let delayed = unsafe { *self.buffer.get_unchecked(self.pos) };
         let output = -input + delayed;
-        let to_store = input + output * self.feedback;
+        let to_store = flush_denormal(input + output * self.feedback);
         // SAFETY: pos can never be outside the bounds.
         unsafe { *self.buffer.get_unchecked_mut(self.pos) = to_store };
 
@@ -119,7 +212,7 @@ impl Allpass {
         output
     }
 
-    fn reset(&mut self) {
+    pub(crate) fn reset(&mut self) {
         self.buffer.fill(0.0);
         self.pos = 0;
     }
@@ -132,7 +225,14 @@ pub struct Reverb {
     allpasses_r: Vec<Allpass>,
     room_size: AudioParam,
     damping: AudioParam,
+    decay_scale: f32,
+    decay_offset: f32,
+    pre_delay_seconds: f32,
+    pre_delay: Vec<f32>,
+    pre_delay_pos: usize,
     sample_rate: f32,
+    room_buffer: Vec<f32>,
+    damp_buffer: Vec<f32>,
 }
 
 impl Reverb {
@@ -145,9 +245,41 @@ impl Reverb {
     }
 
     pub fn new_with_params(room_size: AudioParam, damping: AudioParam, seed: usize) -> Self {
-        let comb_tuning = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
-        let allpass_tuning = [556, 441, 341, 225];
-        let stereo_spread = 23;
+        Self::new_with_tuning(&CLASSIC_TUNING, room_size, damping, seed)
+    }
+
+    /// Creates a new Reverb, or returns the first problem with the arguments.
+    ///
+    /// Fails if a static parameter is out of range.
+    pub fn try_new_with_params(
+        room_size: AudioParam,
+        damping: AudioParam,
+        seed: usize,
+    ) -> Result<Self, DspError> {
+        let reverb = Self::new_with_params(room_size, damping, seed);
+        ValidationReport::of::<Stereo, _>(&reverb).into_first_error()?;
+        Ok(reverb)
+    }
+
+    /// Creates a new Reverb with the delay lengths, pre-delay, decay and damping of a room model.
+    ///
+    /// # Arguments
+    /// * `model` - The room model.
+    pub fn new_with_model(model: RoomModel) -> Self {
+        let tuning = model.tuning();
+        let damping = AudioParam::Static(tuning.damping);
+        Self::new_with_tuning(&tuning, AudioParam::Static(0.8), damping, 0)
+    }
+
+    fn new_with_tuning(
+        tuning: &RoomTuning,
+        room_size: AudioParam,
+        damping: AudioParam,
+        seed: usize,
+    ) -> Self {
+        let comb_tuning = tuning.combs;
+        let allpass_tuning = tuning.allpasses;
+        let stereo_spread = STEREO_SPREAD;
 
         let c1_l = [
             comb_tuning[0] + seed,
@@ -182,10 +314,15 @@ impl Reverb {
         let mut allpasses_r = Vec::with_capacity(allpass_tuning.len());
 
         for t in allpass_tuning {
-            allpasses_l.push(Allpass::new(t + seed));
-            allpasses_r.push(Allpass::new(t + stereo_spread + seed));
+            let mut left = Allpass::new(t + seed);
+            let mut right = Allpass::new(t + stereo_spread + seed);
+            left.set_feedback(tuning.diffusion);
+            right.set_feedback(tuning.diffusion);
+            allpasses_l.push(left);
+            allpasses_r.push(right);
         }
 
+        let sample_rate = 44100.0;
         Reverb {
             combs_l,
             combs_r,
@@ -193,7 +330,14 @@ impl Reverb {
             allpasses_r,
             room_size,
             damping,
-            sample_rate: 44100.0,
+            decay_scale: tuning.decay_scale,
+            decay_offset: tuning.decay_offset,
+            pre_delay_seconds: tuning.pre_delay_seconds,
+            pre_delay: vec![0.0; (tuning.pre_delay_seconds * sample_rate) as usize],
+            pre_delay_pos: 0,
+            sample_rate,
+            room_buffer: Vec::with_capacity(128),
+            damp_buffer: Vec::with_capacity(128),
         }
     }
 
@@ -208,40 +352,61 @@ impl Reverb {
 
 impl FrameProcessor<Stereo> for Reverb {
     fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
-        let mut param_scratch = [0.0; 1];
-
-        self.room_size.process(&mut param_scratch, sample_index);
-        let rs = param_scratch[0] * 0.28 + 0.7;
-
-        self.damping.process(&mut param_scratch, sample_index);
-        let dp = param_scratch[0] * 0.4;
-        let dp_inv = 1.0 - dp;
+        let frames = buffer.len() / 2;
 
-        for c in &mut self.combs_l {
-            c.set_params(rs, dp, dp_inv);
+        if self.room_buffer.len() < frames {
+            self.room_buffer.resize(frames, 0.0);
         }
-        for c in &mut self.combs_r {
-            c.set_params(rs, dp, dp_inv);
+        if self.damp_buffer.len() < frames {
+            self.damp_buffer.resize(frames, 0.0);
         }
-
-        for frame in buffer.chunks_mut(2) {
-            let input = (frame[0] + frame[1]) * 0.5 * 0.015;
-
-            let mut out_l = self.combs_l[0].process(input);
-            out_l += self.combs_l[1].process(input);
-
-            let mut out_r = self.combs_r[0].process(input);
-            out_r += self.combs_r[1].process(input);
-
-            for ap in &mut self.allpasses_l {
-                out_l = ap.process(out_l);
+        self.room_size
+            .process(&mut self.room_buffer[0..frames], sample_index);
+        self.damping
+            .process(&mut self.damp_buffer[0..frames], sample_index);
+
+        // Coefficients are block-constant, except where a parameter jumps mid-block.
+        let params = [&self.room_buffer[0..frames], &self.damp_buffer[0..frames]];
+        for segment in segments(params, PARAM_JUMP_THRESHOLD) {
+            let rs = self.room_buffer[segment.start] * self.decay_scale + self.decay_offset;
+            let dp = self.damp_buffer[segment.start] * 0.4;
+            let dp_inv = 1.0 - dp;
+
+            for c in &mut self.combs_l {
+                c.set_params(rs, dp, dp_inv);
             }
-            for ap in &mut self.allpasses_r {
-                out_r = ap.process(out_r);
+            for c in &mut self.combs_r {
+                c.set_params(rs, dp, dp_inv);
             }
 
-            frame[0] = out_l;
-            frame[1] = out_r;
+            for frame in buffer[segment.start * 2..segment.end * 2].chunks_mut(2) {
+                let mut input = (frame[0] + frame[1]) * 0.5 * 0.015;
+                if !self.pre_delay.is_empty() {
+                    let delayed = self.pre_delay[self.pre_delay_pos];
+                    self.pre_delay[self.pre_delay_pos] = input;
+                    self.pre_delay_pos += 1;
+                    if self.pre_delay_pos >= self.pre_delay.len() {
+                        self.pre_delay_pos = 0;
+                    }
+                    input = delayed;
+                }
+
+                let mut out_l = self.combs_l[0].process(input);
+                out_l += self.combs_l[1].process(input);
+
+                let mut out_r = self.combs_r[0].process(input);
+                out_r += self.combs_r[1].process(input);
+
+                for ap in &mut self.allpasses_l {
+                    out_l = ap.process(out_l);
+                }
+                for ap in &mut self.allpasses_r {
+                    out_r = ap.process(out_r);
+                }
+
+                frame[0] = out_l;
+                frame[1] = out_r;
+            }
         }
     }
 
@@ -249,6 +414,18 @@ impl FrameProcessor<Stereo> for Reverb {
         self.sample_rate = sample_rate;
         self.room_size.set_sample_rate(sample_rate);
         self.damping.set_sample_rate(sample_rate);
+        let pre_delay = (self.pre_delay_seconds * sample_rate) as usize;
+        if pre_delay != self.pre_delay.len() {
+            self.pre_delay = vec![0.0; pre_delay];
+            self.pre_delay_pos = 0;
+        }
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.room_size.prepare(max_block_size);
+        self.damping.prepare(max_block_size);
+        self.room_buffer.resize(max_block_size, 0.0);
+        self.damp_buffer.resize(max_block_size, 0.0);
     }
 
     fn reset(&mut self) {
@@ -264,10 +441,22 @@ impl FrameProcessor<Stereo> for Reverb {
         for ap in &mut self.allpasses_r {
             ap.reset();
         }
+        self.pre_delay.fill(0.0);
+        self.pre_delay_pos = 0;
         self.room_size.reset();
         self.damping.reset();
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("room_size", &self.room_size, 0.0, 1.0);
+        tree.add_audio_param("damping", &self.damping, 0.0, 1.0);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("Reverb", "room_size", &self.room_size, 0.0, 1.0);
+        report.check_param("Reverb", "damping", &self.damping, 0.0, 1.0);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "Reverb (Schroeder)"
@@ -279,3 +468,35 @@ impl Default for Reverb {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn impulse_response(reverb: &mut Reverb, frames: usize) -> Vec<f32> {
+        let mut buffer = vec![0.0; frames * 2];
+        buffer[0] = 1.0;
+        buffer[1] = 1.0;
+        for block in buffer.chunks_mut(128) {
+            reverb.process(block, 0);
+        }
+        buffer.chunks(2).map(|frame| frame[0]).collect()
+    }
+
+    #[test]
+    fn test_room_models() {
+        let mut small = Reverb::new_with_model(RoomModel::SmallRoom);
+        let mut cathedral = Reverb::new_with_model(RoomModel::Cathedral);
+        let small_ir = impulse_response(&mut small, 88200);
+        let cathedral_ir = impulse_response(&mut cathedral, 88200);
+
+        // The first echo arrives after the pre-delay plus the shortest comb.
+        let onset = |ir: &[f32]| ir.iter().position(|s| s.abs() > 1e-9).unwrap();
+        assert_eq!(onset(&small_ir), 88 + 503);
+        assert_eq!(onset(&cathedral_ir), 1984 + 2129);
+
+        // The cathedral tail is still ringing when the small room has died away.
+        let tail = |ir: &[f32]| ir[44100..].iter().map(|s| s * s).sum::<f32>();
+        assert!(tail(&cathedral_ir) > 100.0 * tail(&small_ir));
+    }
+}
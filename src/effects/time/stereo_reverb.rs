@@ -0,0 +1,378 @@
+use crate::core::audio_param::{segments, AudioParam};
+use crate::core::channels::Stereo;
+use crate::core::error::{DspError, ValidationReport};
+use crate::effects::time::reverb::{
+    Allpass, Comb4, ALLPASS_TUNING, COMB_TUNING, PARAM_JUMP_THRESHOLD, STEREO_SPREAD,
+};
+use crate::FrameProcessor;
+use alloc::vec;
+use alloc::vec::Vec;
+
+const MAX_PRE_DELAY_SECONDS: f32 = 0.5;
+
+/// A true stereo Schroeder reverb.
+///
+/// Unlike [`Reverb`](crate::effects::time::reverb::Reverb), which sums its input to mono,
+/// each channel drives its own comb/allpass tank. A cross-feed control blends the inputs
+/// between the tanks, a pre-delay separates the dry signal from the tail, and a width
+/// control scales the side component of the output.
+///
+/// Like `Reverb`, the output is 100% wet.
+pub struct StereoReverb {
+    combs_l: [Comb4; 2],
+    combs_r: [Comb4; 2],
+    allpasses_l: Vec<Allpass>,
+    allpasses_r: Vec<Allpass>,
+    room_size: AudioParam,
+    damping: AudioParam,
+    pre_delay: AudioParam,
+    width: AudioParam,
+    cross_feed: AudioParam,
+
+    pre_delay_l: Vec<f32>,
+    pre_delay_r: Vec<f32>,
+    write_ptr: usize,
+    sample_rate: f32,
+
+    room_buffer: Vec<f32>,
+    damp_buffer: Vec<f32>,
+    pre_delay_buffer: Vec<f32>,
+    width_buffer: Vec<f32>,
+    cross_buffer: Vec<f32>,
+}
+
+impl StereoReverb {
+    /// Creates a new StereoReverb with default settings.
+    pub fn new() -> Self {
+        Self::new_with_params(
+            AudioParam::Static(0.8),
+            AudioParam::Static(0.2),
+            AudioParam::ms(20.0),
+            AudioParam::Static(1.0),
+            0,
+        )
+    }
+
+    /// Creates a new StereoReverb.
+    ///
+    /// # Arguments
+    /// * `room_size` - Room size (0.0 - 1.0).
+    /// * `damping` - High frequency damping (0.0 - 1.0).
+    /// * `pre_delay` - Pre-delay in seconds (up to 0.5 s).
+    /// * `width` - Stereo width (0.0 = mono, 1.0 = natural, up to 2.0 = extra wide).
+    /// * `seed` - Offset added to all delay lengths, to decorrelate multiple instances.
+    pub fn new_with_params(
+        room_size: AudioParam,
+        damping: AudioParam,
+        pre_delay: AudioParam,
+        width: AudioParam,
+        seed: usize,
+    ) -> Self {
+        let tuning = |offset: usize, start: usize| {
+            [
+                COMB_TUNING[start] + offset + seed,
+                COMB_TUNING[start + 1] + offset + seed,
+                COMB_TUNING[start + 2] + offset + seed,
+                COMB_TUNING[start + 3] + offset + seed,
+            ]
+        };
+
+        let combs_l = [
+            Comb4::new(tuning(0, 0), 0.8, 0.2),
+            Comb4::new(tuning(0, 4), 0.8, 0.2),
+        ];
+        let combs_r = [
+            Comb4::new(tuning(STEREO_SPREAD, 0), 0.8, 0.2),
+            Comb4::new(tuning(STEREO_SPREAD, 4), 0.8, 0.2),
+        ];
+
+        let mut allpasses_l = Vec::with_capacity(ALLPASS_TUNING.len());
+        let mut allpasses_r = Vec::with_capacity(ALLPASS_TUNING.len());
+        for t in ALLPASS_TUNING {
+            allpasses_l.push(Allpass::new(t + seed));
+            allpasses_r.push(Allpass::new(t + STEREO_SPREAD + seed));
+        }
+
+        let sample_rate = 44100.0;
+        let pre_delay_size = (MAX_PRE_DELAY_SECONDS * sample_rate) as usize + 1;
+
+        StereoReverb {
+            combs_l,
+            combs_r,
+            allpasses_l,
+            allpasses_r,
+            room_size,
+            damping,
+            pre_delay,
+            width,
+            cross_feed: AudioParam::Static(0.0),
+            pre_delay_l: vec![0.0; pre_delay_size],
+            pre_delay_r: vec![0.0; pre_delay_size],
+            write_ptr: 0,
+            sample_rate,
+            room_buffer: Vec::with_capacity(128),
+            damp_buffer: Vec::with_capacity(128),
+            pre_delay_buffer: Vec::with_capacity(128),
+            width_buffer: Vec::with_capacity(128),
+            cross_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Creates a new StereoReverb, or returns the first problem with the arguments.
+    ///
+    /// Fails if a static parameter is out of range.
+    pub fn try_new_with_params(
+        room_size: AudioParam,
+        damping: AudioParam,
+        pre_delay: AudioParam,
+        width: AudioParam,
+        seed: usize,
+    ) -> Result<Self, DspError> {
+        let reverb = Self::new_with_params(room_size, damping, pre_delay, width, seed);
+        ValidationReport::of::<Stereo, _>(&reverb).into_first_error()?;
+        Ok(reverb)
+    }
+
+    /// Sets the room size parameter (0.0 - 1.0).
+    pub fn set_room_size(&mut self, room_size: AudioParam) {
+        self.room_size = room_size;
+    }
+
+    /// Sets the damping parameter (0.0 - 1.0).
+    pub fn set_damping(&mut self, damping: AudioParam) {
+        self.damping = damping;
+    }
+
+    /// Sets the pre-delay in seconds (up to 0.5 s).
+    pub fn set_pre_delay(&mut self, pre_delay: AudioParam) {
+        self.pre_delay = pre_delay;
+    }
+
+    /// Sets the stereo width (0.0 = mono, 1.0 = natural, 2.0 = extra wide).
+    pub fn set_width(&mut self, width: AudioParam) {
+        self.width = width;
+    }
+
+    /// Sets the cross-feed between the channel tanks (0.0 = independent, 0.5 = fully blended).
+    pub fn set_cross_feed(&mut self, cross_feed: AudioParam) {
+        self.cross_feed = cross_feed;
+    }
+}
+
+impl FrameProcessor<Stereo> for StereoReverb {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let frames = buffer.len() / 2;
+
+        for buf in [
+            &mut self.room_buffer,
+            &mut self.damp_buffer,
+            &mut self.pre_delay_buffer,
+            &mut self.width_buffer,
+            &mut self.cross_buffer,
+        ] {
+            if buf.len() < frames {
+                buf.resize(frames, 0.0);
+            }
+        }
+        self.room_size
+            .process(&mut self.room_buffer[0..frames], sample_index);
+        self.damping
+            .process(&mut self.damp_buffer[0..frames], sample_index);
+        self.pre_delay
+            .process(&mut self.pre_delay_buffer[0..frames], sample_index);
+        self.width
+            .process(&mut self.width_buffer[0..frames], sample_index);
+        self.cross_feed
+            .process(&mut self.cross_buffer[0..frames], sample_index);
+
+        let len = self.pre_delay_l.len();
+        let params = [&self.room_buffer[0..frames], &self.damp_buffer[0..frames]];
+
+        for segment in segments(params, PARAM_JUMP_THRESHOLD) {
+            let rs = self.room_buffer[segment.start] * 0.28 + 0.7;
+            let dp = self.damp_buffer[segment.start] * 0.4;
+            let dp_inv = 1.0 - dp;
+
+            for c in self.combs_l.iter_mut().chain(self.combs_r.iter_mut()) {
+                c.set_params(rs, dp, dp_inv);
+            }
+
+            for i in segment {
+                let frame = &mut buffer[i * 2..i * 2 + 2];
+                self.pre_delay_l[self.write_ptr] = frame[0];
+                self.pre_delay_r[self.write_ptr] = frame[1];
+
+                let delay_samples =
+                    libm::roundf(self.pre_delay_buffer[i].max(0.0) * self.sample_rate) as usize;
+                let delay_samples = delay_samples.min(len - 1);
+
+                let mut read_ptr = self.write_ptr + len - delay_samples;
+                if read_ptr >= len {
+                    read_ptr -= len;
+                }
+                let in_l = self.pre_delay_l[read_ptr];
+                let in_r = self.pre_delay_r[read_ptr];
+
+                self.write_ptr += 1;
+                if self.write_ptr >= len {
+                    self.write_ptr = 0;
+                }
+
+                let cross = self.cross_buffer[i].clamp(0.0, 0.5);
+                let tank_in_l = (in_l * (1.0 - cross) + in_r * cross) * 0.015;
+                let tank_in_r = (in_r * (1.0 - cross) + in_l * cross) * 0.015;
+
+                let mut out_l = self.combs_l[0].process(tank_in_l);
+                out_l += self.combs_l[1].process(tank_in_l);
+
+                let mut out_r = self.combs_r[0].process(tank_in_r);
+                out_r += self.combs_r[1].process(tank_in_r);
+
+                for ap in &mut self.allpasses_l {
+                    out_l = ap.process(out_l);
+                }
+                for ap in &mut self.allpasses_r {
+                    out_r = ap.process(out_r);
+                }
+
+                let width = self.width_buffer[i].clamp(0.0, 2.0);
+                let mid = (out_l + out_r) * 0.5;
+                let side = (out_l - out_r) * 0.5 * width;
+
+                frame[0] = mid + side;
+                frame[1] = mid - side;
+            }
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.room_size.set_sample_rate(sample_rate);
+        self.damping.set_sample_rate(sample_rate);
+        self.pre_delay.set_sample_rate(sample_rate);
+        self.width.set_sample_rate(sample_rate);
+        self.cross_feed.set_sample_rate(sample_rate);
+
+        let new_size = (MAX_PRE_DELAY_SECONDS * sample_rate) as usize + 1;
+        if new_size > self.pre_delay_l.len() {
+            self.pre_delay_l.resize(new_size, 0.0);
+            self.pre_delay_r.resize(new_size, 0.0);
+        }
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.room_size.prepare(max_block_size);
+        self.damping.prepare(max_block_size);
+        self.pre_delay.prepare(max_block_size);
+        self.width.prepare(max_block_size);
+        self.cross_feed.prepare(max_block_size);
+        self.room_buffer.resize(max_block_size, 0.0);
+        self.damp_buffer.resize(max_block_size, 0.0);
+        self.pre_delay_buffer.resize(max_block_size, 0.0);
+        self.width_buffer.resize(max_block_size, 0.0);
+        self.cross_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        for c in self.combs_l.iter_mut().chain(self.combs_r.iter_mut()) {
+            c.reset();
+        }
+        for ap in self
+            .allpasses_l
+            .iter_mut()
+            .chain(self.allpasses_r.iter_mut())
+        {
+            ap.reset();
+        }
+        self.pre_delay_l.fill(0.0);
+        self.pre_delay_r.fill(0.0);
+        self.write_ptr = 0;
+        self.room_size.reset();
+        self.damping.reset();
+        self.pre_delay.reset();
+        self.width.reset();
+        self.cross_feed.reset();
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("StereoReverb", "room_size", &self.room_size, 0.0, 1.0);
+        report.check_param("StereoReverb", "damping", &self.damping, 0.0, 1.0);
+        report.check_param(
+            "StereoReverb",
+            "pre_delay",
+            &self.pre_delay,
+            0.0,
+            MAX_PRE_DELAY_SECONDS,
+        );
+        report.check_param("StereoReverb", "width", &self.width, 0.0, 2.0);
+        report.check_param("StereoReverb", "cross_feed", &self.cross_feed, 0.0, 0.5);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "StereoReverb (Schroeder)"
+    }
+}
+
+impl Default for StereoReverb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(reverb: &mut StereoReverb, left: f32, right: f32) -> Vec<f32> {
+        let mut buffer = vec![0.0; 8192];
+        buffer[0] = left;
+        buffer[1] = right;
+        for (i, block) in buffer.chunks_mut(256).enumerate() {
+            reverb.process(block, (i * 128) as u64);
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_left_input_keeps_image() {
+        let mut reverb = StereoReverb::new_with_params(
+            AudioParam::Static(0.8),
+            AudioParam::Static(0.2),
+            AudioParam::Static(0.0),
+            AudioParam::Static(1.0),
+            0,
+        );
+        let out = render(&mut reverb, 1.0, 0.0);
+
+        let energy_l: f32 = out.iter().step_by(2).map(|s| s * s).sum();
+        let energy_r: f32 = out.iter().skip(1).step_by(2).map(|s| s * s).sum();
+
+        assert!(energy_l > 0.0);
+        assert!(energy_r < energy_l * 0.01);
+    }
+
+    #[test]
+    fn test_zero_width_is_mono() {
+        let mut reverb = StereoReverb::new();
+        reverb.set_width(AudioParam::Static(0.0));
+        let out = render(&mut reverb, 1.0, 0.0);
+
+        assert!(out.iter().any(|s| s.abs() > 1e-6));
+        for frame in out.chunks(2) {
+            assert!((frame[0] - frame[1]).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_pre_delay() {
+        let mut reverb = StereoReverb::new();
+        reverb.set_pre_delay(AudioParam::ms(50.0));
+        let out = render(&mut reverb, 1.0, 1.0);
+
+        // Shortest path is pre-delay plus the shortest comb.
+        let onset = (0.05 * 44100.0) as usize + COMB_TUNING[0];
+        assert!(out[0..(onset - 1) * 2].iter().all(|s| s.abs() < 1e-9));
+        assert!(out[onset * 2..].iter().any(|s| s.abs() > 1e-6));
+    }
+}
@@ -0,0 +1,312 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::{Mono, Stereo};
+use crate::core::error::{DspError, ValidationReport};
+use crate::core::tempo::{NoteValue, TransportClock};
+use crate::effects::time::delay::check_max_delay;
+use crate::effects::time::stereo_delay::read_interleaved;
+use crate::effects::utility::one_pole::OnePole;
+use crate::FrameProcessor;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+
+/// A true stereo tape delay simulation with saturation, wow/flutter, and low-pass filtering.
+///
+/// The stereo counterpart of [`TapeDelay`](crate::effects::time::tape_delay::TapeDelay),
+/// with one interleaved delay line, L/R time offset and cross-feedback. The flutter of the
+/// right channel runs 90 degrees behind the left so the repeats drift slightly apart.
+pub struct StereoTapeDelay {
+    buffer: Vec<f32>,
+    write_ptr: usize,
+    delay_time: AudioParam,
+    feedback: AudioParam,
+    mix: AudioParam,
+    drive: AudioParam,
+    offset: AudioParam,
+    cross_feedback: AudioParam,
+    max_delay_seconds: f32,
+    sample_rate: f32,
+
+    lfo_phase: f32,
+    lfo_inc: f32,
+    tone: [OnePole; 2],
+
+    delay_buffer: Vec<f32>,
+    feedback_buffer: Vec<f32>,
+    mix_buffer: Vec<f32>,
+    drive_buffer: Vec<f32>,
+    offset_buffer: Vec<f32>,
+    cross_buffer: Vec<f32>,
+}
+
+impl StereoTapeDelay {
+    /// Creates a new StereoTapeDelay.
+    ///
+    /// # Arguments
+    /// * `max_delay_s`: Maximum delay time in seconds (including the offset).
+    /// * `delay_time`: Delay time in seconds.
+    /// * `feedback`: Feedback amount (0.0 - 1.0+).
+    /// * `mix`: Dry/Wet mix (0.0 - 1.0).
+    pub fn new(
+        max_delay_s: f32,
+        delay_time: AudioParam,
+        feedback: AudioParam,
+        mix: AudioParam,
+    ) -> Self {
+        let sample_rate = 44100.0;
+        let size = (max_delay_s * sample_rate) as usize;
+
+        StereoTapeDelay {
+            buffer: vec![0.0; size.max(2) * 2],
+            write_ptr: 0,
+            delay_time,
+            feedback,
+            mix,
+            drive: AudioParam::Static(0.0),
+            offset: AudioParam::Static(0.0),
+            cross_feedback: AudioParam::Static(0.0),
+            max_delay_seconds: max_delay_s,
+            sample_rate,
+            lfo_phase: 0.0,
+            lfo_inc: 2.0 * PI * 0.5 / sample_rate,
+            tone: [
+                OnePole::lowpass(AudioParam::hz(2500.0)),
+                OnePole::lowpass(AudioParam::hz(2500.0)),
+            ],
+            delay_buffer: Vec::with_capacity(128),
+            feedback_buffer: Vec::with_capacity(128),
+            mix_buffer: Vec::with_capacity(128),
+            drive_buffer: Vec::with_capacity(128),
+            offset_buffer: Vec::with_capacity(128),
+            cross_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Creates a new StereoTapeDelay, or returns the first problem with the arguments.
+    ///
+    /// Fails if `max_delay_s` is not positive or a static parameter is out of range.
+    pub fn try_new(
+        max_delay_s: f32,
+        delay_time: AudioParam,
+        feedback: AudioParam,
+        mix: AudioParam,
+    ) -> Result<Self, DspError> {
+        check_max_delay("StereoTapeDelay", max_delay_s)?;
+        let delay = Self::new(max_delay_s, delay_time, feedback, mix);
+        ValidationReport::of::<Stereo, _>(&delay).into_first_error()?;
+        Ok(delay)
+    }
+
+    /// Sets the delay time parameter.
+    pub fn set_delay_time(&mut self, delay_time: AudioParam) {
+        self.delay_time = delay_time;
+    }
+
+    /// Locks the delay time to a note length at the tempo of `clock`.
+    pub fn set_delay_note(&mut self, note: NoteValue, clock: &TransportClock) {
+        self.delay_time = AudioParam::synced(note, clock);
+    }
+
+    /// Sets the feedback parameter.
+    pub fn set_feedback(&mut self, feedback: AudioParam) {
+        self.feedback = feedback;
+    }
+
+    /// Sets the mix parameter.
+    pub fn set_mix(&mut self, mix: AudioParam) {
+        self.mix = mix;
+    }
+
+    /// Sets the drive (saturation) parameter.
+    pub fn set_drive(&mut self, drive: AudioParam) {
+        self.drive = drive;
+    }
+
+    /// Sets the L/R time offset in seconds.
+    ///
+    /// Positive values delay the right channel further, negative values the left channel.
+    pub fn set_offset(&mut self, offset: AudioParam) {
+        self.offset = offset;
+    }
+
+    /// Sets the cross-feedback amount (0.0 = independent channels, 1.0 = ping-pong).
+    pub fn set_cross_feedback(&mut self, cross_feedback: AudioParam) {
+        self.cross_feedback = cross_feedback;
+    }
+}
+
+impl FrameProcessor<Stereo> for StereoTapeDelay {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let frames = buffer.len() / 2;
+        let len = self.buffer.len() / 2;
+
+        for buf in [
+            &mut self.delay_buffer,
+            &mut self.feedback_buffer,
+            &mut self.mix_buffer,
+            &mut self.drive_buffer,
+            &mut self.offset_buffer,
+            &mut self.cross_buffer,
+        ] {
+            if buf.len() < frames {
+                buf.resize(frames, 0.0);
+            }
+        }
+        self.delay_time
+            .process(&mut self.delay_buffer[0..frames], sample_index);
+        self.feedback
+            .process(&mut self.feedback_buffer[0..frames], sample_index);
+        self.mix
+            .process(&mut self.mix_buffer[0..frames], sample_index);
+        self.drive
+            .process(&mut self.drive_buffer[0..frames], sample_index);
+        self.offset
+            .process(&mut self.offset_buffer[0..frames], sample_index);
+        self.cross_feedback
+            .process(&mut self.cross_buffer[0..frames], sample_index);
+
+        for (i, frame) in buffer.chunks_exact_mut(2).enumerate() {
+            let delay_s = self.delay_buffer[i];
+            let offset = self.offset_buffer[i];
+            let fb = self.feedback_buffer[i];
+            let mix = self.mix_buffer[i];
+            let drive = self.drive_buffer[i];
+            let cross = self.cross_buffer[i].clamp(0.0, 1.0);
+
+            self.lfo_phase += self.lfo_inc;
+            if self.lfo_phase > 2.0 * PI {
+                self.lfo_phase -= 2.0 * PI;
+            }
+            let flutter_l = libm::sinf(self.lfo_phase) * 0.0005;
+            let flutter_r = libm::cosf(self.lfo_phase) * 0.0005;
+
+            let delay_l = (delay_s + (-offset).max(0.0) + flutter_l) * self.sample_rate;
+            let delay_r = (delay_s + offset.max(0.0) + flutter_r) * self.sample_rate;
+            let mut delayed = [
+                read_interleaved(&self.buffer, self.write_ptr, delay_l, 0),
+                read_interleaved(&self.buffer, self.write_ptr, delay_r, 1),
+            ];
+
+            for (d, tone) in delayed.iter_mut().zip(self.tone.iter_mut()) {
+                if drive > 0.0 {
+                    *d = libm::tanhf(*d * (1.0 + drive));
+                }
+                *d = tone.tick(*d);
+            }
+
+            let (in_l, in_r) = (frame[0], frame[1]);
+            let fb_l = delayed[0] * (1.0 - cross) + delayed[1] * cross;
+            let fb_r = delayed[1] * (1.0 - cross) + delayed[0] * cross;
+            self.buffer[self.write_ptr * 2] = in_l + fb_l * fb;
+            self.buffer[self.write_ptr * 2 + 1] = in_r + fb_r * fb;
+
+            frame[0] = in_l * (1.0 - mix) + delayed[0] * mix;
+            frame[1] = in_r * (1.0 - mix) + delayed[1] * mix;
+
+            self.write_ptr += 1;
+            if self.write_ptr >= len {
+                self.write_ptr -= len;
+            }
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        let old_sr = self.sample_rate;
+        self.sample_rate = sample_rate;
+        self.delay_time.set_sample_rate(sample_rate);
+        for tone in &mut self.tone {
+            FrameProcessor::<Mono>::set_sample_rate(tone, sample_rate);
+        }
+        self.feedback.set_sample_rate(sample_rate);
+        self.mix.set_sample_rate(sample_rate);
+        self.drive.set_sample_rate(sample_rate);
+        self.offset.set_sample_rate(sample_rate);
+        self.cross_feedback.set_sample_rate(sample_rate);
+
+        self.lfo_inc = self.lfo_inc * old_sr / sample_rate;
+
+        let new_size = (self.max_delay_seconds * sample_rate) as usize * 2;
+        if new_size > self.buffer.len() {
+            self.buffer.resize(new_size, 0.0);
+        }
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.delay_time.prepare(max_block_size);
+        self.feedback.prepare(max_block_size);
+        self.mix.prepare(max_block_size);
+        self.drive.prepare(max_block_size);
+        self.offset.prepare(max_block_size);
+        self.cross_feedback.prepare(max_block_size);
+        self.delay_buffer.resize(max_block_size, 0.0);
+        self.feedback_buffer.resize(max_block_size, 0.0);
+        self.mix_buffer.resize(max_block_size, 0.0);
+        self.drive_buffer.resize(max_block_size, 0.0);
+        self.offset_buffer.resize(max_block_size, 0.0);
+        self.cross_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.write_ptr = 0;
+        self.lfo_phase = 0.0;
+        for tone in &mut self.tone {
+            FrameProcessor::<Mono>::reset(tone);
+        }
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param(
+            "StereoTapeDelay",
+            "delay_time",
+            &self.delay_time,
+            0.0,
+            self.max_delay_seconds,
+        );
+        report.check_param("StereoTapeDelay", "feedback", &self.feedback, 0.0, 2.0);
+        report.check_param("StereoTapeDelay", "mix", &self.mix, 0.0, 1.0);
+        report.check_param("StereoTapeDelay", "drive", &self.drive, 0.0, 10.0);
+        report.check_param(
+            "StereoTapeDelay",
+            "offset",
+            &self.offset,
+            -self.max_delay_seconds,
+            self.max_delay_seconds,
+        );
+        report.check_param(
+            "StereoTapeDelay",
+            "cross_feedback",
+            &self.cross_feedback,
+            0.0,
+            1.0,
+        );
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "StereoTapeDelay"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channels_stay_separate_without_cross_feedback() {
+        let mut delay = StereoTapeDelay::new(
+            0.5,
+            AudioParam::ms(20.0),
+            AudioParam::Static(0.6),
+            AudioParam::Static(1.0),
+        );
+        let mut buffer = vec![0.0; 8192];
+        buffer[0] = 1.0;
+        for (i, block) in buffer.chunks_mut(256).enumerate() {
+            delay.process(block, (i * 128) as u64);
+        }
+
+        assert!(buffer.iter().step_by(2).any(|s| s.abs() > 0.1));
+        assert!(buffer.iter().skip(1).step_by(2).all(|&s| s == 0.0));
+    }
+}
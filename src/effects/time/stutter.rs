@@ -191,6 +191,17 @@ impl FrameProcessor<Mono> for Stutter {
         self.mix.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.length.prepare(max_block_size);
+        self.repeats.prepare(max_block_size);
+        self.trigger.prepare(max_block_size);
+        self.mix.prepare(max_block_size);
+        self.length_buffer.resize(max_block_size, 0.0);
+        self.repeats_buffer.resize(max_block_size, 0.0);
+        self.trigger_buffer.resize(max_block_size, 0.0);
+        self.mix_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         self.buffer.fill(0.0);
         self.write_pos = 0;
@@ -1,5 +1,9 @@
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::Mono;
+use crate::core::error::{DspError, ValidationReport};
+use crate::core::tempo::{NoteValue, TransportClock};
+use crate::effects::time::delay::check_max_delay;
+use crate::effects::utility::one_pole::OnePole;
 use crate::FrameProcessor;
 use alloc::vec;
 use alloc::vec::Vec;
@@ -18,7 +22,7 @@ pub struct TapeDelay {
 
     lfo_phase: f32,
     lfo_inc: f32,
-    filter_state: f32,
+    tone: OnePole,
 
     delay_buffer: Vec<f32>,
     feedback_buffer: Vec<f32>,
@@ -54,7 +58,7 @@ impl TapeDelay {
             sample_rate,
             lfo_phase: 0.0,
             lfo_inc: 2.0 * PI * 0.5 / sample_rate,
-            filter_state: 0.0,
+            tone: OnePole::lowpass(AudioParam::hz(2500.0)),
             delay_buffer: Vec::with_capacity(128),
             feedback_buffer: Vec::with_capacity(128),
             mix_buffer: Vec::with_capacity(128),
@@ -62,11 +66,31 @@ impl TapeDelay {
         }
     }
 
+    /// Creates a new TapeDelay, or returns the first problem with the arguments.
+    ///
+    /// Fails if `max_delay_s` is not positive or a static parameter is out of range.
+    pub fn try_new(
+        max_delay_s: f32,
+        delay_time: AudioParam,
+        feedback: AudioParam,
+        mix: AudioParam,
+    ) -> Result<Self, DspError> {
+        check_max_delay("TapeDelay", max_delay_s)?;
+        let delay = Self::new(max_delay_s, delay_time, feedback, mix);
+        ValidationReport::of::<Mono, _>(&delay).into_first_error()?;
+        Ok(delay)
+    }
+
     /// Sets the delay time parameter.
     pub fn set_delay_time(&mut self, delay_time: AudioParam) {
         self.delay_time = delay_time;
     }
 
+    /// Locks the delay time to a note length at the tempo of `clock`.
+    pub fn set_delay_note(&mut self, note: NoteValue, clock: &TransportClock) {
+        self.delay_time = AudioParam::synced(note, clock);
+    }
+
     /// Sets the feedback parameter.
     pub fn set_feedback(&mut self, feedback: AudioParam) {
         self.feedback = feedback;
@@ -144,8 +168,7 @@ impl FrameProcessor<Mono> for TapeDelay {
                 delayed = libm::tanhf(delayed * (1.0 + drive));
             }
 
-            self.filter_state += (delayed - self.filter_state) * 0.3;
-            delayed = self.filter_state;
+            delayed = self.tone.tick(delayed);
 
             self.buffer[self.write_ptr] = input + delayed * fb;
 
@@ -162,6 +185,7 @@ impl FrameProcessor<Mono> for TapeDelay {
         let old_sr = self.sample_rate;
         self.sample_rate = sample_rate;
         self.delay_time.set_sample_rate(sample_rate);
+        FrameProcessor::<Mono>::set_sample_rate(&mut self.tone, sample_rate);
         self.feedback.set_sample_rate(sample_rate);
         self.mix.set_sample_rate(sample_rate);
         self.drive.set_sample_rate(sample_rate);
@@ -174,11 +198,35 @@ impl FrameProcessor<Mono> for TapeDelay {
         }
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.delay_time.prepare(max_block_size);
+        self.feedback.prepare(max_block_size);
+        self.mix.prepare(max_block_size);
+        self.drive.prepare(max_block_size);
+        self.delay_buffer.resize(max_block_size, 0.0);
+        self.feedback_buffer.resize(max_block_size, 0.0);
+        self.mix_buffer.resize(max_block_size, 0.0);
+        self.drive_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         self.buffer.fill(0.0);
         self.write_ptr = 0;
         self.lfo_phase = 0.0;
-        self.filter_state = 0.0;
+        FrameProcessor::<Mono>::reset(&mut self.tone);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param(
+            "TapeDelay",
+            "delay_time",
+            &self.delay_time,
+            0.0,
+            self.max_delay_seconds,
+        );
+        report.check_param("TapeDelay", "feedback", &self.feedback, 0.0, 2.0);
+        report.check_param("TapeDelay", "mix", &self.mix, 0.0, 1.0);
+        report.check_param("TapeDelay", "drive", &self.drive, 0.0, 10.0);
     }
 
     #[cfg(feature = "debug_visualize")]
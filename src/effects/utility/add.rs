@@ -1,5 +1,7 @@
 use crate::core::audio_param::AudioParam;
-use crate::core::channels::ChannelConfig;
+use crate::core::channels::{ChannelConfig, Mono};
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
 use crate::FrameProcessor;
 #[cfg(feature = "debug_visualize")]
 #[cfg(feature = "debug_visualize")]
@@ -71,11 +73,44 @@ impl<C: ChannelConfig> FrameProcessor<C> for Add {
         self.input_b.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.input_a.prepare(max_block_size);
+        self.input_b.prepare(max_block_size);
+        self.buffer_a.resize(max_block_size, 0.0);
+        self.buffer_b.resize(max_block_size, 0.0);
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        for (role, input) in [("A", &self.input_a), ("B", &self.input_b)] {
+            if let AudioParam::Dynamic(p) = input {
+                tree.add_processor::<Mono, _>(role, p.as_ref());
+            }
+        }
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        for (role, input) in [("A", &self.input_a), ("B", &self.input_b)] {
+            if let AudioParam::Dynamic(p) = input {
+                report.add_processor::<Mono, _>(role, p.as_ref());
+            }
+        }
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "Add (Signal Combiner)"
     }
 
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        node.set_routing(crate::core::graph::Routing::Parallel);
+        for (role, input) in [("A", &self.input_a), ("B", &self.input_b)] {
+            if let AudioParam::Dynamic(p) = input {
+                node.add_processor::<crate::core::channels::Mono, _>(role, p);
+            }
+        }
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn visualize(&self, indent: usize) -> String {
         use core::fmt::Write;
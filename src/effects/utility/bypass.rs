@@ -1,4 +1,6 @@
 use crate::core::channels::ChannelConfig;
+use crate::core::error::ValidationReport;
+use crate::core::preset::ParameterTree;
 use crate::FrameProcessor;
 use core::marker::PhantomData;
 
@@ -57,6 +59,10 @@ where
         self.processor.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.processor.prepare(max_block_size);
+    }
+
     fn reset(&mut self) {
         self.processor.reset();
     }
@@ -69,6 +75,14 @@ where
         }
     }
 
+    fn parameters(&self, tree: &mut ParameterTree) {
+        self.processor.parameters(tree);
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        self.processor.validate(report);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         if self.enabled {
@@ -78,6 +92,13 @@ where
         }
     }
 
+    #[cfg(feature = "debug_visualize")]
+    fn describe(&self, node: &mut crate::core::graph::GraphNode) {
+        if self.enabled {
+            self.processor.describe(node);
+        }
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn visualize(&self, indent: usize) -> alloc::string::String {
         if self.enabled {
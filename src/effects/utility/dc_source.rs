@@ -40,6 +40,11 @@ impl FrameProcessor<Mono> for DcSource {
         self.value.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.value.prepare(max_block_size);
+        self.buffer.resize(max_block_size, 0.0);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "DcSource"
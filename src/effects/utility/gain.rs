@@ -1,5 +1,7 @@
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::ChannelConfig;
+use crate::core::preset::ParameterTree;
+use crate::core::utils::velocity_scale;
 use crate::FrameProcessor;
 use alloc::vec::Vec;
 use wide::f32x4;
@@ -7,11 +9,27 @@ use wide::f32x4;
 /// A simple gain processor.
 ///
 /// Multiplies the signal by a gain factor.
+///
+/// An optional velocity input scales the gain, for velocity-sensitive levels in a voice.
+/// [`fade_to`](Self::fade_to) ramps an extra fade gain on top of the parameter, for
+/// click-free mutes and solos.
 pub struct Gain {
     gain: AudioParam,
     gain_buffer: Vec<f32>,
+    decibels: bool,
+    velocity: Option<AudioParam>,
+    velocity_sensitivity: f32,
+    velocity_buffer: Vec<f32>,
+    sample_rate: f32,
+
+    fade_gain: f32,
+    fade_target: f32,
+    fade_step: f32,
 }
 
+/// Fade targets at or below this level fade to silence.
+const SILENCE_DB: f32 = -96.0;
+
 impl Gain {
     /// Creates a new Gain processor.
     ///
@@ -21,25 +39,81 @@ impl Gain {
         Gain {
             gain,
             gain_buffer: Vec::with_capacity(128),
+            decibels: false,
+            velocity: None,
+            velocity_sensitivity: 1.0,
+            velocity_buffer: Vec::new(),
+            sample_rate: 44100.0,
+            fade_gain: 1.0,
+            fade_target: 1.0,
+            fade_step: 0.0,
         }
     }
 
     /// Creates a new Gain processor with a fixed linear gain.
     pub fn new_fixed(gain: f32) -> Self {
-        Gain {
-            gain: AudioParam::Static(gain),
-            gain_buffer: Vec::with_capacity(128),
-        }
+        Self::new(AudioParam::Static(gain))
     }
 
     /// Creates a new Gain processor from a decibel value.
     pub fn new_db(db: f32) -> Self {
-        // libm::powf
-        let val = libm::powf(10.0, db / 20.0);
-        Gain {
-            gain: AudioParam::Static(val),
-            gain_buffer: Vec::with_capacity(128),
-        }
+        Self::new(AudioParam::Static(libm::powf(10.0, db / 20.0)))
+    }
+
+    /// Creates a new Gain processor whose parameter is read in decibels.
+    ///
+    /// # Arguments
+    /// * `gain_db` - The gain in dB, converted to linear gain per sample.
+    pub fn new_db_param(gain_db: AudioParam) -> Self {
+        let mut gain = Self::new(gain_db);
+        gain.decibels = true;
+        gain
+    }
+
+    /// Ramps the fade gain linearly to `target_db` over `ms` milliseconds.
+    ///
+    /// Targets at or below -96 dB fade to silence. The fade gain multiplies the parameter,
+    /// so it starts at 0 dB and leaves the parameter untouched.
+    pub fn fade_to(&mut self, target_db: f32, ms: f32) {
+        self.fade_target = if target_db <= SILENCE_DB {
+            0.0
+        } else {
+            libm::powf(10.0, target_db / 20.0)
+        };
+        let samples = (ms * 0.001 * self.sample_rate).max(1.0);
+        self.fade_step = (self.fade_target - self.fade_gain) / samples;
+    }
+
+    /// Fades to silence over `ms` milliseconds.
+    pub fn fade_out(&mut self, ms: f32) {
+        self.fade_to(f32::NEG_INFINITY, ms);
+    }
+
+    /// Fades back to 0 dB over `ms` milliseconds.
+    pub fn fade_in(&mut self, ms: f32) {
+        self.fade_to(0.0, ms);
+    }
+
+    /// Returns true while a fade is in progress.
+    pub fn is_fading(&self) -> bool {
+        self.fade_gain != self.fade_target
+    }
+
+    /// Returns the current fade gain (linear).
+    pub fn fade_gain(&self) -> f32 {
+        self.fade_gain
+    }
+
+    /// Sets a velocity input that scales the gain and returns the processor.
+    ///
+    /// # Arguments
+    /// * `velocity` - Note velocity (0.0 - 1.0).
+    /// * `sensitivity` - How much velocity scales the gain (0.0 = not at all, 1.0 = fully).
+    pub fn with_velocity(mut self, velocity: AudioParam, sensitivity: f32) -> Self {
+        self.velocity = Some(velocity);
+        self.velocity_sensitivity = sensitivity.clamp(0.0, 1.0);
+        self.velocity_buffer.reserve(128);
+        self
     }
 }
 
@@ -54,6 +128,39 @@ impl<C: ChannelConfig> FrameProcessor<C> for Gain {
 
         let gain_slice = &mut self.gain_buffer[0..frames];
         self.gain.process(gain_slice, sample_index);
+        if self.decibels {
+            for gain in gain_slice.iter_mut() {
+                *gain = libm::powf(10.0, *gain / 20.0);
+            }
+        }
+
+        if let Some(velocity) = &mut self.velocity {
+            if self.velocity_buffer.len() < frames {
+                self.velocity_buffer.resize(frames, 0.0);
+            }
+            let velocity_slice = &mut self.velocity_buffer[0..frames];
+            velocity.process(velocity_slice, sample_index);
+            for (gain, &v) in gain_slice.iter_mut().zip(velocity_slice.iter()) {
+                *gain *= velocity_scale(v, self.velocity_sensitivity);
+            }
+        }
+
+        if self.fade_gain != 1.0 || self.fade_gain != self.fade_target {
+            for gain in gain_slice.iter_mut() {
+                if self.fade_gain != self.fade_target {
+                    self.fade_gain += self.fade_step;
+                    let passed = if self.fade_step > 0.0 {
+                        self.fade_gain >= self.fade_target
+                    } else {
+                        self.fade_gain <= self.fade_target
+                    };
+                    if passed {
+                        self.fade_gain = self.fade_target;
+                    }
+                }
+                *gain *= self.fade_gain;
+            }
+        }
 
         if channels == 1 {
             let (in_chunks, in_rem) = buffer.as_chunks_mut::<4>();
@@ -78,7 +185,28 @@ impl<C: ChannelConfig> FrameProcessor<C> for Gain {
     }
 
     fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
         self.gain.set_sample_rate(sample_rate);
+        if let Some(velocity) = &mut self.velocity {
+            velocity.set_sample_rate(sample_rate);
+        }
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.gain.prepare(max_block_size);
+        self.gain_buffer.resize(max_block_size, 0.0);
+        if let Some(velocity) = &mut self.velocity {
+            velocity.prepare(max_block_size);
+            self.velocity_buffer.resize(max_block_size, 0.0);
+        }
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        if self.decibels {
+            tree.add_audio_param("gain", &self.gain, -60.0, 12.0);
+        } else {
+            tree.add_audio_param("gain", &self.gain, 0.0, 4.0);
+        }
     }
 
     #[cfg(feature = "debug_visualize")]
@@ -91,6 +219,7 @@ impl<C: ChannelConfig> FrameProcessor<C> for Gain {
 mod tests {
     use super::*;
     use crate::core::channels::Mono;
+    use crate::core::parameter::Parameter;
 
     #[test]
     fn test_gain() {
@@ -109,4 +238,62 @@ mod tests {
 
         assert!((buffer[0] - 0.501187).abs() < 0.001);
     }
+
+    #[test]
+    fn test_db_param_and_fades() {
+        let db = Parameter::new(-6.0);
+        let mut gain = Gain::new_db_param(AudioParam::Linked(db.clone()));
+        let mut buffer = [1.0; 4];
+        FrameProcessor::<Mono>::process(&mut gain, &mut buffer, 0);
+        assert!((buffer[0] - 0.501187).abs() < 0.001);
+
+        // 1 ms at 44.1 kHz is 44.1 samples: the fade is done within the first 64.
+        db.set(0.0);
+        gain.fade_out(1.0);
+        let mut buffer = [1.0; 64];
+        FrameProcessor::<Mono>::process(&mut gain, &mut buffer, 0);
+        assert!(buffer.windows(2).all(|w| w[1] <= w[0]));
+        assert!(buffer[0] > 0.95);
+        assert_eq!(buffer[63], 0.0);
+        assert!(!gain.is_fading());
+
+        gain.fade_to(-6.0, 1.0);
+        let mut buffer = [1.0; 64];
+        FrameProcessor::<Mono>::process(&mut gain, &mut buffer, 0);
+        assert!(buffer[0] > 0.0 && buffer[0] < 0.05);
+        assert!((buffer[63] - 0.501187).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_velocity() {
+        let velocity = Parameter::new(0.5);
+        let mut gain =
+            Gain::new_fixed(0.8).with_velocity(AudioParam::Linked(velocity.clone()), 0.5);
+        let mut buffer = [1.0; 4];
+        FrameProcessor::<Mono>::process(&mut gain, &mut buffer, 0);
+        assert!((buffer[0] - 0.6).abs() < 1e-6);
+
+        velocity.set(1.0);
+        let mut buffer = [1.0; 4];
+        FrameProcessor::<Mono>::process(&mut gain, &mut buffer, 0);
+        assert!((buffer[3] - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_prepare_avoids_reallocation() {
+        use crate::synthesis::lfo::{Lfo, LfoWaveform};
+        use alloc::boxed::Box;
+
+        let lfo = Lfo::new(AudioParam::Static(2.0), LfoWaveform::Sine);
+        let mut gain = Gain::new(AudioParam::Dynamic(Box::new(lfo)));
+        FrameProcessor::<Mono>::prepare(&mut gain, 256);
+        let capacity = gain.gain_buffer.capacity();
+        assert!(capacity >= 256);
+
+        for len in [64, 256, 17] {
+            let mut buffer = [1.0; 256];
+            FrameProcessor::<Mono>::process(&mut gain, &mut buffer[0..len], 0);
+        }
+        assert_eq!(gain.gain_buffer.capacity(), capacity);
+    }
 }
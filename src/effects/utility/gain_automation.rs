@@ -0,0 +1,220 @@
+use crate::core::channels::ChannelConfig;
+use crate::core::tempo::NoteValue;
+use crate::core::transport::Transport;
+use crate::FrameProcessor;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A point of a [`GainAutomationClip`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GainBreakpoint {
+    /// Position in quarter-note beats from the start of the loop.
+    pub beat: f32,
+    /// Linear gain at this point.
+    pub level: f32,
+    /// Shape of the segment towards the next point: 0.0 is linear, positive values start
+    /// slowly and end fast, negative values start fast and settle slowly (-1.0 - 1.0).
+    pub curve: f32,
+}
+
+impl GainBreakpoint {
+    /// Creates a new GainBreakpoint.
+    ///
+    /// # Arguments
+    /// * `beat` - Position in beats from the start of the loop.
+    /// * `level` - Linear gain.
+    /// * `curve` - Segment shape (-1.0 - 1.0, 0.0 = linear).
+    pub fn new(beat: f32, level: f32, curve: f32) -> Self {
+        GainBreakpoint { beat, level, curve }
+    }
+}
+
+/// Plays a looping gain envelope locked to the beat position of a [`Transport`].
+///
+/// The envelope is a list of breakpoints within one loop (e.g. a bar), counted from song
+/// position 0. It follows seeks and loops, keeps its place through tempo changes, and
+/// holds its current gain while the transport is stopped.
+///
+/// The typical use is sidechain-pump emulation: [`ghost_kick`](Self::ghost_kick) ducks the
+/// signal on every beat as if a kick drum were keying a compressor.
+pub struct GainAutomationClip {
+    transport: Transport,
+    loop_beats: f32,
+    breakpoints: Vec<GainBreakpoint>,
+}
+
+impl GainAutomationClip {
+    /// Creates a new GainAutomationClip.
+    ///
+    /// # Arguments
+    /// * `transport` - The song timeline.
+    /// * `loop_length` - Length of the loop, e.g. `NoteValue::Bars(1)`.
+    /// * `breakpoints` - The envelope, sorted by beat, all within the loop.
+    pub fn new(
+        transport: &Transport,
+        loop_length: NoteValue,
+        breakpoints: Vec<GainBreakpoint>,
+    ) -> Self {
+        let mut clip = GainAutomationClip {
+            transport: transport.clone(),
+            loop_beats: loop_length.beats(),
+            breakpoints: Vec::new(),
+        };
+        clip.set_breakpoints(breakpoints);
+        clip
+    }
+
+    /// Creates a "ghost kick" pump: the gain drops to `1.0 - depth` on every beat and
+    /// recovers like a compressor release over half a beat.
+    pub fn ghost_kick(transport: &Transport, depth: f32) -> Self {
+        let floor = 1.0 - depth.clamp(0.0, 1.0);
+        Self::new(
+            transport,
+            NoteValue::Quarter,
+            vec![
+                GainBreakpoint::new(0.0, floor, -0.6),
+                GainBreakpoint::new(0.5, 1.0, 0.0),
+                // A short ramp back down avoids a click at the next beat.
+                GainBreakpoint::new(0.97, 1.0, 0.0),
+            ],
+        )
+    }
+
+    /// Replaces the envelope.
+    ///
+    /// # Arguments
+    /// * `breakpoints` - The envelope, sorted by beat, all within the loop.
+    pub fn set_breakpoints(&mut self, breakpoints: Vec<GainBreakpoint>) {
+        assert!(
+            !breakpoints.is_empty(),
+            "GainAutomationClip: At least one breakpoint is required."
+        );
+        assert!(
+            breakpoints.windows(2).all(|w| w[0].beat <= w[1].beat)
+                && breakpoints
+                    .iter()
+                    .all(|b| b.beat >= 0.0 && b.beat < self.loop_beats),
+            "GainAutomationClip: Breakpoints must be sorted and inside the loop."
+        );
+        self.breakpoints = breakpoints;
+    }
+
+    /// Returns the gain at a song position in beats.
+    pub fn level_at(&self, beat: f64) -> f32 {
+        let mut position = libm::fmod(beat, self.loop_beats as f64) as f32;
+        if position < 0.0 {
+            position += self.loop_beats;
+        }
+        let points = &self.breakpoints;
+
+        // Before the first point the segment wraps from the last point of the loop.
+        let index = points.partition_point(|b| b.beat <= position);
+        let (from, to, start, end) = match index {
+            0 => {
+                let last = points[points.len() - 1];
+                (last, points[0], last.beat - self.loop_beats, points[0].beat)
+            }
+            i if i == points.len() => {
+                let first = points[0];
+                (
+                    points[i - 1],
+                    first,
+                    points[i - 1].beat,
+                    first.beat + self.loop_beats,
+                )
+            }
+            i => (points[i - 1], points[i], points[i - 1].beat, points[i].beat),
+        };
+
+        let span = end - start;
+        if span <= 0.0 {
+            return to.level;
+        }
+        let t = (position - start) / span;
+        from.level + (to.level - from.level) * shape_segment(t, from.curve)
+    }
+}
+
+/// Shapes the progress `t` (0.0 - 1.0) through a segment, see [`GainBreakpoint::curve`].
+#[inline]
+pub(crate) fn shape_segment(t: f32, curve: f32) -> f32 {
+    libm::powf(t.clamp(0.0, 1.0), libm::exp2f(curve.clamp(-1.0, 1.0) * 2.0))
+}
+
+impl<C: ChannelConfig> FrameProcessor<C> for GainAutomationClip {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let channels = C::num_channels();
+        for (i, frame) in buffer.chunks_exact_mut(channels).enumerate() {
+            let gain = self.level_at(self.transport.beats_at(sample_index + i as u64));
+            for sample in frame.iter_mut() {
+                *sample *= gain;
+            }
+        }
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "GainAutomationClip"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::{Mono, Stereo};
+
+    #[test]
+    fn test_ghost_kick_follows_the_beat() {
+        // 120 BPM at 1 kHz: one beat every 500 samples.
+        let transport = Transport::new(1000.0);
+        let mut timeline = transport.clone();
+        let mut clip = GainAutomationClip::ghost_kick(&transport, 0.75);
+        transport.start();
+
+        let mut buffer = vec![1.0; 2 * 1000];
+        for (i, block) in buffer.chunks_mut(128).enumerate() {
+            let sample_index = (i * 64) as u64;
+            FrameProcessor::<Stereo>::process(&mut timeline, block, sample_index);
+            FrameProcessor::<Stereo>::process(&mut clip, block, sample_index);
+        }
+        let left: Vec<f32> = buffer.iter().step_by(2).copied().collect();
+
+        assert!((left[0] - 0.25).abs() < 1e-6);
+        assert!((left[500] - 0.25).abs() < 1e-6);
+        assert!((left[300] - 1.0).abs() < 1e-6);
+        assert!(left[0..250].windows(2).all(|w| w[1] >= w[0]));
+        // The recovery starts fast, then settles.
+        assert!(left[62] - left[0] > left[250] - left[187]);
+        assert_eq!(buffer[600], buffer[601]);
+    }
+
+    #[test]
+    fn test_tempo_change_keeps_position() {
+        let transport = Transport::new(1000.0);
+        let mut timeline = transport.clone();
+        let mut clip = GainAutomationClip::new(
+            &transport,
+            NoteValue::Bars(1),
+            vec![
+                GainBreakpoint::new(0.0, 0.0, 0.0),
+                GainBreakpoint::new(3.99, 3.99, 0.0),
+            ],
+        );
+        let mut render = |clip: &mut GainAutomationClip, start: u64| {
+            let mut buffer = [1.0; 250];
+            FrameProcessor::<Mono>::process(&mut timeline, &mut buffer, start);
+            FrameProcessor::<Mono>::process(clip, &mut buffer, start);
+            buffer
+        };
+
+        // Half a beat at 120 BPM, then the tempo halves: the ramp carries on from beat 0.5
+        // at half the rate instead of jumping back to beat 0.25.
+        transport.start();
+        let first = render(&mut clip, 0);
+        transport.set_bpm(60.0);
+        let second = render(&mut clip, 250);
+        assert!((first[249] - 0.498).abs() < 1e-3);
+        assert!((second[0] - 0.5).abs() < 1e-3);
+        assert!((second[249] - 0.749).abs() < 1e-3);
+    }
+}
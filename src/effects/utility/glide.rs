@@ -0,0 +1,189 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+
+/// How the glide time of a [`Glide`] is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlideMode {
+    /// Every glide takes the glide time, however far apart the notes are.
+    ConstantTime,
+    /// The glide time is per octave, so larger intervals take longer.
+    ConstantRate,
+}
+
+/// Portamento for a pitch control signal.
+///
+/// Slews the input frequency towards each new value in the pitch domain (equal steps per
+/// octave), so a glide sounds even rather than rushing through the low notes. Intended to
+/// sit between a frequency `Parameter` (or sequencer) and an oscillator:
+/// `Oscillator::new(AudioParam::Dynamic(Box::new(glide)), ...)`.
+///
+/// The first value is taken without a glide.
+pub struct Glide {
+    input: AudioParam,
+    time: AudioParam,
+    mode: GlideMode,
+    sample_rate: f32,
+
+    // Pitches in octaves (log2 of Hz).
+    current: Option<f32>,
+    target: f32,
+    step: f32,
+
+    input_buffer: Vec<f32>,
+    time_buffer: Vec<f32>,
+}
+
+impl Glide {
+    /// Creates a new Glide.
+    ///
+    /// # Arguments
+    /// * `input` - The pitch signal in Hz.
+    /// * `time` - Glide time in seconds (per glide or per octave, see `mode`).
+    /// * `mode` - How the glide time is measured.
+    pub fn new(input: AudioParam, time: AudioParam, mode: GlideMode) -> Self {
+        Glide {
+            input,
+            time,
+            mode,
+            sample_rate: 44100.0,
+            current: None,
+            target: 0.0,
+            step: 0.0,
+            input_buffer: Vec::with_capacity(128),
+            time_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the glide time parameter in seconds.
+    pub fn set_time(&mut self, time: AudioParam) {
+        self.time = time;
+    }
+
+    /// Sets the glide mode.
+    pub fn set_mode(&mut self, mode: GlideMode) {
+        self.mode = mode;
+    }
+
+    /// Returns true while gliding towards a new pitch.
+    pub fn is_gliding(&self) -> bool {
+        self.current.is_some_and(|c| c != self.target)
+    }
+}
+
+impl FrameProcessor<Mono> for Glide {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        if self.input_buffer.len() < len {
+            self.input_buffer.resize(len, 0.0);
+        }
+        if self.time_buffer.len() < len {
+            self.time_buffer.resize(len, 0.0);
+        }
+        self.input
+            .process(&mut self.input_buffer[0..len], sample_index);
+        self.time
+            .process(&mut self.time_buffer[0..len], sample_index);
+
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let target = libm::log2f(self.input_buffer[i].max(1e-3));
+            let samples = (self.time_buffer[i] * self.sample_rate).max(1.0);
+
+            let current = match self.current {
+                None => target,
+                Some(current) => {
+                    match self.mode {
+                        GlideMode::ConstantTime => {
+                            if target != self.target {
+                                self.step = (target - current).abs() / samples;
+                            }
+                        }
+                        GlideMode::ConstantRate => self.step = 1.0 / samples,
+                    }
+                    if current < target {
+                        (current + self.step).min(target)
+                    } else {
+                        (current - self.step).max(target)
+                    }
+                }
+            };
+            self.current = Some(current);
+            self.target = target;
+            *sample = libm::exp2f(current);
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.input.set_sample_rate(sample_rate);
+        self.time.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.input.prepare(max_block_size);
+        self.time.prepare(max_block_size);
+        self.input_buffer.resize(max_block_size, 0.0);
+        self.time_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.current = None;
+        self.input.reset();
+        self.time.reset();
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "Glide"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parameter::Parameter;
+    use alloc::vec;
+
+    fn glide_to(mode: GlideMode, from: f32, to: f32) -> Vec<f32> {
+        let pitch = Parameter::new(from);
+        let mut glide = Glide::new(
+            AudioParam::Linked(pitch.clone()),
+            AudioParam::Static(0.1),
+            mode,
+        );
+        glide.set_sample_rate(1000.0);
+
+        let mut buffer = vec![0.0; 16];
+        glide.process(&mut buffer, 0);
+        assert!(buffer.iter().all(|&f| (f - from).abs() < 1e-3));
+
+        pitch.set(to);
+        let mut buffer = vec![0.0; 400];
+        for block in buffer.chunks_mut(64) {
+            glide.process(block, 0);
+        }
+        assert!(!glide.is_gliding());
+        buffer
+    }
+
+    #[test]
+    fn test_constant_time() {
+        // 100 ms at 1 kHz: an octave up or two octaves down both take 100 samples.
+        let up = glide_to(GlideMode::ConstantTime, 220.0, 440.0);
+        assert!((up[49] - 311.127).abs() < 0.1);
+        assert!(up[98] < 440.0 && up[99] == 440.0);
+
+        let down = glide_to(GlideMode::ConstantTime, 440.0, 110.0);
+        assert!((down[49] - 220.0).abs() < 0.1);
+        assert!(down[98] > 110.0 && (down[99] - 110.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_constant_rate() {
+        // 100 ms per octave: two octaves take 200 samples.
+        let buffer = glide_to(GlideMode::ConstantRate, 110.0, 440.0);
+        assert!((buffer[99] - 220.0).abs() < 0.1);
+        assert!(buffer[198] < 440.0 && (buffer[199] - 440.0).abs() < 1e-3);
+    }
+}
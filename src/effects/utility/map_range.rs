@@ -87,6 +87,15 @@ impl<C: ChannelConfig> FrameProcessor<C> for MapRange {
         self.max.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.input.prepare(max_block_size);
+        self.min.prepare(max_block_size);
+        self.max.prepare(max_block_size);
+        self.input_buffer.resize(max_block_size, 0.0);
+        self.min_buffer.resize(max_block_size, 0.0);
+        self.max_buffer.resize(max_block_size, 0.0);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "MapRange"
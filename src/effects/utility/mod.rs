@@ -2,11 +2,17 @@ pub mod add;
 pub mod bypass;
 pub mod dc_source;
 pub mod gain;
+pub mod gain_automation;
 pub mod gate;
+pub mod glide;
 pub mod lookahead;
 pub mod map_range;
 pub mod multiply;
 pub mod offset;
+pub mod one_pole;
 pub mod panner;
 pub mod passthrough;
+pub mod safety;
+pub mod sample_hold;
+pub mod slew_limiter;
 pub mod stereo_widener;
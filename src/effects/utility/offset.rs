@@ -77,6 +77,11 @@ impl<C: ChannelConfig> FrameProcessor<C> for Offset {
         self.offset.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.offset.prepare(max_block_size);
+        self.offset_buffer.resize(max_block_size, 0.0);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "Offset"
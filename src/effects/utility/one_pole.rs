@@ -0,0 +1,307 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::core::error::ValidationReport;
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+
+/// The response of a [`OnePole`] filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnePoleMode {
+    /// 6 dB/octave lowpass.
+    LowPass,
+    /// 6 dB/octave highpass (the input minus the lowpass).
+    HighPass,
+}
+
+/// Returns the one-pole coefficient for a cutoff, as used in `y += a · (x - y)`.
+///
+/// # Arguments
+/// * `cutoff` - Cutoff frequency in Hz.
+/// * `sample_rate` - Sample rate in Hz.
+pub fn one_pole_coefficient(cutoff: f32, sample_rate: f32) -> f32 {
+    1.0 - libm::expf(-2.0 * PI * cutoff.max(0.0) / sample_rate)
+}
+
+/// A one-pole (6 dB/octave) lowpass or highpass filter.
+///
+/// Cheap enough for smoothing control signals (parameter de-zippering, envelope followers)
+/// as well as gentle tone shaping. Use [`tick`](Self::tick) to run it per sample inside
+/// another processor; the coefficient then follows the cutoff's constant value.
+pub struct OnePole {
+    cutoff: AudioParam,
+    mode: OnePoleMode,
+    sample_rate: f32,
+    state: f32,
+    coeff: f32,
+    last_cutoff: f32,
+    cutoff_buffer: Vec<f32>,
+}
+
+impl OnePole {
+    /// Creates a new OnePole filter.
+    ///
+    /// # Arguments
+    /// * `cutoff` - Cutoff frequency in Hz.
+    /// * `mode` - Lowpass or highpass.
+    pub fn new(cutoff: AudioParam, mode: OnePoleMode) -> Self {
+        let mut filter = OnePole {
+            cutoff,
+            mode,
+            sample_rate: 44100.0,
+            state: 0.0,
+            coeff: 1.0,
+            last_cutoff: -1.0,
+            cutoff_buffer: Vec::with_capacity(128),
+        };
+        filter.update(filter.cutoff.get_constant().unwrap_or(1000.0));
+        filter
+    }
+
+    /// Creates a new one-pole lowpass.
+    pub fn lowpass(cutoff: AudioParam) -> Self {
+        Self::new(cutoff, OnePoleMode::LowPass)
+    }
+
+    /// Creates a new one-pole highpass.
+    pub fn highpass(cutoff: AudioParam) -> Self {
+        Self::new(cutoff, OnePoleMode::HighPass)
+    }
+
+    /// Sets the cutoff frequency parameter in Hz.
+    pub fn set_cutoff(&mut self, cutoff: AudioParam) {
+        if let Some(value) = cutoff.get_constant() {
+            self.update(value);
+        }
+        self.cutoff = cutoff;
+    }
+
+    /// Sets the cutoff to a plain value in Hz, for use with [`tick`](Self::tick) when the
+    /// owning processor computes the cutoff per sample. The coefficient is only recomputed
+    /// when the value changes.
+    #[inline]
+    pub fn set_cutoff_hz(&mut self, cutoff: f32) {
+        self.update(cutoff);
+    }
+
+    fn update(&mut self, cutoff: f32) {
+        if cutoff != self.last_cutoff {
+            self.coeff = one_pole_coefficient(cutoff, self.sample_rate);
+            self.last_cutoff = cutoff;
+        }
+    }
+
+    /// Filters one sample at the current cutoff.
+    #[inline(always)]
+    pub fn tick(&mut self, input: f32) -> f32 {
+        self.state += self.coeff * (input - self.state);
+        match self.mode {
+            OnePoleMode::LowPass => self.state,
+            OnePoleMode::HighPass => input - self.state,
+        }
+    }
+}
+
+impl FrameProcessor<Mono> for OnePole {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        if let Some(cutoff) = self.cutoff.get_constant() {
+            self.update(cutoff);
+            for sample in buffer.iter_mut() {
+                *sample = self.tick(*sample);
+            }
+            return;
+        }
+
+        let len = buffer.len();
+        if self.cutoff_buffer.len() < len {
+            self.cutoff_buffer.resize(len, 0.0);
+        }
+        self.cutoff
+            .process(&mut self.cutoff_buffer[0..len], sample_index);
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            self.update(self.cutoff_buffer[i]);
+            *sample = self.tick(*sample);
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.cutoff.set_sample_rate(sample_rate);
+        self.last_cutoff = -1.0;
+        self.update(self.cutoff.get_constant().unwrap_or(1000.0));
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.cutoff.prepare(max_block_size);
+        self.cutoff_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.state = 0.0;
+        self.cutoff.reset();
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        let nyquist = self.sample_rate * 0.5;
+        report.check_param("OnePole", "cutoff", &self.cutoff, 0.0, nyquist);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        match self.mode {
+            OnePoleMode::LowPass => "OnePole (LowPass)",
+            OnePoleMode::HighPass => "OnePole (HighPass)",
+        }
+    }
+}
+
+/// Removes DC offset from a signal.
+///
+/// A first-order highpass (`y = x - x[n-1] + R · y[n-1]`) with a very low cutoff, for
+/// feedback loops, waveshapers and asymmetric oscillators that drift off centre.
+pub struct DcBlocker {
+    cutoff: AudioParam,
+    sample_rate: f32,
+    x1: f32,
+    y1: f32,
+    r: f32,
+    last_cutoff: f32,
+    cutoff_buffer: Vec<f32>,
+}
+
+impl DcBlocker {
+    /// Creates a new DcBlocker.
+    ///
+    /// # Arguments
+    /// * `cutoff` - Cutoff frequency in Hz (typically 5 - 20 Hz).
+    pub fn new(cutoff: AudioParam) -> Self {
+        let mut blocker = DcBlocker {
+            cutoff,
+            sample_rate: 44100.0,
+            x1: 0.0,
+            y1: 0.0,
+            r: 0.0,
+            last_cutoff: -1.0,
+            cutoff_buffer: Vec::with_capacity(128),
+        };
+        blocker.update(blocker.cutoff.get_constant().unwrap_or(10.0));
+        blocker
+    }
+
+    /// Sets the cutoff frequency parameter in Hz.
+    pub fn set_cutoff(&mut self, cutoff: AudioParam) {
+        if let Some(value) = cutoff.get_constant() {
+            self.update(value);
+        }
+        self.cutoff = cutoff;
+    }
+
+    fn update(&mut self, cutoff: f32) {
+        if cutoff != self.last_cutoff {
+            self.r = libm::expf(-2.0 * PI * cutoff.max(0.0) / self.sample_rate);
+            self.last_cutoff = cutoff;
+        }
+    }
+
+    /// Filters one sample at the current cutoff.
+    #[inline(always)]
+    pub fn tick(&mut self, input: f32) -> f32 {
+        let output = input - self.x1 + self.r * self.y1;
+        self.x1 = input;
+        self.y1 = output;
+        output
+    }
+}
+
+impl FrameProcessor<Mono> for DcBlocker {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        if let Some(cutoff) = self.cutoff.get_constant() {
+            self.update(cutoff);
+            for sample in buffer.iter_mut() {
+                *sample = self.tick(*sample);
+            }
+            return;
+        }
+
+        let len = buffer.len();
+        if self.cutoff_buffer.len() < len {
+            self.cutoff_buffer.resize(len, 0.0);
+        }
+        self.cutoff
+            .process(&mut self.cutoff_buffer[0..len], sample_index);
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            self.update(self.cutoff_buffer[i]);
+            *sample = self.tick(*sample);
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.cutoff.set_sample_rate(sample_rate);
+        self.last_cutoff = -1.0;
+        self.update(self.cutoff.get_constant().unwrap_or(10.0));
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.cutoff.prepare(max_block_size);
+        self.cutoff_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.x1 = 0.0;
+        self.y1 = 0.0;
+        self.cutoff.reset();
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        let nyquist = self.sample_rate * 0.5;
+        report.check_param("DcBlocker", "cutoff", &self.cutoff, 0.0, nyquist);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "DcBlocker"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synthesis::oscillator::{Oscillator, Waveform};
+    use alloc::vec;
+
+    fn rms(buffer: &[f32]) -> f32 {
+        libm::sqrtf(buffer.iter().map(|s| s * s).sum::<f32>() / buffer.len() as f32)
+    }
+
+    #[test]
+    fn test_one_pole_split() {
+        // Lowpass and highpass sum back to the input.
+        let mut input = vec![0.0; 4410];
+        Oscillator::new(AudioParam::hz(1000.0), Waveform::Saw).process(&mut input, 0);
+        let mut low = input.clone();
+        let mut high = input.clone();
+        OnePole::lowpass(AudioParam::hz(500.0)).process(&mut low, 0);
+        OnePole::highpass(AudioParam::hz(500.0)).process(&mut high, 0);
+        for i in 0..input.len() {
+            assert!((low[i] + high[i] - input[i]).abs() < 1e-5);
+        }
+
+        // -3 dB at the cutoff: a sine at the cutoff keeps about 0.707 of its level.
+        let mut sine = vec![0.0; 44100];
+        Oscillator::new(AudioParam::hz(200.0), Waveform::Sine).process(&mut sine, 0);
+        let level = rms(&sine[22050..]);
+        OnePole::lowpass(AudioParam::hz(200.0)).process(&mut sine, 0);
+        let ratio = rms(&sine[22050..]) / level;
+        assert!((ratio - 0.707).abs() < 0.03);
+    }
+
+    #[test]
+    fn test_dc_blocker() {
+        let mut buffer = vec![0.5; 44100];
+        let mut blocker = DcBlocker::new(AudioParam::hz(10.0));
+        blocker.process(&mut buffer, 0);
+        assert!((buffer[0] - 0.5).abs() < 1e-6);
+        assert!(buffer[44099].abs() < 1e-3);
+    }
+}
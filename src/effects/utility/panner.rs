@@ -4,12 +4,47 @@ use crate::FrameProcessor;
 use alloc::vec::Vec;
 use core::f32::consts::PI;
 
+/// How a pan position maps to the gains of the two channels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanLaw {
+    /// Equal power: -3 dB in the centre, so the loudness stays even across the field.
+    #[default]
+    ConstantPower,
+    /// Equal amplitude: -6 dB in the centre, the two gains sum to one.
+    Linear,
+    /// The geometric mean of the two: -4.5 dB in the centre.
+    Compromise,
+}
+
+impl PanLaw {
+    /// Returns the left and right gains for a pan position (-1.0 = Left, 1.0 = Right).
+    pub fn gains(self, pan: f32) -> (f32, f32) {
+        let pan = pan.clamp(-1.0, 1.0);
+        let linear = || ((1.0 - pan) * 0.5, (1.0 + pan) * 0.5);
+        let power = || {
+            let angle = (pan + 1.0) * PI / 4.0;
+            (libm::cosf(angle), libm::sinf(angle))
+        };
+        match self {
+            PanLaw::ConstantPower => power(),
+            PanLaw::Linear => linear(),
+            PanLaw::Compromise => {
+                let (ll, lr) = linear();
+                let (pl, pr) = power();
+                (libm::sqrtf(ll * pl), libm::sqrtf(lr * pr))
+            }
+        }
+    }
+}
+
 /// A stereo panner.
 ///
 /// Pans a stereo signal (interleaved) between left and right channels.
-/// Uses constant power panning law.
+/// Uses constant power panning law by default. The position can be modulated at audio
+/// rate.
 pub struct StereoPanner {
     pan: AudioParam,
+    law: PanLaw,
     pan_buffer: Vec<f32>,
 }
 
@@ -21,10 +56,17 @@ impl StereoPanner {
     pub fn new(pan: AudioParam) -> Self {
         StereoPanner {
             pan,
+            law: PanLaw::ConstantPower,
             pan_buffer: Vec::with_capacity(128),
         }
     }
 
+    /// Sets the panning law and returns the panner.
+    pub fn with_law(mut self, law: PanLaw) -> Self {
+        self.law = law;
+        self
+    }
+
     /// Sets the pan parameter.
     pub fn set_pan(&mut self, pan: AudioParam) {
         self.pan = pan;
@@ -47,11 +89,7 @@ impl FrameProcessor<Stereo> for StereoPanner {
                 break;
             }
 
-            let pan = self.pan_buffer[i].clamp(-1.0, 1.0);
-
-            let angle = (pan + 1.0) * PI / 4.0;
-            let gain_l = libm::cosf(angle);
-            let gain_r = libm::sinf(angle);
+            let (gain_l, gain_r) = self.law.gains(self.pan_buffer[i]);
 
             frame[0] *= gain_l;
             frame[1] *= gain_r;
@@ -62,8 +100,32 @@ impl FrameProcessor<Stereo> for StereoPanner {
         self.pan.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.pan.prepare(max_block_size);
+        self.pan_buffer.resize(max_block_size, 0.0);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "StereoPanner"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pan_laws() {
+        let db = |g: f32| 20.0 * libm::log10f(g);
+        let centre = |law: PanLaw| db(law.gains(0.0).0);
+        assert!((centre(PanLaw::ConstantPower) + 3.01).abs() < 0.01);
+        assert!((centre(PanLaw::Linear) + 6.02).abs() < 0.01);
+        assert!((centre(PanLaw::Compromise) + 4.52).abs() < 0.01);
+        for law in [PanLaw::ConstantPower, PanLaw::Linear, PanLaw::Compromise] {
+            assert_eq!(law.gains(-1.0), (1.0, 0.0));
+            let (l, r) = law.gains(1.0);
+            assert!(l.abs() < 1e-6 && (r - 1.0).abs() < 1e-6);
+        }
+    }
+}
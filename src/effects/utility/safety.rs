@@ -0,0 +1,199 @@
+use crate::core::channels::ChannelConfig;
+use crate::FrameProcessor;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Cutoff of the DC estimator, in Hz.
+const DC_CUTOFF: f32 = 5.0;
+
+/// A safety stage for the end of a chain.
+///
+/// Catches the failures of an unstable patch before they reach the speakers: a block that
+/// contains NaN or infinity is replaced with silence, a channel whose DC level runs past
+/// the DC threshold is muted until it settles below half of it, and everything else is
+/// hard-clipped at the ceiling. Each fault raises the optional fault flag, so a UI can
+/// show that the guard stepped in. Place it after feedback-heavy processors while
+/// developing a patch.
+pub struct SafetyLimiter {
+    ceiling: f32,
+    dc_threshold: f32,
+    dc_coeff: f32,
+    // One DC estimate per channel, sized in `prepare` (or by the first block without it).
+    dc: Vec<f32>,
+    dc_muted: bool,
+    faults: u32,
+    fault_flag: Option<Arc<AtomicBool>>,
+}
+
+impl SafetyLimiter {
+    /// Creates a new SafetyLimiter with a 0 dBFS ceiling and a DC threshold of 0.5.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        SafetyLimiter {
+            ceiling: 1.0,
+            dc_threshold: 0.5,
+            dc_coeff: Self::dc_coeff(44100.0),
+            dc: Vec::new(),
+            dc_muted: false,
+            faults: 0,
+            fault_flag: None,
+        }
+    }
+
+    /// Sets the hard-clip ceiling (linear) and returns the limiter.
+    pub fn with_ceiling(mut self, ceiling: f32) -> Self {
+        self.ceiling = ceiling.abs();
+        self
+    }
+
+    /// Sets the DC level that mutes the output and returns the limiter.
+    pub fn with_dc_threshold(mut self, threshold: f32) -> Self {
+        self.dc_threshold = threshold.abs();
+        self
+    }
+
+    /// Sets a flag that is raised on every fault and returns the limiter.
+    ///
+    /// The limiter only sets the flag; the reader clears it, e.g. with `swap(false, ..)`.
+    pub fn with_fault_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.fault_flag = Some(flag);
+        self
+    }
+
+    /// Returns the number of blocks in which a fault was caught.
+    pub fn faults(&self) -> u32 {
+        self.faults
+    }
+
+    /// Returns true while the output is muted because of runaway DC.
+    pub fn is_dc_muted(&self) -> bool {
+        self.dc_muted
+    }
+
+    fn dc_coeff(sample_rate: f32) -> f32 {
+        1.0 - libm::expf(-2.0 * core::f32::consts::PI * DC_CUTOFF / sample_rate)
+    }
+
+    fn fault(&mut self) {
+        self.faults = self.faults.saturating_add(1);
+        if let Some(flag) = &self.fault_flag {
+            flag.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<C: ChannelConfig> FrameProcessor<C> for SafetyLimiter {
+    fn process(&mut self, buffer: &mut [f32], _sample_index: u64) {
+        let channels = C::num_channels();
+        if self.dc.len() < channels {
+            self.dc.resize(channels, 0.0);
+        }
+
+        if !buffer.iter().all(|s| s.is_finite()) {
+            buffer.fill(0.0);
+            self.dc.fill(0.0);
+            self.fault();
+            return;
+        }
+
+        let was_muted = self.dc_muted;
+        let mut peak_dc = 0.0f32;
+        for frame in buffer.chunks_exact(channels) {
+            for (dc, &s) in self.dc.iter_mut().zip(frame.iter()) {
+                *dc += (s - *dc) * self.dc_coeff;
+                peak_dc = peak_dc.max(dc.abs());
+            }
+        }
+        self.dc_muted = if was_muted {
+            peak_dc > self.dc_threshold * 0.5
+        } else {
+            peak_dc > self.dc_threshold
+        };
+        if self.dc_muted {
+            if !was_muted {
+                self.fault();
+            }
+            buffer.fill(0.0);
+            return;
+        }
+
+        let ceiling = self.ceiling;
+        for sample in buffer.iter_mut() {
+            *sample = sample.clamp(-ceiling, ceiling);
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.dc_coeff = Self::dc_coeff(sample_rate);
+    }
+
+    fn prepare(&mut self, _max_block_size: usize) {
+        self.dc.resize(C::num_channels(), 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.dc.fill(0.0);
+        self.dc_muted = false;
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "SafetyLimiter"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::channels::{Mono, Stereo};
+
+    #[test]
+    fn test_faults_are_silenced() {
+        let flag = Arc::new(AtomicBool::new(false));
+        let mut safety = SafetyLimiter::new()
+            .with_ceiling(0.5)
+            .with_fault_flag(flag.clone());
+        FrameProcessor::<Stereo>::prepare(&mut safety, 2);
+
+        let mut buffer = [0.2, -0.9, 2.0, 0.1];
+        FrameProcessor::<Stereo>::process(&mut safety, &mut buffer, 0);
+        assert_eq!(buffer, [0.2, -0.5, 0.5, 0.1]);
+        assert!(!flag.load(Ordering::Relaxed));
+
+        let mut buffer = [0.2, f32::NAN, 0.3, f32::INFINITY];
+        FrameProcessor::<Stereo>::process(&mut safety, &mut buffer, 0);
+        assert_eq!(buffer, [0.0; 4]);
+        assert!(flag.swap(false, Ordering::Relaxed));
+        assert_eq!(safety.faults(), 1);
+
+        // A stuck DC level mutes the output until it settles.
+        let mut safety = SafetyLimiter::new();
+        FrameProcessor::<Mono>::prepare(&mut safety, 64);
+        let mut muted_at = None;
+        for block in 0..100 {
+            let mut buffer = [0.9; 64];
+            FrameProcessor::<Mono>::process(&mut safety, &mut buffer, 0);
+            if buffer == [0.0; 64] {
+                muted_at.get_or_insert(block);
+            }
+        }
+        assert!(muted_at.is_some() && safety.is_dc_muted());
+        for _ in 0..100 {
+            let mut buffer = [0.0; 64];
+            FrameProcessor::<Mono>::process(&mut safety, &mut buffer, 0);
+        }
+        assert!(!safety.is_dc_muted());
+        assert_eq!(safety.faults(), 1);
+    }
+
+    #[test]
+    fn test_dc_mute_without_prepare() {
+        let mut safety = SafetyLimiter::new();
+        for _ in 0..100 {
+            let mut buffer = [0.9; 128];
+            FrameProcessor::<Stereo>::process(&mut safety, &mut buffer, 0);
+        }
+        assert!(safety.is_dc_muted());
+    }
+}
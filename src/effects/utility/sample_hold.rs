@@ -0,0 +1,128 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+
+/// Samples a control signal on each trigger and holds it.
+///
+/// The input is captured on every rising edge of the trigger (crossing 0.5), so a gate, a
+/// clock or a square LFO can drive it. With a noise input this is the classic random
+/// stepped modulation; with a slow LFO input it quantizes the LFO in time. The output is
+/// 0.0 until the first edge.
+pub struct SampleHold {
+    input: AudioParam,
+    trigger: AudioParam,
+    held: f32,
+    last_trigger: f32,
+    input_buffer: Vec<f32>,
+    trigger_buffer: Vec<f32>,
+}
+
+impl SampleHold {
+    /// Creates a new SampleHold.
+    ///
+    /// # Arguments
+    /// * `input` - The signal to sample.
+    /// * `trigger` - Samples the input on each rising edge (crossing 0.5).
+    pub fn new(input: AudioParam, trigger: AudioParam) -> Self {
+        SampleHold {
+            input,
+            trigger,
+            held: 0.0,
+            last_trigger: 0.0,
+            input_buffer: Vec::with_capacity(128),
+            trigger_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Returns the value currently held.
+    pub fn held(&self) -> f32 {
+        self.held
+    }
+}
+
+impl FrameProcessor<Mono> for SampleHold {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        if self.input_buffer.len() < len {
+            self.input_buffer.resize(len, 0.0);
+        }
+        if self.trigger_buffer.len() < len {
+            self.trigger_buffer.resize(len, 0.0);
+        }
+        self.input
+            .process(&mut self.input_buffer[0..len], sample_index);
+        self.trigger
+            .process(&mut self.trigger_buffer[0..len], sample_index);
+
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let trigger = self.trigger_buffer[i];
+            if trigger >= 0.5 && self.last_trigger < 0.5 {
+                self.held = self.input_buffer[i];
+            }
+            self.last_trigger = trigger;
+            *sample = self.held;
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.input.set_sample_rate(sample_rate);
+        self.trigger.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.input.prepare(max_block_size);
+        self.trigger.prepare(max_block_size);
+        self.input_buffer.resize(max_block_size, 0.0);
+        self.trigger_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.held = 0.0;
+        self.last_trigger = 0.0;
+        self.input.reset();
+        self.trigger.reset();
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "SampleHold"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synthesis::lfo::{Lfo, LfoWaveform};
+    use alloc::boxed::Box;
+    use alloc::vec;
+
+    #[test]
+    fn test_holds_on_rising_edges() {
+        // A ramp sampled by a 10 Hz square clock at 1 kHz: edges every 100 samples.
+        struct Ramp;
+        impl FrameProcessor<Mono> for Ramp {
+            fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+                for (i, s) in buffer.iter_mut().enumerate() {
+                    *s = (sample_index + i as u64) as f32;
+                }
+            }
+        }
+        let clock = Lfo::new(AudioParam::hz(10.0), LfoWaveform::Square);
+        let mut sh = SampleHold::new(
+            AudioParam::Dynamic(Box::new(Ramp)),
+            AudioParam::Dynamic(Box::new(clock)),
+        );
+        sh.set_sample_rate(1000.0);
+
+        let mut buffer = vec![0.0; 300];
+        for (i, block) in buffer.chunks_mut(64).enumerate() {
+            sh.process(block, (i * 64) as u64);
+        }
+        let edges: Vec<usize> = (1..300).filter(|&i| buffer[i] != buffer[i - 1]).collect();
+        assert_eq!(edges.len(), 2);
+        assert_eq!(edges[1] - edges[0], 100);
+        assert_eq!(buffer[edges[0]], edges[0] as f32);
+        assert_eq!(sh.held(), buffer[299]);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+
+/// Limits how fast a control signal can rise and fall.
+///
+/// Rising and falling rates are set separately, in units per second, so a stepped signal
+/// (a sequencer, a `SampleHold`) can be smoothed into ramps, or a gate turned into a simple
+/// attack/release envelope. Unlike [`Glide`](crate::effects::utility::glide::Glide) it works
+/// on the linear value, for any control signal. The first value is taken without a slew.
+pub struct SlewLimiter {
+    input: AudioParam,
+    rise: AudioParam,
+    fall: AudioParam,
+    sample_rate: f32,
+    current: Option<f32>,
+    input_buffer: Vec<f32>,
+    rise_buffer: Vec<f32>,
+    fall_buffer: Vec<f32>,
+}
+
+impl SlewLimiter {
+    /// Creates a new SlewLimiter.
+    ///
+    /// # Arguments
+    /// * `input` - The signal to limit.
+    /// * `rise` - Maximum rise in units per second.
+    /// * `fall` - Maximum fall in units per second.
+    pub fn new(input: AudioParam, rise: AudioParam, fall: AudioParam) -> Self {
+        SlewLimiter {
+            input,
+            rise,
+            fall,
+            sample_rate: 44100.0,
+            current: None,
+            input_buffer: Vec::with_capacity(128),
+            rise_buffer: Vec::with_capacity(128),
+            fall_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the maximum rise in units per second.
+    pub fn set_rise(&mut self, rise: AudioParam) {
+        self.rise = rise;
+    }
+
+    /// Sets the maximum fall in units per second.
+    pub fn set_fall(&mut self, fall: AudioParam) {
+        self.fall = fall;
+    }
+}
+
+impl FrameProcessor<Mono> for SlewLimiter {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        if self.input_buffer.len() < len {
+            self.input_buffer.resize(len, 0.0);
+        }
+        if self.rise_buffer.len() < len {
+            self.rise_buffer.resize(len, 0.0);
+        }
+        if self.fall_buffer.len() < len {
+            self.fall_buffer.resize(len, 0.0);
+        }
+        self.input
+            .process(&mut self.input_buffer[0..len], sample_index);
+        self.rise
+            .process(&mut self.rise_buffer[0..len], sample_index);
+        self.fall
+            .process(&mut self.fall_buffer[0..len], sample_index);
+
+        let inv_sr = 1.0 / self.sample_rate;
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let target = self.input_buffer[i];
+            let value = match self.current {
+                None => target,
+                Some(current) if target > current => {
+                    (current + self.rise_buffer[i].max(0.0) * inv_sr).min(target)
+                }
+                Some(current) => (current - self.fall_buffer[i].max(0.0) * inv_sr).max(target),
+            };
+            self.current = Some(value);
+            *sample = value;
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.input.set_sample_rate(sample_rate);
+        self.rise.set_sample_rate(sample_rate);
+        self.fall.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.input.prepare(max_block_size);
+        self.rise.prepare(max_block_size);
+        self.fall.prepare(max_block_size);
+        self.input_buffer.resize(max_block_size, 0.0);
+        self.rise_buffer.resize(max_block_size, 0.0);
+        self.fall_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.current = None;
+        self.input.reset();
+        self.rise.reset();
+        self.fall.reset();
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "SlewLimiter"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parameter::Parameter;
+    use alloc::vec;
+
+    #[test]
+    fn test_separate_rise_and_fall() {
+        let input = Parameter::new(0.0);
+        let mut slew = SlewLimiter::new(
+            AudioParam::Linked(input.clone()),
+            AudioParam::Static(10.0),
+            AudioParam::Static(2.0),
+        );
+        slew.set_sample_rate(1000.0);
+        let mut buffer = vec![0.0; 1000];
+        slew.process(&mut buffer[0..1], 0);
+
+        // Up by 1.0 at 10 per second: 100 samples.
+        input.set(1.0);
+        slew.process(&mut buffer, 1);
+        assert!((buffer[49] - 0.5).abs() < 1e-3);
+        assert!(buffer[98] < 1.0 && buffer[100] == 1.0);
+
+        // Down at 2 per second: 500 samples.
+        input.set(0.0);
+        slew.process(&mut buffer, 1001);
+        assert!((buffer[249] - 0.5).abs() < 1e-3);
+        assert!(buffer[498] > 0.0 && buffer[500] == 0.0);
+    }
+}
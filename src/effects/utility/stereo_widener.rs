@@ -58,6 +58,11 @@ impl FrameProcessor<Stereo> for StereoWidener {
         self.width.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.width.prepare(max_block_size);
+        self.width_buffer.resize(max_block_size, 0.0);
+    }
+
     #[cfg(feature = "debug_visualize")]
     fn name(&self) -> &str {
         "StereoWidener"
@@ -31,10 +31,16 @@
 
 extern crate alloc;
 
+pub mod analysis;
+#[cfg(feature = "bench_support")]
+pub mod bench_support;
+pub mod control;
 pub mod core;
 pub mod effects;
 pub mod low_mem;
 pub mod synthesis;
+#[cfg(test)]
+mod testing;
 
-pub use crate::core::channels::{ChannelConfig, Mono, Stereo};
+pub use crate::core::channels::{ChannelConfig, Channels, Mono, Stereo};
 pub use crate::core::frame_processor::FrameProcessor;
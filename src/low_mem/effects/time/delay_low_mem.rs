@@ -374,6 +374,12 @@ impl FrameProcessor<Mono> for DelayLowMem {
         }
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.delay_time.prepare(max_block_size);
+        self.feedback.prepare(max_block_size);
+        self.mix.prepare(max_block_size);
+    }
+
     fn reset(&mut self) {
         self.buffer.fill(0);
         self.write_ptr = 0;
@@ -294,6 +294,11 @@ impl FrameProcessor<Stereo> for ReverbLowMem {
         self.damping.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.room_size.prepare(max_block_size);
+        self.damping.prepare(max_block_size);
+    }
+
     fn reset(&mut self) {
         for c in &mut self.combs_l {
             c.reset();
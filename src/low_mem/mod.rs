@@ -1 +1,2 @@
 pub mod effects;
+pub mod parity;
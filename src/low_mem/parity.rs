@@ -0,0 +1,195 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+use num_complex::Complex32;
+
+const FFT_SIZE: usize = 1024;
+const HOP_SIZE: usize = FFT_SIZE / 2;
+const NOISE_FLOOR_DB: f32 = -60.0;
+
+/// How closely a low-memory implementation tracks its full-precision counterpart.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParityReport {
+    /// Signal-to-noise ratio of the candidate against the reference, in dB.
+    pub snr_db: f32,
+    /// Mean absolute difference of the long-term spectra, in dB.
+    pub spectral_difference_db: f32,
+}
+
+/// Compares a candidate rendering against a reference rendering.
+///
+/// Both signals are compared over their common length. Interleaved multi-channel buffers
+/// can be passed directly; the spectral comparison then treats them as one stream.
+pub fn compare(reference: &[f32], candidate: &[f32]) -> ParityReport {
+    ParityReport {
+        snr_db: snr_db(reference, candidate),
+        spectral_difference_db: spectral_difference_db(reference, candidate),
+    }
+}
+
+/// Returns the signal-to-noise ratio of `candidate` against `reference` in dB.
+///
+/// The noise is the sample-wise difference. Identical signals return `f32::INFINITY`.
+pub fn snr_db(reference: &[f32], candidate: &[f32]) -> f32 {
+    let mut signal = 0.0f64;
+    let mut noise = 0.0f64;
+    for (&r, &c) in reference.iter().zip(candidate.iter()) {
+        signal += (r as f64) * (r as f64);
+        noise += ((r - c) as f64) * ((r - c) as f64);
+    }
+    if noise == 0.0 {
+        return f32::INFINITY;
+    }
+    (10.0 * libm::log10(signal / noise)) as f32
+}
+
+/// Returns the mean absolute difference in dB between the long-term spectra of both signals.
+///
+/// The spectra are averaged over Hann-windowed 1024-point frames. Only bins within 60 dB of
+/// the reference peak are compared, so silence and the noise floor do not dominate the result.
+pub fn spectral_difference_db(reference: &[f32], candidate: &[f32]) -> f32 {
+    let len = reference.len().min(candidate.len());
+    let reference = average_spectrum(&reference[0..len]);
+    let candidate = average_spectrum(&candidate[0..len]);
+
+    let peak = reference.iter().cloned().fold(0.0, f32::max);
+    if peak <= 0.0 {
+        return 0.0;
+    }
+    let floor = peak * libm::powf(10.0, NOISE_FLOOR_DB / 10.0);
+
+    let mut total = 0.0;
+    let mut bins = 0;
+    for (&r, &c) in reference.iter().zip(candidate.iter()) {
+        if r < floor {
+            continue;
+        }
+        total += (10.0 * libm::log10f(r / c.max(floor * 1e-3))).abs();
+        bins += 1;
+    }
+    total / bins as f32
+}
+
+fn average_spectrum(signal: &[f32]) -> Vec<f32> {
+    let mut window = [0.0; FFT_SIZE];
+    for (i, w) in window.iter_mut().enumerate() {
+        *w = 0.5 * (1.0 - libm::cosf(2.0 * PI * i as f32 / (FFT_SIZE - 1) as f32));
+    }
+
+    let mut power = vec![0.0; FFT_SIZE / 2];
+    let mut fft_buffer = [Complex32::new(0.0, 0.0); FFT_SIZE];
+    let mut start = 0;
+    while start + FFT_SIZE <= signal.len() {
+        for i in 0..FFT_SIZE {
+            fft_buffer[i] = Complex32::new(signal[start + i] * window[i], 0.0);
+        }
+        let _ = microfft::complex::cfft_1024(&mut fft_buffer);
+        for (p, bin) in power.iter_mut().zip(fft_buffer.iter()) {
+            *p += bin.norm_sqr();
+        }
+        start += HOP_SIZE;
+    }
+    power
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+    use crate::core::channels::{Mono, Stereo};
+    use crate::core::render::render_offline_with_input;
+    use crate::core::utils::FastRng;
+    use crate::effects::time::delay::Delay;
+    use crate::effects::time::reverb::Reverb;
+    use crate::low_mem::effects::time::delay_low_mem::DelayLowMem;
+    use crate::low_mem::effects::time::reverb_low_mem::ReverbLowMem;
+
+    /// Minimum agreed quality of `DelayLowMem` against `Delay`.
+    const DELAY_MIN_SNR_DB: f32 = 20.0;
+    const DELAY_MAX_SPECTRAL_DIFFERENCE_DB: f32 = 3.0;
+    /// Minimum agreed quality of `ReverbLowMem` against `Reverb`.
+    const REVERB_MIN_SNR_DB: f32 = 6.0;
+    const REVERB_MAX_SPECTRAL_DIFFERENCE_DB: f32 = 10.0;
+
+    /// Decaying tones plus a noise burst, band-limited below the low-mem Nyquist.
+    fn test_signal(frames: usize) -> Vec<f32> {
+        let mut state = 12345;
+        let mut lp = 0.0;
+        (0..frames)
+            .map(|i| {
+                let t = i as f32 / 44100.0;
+                let env = libm::expf(-3.0 * (t % 0.5));
+                let tones = 0.3 * libm::sinf(2.0 * PI * 220.0 * t)
+                    + 0.2 * libm::sinf(2.0 * PI * 1250.0 * t)
+                    + 0.1 * libm::sinf(2.0 * PI * 3100.0 * t);
+                let noise = FastRng::next_f32_bipolar_stateless(&mut state);
+                lp += 0.3 * (noise - lp);
+                let burst = if t % 0.5 < 0.05 { 0.3 * lp } else { 0.0 };
+                tones * env + burst
+            })
+            .collect()
+    }
+
+    fn assert_parity(report: ParityReport, min_snr_db: f32, max_spectral_difference_db: f32) {
+        assert!(
+            report.snr_db >= min_snr_db,
+            "SNR below threshold: {:?}",
+            report
+        );
+        assert!(
+            report.spectral_difference_db <= max_spectral_difference_db,
+            "Spectral difference above threshold: {:?}",
+            report
+        );
+    }
+
+    #[test]
+    fn test_identical_signals() {
+        let signal = test_signal(4096);
+        let report = compare(&signal, &signal);
+        assert_eq!(report.snr_db, f32::INFINITY);
+        assert_eq!(report.spectral_difference_db, 0.0);
+    }
+
+    #[test]
+    fn test_delay_low_mem_parity() {
+        let input = test_signal(44100 * 2);
+        let params = || {
+            (
+                AudioParam::ms(250.0),
+                AudioParam::Static(0.4),
+                AudioParam::Static(0.5),
+            )
+        };
+
+        let (time, feedback, mix) = params();
+        let mut delay = Delay::new(1.0, time, feedback, mix);
+        let (time, feedback, mix) = params();
+        let mut low_mem = DelayLowMem::new(1.0, time, feedback, mix);
+
+        let reference = render_offline_with_input::<Mono, _>(&mut delay, &input, 128);
+        let candidate = render_offline_with_input::<Mono, _>(&mut low_mem, &input, 128);
+        assert_parity(
+            compare(&reference, &candidate),
+            DELAY_MIN_SNR_DB,
+            DELAY_MAX_SPECTRAL_DIFFERENCE_DB,
+        );
+    }
+
+    #[test]
+    fn test_reverb_low_mem_parity() {
+        let mono = test_signal(44100 * 2);
+        let input: Vec<f32> = mono.iter().flat_map(|&s| [s, s]).collect();
+
+        let mut reverb = Reverb::new();
+        let mut low_mem = ReverbLowMem::new();
+
+        let reference = render_offline_with_input::<Stereo, _>(&mut reverb, &input, 128);
+        let candidate = render_offline_with_input::<Stereo, _>(&mut low_mem, &input, 128);
+        assert_parity(
+            compare(&reference, &candidate),
+            REVERB_MIN_SNR_DB,
+            REVERB_MAX_SPECTRAL_DIFFERENCE_DB,
+        );
+    }
+}
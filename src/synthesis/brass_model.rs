@@ -1,5 +1,7 @@
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::Mono;
+use crate::effects::utility::one_pole::{DcBlocker, OnePole};
+use crate::synthesis::excitation::ExcitationNoise;
 use crate::FrameProcessor;
 use alloc::vec;
 use alloc::vec::Vec;
@@ -78,8 +80,8 @@ pub struct BrassModel {
     sample_rate: f32,
 
     lip_filter: PhysBiQuad,
-    dc_blocker: f32,
-    lp_state: f32,
+    lowpass: OnePole,
+    dc_blocker: DcBlocker,
     bell_state: f32,
     last_out: f32,
     vibrato_phase: f32,
@@ -88,7 +90,7 @@ pub struct BrassModel {
     breath_buffer: Vec<f32>,
     tension_buffer: Vec<f32>,
 
-    rng_state: u32,
+    excitation: ExcitationNoise,
 }
 
 impl BrassModel {
@@ -104,21 +106,27 @@ impl BrassModel {
             write_ptr: 0,
             sample_rate,
             lip_filter: PhysBiQuad::new(),
-            dc_blocker: 0.0,
-            lp_state: 0.0,
+            lowpass: OnePole::lowpass(AudioParam::hz(740.0)),
+            dc_blocker: DcBlocker::new(AudioParam::hz(35.0)),
             bell_state: 0.0,
             last_out: 0.0,
             vibrato_phase: 0.0,
             pitch_buffer: Vec::with_capacity(128),
             breath_buffer: Vec::with_capacity(128),
             tension_buffer: Vec::with_capacity(128),
-            rng_state: 12345,
+            excitation: ExcitationNoise::breath(),
         }
     }
 
-    #[inline(always)]
-    fn next_random(rng_state: &mut u32) -> f32 {
-        crate::core::utils::FastRng::next_f32_bipolar_stateless(rng_state)
+    /// Sets the breath noise source. Its output is scaled by the breath pressure.
+    pub fn set_excitation(&mut self, excitation: ExcitationNoise) {
+        self.excitation = excitation;
+        self.excitation.set_sample_rate(self.sample_rate);
+    }
+
+    /// Returns a mutable reference to the breath noise source.
+    pub fn excitation_mut(&mut self) -> &mut ExcitationNoise {
+        &mut self.excitation
     }
 }
 
@@ -187,16 +195,15 @@ impl FrameProcessor<Mono> for BrassModel {
             let threshold = 0.05;
             let lip_opening = (lip_pos - threshold).max(0.0);
 
-            let noise = Self::next_random(&mut self.rng_state) * 0.02 * breath;
+            let noise = self.excitation.next_sample() * breath;
             let airflow = (breath + noise) * lip_opening;
 
             let saturated = libm::tanhf(airflow);
 
-            let lp_cutoff = 0.1 + 0.6 * breath;
-            self.lp_state += lp_cutoff * (saturated - self.lp_state);
+            self.lowpass.set_cutoff_hz(740.0 + 7700.0 * breath);
+            let filtered = self.lowpass.tick(saturated);
 
-            let ac_signal = self.lp_state - self.dc_blocker + 0.995 * self.dc_blocker;
-            self.dc_blocker = self.lp_state;
+            let ac_signal = self.dc_blocker.tick(filtered);
 
             self.delay_line[self.write_ptr] = ac_signal;
 
@@ -221,6 +228,9 @@ impl FrameProcessor<Mono> for BrassModel {
         self.pitch.set_sample_rate(sample_rate);
         self.breath_pressure.set_sample_rate(sample_rate);
         self.lip_tension.set_sample_rate(sample_rate);
+        self.excitation.set_sample_rate(sample_rate);
+        FrameProcessor::<Mono>::set_sample_rate(&mut self.lowpass, sample_rate);
+        FrameProcessor::<Mono>::set_sample_rate(&mut self.dc_blocker, sample_rate);
 
         let buffer_size = (sample_rate / 20.0) as usize;
         if buffer_size > self.delay_line.len() {
@@ -228,15 +238,25 @@ impl FrameProcessor<Mono> for BrassModel {
         }
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.pitch.prepare(max_block_size);
+        self.breath_pressure.prepare(max_block_size);
+        self.lip_tension.prepare(max_block_size);
+        self.pitch_buffer.resize(max_block_size, 0.0);
+        self.breath_buffer.resize(max_block_size, 0.0);
+        self.tension_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         self.delay_line.fill(0.0);
         self.write_ptr = 0;
         self.lip_filter.reset();
-        self.dc_blocker = 0.0;
-        self.lp_state = 0.0;
+        FrameProcessor::<Mono>::reset(&mut self.lowpass);
+        FrameProcessor::<Mono>::reset(&mut self.dc_blocker);
         self.bell_state = 0.0;
         self.last_out = 0.0;
         self.vibrato_phase = 0.0;
+        self.excitation.reset();
     }
 
     #[cfg(feature = "debug_visualize")]
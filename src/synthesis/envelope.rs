@@ -1,5 +1,7 @@
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::Mono;
+use crate::core::preset::ParameterTree;
+use crate::core::utils::velocity_scale;
 use crate::FrameProcessor;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
@@ -14,6 +16,59 @@ enum AdsrState {
     Release,
 }
 
+/// The shape of an envelope segment.
+///
+/// Named after the shape of the level over time, whichever way the segment goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvelopeCurve {
+    /// A straight line.
+    Linear,
+    /// Convex: a rise starts slowly and speeds up, a fall starts fast and settles (like an
+    /// analog RC decay).
+    Exponential,
+    /// Concave: a rise starts fast and settles, a fall starts slowly and speeds up.
+    Logarithmic,
+}
+
+impl EnvelopeCurve {
+    /// Returns the level at position `t` (0.0 - 1.0) of a segment from `from` to `to`.
+    ///
+    /// # Arguments
+    /// * `from` - Level at the start of the segment.
+    /// * `to` - Level at the end of the segment.
+    /// * `t` - Position in the segment (0.0 - 1.0).
+    /// * `curvature` - How strongly the curve bends (0.0 = linear, 3.0 - 6.0 = typical).
+    pub fn shape(self, from: f32, to: f32, t: f32, curvature: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        let k = curvature.max(0.0);
+        let progress = if self == EnvelopeCurve::Linear || k < 1e-3 {
+            t
+        } else {
+            // Fast start for convex falls and concave rises, slow start otherwise.
+            let fast_start = (self == EnvelopeCurve::Exponential) == (to < from);
+            if fast_start {
+                (1.0 - libm::expf(-k * t)) / (1.0 - libm::expf(-k))
+            } else {
+                (libm::expf(k * t) - 1.0) / (libm::expf(k) - 1.0)
+            }
+        };
+        from + (to - from) * progress
+    }
+}
+
+/// What an [`Adsr`] does when a new note starts while the previous one is still sounding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RetriggerMode {
+    /// Attack again from the current level, like most analog envelopes.
+    #[default]
+    Analog,
+    /// Restart from zero, for a consistent attack on every note.
+    Retrigger,
+    /// Skip the attack and continue from the current level to the sustain level, for smooth
+    /// note transitions. Notes starting from silence still get an attack.
+    Legato,
+}
+
 /// A handle to manually trigger an envelope.
 #[derive(Clone)]
 pub struct Trigger {
@@ -21,16 +76,41 @@ pub struct Trigger {
 }
 
 impl Trigger {
+    pub(crate) fn from_flag(flag: &Arc<AtomicBool>) -> Self {
+        Trigger {
+            flag: Arc::clone(flag),
+        }
+    }
+
     /// Fires the trigger.
     pub fn fire(&self) {
         self.flag.store(true, Ordering::Relaxed);
     }
 }
 
+/// Fewest time constants the one-pole decay and release span; a one-pole over fewer
+/// would take many times the set time to settle.
+const MIN_TIME_CONSTANTS: f32 = 1.0;
+
 /// An ADSR (Attack, Decay, Sustain, Release) envelope generator.
 ///
 /// Generates a control signal based on a gate input.
 /// Time parameters are in seconds.
+///
+/// By default the attack is linear and the decay and release are exponential, spanning
+/// three time constants. Each segment's [`EnvelopeCurve`] can be changed, and the
+/// curvature parameter bends the curved segments (for the exponential decay and release:
+/// the number of time constants). Below one time constant the exponential segments follow
+/// the shaped curve instead, so they still end on time and are straight at 0.0.
+///
+/// A velocity input, sampled at each note start, scales the peak and sustain levels and
+/// can shorten the attack of harder notes. Its default of 1.0 leaves the envelope at
+/// full level.
+///
+/// The gate opens when it reaches the threshold plus half the hysteresis and closes below
+/// the threshold minus half the hysteresis, so a noisy gate signal does not chatter. What a
+/// new note does to a still sounding envelope is set by the [`RetriggerMode`]; a
+/// [`Trigger`] always restarts from zero.
 pub struct Adsr {
     gate: AudioParam,
 
@@ -38,11 +118,28 @@ pub struct Adsr {
     decay_time: AudioParam,
     sustain_level: AudioParam,
     release_time: AudioParam,
+    curvature: AudioParam,
+    velocity: AudioParam,
+    velocity_sensitivity: f32,
+    velocity_attack: f32,
+    attack_curve: EnvelopeCurve,
+    decay_curve: EnvelopeCurve,
+    release_curve: EnvelopeCurve,
+    retrigger_mode: RetriggerMode,
+    looping: bool,
+    gate_threshold: f32,
+    gate_hysteresis: f32,
 
     sample_rate: f32,
     state: AdsrState,
     current_level: f32,
-    last_gate: f32,
+    gate_open: bool,
+    // Start level and elapsed samples of the current segment, for the shaped segments.
+    segment_start: f32,
+    segment_pos: f32,
+    // Peak level and attack time factor of the current note, from its velocity.
+    peak: f32,
+    attack_scale: f32,
 
     attack_step: f32,
     decay_coeff: f32,
@@ -51,12 +148,15 @@ pub struct Adsr {
     last_attack: f32,
     last_decay: f32,
     last_release: f32,
+    last_curvature: f32,
 
     gate_buffer: Vec<f32>,
     attack_buffer: Vec<f32>,
     decay_buffer: Vec<f32>,
     sustain_buffer: Vec<f32>,
     release_buffer: Vec<f32>,
+    curvature_buffer: Vec<f32>,
+    velocity_buffer: Vec<f32>,
 
     retrigger: Arc<AtomicBool>,
 }
@@ -83,21 +183,39 @@ impl Adsr {
             decay_time,
             sustain_level,
             release_time,
+            curvature: AudioParam::Static(3.0),
+            velocity: AudioParam::Static(1.0),
+            velocity_sensitivity: 1.0,
+            velocity_attack: 0.0,
+            attack_curve: EnvelopeCurve::Linear,
+            decay_curve: EnvelopeCurve::Exponential,
+            release_curve: EnvelopeCurve::Exponential,
+            retrigger_mode: RetriggerMode::Analog,
+            looping: false,
+            gate_threshold: 0.5,
+            gate_hysteresis: 0.0,
             sample_rate: 44100.0,
             state: AdsrState::Idle,
             current_level: 0.0,
-            last_gate: 0.0,
+            gate_open: false,
+            segment_start: 0.0,
+            segment_pos: 0.0,
+            peak: 1.0,
+            attack_scale: 1.0,
             attack_step: 0.0,
             decay_coeff: 0.0,
             release_coeff: 0.0,
             last_attack: -1.0,
             last_decay: -1.0,
             last_release: -1.0,
+            last_curvature: 3.0,
             gate_buffer: Vec::with_capacity(128), // Pre-allocate standard block size
             attack_buffer: Vec::with_capacity(128),
             decay_buffer: Vec::with_capacity(128),
             sustain_buffer: Vec::with_capacity(128),
             release_buffer: Vec::with_capacity(128),
+            curvature_buffer: Vec::with_capacity(128),
+            velocity_buffer: Vec::with_capacity(128),
             retrigger: Arc::new(AtomicBool::new(false)),
         };
         adsr.recalc(0.01, 0.1, 0.1); // Initial dummy recalc
@@ -107,12 +225,11 @@ impl Adsr {
     /// Creates a trigger handle for this envelope.
     /// Use this to manually retrigger the envelope from any thread.
     pub fn create_trigger(&self) -> Trigger {
-        Trigger {
-            flag: Arc::clone(&self.retrigger),
-        }
+        Trigger::from_flag(&self.retrigger)
     }
 
     fn recalc(&mut self, attack: f32, decay: f32, release: f32) {
+        let time_constants = self.last_curvature.max(MIN_TIME_CONSTANTS);
         if (attack - self.last_attack).abs() > 0.0001 {
             let attack_samples = attack * self.sample_rate;
             self.attack_step = if attack_samples > 0.0 {
@@ -127,7 +244,7 @@ impl Adsr {
             let decay_samples = decay * self.sample_rate;
             self.decay_coeff = if decay_samples > 0.0 {
                 // libm::expf
-                libm::expf(-1.0 / (decay_samples / 3.0))
+                libm::expf(-1.0 / (decay_samples / time_constants))
             } else {
                 0.0
             };
@@ -138,7 +255,7 @@ impl Adsr {
             let release_samples = release * self.sample_rate;
             self.release_coeff = if release_samples > 0.0 {
                 // libm::expf
-                libm::expf(-1.0 / (release_samples / 3.0))
+                libm::expf(-1.0 / (release_samples / time_constants))
             } else {
                 0.0
             };
@@ -146,6 +263,25 @@ impl Adsr {
         }
     }
 
+    /// Enters a state, starting a new segment at the current level.
+    fn enter(&mut self, state: AdsrState) {
+        self.state = state;
+        self.segment_start = self.current_level;
+        self.segment_pos = 0.0;
+    }
+
+    /// Advances a shaped segment and returns its level and whether it has ended.
+    fn shaped(&mut self, curve: EnvelopeCurve, time: f32, to: f32, curvature: f32) -> (f32, bool) {
+        self.segment_pos += 1.0;
+        let samples = time * self.sample_rate;
+        let t = if samples > 0.0 {
+            self.segment_pos / samples
+        } else {
+            1.0
+        };
+        (curve.shape(self.segment_start, to, t, curvature), t >= 1.0)
+    }
+
     /// Sets the attack time parameter (seconds).
     pub fn set_attack(&mut self, time: AudioParam) {
         self.attack_time = time;
@@ -162,6 +298,73 @@ impl Adsr {
     pub fn set_release(&mut self, time: AudioParam) {
         self.release_time = time;
     }
+    /// Sets the curves of the attack, decay and release segments.
+    pub fn set_curves(
+        &mut self,
+        attack: EnvelopeCurve,
+        decay: EnvelopeCurve,
+        release: EnvelopeCurve,
+    ) {
+        self.attack_curve = attack;
+        self.decay_curve = decay;
+        self.release_curve = release;
+    }
+    /// Sets the curvature parameter (default 3.0, see [`EnvelopeCurve::shape`]).
+    pub fn set_curvature(&mut self, curvature: AudioParam) {
+        self.curvature = curvature;
+    }
+    /// Sets the velocity input (0.0 - 1.0), sampled when a note starts.
+    ///
+    /// # Arguments
+    /// * `velocity` - Note velocity, e.g. a `Parameter` set by the note handler.
+    /// * `sensitivity` - How much velocity scales the level (0.0 = not at all, 1.0 = fully).
+    pub fn set_velocity(&mut self, velocity: AudioParam, sensitivity: f32) {
+        self.velocity = velocity;
+        self.velocity_sensitivity = sensitivity.clamp(0.0, 1.0);
+    }
+    /// Sets how much a full velocity shortens the attack (0.0 = not at all, 1.0 = to zero).
+    pub fn set_velocity_attack(&mut self, amount: f32) {
+        self.velocity_attack = amount.clamp(0.0, 1.0);
+    }
+
+    /// Sets what a new note does while the envelope is still sounding.
+    pub fn set_retrigger_mode(&mut self, mode: RetriggerMode) {
+        self.retrigger_mode = mode;
+    }
+
+    /// Makes the envelope cycle attack - decay - attack while the gate is held, as a free
+    /// running modulation source. Each attack starts from the sustain level, so a sustain
+    /// of 0.0 gives full-depth cycles of attack plus decay time.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Sets the gate detection levels.
+    ///
+    /// # Arguments
+    /// * `threshold` - Gate level between closed and open (default 0.5).
+    /// * `hysteresis` - Width of the band around the threshold in which the gate keeps its
+    ///   state (default 0.0).
+    pub fn set_gate_threshold(&mut self, threshold: f32, hysteresis: f32) {
+        self.gate_threshold = threshold;
+        self.gate_hysteresis = hysteresis.max(0.0);
+    }
+
+    /// Starts a note, latching the velocity.
+    fn start(&mut self, velocity: f32) {
+        let velocity = velocity.clamp(0.0, 1.0);
+        self.peak = velocity_scale(velocity, self.velocity_sensitivity);
+        self.attack_scale = 1.0 - self.velocity_attack * velocity;
+        let sounding = self.state != AdsrState::Idle;
+        match self.retrigger_mode {
+            RetriggerMode::Legato if sounding => self.enter(AdsrState::Decay),
+            RetriggerMode::Retrigger => {
+                self.current_level = 0.0;
+                self.enter(AdsrState::Attack);
+            }
+            _ => self.enter(AdsrState::Attack),
+        }
+    }
 }
 
 impl FrameProcessor<Mono> for Adsr {
@@ -183,6 +386,12 @@ impl FrameProcessor<Mono> for Adsr {
         if self.release_buffer.len() < len {
             self.release_buffer.resize(len, 0.0);
         }
+        if self.curvature_buffer.len() < len {
+            self.curvature_buffer.resize(len, 0.0);
+        }
+        if self.velocity_buffer.len() < len {
+            self.velocity_buffer.resize(len, 0.0);
+        }
 
         self.gate
             .process(&mut self.gate_buffer[0..len], sample_index);
@@ -194,6 +403,10 @@ impl FrameProcessor<Mono> for Adsr {
             .process(&mut self.sustain_buffer[0..len], sample_index);
         self.release_time
             .process(&mut self.release_buffer[0..len], sample_index);
+        self.curvature
+            .process(&mut self.curvature_buffer[0..len], sample_index);
+        self.velocity
+            .process(&mut self.velocity_buffer[0..len], sample_index);
 
         // Check for manual retrigger
         let mut triggered = false;
@@ -202,83 +415,94 @@ impl FrameProcessor<Mono> for Adsr {
             triggered = true;
         }
 
-        for (sample, &gate_val, &attack, &decay, &sustain, &release) in buffer
-            .iter_mut()
-            .zip(self.gate_buffer.iter())
-            .zip(self.attack_buffer.iter())
-            .zip(self.decay_buffer.iter())
-            .zip(self.sustain_buffer.iter())
-            .zip(self.release_buffer.iter())
-            .map(|(((((s, g), a), d), su), r)| (s, g, a, d, su, r))
-        {
-            if (attack - self.last_attack).abs() > 0.0001 {
-                let attack_samples = attack * self.sample_rate;
-                self.attack_step = if attack_samples > 0.0 {
-                    1.0 / attack_samples
-                } else {
-                    1.0
-                };
-                self.last_attack = attack;
-            }
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let gate_val = self.gate_buffer[i];
+            let decay = self.decay_buffer[i];
+            let release = self.release_buffer[i];
+            let curvature = self.curvature_buffer[i];
 
-            if (decay - self.last_decay).abs() > 0.0001 {
-                let decay_samples = decay * self.sample_rate;
-                self.decay_coeff = if decay_samples > 0.0 {
-                    // libm::expf
-                    libm::expf(-1.0 / (decay_samples / 3.0))
-                } else {
-                    0.0
-                };
-                self.last_decay = decay;
-            }
-
-            if (release - self.last_release).abs() > 0.0001 {
-                let release_samples = release * self.sample_rate;
-                self.release_coeff = if release_samples > 0.0 {
-                    // libm::expf
-                    libm::expf(-1.0 / (release_samples / 3.0))
-                } else {
-                    0.0
-                };
-                self.last_release = release;
+            if (curvature - self.last_curvature).abs() > 0.0001 {
+                self.last_curvature = curvature;
+                self.last_decay = -1.0;
+                self.last_release = -1.0;
             }
 
+            let half_band = 0.5 * self.gate_hysteresis;
             if triggered {
-                self.state = AdsrState::Attack;
                 self.current_level = 0.0; // Reset level on retrigger
+                self.state = AdsrState::Idle;
+                self.start(self.velocity_buffer[i]);
                 triggered = false; // Only trigger once per block/event
-            } else if gate_val >= 0.5 && self.last_gate < 0.5 {
-                self.state = AdsrState::Attack;
-            } else if gate_val < 0.5 && self.last_gate >= 0.5 {
-                self.state = AdsrState::Release;
+                self.gate_open = gate_val >= self.gate_threshold;
+            } else if !self.gate_open && gate_val >= self.gate_threshold + half_band {
+                self.gate_open = true;
+                self.start(self.velocity_buffer[i]);
+            } else if self.gate_open && gate_val < self.gate_threshold - half_band {
+                self.gate_open = false;
+                self.enter(AdsrState::Release);
             }
-            self.last_gate = gate_val;
+
+            let attack = self.attack_buffer[i] * self.attack_scale;
+            let sustain = self.sustain_buffer[i] * self.peak;
+            self.recalc(attack, decay, release);
 
             match self.state {
                 AdsrState::Idle => {
                     self.current_level = 0.0;
                 }
                 AdsrState::Attack => {
-                    self.current_level += self.attack_step;
-                    if self.current_level >= 1.0 {
-                        self.current_level = 1.0;
-                        self.state = AdsrState::Decay;
+                    let done = if self.attack_curve == EnvelopeCurve::Linear {
+                        self.current_level += self.attack_step * self.peak;
+                        self.current_level >= self.peak
+                    } else {
+                        let (level, done) =
+                            self.shaped(self.attack_curve, attack, self.peak, curvature);
+                        self.current_level = level;
+                        done
+                    };
+                    if done {
+                        self.current_level = self.peak;
+                        self.enter(AdsrState::Decay);
                     }
                 }
                 AdsrState::Decay => {
-                    self.current_level =
-                        sustain + (self.current_level - sustain) * self.decay_coeff;
-                    if (self.current_level - sustain).abs() < 0.001 {
+                    let done = if self.decay_curve == EnvelopeCurve::Exponential
+                        && curvature >= MIN_TIME_CONSTANTS
+                    {
+                        self.current_level =
+                            sustain + (self.current_level - sustain) * self.decay_coeff;
+                        (self.current_level - sustain).abs() < 0.001
+                    } else {
+                        let (level, done) =
+                            self.shaped(self.decay_curve, decay, sustain, curvature);
+                        self.current_level = level;
+                        done
+                    };
+                    if done {
                         self.current_level = sustain;
-                        self.state = AdsrState::Sustain;
+                        if self.looping && self.gate_open {
+                            self.enter(AdsrState::Attack);
+                        } else {
+                            self.state = AdsrState::Sustain;
+                        }
                     }
                 }
                 AdsrState::Sustain => {
                     self.current_level = sustain;
                 }
                 AdsrState::Release => {
-                    self.current_level *= self.release_coeff;
-                    if self.current_level < 0.0001 {
+                    let done = if self.release_curve == EnvelopeCurve::Exponential
+                        && curvature >= MIN_TIME_CONSTANTS
+                    {
+                        self.current_level *= self.release_coeff;
+                        self.current_level < 0.0001
+                    } else {
+                        let (level, done) =
+                            self.shaped(self.release_curve, release, 0.0, curvature);
+                        self.current_level = level;
+                        done
+                    };
+                    if done {
                         self.current_level = 0.0;
                         self.state = AdsrState::Idle;
                     }
@@ -296,12 +520,39 @@ impl FrameProcessor<Mono> for Adsr {
         self.decay_time.set_sample_rate(sample_rate);
         self.sustain_level.set_sample_rate(sample_rate);
         self.release_time.set_sample_rate(sample_rate);
+        self.curvature.set_sample_rate(sample_rate);
+        self.velocity.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.gate.prepare(max_block_size);
+        self.attack_time.prepare(max_block_size);
+        self.decay_time.prepare(max_block_size);
+        self.sustain_level.prepare(max_block_size);
+        self.release_time.prepare(max_block_size);
+        self.curvature.prepare(max_block_size);
+        self.velocity.prepare(max_block_size);
+        self.gate_buffer.resize(max_block_size, 0.0);
+        self.attack_buffer.resize(max_block_size, 0.0);
+        self.decay_buffer.resize(max_block_size, 0.0);
+        self.sustain_buffer.resize(max_block_size, 0.0);
+        self.release_buffer.resize(max_block_size, 0.0);
+        self.curvature_buffer.resize(max_block_size, 0.0);
+        self.velocity_buffer.resize(max_block_size, 0.0);
     }
 
     fn reset(&mut self) {
         self.state = AdsrState::Idle;
         self.current_level = 0.0;
-        self.last_gate = 0.0;
+        self.gate_open = false;
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("attack", &self.attack_time, 0.0, 10.0);
+        tree.add_audio_param("decay", &self.decay_time, 0.0, 10.0);
+        tree.add_audio_param("sustain", &self.sustain_level, 0.0, 1.0);
+        tree.add_audio_param("release", &self.release_time, 0.0, 10.0);
+        tree.add_audio_param("curvature", &self.curvature, 0.0, 10.0);
     }
 
     #[cfg(feature = "debug_visualize")]
@@ -313,6 +564,7 @@ impl FrameProcessor<Mono> for Adsr {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::parameter::Parameter;
 
     #[test]
     fn test_adsr_basic_envelope() {
@@ -400,4 +652,117 @@ mod tests {
             "Should be releasing/decaying to 0"
         );
     }
+
+    #[test]
+    fn test_adsr_curves() {
+        let mut adsr = Adsr::new(
+            AudioParam::Static(1.0),
+            AudioParam::Static(0.5),
+            AudioParam::Static(0.5),
+            AudioParam::Static(0.5),
+            AudioParam::Static(0.1),
+        );
+        adsr.set_sample_rate(100.0);
+        adsr.set_curves(
+            EnvelopeCurve::Logarithmic,
+            EnvelopeCurve::Linear,
+            EnvelopeCurve::Linear,
+        );
+        let mut buffer = [0.0; 100];
+        adsr.process(&mut buffer, 0);
+
+        // A concave attack over 50 samples, then a straight decay over 50.
+        assert!(buffer[9] > 0.4);
+        assert_eq!(buffer[49], 1.0);
+        assert!((buffer[74] - 0.75).abs() < 1e-5);
+        assert_eq!(buffer[99], 0.5);
+    }
+
+    #[test]
+    fn test_zero_curvature_release_reaches_idle() {
+        let gate = Parameter::new(1.0);
+        let mut adsr = Adsr::new(
+            AudioParam::Linked(gate.clone()),
+            AudioParam::Static(0.0),
+            AudioParam::Static(0.1),
+            AudioParam::Static(0.5),
+            AudioParam::Static(0.5),
+        );
+        adsr.set_sample_rate(100.0);
+        adsr.set_curvature(AudioParam::Static(0.0));
+        let mut buffer = [0.0; 50];
+        adsr.process(&mut buffer, 0);
+        assert_eq!(buffer[49], 0.5);
+
+        // A straight release: halfway down after 25 samples, silent after 50.
+        gate.set(0.0);
+        adsr.process(&mut buffer, 50);
+        assert!((buffer[24] - 0.25).abs() < 0.02);
+        assert_eq!(buffer[49], 0.0);
+        assert!(adsr.state == AdsrState::Idle);
+    }
+
+    #[test]
+    fn test_gate_hysteresis_and_retrigger_modes() {
+        let gate = crate::core::parameter::Parameter::new(0.0);
+        let mut adsr = Adsr::new(
+            AudioParam::Linked(gate.clone()),
+            AudioParam::Static(0.1),
+            AudioParam::Static(0.1),
+            AudioParam::Static(0.5),
+            AudioParam::Static(1.0),
+        );
+        adsr.set_sample_rate(100.0);
+        adsr.set_gate_threshold(0.5, 0.2);
+        let mut buffer = [0.0; 5];
+        let mut run = |adsr: &mut Adsr, level: f32| {
+            gate.set(level);
+            adsr.process(&mut buffer, 0);
+            buffer[4]
+        };
+
+        // Noise around the threshold opens the gate once and does not close it.
+        let rising = run(&mut adsr, 0.65);
+        assert!(rising > 0.0);
+        assert_eq!(run(&mut adsr, 0.45), 1.0);
+        let decaying = run(&mut adsr, 0.55);
+        assert!(decaying < 1.0 && decaying > 0.5);
+
+        // Releasing and starting again in legato mode continues from the current level.
+        adsr.set_retrigger_mode(RetriggerMode::Legato);
+        let released = run(&mut adsr, 0.0);
+        assert!(released < 1.0);
+        let legato = run(&mut adsr, 1.0);
+        assert!(legato < released && legato > 0.5);
+
+        adsr.set_retrigger_mode(RetriggerMode::Retrigger);
+        run(&mut adsr, 0.0);
+        assert!((run(&mut adsr, 1.0) - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_looping_cycles_while_held() {
+        let mut adsr = Adsr::new(
+            AudioParam::Static(1.0),
+            AudioParam::Static(0.1),
+            AudioParam::Static(0.1),
+            AudioParam::Static(0.0),
+            AudioParam::Static(0.1),
+        );
+        adsr.set_sample_rate(100.0);
+        adsr.set_curves(
+            EnvelopeCurve::Linear,
+            EnvelopeCurve::Linear,
+            EnvelopeCurve::Linear,
+        );
+        adsr.set_looping(true);
+        let mut buffer = [0.0; 60];
+        adsr.process(&mut buffer, 0);
+
+        // 10 samples up, 10 samples down, repeating.
+        let peaks: Vec<usize> = (0..60).filter(|&i| buffer[i] == 1.0).collect();
+        assert_eq!(peaks, [9, 29, 49]);
+        assert_eq!(buffer[19], 0.0);
+        assert_eq!(buffer[39], 0.0);
+    }
 }
@@ -0,0 +1,244 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::core::preset::ParameterTree;
+use crate::core::tempo::NoteValue;
+use crate::core::transport::Transport;
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+
+/// Longest pattern an [`EuclideanGate`] plays.
+const MAX_STEPS: u32 = 64;
+
+/// Returns true if step `index` of the Euclidean rhythm E(`pulses`, `steps`), rotated
+/// `rotation` steps later, is a pulse.
+///
+/// The pulses are spread as evenly as possible over the steps, with the first one on step
+/// 0 before rotation: E(3, 8) is `x..x..x.`.
+pub fn euclidean_pulse(index: u32, steps: u32, pulses: u32, rotation: u32) -> bool {
+    if steps == 0 {
+        return false;
+    }
+    let shifted = (index % steps + steps - rotation % steps) % steps;
+    (shifted * pulses) % steps < pulses
+}
+
+/// A Euclidean rhythm generator as a control source.
+///
+/// Walks through a pattern of `steps` steps, one per `rate` of the song position of a
+/// [`Transport`], and outputs 1.0 for the first part of every pulse step and 0.0 otherwise.
+/// The pattern counts from song position 0, so it follows seeks and loops, keeps its
+/// place through tempo changes, and outputs 0.0 while the transport is stopped. Steps,
+/// pulses and rotation are read once per block and rounded, so they can be automated or
+/// modulated without rebuilding anything.
+pub struct EuclideanGate {
+    transport: Transport,
+    rate: NoteValue,
+    steps: AudioParam,
+    pulses: AudioParam,
+    rotation: AudioParam,
+    gate_length: f32,
+    param_buffer: Vec<f32>,
+}
+
+impl EuclideanGate {
+    /// Creates a new EuclideanGate with gates half a step long.
+    ///
+    /// # Arguments
+    /// * `transport` - The song timeline.
+    /// * `rate` - Length of one step, e.g. `NoteValue::Sixteenth`.
+    /// * `steps` - Pattern length (1 - 64).
+    /// * `pulses` - Number of pulses in the pattern (0 - steps).
+    /// * `rotation` - Steps the pattern is moved later.
+    pub fn new(
+        transport: &Transport,
+        rate: NoteValue,
+        steps: AudioParam,
+        pulses: AudioParam,
+        rotation: AudioParam,
+    ) -> Self {
+        EuclideanGate {
+            transport: transport.clone(),
+            rate,
+            steps,
+            pulses,
+            rotation,
+            gate_length: 0.5,
+            param_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the step length.
+    pub fn set_rate(&mut self, rate: NoteValue) {
+        self.rate = rate;
+    }
+
+    /// Sets the share of a pulse step the gate stays high (0.0 - 1.0, 1.0 = tied).
+    pub fn set_gate_length(&mut self, length: f32) {
+        self.gate_length = length.clamp(0.0, 1.0);
+    }
+}
+
+impl FrameProcessor<Mono> for EuclideanGate {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        if self.param_buffer.len() < buffer.len() {
+            self.param_buffer.resize(buffer.len(), 0.0);
+        }
+        let values = &mut self.param_buffer[0..buffer.len()];
+        let steps = libm::roundf(self.steps.block_value(values, sample_index))
+            .clamp(1.0, MAX_STEPS as f32) as u32;
+        let pulses = libm::roundf(self.pulses.block_value(values, sample_index))
+            .clamp(0.0, steps as f32) as u32;
+        let rotation = (libm::roundf(self.rotation.block_value(values, sample_index)) as i64)
+            .rem_euclid(steps as i64) as u32;
+        let step_beats = self.rate.beats() as f64;
+
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let index = sample_index + i as u64;
+            if !self.transport.is_playing_at(index) {
+                *sample = 0.0;
+                continue;
+            }
+            let position = self.transport.beats_at(index) / step_beats;
+            let step = libm::floor(position);
+            let phase = (position - step) as f32;
+            let pulse =
+                euclidean_pulse((step as u64 % steps as u64) as u32, steps, pulses, rotation);
+            *sample = if pulse && phase < self.gate_length {
+                1.0
+            } else {
+                0.0
+            };
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.steps.set_sample_rate(sample_rate);
+        self.pulses.set_sample_rate(sample_rate);
+        self.rotation.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.steps.prepare(max_block_size);
+        self.pulses.prepare(max_block_size);
+        self.rotation.prepare(max_block_size);
+        self.param_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.steps.reset();
+        self.pulses.reset();
+        self.rotation.reset();
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("steps", &self.steps, 1.0, MAX_STEPS as f32);
+        tree.add_audio_param("pulses", &self.pulses, 0.0, MAX_STEPS as f32);
+        tree.add_audio_param("rotation", &self.rotation, 0.0, (MAX_STEPS - 1) as f32);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "EuclideanGate"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parameter::Parameter;
+    use alloc::vec::Vec;
+
+    fn pattern(steps: u32, pulses: u32, rotation: u32) -> Vec<bool> {
+        (0..steps)
+            .map(|i| euclidean_pulse(i, steps, pulses, rotation))
+            .collect()
+    }
+
+    #[test]
+    fn test_patterns() {
+        let (x, o) = (true, false);
+        assert_eq!(pattern(8, 3, 0), [x, o, o, x, o, o, x, o]);
+        assert_eq!(pattern(8, 3, 2), [x, o, x, o, o, x, o, o]);
+        assert_eq!(pattern(5, 2, 0), [x, o, o, x, o]);
+        assert_eq!(pattern(4, 4, 1), [x; 4]);
+        assert_eq!(pattern(4, 0, 0), [o; 4]);
+    }
+
+    #[test]
+    fn test_gate_follows_transport() {
+        // 120 BPM at 1 kHz: a sixteenth step is 125 samples.
+        let transport = Transport::new(1000.0);
+        let pulses = Parameter::new(3.0);
+        let mut gate = EuclideanGate::new(
+            &transport,
+            NoteValue::Sixteenth,
+            AudioParam::Static(8.0),
+            AudioParam::Linked(pulses.clone()),
+            AudioParam::Static(0.0),
+        );
+        let mut timeline = transport.clone();
+        let mut render = |gate: &mut EuclideanGate, start: u64| {
+            let mut buffer = [0.0; 1000];
+            FrameProcessor::<Mono>::process(&mut timeline, &mut buffer, start);
+            gate.process(&mut buffer, start);
+            buffer
+        };
+
+        transport.stop();
+        assert!(render(&mut gate, 0).iter().all(|&g| g == 0.0));
+
+        transport.start();
+        let out = render(&mut gate, 1000);
+        let on: Vec<usize> = (0..8).filter(|&s| out[s * 125 + 10] == 1.0).collect();
+        assert_eq!(on, [0, 3, 6]);
+        assert_eq!(out[70], 0.0);
+
+        pulses.set(8.0);
+        let out = render(&mut gate, 2000);
+        assert!((0..8).all(|s| out[s * 125 + 10] == 1.0));
+    }
+
+    #[test]
+    fn test_tempo_change_keeps_step() {
+        let transport = Transport::new(1000.0);
+        let mut gate = EuclideanGate::new(
+            &transport,
+            NoteValue::Sixteenth,
+            AudioParam::Static(8.0),
+            AudioParam::Static(3.0),
+            AudioParam::Static(0.0),
+        );
+        let mut timeline = transport.clone();
+        let mut buffer = [0.0; 1000];
+        transport.start();
+        FrameProcessor::<Mono>::process(&mut timeline, &mut buffer, 0);
+        gate.process(&mut buffer, 0);
+
+        // Two beats in, the tempo halves: the next bar of the pattern starts right away,
+        // with 250-sample steps.
+        transport.set_bpm(60.0);
+        FrameProcessor::<Mono>::process(&mut timeline, &mut buffer, 1000);
+        gate.process(&mut buffer, 1000);
+        assert_eq!((buffer[10], buffer[760]), (1.0, 1.0));
+        assert_eq!((buffer[260], buffer[510]), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_smoothed_parameters_glide_per_block() {
+        let transport = Transport::new(1000.0);
+        let steps = Parameter::new(4.0);
+        let mut gate = EuclideanGate::new(
+            &transport,
+            NoteValue::Sixteenth,
+            AudioParam::smoothed(steps.clone(), 10.0),
+            AudioParam::Static(1.0),
+            AudioParam::Static(0.0),
+        );
+        gate.set_sample_rate(1000.0);
+
+        // The 10-sample glide ends within one 64-sample block, though it is read once.
+        steps.set(8.0);
+        gate.process(&mut [0.0; 64], 0);
+        assert_eq!(gate.steps.get_constant(), Some(8.0));
+    }
+}
@@ -0,0 +1,257 @@
+use crate::core::utils::FastRng;
+use core::f32::consts::PI;
+
+/// The spectral color of an excitation noise source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NoiseColor {
+    /// Flat spectrum.
+    White,
+    /// -3 dB/octave (softer, breathier).
+    Pink,
+    /// -6 dB/octave (dark, rumbly).
+    Brown,
+    /// +3 dB/octave (bright, hissy).
+    Blue,
+    /// +6 dB/octave (thin, high hiss).
+    Violet,
+}
+
+/// Shapes white noise into the other colors, all at about the level of the white noise.
+///
+/// Every color is computed from the same white sample, so they stay correlated and can be
+/// crossfaded.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ColorFilter {
+    pink: [f32; 3],
+    last_pink: f32,
+    brown: f32,
+    last_white: f32,
+}
+
+impl ColorFilter {
+    /// Returns brown, pink, white, blue and violet noise for one white sample.
+    #[inline(always)]
+    pub(crate) fn spectrum(&mut self, white: f32) -> [f32; 5] {
+        // Paul Kellet's economy pink filter.
+        self.pink[0] = 0.99765 * self.pink[0] + white * 0.099_046;
+        self.pink[1] = 0.963 * self.pink[1] + white * 0.296_516_4;
+        self.pink[2] = 0.57 * self.pink[2] + white * 1.052_691_3;
+        let pink = (self.pink[0] + self.pink[1] + self.pink[2] + white * 0.1848) * 0.25;
+        self.brown = (self.brown + white * 0.02) * 0.998;
+
+        // Differencing tilts the spectrum up by 6 dB/octave.
+        let blue = (pink - self.last_pink) * 2.2;
+        let violet = (white - self.last_white) * 0.707;
+        self.last_pink = pink;
+        self.last_white = white;
+
+        [self.brown * 3.5, pink, white, blue, violet]
+    }
+
+    /// Shapes one white sample into `color`.
+    #[inline(always)]
+    pub(crate) fn process(&mut self, color: NoiseColor, white: f32) -> f32 {
+        let [brown, pink, white, blue, violet] = self.spectrum(white);
+        match color {
+            NoiseColor::White => white,
+            NoiseColor::Pink => pink,
+            NoiseColor::Brown => brown,
+            NoiseColor::Blue => blue,
+            NoiseColor::Violet => violet,
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// A configurable noise source used to excite physical models.
+///
+/// Shared by [`KarplusStrong`](crate::synthesis::karplus_strong::KarplusStrong) (pick noise)
+/// and [`BrassModel`](crate::synthesis::brass_model::BrassModel) (breath noise). The color,
+/// level and bandwidth shape the character of the excitation, and the seed makes it
+/// reproducible: `reset` restarts the sequence from the seed.
+#[derive(Clone)]
+pub struct ExcitationNoise {
+    color: NoiseColor,
+    level: f32,
+    bandwidth: f32,
+    seed: u32,
+    sample_rate: f32,
+
+    rng_state: u32,
+    filter: ColorFilter,
+    lp_state: f32,
+    lp_coeff: f32,
+}
+
+impl ExcitationNoise {
+    /// Creates a new ExcitationNoise.
+    ///
+    /// # Arguments
+    /// * `color` - Spectral color of the noise.
+    /// * `level` - Output level (linear).
+    /// * `bandwidth` - Upper bandwidth limit in Hz. Values at or above Nyquist disable the filter.
+    pub fn new(color: NoiseColor, level: f32, bandwidth: f32) -> Self {
+        let mut noise = ExcitationNoise {
+            color,
+            level,
+            bandwidth,
+            seed: 12345,
+            sample_rate: 44100.0,
+            rng_state: 12345,
+            filter: ColorFilter::default(),
+            lp_state: 0.0,
+            lp_coeff: 1.0,
+        };
+        noise.update_filter();
+        noise
+    }
+
+    /// The default pick excitation of KarplusStrong: full-level, full-band white noise.
+    pub fn pluck() -> Self {
+        Self::new(NoiseColor::White, 1.0, f32::INFINITY)
+    }
+
+    /// The default breath noise of BrassModel: quiet, full-band white noise.
+    pub fn breath() -> Self {
+        Self::new(NoiseColor::White, 0.02, f32::INFINITY)
+    }
+
+    /// Sets the seed and returns the source. The sequence restarts from the new seed.
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self.reset();
+        self
+    }
+
+    /// Sets the noise color.
+    pub fn set_color(&mut self, color: NoiseColor) {
+        self.color = color;
+    }
+
+    /// Sets the output level (linear).
+    pub fn set_level(&mut self, level: f32) {
+        self.level = level;
+    }
+
+    /// Sets the bandwidth limit in Hz.
+    pub fn set_bandwidth(&mut self, bandwidth: f32) {
+        self.bandwidth = bandwidth;
+        self.update_filter();
+    }
+
+    /// Returns the output level.
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// Sets the sample rate.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.update_filter();
+    }
+
+    /// Restarts the noise sequence from the seed and clears the filter state.
+    pub fn reset(&mut self) {
+        self.rng_state = self.seed;
+        self.filter.reset();
+        self.lp_state = 0.0;
+    }
+
+    fn update_filter(&mut self) {
+        let nyquist = self.sample_rate * 0.5;
+        self.lp_coeff = if self.bandwidth >= nyquist {
+            1.0
+        } else {
+            1.0 - libm::expf(-2.0 * PI * self.bandwidth.max(1.0) / self.sample_rate)
+        };
+    }
+
+    /// Generates the next noise sample.
+    #[inline(always)]
+    pub fn next_sample(&mut self) -> f32 {
+        let white = FastRng::next_f32_bipolar_stateless(&mut self.rng_state);
+
+        let colored = self.filter.process(self.color, white);
+
+        let shaped = if self.lp_coeff < 1.0 {
+            self.lp_state += self.lp_coeff * (colored - self.lp_state);
+            self.lp_state
+        } else {
+            colored
+        };
+
+        shaped * self.level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_match_raw_lcg() {
+        let mut noise = ExcitationNoise::pluck();
+        let mut state = 12345;
+        for _ in 0..64 {
+            assert_eq!(
+                noise.next_sample(),
+                FastRng::next_f32_bipolar_stateless(&mut state)
+            );
+        }
+    }
+
+    #[test]
+    fn test_colors_tilt_the_spectrum() {
+        let mut filter = ColorFilter::default();
+        let mut state = 1;
+        let mut power = [0.0f32; 5];
+        let mut hf = [0.0f32; 5];
+        let mut last = [0.0f32; 5];
+        for _ in 0..100_000 {
+            let s = filter.spectrum(FastRng::next_f32_bipolar_stateless(&mut state));
+            for k in 0..5 {
+                power[k] += s[k] * s[k];
+                hf[k] += (s[k] - last[k]) * (s[k] - last[k]);
+            }
+            last = s;
+        }
+        // Brown to violet: each brighter than the last, all at a similar level.
+        let brightness: [f32; 5] = core::array::from_fn(|k| hf[k] / power[k]);
+        assert!(brightness.windows(2).all(|w| w[0] < w[1]));
+        for p in power {
+            let rms = libm::sqrtf(p / 100_000.0);
+            assert!((0.4..0.7).contains(&rms));
+        }
+    }
+
+    #[test]
+    fn test_reset_reproduces_sequence() {
+        let mut noise = ExcitationNoise::new(NoiseColor::Pink, 0.5, 2000.0).with_seed(7);
+        let first: [f32; 16] = core::array::from_fn(|_| noise.next_sample());
+        noise.reset();
+        let second: [f32; 16] = core::array::from_fn(|_| noise.next_sample());
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_bandwidth_reduces_high_frequencies() {
+        fn hf_energy(noise: &mut ExcitationNoise) -> f32 {
+            let mut last = 0.0;
+            let mut energy = 0.0;
+            for _ in 0..4096 {
+                let s = noise.next_sample();
+                energy += (s - last) * (s - last);
+                last = s;
+            }
+            energy
+        }
+
+        let mut wide = ExcitationNoise::new(NoiseColor::White, 1.0, f32::INFINITY);
+        let mut narrow = ExcitationNoise::new(NoiseColor::White, 1.0, 500.0);
+        assert!(hf_energy(&mut narrow) < hf_energy(&mut wide) * 0.1);
+    }
+}
@@ -0,0 +1,410 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Stereo;
+use crate::core::utils::FastRng;
+use crate::FrameProcessor;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+
+const MAX_GRAINS: usize = 64;
+
+#[derive(Clone, Copy)]
+struct Grain {
+    active: bool,
+    position: f32,
+    increment: f32,
+    age: f32,
+    length: f32,
+    taper: f32,
+    gain_l: f32,
+    gain_r: f32,
+}
+
+impl Grain {
+    const IDLE: Grain = Grain {
+        active: false,
+        position: 0.0,
+        increment: 1.0,
+        age: 0.0,
+        length: 1.0,
+        taper: 1.0,
+        gain_l: 0.0,
+        gain_r: 0.0,
+    };
+
+    /// Tukey window: raised-cosine fades over `taper / 2` of the grain at each end.
+    #[inline(always)]
+    fn envelope(&self) -> f32 {
+        let x = self.age / self.length;
+        let edge = self.taper * 0.5;
+        if x < edge {
+            0.5 * (1.0 - libm::cosf(PI * x / edge))
+        } else if x > 1.0 - edge {
+            0.5 * (1.0 - libm::cosf(PI * (1.0 - x) / edge))
+        } else {
+            1.0
+        }
+    }
+}
+
+/// A granular texture engine playing grains from a stored buffer.
+///
+/// Grains are spawned at the given density, each reading the source around `position`
+/// (offset randomly by `spray`), at a pitch offset randomly by `pitch_jitter`, shaped by a
+/// Tukey envelope and panned randomly within `pan_spread`. Up to 64 grains play at once;
+/// further grains are dropped until a voice becomes free.
+///
+/// The source is shared via `Arc` and is assumed to be recorded at the processing sample rate.
+pub struct GranularEngine {
+    source: Arc<Vec<f32>>,
+    grains: [Grain; MAX_GRAINS],
+    position: AudioParam,
+    grain_size: AudioParam,
+    density: AudioParam,
+    spray: AudioParam,
+    pitch: AudioParam,
+    pitch_jitter: AudioParam,
+    shape: AudioParam,
+    pan_spread: AudioParam,
+
+    sample_rate: f32,
+    until_next_grain: f32,
+    rng_state: u32,
+
+    position_buffer: Vec<f32>,
+    size_buffer: Vec<f32>,
+    density_buffer: Vec<f32>,
+    spray_buffer: Vec<f32>,
+    pitch_buffer: Vec<f32>,
+    jitter_buffer: Vec<f32>,
+    shape_buffer: Vec<f32>,
+    pan_buffer: Vec<f32>,
+}
+
+impl GranularEngine {
+    /// Creates a new GranularEngine.
+    ///
+    /// # Arguments
+    /// * `source` - The sample buffer grains are played from (mono).
+    /// * `position` - Playback position in the source (0.0 = start, 1.0 = end).
+    /// * `grain_size` - Grain length in seconds.
+    /// * `density` - Grains spawned per second.
+    pub fn new(
+        source: Arc<Vec<f32>>,
+        position: AudioParam,
+        grain_size: AudioParam,
+        density: AudioParam,
+    ) -> Self {
+        GranularEngine {
+            source,
+            grains: [Grain::IDLE; MAX_GRAINS],
+            position,
+            grain_size,
+            density,
+            spray: AudioParam::Static(0.0),
+            pitch: AudioParam::Static(0.0),
+            pitch_jitter: AudioParam::Static(0.0),
+            shape: AudioParam::Static(1.0),
+            pan_spread: AudioParam::Static(0.0),
+            sample_rate: 44100.0,
+            until_next_grain: 0.0,
+            rng_state: 12345,
+            position_buffer: Vec::with_capacity(128),
+            size_buffer: Vec::with_capacity(128),
+            density_buffer: Vec::with_capacity(128),
+            spray_buffer: Vec::with_capacity(128),
+            pitch_buffer: Vec::with_capacity(128),
+            jitter_buffer: Vec::with_capacity(128),
+            shape_buffer: Vec::with_capacity(128),
+            pan_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Replaces the source buffer. Playing grains are stopped.
+    pub fn set_source(&mut self, source: Arc<Vec<f32>>) {
+        self.source = source;
+        self.grains = [Grain::IDLE; MAX_GRAINS];
+    }
+
+    /// Sets the playback position (0.0 - 1.0).
+    pub fn set_position(&mut self, position: AudioParam) {
+        self.position = position;
+    }
+
+    /// Sets the grain length in seconds.
+    pub fn set_grain_size(&mut self, grain_size: AudioParam) {
+        self.grain_size = grain_size;
+    }
+
+    /// Sets the grain density in grains per second.
+    pub fn set_density(&mut self, density: AudioParam) {
+        self.density = density;
+    }
+
+    /// Sets the random position offset in seconds (positive or negative).
+    pub fn set_spray(&mut self, spray: AudioParam) {
+        self.spray = spray;
+    }
+
+    /// Sets the grain pitch in semitones.
+    pub fn set_pitch(&mut self, pitch: AudioParam) {
+        self.pitch = pitch;
+    }
+
+    /// Sets the random pitch offset range in semitones (positive or negative).
+    pub fn set_pitch_jitter(&mut self, pitch_jitter: AudioParam) {
+        self.pitch_jitter = pitch_jitter;
+    }
+
+    /// Sets the envelope shape (0.0 = nearly rectangular, 1.0 = Hann).
+    pub fn set_shape(&mut self, shape: AudioParam) {
+        self.shape = shape;
+    }
+
+    /// Sets the random pan range (0.0 = all grains centered, 1.0 = full stereo).
+    pub fn set_pan_spread(&mut self, pan_spread: AudioParam) {
+        self.pan_spread = pan_spread;
+    }
+
+    /// Returns the number of grains currently playing.
+    pub fn active_grains(&self) -> usize {
+        self.grains.iter().filter(|g| g.active).count()
+    }
+
+    fn spawn_grain(&mut self, i: usize) {
+        let Some(slot) = self.grains.iter().position(|g| !g.active) else {
+            return;
+        };
+        let len = self.source.len() as f32;
+
+        let spray = self.spray_buffer[i] * self.sample_rate;
+        let offset = FastRng::next_f32_bipolar_stateless(&mut self.rng_state) * spray;
+        let mut position = self.position_buffer[i].clamp(0.0, 1.0) * len + offset;
+        while position < 0.0 {
+            position += len;
+        }
+        while position >= len {
+            position -= len;
+        }
+
+        let jitter = FastRng::next_f32_bipolar_stateless(&mut self.rng_state);
+        let semitones = self.pitch_buffer[i] + jitter * self.jitter_buffer[i];
+
+        let pan = FastRng::next_f32_bipolar_stateless(&mut self.rng_state)
+            * self.pan_buffer[i].clamp(0.0, 1.0);
+        let angle = (pan + 1.0) * PI / 4.0;
+
+        self.grains[slot] = Grain {
+            active: true,
+            position,
+            increment: libm::powf(2.0, semitones / 12.0),
+            age: 0.0,
+            length: (self.size_buffer[i] * self.sample_rate).max(2.0),
+            taper: self.shape_buffer[i].clamp(0.01, 1.0),
+            gain_l: libm::cosf(angle),
+            gain_r: libm::sinf(angle),
+        };
+    }
+}
+
+impl FrameProcessor<Stereo> for GranularEngine {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let frames = buffer.len() / 2;
+        buffer.fill(0.0);
+        if self.source.is_empty() {
+            return;
+        }
+
+        for buf in [
+            &mut self.position_buffer,
+            &mut self.size_buffer,
+            &mut self.density_buffer,
+            &mut self.spray_buffer,
+            &mut self.pitch_buffer,
+            &mut self.jitter_buffer,
+            &mut self.shape_buffer,
+            &mut self.pan_buffer,
+        ] {
+            if buf.len() < frames {
+                buf.resize(frames, 0.0);
+            }
+        }
+        self.position
+            .process(&mut self.position_buffer[0..frames], sample_index);
+        self.grain_size
+            .process(&mut self.size_buffer[0..frames], sample_index);
+        self.density
+            .process(&mut self.density_buffer[0..frames], sample_index);
+        self.spray
+            .process(&mut self.spray_buffer[0..frames], sample_index);
+        self.pitch
+            .process(&mut self.pitch_buffer[0..frames], sample_index);
+        self.pitch_jitter
+            .process(&mut self.jitter_buffer[0..frames], sample_index);
+        self.shape
+            .process(&mut self.shape_buffer[0..frames], sample_index);
+        self.pan_spread
+            .process(&mut self.pan_buffer[0..frames], sample_index);
+
+        let len = self.source.len();
+        let len_f = len as f32;
+
+        for i in 0..frames {
+            self.until_next_grain -= 1.0;
+            let density = self.density_buffer[i];
+            if self.until_next_grain <= 0.0 && density > 0.0 {
+                self.spawn_grain(i);
+                self.until_next_grain += self.sample_rate / density;
+                self.until_next_grain = self.until_next_grain.max(1.0);
+            }
+
+            let mut out_l = 0.0;
+            let mut out_r = 0.0;
+            for grain in self.grains.iter_mut().filter(|g| g.active) {
+                let idx_a = grain.position as usize;
+                let idx_b = if idx_a + 1 >= len { 0 } else { idx_a + 1 };
+                let frac = grain.position - idx_a as f32;
+                let value = self.source[idx_a] + (self.source[idx_b] - self.source[idx_a]) * frac;
+
+                let sample = value * grain.envelope();
+                out_l += sample * grain.gain_l;
+                out_r += sample * grain.gain_r;
+
+                grain.position += grain.increment;
+                if grain.position >= len_f {
+                    grain.position -= len_f;
+                }
+                grain.age += 1.0;
+                if grain.age >= grain.length {
+                    grain.active = false;
+                }
+            }
+
+            buffer[i * 2] = out_l;
+            buffer[i * 2 + 1] = out_r;
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.position.set_sample_rate(sample_rate);
+        self.grain_size.set_sample_rate(sample_rate);
+        self.density.set_sample_rate(sample_rate);
+        self.spray.set_sample_rate(sample_rate);
+        self.pitch.set_sample_rate(sample_rate);
+        self.pitch_jitter.set_sample_rate(sample_rate);
+        self.shape.set_sample_rate(sample_rate);
+        self.pan_spread.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.position.prepare(max_block_size);
+        self.grain_size.prepare(max_block_size);
+        self.density.prepare(max_block_size);
+        self.spray.prepare(max_block_size);
+        self.pitch.prepare(max_block_size);
+        self.pitch_jitter.prepare(max_block_size);
+        self.shape.prepare(max_block_size);
+        self.pan_spread.prepare(max_block_size);
+        self.position_buffer.resize(max_block_size, 0.0);
+        self.size_buffer.resize(max_block_size, 0.0);
+        self.density_buffer.resize(max_block_size, 0.0);
+        self.spray_buffer.resize(max_block_size, 0.0);
+        self.pitch_buffer.resize(max_block_size, 0.0);
+        self.jitter_buffer.resize(max_block_size, 0.0);
+        self.shape_buffer.resize(max_block_size, 0.0);
+        self.pan_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.grains = [Grain::IDLE; MAX_GRAINS];
+        self.until_next_grain = 0.0;
+        self.rng_state = 12345;
+        self.position.reset();
+        self.grain_size.reset();
+        self.density.reset();
+        self.spray.reset();
+        self.pitch.reset();
+        self.pitch_jitter.reset();
+        self.shape.reset();
+        self.pan_spread.reset();
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "GranularEngine"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn half_silent_source() -> Arc<Vec<f32>> {
+        let mut data = vec![0.0; 44100];
+        data[22050..].fill(1.0);
+        Arc::new(data)
+    }
+
+    fn render(engine: &mut GranularEngine) -> Vec<f32> {
+        let mut buffer = vec![0.0; 8192];
+        for (i, block) in buffer.chunks_mut(256).enumerate() {
+            engine.process(block, (i * 128) as u64);
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_grains_follow_position() {
+        let mut engine = GranularEngine::new(
+            half_silent_source(),
+            AudioParam::Static(0.1),
+            AudioParam::ms(20.0),
+            AudioParam::Static(100.0),
+        );
+        assert!(render(&mut engine).iter().all(|&s| s == 0.0));
+
+        engine.reset();
+        engine.set_position(AudioParam::Static(0.75));
+        let out = render(&mut engine);
+        assert!(out.iter().any(|&s| s > 0.1));
+        assert!(engine.active_grains() > 0);
+    }
+
+    #[test]
+    fn test_centered_grains_are_mono() {
+        let mut engine = GranularEngine::new(
+            half_silent_source(),
+            AudioParam::Static(0.75),
+            AudioParam::ms(30.0),
+            AudioParam::Static(200.0),
+        );
+        engine.set_spray(AudioParam::ms(100.0));
+        engine.set_pitch_jitter(AudioParam::Static(2.0));
+        let out = render(&mut engine);
+        for frame in out.chunks(2) {
+            assert!((frame[0] - frame[1]).abs() < 1e-6);
+        }
+
+        engine.reset();
+        engine.set_pan_spread(AudioParam::Static(1.0));
+        let out = render(&mut engine);
+        assert!(out.chunks(2).any(|f| (f[0] - f[1]).abs() > 1e-3));
+    }
+
+    #[test]
+    fn test_reset_is_deterministic() {
+        let mut engine = GranularEngine::new(
+            half_silent_source(),
+            AudioParam::Static(0.6),
+            AudioParam::ms(25.0),
+            AudioParam::Static(80.0),
+        );
+        engine.set_spray(AudioParam::ms(200.0));
+        engine.set_pan_spread(AudioParam::Static(1.0));
+        let first = render(&mut engine);
+        engine.reset();
+        assert_eq!(first, render(&mut engine));
+    }
+}
@@ -1,23 +1,147 @@
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::Mono;
+use crate::synthesis::excitation::ExcitationNoise;
 use crate::FrameProcessor;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::f32::consts::PI;
+
+const FEEDBACK: f32 = 0.995;
+const SYMPATHETIC_FEEDBACK: f32 = 0.998;
+const SYMPATHETIC_DAMPING: f32 = 0.3;
+/// Share of the main string output that drives the sympathetic strings.
+const SYMPATHETIC_COUPLING: f32 = 0.02;
+
+/// A tuned feedback delay loop: integer delay line, first-order allpass for the
+/// fractional part and the one-pole damping filter.
+struct StringLoop {
+    delay_line: Vec<f32>,
+    write_ptr: usize,
+    delay: usize,
+    allpass_coeff: f32,
+    allpass_x1: f32,
+    allpass_y1: f32,
+    filter_state: f32,
+    last_pitch: f32,
+    last_damping: f32,
+}
+
+impl StringLoop {
+    fn new(max_delay: usize) -> Self {
+        StringLoop {
+            delay_line: vec![0.0; max_delay],
+            write_ptr: 0,
+            delay: 1,
+            allpass_coeff: 0.0,
+            allpass_x1: 0.0,
+            allpass_y1: 0.0,
+            filter_state: 0.0,
+            last_pitch: -1.0,
+            last_damping: -1.0,
+        }
+    }
+
+    /// Splits the loop delay into the integer delay line and the allpass fraction.
+    ///
+    /// The damping filter delays the fundamental too (by `atan(d·sinω / (1 - d·cosω)) / ω`
+    /// samples), so that is subtracted from the period first.
+    fn tune(&mut self, pitch: f32, damping: f32, sample_rate: f32) {
+        if (pitch - self.last_pitch).abs() <= 0.001 && (damping - self.last_damping).abs() <= 0.001
+        {
+            return;
+        }
+        self.last_pitch = pitch;
+        self.last_damping = damping;
+
+        let period = sample_rate / pitch.max(1.0);
+        let omega = 2.0 * PI / period;
+        let filter_delay = libm::atan2f(
+            damping * libm::sinf(omega),
+            1.0 - damping * libm::cosf(omega),
+        ) / omega;
+
+        let max_delay = (self.delay_line.len() - 1) as f32;
+        let length = (period - filter_delay).clamp(1.1, max_delay);
+        let mut delay = length as usize;
+        let mut frac = length - delay as f32;
+        // Keep the allpass fraction away from 0, where its coefficient approaches 1.
+        if frac < 0.1 && delay > 1 {
+            delay -= 1;
+            frac += 1.0;
+        }
+        self.delay = delay;
+        // Exact phase delay of `frac` samples at the fundamental, rather than the
+        // low-frequency approximation (1 - frac) / (1 + frac).
+        self.allpass_coeff =
+            libm::sinf((1.0 - frac) * omega * 0.5) / libm::sinf((1.0 + frac) * omega * 0.5);
+    }
+
+    #[inline(always)]
+    fn tick(&mut self, input: f32, damping: f32, feedback: f32) -> f32 {
+        let len = self.delay_line.len();
+        let mut read_ptr = self.write_ptr + len - self.delay;
+        if read_ptr >= len {
+            read_ptr -= len;
+        }
+        let delayed = self.delay_line[read_ptr];
+
+        let fractional = self.allpass_coeff * (delayed - self.allpass_y1) + self.allpass_x1;
+        self.allpass_x1 = delayed;
+        self.allpass_y1 = fractional;
+
+        let filtered = damping * self.filter_state + (1.0 - damping) * fractional;
+        self.filter_state = filtered;
+
+        let output = input + filtered * feedback;
+        self.delay_line[self.write_ptr] = output;
+        self.write_ptr += 1;
+        if self.write_ptr >= len {
+            self.write_ptr -= len;
+        }
+        output
+    }
+
+    fn resize(&mut self, max_delay: usize) {
+        if max_delay > self.delay_line.len() {
+            self.delay_line.resize(max_delay, 0.0);
+        }
+        self.last_pitch = -1.0;
+    }
+
+    fn reset(&mut self) {
+        self.delay_line.fill(0.0);
+        self.write_ptr = 0;
+        self.allpass_x1 = 0.0;
+        self.allpass_y1 = 0.0;
+        self.filter_state = 0.0;
+    }
+}
+
+struct SympatheticString {
+    frequency: f32,
+    string: StringLoop,
+}
 
 /// A Karplus-Strong string synthesis model.
 ///
-/// Simulates a plucked string using a delay line and a low-pass filter.
+/// Simulates a plucked string using a delay line and a low-pass filter. The fractional
+/// part of the period is realized with a first-order allpass and the delay of the
+/// damping filter is compensated, so high notes stay in tune.
+///
+/// An optional bank of sympathetic strings, tuned to fixed frequencies, is driven by
+/// the string output and rings along with it (like the resonance strings of a sitar or
+/// the undamped strings of a piano).
 pub struct KarplusStrong {
-    delay_line: Vec<f32>,
-    write_ptr: usize,
+    string: StringLoop,
+    sympathetic: Vec<SympatheticString>,
     pitch: AudioParam,
     gate: AudioParam,
     damping: AudioParam,
     pick_position: AudioParam,
+    sympathetic_level: AudioParam,
     sample_rate: f32,
 
     last_gate: f32,
-    filter_state: f32,
     noise_burst_samples: usize,
     current_burst_sample: usize,
 
@@ -25,8 +149,9 @@ pub struct KarplusStrong {
     gate_buffer: Vec<f32>,
     damping_buffer: Vec<f32>,
     pick_buffer: Vec<f32>,
+    sympathetic_buffer: Vec<f32>,
 
-    rng_state: u32,
+    excitation: ExcitationNoise,
 }
 
 impl KarplusStrong {
@@ -47,34 +172,64 @@ impl KarplusStrong {
         let max_delay = (sample_rate / 20.0) as usize;
 
         KarplusStrong {
-            delay_line: vec![0.0; max_delay],
-            write_ptr: 0,
+            string: StringLoop::new(max_delay),
+            sympathetic: Vec::new(),
             pitch,
             gate,
             damping,
             pick_position,
+            sympathetic_level: AudioParam::Static(0.5),
             sample_rate,
             last_gate: 0.0,
-            filter_state: 0.0,
             noise_burst_samples: 0,
             current_burst_sample: 0,
             pitch_buffer: Vec::with_capacity(128),
             gate_buffer: Vec::with_capacity(128),
             damping_buffer: Vec::with_capacity(128),
             pick_buffer: Vec::with_capacity(128),
-            rng_state: 12345,
+            sympathetic_buffer: Vec::with_capacity(128),
+            excitation: ExcitationNoise::pluck(),
         }
     }
 
-    fn next_random(&mut self) -> f32 {
-        crate::core::utils::FastRng::next_f32_bipolar_stateless(&mut self.rng_state)
+    /// Sets the noise source used to excite the string when plucked.
+    pub fn set_excitation(&mut self, excitation: ExcitationNoise) {
+        self.excitation = excitation;
+        self.excitation.set_sample_rate(self.sample_rate);
+    }
+
+    /// Returns a mutable reference to the excitation noise source.
+    pub fn excitation_mut(&mut self) -> &mut ExcitationNoise {
+        &mut self.excitation
+    }
+
+    /// Sets the tuning of the sympathetic strings, replacing any previous ones.
+    ///
+    /// An empty slice removes them. Allocates, so call it outside the audio thread.
+    ///
+    /// # Arguments
+    /// * `frequencies` - Frequency of each sympathetic string in Hz (20 Hz and up).
+    pub fn set_sympathetic_strings(&mut self, frequencies: &[f32]) {
+        let max_delay = (self.sample_rate / 20.0) as usize;
+        self.sympathetic = frequencies
+            .iter()
+            .map(|&frequency| {
+                let mut string = StringLoop::new(max_delay);
+                string.tune(frequency, SYMPATHETIC_DAMPING, self.sample_rate);
+                SympatheticString { frequency, string }
+            })
+            .collect();
+    }
+
+    /// Sets the level of the sympathetic strings in the output (0.0 - 1.0).
+    pub fn set_sympathetic_level(&mut self, level: AudioParam) {
+        self.sympathetic_level = level;
     }
 }
 
 impl FrameProcessor<Mono> for KarplusStrong {
     fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
         let len = buffer.len();
-        let dl_len = self.delay_line.len();
 
         if self.pitch_buffer.len() < len {
             self.pitch_buffer.resize(len, 0.0);
@@ -112,40 +267,34 @@ impl FrameProcessor<Mono> for KarplusStrong {
 
             let mut input = 0.0;
             if self.current_burst_sample < self.noise_burst_samples {
-                input = self.next_random();
+                input = self.excitation.next_sample();
                 self.current_burst_sample += 1;
             }
 
-            let period = self.sample_rate / pitch.max(1.0);
-            let delay_samples = period;
-
-            let mut read_ptr_f = self.write_ptr as f32 - delay_samples + dl_len as f32;
-            while read_ptr_f >= dl_len as f32 {
-                read_ptr_f -= dl_len as f32;
-            }
-            let idx_a = read_ptr_f as usize;
-            let mut idx_b = idx_a + 1;
-            if idx_b >= dl_len {
-                idx_b -= dl_len;
-            }
-            let frac = read_ptr_f - idx_a as f32;
-
-            let delayed = self.delay_line[idx_a] * (1.0 - frac) + self.delay_line[idx_b] * frac;
-
-            let filtered = damping * self.filter_state + (1.0 - damping) * delayed;
-            self.filter_state = filtered;
+            self.string.tune(pitch, damping, self.sample_rate);
+            *sample = self.string.tick(input, damping, FEEDBACK);
+        }
 
-            let feedback = filtered * 0.995;
+        if self.sympathetic.is_empty() {
+            return;
+        }
 
-            let output = input + feedback;
-            self.delay_line[self.write_ptr] = output;
+        if self.sympathetic_buffer.len() < len {
+            self.sympathetic_buffer.resize(len, 0.0);
+        }
+        self.sympathetic_level
+            .process(&mut self.sympathetic_buffer[0..len], sample_index);
 
-            self.write_ptr += 1;
-            if self.write_ptr >= dl_len {
-                self.write_ptr -= dl_len;
+        for (sample, &level) in buffer.iter_mut().zip(self.sympathetic_buffer.iter()) {
+            let drive = *sample * SYMPATHETIC_COUPLING;
+            let mut resonance = 0.0;
+            for sympathetic in &mut self.sympathetic {
+                resonance +=
+                    sympathetic
+                        .string
+                        .tick(drive, SYMPATHETIC_DAMPING, SYMPATHETIC_FEEDBACK);
             }
-
-            *sample = output;
+            *sample += resonance * level;
         }
     }
 
@@ -155,18 +304,39 @@ impl FrameProcessor<Mono> for KarplusStrong {
         self.gate.set_sample_rate(sample_rate);
         self.damping.set_sample_rate(sample_rate);
         self.pick_position.set_sample_rate(sample_rate);
+        self.sympathetic_level.set_sample_rate(sample_rate);
+        self.excitation.set_sample_rate(sample_rate);
 
         let max_delay = (sample_rate / 20.0) as usize;
-        if max_delay > self.delay_line.len() {
-            self.delay_line.resize(max_delay, 0.0);
+        self.string.resize(max_delay);
+        for sympathetic in &mut self.sympathetic {
+            sympathetic.string.resize(max_delay);
+            sympathetic
+                .string
+                .tune(sympathetic.frequency, SYMPATHETIC_DAMPING, sample_rate);
         }
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.pitch.prepare(max_block_size);
+        self.gate.prepare(max_block_size);
+        self.damping.prepare(max_block_size);
+        self.pick_position.prepare(max_block_size);
+        self.sympathetic_level.prepare(max_block_size);
+        self.pitch_buffer.resize(max_block_size, 0.0);
+        self.gate_buffer.resize(max_block_size, 0.0);
+        self.damping_buffer.resize(max_block_size, 0.0);
+        self.pick_buffer.resize(max_block_size, 0.0);
+        self.sympathetic_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
-        self.delay_line.fill(0.0);
-        self.write_ptr = 0;
-        self.filter_state = 0.0;
+        self.string.reset();
+        for sympathetic in &mut self.sympathetic {
+            sympathetic.string.reset();
+        }
         self.current_burst_sample = self.noise_burst_samples;
+        self.excitation.reset();
     }
 
     #[cfg(feature = "debug_visualize")]
@@ -174,3 +344,71 @@ impl FrameProcessor<Mono> for KarplusStrong {
         "KarplusStrong"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Estimates the frequency near `expected` from the phase advance between two windows.
+    fn measure_frequency(signal: &[f32], expected: f32, sample_rate: f32) -> f32 {
+        let omega = 2.0 * PI * expected / sample_rate;
+        let phase = |start: usize| {
+            let (mut re, mut im) = (0.0f32, 0.0f32);
+            for (n, &x) in signal[start..start + 1024].iter().enumerate() {
+                let t = omega * (start + n) as f32;
+                let window = 0.5 - 0.5 * libm::cosf(2.0 * PI * n as f32 / 1024.0);
+                re += x * window * libm::cosf(t);
+                im -= x * window * libm::sinf(t);
+            }
+            libm::atan2f(im, re)
+        };
+        let hop = 400;
+        let mut delta = phase(512 + hop) - phase(512);
+        if delta > PI {
+            delta -= 2.0 * PI;
+        } else if delta < -PI {
+            delta += 2.0 * PI;
+        }
+        expected + delta * sample_rate / (2.0 * PI * hop as f32)
+    }
+
+    #[test]
+    fn test_high_notes_are_in_tune() {
+        for pitch in [440.0, 1760.0, 3000.0] {
+            let mut string = KarplusStrong::new(
+                AudioParam::Static(pitch),
+                AudioParam::Static(1.0),
+                AudioParam::Static(0.2),
+                AudioParam::Static(0.5),
+            );
+            let mut buffer = vec![0.0; 4096];
+            string.process(&mut buffer, 0);
+
+            let measured = measure_frequency(&buffer, pitch, 44100.0);
+            let cents = 1200.0 * libm::log2f(measured / pitch);
+            assert!(cents.abs() < 2.0, "{} Hz is off by {} cents", pitch, cents);
+        }
+    }
+
+    #[test]
+    fn test_sympathetic_strings_ring() {
+        let render = |sympathetic: &[f32]| {
+            let mut string = KarplusStrong::new(
+                AudioParam::Static(220.0),
+                AudioParam::Static(1.0),
+                AudioParam::Static(0.5),
+                AudioParam::Static(0.5),
+            );
+            string.set_sympathetic_strings(sympathetic);
+            string.set_sympathetic_level(AudioParam::Static(1.0));
+            let mut buffer = vec![0.0; 44100];
+            string.process(&mut buffer, 0);
+            buffer[22050..].iter().map(|s| s * s).sum::<f32>()
+        };
+
+        let dry = render(&[]);
+        let wet = render(&[220.0, 330.0, 440.0]);
+        assert!(wet.is_finite());
+        assert!(wet > dry * 1.5);
+    }
+}
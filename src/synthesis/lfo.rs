@@ -1,7 +1,12 @@
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::Mono;
+use crate::core::tempo::NoteValue;
+use crate::core::transport::Transport;
+use crate::synthesis::envelope::Trigger;
 use crate::FrameProcessor;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
 
 /// The waveform shape for the LFO.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -18,12 +23,46 @@ pub enum LfoWaveform {
     SampleAndHold,
 }
 
+impl LfoWaveform {
+    /// Returns the value (-1.0 - 1.0) at `phase` (0.0 - 1.0), or `None` for the random
+    /// `SampleAndHold`, which depends on state.
+    pub(crate) fn periodic(self, phase: f32) -> Option<f32> {
+        match self {
+            LfoWaveform::Sine => {
+                let mut t = phase * 2.0 - 1.0;
+                t = 2.0 * libm::fabsf(t) - 1.0;
+                Some(t * (1.5 - 0.5 * t * t))
+            }
+            LfoWaveform::Triangle => {
+                let t = phase * 2.0 - 1.0;
+                Some(2.0 * libm::fabsf(t) - 1.0)
+            }
+            LfoWaveform::Saw => Some(2.0 * phase - 1.0),
+            LfoWaveform::Square => Some(if phase < 0.5 { 1.0 } else { -1.0 }),
+            LfoWaveform::SampleAndHold => None,
+        }
+    }
+}
+
 /// A Low Frequency Oscillator (LFO).
 ///
 /// Generates control signals for modulation.
+///
+/// Free-running by default. A retrigger input (a gate, or a [`Trigger`] from
+/// `create_trigger`) restarts the cycle at each note, the phase offset sets where the
+/// cycle starts, and a fade-in lets the modulation swell in from the centre of the range
+/// after each restart, e.g. for delayed vibrato.
+///
+/// With [`set_sync`](Self::set_sync) the rate follows the tempo of a [`Transport`], and
+/// while it plays the phase is taken from the beat position, so each cycle starts on a bar
+/// line (or on a song-start multiple for notes longer than a bar). The beat position runs
+/// on through tempo changes, so the phase does too.
 pub struct Lfo {
     phase: f32,
     frequency: AudioParam,
+    phase_offset: AudioParam,
+    retrigger: Option<AudioParam>,
+    fade_in: f32,
     waveform: LfoWaveform,
     min: f32,
     max: f32,
@@ -32,6 +71,12 @@ pub struct Lfo {
     rng_state: u32,
     last_sh_value: f32,
     sh_triggered: bool,
+    last_gate: f32,
+    fade: f32,
+    offset_buffer: Vec<f32>,
+    gate_buffer: Vec<f32>,
+    trigger: Arc<AtomicBool>,
+    sync: Option<(Transport, NoteValue)>,
 }
 
 impl Lfo {
@@ -44,6 +89,9 @@ impl Lfo {
         Lfo {
             phase: 0.0,
             frequency,
+            phase_offset: AudioParam::Static(0.0),
+            retrigger: None,
+            fade_in: 0.0,
             waveform,
             min: -1.0,
             max: 1.0,
@@ -52,7 +100,71 @@ impl Lfo {
             rng_state: 12345,
             last_sh_value: 0.0,
             sh_triggered: false,
+            last_gate: 0.0,
+            fade: 1.0,
+            offset_buffer: Vec::with_capacity(128),
+            gate_buffer: Vec::new(),
+            trigger: Arc::new(AtomicBool::new(false)),
+            sync: None,
+        }
+    }
+
+    /// Sets the phase offset in cycles (0.0 - 1.0), e.g. 0.25 to start a sine at its peak.
+    pub fn set_phase_offset(&mut self, offset: AudioParam) {
+        self.phase_offset = offset;
+    }
+
+    /// Sets a gate input whose rising edges (crossing 0.5) restart the cycle.
+    pub fn set_retrigger(&mut self, gate: Option<AudioParam>) {
+        if gate.is_some() && self.gate_buffer.capacity() == 0 {
+            self.gate_buffer.reserve(128);
+        }
+        self.retrigger = gate;
+    }
+
+    /// Sets the time in seconds the modulation takes to fade in after each restart.
+    pub fn set_fade_in(&mut self, seconds: f32) {
+        self.fade_in = seconds.max(0.0);
+    }
+
+    /// Locks the LFO to the tempo and song position of a transport, one cycle per `note`.
+    ///
+    /// Replaces the frequency input. While the transport is stopped the LFO runs freely at
+    /// the synced rate; while it plays, retriggers are ignored.
+    pub fn set_sync(&mut self, transport: &Transport, note: NoteValue) {
+        self.sync = Some((transport.clone(), note));
+    }
+
+    /// Returns to the frequency input.
+    pub fn clear_sync(&mut self) {
+        self.sync = None;
+    }
+
+    /// Returns the phase (0.0 - 1.0) the song position dictates, if the transport plays.
+    fn synced_phase(transport: &Transport, note: NoteValue, sample_index: u64) -> Option<f32> {
+        if !transport.is_playing_at(sample_index) {
+            return None;
+        }
+        let (numerator, denominator) = transport.time_signature();
+        let bar_beats = numerator as f64 * 4.0 / denominator as f64;
+        let note_beats = note.beats() as f64;
+        let mut beats = transport.beats_at(sample_index);
+        if note_beats <= bar_beats {
+            beats -= libm::floor(beats / bar_beats) * bar_beats;
         }
+        let cycles = beats / note_beats;
+        Some((cycles - libm::floor(cycles)) as f32)
+    }
+
+    /// Creates a trigger handle that restarts the cycle from any thread.
+    pub fn create_trigger(&self) -> Trigger {
+        Trigger::from_flag(&self.trigger)
+    }
+
+    fn restart(&mut self) {
+        self.phase = 0.0;
+        self.sh_triggered = false;
+        self.fade = if self.fade_in > 0.0 { 0.0 } else { 1.0 };
     }
 
     /// Sets the output range of the LFO.
@@ -82,16 +194,55 @@ impl FrameProcessor<Mono> for Lfo {
             self.freq_buffer.resize(buffer.len(), 0.0);
         }
 
-        self.frequency.process(&mut self.freq_buffer, sample_index);
+        match &self.sync {
+            Some((transport, note)) => self.freq_buffer.fill(transport.clock().note_hz(*note)),
+            None => self.frequency.process(&mut self.freq_buffer, sample_index),
+        }
+
+        let len = buffer.len();
+        if self.offset_buffer.len() < len {
+            self.offset_buffer.resize(len, 0.0);
+        }
+        self.phase_offset
+            .process(&mut self.offset_buffer[0..len], sample_index);
+        if let Some(gate) = &mut self.retrigger {
+            if self.gate_buffer.len() < len {
+                self.gate_buffer.resize(len, 0.0);
+            }
+            gate.process(&mut self.gate_buffer[0..len], sample_index);
+        }
+        if self.trigger.swap(false, Ordering::Relaxed) {
+            self.restart();
+        }
 
         let range = self.max - self.min;
         let offset = self.min;
+        let fade_step = if self.fade_in > 0.0 {
+            1.0 / (self.fade_in * self.sample_rate)
+        } else {
+            1.0
+        };
 
         for (i, sample) in buffer.iter_mut().enumerate() {
+            if self.retrigger.is_some() {
+                let gate = self.gate_buffer[i];
+                if gate >= 0.5 && self.last_gate < 0.5 {
+                    self.restart();
+                }
+                self.last_gate = gate;
+            }
+
+            if let Some((transport, note)) = &self.sync {
+                if let Some(phase) = Self::synced_phase(transport, *note, sample_index + i as u64) {
+                    self.phase = phase;
+                }
+            }
+
             let freq = self.freq_buffer[i];
             let inc = freq / self.sample_rate;
 
-            let current_phase = self.phase;
+            let mut current_phase = self.phase + self.offset_buffer[i];
+            current_phase -= libm::floorf(current_phase);
             self.phase += inc;
 
             if self.phase >= 1.0 {
@@ -101,25 +252,9 @@ impl FrameProcessor<Mono> for Lfo {
                 self.phase += 1.0;
             }
 
-            let raw = match self.waveform {
-                LfoWaveform::Sine => {
-                    let mut t = current_phase * 2.0 - 1.0;
-                    t = 2.0 * libm::fabsf(t) - 1.0;
-                    t * (1.5 - 0.5 * t * t)
-                }
-                LfoWaveform::Triangle => {
-                    let t = current_phase * 2.0 - 1.0;
-                    2.0 * libm::fabsf(t) - 1.0
-                }
-                LfoWaveform::Saw => 2.0 * current_phase - 1.0,
-                LfoWaveform::Square => {
-                    if current_phase < 0.5 {
-                        1.0
-                    } else {
-                        -1.0
-                    }
-                }
-                LfoWaveform::SampleAndHold => {
+            let raw = match self.waveform.periodic(current_phase) {
+                Some(value) => value,
+                None => {
                     if !self.sh_triggered {
                         self.last_sh_value = self.next_random();
                         self.sh_triggered = true;
@@ -128,7 +263,10 @@ impl FrameProcessor<Mono> for Lfo {
                 }
             };
 
-            let normalized = (raw + 1.0) * 0.5;
+            let faded = raw * self.fade;
+            self.fade = (self.fade + fade_step).min(1.0);
+
+            let normalized = (faded + 1.0) * 0.5;
             *sample = offset + normalized * range;
         }
     }
@@ -136,11 +274,30 @@ impl FrameProcessor<Mono> for Lfo {
     fn set_sample_rate(&mut self, sample_rate: f32) {
         self.sample_rate = sample_rate;
         self.frequency.set_sample_rate(sample_rate);
+        self.phase_offset.set_sample_rate(sample_rate);
+        if let Some(gate) = &mut self.retrigger {
+            gate.set_sample_rate(sample_rate);
+        }
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.frequency.prepare(max_block_size);
+        self.freq_buffer.resize(max_block_size, 0.0);
+        self.phase_offset.prepare(max_block_size);
+        self.offset_buffer.resize(max_block_size, 0.0);
+        if let Some(gate) = &mut self.retrigger {
+            gate.prepare(max_block_size);
+            self.gate_buffer.resize(max_block_size, 0.0);
+        }
     }
 
     fn reset(&mut self) {
-        self.phase = 0.0;
-        self.sh_triggered = false;
+        self.restart();
+        self.last_gate = 0.0;
+        self.phase_offset.reset();
+        if let Some(gate) = &mut self.retrigger {
+            gate.reset();
+        }
     }
 
     #[cfg(feature = "debug_visualize")]
@@ -154,3 +311,106 @@ impl FrameProcessor<Mono> for Lfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parameter::Parameter;
+    use alloc::vec;
+
+    #[test]
+    fn test_phase_offset_and_retrigger() {
+        // 10 Hz at 1 kHz: 100 samples per cycle.
+        let gate = Parameter::new(0.0);
+        let mut lfo = Lfo::new(AudioParam::hz(10.0), LfoWaveform::Saw);
+        lfo.set_sample_rate(1000.0);
+        lfo.set_phase_offset(AudioParam::Static(0.25));
+        lfo.set_retrigger(Some(AudioParam::Linked(gate.clone())));
+
+        let mut buffer = vec![0.0; 130];
+        lfo.process(&mut buffer, 0);
+        assert!((buffer[0] + 0.5).abs() < 1e-5);
+        assert!((buffer[75] - 1.0).abs() < 0.03 && buffer[76] < -0.9);
+
+        gate.set(1.0);
+        lfo.process(&mut buffer[0..10], 130);
+        assert!((buffer[0] + 0.5).abs() < 1e-5);
+
+        let trigger = lfo.create_trigger();
+        let mut buffer = vec![0.0; 10];
+        lfo.process(&mut buffer, 140);
+        assert!((buffer[0] + 0.3).abs() < 1e-5);
+        trigger.fire();
+        lfo.process(&mut buffer, 150);
+        assert!((buffer[0] + 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_fade_in() {
+        let mut lfo = Lfo::new(AudioParam::hz(25.0), LfoWaveform::Square);
+        lfo.set_sample_rate(1000.0);
+        lfo.set_unipolar(true);
+        lfo.set_fade_in(0.1);
+        lfo.reset();
+
+        // Swells in from the centre of the range over 100 samples.
+        let mut buffer = vec![0.0; 200];
+        lfo.process(&mut buffer, 0);
+        assert_eq!(buffer[0], 0.5);
+        assert!((buffer[10] - 0.55).abs() < 1e-5);
+        assert!((buffer[30] - 0.35).abs() < 1e-5);
+        assert_eq!((buffer[125], buffer[145]), (1.0, 0.0));
+    }
+
+    #[test]
+    fn test_sync_locks_to_bars() {
+        // 120 BPM at 1 kHz: a quarter note is 500 samples, a 4/4 bar 2000.
+        let mut transport = Transport::new(1000.0);
+        let mut lfo = Lfo::new(AudioParam::hz(1.0), LfoWaveform::Saw);
+        lfo.set_sample_rate(1000.0);
+        lfo.set_sync(&transport, NoteValue::DottedQuarter);
+
+        // Stopped: free-running at the synced rate (750 samples per cycle).
+        let mut buffer = vec![0.0; 375];
+        lfo.process(&mut buffer, 0);
+        assert!(buffer[374].abs() < 0.01);
+
+        // Playing: the cycle restarts on the bar line even though 2000 / 750 is not whole.
+        transport.seek(1900);
+        transport.start();
+        let mut block = vec![0.0; 200];
+        FrameProcessor::<Mono>::process(&mut transport, &mut block, 375);
+        lfo.process(&mut block, 375);
+        assert!((block[0] - (-1.0 + 2.0 * 1150.0 / 750.0 - 2.0)).abs() < 0.01);
+        assert!((block[100] + 1.0).abs() < 0.01);
+
+        // A tempo change carries on from beat 4.2, 0.2 beats into the second bar.
+        transport.set_bpm(60.0);
+        FrameProcessor::<Mono>::process(&mut transport, &mut block, 575);
+        lfo.process(&mut block, 575);
+        assert!((block[0] - (-1.0 + 2.0 * 0.2 / 1.5)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_sync_is_continuous_through_tempo_ramp() {
+        let mut transport = Transport::new(1000.0);
+        let mut lfo = Lfo::new(AudioParam::hz(1.0), LfoWaveform::Saw);
+        lfo.set_sample_rate(1000.0);
+        lfo.set_sync(&transport, NoteValue::DottedQuarter);
+        transport.start();
+
+        // Slow down from 120 to 60 BPM over 30 blocks; the saw only rises or wraps.
+        let mut output = Vec::new();
+        for block_index in 0..30u64 {
+            transport.set_bpm(120.0 - 2.0 * block_index as f32);
+            let mut block = vec![0.0; 50];
+            FrameProcessor::<Mono>::process(&mut transport, &mut block, block_index * 50);
+            lfo.process(&mut block, block_index * 50);
+            output.extend_from_slice(&block);
+        }
+        for pair in output.windows(2) {
+            let step = pair[1] - pair[0];
+            assert!((0.0..0.005).contains(&step) || step < -1.9, "step {}", step);
+        }
+    }
+}
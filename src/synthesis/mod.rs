@@ -1,8 +1,17 @@
 pub mod brass_model;
 pub mod envelope;
+pub mod euclid;
+pub mod excitation;
+pub mod granular;
 pub mod karplus_strong;
 pub mod lfo;
+pub mod modal;
+pub mod multi_stage_envelope;
+pub mod noise;
 pub mod oscillator;
+pub mod sequencer;
 pub mod speech;
 pub mod stack;
+pub mod tuning;
+pub mod unison;
 pub mod wavetable;
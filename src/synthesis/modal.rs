@@ -0,0 +1,366 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::synthesis::envelope::Trigger;
+use crate::synthesis::excitation::{ExcitationNoise, NoiseColor};
+use crate::FrameProcessor;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// One resonant mode of a [`ModalSynth`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mode {
+    /// Frequency as a multiple of the fundamental.
+    pub ratio: f32,
+    /// Time in seconds for the mode to decay by 60 dB.
+    pub decay: f32,
+    /// Amplitude of the mode.
+    pub gain: f32,
+}
+
+impl Mode {
+    /// Creates a new Mode.
+    ///
+    /// # Arguments
+    /// * `ratio` - Frequency relative to the fundamental.
+    /// * `decay` - 60 dB decay time in seconds.
+    /// * `gain` - Amplitude.
+    pub const fn new(ratio: f32, decay: f32, gain: f32) -> Self {
+        Mode { ratio, decay, gain }
+    }
+}
+
+/// Mode tables of common percussive bodies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModalPreset {
+    /// A church bell: hum, prime, minor-third tierce, quint and nominal partials.
+    Bell,
+    /// A free-free bar (marimba/glockenspiel): strongly inharmonic, fast-decaying overtones.
+    Bar,
+    /// An ideal circular membrane (tom/tabla-like), from the Bessel function zeros.
+    Membrane,
+}
+
+const BELL: [Mode; 9] = [
+    Mode::new(0.5, 6.0, 0.6),
+    Mode::new(1.0, 4.5, 0.8),
+    Mode::new(1.183, 3.5, 0.5),
+    Mode::new(1.506, 3.0, 0.35),
+    Mode::new(2.0, 2.5, 0.45),
+    Mode::new(2.514, 1.8, 0.25),
+    Mode::new(2.662, 1.6, 0.2),
+    Mode::new(3.011, 1.3, 0.2),
+    Mode::new(4.166, 0.9, 0.12),
+];
+
+const BAR: [Mode; 5] = [
+    Mode::new(1.0, 1.2, 1.0),
+    Mode::new(2.756, 0.5, 0.35),
+    Mode::new(5.404, 0.25, 0.15),
+    Mode::new(8.933, 0.12, 0.08),
+    Mode::new(13.344, 0.06, 0.04),
+];
+
+const MEMBRANE: [Mode; 8] = [
+    Mode::new(1.0, 0.6, 1.0),
+    Mode::new(1.594, 0.45, 0.6),
+    Mode::new(2.136, 0.35, 0.45),
+    Mode::new(2.296, 0.3, 0.35),
+    Mode::new(2.653, 0.25, 0.3),
+    Mode::new(2.918, 0.2, 0.25),
+    Mode::new(3.156, 0.18, 0.2),
+    Mode::new(3.501, 0.15, 0.15),
+];
+
+impl ModalPreset {
+    /// Returns the mode table of the preset.
+    pub fn modes(&self) -> &'static [Mode] {
+        match self {
+            ModalPreset::Bell => &BELL,
+            ModalPreset::Bar => &BAR,
+            ModalPreset::Membrane => &MEMBRANE,
+        }
+    }
+}
+
+/// The signal that strikes a [`ModalSynth`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Exciter {
+    /// A raised-cosine mallet pulse. Longer pulses are softer and darker.
+    Impulse { duration: f32 },
+    /// A noise burst, low-pass filtered at `bandwidth` Hz.
+    Noise { duration: f32, bandwidth: f32 },
+}
+
+/// Two-pole resonator: `y = sin(w)·x + 2r·cos(w)·y1 - r²·y2`, ringing with unit amplitude.
+#[derive(Clone, Copy)]
+struct Resonator {
+    b0: f32,
+    a1: f32,
+    a2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+/// A modal synthesizer for struck percussion.
+///
+/// A bank of resonators, one per [`Mode`], is excited by a short mallet pulse or noise
+/// burst whenever the gate rises (or a [`Trigger`] fires). Each mode rings at its own
+/// frequency ratio, decay time and gain, so a mode table describes the body of the
+/// instrument: see [`ModalPreset`] for bells, bars and membranes.
+pub struct ModalSynth {
+    modes: Vec<Mode>,
+    resonators: Vec<Resonator>,
+    frequency: AudioParam,
+    gate: AudioParam,
+    exciter: Exciter,
+    noise: ExcitationNoise,
+    sample_rate: f32,
+
+    last_gate: f32,
+    last_frequency: f32,
+    excite_position: usize,
+    excite_length: usize,
+
+    frequency_buffer: Vec<f32>,
+    gate_buffer: Vec<f32>,
+    retrigger: Arc<AtomicBool>,
+}
+
+impl ModalSynth {
+    /// Creates a new ModalSynth.
+    ///
+    /// # Arguments
+    /// * `frequency` - Fundamental frequency in Hz.
+    /// * `gate` - Trigger signal (0.0 -> 1.0 strikes the body).
+    /// * `modes` - The mode table.
+    pub fn new(frequency: AudioParam, gate: AudioParam, modes: &[Mode]) -> Self {
+        ModalSynth {
+            modes: modes.to_vec(),
+            resonators: alloc::vec![
+                Resonator {
+                    b0: 0.0,
+                    a1: 0.0,
+                    a2: 0.0,
+                    y1: 0.0,
+                    y2: 0.0,
+                };
+                modes.len()
+            ],
+            frequency,
+            gate,
+            exciter: Exciter::Impulse { duration: 0.001 },
+            noise: ExcitationNoise::new(NoiseColor::White, 1.0, 8000.0),
+            sample_rate: 44100.0,
+            last_gate: 0.0,
+            last_frequency: -1.0,
+            excite_position: 0,
+            excite_length: 0,
+            frequency_buffer: Vec::with_capacity(128),
+            gate_buffer: Vec::with_capacity(128),
+            retrigger: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Creates a new ModalSynth with the modes of a preset.
+    ///
+    /// # Arguments
+    /// * `preset` - The body to model.
+    /// * `frequency` - Fundamental frequency in Hz.
+    /// * `gate` - Trigger signal (0.0 -> 1.0 strikes the body).
+    pub fn from_preset(preset: ModalPreset, frequency: AudioParam, gate: AudioParam) -> Self {
+        Self::new(frequency, gate, preset.modes())
+    }
+
+    /// Sets the exciter used on the next strike.
+    pub fn set_exciter(&mut self, exciter: Exciter) {
+        self.exciter = exciter;
+        if let Exciter::Noise { bandwidth, .. } = exciter {
+            self.noise.set_bandwidth(bandwidth);
+        }
+    }
+
+    /// Creates a trigger handle that strikes the body from any thread.
+    pub fn create_trigger(&self) -> Trigger {
+        Trigger::from_flag(&self.retrigger)
+    }
+
+    fn update_resonators(&mut self, frequency: f32) {
+        let nyquist = self.sample_rate * 0.49;
+        for (resonator, mode) in self.resonators.iter_mut().zip(self.modes.iter()) {
+            let hz = frequency * mode.ratio;
+            if hz <= 0.0 || hz >= nyquist {
+                // Modes above Nyquist would alias; silence them.
+                resonator.b0 = 0.0;
+                resonator.a1 = 0.0;
+                resonator.a2 = 0.0;
+                continue;
+            }
+            let w = 2.0 * PI * hz / self.sample_rate;
+            // 60 dB = a factor of 1000 over `decay` seconds.
+            let r = libm::expf(-libm::logf(1000.0) / (mode.decay.max(0.001) * self.sample_rate));
+            resonator.b0 = libm::sinf(w) * mode.gain;
+            resonator.a1 = 2.0 * r * libm::cosf(w);
+            resonator.a2 = -r * r;
+        }
+        self.last_frequency = frequency;
+    }
+
+    fn strike(&mut self) {
+        let duration = match self.exciter {
+            Exciter::Impulse { duration } => duration,
+            Exciter::Noise { duration, .. } => duration,
+        };
+        self.excite_length = ((duration * self.sample_rate) as usize).max(1);
+        self.excite_position = 0;
+    }
+
+    #[inline(always)]
+    fn next_excitation(&mut self) -> f32 {
+        if self.excite_position >= self.excite_length {
+            return 0.0;
+        }
+        let t = self.excite_position as f32 / self.excite_length as f32;
+        self.excite_position += 1;
+        match self.exciter {
+            // Unit area, so short and long mallets strike equally hard.
+            Exciter::Impulse { .. } => (1.0 - libm::cosf(2.0 * PI * t)) / self.excite_length as f32,
+            Exciter::Noise { .. } => self.noise.next_sample() * 4.0 / self.excite_length as f32,
+        }
+    }
+}
+
+impl FrameProcessor<Mono> for ModalSynth {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        if self.frequency_buffer.len() < len {
+            self.frequency_buffer.resize(len, 0.0);
+        }
+        if self.gate_buffer.len() < len {
+            self.gate_buffer.resize(len, 0.0);
+        }
+        self.frequency
+            .process(&mut self.frequency_buffer[0..len], sample_index);
+        self.gate
+            .process(&mut self.gate_buffer[0..len], sample_index);
+
+        if self.retrigger.swap(false, Ordering::Relaxed) {
+            self.strike();
+        }
+
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let frequency = self.frequency_buffer[i];
+            if (frequency - self.last_frequency).abs() > 0.01 {
+                self.update_resonators(frequency);
+            }
+
+            let gate = self.gate_buffer[i];
+            if gate >= 0.5 && self.last_gate < 0.5 {
+                self.strike();
+            }
+            self.last_gate = gate;
+
+            let input = self.next_excitation();
+            let mut output = 0.0;
+            for r in &mut self.resonators {
+                let y = r.b0 * input + r.a1 * r.y1 + r.a2 * r.y2;
+                r.y2 = r.y1;
+                r.y1 = y;
+                output += y;
+            }
+            *sample = output;
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.frequency.set_sample_rate(sample_rate);
+        self.gate.set_sample_rate(sample_rate);
+        self.noise.set_sample_rate(sample_rate);
+        self.last_frequency = -1.0;
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.frequency.prepare(max_block_size);
+        self.gate.prepare(max_block_size);
+        self.frequency_buffer.resize(max_block_size, 0.0);
+        self.gate_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        for r in &mut self.resonators {
+            r.y1 = 0.0;
+            r.y2 = 0.0;
+        }
+        self.excite_position = self.excite_length;
+        self.noise.reset();
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "ModalSynth"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn magnitude(signal: &[f32], hz: f32, sample_rate: f32) -> f32 {
+        let (mut re, mut im) = (0.0f32, 0.0f32);
+        for (n, &x) in signal.iter().enumerate() {
+            let phase = 2.0 * PI * hz * n as f32 / sample_rate;
+            re += x * libm::cosf(phase);
+            im += x * libm::sinf(phase);
+        }
+        libm::sqrtf(re * re + im * im)
+    }
+
+    #[test]
+    fn test_bar_rings_at_its_modes() {
+        let mut bar = ModalSynth::from_preset(
+            ModalPreset::Bar,
+            AudioParam::Static(440.0),
+            AudioParam::Static(0.0),
+        );
+        let trigger = bar.create_trigger();
+
+        let mut silent = vec![0.0; 256];
+        bar.process(&mut silent, 0);
+        assert!(silent.iter().all(|&s| s == 0.0));
+
+        trigger.fire();
+        let mut buffer = vec![0.0; 8192];
+        for block in buffer.chunks_mut(256) {
+            bar.process(block, 0);
+        }
+
+        let fundamental = magnitude(&buffer, 440.0, 44100.0);
+        let overtone = magnitude(&buffer, 440.0 * 2.756, 44100.0);
+        let between = magnitude(&buffer, 700.0, 44100.0);
+        assert!(fundamental > 10.0 * between);
+        assert!(overtone > 10.0 * between);
+    }
+
+    #[test]
+    fn test_gate_strikes_with_noise() {
+        let mut drum = ModalSynth::from_preset(
+            ModalPreset::Membrane,
+            AudioParam::Static(120.0),
+            AudioParam::Static(1.0),
+        );
+        drum.set_exciter(Exciter::Noise {
+            duration: 0.005,
+            bandwidth: 4000.0,
+        });
+        let mut buffer = vec![0.0; 44100];
+        drum.process(&mut buffer, 0);
+
+        let energy =
+            |range: core::ops::Range<usize>| buffer[range].iter().map(|s| s * s).sum::<f32>();
+        assert!(energy(0..4410) > 0.0);
+        assert!(energy(0..4410) > 100.0 * energy(39690..44100));
+    }
+}
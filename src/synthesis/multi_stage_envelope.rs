@@ -0,0 +1,362 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::synthesis::envelope::{EnvelopeCurve, Trigger};
+use crate::FrameProcessor;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// One breakpoint of a [`MultiStageEnvelope`]: the level to reach, and how.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnvelopeStage {
+    /// Level at the end of the stage.
+    pub level: f32,
+    /// Time to reach it, in seconds.
+    pub time: f32,
+    /// Shape of the segment.
+    pub curve: EnvelopeCurve,
+}
+
+impl EnvelopeStage {
+    /// Creates a new EnvelopeStage.
+    ///
+    /// # Arguments
+    /// * `level` - Level at the end of the stage.
+    /// * `time` - Time to reach it, in seconds.
+    /// * `curve` - Shape of the segment.
+    pub fn new(level: f32, time: f32, curve: EnvelopeCurve) -> Self {
+        EnvelopeStage { level, time, curve }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Idle,
+    Stage(usize),
+    Sustain(usize),
+}
+
+/// An envelope of any number of breakpoint stages, with an optional sustain and loop.
+///
+/// A rising gate starts the first stage from the current level. Without a sustain stage
+/// the envelope runs to the end as a one-shot. With one, the envelope holds that stage's
+/// level while the gate is high, and a falling gate jumps to the stage after it (the
+/// release). With a loop as well, the stages from the loop start to the sustain stage
+/// repeat while the gate is held, for pads that keep evolving.
+///
+/// The curvature parameter bends the curved stages (see [`EnvelopeCurve::shape`]).
+pub struct MultiStageEnvelope {
+    gate: AudioParam,
+    curvature: AudioParam,
+    stages: Vec<EnvelopeStage>,
+    sustain: Option<usize>,
+    loop_start: Option<usize>,
+    sample_rate: f32,
+
+    phase: Phase,
+    level: f32,
+    last_gate: f32,
+    segment_start: f32,
+    segment_pos: f32,
+
+    gate_buffer: Vec<f32>,
+    curvature_buffer: Vec<f32>,
+    retrigger: Arc<AtomicBool>,
+}
+
+impl MultiStageEnvelope {
+    /// Creates a new one-shot MultiStageEnvelope.
+    ///
+    /// # Arguments
+    /// * `gate` - Gate signal (0.0 = off, 1.0 = on).
+    /// * `stages` - The breakpoints, in order. The envelope starts from 0.0.
+    pub fn new(gate: AudioParam, stages: Vec<EnvelopeStage>) -> Self {
+        assert!(
+            !stages.is_empty(),
+            "MultiStageEnvelope: At least one stage is required."
+        );
+        MultiStageEnvelope {
+            gate,
+            curvature: AudioParam::Static(3.0),
+            stages,
+            sustain: None,
+            loop_start: None,
+            sample_rate: 44100.0,
+            phase: Phase::Idle,
+            level: 0.0,
+            last_gate: 0.0,
+            segment_start: 0.0,
+            segment_pos: 0.0,
+            gate_buffer: Vec::with_capacity(128),
+            curvature_buffer: Vec::with_capacity(128),
+            retrigger: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Creates a DAHDSR (Delay, Attack, Hold, Decay, Sustain, Release) envelope.
+    ///
+    /// The attack is linear; the decay and release are exponential. Times are in seconds.
+    ///
+    /// # Arguments
+    /// * `gate` - Gate signal (0.0 = off, 1.0 = on).
+    /// * `delay` - Time before the attack starts.
+    /// * `attack` - Attack time.
+    /// * `hold` - Time at full level before the decay.
+    /// * `decay` - Decay time.
+    /// * `sustain` - Sustain level (0.0 - 1.0).
+    /// * `release` - Release time.
+    pub fn dahdsr(
+        gate: AudioParam,
+        delay: f32,
+        attack: f32,
+        hold: f32,
+        decay: f32,
+        sustain: f32,
+        release: f32,
+    ) -> Self {
+        let stages = vec![
+            EnvelopeStage::new(0.0, delay, EnvelopeCurve::Linear),
+            EnvelopeStage::new(1.0, attack, EnvelopeCurve::Linear),
+            EnvelopeStage::new(1.0, hold, EnvelopeCurve::Linear),
+            EnvelopeStage::new(sustain, decay, EnvelopeCurve::Exponential),
+            EnvelopeStage::new(0.0, release, EnvelopeCurve::Exponential),
+        ];
+        Self::new(gate, stages).with_sustain(3)
+    }
+
+    /// Holds the level of stage `stage` while the gate is high, and returns the envelope.
+    ///
+    /// The stages after it form the release, so it can't be the last stage.
+    pub fn with_sustain(mut self, stage: usize) -> Self {
+        assert!(
+            stage + 1 < self.stages.len(),
+            "MultiStageEnvelope: The sustain stage must be followed by a release stage."
+        );
+        self.sustain = Some(stage);
+        self
+    }
+
+    /// Loops from stage `stage` to the sustain stage while the gate is high, and returns the
+    /// envelope. Requires a sustain stage.
+    pub fn with_loop(mut self, stage: usize) -> Self {
+        let sustain = self
+            .sustain
+            .expect("MultiStageEnvelope: A loop requires a sustain stage.");
+        assert!(
+            stage <= sustain,
+            "MultiStageEnvelope: The loop must start at or before the sustain stage."
+        );
+        self.loop_start = Some(stage);
+        self
+    }
+
+    /// Sets the curvature parameter (default 3.0).
+    pub fn set_curvature(&mut self, curvature: AudioParam) {
+        self.curvature = curvature;
+    }
+
+    /// Replaces the stage at `index`. Takes effect the next time the stage starts.
+    pub fn set_stage(&mut self, index: usize, stage: EnvelopeStage) {
+        self.stages[index] = stage;
+    }
+
+    /// Returns the stages.
+    pub fn stages(&self) -> &[EnvelopeStage] {
+        &self.stages
+    }
+
+    /// Returns true while the envelope is running or sustaining.
+    pub fn is_active(&self) -> bool {
+        self.phase != Phase::Idle
+    }
+
+    /// Creates a trigger handle for this envelope.
+    /// Use this to restart the envelope from 0.0 from any thread.
+    pub fn create_trigger(&self) -> Trigger {
+        Trigger::from_flag(&self.retrigger)
+    }
+
+    fn enter(&mut self, stage: usize) {
+        self.phase = Phase::Stage(stage);
+        self.segment_start = self.level;
+        self.segment_pos = 0.0;
+    }
+
+    /// Where to go when stage `stage` has ended.
+    fn after(&self, stage: usize, held: bool) -> Phase {
+        if held && self.sustain == Some(stage) {
+            match self.loop_start {
+                Some(start) => Phase::Stage(start),
+                None => Phase::Sustain(stage),
+            }
+        } else if stage + 1 < self.stages.len() {
+            Phase::Stage(stage + 1)
+        } else {
+            Phase::Idle
+        }
+    }
+}
+
+impl FrameProcessor<Mono> for MultiStageEnvelope {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        if self.gate_buffer.len() < len {
+            self.gate_buffer.resize(len, 0.0);
+        }
+        if self.curvature_buffer.len() < len {
+            self.curvature_buffer.resize(len, 0.0);
+        }
+        self.gate
+            .process(&mut self.gate_buffer[0..len], sample_index);
+        self.curvature
+            .process(&mut self.curvature_buffer[0..len], sample_index);
+
+        let mut triggered = self.retrigger.swap(false, Ordering::Relaxed);
+
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let gate = self.gate_buffer[i];
+            let held = gate >= 0.5;
+            if triggered {
+                self.level = 0.0;
+                self.enter(0);
+                triggered = false;
+            } else if held && self.last_gate < 0.5 {
+                self.enter(0);
+            } else if !held && self.last_gate >= 0.5 {
+                if let Some(sustain) = self.sustain {
+                    self.enter(sustain + 1);
+                }
+            }
+            self.last_gate = gate;
+
+            match self.phase {
+                Phase::Idle => {}
+                Phase::Sustain(stage) => self.level = self.stages[stage].level,
+                Phase::Stage(index) => {
+                    let stage = self.stages[index];
+                    self.segment_pos += 1.0;
+                    let samples = stage.time * self.sample_rate;
+                    let t = if samples > 0.0 {
+                        self.segment_pos / samples
+                    } else {
+                        1.0
+                    };
+                    self.level = stage.curve.shape(
+                        self.segment_start,
+                        stage.level,
+                        t,
+                        self.curvature_buffer[i],
+                    );
+                    if t >= 1.0 {
+                        self.level = stage.level;
+                        match self.after(index, held) {
+                            Phase::Stage(next) => self.enter(next),
+                            next => self.phase = next,
+                        }
+                    }
+                }
+            }
+            *sample = self.level;
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        self.gate.set_sample_rate(sample_rate);
+        self.curvature.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.gate.prepare(max_block_size);
+        self.curvature.prepare(max_block_size);
+        self.gate_buffer.resize(max_block_size, 0.0);
+        self.curvature_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.phase = Phase::Idle;
+        self.level = 0.0;
+        self.last_gate = 0.0;
+        self.gate.reset();
+        self.curvature.reset();
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "MultiStageEnvelope"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parameter::Parameter;
+
+    fn render(envelope: &mut MultiStageEnvelope, frames: usize) -> Vec<f32> {
+        let mut buffer = vec![0.0; frames];
+        for block in buffer.chunks_mut(64) {
+            envelope.process(block, 0);
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_dahdsr() {
+        let gate = Parameter::new(1.0);
+        let mut env = MultiStageEnvelope::dahdsr(
+            AudioParam::Linked(gate.clone()),
+            0.01,
+            0.02,
+            0.01,
+            0.05,
+            0.5,
+            0.1,
+        );
+        env.set_sample_rate(1000.0);
+
+        // 10 samples delay, 20 attack, 10 hold, 50 decay, then sustain.
+        let held = render(&mut env, 200);
+        assert_eq!(held[8], 0.0);
+        assert!((held[19] - 0.5).abs() < 1e-5);
+        assert_eq!((held[29], held[38]), (1.0, 1.0));
+        // The exponential decay falls fast at first.
+        assert!(held[50] < 0.75);
+        assert!(held.iter().skip(89).all(|&l| l == 0.5));
+
+        gate.set(0.0);
+        let released = render(&mut env, 200);
+        assert!(released[0] < 0.5 && released[50] < 0.25);
+        assert_eq!(released[100], 0.0);
+        assert!(!env.is_active());
+    }
+
+    #[test]
+    fn test_loop_while_held() {
+        let gate = Parameter::new(1.0);
+        let stages = vec![
+            EnvelopeStage::new(1.0, 0.01, EnvelopeCurve::Linear),
+            EnvelopeStage::new(0.2, 0.02, EnvelopeCurve::Logarithmic),
+            EnvelopeStage::new(0.8, 0.02, EnvelopeCurve::Exponential),
+            EnvelopeStage::new(0.0, 0.01, EnvelopeCurve::Linear),
+        ];
+        let mut env = MultiStageEnvelope::new(AudioParam::Linked(gate.clone()), stages)
+            .with_sustain(2)
+            .with_loop(1);
+        env.set_sample_rate(1000.0);
+
+        // Stages 1 and 2 cycle every 40 samples after the 10 sample attack.
+        let held = render(&mut env, 250);
+        for cycle in 0..5 {
+            let start = 10 + cycle * 40;
+            assert!((held[start + 19] - 0.2).abs() < 1e-5);
+            assert!((held[start + 39] - 0.8).abs() < 1e-5);
+        }
+        // A concave fall stays high at first, a convex rise stays low.
+        assert!(held[20] > 0.6 && held[40] < 0.5);
+
+        gate.set(0.0);
+        let released = render(&mut env, 20);
+        assert_eq!(released[10], 0.0);
+    }
+}
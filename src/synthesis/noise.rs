@@ -0,0 +1,130 @@
+use crate::core::audio_param::AudioParam;
+use crate::core::channels::Mono;
+use crate::core::error::ValidationReport;
+use crate::core::utils::FastRng;
+use crate::synthesis::excitation::ColorFilter;
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+
+/// A noise source with a continuously variable color.
+///
+/// `color` sets the spectral tilt in steps of 3 dB/octave: -1.0 is brown, -0.5 pink,
+/// 0.0 white, 0.5 blue and 1.0 violet. Values in between crossfade the two neighbouring
+/// colors, which are shaped from the same white noise, so the color can be swept or
+/// modulated without a change in level. `reset` restarts the sequence from the seed.
+pub struct NoiseGenerator {
+    color: AudioParam,
+    seed: u32,
+    rng_state: u32,
+    filter: ColorFilter,
+    color_buffer: Vec<f32>,
+}
+
+impl NoiseGenerator {
+    /// Creates a new NoiseGenerator.
+    ///
+    /// # Arguments
+    /// * `color` - Spectral color (-1.0 = brown, 0.0 = white, 1.0 = violet).
+    pub fn new(color: AudioParam) -> Self {
+        NoiseGenerator {
+            color,
+            seed: 12345,
+            rng_state: 12345,
+            filter: ColorFilter::default(),
+            color_buffer: Vec::with_capacity(128),
+        }
+    }
+
+    /// Sets the seed and returns the generator. The sequence restarts from the new seed.
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self.rng_state = seed;
+        self.filter.reset();
+        self
+    }
+
+    /// Sets the color parameter.
+    pub fn set_color(&mut self, color: AudioParam) {
+        self.color = color;
+    }
+}
+
+impl FrameProcessor<Mono> for NoiseGenerator {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        if self.color_buffer.len() < len {
+            self.color_buffer.resize(len, 0.0);
+        }
+        self.color
+            .process(&mut self.color_buffer[0..len], sample_index);
+
+        for (sample, &color) in buffer.iter_mut().zip(self.color_buffer.iter()) {
+            let white = FastRng::next_f32_bipolar_stateless(&mut self.rng_state);
+            let colors = self.filter.spectrum(white);
+
+            let position = (color.clamp(-1.0, 1.0) + 1.0) * 2.0;
+            let index = (position as usize).min(3);
+            let blend = position - index as f32;
+            *sample = colors[index] + (colors[index + 1] - colors[index]) * blend;
+        }
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.color.set_sample_rate(sample_rate);
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.color.prepare(max_block_size);
+        self.color_buffer.resize(max_block_size, 0.0);
+    }
+
+    fn reset(&mut self) {
+        self.rng_state = self.seed;
+        self.filter.reset();
+        self.color.reset();
+    }
+
+    fn validate(&self, report: &mut ValidationReport) {
+        report.check_param("NoiseGenerator", "color", &self.color, -1.0, 1.0);
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "NoiseGenerator"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synthesis::oscillator::{Oscillator, Waveform};
+    use alloc::vec;
+
+    fn brightness(buffer: &[f32]) -> f32 {
+        let power: f32 = buffer.iter().map(|s| s * s).sum();
+        let hf: f32 = buffer
+            .windows(2)
+            .map(|w| (w[1] - w[0]) * (w[1] - w[0]))
+            .sum();
+        hf / power
+    }
+
+    #[test]
+    fn test_color_sweep() {
+        let render = |color: f32| {
+            let mut noise = NoiseGenerator::new(AudioParam::Static(color));
+            let mut buffer = vec![0.0; 32768];
+            noise.process(&mut buffer, 0);
+            buffer
+        };
+        let colors = [-1.0, -0.75, -0.5, 0.0, 0.25, 0.5, 1.0];
+        let levels: Vec<f32> = colors.iter().map(|&c| brightness(&render(c))).collect();
+        assert!(levels.windows(2).all(|w| w[0] < w[1]));
+
+        // The named colors match the oscillator's noise waveforms.
+        let mut pink = Oscillator::new(AudioParam::Static(0.0), Waveform::PinkNoise);
+        let mut buffer = vec![0.0; 64];
+        pink.process(&mut buffer, 0);
+        assert_eq!(&buffer[..], &render(-0.5)[0..64]);
+    }
+}
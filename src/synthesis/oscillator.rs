@@ -1,5 +1,7 @@
 use crate::core::audio_param::AudioParam;
 use crate::core::channels::Mono;
+use crate::core::preset::ParameterTree;
+use crate::synthesis::excitation::{ColorFilter, NoiseColor};
 use crate::FrameProcessor;
 use alloc::vec::Vec;
 use core::f32::consts::PI;
@@ -32,8 +34,12 @@ fn sine_norm(phase: f32) -> f32 {
     0.225 * (y * abs_y - y) + y
 }
 
+/// Narrowest pulse, so a pulse never vanishes.
+const MIN_WIDTH: f32 = 0.01;
+
 /// The waveform shape for the oscillator.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
 pub enum Waveform {
     /// Sine wave.
     Sine,
@@ -47,11 +53,42 @@ pub enum Waveform {
     Square,
     /// White noise.
     WhiteNoise,
+    /// Pink noise (-3 dB/octave).
+    PinkNoise,
+    /// Brown noise (-6 dB/octave).
+    BrownNoise,
+    /// Blue noise (+3 dB/octave).
+    BlueNoise,
+}
+
+impl Waveform {
+    /// Returns true for the noise waveforms, which ignore the frequency.
+    pub fn is_noise(self) -> bool {
+        self.noise_color().is_some()
+    }
+
+    fn noise_color(self) -> Option<NoiseColor> {
+        match self {
+            Waveform::WhiteNoise => Some(NoiseColor::White),
+            Waveform::PinkNoise => Some(NoiseColor::Pink),
+            Waveform::BrownNoise => Some(NoiseColor::Brown),
+            Waveform::BlueNoise => Some(NoiseColor::Blue),
+            _ => None,
+        }
+    }
 }
 
 /// A band-limited oscillator.
 ///
 /// Generates standard waveforms using PolyBLEP for anti-aliasing.
+///
+/// Two optional inputs extend it for classic synth patches: a sync input whose rising edges
+/// (crossing 0.5) reset the phase, for hard-sync leads driven by a master oscillator, and a
+/// phase modulation input in cycles that is added to the phase, for PM/FM patches
+/// (`Sine` carrier, modulator output scaled by the index).
+///
+/// The `Square` waveform has a pulse width (duty cycle) input for PWM, with PolyBLEP
+/// correction at both edges.
 pub struct Oscillator {
     pub phase: f32,
     pub frequency: AudioParam,
@@ -63,6 +100,18 @@ pub struct Oscillator {
     inv_sample_rate: f32,
     freq_buffer: Vec<f32>,
     pub rng_state: u32,
+    color_filter: ColorFilter,
+
+    sync: Option<AudioParam>,
+    phase_mod: Option<AudioParam>,
+    pulse_width: AudioParam,
+    // Pulse width used by `tick()`: the static value, or the last one processed.
+    last_width: f32,
+    last_sync: f32,
+    last_phase_mod: f32,
+    sync_buffer: Vec<f32>,
+    phase_mod_buffer: Vec<f32>,
+    width_buffer: Vec<f32>,
 }
 
 impl Oscillator {
@@ -80,6 +129,63 @@ impl Oscillator {
             inv_sample_rate: 1.0 / 44100.0,
             freq_buffer: Vec::with_capacity(128),
             rng_state: 12345,
+            color_filter: ColorFilter::default(),
+            sync: None,
+            phase_mod: None,
+            pulse_width: AudioParam::Static(0.5),
+            last_width: 0.5,
+            last_sync: 0.0,
+            last_phase_mod: 0.0,
+            sync_buffer: Vec::new(),
+            phase_mod_buffer: Vec::new(),
+            width_buffer: Vec::new(),
+        }
+    }
+
+    /// Sets the pulse width of the `Square` waveform (0.01 - 0.99, 0.5 = square).
+    ///
+    /// Modulate it with an LFO for PWM pads. Pulses other than 0.5 carry a DC offset of
+    /// `2 · width - 1`.
+    pub fn set_pulse_width(&mut self, pulse_width: AudioParam) {
+        if let Some(width) = pulse_width.get_constant() {
+            self.last_width = width.clamp(MIN_WIDTH, 1.0 - MIN_WIDTH);
+        }
+        self.pulse_width = pulse_width;
+    }
+
+    /// Sets the pulse width and returns the oscillator.
+    pub fn with_pulse_width(mut self, pulse_width: AudioParam) -> Self {
+        self.set_pulse_width(pulse_width);
+        self
+    }
+
+    /// Sets the hard-sync input and returns the oscillator.
+    pub fn with_sync(mut self, sync: AudioParam) -> Self {
+        self.set_sync(Some(sync));
+        self
+    }
+
+    /// Sets the phase modulation input and returns the oscillator.
+    pub fn with_phase_modulation(mut self, phase_mod: AudioParam) -> Self {
+        self.set_phase_modulation(Some(phase_mod));
+        self
+    }
+
+    /// Sets the hard-sync input: the phase restarts on each rising edge (crossing 0.5).
+    ///
+    /// A master oscillator's square wave or a gate works as the sync signal.
+    pub fn set_sync(&mut self, sync: Option<AudioParam>) {
+        self.sync = sync;
+        if self.sync.is_some() && self.sync_buffer.capacity() == 0 {
+            self.sync_buffer.reserve(128);
+        }
+    }
+
+    /// Sets the phase modulation input in cycles (1.0 = a full period).
+    pub fn set_phase_modulation(&mut self, phase_mod: Option<AudioParam>) {
+        self.phase_mod = phase_mod;
+        if self.phase_mod.is_some() && self.phase_mod_buffer.capacity() == 0 {
+            self.phase_mod_buffer.reserve(128);
         }
     }
 
@@ -125,7 +231,7 @@ impl Oscillator {
     pub fn tick(&mut self, freq_hz: f32) -> f32 {
         let inc = freq_hz * self.inv_sample_rate;
 
-        if self.waveform != Waveform::WhiteNoise {
+        if !self.waveform.is_noise() {
             self.phase += inc;
             if self.phase >= 1.0 {
                 self.phase -= 1.0;
@@ -148,17 +254,118 @@ impl Oscillator {
                 naive - Self::poly_blep(self.phase, inc.abs())
             }
             Waveform::NaiveSaw => 2.0 * self.phase - 1.0,
-            Waveform::Square => {
-                let naive = if self.phase < 0.5 { 1.0 } else { -1.0 };
-                let dt = inc.abs();
-                let mut p2 = self.phase + 0.5;
-                if p2 >= 1.0 {
-                    p2 -= 1.0;
+            Waveform::Square => Self::pulse(self.phase, inc.abs(), self.last_width),
+            Waveform::WhiteNoise => Self::next_random(&mut self.rng_state),
+            Waveform::PinkNoise | Waveform::BrownNoise | Waveform::BlueNoise => self.next_colored(),
+        }
+    }
+
+    #[inline(always)]
+    fn next_colored(&mut self) -> f32 {
+        let white = Self::next_random(&mut self.rng_state);
+        match self.waveform.noise_color() {
+            Some(color) => self.color_filter.process(color, white),
+            None => white,
+        }
+    }
+
+    /// A pulse that is high for `width` of the cycle, with PolyBLEPs at both edges.
+    #[inline(always)]
+    fn pulse(phase: f32, dt: f32, width: f32) -> f32 {
+        let naive = if phase < width { 1.0 } else { -1.0 };
+        let mut p2 = phase + 1.0 - width;
+        if p2 >= 1.0 {
+            p2 -= 1.0;
+        }
+        naive + Self::poly_blep(phase, dt) - Self::poly_blep(p2, dt)
+    }
+
+    #[inline(always)]
+    fn shape(waveform: Waveform, phase: f32, dt: f32, width: f32) -> f32 {
+        match waveform {
+            Waveform::Sine => sine_norm(phase),
+            Waveform::Triangle => {
+                if phase < 0.5 {
+                    4.0 * phase - 1.0
+                } else {
+                    4.0 * (1.0 - phase) - 1.0
                 }
-                let core = Self::poly_blep(self.phase, dt) - Self::poly_blep(p2, dt);
-                naive + core
             }
-            Waveform::WhiteNoise => Self::next_random(&mut self.rng_state),
+            Waveform::Saw => 2.0 * phase - 1.0 - Self::poly_blep(phase, dt),
+            Waveform::NaiveSaw => 2.0 * phase - 1.0,
+            Waveform::Square => Self::pulse(phase, dt, width),
+            Waveform::WhiteNoise
+            | Waveform::PinkNoise
+            | Waveform::BrownNoise
+            | Waveform::BlueNoise => 0.0,
+        }
+    }
+
+    /// The per-sample path used while a sync, phase modulation or pulse width input is in use.
+    fn process_modulated(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let len = buffer.len();
+        let has_width = self.waveform == Waveform::Square;
+        if has_width {
+            if self.width_buffer.len() < len {
+                self.width_buffer.resize(len, 0.0);
+            }
+            self.pulse_width
+                .process(&mut self.width_buffer[0..len], sample_index);
+        }
+        if let Some(sync) = &mut self.sync {
+            if self.sync_buffer.len() < len {
+                self.sync_buffer.resize(len, 0.0);
+            }
+            sync.process(&mut self.sync_buffer[0..len], sample_index);
+        }
+        if let Some(phase_mod) = &mut self.phase_mod {
+            if self.phase_mod_buffer.len() < len {
+                self.phase_mod_buffer.resize(len, 0.0);
+            }
+            phase_mod.process(&mut self.phase_mod_buffer[0..len], sample_index);
+        }
+
+        let has_sync = self.sync.is_some();
+        let has_phase_mod = self.phase_mod.is_some();
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let inc = self.freq_buffer[i] * self.inv_sample_rate;
+            self.phase += inc;
+            if self.phase >= 1.0 {
+                self.phase -= 1.0;
+            } else if self.phase < 0.0 {
+                self.phase += 1.0;
+            }
+
+            if has_sync {
+                let sync = self.sync_buffer[i];
+                if sync >= 0.5 && self.last_sync < 0.5 {
+                    self.phase = 0.0;
+                }
+                self.last_sync = sync;
+            }
+
+            let mut phase = self.phase;
+            let mut dt = inc;
+            if has_phase_mod {
+                let phase_mod = self.phase_mod_buffer[i];
+                phase += phase_mod - libm::floorf(phase_mod);
+                if phase >= 1.0 {
+                    phase -= 1.0;
+                }
+                // The modulation speeds the phase up or down, which widens or narrows the BLEP.
+                dt += phase_mod - self.last_phase_mod;
+                self.last_phase_mod = phase_mod;
+            }
+
+            let width = if has_width {
+                self.width_buffer[i].clamp(MIN_WIDTH, 1.0 - MIN_WIDTH)
+            } else {
+                0.5
+            };
+            *sample = Self::shape(self.waveform, phase, dt.abs().min(0.5), width);
+        }
+        if has_width && len > 0 {
+            self.last_width = self.width_buffer[len - 1].clamp(MIN_WIDTH, 1.0 - MIN_WIDTH);
         }
     }
 }
@@ -171,6 +378,12 @@ impl FrameProcessor<Mono> for Oscillator {
 
         self.frequency.process(&mut self.freq_buffer, sample_index);
 
+        let pwm = self.waveform == Waveform::Square && self.pulse_width.get_constant() != Some(0.5);
+        if (self.sync.is_some() || self.phase_mod.is_some() || pwm) && !self.waveform.is_noise() {
+            self.process_modulated(buffer, sample_index);
+            return;
+        }
+
         let sample_rate = self.sample_rate;
         let mut phase = self.phase;
         let inv_sr = 1.0 / sample_rate;
@@ -281,6 +494,13 @@ impl FrameProcessor<Mono> for Oscillator {
                 }
                 self.rng_state = rng;
             }
+            Waveform::PinkNoise | Waveform::BrownNoise | Waveform::BlueNoise => {
+                for out_chunk in chunks.iter_mut() {
+                    for sample in out_chunk.iter_mut() {
+                        *sample = self.next_colored();
+                    }
+                }
+            }
         }
 
         for (i, sample) in remainder.iter_mut().enumerate() {
@@ -288,7 +508,7 @@ impl FrameProcessor<Mono> for Oscillator {
             let freq = self.freq_buffer[freq_idx];
             let inc = freq * inv_sr;
 
-            if !matches!(self.waveform, Waveform::WhiteNoise) {
+            if !self.waveform.is_noise() {
                 phase += inc;
                 if phase >= 1.0 {
                     phase -= 1.0;
@@ -328,6 +548,9 @@ impl FrameProcessor<Mono> for Oscillator {
                     self.rng_state = rng;
                     v
                 }
+                Waveform::PinkNoise | Waveform::BrownNoise | Waveform::BlueNoise => {
+                    self.next_colored()
+                }
             };
             *sample = val;
         }
@@ -339,10 +562,41 @@ impl FrameProcessor<Mono> for Oscillator {
         self.sample_rate = sample_rate;
         self.inv_sample_rate = 1.0 / sample_rate;
         self.frequency.set_sample_rate(sample_rate);
+        self.pulse_width.set_sample_rate(sample_rate);
+        for input in [&mut self.sync, &mut self.phase_mod].into_iter().flatten() {
+            input.set_sample_rate(sample_rate);
+        }
+    }
+
+    fn prepare(&mut self, max_block_size: usize) {
+        self.frequency.prepare(max_block_size);
+        self.freq_buffer.resize(max_block_size, 0.0);
+        self.pulse_width.prepare(max_block_size);
+        self.width_buffer.resize(max_block_size, 0.0);
+        if let Some(sync) = &mut self.sync {
+            sync.prepare(max_block_size);
+            self.sync_buffer.resize(max_block_size, 0.0);
+        }
+        if let Some(phase_mod) = &mut self.phase_mod {
+            phase_mod.prepare(max_block_size);
+            self.phase_mod_buffer.resize(max_block_size, 0.0);
+        }
     }
 
     fn reset(&mut self) {
         self.phase = 0.0;
+        self.last_sync = 0.0;
+        self.last_phase_mod = 0.0;
+        self.color_filter.reset();
+        self.pulse_width.reset();
+        for input in [&mut self.sync, &mut self.phase_mod].into_iter().flatten() {
+            input.reset();
+        }
+    }
+
+    fn parameters(&self, tree: &mut ParameterTree) {
+        tree.add_audio_param("frequency", &self.frequency, 0.0, 20000.0);
+        tree.add_audio_param("pulse_width", &self.pulse_width, 0.01, 0.99);
     }
 
     #[cfg(feature = "debug_visualize")]
@@ -354,6 +608,9 @@ impl FrameProcessor<Mono> for Oscillator {
             Waveform::NaiveSaw => "Oscillator (NaiveSaw)",
             Waveform::Square => "Oscillator (Square)",
             Waveform::WhiteNoise => "Oscillator (WhiteNoise)",
+            Waveform::PinkNoise => "Oscillator (PinkNoise)",
+            Waveform::BrownNoise => "Oscillator (BrownNoise)",
+            Waveform::BlueNoise => "Oscillator (BlueNoise)",
         }
     }
 }
@@ -380,4 +637,78 @@ mod tests {
         let tol = 5e-3;
         assert!((buffer[0] - libm::sinf(0.01 * 2.0 * PI)).abs() < tol);
     }
+
+    #[test]
+    fn test_hard_sync_resets_phase() {
+        // A 100 Hz master square syncs a 233 Hz slave: every master cycle (441 samples)
+        // restarts the slave, so its output repeats with the master's period.
+        let master = Oscillator::new(AudioParam::hz(100.0), Waveform::Square);
+        let mut slave = Oscillator::new(AudioParam::hz(233.0), Waveform::NaiveSaw)
+            .with_sync(AudioParam::Dynamic(alloc::boxed::Box::new(master)));
+        slave.set_phase(0.9);
+
+        let mut buffer = [0.0; 1323];
+        for block in buffer.chunks_mut(64) {
+            slave.process(block, 0);
+        }
+        // The master starts high, so the first sample is already a reset.
+        assert_eq!(buffer[0], -1.0);
+        for k in 0..882 {
+            assert!((buffer[k + 441] - buffer[k]).abs() < 1e-3);
+        }
+        // The slave's own period (~189 samples) is cut short at each reset.
+        assert!(buffer[440] > buffer[441] + 0.5);
+    }
+
+    #[test]
+    fn test_pulse_width() {
+        // 441 Hz at 44.1 kHz: 100 samples per cycle.
+        let mut osc = Oscillator::new(AudioParam::hz(441.0), Waveform::Square)
+            .with_pulse_width(AudioParam::Static(0.25));
+        let mut buffer = [0.0; 1000];
+        osc.process(&mut buffer, 0);
+
+        let high = buffer[100..900].iter().filter(|&&s| s > 0.0).count();
+        assert!((198..=202).contains(&high));
+        let mean = buffer[100..900].iter().sum::<f32>() / 800.0;
+        assert!((mean + 0.5).abs() < 0.01);
+        // Both edges are smoothed.
+        assert!(buffer.iter().any(|&s| s.abs() < 0.9));
+
+        let mut ticked = Oscillator::new(AudioParam::hz(441.0), Waveform::Square)
+            .with_pulse_width(AudioParam::Static(0.25));
+        for &s in &buffer[0..100] {
+            assert!((ticked.tick(441.0) - s).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_phase_modulation() {
+        let offset = Parameter::new(0.25);
+        let mut plain = Oscillator::new(AudioParam::hz(441.0), Waveform::Sine);
+        let mut shifted = Oscillator::new(AudioParam::hz(441.0), Waveform::Sine)
+            .with_phase_modulation(AudioParam::Linked(offset.clone()));
+
+        let mut a = [0.0; 64];
+        let mut b = [0.0; 64];
+        plain.process(&mut a, 0);
+        shifted.process(&mut b, 0);
+
+        // A quarter cycle of phase offset turns the sine into a cosine.
+        #[cfg(not(feature = "perf-approximations"))]
+        let tol = 1e-4;
+        #[cfg(feature = "perf-approximations")]
+        let tol = 1e-2;
+        for (n, &s) in b.iter().enumerate() {
+            let phase = (n + 1) as f32 * 0.01;
+            assert!((s - libm::cosf(phase * 2.0 * PI)).abs() < tol);
+        }
+
+        // Removing the input leaves the plain path untouched.
+        shifted.set_phase_modulation(None);
+        shifted.set_phase(plain.get_phase());
+        plain.process(&mut a, 64);
+        shifted.process(&mut b, 64);
+        assert_eq!(a, b);
+    }
 }
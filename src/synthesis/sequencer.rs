@@ -0,0 +1,333 @@
+use crate::core::channels::Mono;
+use crate::core::tempo::NoteValue;
+use crate::core::transport::Transport;
+use crate::core::utils::FastRng;
+use crate::synthesis::tuning::TuningTable;
+use crate::FrameProcessor;
+use alloc::vec::Vec;
+
+/// One step of a [`StepSequencer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    /// Pitch in Hz.
+    pub frequency: f32,
+    /// Velocity (0.0 - 1.0).
+    pub velocity: f32,
+    /// Share of the step the gate stays high (0.0 = rest, 1.0 = tied to the next step).
+    pub gate: f32,
+    /// Chance that the step plays (0.0 - 1.0).
+    pub probability: f32,
+}
+
+impl Step {
+    /// Creates a new Step that always plays, with the gate open for half the step.
+    ///
+    /// # Arguments
+    /// * `frequency` - Pitch in Hz.
+    /// * `velocity` - Velocity (0.0 - 1.0).
+    pub fn new(frequency: f32, velocity: f32) -> Self {
+        Step {
+            frequency,
+            velocity,
+            gate: 0.5,
+            probability: 1.0,
+        }
+    }
+
+    /// Creates a new Step from a MIDI note number (69 = A4 = 440 Hz).
+    pub fn midi(note: u8, velocity: f32) -> Self {
+        Self::new(440.0 * libm::exp2f((note as f32 - 69.0) / 12.0), velocity)
+    }
+
+    /// Creates a new Step from a MIDI note number in a tuning.
+    ///
+    /// # Arguments
+    /// * `note` - MIDI key.
+    /// * `velocity` - Velocity (0.0 - 1.0).
+    /// * `tuning` - Maps the key to its frequency.
+    pub fn tuned(note: u8, velocity: f32, tuning: &TuningTable) -> Self {
+        Self::new(tuning.frequency(note), velocity)
+    }
+
+    /// Creates a silent step.
+    pub fn rest() -> Self {
+        Step {
+            frequency: 0.0,
+            velocity: 0.0,
+            gate: 0.0,
+            probability: 0.0,
+        }
+    }
+
+    /// Sets the gate length (0.0 - 1.0 of the step) and returns the step.
+    pub fn with_gate(mut self, gate: f32) -> Self {
+        self.gate = gate;
+        self
+    }
+
+    /// Sets the probability (0.0 - 1.0) and returns the step.
+    pub fn with_probability(mut self, probability: f32) -> Self {
+        self.probability = probability;
+        self
+    }
+}
+
+/// The control signal a [`StepSequencer`] outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequencerLane {
+    /// Pitch in Hz, held until the next step that plays.
+    Pitch,
+    /// 1.0 while a step's gate is open, else 0.0.
+    Gate,
+    /// Velocity of the last step that played.
+    Velocity,
+}
+
+/// A tempo-synced step sequencer as a control source.
+///
+/// Plays a looping list of [`Step`]s, one per `rate` of the song position of a
+/// [`Transport`], and outputs one lane (pitch, gate or velocity) as a signal for an
+/// `AudioParam`. The steps count from song position 0, so they follow seeks and loops and
+/// keep their place through tempo changes; while the transport is stopped the gate is
+/// closed. The probability rolls are seeded by the step number, so the lanes of one
+/// sequence are separate clones (see [`lane`](Self::lane)) that stay in lockstep without
+/// sharing state.
+///
+/// Swing delays every second step: 0.0 is straight, about 0.67 is a triplet shuffle and
+/// 1.0 moves the off-beat steps half a step late.
+#[derive(Clone)]
+pub struct StepSequencer {
+    transport: Transport,
+    rate: NoteValue,
+    steps: Vec<Step>,
+    swing: f32,
+    seed: u32,
+    lane: SequencerLane,
+
+    last_step: Option<u64>,
+    playing: bool,
+    frequency: f32,
+    velocity: f32,
+}
+
+impl StepSequencer {
+    /// Creates a new StepSequencer outputting the pitch lane.
+    ///
+    /// # Arguments
+    /// * `transport` - The song timeline.
+    /// * `rate` - Length of one step, e.g. `NoteValue::Sixteenth`.
+    /// * `steps` - The sequence.
+    pub fn new(transport: &Transport, rate: NoteValue, steps: Vec<Step>) -> Self {
+        assert!(
+            !steps.is_empty(),
+            "StepSequencer: At least one step is required."
+        );
+        StepSequencer {
+            transport: transport.clone(),
+            rate,
+            steps,
+            swing: 0.0,
+            seed: 0x5EED_1234,
+            lane: SequencerLane::Pitch,
+            last_step: None,
+            playing: false,
+            frequency: 0.0,
+            velocity: 0.0,
+        }
+    }
+
+    /// Returns a clone of the sequencer that outputs `lane`.
+    pub fn lane(&self, lane: SequencerLane) -> Self {
+        let mut clone = self.clone();
+        clone.lane = lane;
+        clone
+    }
+
+    /// Sets the step length.
+    pub fn set_rate(&mut self, rate: NoteValue) {
+        self.rate = rate;
+    }
+
+    /// Sets the swing (0.0 - 1.0).
+    pub fn set_swing(&mut self, swing: f32) {
+        self.swing = swing.clamp(0.0, 1.0);
+    }
+
+    /// Sets the seed of the probability rolls. Lanes must share it to stay in lockstep.
+    pub fn set_seed(&mut self, seed: u32) {
+        self.seed = seed;
+    }
+
+    /// Replaces the step at `index`.
+    pub fn set_step(&mut self, index: usize, step: Step) {
+        self.steps[index] = step;
+    }
+
+    /// Returns the steps.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Returns true if step number `step` (counted from the start) passes its probability roll.
+    fn plays(&self, step: u64, probability: f32) -> bool {
+        if probability >= 1.0 {
+            return true;
+        }
+        let mut state = self.seed ^ (step as u32).wrapping_mul(0x9E37_79B9);
+        FastRng::next_u32_stateless(&mut state);
+        FastRng::next_f32_unipolar_stateless(&mut state) < probability
+    }
+}
+
+impl FrameProcessor<Mono> for StepSequencer {
+    fn process(&mut self, buffer: &mut [f32], sample_index: u64) {
+        let step_beats = self.rate.beats() as f64;
+        let offset = self.swing as f64 * 0.5;
+
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            let index = sample_index + i as u64;
+            let position = self.transport.beats_at(index) / step_beats;
+            let running = self.transport.is_playing_at(index);
+            let pair = libm::floor(position * 0.5);
+            let within = position - pair * 2.0;
+            let (step, phase) = if within < 1.0 + offset {
+                (pair as u64 * 2, within / (1.0 + offset))
+            } else {
+                (
+                    pair as u64 * 2 + 1,
+                    (within - 1.0 - offset) / (1.0 - offset),
+                )
+            };
+
+            let current = self.steps[(step % self.steps.len() as u64) as usize];
+            if self.last_step != Some(step) {
+                self.last_step = Some(step);
+                self.playing = current.gate > 0.0 && self.plays(step, current.probability);
+                if self.playing {
+                    self.frequency = current.frequency;
+                    self.velocity = current.velocity;
+                }
+            }
+
+            *sample = match self.lane {
+                SequencerLane::Pitch => self.frequency,
+                SequencerLane::Gate => {
+                    if running && self.playing && (phase as f32) < current.gate {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                }
+                SequencerLane::Velocity => self.velocity,
+            };
+        }
+    }
+
+    fn reset(&mut self) {
+        self.last_step = None;
+        self.playing = false;
+    }
+
+    #[cfg(feature = "debug_visualize")]
+    fn name(&self) -> &str {
+        "StepSequencer"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    /// A playing 120 BPM transport at 1 kHz.
+    fn transport() -> Transport {
+        let transport = Transport::new(1000.0);
+        transport.start();
+        transport
+    }
+
+    fn render(sequencer: &mut StepSequencer, frames: usize) -> Vec<f32> {
+        render_lanes(&mut [sequencer], 0, frames).remove(0)
+    }
+
+    /// Runs a clone of the shared transport, then every lane, block by block from `start`.
+    fn render_lanes(lanes: &mut [&mut StepSequencer], start: u64, frames: usize) -> Vec<Vec<f32>> {
+        let mut timeline = lanes[0].transport.clone();
+        let mut out = vec![vec![0.0; frames]; lanes.len()];
+        for offset in (0..frames).step_by(64) {
+            let end = (offset + 64).min(frames);
+            let sample_index = start + offset as u64;
+            let mut block = vec![0.0; end - offset];
+            FrameProcessor::<Mono>::process(&mut timeline, &mut block, sample_index);
+            for (lane, buffer) in lanes.iter_mut().zip(out.iter_mut()) {
+                lane.process(&mut buffer[offset..end], sample_index);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_lanes_and_swing() {
+        // 120 BPM at 1 kHz with sixteenth steps: one step every 125 samples.
+        let steps = vec![
+            Step::new(100.0, 1.0),
+            Step::new(200.0, 0.5).with_gate(1.0),
+            Step::rest(),
+            Step::midi(69, 0.25),
+        ];
+        let mut pitch = StepSequencer::new(&transport(), NoteValue::Sixteenth, steps);
+        let mut gate = pitch.lane(SequencerLane::Gate);
+        let mut velocity = pitch.lane(SequencerLane::Velocity);
+
+        let lanes = render_lanes(&mut [&mut pitch, &mut gate, &mut velocity], 0, 1000);
+        let (p, g, v) = (&lanes[0], &lanes[1], &lanes[2]);
+
+        assert_eq!((p[10], g[10], v[10]), (100.0, 1.0, 1.0));
+        assert_eq!(g[70], 0.0);
+        assert_eq!((p[130], g[249], v[130]), (200.0, 1.0, 0.5));
+        // The rest holds pitch and velocity, with the gate closed.
+        assert_eq!((p[260], g[260], v[260]), (200.0, 0.0, 0.5));
+        assert!((p[380] - 440.0).abs() < 1e-3);
+        assert_eq!(p[510], 100.0);
+
+        // Swing 1.0 moves the second step to 187.5 samples.
+        let mut gate = StepSequencer::new(&transport(), NoteValue::Sixteenth, pitch.steps.clone())
+            .lane(SequencerLane::Gate);
+        gate.set_swing(1.0);
+        let g = render(&mut gate, 500);
+        assert_eq!((g[150], g[190]), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_probability_is_shared_by_lanes() {
+        let steps = vec![Step::new(300.0, 1.0).with_probability(0.5)];
+        let mut gate =
+            StepSequencer::new(&transport(), NoteValue::Sixteenth, steps).lane(SequencerLane::Gate);
+        let mut copy = gate.clone();
+
+        let lanes = render_lanes(&mut [&mut gate, &mut copy], 0, 125 * 64);
+        let (a, b) = (&lanes[0], &lanes[1]);
+        assert_eq!(a, b);
+        let played = a.chunks(125).filter(|step| step[0] > 0.0).count();
+        assert!((16..48).contains(&played));
+    }
+
+    #[test]
+    fn test_tempo_change_keeps_step() {
+        let transport = transport();
+        let steps = vec![Step::new(100.0, 1.0), Step::new(200.0, 1.0)];
+        let mut pitch = StepSequencer::new(&transport, NoteValue::Sixteenth, steps);
+
+        // 100 samples at 120 BPM are 0.8 of a step; at 60 BPM the rest takes 50 samples.
+        render(&mut pitch, 100);
+        transport.set_bpm(60.0);
+        let p = render_lanes(&mut [&mut pitch], 100, 100).remove(0);
+        assert_eq!((p[45], p[55]), (100.0, 200.0));
+
+        // Stopped, the gate closes.
+        let mut gate = pitch.lane(SequencerLane::Gate);
+        transport.stop();
+        let g = render_lanes(&mut [&mut gate], 200, 64).remove(0);
+        assert!(g.iter().all(|&g| g == 0.0));
+    }
+}
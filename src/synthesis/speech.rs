@@ -602,6 +602,14 @@ impl<'a> FrameProcessor<Mono> for SpeechSynth<'a> {
         self.noise_gen.set_sample_rate(sr);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.vowel_filter.prepare(max_block_size);
+        self.stutter.prepare(max_block_size);
+        self.voice_stack.prepare(max_block_size);
+        self.sub_osc.prepare(max_block_size);
+        self.noise_gen.prepare(max_block_size);
+    }
+
     fn reset(&mut self) {
         self.current_idx = 0;
         self.time_in_phoneme = 0.0;
@@ -109,6 +109,17 @@ impl FrameProcessor<Mono> for Stack {
         }
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        for osc in &mut self.oscillators {
+            osc.prepare(max_block_size);
+        }
+        self.detune.prepare(max_block_size);
+        self.mix.prepare(max_block_size);
+        self.detune_buffer.resize(max_block_size, 0.0);
+        self.mix_buffer.resize(max_block_size, 0.0);
+        self.temp_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         for osc in &mut self.oscillators {
             osc.reset();
@@ -0,0 +1,209 @@
+use alloc::vec::Vec;
+
+/// Maps MIDI keys to frequencies for any scale that repeats at a period.
+///
+/// A scale is a list of degrees in cents above the tonic; the last degree is the period
+/// (1200 cents for octave-repeating scales) and the tonic itself is implied. Keys map to
+/// consecutive degrees from the reference key, which sounds at the reference frequency.
+/// The default is 12-tone equal temperament with A4 (key 69) at 440 Hz.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningTable {
+    degrees: Vec<f64>,
+    reference_key: u8,
+    reference_hz: f64,
+}
+
+impl TuningTable {
+    /// Creates a new TuningTable from degrees in cents, the last one being the period.
+    ///
+    /// # Arguments
+    /// * `degrees` - Cents of each degree above the tonic, ascending, ending at the period.
+    pub fn from_cents(degrees: Vec<f64>) -> Self {
+        assert!(
+            !degrees.is_empty() && degrees[degrees.len() - 1] > 0.0,
+            "TuningTable: The scale needs a positive period."
+        );
+        TuningTable {
+            degrees,
+            reference_key: 69,
+            reference_hz: 440.0,
+        }
+    }
+
+    /// Creates a new TuningTable that divides the octave into equal steps.
+    ///
+    /// # Arguments
+    /// * `divisions` - Steps per octave (12 = standard tuning, 19, 24, 31, ...).
+    pub fn equal(divisions: u32) -> Self {
+        assert!(
+            divisions > 0,
+            "TuningTable: At least one division is required."
+        );
+        let step = 1200.0 / divisions as f64;
+        Self::from_cents((1..=divisions).map(|i| i as f64 * step).collect())
+    }
+
+    /// Creates a new TuningTable from just intonation ratios.
+    ///
+    /// # Arguments
+    /// * `ratios` - `(numerator, denominator)` of each degree above the tonic, ending at the
+    ///   period, e.g. `(2, 1)`.
+    pub fn from_ratios(ratios: &[(u32, u32)]) -> Self {
+        Self::from_cents(
+            ratios
+                .iter()
+                .map(|&(n, d)| ratio_to_cents(n as f64 / d as f64))
+                .collect(),
+        )
+    }
+
+    /// Sets the key that sounds at the reference frequency and returns the table.
+    ///
+    /// # Arguments
+    /// * `key` - MIDI key of the tonic.
+    /// * `hz` - Frequency of that key.
+    pub fn with_reference(mut self, key: u8, hz: f32) -> Self {
+        self.reference_key = key;
+        self.reference_hz = hz as f64;
+        self
+    }
+
+    /// Returns the degrees in cents, ending at the period.
+    pub fn degrees(&self) -> &[f64] {
+        &self.degrees
+    }
+
+    /// Returns the frequency of a MIDI key in Hz.
+    pub fn frequency(&self, key: u8) -> f32 {
+        let len = self.degrees.len() as i32;
+        let steps = key as i32 - self.reference_key as i32;
+        let (period, degree) = (steps.div_euclid(len), steps.rem_euclid(len));
+        let mut cents = period as f64 * self.degrees[len as usize - 1];
+        if degree > 0 {
+            cents += self.degrees[degree as usize - 1];
+        }
+        (self.reference_hz * libm::exp2(cents / 1200.0)) as f32
+    }
+
+    /// Parses a Scala `.scl` scale. The table is tuned to A4 = 440 Hz; move the tonic with
+    /// [`TuningTable::with_reference`].
+    #[cfg(feature = "scala")]
+    pub fn from_scl(text: &str) -> Result<Self, ScalaError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.starts_with('!'));
+        // The first line is the description, which may be empty.
+        lines.next().ok_or(ScalaError::MissingCount)?;
+        let count: usize = lines
+            .next()
+            .and_then(|(_, line)| line.split_whitespace().next()?.parse().ok())
+            .ok_or(ScalaError::MissingCount)?;
+
+        let mut degrees = Vec::with_capacity(count);
+        for (index, line) in lines.take(count) {
+            let pitch = line.split_whitespace().next().unwrap_or("");
+            let cents = parse_pitch(pitch).ok_or(ScalaError::InvalidPitch { line: index + 1 })?;
+            degrees.push(cents);
+        }
+        if degrees.len() != count || count == 0 {
+            return Err(ScalaError::CountMismatch);
+        }
+        if degrees[count - 1] <= 0.0 {
+            return Err(ScalaError::InvalidPitch { line: 0 });
+        }
+        Ok(Self::from_cents(degrees))
+    }
+}
+
+impl Default for TuningTable {
+    fn default() -> Self {
+        Self::equal(12)
+    }
+}
+
+/// Errors produced while parsing a Scala scale file.
+#[cfg(feature = "scala")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalaError {
+    /// The note count line is missing or not a number.
+    MissingCount,
+    /// A pitch is neither cents (with a `.`) nor a ratio (1-based line number, 0 for a
+    /// period that is not above the tonic).
+    InvalidPitch { line: usize },
+    /// The file has fewer pitches than its note count, or none.
+    CountMismatch,
+}
+
+#[cfg(feature = "scala")]
+impl core::fmt::Display for ScalaError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ScalaError::MissingCount => write!(f, "Scala file has no note count"),
+            ScalaError::InvalidPitch { line } => write!(f, "invalid Scala pitch on line {}", line),
+            ScalaError::CountMismatch => write!(f, "Scala file has fewer pitches than its count"),
+        }
+    }
+}
+
+fn ratio_to_cents(ratio: f64) -> f64 {
+    1200.0 * libm::log2(ratio)
+}
+
+/// Scala pitches with a period are cents, others are ratios (`3/2`) or whole numbers.
+#[cfg(feature = "scala")]
+fn parse_pitch(pitch: &str) -> Option<f64> {
+    if pitch.contains('.') {
+        return pitch.parse().ok();
+    }
+    let (n, d) = match pitch.split_once('/') {
+        Some((n, d)) => (n.parse::<u64>().ok()?, d.parse::<u64>().ok()?),
+        None => (pitch.parse::<u64>().ok()?, 1),
+    };
+    if n == 0 || d == 0 {
+        return None;
+    }
+    Some(ratio_to_cents(n as f64 / d as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_and_just_tables() {
+        let standard = TuningTable::default();
+        assert!((standard.frequency(69) - 440.0).abs() < 1e-3);
+        assert!((standard.frequency(60) - 261.6256).abs() < 1e-3);
+        assert!((standard.frequency(57) - 220.0).abs() < 1e-3);
+
+        let quarter_tone = TuningTable::equal(24);
+        assert!((quarter_tone.frequency(93) - 880.0).abs() < 1e-3);
+
+        let just =
+            TuningTable::from_ratios(&[(9, 8), (5, 4), (4, 3), (3, 2), (5, 3), (15, 8), (2, 1)])
+                .with_reference(60, 264.0);
+        assert!((just.frequency(64) - 396.0).abs() < 1e-3);
+        assert!((just.frequency(67) - 528.0).abs() < 1e-3);
+        assert!((just.frequency(59) - 247.5).abs() < 1e-3);
+    }
+
+    #[cfg(feature = "scala")]
+    #[test]
+    fn test_scala_parsing() {
+        let scl = "! meantone.scl\n!\n1/4-comma meantone\n 3\n!\n 386.31371 cents\n 5/4\n 2\n";
+        let table = TuningTable::from_scl(scl).unwrap();
+        assert_eq!(table.degrees().len(), 3);
+        assert!((table.degrees()[0] - 386.31371).abs() < 1e-9);
+        assert!((table.frequency(72) - 880.0).abs() < 1e-3);
+
+        assert_eq!(
+            TuningTable::from_scl("name\n2\n100.0\nabc\n"),
+            Err(ScalaError::InvalidPitch { line: 4 })
+        );
+        assert_eq!(
+            TuningTable::from_scl("name\n2\n100.0\n"),
+            Err(ScalaError::CountMismatch)
+        );
+    }
+}
@@ -245,6 +245,13 @@ impl FrameProcessor<Mono> for WavetableOscillator {
         self.position.set_sample_rate(sample_rate);
     }
 
+    fn prepare(&mut self, max_block_size: usize) {
+        self.frequency.prepare(max_block_size);
+        self.position.prepare(max_block_size);
+        self.freq_buffer.resize(max_block_size, 0.0);
+        self.pos_buffer.resize(max_block_size, 0.0);
+    }
+
     fn reset(&mut self) {
         self.phase = 0.0;
     }
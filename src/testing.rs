@@ -0,0 +1,218 @@
+extern crate std;
+
+use crate::core::channels::ChannelConfig;
+use crate::FrameProcessor;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use std::path::PathBuf;
+
+/// Block-size patterns for [`assert_block_size_independent`]. Each pattern is repeated
+/// until the input is used up; the last one mixes sizes that straddle the internal chunk
+/// sizes (`PARAM_CHUNK_SIZE`, control intervals) of the processors.
+pub(crate) const BLOCK_PATTERNS: [&[usize]; 5] = [
+    &[1],
+    &[7],
+    &[64],
+    &[512],
+    &[1, 3, 64, 17, 512, 2, 100, 255, 33],
+];
+
+/// Renders `input` (interleaved) through a processor, with block sizes taken in turn from
+/// `pattern`.
+pub(crate) fn render_blocks<C, P>(processor: &mut P, input: &[f32], pattern: &[usize]) -> Vec<f32>
+where
+    C: ChannelConfig,
+    P: FrameProcessor<C> + ?Sized,
+{
+    let channels = C::num_channels();
+    let mut output = input.to_vec();
+    let mut sample_index = 0u64;
+    let mut start = 0;
+    for &frames in pattern.iter().cycle() {
+        if start >= output.len() {
+            break;
+        }
+        let end = (start + frames * channels).min(output.len());
+        processor.process(&mut output[start..end], sample_index);
+        sample_index += ((end - start) / channels) as u64;
+        start = end;
+    }
+    output
+}
+
+/// Asserts that a processor produces the same output for every pattern of
+/// [`BLOCK_PATTERNS`].
+///
+/// `make` builds a fresh processor for each render. It is prepared for 512-frame blocks.
+/// Differences up to `tolerance` are accepted, for processors whose block-rate smoothing
+/// legitimately rounds differently; pass 0.0 to require identical output.
+pub(crate) fn assert_block_size_independent<C, P>(
+    mut make: impl FnMut() -> P,
+    input: &[f32],
+    tolerance: f32,
+) where
+    C: ChannelConfig,
+    P: FrameProcessor<C>,
+{
+    let mut render = |pattern: &[usize]| {
+        let mut processor = make();
+        processor.prepare(512);
+        render_blocks::<C, P>(&mut processor, input, pattern)
+    };
+
+    let reference = render(BLOCK_PATTERNS[0]);
+    for pattern in &BLOCK_PATTERNS[1..] {
+        let output = render(pattern);
+        if let Some(i) = first_mismatch(&reference, &output, tolerance) {
+            panic!(
+                "Output with block sizes {:?} differs at sample {} (frame {}): {} instead of {}",
+                pattern,
+                i,
+                i / C::num_channels(),
+                output[i],
+                reference[i]
+            );
+        }
+    }
+}
+
+fn first_mismatch(expected: &[f32], actual: &[f32], tolerance: f32) -> Option<usize> {
+    assert_eq!(expected.len(), actual.len(), "Render lengths differ.");
+    expected
+        .iter()
+        .zip(actual.iter())
+        .position(|(e, a)| !(e == a || (e - a).abs() <= tolerance))
+}
+
+/// Compares a render against the golden file `testdata/golden/<name>.txt`.
+///
+/// Golden files hold one sample per line and record the default (bit-exact) build, so the
+/// comparison is skipped with `perf-approximations`. Run the tests with
+/// `INFINITEDSP_BLESS=1` to write the files after an intended change in output.
+pub(crate) fn assert_golden(name: &str, output: &[f32], tolerance: f32) {
+    let path = golden_path(name);
+    if std::env::var_os("INFINITEDSP_BLESS").is_some() {
+        let text: String = output.iter().map(|s| format!("{:e}\n", s)).collect();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, text).unwrap();
+        return;
+    }
+    if cfg!(feature = "perf-approximations") {
+        return;
+    }
+
+    let text = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "Missing golden file {}; run with INFINITEDSP_BLESS=1 to create it.",
+            path.display()
+        )
+    });
+    let expected: Vec<f32> = text.lines().map(|line| line.parse().unwrap()).collect();
+    if let Some(i) = first_mismatch(&expected, output, tolerance) {
+        panic!(
+            "Render differs from {} at sample {}: {} instead of {}",
+            path.display(),
+            i,
+            output[i],
+            expected[i]
+        );
+    }
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("testdata")
+        .join("golden")
+        .join(format!("{}.txt", name))
+}
+
+mod tests {
+    use super::*;
+    use crate::core::audio_param::AudioParam;
+    use crate::core::channels::{Mono, Stereo};
+    use crate::effects::dynamics::compressor::Compressor;
+    use crate::effects::filter::biquad::Biquad;
+    use crate::effects::filter::state_variable::{StateVariableFilter, SvfType};
+    use crate::effects::time::delay::Delay;
+    use crate::effects::time::reverb::Reverb;
+    use crate::synthesis::lfo::{Lfo, LfoWaveform};
+    use crate::synthesis::oscillator::{Oscillator, Waveform};
+
+    fn noise(frames: usize, channels: usize) -> Vec<f32> {
+        let mut state = 1u32;
+        (0..frames * channels)
+            .map(|_| crate::core::utils::FastRng::next_f32_bipolar_stateless(&mut state) * 0.5)
+            .collect()
+    }
+
+    fn lfo(frequency: f32, min: f32, max: f32) -> AudioParam {
+        let mut lfo = Lfo::new(AudioParam::hz(frequency), LfoWaveform::Sine);
+        lfo.set_range(min, max);
+        AudioParam::Dynamic(alloc::boxed::Box::new(lfo))
+    }
+
+    #[test]
+    fn test_block_size_independence() {
+        let input = noise(4096, 1);
+        assert_block_size_independent::<Mono, _>(
+            || Oscillator::new(lfo(3.0, 200.0, 800.0), Waveform::Saw),
+            &[0.0; 4096],
+            0.0,
+        );
+        assert_block_size_independent::<Mono, _>(
+            || Biquad::new_lowpass(lfo(5.0, 300.0, 3000.0), AudioParam::Static(0.7)),
+            &input,
+            0.0,
+        );
+        assert_block_size_independent::<Mono, _>(
+            || {
+                StateVariableFilter::new(
+                    SvfType::LowPass,
+                    lfo(5.0, 300.0, 3000.0),
+                    AudioParam::Static(0.5),
+                )
+            },
+            &input,
+            0.0,
+        );
+        assert_block_size_independent::<Mono, _>(
+            || {
+                Delay::new(
+                    0.1,
+                    lfo(2.0, 0.01, 0.03),
+                    AudioParam::Static(0.5),
+                    AudioParam::Static(0.5),
+                )
+            },
+            &input,
+            0.0,
+        );
+        assert_block_size_independent::<Mono, _>(
+            || Compressor::new(AudioParam::Static(-20.0), AudioParam::Static(4.0)),
+            &input,
+            0.0,
+        );
+        assert_block_size_independent::<Stereo, _>(Reverb::new, &noise(4096, 2), 0.0);
+    }
+
+    #[test]
+    fn test_golden_render() {
+        let mut chain = crate::core::dsp_chain::DspChain::new(
+            Oscillator::new(AudioParam::hz(441.0), Waveform::Saw),
+            44100.0,
+        )
+        .and(Biquad::new_lowpass(
+            AudioParam::hz(2000.0),
+            AudioParam::Static(0.7),
+        ))
+        .and(Delay::new(
+            0.01,
+            AudioParam::Static(0.003),
+            AudioParam::Static(0.5),
+            AudioParam::Static(0.5),
+        ));
+        let output = crate::core::render::render_offline::<Mono, _>(&mut chain, 512, 64);
+        assert_golden("saw_lowpass_delay", &output, 1e-6);
+    }
+}